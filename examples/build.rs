@@ -12,6 +12,14 @@ fn main() {
         .compile(&["proto/helloworld/helloworld.proto"], &["proto"])
         .unwrap();
 
+    tonic_build::configure()
+        .file_descriptor_set_path(out_dir.join("transcoding_descriptor.bin"))
+        .compile(
+            &["proto/transcoding/greeter.proto"],
+            &["proto", "proto/googleapis"],
+        )
+        .unwrap();
+
     tonic_build::compile_protos("proto/echo/echo.proto").unwrap();
 
     tonic_build::compile_protos("proto/unaryecho/echo.proto").unwrap();
@@ -33,6 +41,7 @@ fn main() {
         .unwrap();
 
     build_json_codec_service();
+    build_bincode_codec_service();
 }
 
 // Manually define the json.helloworld.Greeter service which used a custom JsonCodec to use json
@@ -58,3 +67,26 @@ fn build_json_codec_service() {
 
     tonic_build::manual::Builder::new().compile(&[greeter_service]);
 }
+
+// Manually define the bincode.helloworld.Greeter service which uses tonic's BincodeCodec to send
+// messages encoded with bincode instead of protobuf. The request and response types are expected
+// to be defined in a module `crate::common`.
+//
+// See the client/server examples defined in `src/bincode-codec` for more information.
+fn build_bincode_codec_service() {
+    let greeter_service = tonic_build::manual::Service::builder()
+        .name("Greeter")
+        .package("bincode.helloworld")
+        .method(
+            tonic_build::manual::Method::builder()
+                .name("say_hello")
+                .route_name("SayHello")
+                .input_type("crate::common::HelloRequest")
+                .output_type("crate::common::HelloResponse")
+                .codec_path("tonic::codec::BincodeCodec")
+                .build(),
+        )
+        .build();
+
+    tonic_build::manual::Builder::new().compile(&[greeter_service]);
+}
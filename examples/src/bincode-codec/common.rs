@@ -0,0 +1,15 @@
+//! This module defines the request/response types used by the bincode.helloworld.Greeter service
+//! which is defined manually (instead of via proto files) by the `build_bincode_codec_service`
+//! function in the `examples/build.rs` file.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HelloRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HelloResponse {
+    pub message: String,
+}
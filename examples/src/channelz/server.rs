@@ -0,0 +1,76 @@
+use tonic::transport::{server::TcpIncoming, Channel, Server};
+use tonic::{Request, Response, Status};
+use tower::Layer;
+
+use hello_world::greeter_client::GreeterClient;
+use hello_world::greeter_server::{Greeter, GreeterServer};
+use hello_world::{HelloReply, HelloRequest};
+use std::time::Duration;
+use tonic_channelz::layer::Tracked;
+
+pub mod hello_world {
+    tonic::include_proto!("helloworld");
+}
+
+#[derive(Default)]
+pub struct MyGreeter {}
+
+#[tonic::async_trait]
+impl Greeter for MyGreeter {
+    async fn say_hello(
+        &self,
+        request: Request<HelloRequest>,
+    ) -> Result<Response<HelloReply>, Status> {
+        let name = request.into_inner().name;
+        if name.is_empty() {
+            return Err(Status::invalid_argument("name is empty"));
+        }
+
+        Ok(Response::new(HelloReply {
+            message: format!("Hello {}!", name),
+        }))
+    }
+}
+
+/// Calls the greeter every second, failing every other call, so that the calls of both
+/// the channel and the server can be seen changing in channelz.
+async fn call_greeter(mut client: GreeterClient<Tracked<Channel>>) {
+    let mut iter = 0u64;
+    loop {
+        iter += 1;
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let name = if iter % 2 == 0 { "Tonic" } else { "" };
+        let _ = client
+            .say_hello(HelloRequest {
+                name: name.to_string(),
+            })
+            .await;
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let registry = tonic_channelz::Registry::new();
+
+    let addr = "[::1]:50051".parse().unwrap();
+    let server = registry.server();
+    let incoming = TcpIncoming::new(addr, true, None)?;
+
+    println!("ChannelzServer + GreeterServer listening on {}", addr);
+
+    let serve = Server::builder()
+        .layer(server.clone())
+        .add_service(registry.service())
+        .add_service(GreeterServer::new(MyGreeter::default()))
+        .serve_with_incoming(server.incoming(incoming));
+
+    let target = "http://[::1]:50051";
+    let channel = Channel::from_static(target).connect_lazy();
+    let client = GreeterClient::new(registry.channel_with_state(target, &channel).layer(channel));
+    tokio::spawn(call_greeter(client));
+
+    serve.await?;
+
+    Ok(())
+}
@@ -1,8 +1,7 @@
 use tonic::{
-    transport::{Endpoint, Server, Uri},
+    transport::{server::MemoryIncoming, Endpoint, Server},
     Request, Response, Status,
 };
-use tower::service_fn;
 
 pub mod hello_world {
     tonic::include_proto!("helloworld");
@@ -16,35 +15,21 @@ use hello_world::{
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let (client, server) = tokio::io::duplex(1024);
+    let incoming = MemoryIncoming::new();
+    let connector = incoming.connector();
 
     let greeter = MyGreeter::default();
 
     tokio::spawn(async move {
         Server::builder()
             .add_service(GreeterServer::new(greeter))
-            .serve_with_incoming(futures::stream::iter(vec![Ok::<_, std::io::Error>(server)]))
+            .serve_with_incoming(incoming)
             .await
     });
 
-    // Move client to an option so we can _move_ the inner value
-    // on the first attempt to connect. All other attempts will fail.
-    let mut client = Some(client);
+    // The uri is ignored, every connection is made in memory.
     let channel = Endpoint::try_from("http://[::]:50051")?
-        .connect_with_connector(service_fn(move |_: Uri| {
-            let client = client.take();
-
-            async move {
-                if let Some(client) = client {
-                    Ok(client)
-                } else {
-                    Err(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "Client already taken",
-                    ))
-                }
-            }
-        }))
+        .connect_with_connector(connector)
         .await?;
 
     let mut client = GreeterClient::new(channel);
@@ -28,16 +28,22 @@ impl proto::greeter_server::Greeter for MyGreeter {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Clients support different versions of the reflection protocol, so serve both.
     let service = tonic_reflection::server::Builder::configure()
         .register_encoded_file_descriptor_set(proto::FILE_DESCRIPTOR_SET)
         .build()
         .unwrap();
+    let service_v1 = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(proto::FILE_DESCRIPTOR_SET)
+        .build_v1()
+        .unwrap();
 
     let addr = "[::1]:50052".parse().unwrap();
     let greeter = MyGreeter::default();
 
     Server::builder()
         .add_service(service)
+        .add_service(service_v1)
         .add_service(proto::greeter_server::GreeterServer::new(greeter))
         .serve(addr)
         .await?;
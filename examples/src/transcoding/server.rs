@@ -0,0 +1,50 @@
+use tonic::{transport::Server, Request, Response, Status};
+use tonic_transcoding::TranscodingLayer;
+
+use hello_world::greeter_server::{Greeter, GreeterServer};
+use hello_world::{HelloReply, HelloRequest};
+
+pub mod hello_world {
+    tonic::include_proto!("transcoding");
+
+    pub(crate) const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("transcoding_descriptor");
+}
+
+#[derive(Default)]
+pub struct MyGreeter {}
+
+#[tonic::async_trait]
+impl Greeter for MyGreeter {
+    async fn say_hello(
+        &self,
+        request: Request<HelloRequest>,
+    ) -> Result<Response<HelloReply>, Status> {
+        let name = request.into_inner().name;
+        if name.is_empty() {
+            return Err(Status::invalid_argument("name is empty"));
+        }
+
+        Ok(Response::new(HelloReply {
+            message: format!("Hello {}!", name),
+        }))
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let addr = "[::1]:50051".parse().unwrap();
+
+    println!("GreeterServer listening on {}", addr);
+    println!("Try `curl http://{}/v1/hello/Tonic`", addr);
+
+    Server::builder()
+        // REST clients use HTTP/1.1.
+        .accept_http1(true)
+        .layer(TranscodingLayer::new(hello_world::FILE_DESCRIPTOR_SET)?)
+        .add_service(GreeterServer::new(MyGreeter::default()))
+        .serve(addr)
+        .await?;
+
+    Ok(())
+}
@@ -5,24 +5,14 @@ pub mod hello_world {
 }
 
 use hello_world::{greeter_client::GreeterClient, HelloRequest};
-#[cfg(unix)]
-use tokio::net::UnixStream;
-use tonic::transport::{Endpoint, Uri};
-use tower::service_fn;
+use tonic::transport::Endpoint;
 
 #[cfg(unix)]
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // We will ignore this uri because uds do not use it
-    // if your connector does use the uri it will be provided
-    // as the request to the `MakeConnection`.
-    let channel = Endpoint::try_from("http://[::]:50051")?
-        .connect_with_connector(service_fn(|_: Uri| {
-            let path = "/tmp/tonic/helloworld";
-
-            // Connect to a Uds socket
-            UnixStream::connect(path)
-        }))
+    // Connect to a Uds socket
+    let channel = Endpoint::try_from("unix:///tmp/tonic/helloworld")?
+        .connect()
         .await?;
 
     let mut client = GreeterClient::new(channel);
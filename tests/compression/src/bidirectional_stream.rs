@@ -1,21 +1,27 @@
 use super::*;
 use tonic::codec::CompressionEncoding;
 
+#[test_case::test_case(CompressionEncoding::Gzip ; "gzip")]
+#[test_case::test_case(CompressionEncoding::Deflate ; "deflate")]
+#[test_case::test_case(CompressionEncoding::Zstd ; "zstd")]
 #[tokio::test(flavor = "multi_thread")]
-async fn client_enabled_server_enabled() {
+async fn client_enabled_server_enabled(encoding: CompressionEncoding) {
     let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
 
     let svc = test_server::TestServer::new(Svc::default())
-        .accept_compressed(CompressionEncoding::Gzip)
-        .send_compressed(CompressionEncoding::Gzip);
+        .accept_compressed(encoding)
+        .send_compressed(encoding);
 
     let request_bytes_counter = Arc::new(AtomicUsize::new(0));
     let response_bytes_counter = Arc::new(AtomicUsize::new(0));
 
-    fn assert_right_encoding<B>(req: http::Request<B>) -> http::Request<B> {
-        assert_eq!(req.headers().get("grpc-encoding").unwrap(), "gzip");
+    let assert_right_encoding = move |req: http::Request<hyper::Body>| {
+        assert_eq!(
+            req.headers().get("grpc-encoding").unwrap(),
+            encoding.to_string().as_str()
+        );
         req
-    }
+    };
 
     tokio::spawn({
         let request_bytes_counter = request_bytes_counter.clone();
@@ -44,8 +50,8 @@ async fn client_enabled_server_enabled() {
     });
 
     let mut client = test_client::TestClient::new(mock_io_channel(client).await)
-        .send_compressed(CompressionEncoding::Gzip)
-        .accept_compressed(CompressionEncoding::Gzip);
+        .send_compressed(encoding)
+        .accept_compressed(encoding);
 
     let data = [0_u8; UNCOMPRESSED_MIN_BODY_SIZE].to_vec();
     let stream = futures::stream::iter(vec![SomeData { data: data.clone() }, SomeData { data }]);
@@ -56,7 +62,10 @@ async fn client_enabled_server_enabled() {
         .await
         .unwrap();
 
-    assert_eq!(res.metadata().get("grpc-encoding").unwrap(), "gzip");
+    assert_eq!(
+        res.metadata().get("grpc-encoding").unwrap(),
+        encoding.to_string().as_str()
+    );
 
     let mut stream: Streaming<SomeData> = res.into_inner();
 
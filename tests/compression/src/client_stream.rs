@@ -2,19 +2,24 @@ use super::*;
 use http_body::Body as _;
 use tonic::codec::CompressionEncoding;
 
+#[test_case::test_case(CompressionEncoding::Gzip ; "gzip")]
+#[test_case::test_case(CompressionEncoding::Deflate ; "deflate")]
+#[test_case::test_case(CompressionEncoding::Zstd ; "zstd")]
 #[tokio::test(flavor = "multi_thread")]
-async fn client_enabled_server_enabled() {
+async fn client_enabled_server_enabled(encoding: CompressionEncoding) {
     let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
 
-    let svc =
-        test_server::TestServer::new(Svc::default()).accept_compressed(CompressionEncoding::Gzip);
+    let svc = test_server::TestServer::new(Svc::default()).accept_compressed(encoding);
 
     let request_bytes_counter = Arc::new(AtomicUsize::new(0));
 
-    fn assert_right_encoding<B>(req: http::Request<B>) -> http::Request<B> {
-        assert_eq!(req.headers().get("grpc-encoding").unwrap(), "gzip");
+    let assert_right_encoding = move |req: http::Request<hyper::Body>| {
+        assert_eq!(
+            req.headers().get("grpc-encoding").unwrap(),
+            encoding.to_string().as_str()
+        );
         req
-    }
+    };
 
     tokio::spawn({
         let request_bytes_counter = request_bytes_counter.clone();
@@ -35,8 +40,8 @@ async fn client_enabled_server_enabled() {
         }
     });
 
-    let mut client = test_client::TestClient::new(mock_io_channel(client).await)
-        .send_compressed(CompressionEncoding::Gzip);
+    let mut client =
+        test_client::TestClient::new(mock_io_channel(client).await).send_compressed(encoding);
 
     let data = [0_u8; UNCOMPRESSED_MIN_BODY_SIZE].to_vec();
     let stream = futures::stream::iter(vec![SomeData { data: data.clone() }, SomeData { data }]);
@@ -48,12 +53,14 @@ async fn client_enabled_server_enabled() {
     assert!(bytes_sent < UNCOMPRESSED_MIN_BODY_SIZE);
 }
 
+#[test_case::test_case(CompressionEncoding::Gzip ; "gzip")]
+#[test_case::test_case(CompressionEncoding::Deflate ; "deflate")]
+#[test_case::test_case(CompressionEncoding::Zstd ; "zstd")]
 #[tokio::test(flavor = "multi_thread")]
-async fn client_disabled_server_enabled() {
+async fn client_disabled_server_enabled(encoding: CompressionEncoding) {
     let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
 
-    let svc =
-        test_server::TestServer::new(Svc::default()).accept_compressed(CompressionEncoding::Gzip);
+    let svc = test_server::TestServer::new(Svc::default()).accept_compressed(encoding);
 
     let request_bytes_counter = Arc::new(AtomicUsize::new(0));
 
@@ -93,8 +100,11 @@ async fn client_disabled_server_enabled() {
     assert!(bytes_sent > UNCOMPRESSED_MIN_BODY_SIZE);
 }
 
+#[test_case::test_case(CompressionEncoding::Gzip ; "gzip")]
+#[test_case::test_case(CompressionEncoding::Deflate ; "deflate")]
+#[test_case::test_case(CompressionEncoding::Zstd ; "zstd")]
 #[tokio::test(flavor = "multi_thread")]
-async fn client_enabled_server_disabled() {
+async fn client_enabled_server_disabled(encoding: CompressionEncoding) {
     let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
 
     let svc = test_server::TestServer::new(Svc::default());
@@ -107,8 +117,8 @@ async fn client_enabled_server_disabled() {
             .unwrap();
     });
 
-    let mut client = test_client::TestClient::new(mock_io_channel(client).await)
-        .send_compressed(CompressionEncoding::Gzip);
+    let mut client =
+        test_client::TestClient::new(mock_io_channel(client).await).send_compressed(encoding);
 
     let data = [0_u8; UNCOMPRESSED_MIN_BODY_SIZE].to_vec();
     let stream = futures::stream::iter(vec![SomeData { data: data.clone() }, SomeData { data }]);
@@ -119,16 +129,21 @@ async fn client_enabled_server_disabled() {
     assert_eq!(status.code(), tonic::Code::Unimplemented);
     assert_eq!(
         status.message(),
-        "Content is compressed with `gzip` which isn't supported"
+        format!(
+            "Content is compressed with `{}` which isn't supported",
+            encoding
+        )
     );
 }
 
+#[test_case::test_case(CompressionEncoding::Gzip ; "gzip")]
+#[test_case::test_case(CompressionEncoding::Deflate ; "deflate")]
+#[test_case::test_case(CompressionEncoding::Zstd ; "zstd")]
 #[tokio::test(flavor = "multi_thread")]
-async fn compressing_response_from_client_stream() {
+async fn compressing_response_from_client_stream(encoding: CompressionEncoding) {
     let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
 
-    let svc =
-        test_server::TestServer::new(Svc::default()).send_compressed(CompressionEncoding::Gzip);
+    let svc = test_server::TestServer::new(Svc::default()).send_compressed(encoding);
 
     let response_bytes_counter = Arc::new(AtomicUsize::new(0));
 
@@ -153,14 +168,17 @@ async fn compressing_response_from_client_stream() {
         }
     });
 
-    let mut client = test_client::TestClient::new(mock_io_channel(client).await)
-        .accept_compressed(CompressionEncoding::Gzip);
+    let mut client =
+        test_client::TestClient::new(mock_io_channel(client).await).accept_compressed(encoding);
 
     let stream = futures::stream::iter(vec![]);
     let req = Request::new(Box::pin(stream));
 
     let res = client.compress_output_client_stream(req).await.unwrap();
-    assert_eq!(res.metadata().get("grpc-encoding").unwrap(), "gzip");
+    assert_eq!(
+        res.metadata().get("grpc-encoding").unwrap(),
+        encoding.to_string().as_str()
+    );
     let bytes_sent = response_bytes_counter.load(SeqCst);
     assert!(bytes_sent < UNCOMPRESSED_MIN_BODY_SIZE);
 }
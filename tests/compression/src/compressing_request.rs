@@ -2,19 +2,24 @@ use super::*;
 use http_body::Body as _;
 use tonic::codec::CompressionEncoding;
 
+#[test_case::test_case(CompressionEncoding::Gzip ; "gzip")]
+#[test_case::test_case(CompressionEncoding::Deflate ; "deflate")]
+#[test_case::test_case(CompressionEncoding::Zstd ; "zstd")]
 #[tokio::test(flavor = "multi_thread")]
-async fn client_enabled_server_enabled() {
+async fn client_enabled_server_enabled(encoding: CompressionEncoding) {
     let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
 
-    let svc =
-        test_server::TestServer::new(Svc::default()).accept_compressed(CompressionEncoding::Gzip);
+    let svc = test_server::TestServer::new(Svc::default()).accept_compressed(encoding);
 
     let request_bytes_counter = Arc::new(AtomicUsize::new(0));
 
-    fn assert_right_encoding<B>(req: http::Request<B>) -> http::Request<B> {
-        assert_eq!(req.headers().get("grpc-encoding").unwrap(), "gzip");
+    let assert_right_encoding = move |req: http::Request<hyper::Body>| {
+        assert_eq!(
+            req.headers().get("grpc-encoding").unwrap(),
+            encoding.to_string().as_str()
+        );
         req
-    }
+    };
 
     tokio::spawn({
         let request_bytes_counter = request_bytes_counter.clone();
@@ -37,8 +42,8 @@ async fn client_enabled_server_enabled() {
         }
     });
 
-    let mut client = test_client::TestClient::new(mock_io_channel(client).await)
-        .send_compressed(CompressionEncoding::Gzip);
+    let mut client =
+        test_client::TestClient::new(mock_io_channel(client).await).send_compressed(encoding);
 
     for _ in 0..3 {
         client
@@ -52,8 +57,11 @@ async fn client_enabled_server_enabled() {
     }
 }
 
+#[test_case::test_case(CompressionEncoding::Gzip ; "gzip")]
+#[test_case::test_case(CompressionEncoding::Deflate ; "deflate")]
+#[test_case::test_case(CompressionEncoding::Zstd ; "zstd")]
 #[tokio::test(flavor = "multi_thread")]
-async fn client_enabled_server_disabled() {
+async fn client_enabled_server_disabled(encoding: CompressionEncoding) {
     let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
 
     let svc = test_server::TestServer::new(Svc::default());
@@ -66,8 +74,8 @@ async fn client_enabled_server_disabled() {
             .unwrap();
     });
 
-    let mut client = test_client::TestClient::new(mock_io_channel(client).await)
-        .send_compressed(CompressionEncoding::Gzip);
+    let mut client =
+        test_client::TestClient::new(mock_io_channel(client).await).send_compressed(encoding);
 
     let status = client
         .compress_input_unary(SomeData {
@@ -79,7 +87,10 @@ async fn client_enabled_server_disabled() {
     assert_eq!(status.code(), tonic::Code::Unimplemented);
     assert_eq!(
         status.message(),
-        "Content is compressed with `gzip` which isn't supported"
+        format!(
+            "Content is compressed with `{}` which isn't supported",
+            encoding
+        )
     );
 
     assert_eq!(
@@ -88,12 +99,14 @@ async fn client_enabled_server_disabled() {
     );
 }
 
+#[test_case::test_case(CompressionEncoding::Gzip ; "gzip")]
+#[test_case::test_case(CompressionEncoding::Deflate ; "deflate")]
+#[test_case::test_case(CompressionEncoding::Zstd ; "zstd")]
 #[tokio::test(flavor = "multi_thread")]
-async fn client_mark_compressed_without_header_server_enabled() {
+async fn client_mark_compressed_without_header_server_enabled(encoding: CompressionEncoding) {
     let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
 
-    let svc =
-        test_server::TestServer::new(Svc::default()).accept_compressed(CompressionEncoding::Gzip);
+    let svc = test_server::TestServer::new(Svc::default()).accept_compressed(encoding);
 
     tokio::spawn({
         async move {
@@ -112,7 +125,7 @@ async fn client_mark_compressed_without_header_server_enabled() {
             Ok(req)
         },
     )
-    .send_compressed(CompressionEncoding::Gzip);
+    .send_compressed(encoding);
 
     let status = client
         .compress_input_unary(SomeData {
@@ -127,3 +140,158 @@ async fn client_mark_compressed_without_header_server_enabled() {
         "protocol error: received message with compressed-flag but no grpc-encoding was specified"
     );
 }
+
+#[test_case::test_case(CompressionEncoding::Gzip ; "gzip")]
+#[test_case::test_case(CompressionEncoding::Deflate ; "deflate")]
+#[test_case::test_case(CompressionEncoding::Zstd ; "zstd")]
+#[tokio::test(flavor = "multi_thread")]
+async fn server_advertises_accepted_encodings(encoding: CompressionEncoding) {
+    let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
+
+    let svc = test_server::TestServer::new(Svc::default()).accept_compressed(encoding);
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(futures::stream::iter(vec![Ok::<_, std::io::Error>(server)]))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::new(mock_io_channel(client).await);
+
+    let res = client
+        .compress_input_unary(SomeData {
+            data: [0_u8; UNCOMPRESSED_MIN_BODY_SIZE].to_vec(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(
+        res.metadata().get("grpc-accept-encoding").unwrap(),
+        format!("{},identity", encoding).as_str()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn server_without_compression_does_not_advertise_encodings() {
+    let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
+
+    let svc = test_server::TestServer::new(Svc::default());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(futures::stream::iter(vec![Ok::<_, std::io::Error>(server)]))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::new(mock_io_channel(client).await);
+
+    let res = client
+        .compress_input_unary(SomeData {
+            data: [0_u8; UNCOMPRESSED_MIN_BODY_SIZE].to_vec(),
+        })
+        .await
+        .unwrap();
+
+    assert!(res.metadata().get("grpc-accept-encoding").is_none());
+}
+
+#[test_case::test_case(CompressionEncoding::Gzip ; "gzip")]
+#[test_case::test_case(CompressionEncoding::Deflate ; "deflate")]
+#[test_case::test_case(CompressionEncoding::Zstd ; "zstd")]
+#[tokio::test(flavor = "multi_thread")]
+async fn compression_enabled_on_single_request(encoding: CompressionEncoding) {
+    let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
+
+    let svc = test_server::TestServer::new(Svc::default()).accept_compressed(encoding);
+
+    let request_bytes_counter = Arc::new(AtomicUsize::new(0));
+
+    let assert_right_encoding = move |req: http::Request<hyper::Body>| {
+        assert_eq!(
+            req.headers().get("grpc-encoding").unwrap(),
+            encoding.to_string().as_str()
+        );
+        req
+    };
+
+    tokio::spawn({
+        let request_bytes_counter = request_bytes_counter.clone();
+        async move {
+            Server::builder()
+                .layer(
+                    ServiceBuilder::new()
+                        .map_request(assert_right_encoding)
+                        .layer(measure_request_body_size_layer(request_bytes_counter))
+                        .into_inner(),
+                )
+                .add_service(svc)
+                .serve_with_incoming(futures::stream::iter(vec![Ok::<_, std::io::Error>(server)]))
+                .await
+                .unwrap();
+        }
+    });
+
+    // compression is not enabled on the client itself
+    let mut client = test_client::TestClient::new(mock_io_channel(client).await);
+
+    let mut req = Request::new(SomeData {
+        data: [0_u8; UNCOMPRESSED_MIN_BODY_SIZE].to_vec(),
+    });
+    req.set_compression(encoding);
+
+    client.compress_input_unary(req).await.unwrap();
+
+    let bytes_sent = request_bytes_counter.load(SeqCst);
+    assert!(bytes_sent < UNCOMPRESSED_MIN_BODY_SIZE);
+}
+
+#[test_case::test_case(CompressionEncoding::Gzip ; "gzip")]
+#[test_case::test_case(CompressionEncoding::Deflate ; "deflate")]
+#[test_case::test_case(CompressionEncoding::Zstd ; "zstd")]
+#[tokio::test(flavor = "multi_thread")]
+async fn disabling_compression_on_single_request(encoding: CompressionEncoding) {
+    let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
+
+    let svc = test_server::TestServer::new(Svc::default()).accept_compressed(encoding);
+
+    let request_bytes_counter = Arc::new(AtomicUsize::new(0));
+
+    fn assert_right_encoding<B>(req: http::Request<B>) -> http::Request<B> {
+        assert!(req.headers().get("grpc-encoding").is_none());
+        req
+    }
+
+    tokio::spawn({
+        let request_bytes_counter = request_bytes_counter.clone();
+        async move {
+            Server::builder()
+                .layer(
+                    ServiceBuilder::new()
+                        .map_request(assert_right_encoding)
+                        .layer(measure_request_body_size_layer(request_bytes_counter))
+                        .into_inner(),
+                )
+                .add_service(svc)
+                .serve_with_incoming(futures::stream::iter(vec![Ok::<_, std::io::Error>(server)]))
+                .await
+                .unwrap();
+        }
+    });
+
+    let mut client =
+        test_client::TestClient::new(mock_io_channel(client).await).send_compressed(encoding);
+
+    let mut req = Request::new(SomeData {
+        data: [0_u8; UNCOMPRESSED_MIN_BODY_SIZE].to_vec(),
+    });
+    req.disable_compression();
+
+    client.compress_input_unary(req).await.unwrap();
+
+    let bytes_sent = request_bytes_counter.load(SeqCst);
+    assert!(bytes_sent > UNCOMPRESSED_MIN_BODY_SIZE);
+}
@@ -18,10 +18,15 @@ use tonic::{
 use tower::{layer::layer_fn, service_fn, Service, ServiceBuilder};
 use tower_http::{map_request_body::MapRequestBodyLayer, map_response_body::MapResponseBodyLayer};
 
+#[cfg(test)]
 mod bidirectional_stream;
+#[cfg(test)]
 mod client_stream;
+#[cfg(test)]
 mod compressing_request;
+#[cfg(test)]
 mod compressing_response;
+#[cfg(test)]
 mod server_stream;
 mod util;
 
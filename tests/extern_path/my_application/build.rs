@@ -1,3 +1,5 @@
+use std::{env, path::PathBuf};
+
 fn main() -> Result<(), std::io::Error> {
     tonic_build::configure()
         .build_server(false)
@@ -7,5 +9,17 @@ fn main() -> Result<(), std::io::Error> {
             &["service.proto", "uuid.proto"],
             &["../proto/my_application", "../proto/uuid"],
         )?;
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap()).join("crate_relative");
+    std::fs::create_dir_all(&out_dir)?;
+    tonic_build::configure()
+        .build_server(false)
+        .build_client(true)
+        .extern_path(".uuid", "uuid")
+        .out_dir(out_dir)
+        .compile(
+            &["service.proto", "uuid.proto"],
+            &["../proto/my_application", "../proto/uuid"],
+        )?;
     Ok(())
 }
@@ -2,6 +2,14 @@ use uuid::DoSomething;
 mod pb {
     tonic::include_proto!("my_application");
 }
+// The same protos, with the extern path mapped to `uuid` instead of `::uuid`.
+#[allow(dead_code)]
+mod crate_relative {
+    include!(concat!(
+        env!("OUT_DIR"),
+        "/crate_relative/my_application.rs"
+    ));
+}
 fn main() {
     // verify that extern_path to replace proto's with impl's from other crates works.
     let message = pb::MyMessage {
@@ -24,3 +32,25 @@ fn service_types_have_extern_types() {
     };
     assert_eq!(message.message_id.unwrap().do_it(), "Done");
 }
+
+#[cfg(test)]
+#[test]
+fn crate_relative_extern_paths_are_not_prefixed() {
+    use crate_relative::{my_service_client::MyServiceClient, MyMessage};
+    use tonic::transport::Channel;
+
+    // Only needs to compile: the client must take and return `uuid::Uuid`, not a type
+    // relative to the generated client module.
+    #[allow(dead_code)]
+    async fn round_trip(
+        client: &mut MyServiceClient<Channel>,
+    ) -> Result<uuid::Uuid, tonic::Status> {
+        let message: MyMessage = client
+            .get_my_message(uuid::Uuid {
+                uuid_str: "not really a uuid".to_string(),
+            })
+            .await?
+            .into_inner();
+        Ok(client.get_uuid(message).await?.into_inner())
+    }
+}
@@ -1,4 +1,34 @@
+use std::{env, path::PathBuf};
+
 fn main() {
     tonic_build::compile_protos("proto/test.proto").unwrap();
     tonic_build::compile_protos("proto/stream.proto").unwrap();
+    tonic_build::compile_protos("proto/test1.proto").unwrap();
+    tonic_build::compile_protos("proto/streaming.proto").unwrap();
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    tonic_build::configure()
+        .file_descriptor_set_path(out_dir.join("transcoding_descriptor.bin"))
+        .compile(
+            &["proto/transcoding.proto"],
+            &["proto", "../../tonic-transcoding/proto"],
+        )
+        .unwrap();
+
+    tonic_build::configure()
+        .codec_path("crate::codec::CountingCodec")
+        .emit_file_descriptor_set(true)
+        .compile(&["proto/codec.proto"], &["proto"])
+        .unwrap();
+
+    tonic_build::configure()
+        .derive_serde(".persisted")
+        .serde_attribute(".persisted.AuditRecord", "rename_all = \"camelCase\"")
+        .compile(&["proto/persisted.proto"], &["proto"])
+        .unwrap();
+
+    tonic_build::configure()
+        .bytes(".blob")
+        .compile(&["proto/blob.proto"], &["proto"])
+        .unwrap();
 }
@@ -1,6 +1,73 @@
 pub mod pb {
     tonic::include_proto!("test");
     tonic::include_proto!("stream");
+    tonic::include_proto!("test1");
+
+    pub mod transcoding {
+        tonic::include_proto!("transcoding");
+
+        pub const FILE_DESCRIPTOR_SET: &[u8] =
+            tonic::include_file_descriptor_set!("transcoding_descriptor");
+    }
+
+    pub mod persisted {
+        tonic::include_proto!("persisted");
+    }
+
+    pub mod blob {
+        tonic::include_proto!("blob");
+    }
+
+    pub mod streaming {
+        tonic::include_proto!("streaming");
+    }
+}
+
+pub mod codec {
+    //! A codec that counts the messages it encodes, which the `codec` service is generated
+    //! with.
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tonic::codec::{Codec, ProstCodec};
+
+    pub mod pb {
+        tonic::include_proto!("codec");
+    }
+
+    static ENCODERS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Returns the number of encoders the `CountingCodec`s handed out.
+    pub fn encoders() -> usize {
+        ENCODERS.load(Ordering::SeqCst)
+    }
+
+    #[derive(Debug)]
+    pub struct CountingCodec<T, U>(ProstCodec<T, U>);
+
+    impl<T, U> Default for CountingCodec<T, U> {
+        fn default() -> Self {
+            Self(ProstCodec::default())
+        }
+    }
+
+    impl<T, U> Codec for CountingCodec<T, U>
+    where
+        ProstCodec<T, U>: Codec,
+    {
+        type Encode = <ProstCodec<T, U> as Codec>::Encode;
+        type Decode = <ProstCodec<T, U> as Codec>::Decode;
+        type Encoder = <ProstCodec<T, U> as Codec>::Encoder;
+        type Decoder = <ProstCodec<T, U> as Codec>::Decoder;
+
+        fn encoder(&mut self) -> Self::Encoder {
+            ENCODERS.fetch_add(1, Ordering::SeqCst);
+            self.0.encoder()
+        }
+
+        fn decoder(&mut self) -> Self::Decoder {
+            self.0.decoder()
+        }
+    }
 }
 
 pub mod mock {
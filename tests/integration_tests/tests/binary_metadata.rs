@@ -0,0 +1,110 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    metadata::{BinaryMetadataValue, MetadataValue},
+    transport::{Channel, Server},
+    Request, Response, Status,
+};
+
+const PAYLOAD: &[u8] = &[0xff, 0x00, 0x80, 0x7f, b'\n'];
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    // Echoes the decoded `x-payload-bin` value and reports how it was encoded
+    // on the wire.
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        let value = req
+            .metadata()
+            .get_bin("x-payload-bin")
+            .ok_or_else(|| Status::invalid_argument("missing x-payload-bin"))?;
+
+        let decoded = value
+            .to_bytes()
+            .map_err(|_| Status::invalid_argument("invalid x-payload-bin"))?;
+        let encoded = MetadataValue::try_from(value.as_encoded_bytes())
+            .map_err(|_| Status::internal("encoded value is not ascii"))?;
+
+        let mut res = Response::new(Output {});
+        res.metadata_mut()
+            .insert_bin("x-payload-bin", BinaryMetadataValue::from_bytes(&decoded));
+        res.metadata_mut().insert("x-payload-encoded", encoded);
+        Ok(res)
+    }
+}
+
+async fn client() -> test_client::TestClient<Channel> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Channel::from_shared(addr).unwrap().connect().await.unwrap();
+    test_client::TestClient::new(channel)
+}
+
+#[tokio::test]
+async fn binary_values_are_base64_encoded_on_the_wire() {
+    let mut client = client().await;
+
+    let mut req = Request::new(Input {});
+    req.metadata_mut()
+        .insert_bin("x-payload-bin", BinaryMetadataValue::from_bytes(PAYLOAD));
+
+    let res = client.unary_call(req).await.unwrap();
+
+    assert_eq!(res.metadata().get("x-payload-encoded").unwrap(), "/wCAfwo");
+    assert_eq!(
+        res.metadata()
+            .get_bin("x-payload-bin")
+            .unwrap()
+            .to_bytes()
+            .unwrap(),
+        PAYLOAD
+    );
+}
+
+#[tokio::test]
+async fn padded_binary_values_are_decoded() {
+    let mut client = client().await;
+
+    // Other implementations may pad the encoded value.
+    let mut req = Request::new(Input {});
+    req.metadata_mut().insert_bin(
+        "x-payload-bin",
+        BinaryMetadataValue::from_static("/wCAfwo="),
+    );
+
+    let res = client.unary_call(req).await.unwrap();
+
+    assert_eq!(
+        res.metadata()
+            .get_bin("x-payload-bin")
+            .unwrap()
+            .to_bytes()
+            .unwrap(),
+        PAYLOAD
+    );
+}
+
+#[test]
+fn binary_keys_are_kept_apart_from_ascii_keys() {
+    let mut metadata = tonic::metadata::MetadataMap::new();
+    metadata.insert_bin("x-payload-bin", BinaryMetadataValue::from_bytes(PAYLOAD));
+
+    // A binary entry can't be read back as ascii, the key type decides how
+    // the value is encoded.
+    assert!(metadata.get("x-payload-bin").is_none());
+    assert!(metadata.get_bin("x-payload").is_none());
+    assert!(std::panic::catch_unwind(|| {
+        tonic::metadata::MetadataMap::new().insert("x-payload-bin", "raw".parse().unwrap())
+    })
+    .is_err());
+}
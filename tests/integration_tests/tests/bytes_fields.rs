@@ -0,0 +1,47 @@
+use bytes::Bytes;
+use integration_tests::pb::blob::{blobs_client::BlobsClient, blobs_server, Blob};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl blobs_server::Blobs for Svc {
+    async fn echo(&self, req: Request<Blob>) -> Result<Response<Blob>, Status> {
+        Ok(Response::new(req.into_inner()))
+    }
+}
+
+#[tokio::test]
+async fn bytes_fields_are_generated_as_bytes() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(blobs_server::BlobsServer::new(Svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = BlobsClient::new(channel);
+
+    let data = Bytes::from(vec![7; 64 * 1024]);
+    let blob = Blob {
+        data: data.clone(),
+        chunks: vec![Bytes::from_static(b"a"), Bytes::from_static(b"b")],
+    };
+
+    let res = client.echo(blob.clone()).await.unwrap().into_inner();
+    assert_eq!(res.data, data);
+    assert_eq!(res, blob);
+}
@@ -0,0 +1,94 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    metadata::MetadataValue,
+    service::interceptor,
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+use tower::ServiceBuilder;
+
+#[derive(Clone, Default)]
+struct Svc {
+    calls: Arc<AtomicUsize>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+
+        match req.metadata().get("authorization") {
+            Some(token) if token == "Bearer secret" => Ok(Response::new(Output {})),
+            _ => Err(Status::unauthenticated("missing token")),
+        }
+    }
+}
+
+async fn channel(svc: Svc) -> Channel {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    Channel::from_shared(addr).unwrap().connect().await.unwrap()
+}
+
+fn add_token(mut req: Request<()>) -> Result<Request<()>, Status> {
+    req.metadata_mut()
+        .insert("authorization", MetadataValue::from_static("Bearer secret"));
+    Ok(req)
+}
+
+#[tokio::test]
+async fn interceptor_adds_metadata_to_every_call() {
+    let svc = Svc::default();
+    let mut client = TestClient::with_interceptor(channel(svc.clone()).await, add_token);
+
+    client.unary_call(Input {}).await.unwrap();
+    client.unary_call(Input {}).await.unwrap();
+
+    assert_eq!(svc.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn interceptor_can_cancel_a_call() {
+    let svc = Svc::default();
+    let mut client = TestClient::with_interceptor(channel(svc.clone()).await, |_| {
+        Err(Status::unauthenticated("no credentials available"))
+    });
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+    assert_eq!(status.message(), "no credentials available");
+
+    // The request never reached the server.
+    assert_eq!(svc.calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn interceptor_layer_is_shared_by_clients() {
+    let svc = Svc::default();
+    let channel = ServiceBuilder::new()
+        .layer(interceptor(add_token))
+        .service(channel(svc.clone()).await);
+
+    // Every client created from the intercepted channel sends the token.
+    TestClient::new(channel.clone())
+        .unary_call(Input {})
+        .await
+        .unwrap();
+    TestClient::new(channel).unary_call(Input {}).await.unwrap();
+
+    assert_eq!(svc.calls.load(Ordering::SeqCst), 2);
+}
@@ -3,9 +3,10 @@ use std::time::Duration;
 use futures::{channel::oneshot, FutureExt};
 use http::{header::HeaderName, HeaderValue};
 use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use tokio_stream::wrappers::TcpListenerStream;
 use tonic::{
     transport::{Endpoint, Server},
-    Request, Response, Status,
+    Code, Request, Response, Status,
 };
 use tower::ServiceBuilder;
 use tower_http::{set_header::SetRequestHeaderLayer, trace::TraceLayer};
@@ -56,3 +57,43 @@ async fn connect_supports_standard_tower_layers() {
     tx.send(()).unwrap();
     jh.await.unwrap();
 }
+
+#[tokio::test]
+async fn client_tower_timeout_is_deadline_exceeded() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    let mut client = TestClient::new(
+        ServiceBuilder::new()
+            .concurrency_limit(1)
+            .timeout(Duration::from_millis(100))
+            .service(channel),
+    );
+
+    let status = client.unary_call(Request::new(Input {})).await.unwrap_err();
+    assert_eq!(status.code(), Code::DeadlineExceeded);
+}
@@ -0,0 +1,47 @@
+use integration_tests::codec::{
+    encoders,
+    pb::{counted_client::CountedClient, counted_server, Input, Output},
+};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl counted_server::Counted for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {
+            value: req.into_inner().value + 1,
+        }))
+    }
+}
+
+#[tokio::test]
+async fn generated_code_uses_the_configured_codec() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(counted_server::CountedServer::new(Svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = CountedClient::new(channel);
+
+    let res = client.unary_call(Input { value: 1 }).await.unwrap();
+    assert_eq!(res.into_inner().value, 2);
+
+    // One for the request and one for the response.
+    assert_eq!(encoders(), 2);
+}
@@ -0,0 +1,157 @@
+use futures::StreamExt;
+use integration_tests::pb::{
+    test_client::TestClient, test_server, test_stream_client::TestStreamClient, test_stream_server,
+    Input, InputStream, Output, OutputStream,
+};
+use std::{pin::Pin, sync::Arc, time::Duration};
+use tokio::sync::{mpsc, Mutex};
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tonic::{
+    transport::{server::MemoryIncoming, Channel, Endpoint, Server},
+    Code, Request, Response, Status,
+};
+
+/// Handles calls when it is told to, and lets the test know when a call started.
+#[derive(Clone)]
+struct Svc {
+    started: mpsc::UnboundedSender<()>,
+    release: Arc<Mutex<mpsc::UnboundedReceiver<()>>>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.started.send(()).unwrap();
+        self.release.lock().await.recv().await;
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tonic::async_trait]
+impl test_stream_server::TestStream for Svc {
+    type StreamCallStream =
+        Pin<Box<dyn Stream<Item = Result<OutputStream, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<InputStream>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let (tx, rx) = mpsc::channel(1);
+        let release = self.release.clone();
+        tokio::spawn(async move {
+            tx.send(Ok(OutputStream {})).await.unwrap();
+            release.lock().await.recv().await;
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+}
+
+/// Serves `svc` with `server`, returning a way to open new connections.
+async fn serve(mut server: Server, svc: Svc) -> impl Fn() -> Channel {
+    let incoming = MemoryIncoming::new();
+    let connector = incoming.connector();
+
+    tokio::spawn(
+        server
+            .add_service(test_server::TestServer::new(svc.clone()))
+            .add_service(test_stream_server::TestStreamServer::new(svc))
+            .serve_with_incoming(incoming),
+    );
+
+    move || Endpoint::from_static("http://localhost").connect_with_connector_lazy(connector.clone())
+}
+
+fn svc() -> (Svc, mpsc::UnboundedReceiver<()>, mpsc::UnboundedSender<()>) {
+    let (started, started_rx) = mpsc::unbounded_channel();
+    let (release_tx, release) = mpsc::unbounded_channel();
+    let svc = Svc {
+        started,
+        release: Arc::new(Mutex::new(release)),
+    };
+    (svc, started_rx, release_tx)
+}
+
+#[tokio::test]
+async fn rejects_requests_over_the_limit_across_connections() {
+    let (svc, mut started, release) = svc();
+    let connect = serve(Server::builder().concurrency_limit(1), svc).await;
+
+    let mut first = TestClient::new(connect());
+    let in_flight = tokio::spawn(async move { first.unary_call(Input {}).await });
+    started.recv().await.unwrap();
+
+    let status = TestClient::new(connect())
+        .unary_call(Input {})
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+
+    release.send(()).unwrap();
+    in_flight.await.unwrap().unwrap();
+
+    // The permit of the finished request is available again.
+    release.send(()).unwrap();
+    TestClient::new(connect())
+        .unary_call(Input {})
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn streaming_responses_count_until_they_end() {
+    let (svc, _started, release) = svc();
+    let connect = serve(Server::builder().concurrency_limit(1), svc).await;
+
+    let mut stream = TestStreamClient::new(connect())
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    stream.next().await.unwrap().unwrap();
+
+    let status = TestClient::new(connect())
+        .unary_call(Input {})
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+
+    release.send(()).unwrap();
+    assert!(stream.next().await.is_none());
+
+    release.send(()).unwrap();
+    TestClient::new(connect())
+        .unary_call(Input {})
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn queues_requests_and_sheds_once_the_queue_is_full() {
+    let (svc, mut started, release) = svc();
+    let server = Server::builder()
+        .concurrency_limit(1)
+        .max_queued_requests(1);
+    let connect = serve(server, svc).await;
+
+    let mut first = TestClient::new(connect());
+    let first = tokio::spawn(async move { first.unary_call(Input {}).await });
+    started.recv().await.unwrap();
+
+    let mut queued = TestClient::new(connect());
+    let queued = tokio::spawn(async move { queued.unary_call(Input {}).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let status = TestClient::new(connect())
+        .unary_call(Input {})
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+
+    release.send(()).unwrap();
+    first.await.unwrap().unwrap();
+
+    // The queued request runs once the first one is done.
+    started.recv().await.unwrap();
+    release.send(()).unwrap();
+    queued.await.unwrap().unwrap();
+}
@@ -15,6 +15,7 @@ async fn getting_connect_info() {
     impl test_server::Test for Svc {
         async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
             assert!(req.remote_addr().is_some());
+            assert_eq!(req.local_addr(), Some("127.0.0.1:1400".parse().unwrap()));
             assert!(req.extensions().get::<TcpConnectInfo>().is_some());
 
             Ok(Response::new(Output {}))
@@ -74,6 +75,7 @@ pub mod unix {
 
             // Client-side unix sockets are unnamed.
             assert!(req.remote_addr().is_none());
+            assert!(req.local_addr().is_none());
             assert!(conn_info.peer_addr.as_ref().unwrap().is_unnamed());
             // This should contain process credentials for the client socket.
             assert!(conn_info.peer_cred.as_ref().is_some());
@@ -119,4 +121,109 @@ pub mod unix {
 
         std::fs::remove_file(unix_socket_path).unwrap();
     }
+
+    #[tokio::test]
+    async fn connecting_to_unix_target() {
+        struct Svc;
+
+        #[tonic::async_trait]
+        impl test_server::Test for Svc {
+            async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+                let conn_info = req.extensions().get::<UdsConnectInfo>().unwrap();
+                let pid = conn_info.peer_cred.unwrap().pid();
+                assert_eq!(pid, Some(std::process::id() as i32));
+
+                Ok(Response::new(Output {}))
+            }
+        }
+
+        let mut unix_socket_path = std::env::temp_dir();
+        unix_socket_path.push("uds-target-integration-test");
+        let _ = std::fs::remove_file(&unix_socket_path);
+
+        let uds = UnixListener::bind(&unix_socket_path).unwrap();
+        let (tx, rx) = oneshot::channel::<()>();
+
+        let jh = tokio::spawn(async move {
+            Server::builder()
+                .add_service(test_server::TestServer::new(Svc))
+                .serve_with_incoming_shutdown(UnixListenerStream::new(uds), rx.map(drop))
+                .await
+                .unwrap();
+        });
+
+        let target = format!("unix://{}", unix_socket_path.display());
+        let channel = Endpoint::from_shared(target)
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+
+        let mut client = test_client::TestClient::new(channel);
+
+        client.unary_call(Input {}).await.unwrap();
+
+        tx.send(()).unwrap();
+        jh.await.unwrap();
+
+        std::fs::remove_file(unix_socket_path).unwrap();
+    }
+}
+
+#[cfg(windows)]
+pub mod windows {
+    use futures_util::FutureExt;
+    use tokio::sync::oneshot;
+    use tonic::{
+        transport::{channel::NamedPipeConnector, server::NamedPipeIncoming, Endpoint, Server},
+        Request, Response, Status,
+    };
+
+    use integration_tests::pb::{test_client, test_server, Input, Output};
+
+    struct Svc {}
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            assert!(req.remote_addr().is_none());
+            assert!(req.extensions().get::<()>().is_some());
+
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    #[tokio::test]
+    async fn connecting_over_named_pipe() {
+        let path = format!(r"\\.\pipe\tonic-integration-test-{}", std::process::id());
+        let incoming = NamedPipeIncoming::new(&path).unwrap();
+
+        let service = test_server::TestServer::new(Svc {});
+        let (tx, rx) = oneshot::channel::<()>();
+
+        let jh = tokio::spawn(async move {
+            Server::builder()
+                .add_service(service)
+                .serve_with_incoming_shutdown(incoming, rx.map(drop))
+                .await
+                .unwrap();
+        });
+
+        let connector = NamedPipeConnector::new(&path);
+
+        // Every client gets an instance of the pipe of its own.
+        for _ in 0..2 {
+            let channel = Endpoint::from_static("http://localhost")
+                .connect_with_connector(connector.clone())
+                .await
+                .unwrap();
+
+            let mut client = test_client::TestClient::new(channel);
+
+            client.unary_call(Input {}).await.unwrap();
+        }
+
+        tx.send(()).unwrap();
+        jh.await.unwrap();
+    }
 }
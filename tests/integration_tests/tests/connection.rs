@@ -1,10 +1,13 @@
 use futures_util::FutureExt;
 use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
-use std::sync::{Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
 use std::time::Duration;
 use tokio::sync::oneshot;
 use tonic::{
-    transport::{Endpoint, Server},
+    transport::{Endpoint, Server, Uri},
     Code, Request, Response, Status,
 };
 
@@ -64,7 +67,9 @@ async fn connect_lazy_reconnects_after_first_failure() {
     let sender = Arc::new(Mutex::new(Some(tx)));
     let svc = test_server::TestServer::new(Svc(sender));
 
-    let channel = Endpoint::from_static("http://127.0.0.1:1339").connect_lazy();
+    let channel = Endpoint::from_static("http://127.0.0.1:1339")
+        .initial_reconnect_backoff(Duration::from_millis(50))
+        .connect_lazy();
 
     let mut client = TestClient::new(channel);
 
@@ -91,3 +96,114 @@ async fn connect_lazy_reconnects_after_first_failure() {
 
     jh.await.unwrap();
 }
+
+#[tokio::test]
+async fn connect_lazy_backs_off_between_attempts() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let connector = {
+        let attempts = attempts.clone();
+        tower::service_fn(move |_: Uri| {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            let refused = std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+            async move { Err::<tokio::net::TcpStream, _>(refused) }
+        })
+    };
+
+    let channel = Endpoint::from_static("http://[::]:50051")
+        .initial_reconnect_backoff(Duration::from_millis(200))
+        .reconnect_backoff_jitter(0.0)
+        .connect_with_connector_lazy(connector);
+    let mut client = TestClient::new(channel);
+
+    client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+    // Calls fail without reconnecting until the backoff has passed.
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+    // The second backoff is longer.
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn custom_connector_is_called_with_the_endpoint_uri() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc(Arc::new(Mutex::new(
+                None,
+            )))))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let uris = Arc::new(Mutex::new(Vec::new()));
+    let connector = {
+        let uris = uris.clone();
+        tower::service_fn(move |uri: Uri| {
+            uris.lock().unwrap().push(uri);
+            tokio::net::TcpStream::connect(addr)
+        })
+    };
+
+    Endpoint::from_static("http://tunnel.example:50051")
+        .connect_with_connector(connector)
+        .await
+        .unwrap();
+
+    assert_eq!(*uris.lock().unwrap(), ["http://tunnel.example:50051"]);
+}
+
+#[tokio::test]
+async fn connect_with_connector_lazy_applies_connect_timeout() {
+    let connector = tower::service_fn(|_: Uri| {
+        std::future::pending::<Result<tokio::net::TcpStream, std::io::Error>>()
+    });
+
+    let channel = Endpoint::from_static("http://[::]:50051")
+        .connect_timeout(Duration::from_millis(100))
+        .connect_with_connector_lazy(connector);
+    let mut client = TestClient::new(channel);
+
+    let call = client.unary_call(Input {});
+    let status = tokio::time::timeout(Duration::from_secs(5), call)
+        .await
+        .expect("connecting should time out")
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+}
+
+#[tokio::test]
+async fn generated_client_connects_lazily() {
+    let addr = "http://127.0.0.1:1342";
+
+    // Nothing is listening yet, so only the lazy client can be created.
+    TestClient::connect(addr).await.unwrap_err();
+    let mut client = TestClient::connect_lazy(addr).unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let svc = test_server::TestServer::new(Svc(Arc::new(Mutex::new(Some(tx)))));
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_shutdown("127.0.0.1:1342".parse().unwrap(), rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    jh.await.unwrap();
+}
@@ -0,0 +1,47 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn waits_for_a_connection_to_close_before_accepting_another() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .max_connections(1)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let endpoint = Endpoint::from_shared(format!("http://{}", addr)).unwrap();
+
+    let mut first = TestClient::new(endpoint.connect().await.unwrap());
+    first.unary_call(Input {}).await.unwrap();
+
+    // The second connection waits in the backlog while the first one is open.
+    let mut second = TestClient::new(endpoint.connect_lazy());
+    let call = tokio::time::timeout(Duration::from_millis(200), second.unary_call(Input {}));
+    assert!(call.await.is_err());
+
+    drop(first);
+    tokio::time::timeout(Duration::from_secs(5), second.unary_call(Input {}))
+        .await
+        .unwrap()
+        .unwrap();
+}
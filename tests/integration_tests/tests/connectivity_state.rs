@@ -0,0 +1,116 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::time::Duration;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{channel::ConnectivityState, Channel, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn run_server() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+#[tokio::test]
+async fn eager_channel_is_ready() {
+    let addr = run_server().await;
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    assert_eq!(channel.state(), ConnectivityState::Ready);
+}
+
+#[tokio::test]
+async fn lazy_channel_becomes_ready_once_used() {
+    let addr = run_server().await;
+    let channel = Endpoint::from_shared(addr).unwrap().connect_lazy();
+    assert_eq!(channel.state(), ConnectivityState::Idle);
+
+    let waiter = {
+        let channel = channel.clone();
+        tokio::spawn(async move {
+            let mut states = vec![];
+            let mut state = channel.state();
+            while state != ConnectivityState::Ready {
+                state = channel.wait_for_state_change(state).await;
+                states.push(state);
+            }
+            states
+        })
+    };
+
+    TestClient::new(channel.clone())
+        .unary_call(Input {})
+        .await
+        .unwrap();
+
+    let states = tokio::time::timeout(Duration::from_secs(5), waiter)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(states.last(), Some(&ConnectivityState::Ready));
+    assert_eq!(channel.state(), ConnectivityState::Ready);
+}
+
+#[tokio::test]
+async fn failed_connection_is_a_transient_failure() {
+    let unused = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let channel = Endpoint::from_shared(format!("http://{}", unused))
+        .unwrap()
+        .connect_lazy();
+
+    TestClient::new(channel.clone())
+        .unary_call(Input {})
+        .await
+        .unwrap_err();
+
+    assert_eq!(channel.state(), ConnectivityState::TransientFailure);
+}
+
+#[tokio::test]
+async fn balanced_channel_is_ready_when_any_endpoint_is() {
+    let addr = run_server().await;
+    let unused = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let endpoints = vec![
+        Endpoint::from_shared(format!("http://{}", unused)).unwrap(),
+        Endpoint::from_shared(addr).unwrap(),
+    ];
+    let channel = Channel::round_robin_list(endpoints.into_iter());
+
+    TestClient::new(channel.clone())
+        .unary_call(Input {})
+        .await
+        .unwrap();
+
+    assert_eq!(channel.state(), ConnectivityState::Ready);
+}
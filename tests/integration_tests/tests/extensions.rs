@@ -104,6 +104,50 @@ async fn setting_extension_from_tower() {
     jh.await.unwrap();
 }
 
+#[tokio::test]
+async fn reading_response_extension_from_tower() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            let mut res = Response::new(Output {});
+            res.extensions_mut().insert(ExtensionValue(7));
+            Ok(res)
+        }
+    }
+
+    let svc = ExtensionToHeader {
+        inner: test_server::TestServer::new(Svc),
+    };
+
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_shutdown("127.0.0.1:1325".parse().unwrap(), rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_static("http://127.0.0.1:1325")
+        .connect()
+        .await
+        .unwrap();
+
+    let mut client = test_client::TestClient::new(channel);
+
+    let res = client.unary_call(Input {}).await.unwrap();
+    assert_eq!(res.metadata().get("x-extension-value").unwrap(), "7");
+
+    tx.send(()).unwrap();
+
+    jh.await.unwrap();
+}
+
 #[derive(Debug, Clone)]
 struct InterceptedService<S> {
     inner: S,
@@ -142,3 +186,45 @@ where
 impl<S: NamedService> NamedService for InterceptedService<S> {
     const NAME: &'static str = S::NAME;
 }
+
+// Exposes the `ExtensionValue` a handler attached to its response as a header.
+#[derive(Debug, Clone)]
+struct ExtensionToHeader<S> {
+    inner: S,
+}
+
+impl<S> Service<HyperRequest<Body>> for ExtensionToHeader<S>
+where
+    S: Service<HyperRequest<Body>, Response = HyperResponse<BoxBody>>
+        + NamedService
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = futures::future::BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: HyperRequest<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let mut response = inner.call(req).await?;
+            let value = response.extensions().get::<ExtensionValue>().unwrap().0;
+            response
+                .headers_mut()
+                .insert("x-extension-value", value.into());
+            Ok(response)
+        })
+    }
+}
+
+impl<S: NamedService> NamedService for ExtensionToHeader<S> {
+    const NAME: &'static str = S::NAME;
+}
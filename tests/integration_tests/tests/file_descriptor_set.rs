@@ -0,0 +1,16 @@
+use integration_tests::codec::pb::FILE_DESCRIPTOR_SET;
+use prost::Message;
+use prost_types::FileDescriptorSet;
+
+#[test]
+fn generated_packages_embed_their_file_descriptor_set() {
+    let set = FileDescriptorSet::decode(FILE_DESCRIPTOR_SET).unwrap();
+
+    let file = set
+        .file
+        .iter()
+        .find(|file| file.name() == "codec.proto")
+        .unwrap();
+    assert_eq!(file.package(), "codec");
+    assert_eq!(file.service[0].name(), "Counted");
+}
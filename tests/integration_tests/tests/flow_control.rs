@@ -0,0 +1,61 @@
+use integration_tests::pb::{test1_client, test1_server, Input1, Output1};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Channel, Server},
+    Request, Response, Status,
+};
+
+const WINDOW: u32 = 4 * 1024 * 1024;
+const MESSAGE: usize = 10 * 1024 * 1024;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Ok(Response::new(Output1 {
+            buf: req.into_inner().buf,
+        }))
+    }
+}
+
+#[tokio::test]
+async fn large_windows_carry_large_messages() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        let svc = test1_server::Test1Server::new(Svc)
+            .max_decoding_message_size(usize::MAX)
+            .max_encoding_message_size(usize::MAX);
+
+        Server::builder()
+            .initial_stream_window_size(WINDOW)
+            .initial_connection_window_size(2 * WINDOW)
+            .add_service(svc)
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Channel::from_shared(addr)
+        .unwrap()
+        .initial_stream_window_size(WINDOW)
+        .initial_connection_window_size(2 * WINDOW)
+        .connect()
+        .await
+        .unwrap();
+
+    let mut client = test1_client::Test1Client::new(channel)
+        .max_decoding_message_size(usize::MAX)
+        .max_encoding_message_size(usize::MAX);
+
+    let res = client
+        .unary_call(Input1 {
+            buf: vec![7; MESSAGE],
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(res.into_inner().buf, vec![7; MESSAGE]);
+}
@@ -0,0 +1,77 @@
+use futures_util::future;
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{server::TcpConnectInfo, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        if req.extensions().get::<TcpConnectInfo>().is_none() {
+            return Err(Status::internal("missing connect info"));
+        }
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn serve(accept_h2c_upgrade: bool) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .accept_h2c_upgrade(accept_h2c_upgrade)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+#[tokio::test]
+async fn upgrades_connections() {
+    let addr = serve(true).await;
+
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .h2c_upgrade(true)
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    client.unary_call(Input {}).await.unwrap();
+    let calls = (0..10).map(|_| {
+        let mut client = client.clone();
+        async move { client.unary_call(Input {}).await }
+    });
+    for res in future::join_all(calls).await {
+        res.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn prior_knowledge_still_works() {
+    let addr = serve(true).await;
+
+    let mut client = TestClient::connect(addr).await.unwrap();
+    client.unary_call(Input {}).await.unwrap();
+}
+
+#[tokio::test]
+async fn upgrade_not_accepted() {
+    let addr = serve(false).await;
+
+    let res = Endpoint::from_shared(addr)
+        .unwrap()
+        .h2c_upgrade(true)
+        .connect()
+        .await;
+    assert!(res.is_err());
+}
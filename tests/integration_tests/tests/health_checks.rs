@@ -0,0 +1,116 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{channel::ServiceConfig, Channel, Endpoint, Server},
+    Code, Request, Response, Status,
+};
+use tonic_health::{server::HealthReporter, ServingStatus};
+
+#[derive(Clone, Default)]
+struct Svc {
+    calls: Arc<AtomicUsize>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(Response::new(Output {}))
+    }
+}
+
+/// Serves `svc`, along with a health service unless `health` is false.
+async fn run_server(svc: Svc, health: bool) -> (SocketAddr, HealthReporter) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (reporter, health_service) = tonic_health::server::health_reporter();
+
+    let mut server = Server::builder();
+    let router = server
+        .add_service(test_server::TestServer::new(svc))
+        .add_optional_service(Some(health_service).filter(|_| health));
+    tokio::spawn(router.serve_with_incoming(TcpListenerStream::new(listener)));
+
+    (addr, reporter)
+}
+
+fn channel(addrs: &[SocketAddr]) -> Channel {
+    let config = r#"{ "healthCheckConfig": { "serviceName": "test.Test" } }"#;
+    let endpoints = addrs.iter().map(|addr| {
+        Endpoint::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .service_config(ServiceConfig::from_json(config).unwrap())
+    });
+
+    Channel::round_robin_list(endpoints)
+}
+
+#[tokio::test]
+async fn skips_endpoints_that_are_not_serving() {
+    let (a, b) = (Svc::default(), Svc::default());
+    let (a_addr, mut a_health) = run_server(a.clone(), true).await;
+    let (b_addr, mut b_health) = run_server(b.clone(), true).await;
+    a_health
+        .set_service_status("test.Test", ServingStatus::Serving)
+        .await;
+    b_health
+        .set_service_status("test.Test", ServingStatus::NotServing)
+        .await;
+
+    let mut client = TestClient::new(channel(&[a_addr, b_addr]));
+    for _ in 0..10 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+    assert_eq!(a.calls.load(Ordering::SeqCst), 10);
+    assert_eq!(b.calls.load(Ordering::SeqCst), 0);
+
+    a_health
+        .set_service_status("test.Test", ServingStatus::NotServing)
+        .await;
+    b_health
+        .set_service_status("test.Test", ServingStatus::Serving)
+        .await;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while b.calls.load(Ordering::SeqCst) == 0 {
+            let _ = client.unary_call(Input {}).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    let calls = a.calls.load(Ordering::SeqCst);
+    for _ in 0..10 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+    assert_eq!(a.calls.load(Ordering::SeqCst), calls);
+}
+
+#[tokio::test]
+async fn fails_calls_when_no_endpoint_is_serving() {
+    let (addr, mut health) = run_server(Svc::default(), true).await;
+    health
+        .set_service_status("test.Test", ServingStatus::NotServing)
+        .await;
+
+    let mut client = TestClient::new(channel(&[addr]));
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+}
+
+#[tokio::test]
+async fn assumes_servers_without_a_health_service_are_healthy() {
+    let svc = Svc::default();
+    let (addr, _health) = run_server(svc.clone(), false).await;
+
+    let mut client = TestClient::new(channel(&[addr]));
+    client.unary_call(Input {}).await.unwrap();
+    assert_eq!(svc.calls.load(Ordering::SeqCst), 1);
+}
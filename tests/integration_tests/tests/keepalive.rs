@@ -0,0 +1,163 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{tcp::OwnedReadHalf, tcp::OwnedWriteHalf, TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+/// Answers the first call and leaves every later call pending.
+#[derive(Default)]
+struct Svc {
+    answered: AtomicBool,
+    dropped: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+struct NotifyOnDrop(Option<oneshot::Sender<()>>);
+
+impl Drop for NotifyOnDrop {
+    fn drop(&mut self) {
+        if let Some(tx) = self.0.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        if !self.answered.swap(true, Ordering::SeqCst) {
+            return Ok(Response::new(Output {}));
+        }
+
+        let _guard = NotifyOnDrop(self.dropped.lock().unwrap().take());
+        std::future::pending().await
+    }
+}
+
+/// A TCP proxy that stops forwarding, without closing the connections, once `frozen` is
+/// set, like a NAT that dropped its mapping.
+async fn run_proxy(target: SocketAddr, frozen: Arc<AtomicBool>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let (client, _) = listener.accept().await.unwrap();
+            let server = TcpStream::connect(target).await.unwrap();
+            let (client_read, client_write) = client.into_split();
+            let (server_read, server_write) = server.into_split();
+            tokio::spawn(pipe(client_read, server_write, frozen.clone()));
+            tokio::spawn(pipe(server_read, client_write, frozen.clone()));
+        }
+    });
+
+    addr
+}
+
+async fn pipe(mut from: OwnedReadHalf, mut to: OwnedWriteHalf, frozen: Arc<AtomicBool>) {
+    let mut buf = [0; 8192];
+    loop {
+        let n = match from.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => n,
+        };
+
+        if frozen.load(Ordering::SeqCst) {
+            // Keep both halves open so the peers are not told the connection is gone.
+            std::future::pending::<()>().await;
+        }
+
+        if to.write_all(&buf[..n]).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn run_server(server: Server, svc: Svc) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let mut server = server;
+        server
+            .add_service(test_server::TestServer::new(svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+#[tokio::test]
+async fn client_keepalive_detects_a_dead_connection() {
+    let frozen = Arc::new(AtomicBool::new(false));
+    let addr = run_server(Server::builder(), Svc::default()).await;
+    let proxy = run_proxy(addr, frozen.clone()).await;
+
+    let channel = Endpoint::from_shared(format!("http://{}", proxy))
+        .unwrap()
+        .http2_keep_alive_interval(Duration::from_millis(100))
+        .keep_alive_timeout(Duration::from_millis(100))
+        .keep_alive_while_idle(true)
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    client.unary_call(Input {}).await.unwrap();
+
+    frozen.store(true, Ordering::SeqCst);
+
+    // Without keepalive this call would hang forever.
+    let call = tokio::time::timeout(Duration::from_secs(5), client.unary_call(Input {}));
+    assert!(call.await.expect("dead connection not detected").is_err());
+}
+
+#[tokio::test]
+async fn server_keepalive_detects_a_dead_connection() {
+    let frozen = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = oneshot::channel();
+    let svc = Svc {
+        dropped: Mutex::new(Some(tx)),
+        ..Svc::default()
+    };
+    let server = Server::builder()
+        .http2_keepalive_interval(Some(Duration::from_millis(100)))
+        .http2_keepalive_timeout(Some(Duration::from_millis(100)));
+    let addr = run_server(server, svc).await;
+    let proxy = run_proxy(addr, frozen.clone()).await;
+
+    let channel = Endpoint::from_shared(format!("http://{}", proxy))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    client.unary_call(Input {}).await.unwrap();
+
+    // Leave a call running on the server, then cut the client off.
+    tokio::spawn(async move { client.unary_call(Input {}).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    frozen.store(true, Ordering::SeqCst);
+
+    // The server closes the connection, which drops the running call.
+    tokio::time::timeout(Duration::from_secs(5), rx)
+        .await
+        .expect("dead connection not detected")
+        .unwrap();
+}
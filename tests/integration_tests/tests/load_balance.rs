@@ -0,0 +1,225 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{
+    transport::{channel::Resolver, Channel, Endpoint, Server},
+    Request, Response, Status,
+};
+use tower::discover::Change;
+
+#[derive(Clone, Default)]
+struct Svc {
+    calls: Arc<AtomicUsize>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn run_server(svc: Svc) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+fn endpoint(addr: SocketAddr) -> Endpoint {
+    Endpoint::from_shared(format!("http://{}", addr)).unwrap()
+}
+
+#[tokio::test]
+async fn balance_list_spreads_calls_over_endpoints() {
+    let (a, b) = (Svc::default(), Svc::default());
+    let endpoints = vec![
+        endpoint(run_server(a.clone()).await),
+        endpoint(run_server(b.clone()).await),
+    ];
+
+    let mut client = TestClient::new(Channel::balance_list(endpoints.into_iter()));
+
+    for _ in 0..50 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+
+    assert_eq!(
+        a.calls.load(Ordering::SeqCst) + b.calls.load(Ordering::SeqCst),
+        50
+    );
+    assert!(a.calls.load(Ordering::SeqCst) > 0);
+    assert!(b.calls.load(Ordering::SeqCst) > 0);
+}
+
+#[tokio::test]
+async fn balance_list_accepts_more_endpoints_than_the_buffer_size() {
+    // None of these are connected to until a call is made.
+    let endpoints = (0..2000).map(|i| endpoint(([127, 0, 0, 1], 20000 + i).into()));
+
+    let _channel = Channel::balance_list(endpoints);
+}
+
+#[tokio::test]
+async fn balance_channel_follows_changes() {
+    let (a, b) = (Svc::default(), Svc::default());
+    let (addr_a, addr_b) = (run_server(a.clone()).await, run_server(b.clone()).await);
+
+    let (channel, tx) = Channel::balance_channel(10);
+    let mut client = TestClient::new(channel);
+
+    tx.send(Change::Insert("a", endpoint(addr_a)))
+        .await
+        .unwrap();
+    client.unary_call(Input {}).await.unwrap();
+    assert_eq!(a.calls.load(Ordering::SeqCst), 1);
+
+    tx.send(Change::Insert("b", endpoint(addr_b)))
+        .await
+        .unwrap();
+    tx.send(Change::Remove("a")).await.unwrap();
+
+    for _ in 0..10 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+    assert_eq!(a.calls.load(Ordering::SeqCst), 1);
+    assert_eq!(b.calls.load(Ordering::SeqCst), 10);
+}
+
+#[tokio::test]
+async fn round_robin_alternates_between_endpoints() {
+    let (a, b) = (Svc::default(), Svc::default());
+    let endpoints = vec![
+        endpoint(run_server(a.clone()).await),
+        endpoint(run_server(b.clone()).await),
+    ];
+
+    let mut client = TestClient::new(Channel::round_robin_list(endpoints.into_iter()));
+
+    // Calls made while the endpoints are connecting go to whichever is ready first.
+    while a.calls.load(Ordering::SeqCst) == 0 || b.calls.load(Ordering::SeqCst) == 0 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+
+    let (start_a, start_b) = (
+        a.calls.load(Ordering::SeqCst),
+        b.calls.load(Ordering::SeqCst),
+    );
+    for _ in 0..10 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+
+    assert_eq!(a.calls.load(Ordering::SeqCst) - start_a, 5);
+    assert_eq!(b.calls.load(Ordering::SeqCst) - start_b, 5);
+}
+
+#[tokio::test]
+async fn round_robin_skips_failing_endpoints() {
+    let svc = Svc::default();
+    let unused = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let endpoints = vec![endpoint(unused), endpoint(run_server(svc.clone()).await)];
+
+    let mut client = TestClient::new(Channel::round_robin_list(endpoints.into_iter()));
+
+    for _ in 0..10 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+
+    assert_eq!(svc.calls.load(Ordering::SeqCst), 10);
+}
+
+#[tokio::test]
+async fn round_robin_fails_calls_when_all_endpoints_are_failing() {
+    let unused = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let mut client = TestClient::new(Channel::round_robin_list(
+        vec![endpoint(unused)].into_iter(),
+    ));
+
+    let call = tokio::time::timeout(Duration::from_secs(5), client.unary_call(Input {}));
+    assert!(call.await.expect("call did not fail").is_err());
+}
+
+#[tokio::test]
+async fn balance_dns_connects_to_the_resolved_addresses() {
+    let svc = Svc::default();
+    let addr = run_server(svc.clone()).await;
+    let endpoint = Endpoint::from_shared(format!("http://localhost:{}", addr.port())).unwrap();
+
+    // `localhost` may also resolve to `::1`, which the server is not listening on and is
+    // skipped.
+    let mut client = TestClient::new(Channel::balance_dns(endpoint, Duration::from_secs(30)));
+
+    for _ in 0..5 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+
+    assert_eq!(svc.calls.load(Ordering::SeqCst), 5);
+}
+
+struct TestResolver(Option<mpsc::Receiver<Result<Vec<SocketAddr>, Infallible>>>);
+
+impl Resolver for TestResolver {
+    type Error = Infallible;
+    type Stream = ReceiverStream<Result<Vec<SocketAddr>, Infallible>>;
+
+    fn resolve(&mut self, target: &str) -> Self::Stream {
+        assert_eq!(target, "http://my-service:50051/");
+        ReceiverStream::new(self.0.take().unwrap())
+    }
+}
+
+#[tokio::test]
+async fn balance_resolver_follows_address_updates() {
+    let (a, b) = (Svc::default(), Svc::default());
+    let (addr_a, addr_b) = (run_server(a.clone()).await, run_server(b.clone()).await);
+
+    let (tx, rx) = mpsc::channel(1);
+    let endpoint = Endpoint::from_static("http://my-service:50051");
+    let channel = Channel::balance_resolver(endpoint, TestResolver(Some(rx)));
+    let mut client = TestClient::new(channel);
+
+    tx.send(Ok(vec![addr_a])).await.unwrap();
+    for _ in 0..5 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+    assert_eq!(a.calls.load(Ordering::SeqCst), 5);
+
+    tx.send(Ok(vec![addr_b])).await.unwrap();
+    // The update is applied asynchronously, calls go to `a` until it is.
+    while b.calls.load(Ordering::SeqCst) == 0 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+
+    let calls_a = a.calls.load(Ordering::SeqCst);
+    for _ in 0..5 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+    assert_eq!(a.calls.load(Ordering::SeqCst), calls_a);
+}
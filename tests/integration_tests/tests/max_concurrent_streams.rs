@@ -0,0 +1,153 @@
+use integration_tests::pb::{
+    test_client::TestClient, test_server, test_stream_client::TestStreamClient, test_stream_server,
+    Input, InputStream, Output, OutputStream,
+};
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+use tokio_stream::{wrappers::TcpListenerStream, Stream, StreamExt};
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc {
+    peers: Arc<Mutex<Vec<SocketAddr>>>,
+    release: Arc<Semaphore>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        self.peers.lock().unwrap().push(req.remote_addr().unwrap());
+        self.release.acquire().await.unwrap().forget();
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn calls_in_flight(peers: &Mutex<Vec<SocketAddr>>, calls: usize) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while peers.lock().unwrap().len() < calls {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn opens_connections_when_streams_are_capped() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    let peers = Arc::new(Mutex::new(Vec::new()));
+    let release = Arc::new(Semaphore::new(0));
+    let svc = Svc {
+        peers: peers.clone(),
+        release: release.clone(),
+    };
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .max_concurrent_streams(2)
+        .connect()
+        .await
+        .unwrap();
+    let client = TestClient::new(channel);
+
+    let batch = || {
+        (0..5)
+            .map(|_| {
+                let mut client = client.clone();
+                tokio::spawn(async move { client.unary_call(Input {}).await })
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let calls = batch();
+    calls_in_flight(&peers, 5).await;
+    let connections = peers.lock().unwrap().iter().collect::<HashSet<_>>().len();
+    assert_eq!(connections, 3);
+
+    release.add_permits(5);
+    for call in calls {
+        call.await.unwrap().unwrap();
+    }
+    // Let the finished streams be released from their connections.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // The connections are reused once their streams are done.
+    let calls = batch();
+    calls_in_flight(&peers, 10).await;
+    let connections = peers.lock().unwrap().iter().collect::<HashSet<_>>().len();
+    assert_eq!(connections, 3);
+
+    release.add_permits(5);
+    for call in calls {
+        call.await.unwrap().unwrap();
+    }
+}
+
+struct Hang;
+
+#[tonic::async_trait]
+impl test_stream_server::TestStream for Hang {
+    type StreamCallStream =
+        std::pin::Pin<Box<dyn Stream<Item = Result<OutputStream, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<InputStream>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        // One message, then the stream stays open without sending anything else.
+        let stream = tokio_stream::once(Ok(OutputStream {})).chain(tokio_stream::pending());
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[tokio::test]
+async fn dropped_response_stream_releases_its_stream() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_stream_server::TestStreamServer::new(Hang))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .max_concurrent_streams(1)
+        .max_connections(1)
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestStreamClient::new(channel);
+
+    for _ in 0..2 {
+        let call = async {
+            let mut stream = client.stream_call(InputStream {}).await?.into_inner();
+            stream.message().await
+        };
+        let message = tokio::time::timeout(Duration::from_secs(5), call)
+            .await
+            .expect("the stream of the dropped call was not released")
+            .unwrap();
+        assert!(message.is_some());
+    }
+}
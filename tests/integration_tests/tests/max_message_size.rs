@@ -0,0 +1,121 @@
+use integration_tests::pb::{test1_client, test1_server, Input1, Output1};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+
+const LIMIT: usize = 1024;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Ok(Response::new(Output1 {
+            buf: req.into_inner().buf,
+        }))
+    }
+}
+
+async fn serve(svc: test1_server::Test1Server<Svc>) -> Channel {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    Channel::from_shared(addr).unwrap().connect().await.unwrap()
+}
+
+#[tokio::test]
+async fn within_limits() {
+    let channel = serve(
+        test1_server::Test1Server::new(Svc)
+            .max_decoding_message_size(LIMIT)
+            .max_encoding_message_size(LIMIT),
+    )
+    .await;
+
+    let mut client = test1_client::Test1Client::new(channel)
+        .max_decoding_message_size(LIMIT)
+        .max_encoding_message_size(LIMIT);
+
+    let res = client
+        .unary_call(Input1 {
+            buf: vec![0; LIMIT / 2],
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(res.into_inner().buf.len(), LIMIT / 2);
+}
+
+#[tokio::test]
+async fn client_encoding_limit_exceeded() {
+    let channel = serve(test1_server::Test1Server::new(Svc)).await;
+
+    let mut client = test1_client::Test1Client::new(channel).max_encoding_message_size(LIMIT);
+
+    let status = client
+        .unary_call(Input1 {
+            buf: vec![0; LIMIT * 2],
+        })
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::OutOfRange);
+}
+
+#[tokio::test]
+async fn server_encoding_limit_exceeded() {
+    let channel = serve(test1_server::Test1Server::new(Svc).max_encoding_message_size(LIMIT)).await;
+
+    let mut client = test1_client::Test1Client::new(channel);
+
+    let status = client
+        .unary_call(Input1 {
+            buf: vec![0; LIMIT * 2],
+        })
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::OutOfRange);
+}
+
+#[tokio::test]
+async fn client_decoding_limit_exceeded() {
+    let channel = serve(test1_server::Test1Server::new(Svc)).await;
+
+    let mut client = test1_client::Test1Client::new(channel).max_decoding_message_size(LIMIT);
+
+    let status = client
+        .unary_call(Input1 {
+            buf: vec![0; LIMIT * 2],
+        })
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::ResourceExhausted);
+}
+
+#[tokio::test]
+async fn server_decoding_limit_exceeded() {
+    let channel = serve(test1_server::Test1Server::new(Svc).max_decoding_message_size(LIMIT)).await;
+
+    let mut client = test1_client::Test1Client::new(channel);
+
+    let status = client
+        .unary_call(Input1 {
+            buf: vec![0; LIMIT * 2],
+        })
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::ResourceExhausted);
+}
@@ -0,0 +1,50 @@
+use futures_util::FutureExt;
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use tokio::sync::oneshot;
+use tonic::{
+    transport::{server::MemoryIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn serves_in_memory_connections() {
+    let incoming = MemoryIncoming::new();
+    let connector = incoming.connector();
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(incoming, rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    let endpoint = Endpoint::from_static("http://localhost");
+
+    // Every channel gets a connection of its own.
+    for _ in 0..2 {
+        let channel = endpoint
+            .connect_with_connector(connector.clone())
+            .await
+            .unwrap();
+        TestClient::new(channel).unary_call(Input {}).await.unwrap();
+    }
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+
+    endpoint
+        .connect_with_connector(connector)
+        .await
+        .unwrap_err();
+}
@@ -0,0 +1,117 @@
+use integration_tests::pb::{
+    test_client, test_server, test_stream_client, test_stream_server, Input, InputStream, Output,
+    OutputStream,
+};
+use std::pin::Pin;
+use tokio_stream::{wrappers::TcpListenerStream, Stream};
+use tonic::{
+    metadata::MetadataMap,
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn oversized_metadata_is_resource_exhausted() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .http2_max_header_list_size(1024)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Channel::from_shared(addr).unwrap().connect().await.unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    let mut req = Request::new(Input {});
+    req.metadata_mut()
+        .insert("x-large", "a".repeat(4096).parse().unwrap());
+
+    let status = client.unary_call(req).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+
+    // The connection is still usable for requests within the limit.
+    client.unary_call(Request::new(Input {})).await.unwrap();
+}
+
+struct LargeMetadata;
+
+fn large_metadata() -> MetadataMap {
+    let mut metadata = MetadataMap::new();
+    metadata.insert("x-large", "a".repeat(4096).parse().unwrap());
+    metadata
+}
+
+#[tonic::async_trait]
+impl test_server::Test for LargeMetadata {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        let mut response = Response::new(Output {});
+        *response.metadata_mut() = large_metadata();
+        Ok(response)
+    }
+}
+
+#[tonic::async_trait]
+impl test_stream_server::TestStream for LargeMetadata {
+    type StreamCallStream =
+        Pin<Box<dyn Stream<Item = Result<OutputStream, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<InputStream>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        // The status is sent in the trailers, after the message.
+        let status = Status::with_metadata(Code::Aborted, "aborted", large_metadata());
+        let stream = tokio_stream::iter(vec![Ok(OutputStream {}), Err(status)]);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[tokio::test]
+async fn oversized_response_metadata_is_resource_exhausted() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(LargeMetadata))
+            .add_service(test_stream_server::TestStreamServer::new(LargeMetadata))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Channel::from_shared(addr)
+        .unwrap()
+        .http2_max_header_list_size(1024)
+        .connect()
+        .await
+        .unwrap();
+
+    let mut client = test_client::TestClient::new(channel.clone());
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+
+    let mut client = test_stream_client::TestStreamClient::new(channel);
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(stream.message().await.unwrap().is_some());
+    let status = stream.message().await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+}
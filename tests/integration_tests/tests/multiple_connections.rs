@@ -0,0 +1,200 @@
+use integration_tests::pb::{
+    test_client::TestClient, test_server, test_stream_client::TestStreamClient, test_stream_server,
+    Input, InputStream, Output, OutputStream,
+};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+use tokio_stream::{wrappers::TcpListenerStream, Stream, StreamExt};
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc {
+    peers: Arc<Mutex<Vec<SocketAddr>>>,
+    release: Arc<Semaphore>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        self.peers.lock().unwrap().push(req.remote_addr().unwrap());
+        self.release.acquire().await.unwrap().forget();
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn calls_per_connection(peers: &Mutex<Vec<SocketAddr>>, calls: usize) -> Vec<usize> {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while peers.lock().unwrap().len() < calls {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    let mut counts = HashMap::new();
+    for peer in peers.lock().unwrap().iter() {
+        *counts.entry(*peer).or_insert(0) += 1;
+    }
+    counts.into_values().collect()
+}
+
+async fn serve(svc: Svc) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+#[tokio::test]
+async fn spreads_calls_over_connections() {
+    let peers = Arc::new(Mutex::new(Vec::new()));
+    let release = Arc::new(Semaphore::new(0));
+    let addr = serve(Svc {
+        peers: peers.clone(),
+        release: release.clone(),
+    })
+    .await;
+
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .connections(4)
+        .connect()
+        .await
+        .unwrap();
+    let client = TestClient::new(channel);
+
+    let calls = (0..8)
+        .map(|_| {
+            let mut client = client.clone();
+            tokio::spawn(async move { client.unary_call(Input {}).await })
+        })
+        .collect::<Vec<_>>();
+
+    let counts = calls_per_connection(&peers, 8).await;
+    assert_eq!(counts, vec![2; 4]);
+
+    release.add_permits(8);
+    for call in calls {
+        call.await.unwrap().unwrap();
+    }
+}
+
+#[tokio::test]
+async fn lazy_channels_open_their_connections_on_first_use() {
+    let peers = Arc::new(Mutex::new(Vec::new()));
+    let release = Arc::new(Semaphore::new(0));
+    let addr = serve(Svc {
+        peers: peers.clone(),
+        release: release.clone(),
+    })
+    .await;
+
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .connections(2)
+        .connect_lazy();
+    let client = TestClient::new(channel);
+
+    let calls = (0..4)
+        .map(|_| {
+            let mut client = client.clone();
+            tokio::spawn(async move { client.unary_call(Input {}).await })
+        })
+        .collect::<Vec<_>>();
+
+    let counts = calls_per_connection(&peers, 4).await;
+    assert_eq!(counts, vec![2; 2]);
+
+    release.add_permits(4);
+    for call in calls {
+        call.await.unwrap().unwrap();
+    }
+}
+
+struct Hang {
+    peers: Arc<Mutex<Vec<SocketAddr>>>,
+}
+
+#[tonic::async_trait]
+impl test_stream_server::TestStream for Hang {
+    type StreamCallStream =
+        std::pin::Pin<Box<dyn Stream<Item = Result<OutputStream, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        req: Request<InputStream>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        self.peers.lock().unwrap().push(req.remote_addr().unwrap());
+        // One message, then the stream stays open without sending anything else.
+        let stream = tokio_stream::once(Ok(OutputStream {})).chain(tokio_stream::pending());
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[tokio::test]
+async fn dropped_response_streams_no_longer_count_towards_their_connection() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+    let peers = Arc::new(Mutex::new(Vec::new()));
+    let svc = Hang {
+        peers: peers.clone(),
+    };
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_stream_server::TestStreamServer::new(svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .connections(2)
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestStreamClient::new(channel);
+
+    let mut first = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    first.message().await.unwrap().unwrap();
+    let mut second = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    second.message().await.unwrap().unwrap();
+    drop(second);
+
+    // The next call goes to the connection that has no stream left, the one of the
+    // dropped call, although the server never ended that stream.
+    let mut third = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    third.message().await.unwrap().unwrap();
+
+    let peers = peers.lock().unwrap();
+    assert_ne!(peers[0], peers[1]);
+    assert_eq!(peers[2], peers[1]);
+}
@@ -0,0 +1,154 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{
+        channel::{OutlierDetection, Resolver},
+        Channel, Endpoint, Server,
+    },
+    Request, Response, Status,
+};
+
+/// Counts its calls, and fails them all if `failing` is set.
+#[derive(Clone, Default)]
+struct Svc {
+    calls: Arc<AtomicUsize>,
+    failing: bool,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.failing {
+            return Err(Status::internal("failing"));
+        }
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn run_server(svc: Svc) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(
+        Server::builder()
+            .add_service(test_server::TestServer::new(svc))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+
+    addr
+}
+
+struct StaticResolver(Vec<SocketAddr>);
+
+impl Resolver for StaticResolver {
+    type Error = Infallible;
+    type Stream = tokio_stream::Once<Result<Vec<SocketAddr>, Infallible>>;
+
+    fn resolve(&mut self, _target: &str) -> Self::Stream {
+        tokio_stream::once(Ok(self.0.clone()))
+    }
+}
+
+/// Connects to a healthy and a failing server.
+async fn connect(outlier_detection: OutlierDetection) -> (TestClient<Channel>, Svc, Svc) {
+    let good = Svc::default();
+    let bad = Svc {
+        failing: true,
+        ..Svc::default()
+    };
+    let addrs = vec![
+        run_server(good.clone()).await,
+        run_server(bad.clone()).await,
+    ];
+
+    let endpoint = Endpoint::from_static("http://my-service:50051")
+        .outlier_detection(outlier_detection.max_ejection_percent(50));
+    let channel = Channel::balance_resolver(endpoint, StaticResolver(addrs));
+
+    (TestClient::new(channel), good, bad)
+}
+
+#[tokio::test]
+async fn ejects_endpoints_after_consecutive_failures() {
+    let (mut client, good, bad) = connect(OutlierDetection::new().consecutive_failures(3)).await;
+
+    for _ in 0..20 {
+        let _ = client.unary_call(Input {}).await;
+    }
+
+    assert_eq!(bad.calls.load(Ordering::SeqCst), 3);
+    assert_eq!(good.calls.load(Ordering::SeqCst), 17);
+}
+
+#[tokio::test]
+async fn puts_endpoints_back_after_their_ejection_time() {
+    let (mut client, _good, bad) = connect(
+        OutlierDetection::new()
+            .consecutive_failures(1)
+            .base_ejection_time(Duration::from_millis(100)),
+    )
+    .await;
+
+    for _ in 0..10 {
+        let _ = client.unary_call(Input {}).await;
+    }
+    assert_eq!(bad.calls.load(Ordering::SeqCst), 1);
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    for _ in 0..4 {
+        let _ = client.unary_call(Input {}).await;
+    }
+    assert_eq!(bad.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn ejects_endpoints_with_a_high_failure_percentage() {
+    let (mut client, _good, bad) = connect(
+        OutlierDetection::new()
+            .consecutive_failures(0)
+            .failure_percentage(50, 4)
+            .interval(Duration::from_millis(100)),
+    )
+    .await;
+
+    for _ in 0..10 {
+        let _ = client.unary_call(Input {}).await;
+    }
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let _ = client.unary_call(Input {}).await;
+
+    let calls = bad.calls.load(Ordering::SeqCst);
+    for _ in 0..10 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+    assert_eq!(bad.calls.load(Ordering::SeqCst), calls);
+}
+
+#[tokio::test]
+async fn accepts_times_too_large_for_an_instant() {
+    let (mut client, good, bad) = connect(
+        OutlierDetection::new()
+            .consecutive_failures(1)
+            .interval(Duration::MAX)
+            .base_ejection_time(Duration::MAX)
+            .max_ejection_time(Duration::MAX),
+    )
+    .await;
+
+    for _ in 0..10 {
+        let _ = client.unary_call(Input {}).await;
+    }
+
+    assert_eq!(bad.calls.load(Ordering::SeqCst), 1);
+    assert_eq!(good.calls.load(Ordering::SeqCst), 9);
+}
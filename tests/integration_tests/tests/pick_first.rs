@@ -0,0 +1,126 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::sync::oneshot;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Channel, Endpoint, Error, Server},
+    Request, Response, Status,
+};
+
+#[derive(Clone, Default)]
+struct Svc {
+    calls: Arc<AtomicUsize>,
+    user_agents: Arc<Mutex<Vec<String>>>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let user_agent = req.metadata().get("user-agent").unwrap().to_str().unwrap();
+        self.user_agents.lock().unwrap().push(user_agent.to_owned());
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn run_server(svc: Svc) -> (SocketAddr, oneshot::Sender<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = oneshot::channel::<()>();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(svc))
+            .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async {
+                drop(rx.await)
+            })
+            .await
+            .unwrap();
+    });
+
+    (addr, tx)
+}
+
+async fn unused_addr() -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.local_addr().unwrap()
+}
+
+fn endpoint(addr: SocketAddr) -> Endpoint {
+    Endpoint::from_shared(format!("http://{}", addr)).unwrap()
+}
+
+#[tokio::test]
+async fn skips_unreachable_endpoints() {
+    let svc = Svc::default();
+    let (addr, _shutdown) = run_server(svc.clone()).await;
+    let endpoints = vec![endpoint(unused_addr().await), endpoint(addr)];
+
+    let mut client = TestClient::new(Channel::pick_first(endpoints.into_iter()).unwrap());
+
+    for _ in 0..5 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+
+    assert_eq!(svc.calls.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn sticks_to_the_first_endpoint_and_fails_over() {
+    let (a, b) = (Svc::default(), Svc::default());
+    let (addr_a, shutdown_a) = run_server(a.clone()).await;
+    let (addr_b, _shutdown_b) = run_server(b.clone()).await;
+    let endpoints = vec![endpoint(addr_a), endpoint(addr_b)];
+
+    let mut client = TestClient::new(Channel::pick_first(endpoints.into_iter()).unwrap());
+
+    for _ in 0..10 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+
+    assert_eq!(a.calls.load(Ordering::SeqCst), 10);
+    assert_eq!(b.calls.load(Ordering::SeqCst), 0);
+
+    shutdown_a.send(()).unwrap();
+
+    // Calls racing the shutdown may still reach `a` or fail, once the connection is gone they
+    // go to `b`.
+    let mut attempts = 0;
+    while b.calls.load(Ordering::SeqCst) == 0 {
+        attempts += 1;
+        assert!(attempts < 50, "channel did not fail over");
+
+        if client.unary_call(Input {}).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn connects_with_the_settings_of_each_endpoint() {
+    let svc = Svc::default();
+    let (addr, _shutdown) = run_server(svc.clone()).await;
+    let endpoints = vec![
+        endpoint(unused_addr().await).user_agent("primary").unwrap(),
+        endpoint(addr).user_agent("backup").unwrap(),
+    ];
+
+    let mut client = TestClient::new(Channel::pick_first(endpoints.into_iter()).unwrap());
+    client.unary_call(Input {}).await.unwrap();
+
+    let user_agents = svc.user_agents.lock().unwrap();
+    assert!(user_agents[0].starts_with("backup "), "{:?}", user_agents);
+}
+
+#[tokio::test]
+async fn fails_without_endpoints() {
+    let error: Error = Channel::pick_first(std::iter::empty()).unwrap_err();
+    assert_eq!(error.to_string(), "no endpoints to connect to");
+}
@@ -0,0 +1,132 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::sync::{Arc, Mutex};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Certificate, ClientTlsConfig, Endpoint, Identity, Server, ServerTlsConfig},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn serve() -> String {
+    serve_with(Server::builder()).await
+}
+
+async fn serve_with(mut server: Server) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    tokio::spawn(async move {
+        server
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+/// Runs a proxy that tunnels `CONNECT` requests, answering them with `status`, and returns
+/// its uri and the targets it was asked for.
+async fn proxy(status: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let uri = format!("http://{}", listener.local_addr().unwrap());
+    let targets = Arc::new(Mutex::new(Vec::new()));
+
+    let seen = targets.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut client, _) = listener.accept().await.unwrap();
+
+            let mut head = Vec::new();
+            while !head.ends_with(b"\r\n\r\n") {
+                head.push(client.read_u8().await.unwrap());
+            }
+            let head = String::from_utf8(head).unwrap();
+            let target = head.split(' ').nth(1).unwrap().to_string();
+            seen.lock().unwrap().push(target.clone());
+
+            let response = format!("HTTP/1.1 {}\r\n\r\n", status);
+            client.write_all(response.as_bytes()).await.unwrap();
+            if status.starts_with("200") {
+                let mut server = TcpStream::connect(target).await.unwrap();
+                tokio::spawn(async move {
+                    let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+                });
+            }
+        }
+    });
+
+    (uri, targets)
+}
+
+#[tokio::test]
+async fn connects_through_proxy() {
+    let addr = serve().await;
+    let (proxy, targets) = proxy("200 Connection established").await;
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .proxy(proxy.parse::<http::Uri>().unwrap())
+        .connect()
+        .await
+        .unwrap();
+    TestClient::new(channel).unary_call(Input {}).await.unwrap();
+
+    assert_eq!(*targets.lock().unwrap(), [addr]);
+}
+
+#[tokio::test]
+async fn connects_through_proxy_with_tls() {
+    let identity = Identity::from_pem(
+        include_str!("../../../examples/data/tls/server.pem"),
+        include_str!("../../../examples/data/tls/server.key"),
+    );
+    let tls = ServerTlsConfig::new().identity(identity);
+    let addr = serve_with(Server::builder().tls_config(tls).unwrap()).await;
+    let (proxy, targets) = proxy("200 Connection established").await;
+
+    let ca = Certificate::from_pem(include_str!("../../../examples/data/tls/ca.pem"));
+    let channel = Endpoint::from_shared(format!("https://{}", addr))
+        .unwrap()
+        .tls_config(ClientTlsConfig::new().ca_certificate(ca))
+        .unwrap()
+        .tls_domain_name("example.com")
+        .unwrap()
+        .proxy(proxy.parse::<http::Uri>().unwrap())
+        .connect()
+        .await
+        .unwrap();
+    TestClient::new(channel).unary_call(Input {}).await.unwrap();
+
+    assert_eq!(*targets.lock().unwrap(), [addr]);
+}
+
+#[tokio::test]
+async fn fails_when_proxy_refuses() {
+    let addr = serve().await;
+    let (proxy, _) = proxy("403 Forbidden").await;
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .proxy(proxy.parse::<http::Uri>().unwrap())
+        .connect_lazy();
+    let status = TestClient::new(channel)
+        .unary_call(Input {})
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::Unavailable);
+}
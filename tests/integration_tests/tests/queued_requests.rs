@@ -0,0 +1,129 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Channel, Endpoint, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc {
+    calls: Arc<Mutex<Vec<String>>>,
+    release: Arc<Semaphore>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        let call = req.metadata().get("call").unwrap().to_str().unwrap();
+        self.calls.lock().unwrap().push(call.into());
+        self.release.acquire().await.unwrap().forget();
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn serve(calls: Arc<Mutex<Vec<String>>>, release: Arc<Semaphore>) -> Endpoint {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc { calls, release }))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    Endpoint::from_shared(addr)
+        .unwrap()
+        .max_concurrent_streams(1)
+        .max_connections(1)
+}
+
+fn call(channel: &Channel, name: &str) -> tokio::task::JoinHandle<Result<(), Status>> {
+    call_with_timeout(channel, name, None)
+}
+
+fn call_with_timeout(
+    channel: &Channel,
+    name: &str,
+    timeout: Option<Duration>,
+) -> tokio::task::JoinHandle<Result<(), Status>> {
+    let mut client = TestClient::new(channel.clone());
+    let mut request = Request::new(Input {});
+    request.metadata_mut().insert("call", name.parse().unwrap());
+    if let Some(timeout) = timeout {
+        request.set_timeout(timeout);
+    }
+
+    tokio::spawn(async move { client.unary_call(request).await.map(|_| ()) })
+}
+
+async fn wait_for(condition: impl Fn() -> bool) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn queues_requests_in_order_until_the_queue_is_full() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let release = Arc::new(Semaphore::new(0));
+    let channel = serve(calls.clone(), release.clone())
+        .await
+        .max_queued_requests(2)
+        .connect()
+        .await
+        .unwrap();
+
+    let first = call(&channel, "first");
+    wait_for(|| calls.lock().unwrap().len() == 1).await;
+
+    let second = call(&channel, "second");
+    wait_for(|| channel.queued_requests() == 1).await;
+    let third = call(&channel, "third");
+    wait_for(|| channel.queued_requests() == 2).await;
+
+    let status = call(&channel, "fourth").await.unwrap().unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+
+    release.add_permits(3);
+    for call in [first, second, third] {
+        call.await.unwrap().unwrap();
+    }
+    assert_eq!(*calls.lock().unwrap(), ["first", "second", "third"]);
+    assert_eq!(channel.queued_requests(), 0);
+}
+
+#[tokio::test]
+async fn queued_requests_fail_at_their_deadline() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let release = Arc::new(Semaphore::new(0));
+    let channel = serve(calls.clone(), release.clone())
+        .await
+        .connect()
+        .await
+        .unwrap();
+
+    let first = call(&channel, "first");
+    wait_for(|| calls.lock().unwrap().len() == 1).await;
+
+    let started = tokio::time::Instant::now();
+    let second = call_with_timeout(&channel, "second", Some(Duration::from_millis(200)));
+    let status = second.await.unwrap().unwrap_err();
+    assert_eq!(status.code(), Code::DeadlineExceeded);
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(channel.queued_requests(), 0);
+
+    release.add_permits(1);
+    first.await.unwrap().unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(*calls.lock().unwrap(), ["first"]);
+}
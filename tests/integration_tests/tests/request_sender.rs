@@ -0,0 +1,126 @@
+use futures::{Stream, StreamExt};
+use integration_tests::pb::streaming::{
+    accumulator_client::AccumulatorClient, accumulator_server, Number,
+};
+use std::pin::Pin;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Channel, Endpoint, Server},
+    Code, Request, Response, Status, Streaming,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl accumulator_server::Accumulator for Svc {
+    async fn sum(&self, req: Request<Streaming<Number>>) -> Result<Response<Number>, Status> {
+        let mut numbers = req.into_inner();
+        let mut sum = 0;
+        while let Some(number) = numbers.next().await {
+            let value = number?.value;
+            if value < 0 {
+                return Err(Status::invalid_argument("negative number"));
+            }
+            sum += value;
+        }
+        Ok(Response::new(Number { value: sum }))
+    }
+
+    type RunningSumStream = Pin<Box<dyn Stream<Item = Result<Number, Status>> + Send>>;
+
+    async fn running_sum(
+        &self,
+        req: Request<Streaming<Number>>,
+    ) -> Result<Response<Self::RunningSumStream>, Status> {
+        let sums = req.into_inner().scan(0, |sum, number| {
+            let number = number.map(|number| {
+                *sum += number.value;
+                Number { value: *sum }
+            });
+            futures::future::ready(Some(number))
+        });
+        Ok(Response::new(Box::pin(sums)))
+    }
+}
+
+async fn client() -> AccumulatorClient<Channel> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(accumulator_server::AccumulatorServer::new(Svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    AccumulatorClient::new(channel)
+}
+
+#[tokio::test]
+async fn client_streaming_with_sender() {
+    let mut client = client().await;
+
+    let (mut sender, call) = client.sum_with_sender(1);
+    let producer = tokio::spawn(async move {
+        for value in 1..=100 {
+            sender.send(Number { value }).await.unwrap();
+        }
+    });
+
+    let res = call.await.unwrap();
+    assert_eq!(res.into_inner().value, 5050);
+    producer.await.unwrap();
+}
+
+#[tokio::test]
+async fn sender_fails_once_the_call_failed() {
+    let mut client = client().await;
+
+    let (mut sender, call) = client.sum_with_sender(1);
+    let producer = tokio::spawn(async move {
+        sender.send(Number { value: -1 }).await.unwrap();
+        // The server fails the call without reading the rest of the stream, so sends
+        // fail once it has ended.
+        loop {
+            if let Err(error) = sender.send(Number { value: 1 }).await {
+                break error;
+            }
+        }
+    });
+
+    let status = call.await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(producer.await.unwrap().0.value, 1);
+}
+
+#[tokio::test]
+async fn bidirectional_streaming_halves_in_separate_tasks() {
+    let mut client = client().await;
+
+    let (mut sender, call) = client.running_sum_with_sender(1);
+    sender.send(Number { value: 1 }).await.unwrap();
+    let mut sums = call.await.unwrap().into_inner();
+
+    let producer = tokio::spawn(async move {
+        for value in 2..=4 {
+            sender.send(Number { value }).await.unwrap();
+        }
+    });
+    let consumer = tokio::spawn(async move {
+        let mut received = Vec::new();
+        while let Some(sum) = sums.message().await.unwrap() {
+            received.push(sum.value);
+        }
+        received
+    });
+
+    producer.await.unwrap();
+    assert_eq!(consumer.await.unwrap(), vec![1, 3, 6, 10]);
+}
@@ -0,0 +1,305 @@
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    metadata::MetadataMap,
+    transport::{
+        channel::{HedgingPolicy, RetryPolicy},
+        Endpoint, Server,
+    },
+    Code, Request, Response, Status,
+};
+
+/// Fails the first `failures` calls with `code`, sending `pushback` along if it is set, after
+/// answering the first `slow` calls a second late.
+struct Svc {
+    calls: Arc<AtomicUsize>,
+    failures: usize,
+    code: Code,
+    pushback: Option<&'static str>,
+    slow: usize,
+}
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        let attempt = self.calls.fetch_add(1, Ordering::SeqCst);
+
+        let previous_attempts = req
+            .metadata()
+            .get("grpc-previous-rpc-attempts")
+            .map(|value| value.to_str().unwrap().parse().unwrap())
+            .unwrap_or(0);
+        if previous_attempts != attempt {
+            return Err(Status::internal(format!(
+                "attempt {} was sent as attempt {}",
+                attempt, previous_attempts
+            )));
+        }
+
+        if attempt < self.slow {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+
+        if attempt < self.failures {
+            let mut metadata = MetadataMap::new();
+            if let Some(pushback) = self.pushback {
+                metadata.insert("grpc-retry-pushback-ms", pushback.parse().unwrap());
+            }
+            return Err(Status::with_metadata(self.code, "try again", metadata));
+        }
+
+        Ok(Response::new(Output1 {
+            buf: req.into_inner().buf,
+        }))
+    }
+}
+
+async fn serve(svc: Svc) -> Endpoint {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    Endpoint::from_shared(addr).unwrap()
+}
+
+fn svc(failures: usize, code: Code) -> (Svc, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let svc = Svc {
+        calls: calls.clone(),
+        failures,
+        code,
+        pushback: None,
+        slow: 0,
+    };
+    (svc, calls)
+}
+
+fn policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy::new(max_attempts)
+        .initial_backoff(Duration::from_millis(10))
+        .max_backoff(Duration::from_millis(50))
+}
+
+#[tokio::test]
+async fn retries_until_success() {
+    let (svc, calls) = svc(2, Code::Unavailable);
+    let channel = serve(svc)
+        .await
+        .retry_policy(policy(3))
+        .connect()
+        .await
+        .unwrap();
+
+    let buf = vec![7; 64 * 1024];
+    let res = Test1Client::new(channel)
+        .unary_call(Input1 { buf: buf.clone() })
+        .await
+        .unwrap();
+    assert_eq!(res.into_inner().buf, buf);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let (svc, calls) = svc(5, Code::Unavailable);
+    let channel = serve(svc)
+        .await
+        .retry_policy(policy(3))
+        .connect()
+        .await
+        .unwrap();
+
+    let err = Test1Client::new(channel)
+        .unary_call(Input1 { buf: vec![] })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unavailable);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn only_retries_retryable_codes() {
+    let (svc, calls) = svc(1, Code::InvalidArgument);
+    let channel = serve(svc)
+        .await
+        .retry_policy(policy(3))
+        .connect()
+        .await
+        .unwrap();
+
+    let err = Test1Client::new(channel)
+        .unary_call(Input1 { buf: vec![] })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn method_policies_take_precedence() {
+    let (svc, calls) = svc(1, Code::Unavailable);
+    let channel = serve(svc)
+        .await
+        .retry_policy(policy(3))
+        .method_retry_policy("test1.Test1/UnaryCall", policy(1))
+        .connect()
+        .await
+        .unwrap();
+
+    let err = Test1Client::new(channel)
+        .unary_call(Input1 { buf: vec![] })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unavailable);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn honors_pushback() {
+    let (mut svc, calls) = svc(1, Code::Unavailable);
+    svc.pushback = Some("200");
+    let channel = serve(svc)
+        .await
+        .retry_policy(policy(2))
+        .connect()
+        .await
+        .unwrap();
+
+    let start = Instant::now();
+    Test1Client::new(channel)
+        .unary_call(Input1 { buf: vec![] })
+        .await
+        .unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let (mut svc, calls) = self::svc(1, Code::Unavailable);
+    svc.pushback = Some("-1");
+    let channel = serve(svc)
+        .await
+        .retry_policy(policy(2))
+        .connect()
+        .await
+        .unwrap();
+
+    Test1Client::new(channel)
+        .unary_call(Input1 { buf: vec![] })
+        .await
+        .unwrap_err();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn retries_stay_within_the_timeout() {
+    let (mut svc, calls) = svc(5, Code::Unavailable);
+    svc.pushback = Some("1000");
+    let channel = serve(svc)
+        .await
+        .retry_policy(policy(5))
+        .timeout(Duration::from_millis(100))
+        .connect()
+        .await
+        .unwrap();
+
+    let start = Instant::now();
+    let err = Test1Client::new(channel)
+        .unary_call(Input1 { buf: vec![] })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unavailable);
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn hedges_slow_calls() {
+    let (mut svc, calls) = svc(0, Code::Unavailable);
+    svc.slow = 1;
+    let channel = serve(svc)
+        .await
+        .hedging_policy(HedgingPolicy::new(3, Duration::from_millis(50)))
+        .connect()
+        .await
+        .unwrap();
+
+    let start = Instant::now();
+    let buf = vec![7; 1024];
+    let res = Test1Client::new(channel)
+        .unary_call(Input1 { buf: buf.clone() })
+        .await
+        .unwrap();
+    assert_eq!(res.into_inner().buf, buf);
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn non_fatal_failures_hedge_right_away() {
+    let (svc, calls) = svc(1, Code::Unavailable);
+    let channel = serve(svc)
+        .await
+        .hedging_policy(HedgingPolicy::new(3, Duration::from_secs(10)))
+        .connect()
+        .await
+        .unwrap();
+
+    let start = Instant::now();
+    Test1Client::new(channel)
+        .unary_call(Input1 { buf: vec![] })
+        .await
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn fatal_failures_end_hedged_calls() {
+    let (svc, calls) = svc(1, Code::InvalidArgument);
+    let channel = serve(svc)
+        .await
+        .hedging_policy(HedgingPolicy::new(3, Duration::from_millis(50)))
+        .connect()
+        .await
+        .unwrap();
+
+    let err = Test1Client::new(channel)
+        .unary_call(Input1 { buf: vec![] })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn hedged_calls_fail_with_the_last_attempt() {
+    let (svc, calls) = svc(5, Code::Unavailable);
+    let channel = serve(svc)
+        .await
+        .method_hedging_policy(
+            "test1.Test1",
+            HedgingPolicy::new(3, Duration::from_millis(10)),
+        )
+        .connect()
+        .await
+        .unwrap();
+
+    let err = Test1Client::new(channel)
+        .unary_call(Input1 { buf: vec![] })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unavailable);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
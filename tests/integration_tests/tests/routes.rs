@@ -0,0 +1,125 @@
+use integration_tests::pb::{
+    test1_client::Test1Client, test1_server, test_client::TestClient, test_server,
+    test_stream_client::TestStreamClient, Input, Input1, InputStream, Output, Output1,
+};
+use tonic::{
+    service::{interceptor, layered::method_layer, LayerExt},
+    transport::{server::MemoryIncoming, Channel, Endpoint, Server},
+    Code, GrpcMethod, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+struct Svc1;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc1 {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Ok(Response::new(Output1 {
+            buf: req.into_inner().buf,
+        }))
+    }
+}
+
+async fn channel(router: tonic::transport::server::Router) -> Channel {
+    let incoming = MemoryIncoming::new();
+    let connector = incoming.connector();
+    tokio::spawn(router.serve_with_incoming(incoming));
+
+    Endpoint::from_static("http://localhost")
+        .connect_with_connector(connector)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn routes_by_service_name() {
+    let router = Server::builder()
+        .add_service(test_server::TestServer::new(Svc))
+        .add_service(test1_server::Test1Server::new(Svc1));
+    let channel = channel(router).await;
+
+    TestClient::new(channel.clone())
+        .unary_call(Input {})
+        .await
+        .unwrap();
+
+    let res = Test1Client::new(channel.clone())
+        .unary_call(Input1 { buf: vec![1, 2, 3] })
+        .await
+        .unwrap();
+    assert_eq!(res.into_inner().buf, [1, 2, 3]);
+
+    // Services that were not added are unimplemented.
+    let status = TestStreamClient::new(channel)
+        .stream_call(InputStream {})
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
+
+#[test]
+#[should_panic]
+fn rejects_services_with_the_same_name() {
+    let _ = Server::builder()
+        .add_service(test_server::TestServer::new(Svc))
+        .add_service(test_server::TestServer::new(Svc));
+}
+
+#[tokio::test]
+async fn optional_services_that_are_none_are_unimplemented() {
+    let router = Server::builder()
+        .add_optional_service(None::<test_server::TestServer<Svc>>)
+        .add_optional_service(Some(test1_server::Test1Server::new(Svc1)));
+    let channel = channel(router).await;
+
+    let status = TestClient::new(channel.clone())
+        .unary_call(Input {})
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+    // The status is a gRPC response, not an HTTP 404 mapped by the client.
+    assert_eq!(
+        status.metadata().get("content-type").unwrap(),
+        "application/grpc"
+    );
+
+    Test1Client::new(channel)
+        .unary_call(Input1 { buf: vec![] })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn layers_apply_to_single_services_and_methods() {
+    let deny = |_: Request<()>| Err(Status::permission_denied("denied"));
+
+    let router = Server::builder()
+        .add_service(
+            method_layer(
+                |method: &GrpcMethod| method.method() == "UnaryCall",
+                interceptor(deny),
+            )
+            .named_layer(test_server::TestServer::new(Svc)),
+        )
+        .add_service(test1_server::Test1Server::new(Svc1));
+    let channel = channel(router).await;
+
+    let status = TestClient::new(channel.clone())
+        .unary_call(Input {})
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+
+    Test1Client::new(channel)
+        .unary_call(Input1 { buf: vec![] })
+        .await
+        .unwrap();
+}
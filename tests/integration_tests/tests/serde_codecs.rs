@@ -0,0 +1,113 @@
+use futures_util::future::BoxFuture;
+use http::uri::PathAndQuery;
+use serde_json::{json, Value};
+use std::{
+    convert::Infallible,
+    future::{ready, Ready},
+    marker::PhantomData,
+    task::{Context, Poll},
+};
+use tonic::{
+    body::BoxBody,
+    codec::{Codec, JsonCodec, MsgPackCodec},
+    Request, Response, Status,
+};
+use tower_service::Service;
+
+// Echoes messages back, served with `tonic::server::Grpc` directly.
+struct EchoServer<C>(PhantomData<C>);
+
+impl<C> Clone for EchoServer<C> {
+    fn clone(&self) -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C> Service<http::Request<BoxBody>> for EchoServer<C>
+where
+    C: Codec<Encode = Value, Decode = Value> + Default + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        Box::pin(async move {
+            let content_type = req.headers()[http::header::CONTENT_TYPE].clone();
+            let mut grpc = tonic::server::Grpc::new(C::default());
+            let mut res = grpc.unary(Echo, req).await;
+            res.headers_mut()
+                .insert("x-request-content-type", content_type);
+            Ok(res)
+        })
+    }
+}
+
+struct Echo;
+
+impl Service<Request<Value>> for Echo {
+    type Response = Response<Value>;
+    type Error = Status;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Value>) -> Self::Future {
+        ready(Ok(Response::new(req.into_inner())))
+    }
+}
+
+async fn round_trip<C>(msg: Value) -> Response<Value>
+where
+    C: Codec<Encode = Value, Decode = Value> + Default + Send + 'static,
+{
+    let mut client = tonic::client::Grpc::new(EchoServer::<C>(PhantomData));
+    client.ready().await.unwrap();
+
+    client
+        .unary(
+            Request::new(msg),
+            PathAndQuery::from_static("/serde.Echo/Echo"),
+            C::default(),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn json_unary_round_trip() {
+    let msg = json!({ "name": "tonic", "values": [1, 2, 3] });
+
+    let res = round_trip::<JsonCodec<Value, Value>>(msg.clone()).await;
+    assert_eq!(
+        res.metadata().get("content-type").unwrap(),
+        "application/grpc+json"
+    );
+    assert_eq!(
+        res.metadata().get("x-request-content-type").unwrap(),
+        "application/grpc+json"
+    );
+    assert_eq!(res.into_inner(), msg);
+}
+
+#[tokio::test]
+async fn msgpack_unary_round_trip() {
+    let msg = json!({ "name": "tonic", "values": [1, 2, 3] });
+
+    let res = round_trip::<MsgPackCodec<Value, Value>>(msg.clone()).await;
+    assert_eq!(
+        res.metadata().get("content-type").unwrap(),
+        "application/grpc+msgpack"
+    );
+    assert_eq!(
+        res.metadata().get("x-request-content-type").unwrap(),
+        "application/grpc+msgpack"
+    );
+    assert_eq!(res.into_inner(), msg);
+}
@@ -0,0 +1,19 @@
+use integration_tests::pb::persisted::{audit_record::Detail, AuditRecord, Outcome};
+
+#[test]
+fn generated_messages_round_trip_through_json() {
+    let record = AuditRecord {
+        user_id: "alice".into(),
+        actions: vec!["login".into()],
+        outcome: Outcome::Allowed as i32,
+        detail: Some(Detail::DurationMs(12)),
+    };
+
+    let json = serde_json::to_value(&record).unwrap();
+    assert_eq!(json["userId"], "alice");
+    assert_eq!(json["actions"][0], "login");
+    assert_eq!(json["detail"]["durationMs"], 12);
+
+    let decoded: AuditRecord = serde_json::from_value(json).unwrap();
+    assert_eq!(decoded, record);
+}
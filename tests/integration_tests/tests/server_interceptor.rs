@@ -0,0 +1,120 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    service::interceptor::AsyncInterceptedService,
+    transport::{Channel, Server},
+    Code, GrpcMethod, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        // Extensions set by the interceptor are visible to the handler.
+        let user = req.extensions().get::<User>().unwrap();
+        assert_eq!(user.0, "alice");
+
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[derive(Clone)]
+struct User(&'static str);
+
+fn authorize(mut req: Request<()>) -> Result<Request<()>, Status> {
+    let method = req.extensions().get::<GrpcMethod>().unwrap();
+    assert_eq!(method.service(), "test.Test");
+    assert_eq!(method.method(), "UnaryCall");
+
+    match req.metadata().get("authorization") {
+        Some(token) if token == "Bearer alice" => {
+            req.extensions_mut().insert(User("alice"));
+            Ok(req)
+        }
+        Some(_) => Err(Status::permission_denied("unknown user")),
+        None => Err(Status::unauthenticated("missing token")),
+    }
+}
+
+async fn authorize_async(req: Request<()>) -> Result<Request<()>, Status> {
+    // Stands in for looking the token up in a remote service.
+    tokio::task::yield_now().await;
+    authorize(req)
+}
+
+async fn client() -> TestClient<Channel> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::with_interceptor(Svc, authorize))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    connect(addr).await
+}
+
+async fn async_client() -> TestClient<Channel> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        let svc = AsyncInterceptedService::new(test_server::TestServer::new(Svc), authorize_async);
+
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    connect(addr).await
+}
+
+async fn connect(addr: String) -> TestClient<Channel> {
+    let channel = Channel::from_shared(addr).unwrap().connect().await.unwrap();
+    TestClient::new(channel)
+}
+
+#[tokio::test]
+async fn interceptor_admits_authorized_calls() {
+    let mut client = client().await;
+
+    let mut req = Request::new(Input {});
+    req.metadata_mut()
+        .insert("authorization", "Bearer alice".parse().unwrap());
+
+    client.unary_call(req).await.unwrap();
+}
+
+#[tokio::test]
+async fn interceptor_rejects_unauthorized_calls() {
+    let mut client = client().await;
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let mut req = Request::new(Input {});
+    req.metadata_mut()
+        .insert("authorization", "Bearer mallory".parse().unwrap());
+
+    let status = client.unary_call(req).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+}
+
+#[tokio::test]
+async fn async_interceptor_admits_and_rejects_calls() {
+    let mut client = async_client().await;
+
+    let mut req = Request::new(Input {});
+    req.metadata_mut()
+        .insert("authorization", "Bearer alice".parse().unwrap());
+    client.unary_call(req).await.unwrap();
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+}
@@ -0,0 +1,81 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::Notify;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+use tower::ServiceBuilder;
+use tower_http::trace::TraceLayer;
+
+#[derive(Clone, Default)]
+struct Svc {
+    release: Arc<Notify>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.release.notified().await;
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn connect(addr: String) -> TestClient<Channel> {
+    let channel = Channel::from_shared(addr).unwrap().connect().await.unwrap();
+    TestClient::new(channel)
+}
+
+#[tokio::test]
+async fn tower_timeout_is_deadline_exceeded() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .layer(
+                ServiceBuilder::new()
+                    .layer(TraceLayer::new_for_grpc())
+                    .timeout(Duration::from_millis(100)),
+            )
+            .add_service(test_server::TestServer::new(Svc::default()))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let status = connect(addr).await.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::DeadlineExceeded);
+}
+
+#[tokio::test]
+async fn tower_load_shed_is_unavailable() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    let svc = Svc::default();
+    let release = svc.release.clone();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .layer(ServiceBuilder::new().load_shed().concurrency_limit(1))
+            .add_service(test_server::TestServer::new(svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = connect(addr).await;
+
+    // The first call holds the only permit until it is released.
+    let mut first = client.clone();
+    let first = tokio::spawn(async move { first.unary_call(Input {}).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+
+    release.notify_one();
+    first.await.unwrap().unwrap();
+}
@@ -0,0 +1,178 @@
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{
+        channel::{Resolver, ServiceConfig},
+        Channel, Endpoint, Server,
+    },
+    Code, Request, Response, Status,
+};
+
+/// Echoes the request, failing the first `failures` calls and answering calls with an empty
+/// buffer a second late.
+#[derive(Clone, Default)]
+struct Svc {
+    calls: Arc<AtomicUsize>,
+    failures: usize,
+}
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+            return Err(Status::unavailable("try again"));
+        }
+
+        let buf = req.into_inner().buf;
+        if buf.is_empty() {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        Ok(Response::new(Output1 { buf }))
+    }
+}
+
+async fn serve(svc: Svc) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+async fn connect(svc: Svc, config: &str) -> Test1Client<Channel> {
+    let addr = serve(svc).await;
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .service_config(ServiceConfig::from_json(config).unwrap())
+        .connect()
+        .await
+        .unwrap();
+
+    Test1Client::new(channel)
+}
+
+#[tokio::test]
+async fn applies_method_timeouts() {
+    let mut client = connect(
+        Svc::default(),
+        r#"{ "methodConfig": [{ "name": [{ "service": "test1.Test1" }], "timeout": "0.1s" }] }"#,
+    )
+    .await;
+
+    let err = client.unary_call(Input1 { buf: vec![] }).await.unwrap_err();
+    assert_eq!(err.code(), Code::DeadlineExceeded);
+}
+
+#[tokio::test]
+async fn applies_retry_policies() {
+    let svc = Svc {
+        failures: 2,
+        ..Svc::default()
+    };
+    let calls = svc.calls.clone();
+    let mut client = connect(
+        svc,
+        r#"{
+            "methodConfig": [{
+                "name": [{ "service": "test1.Test1", "method": "UnaryCall" }],
+                "retryPolicy": {
+                    "maxAttempts": 3,
+                    "initialBackoff": "0.01s",
+                    "maxBackoff": "0.05s",
+                    "backoffMultiplier": 2,
+                    "retryableStatusCodes": ["UNAVAILABLE"]
+                }
+            }]
+        }"#,
+    )
+    .await;
+
+    client.unary_call(Input1 { buf: vec![1] }).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn applies_message_size_limits() {
+    let mut client = connect(
+        Svc::default(),
+        r#"{ "methodConfig": [{ "name": [{}], "maxRequestMessageBytes": 1024 }] }"#,
+    )
+    .await;
+
+    client
+        .unary_call(Input1 { buf: vec![1; 512] })
+        .await
+        .unwrap();
+    let err = client
+        .unary_call(Input1 { buf: vec![1; 2048] })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::OutOfRange);
+
+    let mut client = connect(
+        Svc::default(),
+        r#"{ "methodConfig": [{ "name": [{}], "maxResponseMessageBytes": 1024 }] }"#,
+    )
+    .await;
+
+    client
+        .unary_call(Input1 { buf: vec![1; 512] })
+        .await
+        .unwrap();
+    let err = client
+        .unary_call(Input1 { buf: vec![1; 2048] })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::ResourceExhausted);
+}
+
+struct ConfigResolver(Vec<SocketAddr>);
+
+impl Resolver for ConfigResolver {
+    type Error = Infallible;
+    type Stream = tokio_stream::Once<Result<Vec<SocketAddr>, Infallible>>;
+
+    fn resolve(&mut self, _target: &str) -> Self::Stream {
+        tokio_stream::once(Ok(self.0.clone()))
+    }
+
+    fn service_config(&mut self, _target: &str) -> Option<ServiceConfig> {
+        ServiceConfig::from_json(r#"{ "loadBalancingConfig": [{ "pick_first": {} }] }"#).ok()
+    }
+}
+
+#[tokio::test]
+async fn resolvers_choose_the_load_balancing_policy() {
+    let (a, b) = (Svc::default(), Svc::default());
+    let addrs = vec![serve(a.clone()).await, serve(b.clone()).await];
+
+    let endpoint = Endpoint::from_static("http://my-service:50051");
+    let channel = Channel::balance_resolver(endpoint, ConfigResolver(addrs));
+    let mut client = Test1Client::new(channel);
+
+    for _ in 0..20 {
+        client.unary_call(Input1 { buf: vec![1] }).await.unwrap();
+    }
+
+    let calls = [
+        a.calls.load(Ordering::SeqCst),
+        b.calls.load(Ordering::SeqCst),
+    ];
+    assert!(calls == [20, 0] || calls == [0, 20], "{:?}", calls);
+}
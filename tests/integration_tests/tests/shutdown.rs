@@ -0,0 +1,83 @@
+use futures_util::FutureExt;
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{oneshot, Notify};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Channel, Server},
+    Request, Response, Status,
+};
+
+#[derive(Clone, Default)]
+struct Svc {
+    received: Arc<Notify>,
+    release: Arc<Notify>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.received.notify_one();
+        self.release.notified().await;
+        Ok(Response::new(Output {}))
+    }
+}
+
+/// Serves `svc` until the returned sender is used, and makes a call that is in flight when
+/// the server is shut down.
+async fn serve_in_flight(
+    mut server: Server,
+    svc: Svc,
+) -> (
+    oneshot::Sender<()>,
+    tokio::task::JoinHandle<()>,
+    tokio::task::JoinHandle<Result<Response<Output>, Status>>,
+) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let received = svc.received.clone();
+    let server = tokio::spawn(async move {
+        server
+            .add_service(test_server::TestServer::new(svc))
+            .serve_with_incoming_shutdown(TcpListenerStream::new(listener), rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    let channel = Channel::from_shared(addr).unwrap().connect().await.unwrap();
+    let call = tokio::spawn(async move { TestClient::new(channel).unary_call(Input {}).await });
+    received.notified().await;
+
+    (tx, server, call)
+}
+
+#[tokio::test]
+async fn waits_for_in_flight_requests() {
+    let svc = Svc::default();
+    let release = svc.release.clone();
+    let (shutdown, server, call) = serve_in_flight(Server::builder(), svc).await;
+
+    shutdown.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!server.is_finished());
+
+    release.notify_one();
+    call.await.unwrap().unwrap();
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn closes_connections_after_grace_period() {
+    let server = Server::builder().shutdown_grace_period(Duration::from_millis(100));
+    let (shutdown, server, call) = serve_in_flight(server, Svc::default()).await;
+
+    shutdown.send(()).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("server should stop after the grace period")
+        .unwrap();
+
+    call.await.unwrap().unwrap_err();
+}
@@ -176,6 +176,73 @@ async fn status_from_server_stream() {
     assert_eq!(stream.message().await.unwrap(), None);
 }
 
+#[tokio::test]
+async fn status_from_server_stream_with_trailing_metadata() {
+    trace_init();
+
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_stream_server::TestStream for Svc {
+        type StreamCallStream = Stream<OutputStream>;
+
+        async fn stream_call(
+            &self,
+            _: Request<InputStream>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            let mut metadata = MetadataMap::new();
+            metadata.insert("x-request-id", "6d1f".parse().unwrap());
+            metadata.insert("x-ratelimit-remaining", "0".parse().unwrap());
+
+            let s = futures::stream::iter(vec![
+                Ok(OutputStream {}),
+                Err(Status::with_metadata(
+                    Code::ResourceExhausted,
+                    "rate limited",
+                    metadata,
+                )),
+            ]);
+            Ok(Response::new(Box::pin(s) as Self::StreamCallStream))
+        }
+    }
+
+    let svc = test_stream_server::TestStreamServer::new(Svc);
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve("127.0.0.1:1341".parse().unwrap())
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = test_stream_client::TestStreamClient::connect("http://127.0.0.1:1341")
+        .await
+        .unwrap();
+
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert!(stream.message().await.unwrap().is_some());
+
+    // The status arrives in the trailers, after the first message.
+    let status = stream.message().await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+    assert_eq!(status.message(), "rate limited");
+
+    let metadata = status.metadata();
+    assert_eq!(metadata.get("x-request-id").unwrap(), "6d1f");
+    assert_eq!(metadata.get("x-ratelimit-remaining").unwrap(), "0");
+    // Only custom metadata remains, the status itself is parsed out of it.
+    assert!(metadata.get("grpc-status").is_none());
+    assert!(metadata.get("grpc-message").is_none());
+}
+
 #[tokio::test]
 async fn status_from_server_stream_with_source() {
     trace_init();
@@ -194,6 +261,61 @@ async fn status_from_server_stream_with_source() {
     source.downcast_ref::<tonic::transport::Error>().unwrap();
 }
 
+// Responds to every call with a Trailers-Only response carrying the given `grpc-status`.
+#[derive(Clone)]
+struct TrailersOnly(&'static str);
+
+impl tower_service::Service<http::Request<tonic::body::BoxBody>> for TrailersOnly {
+    type Response = http::Response<tonic::body::BoxBody>;
+    type Error = std::convert::Infallible;
+    type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        _: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: http::Request<tonic::body::BoxBody>) -> Self::Future {
+        let response = http::Response::builder()
+            .header("content-type", "application/grpc")
+            .header("grpc-status", self.0)
+            .header("grpc-message", "trailers only")
+            .body(tonic::body::empty_body())
+            .unwrap();
+        std::future::ready(Ok(response))
+    }
+}
+
+#[tokio::test]
+async fn status_from_trailers_only_response() {
+    trace_init();
+
+    let mut client = test_client::TestClient::new(TrailersOnly("5"));
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+
+    assert_eq!(status.code(), Code::NotFound);
+    assert_eq!(status.message(), "trailers only");
+}
+
+#[tokio::test]
+async fn ok_trailers_only_response_ends_server_stream() {
+    trace_init();
+
+    let mut client = test_stream_client::TestStreamClient::new(TrailersOnly("0"));
+
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert_eq!(stream.message().await.unwrap(), None);
+    assert!(stream.trailers().await.unwrap().is_none());
+}
+
 fn trace_init() {
     let _ = tracing_subscriber::FmtSubscriber::builder()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
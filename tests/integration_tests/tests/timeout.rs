@@ -1,10 +1,20 @@
-use integration_tests::pb::{test_client, test_server, Input, Output};
-use std::{net::SocketAddr, time::Duration};
-use tokio::net::TcpListener;
-use tonic::{transport::Server, Code, Request, Response, Status};
+use futures::{stream, StreamExt};
+use integration_tests::pb::{
+    test_client, test_server, test_stream_client, test_stream_server, Input, InputStream, Output,
+    OutputStream,
+};
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{Endpoint, Server},
+    Code, Request, Response, Status,
+};
 
 #[tokio::test]
-async fn cancelation_on_timeout() {
+async fn deadline_exceeded_on_timeout() {
     let addr = run_service_in_background(Duration::from_secs(1), Duration::from_secs(100)).await;
 
     let mut client = test_client::TestClient::connect(format!("http://{}", addr))
@@ -20,7 +30,7 @@ async fn cancelation_on_timeout() {
 
     let err = res.unwrap_err();
     assert!(err.message().contains("Timeout expired"));
-    assert_eq!(err.code(), Code::Cancelled);
+    assert_eq!(err.code(), Code::DeadlineExceeded);
 }
 
 #[tokio::test]
@@ -39,7 +49,7 @@ async fn picks_server_timeout_if_thats_sorter() {
     let res = client.unary_call(req).await;
     let err = res.unwrap_err();
     assert!(err.message().contains("Timeout expired"));
-    assert_eq!(err.code(), Code::Cancelled);
+    assert_eq!(err.code(), Code::DeadlineExceeded);
 }
 
 #[tokio::test]
@@ -58,7 +68,244 @@ async fn picks_client_timeout_if_thats_sorter() {
     let res = client.unary_call(req).await;
     let err = res.unwrap_err();
     assert!(err.message().contains("Timeout expired"));
-    assert_eq!(err.code(), Code::Cancelled);
+    assert_eq!(err.code(), Code::DeadlineExceeded);
+}
+
+#[tokio::test]
+async fn deadline_is_visible_to_handler() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            let deadline = req
+                .deadline()
+                .ok_or_else(|| Status::internal("missing deadline"))?;
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() || remaining > Duration::from_secs(5) {
+                return Err(Status::internal(format!(
+                    "unexpected deadline {:?}",
+                    remaining
+                )));
+            }
+
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    let mut req = Request::new(Input {});
+    req.metadata_mut()
+        // 5 seconds
+        .insert("grpc-timeout", "5S".parse().unwrap());
+
+    client.unary_call(req).await.unwrap();
+}
+
+#[tokio::test]
+async fn server_stream_is_cut_off_at_deadline() {
+    type Stream<T> = std::pin::Pin<
+        Box<dyn futures::Stream<Item = std::result::Result<T, Status>> + Send + 'static>,
+    >;
+
+    struct Svc(std::sync::Mutex<Option<oneshot::Sender<()>>>);
+
+    #[tonic::async_trait]
+    impl test_stream_server::TestStream for Svc {
+        type StreamCallStream = Stream<OutputStream>;
+
+        async fn stream_call(
+            &self,
+            _: Request<InputStream>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            let guard = NotifyOnDrop(self.0.lock().unwrap().take());
+
+            // Sends one message and then never finishes.
+            let s = stream::once(async { Ok(OutputStream {}) }).chain(stream::pending().map(
+                move |()| {
+                    let _ = &guard;
+                    Ok(OutputStream {})
+                },
+            ));
+
+            Ok(Response::new(Box::pin(s) as Self::StreamCallStream))
+        }
+    }
+
+    let (dropped_tx, dropped_rx) = oneshot::channel();
+    let svc =
+        test_stream_server::TestStreamServer::new(Svc(std::sync::Mutex::new(Some(dropped_tx))));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_stream_client::TestStreamClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    let mut req = Request::new(InputStream {});
+    req.metadata_mut()
+        // 200 ms
+        .insert("grpc-timeout", "200m".parse().unwrap());
+
+    let mut stream = client.stream_call(req).await.unwrap().into_inner();
+
+    stream.message().await.unwrap().unwrap();
+
+    let err = stream.message().await.unwrap_err();
+    assert!(err.message().contains("Timeout expired"));
+    assert_eq!(err.code(), Code::DeadlineExceeded);
+
+    // The handler's stream is dropped once the deadline has passed.
+    tokio::time::timeout(Duration::from_secs(5), dropped_rx)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn server_timeout_aborts_handler_without_client_timeout() {
+    struct Svc(std::sync::Mutex<Option<oneshot::Sender<()>>>);
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            let _guard = NotifyOnDrop(self.0.lock().unwrap().take());
+            futures::future::pending().await
+        }
+    }
+
+    let (dropped_tx, dropped_rx) = oneshot::channel();
+    let svc = test_server::TestServer::new(Svc(std::sync::Mutex::new(Some(dropped_tx))));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .timeout(Duration::from_millis(200))
+            .add_service(svc)
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    let err = client.unary_call(Input {}).await.unwrap_err();
+    assert!(err.message().contains("Timeout expired"));
+    assert_eq!(err.code(), Code::DeadlineExceeded);
+
+    // The handler is dropped once the timeout has passed.
+    tokio::time::timeout(Duration::from_secs(5), dropped_rx)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn endpoint_timeout_is_sent_to_the_server() {
+    let addr = run_echo_timeout_service_in_background().await;
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .timeout(Duration::from_secs(5))
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    let res = client.unary_call(Request::new(Input {})).await.unwrap();
+    assert_eq!(res.metadata().get("x-grpc-timeout").unwrap(), "5000000u");
+
+    // A shorter timeout set on the request is kept.
+    let mut req = Request::new(Input {});
+    req.set_timeout(Duration::from_secs(1));
+    let res = client.unary_call(req).await.unwrap();
+    assert_eq!(res.metadata().get("x-grpc-timeout").unwrap(), "1000000u");
+
+    // A longer one is replaced by the timeout that is actually enforced.
+    let mut req = Request::new(Input {});
+    req.set_timeout(Duration::from_secs(60));
+    let res = client.unary_call(req).await.unwrap();
+    assert_eq!(res.metadata().get("x-grpc-timeout").unwrap(), "5000000u");
+}
+
+#[tokio::test]
+async fn endpoint_timeout_fails_with_deadline_exceeded() {
+    let addr = run_service_in_background(Duration::from_secs(1), Duration::from_secs(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .timeout(Duration::from_millis(100))
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    let err = client.unary_call(Request::new(Input {})).await.unwrap_err();
+    assert_eq!(err.code(), Code::DeadlineExceeded);
+}
+
+struct NotifyOnDrop(Option<oneshot::Sender<()>>);
+
+impl Drop for NotifyOnDrop {
+    fn drop(&mut self) {
+        let _ = self.0.take().unwrap().send(());
+    }
+}
+
+async fn run_echo_timeout_service_in_background() -> SocketAddr {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            let mut res = Response::new(Output {});
+            if let Some(timeout) = req.metadata().get("grpc-timeout") {
+                res.metadata_mut().insert("x-grpc-timeout", timeout.clone());
+            }
+            Ok(res)
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
 }
 
 async fn run_service_in_background(latency: Duration, server_timeout: Duration) -> SocketAddr {
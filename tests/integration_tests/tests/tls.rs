@@ -0,0 +1,235 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{
+        server::SubjectAltName, Certificate, Channel, ClientTlsConfig, Identity, Server,
+        ServerTlsConfig,
+    },
+    Request, Response, Status,
+};
+
+const CA: &str = include_str!("../../../examples/data/tls/ca.pem");
+const SERVER_CERT: &str = include_str!("../../../examples/data/tls/server.pem");
+const SERVER_KEY: &str = include_str!("../../../examples/data/tls/server.key");
+const CLIENT_CA: &str = include_str!("../data/tls/client_ca.pem");
+const CLIENT_CERT: &str = include_str!("../data/tls/client.pem");
+const CLIENT_KEY: &str = include_str!("../data/tls/client.key");
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        let certs = req.peer_certs().map_or(0, |certs| certs.len());
+
+        let uris = req
+            .peer_subject_alt_names()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|san| match san {
+                SubjectAltName::Uri(uri) => Some(uri),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut res = Response::new(Output {});
+        res.metadata_mut()
+            .insert("peer-certs", certs.to_string().parse().unwrap());
+        res.metadata_mut()
+            .insert("peer-uris", uris.join(",").parse().unwrap());
+        Ok(res)
+    }
+}
+
+async fn serve() -> String {
+    serve_with(ServerTlsConfig::new()).await
+}
+
+async fn serve_with(tls: ServerTlsConfig) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("https://{}", listener.local_addr().unwrap());
+
+    let identity = Identity::from_pem(SERVER_CERT, SERVER_KEY);
+    tokio::spawn(async move {
+        Server::builder()
+            .tls_config(tls.identity(identity))
+            .unwrap()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+/// Returns the number of client certificates the server saw.
+async fn call(addr: String, tls: ClientTlsConfig) -> Result<usize, Box<dyn std::error::Error>> {
+    let channel = Channel::from_shared(addr)
+        .unwrap()
+        .tls_config(tls)?
+        .connect()
+        .await?;
+
+    let res = TestClient::new(channel).unary_call(Input {}).await?;
+    Ok(res
+        .metadata()
+        .get("peer-certs")
+        .unwrap()
+        .to_str()?
+        .parse()?)
+}
+
+#[tokio::test]
+async fn verifies_server_with_ca_certificate() {
+    let addr = serve().await.replace("127.0.0.1", "localhost");
+    let tls = ClientTlsConfig::new().ca_certificate(Certificate::from_pem(CA));
+
+    call(addr, tls).await.unwrap();
+}
+
+#[tokio::test]
+async fn verifies_server_against_domain_name() {
+    let addr = serve().await;
+    let tls = ClientTlsConfig::new()
+        .ca_certificate(Certificate::from_pem(CA))
+        .domain_name("example.com");
+    call(addr.clone(), tls).await.unwrap();
+
+    let tls = ClientTlsConfig::new()
+        .ca_certificate(Certificate::from_pem(CA))
+        .domain_name("example.org");
+    call(addr, tls).await.unwrap_err();
+}
+
+#[tokio::test]
+async fn endpoint_sets_tls_domain_name() {
+    let addr = serve().await;
+    let tls = || ClientTlsConfig::new().ca_certificate(Certificate::from_pem(CA));
+
+    // The name applies whether it is set before or after the TLS config, and
+    // overrides the one of the config.
+    let endpoints = [
+        Channel::from_shared(addr.clone())
+            .unwrap()
+            .tls_domain_name("example.com")
+            .unwrap()
+            .tls_config(tls().domain_name("example.org"))
+            .unwrap(),
+        Channel::from_shared(addr.clone())
+            .unwrap()
+            .tls_config(tls())
+            .unwrap()
+            .tls_domain_name("example.com")
+            .unwrap(),
+    ];
+    for endpoint in endpoints {
+        let channel = endpoint.connect().await.unwrap();
+        TestClient::new(channel).unary_call(Input {}).await.unwrap();
+    }
+
+    let endpoint = Channel::from_shared(addr)
+        .unwrap()
+        .tls_config(tls())
+        .unwrap()
+        .tls_domain_name("example.org")
+        .unwrap();
+    endpoint.connect().await.unwrap_err();
+}
+
+#[tokio::test]
+async fn rejects_unknown_server_certificate() {
+    let addr = serve().await;
+
+    call(addr, ClientTlsConfig::new()).await.unwrap_err();
+}
+
+// With roots to trust, `https` channels connect without a `tls_config`.
+#[cfg(not(feature = "tls-roots"))]
+#[tokio::test]
+async fn https_requires_tls_config_without_roots() {
+    let addr = serve().await;
+
+    let err = Channel::from_shared(addr)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap_err();
+    let source = std::error::Error::source(&err).unwrap().to_string();
+    assert!(source.contains("tls_config"), "{}", source);
+}
+
+fn client_tls() -> ClientTlsConfig {
+    ClientTlsConfig::new()
+        .ca_certificate(Certificate::from_pem(CA))
+        .domain_name("localhost")
+}
+
+#[tokio::test]
+async fn presents_client_identity() {
+    let addr =
+        serve_with(ServerTlsConfig::new().client_ca_root(Certificate::from_pem(CLIENT_CA))).await;
+    let tls = client_tls().identity(Identity::from_pem(CLIENT_CERT, CLIENT_KEY));
+
+    assert_eq!(call(addr, tls).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn exposes_client_subject_alt_names() {
+    let addr =
+        serve_with(ServerTlsConfig::new().client_ca_root(Certificate::from_pem(CLIENT_CA))).await;
+    let tls = client_tls().identity(Identity::from_pem(CLIENT_CERT, CLIENT_KEY));
+
+    let channel = Channel::from_shared(addr)
+        .unwrap()
+        .tls_config(tls)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let res = TestClient::new(channel).unary_call(Input {}).await.unwrap();
+
+    assert_eq!(
+        res.metadata().get("peer-uris").unwrap(),
+        "spiffe://example.org/client"
+    );
+}
+
+#[tokio::test]
+async fn server_requires_client_identity() {
+    let addr =
+        serve_with(ServerTlsConfig::new().client_ca_root(Certificate::from_pem(CLIENT_CA))).await;
+
+    call(addr.clone(), client_tls()).await.unwrap_err();
+
+    // A certificate the client CA did not issue is rejected as well.
+    let tls = client_tls().identity(Identity::from_pem(SERVER_CERT, SERVER_KEY));
+    call(addr, tls).await.unwrap_err();
+}
+
+#[tokio::test]
+async fn server_accepts_optional_client_identity() {
+    let addr = serve_with(
+        ServerTlsConfig::new()
+            .client_ca_root(Certificate::from_pem(CLIENT_CA))
+            .client_auth_optional(true),
+    )
+    .await;
+
+    assert_eq!(call(addr.clone(), client_tls()).await.unwrap(), 0);
+
+    let tls = client_tls().identity(Identity::from_pem(CLIENT_CERT, CLIENT_KEY));
+    assert_eq!(call(addr, tls).await.unwrap(), 1);
+}
+
+#[test]
+fn server_tls_requires_identity() {
+    Server::builder()
+        .tls_config(ServerTlsConfig::new())
+        .unwrap_err();
+
+    let identity = Identity::from_pem(SERVER_CERT, "not a key");
+    Server::builder()
+        .tls_config(ServerTlsConfig::new().identity(identity))
+        .unwrap_err();
+}
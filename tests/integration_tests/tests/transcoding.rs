@@ -0,0 +1,233 @@
+use http::{Method, Request, StatusCode};
+use hyper::{Body, Client};
+use integration_tests::pb::transcoding::{
+    users_client::UsersClient, users_server, CreateUserRequest, GetUserRequest, ListUsersRequest,
+    ListUsersResponse, User, FILE_DESCRIPTOR_SET,
+};
+use serde_json::{json, Value};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Channel, Server},
+    Request as GrpcRequest, Response, Status,
+};
+use tonic_transcoding::TranscodingLayer;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl users_server::Users for Svc {
+    async fn get_user(&self, req: GrpcRequest<GetUserRequest>) -> Result<Response<User>, Status> {
+        let id = req.into_inner().id;
+        if id != 42 {
+            return Err(Status::not_found(format!("user {} not found", id)));
+        }
+
+        let mut response = Response::new(User {
+            id,
+            display_name: "Ferris".into(),
+            tags: vec!["crab".into()],
+        });
+        response
+            .metadata_mut()
+            .insert("x-served-by", "users".parse().unwrap());
+        Ok(response)
+    }
+
+    async fn list_users(
+        &self,
+        req: GrpcRequest<ListUsersRequest>,
+    ) -> Result<Response<ListUsersResponse>, Status> {
+        let req = req.into_inner();
+        let users = req
+            .tags
+            .into_iter()
+            .take(req.page_size as usize)
+            .zip(1..)
+            .map(|(tag, id)| User {
+                id,
+                display_name: String::new(),
+                tags: vec![tag],
+            })
+            .collect();
+        Ok(Response::new(ListUsersResponse { users }))
+    }
+
+    async fn create_user(
+        &self,
+        req: GrpcRequest<CreateUserRequest>,
+    ) -> Result<Response<User>, Status> {
+        let req = req.into_inner();
+        let mut user = req.user.unwrap_or_default();
+        user.id = 7;
+        user.tags.push(req.request_id);
+        Ok(Response::new(user))
+    }
+
+    async fn update_user(&self, req: GrpcRequest<User>) -> Result<Response<User>, Status> {
+        Ok(Response::new(req.into_inner()))
+    }
+}
+
+async fn serve() -> String {
+    serve_with(TranscodingLayer::new(FILE_DESCRIPTOR_SET).unwrap()).await
+}
+
+async fn serve_with(layer: TranscodingLayer) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .accept_http1(true)
+            .layer(layer)
+            .add_service(users_server::UsersServer::new(Svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+async fn call(method: Method, uri: String, body: Option<Value>) -> (StatusCode, Value) {
+    let mut req = Request::builder().method(method).uri(uri);
+    let body = match body {
+        Some(body) => {
+            req = req.header("content-type", "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let res = Client::new()
+        .request(req.body(body).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.headers()["content-type"], "application/json");
+    let status = res.status();
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn transcodes_path_query_and_body() {
+    let addr = serve().await;
+
+    let res = Client::new()
+        .get(format!("{}/v1/users/42", addr).parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["x-served-by"], "users");
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(
+        serde_json::from_slice::<Value>(&body).unwrap(),
+        json!({"id": "42", "displayName": "Ferris", "tags": ["crab"]})
+    );
+
+    assert_eq!(
+        call(
+            Method::GET,
+            format!("{}/v1/users?page_size=2&tags=a&tags=b&tags=c", addr),
+            None
+        )
+        .await,
+        (
+            StatusCode::OK,
+            json!([{"id": "1", "tags": ["a"]}, {"id": "2", "tags": ["b"]}])
+        )
+    );
+
+    assert_eq!(
+        call(
+            Method::POST,
+            format!("{}/v1/users?requestId=r1", addr),
+            Some(json!({"display_name": "Corro", "tags": ["unsafe"]}))
+        )
+        .await,
+        (
+            StatusCode::OK,
+            json!({"id": "7", "displayName": "Corro", "tags": ["unsafe", "r1"]})
+        )
+    );
+
+    // The id in the path takes precedence over the one in the body.
+    for method in [Method::PATCH, Method::PUT] {
+        assert_eq!(
+            call(
+                method,
+                format!("{}/v1/users/3", addr),
+                Some(json!({"id": "5", "displayName": "Ferris"}))
+            )
+            .await,
+            (StatusCode::OK, json!({"id": "3", "displayName": "Ferris"}))
+        );
+    }
+}
+
+#[tokio::test]
+async fn maps_statuses_to_http_errors() {
+    let addr = serve().await;
+
+    assert_eq!(
+        call(Method::GET, format!("{}/v1/users/1", addr), None).await,
+        (
+            StatusCode::NOT_FOUND,
+            json!({"code": 5, "message": "user 1 not found"})
+        )
+    );
+
+    let (status, body) = call(Method::GET, format!("{}/v1/users/abc", addr), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], 3);
+
+    let (status, body) = call(Method::GET, format!("{}/v1/users?unknown=1", addr), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["message"],
+        "unknown field `unknown` in message `transcoding.ListUsersRequest`"
+    );
+}
+
+#[tokio::test]
+async fn rejects_bodies_over_the_limit() {
+    let layer = TranscodingLayer::new(FILE_DESCRIPTOR_SET)
+        .unwrap()
+        .max_body_size(64);
+    let addr = serve_with(layer).await;
+
+    let (status, body) = call(
+        Method::POST,
+        format!("{}/v1/users?requestId=r1", addr),
+        Some(json!({"displayName": "x".repeat(64)})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["code"], 8);
+
+    let tags = "&tags=abcdefghij".repeat(8);
+    let (status, body) = call(
+        Method::GET,
+        format!("{}/v1/users?page_size=8{}", addr, tags),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        body["message"],
+        "the response is larger than the limit of 64 bytes"
+    );
+}
+
+#[tokio::test]
+async fn grpc_requests_pass_through() {
+    let addr = serve().await;
+
+    let channel = Channel::from_shared(addr).unwrap().connect().await.unwrap();
+    let user = UsersClient::new(channel)
+        .get_user(GetUserRequest { id: 42 })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(user.display_name, "Ferris");
+}
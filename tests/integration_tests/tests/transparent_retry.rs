@@ -0,0 +1,97 @@
+use bytes::Bytes;
+use integration_tests::pb::{test_client::TestClient, Input};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tonic::{
+    transport::{channel::RetryPolicy, Endpoint},
+    Code,
+};
+
+/// Serves `test.Test/UnaryCall` over plain h2, refusing the first `refusals` streams with
+/// `REFUSED_STREAM`.
+async fn serve(refusals: usize) -> (Endpoint, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+    let streams = Arc::new(AtomicUsize::new(0));
+
+    let count = streams.clone();
+    tokio::spawn(async move {
+        loop {
+            let (io, _) = listener.accept().await.unwrap();
+            let count = count.clone();
+            tokio::spawn(async move {
+                let mut conn = h2::server::handshake(io).await.unwrap();
+                while let Some(stream) = conn.accept().await {
+                    let (_, mut respond) = stream.unwrap();
+                    if count.fetch_add(1, Ordering::SeqCst) < refusals {
+                        respond.send_reset(h2::Reason::REFUSED_STREAM);
+                        continue;
+                    }
+
+                    let response = http::Response::builder()
+                        .header("content-type", "application/grpc")
+                        .body(())
+                        .unwrap();
+                    let mut send = respond.send_response(response, false).unwrap();
+                    // An empty `Output` message.
+                    send.send_data(Bytes::from_static(&[0; 5]), false).unwrap();
+                    let mut trailers = http::HeaderMap::new();
+                    trailers.insert("grpc-status", "0".parse().unwrap());
+                    send.send_trailers(trailers).unwrap();
+                }
+            });
+        }
+    });
+
+    (Endpoint::from_shared(addr).unwrap(), streams)
+}
+
+#[tokio::test]
+async fn refused_streams_are_retried() {
+    let (endpoint, streams) = serve(1).await;
+    let channel = endpoint.connect().await.unwrap();
+
+    TestClient::new(channel).unary_call(Input {}).await.unwrap();
+    assert_eq!(streams.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn calls_are_retried_transparently_once() {
+    let (endpoint, streams) = serve(2).await;
+    let channel = endpoint.connect().await.unwrap();
+
+    let err = TestClient::new(channel)
+        .unary_call(Input {})
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unavailable);
+    assert_eq!(streams.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn transparent_retries_can_be_disabled() {
+    let (endpoint, streams) = serve(1).await;
+    let channel = endpoint.transparent_retries(false).connect().await.unwrap();
+
+    let err = TestClient::new(channel)
+        .unary_call(Input {})
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unavailable);
+    assert_eq!(streams.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn transparent_retries_do_not_count_as_attempts() {
+    let (endpoint, streams) = serve(2).await;
+    let channel = endpoint
+        .retry_policy(RetryPolicy::new(2))
+        .connect()
+        .await
+        .unwrap();
+
+    TestClient::new(channel).unary_call(Input {}).await.unwrap();
+    assert_eq!(streams.load(Ordering::SeqCst), 3);
+}
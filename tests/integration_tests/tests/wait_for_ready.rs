@@ -0,0 +1,81 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+use tonic::{
+    transport::{Channel, Endpoint, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+/// Returns an address nothing listens on, and a channel to it.
+async fn unbound() -> (SocketAddr, Channel) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .initial_reconnect_backoff(Duration::from_millis(50))
+        .connect_lazy();
+    (addr, channel)
+}
+
+fn request(timeout: Duration) -> Request<Input> {
+    let mut request = Request::new(Input {});
+    request.set_wait_for_ready(true);
+    request.set_timeout(timeout);
+    request
+}
+
+#[tokio::test]
+async fn waits_for_the_server_to_start() {
+    let (addr, channel) = unbound().await;
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve(addr)
+            .await
+            .unwrap();
+    });
+
+    TestClient::new(channel)
+        .unary_call(request(Duration::from_secs(5)))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn waits_until_the_deadline() {
+    let (_, channel) = unbound().await;
+
+    let start = Instant::now();
+    let err = TestClient::new(channel)
+        .unary_call(request(Duration::from_millis(300)))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::DeadlineExceeded);
+    assert!(start.elapsed() >= Duration::from_millis(300));
+}
+
+#[tokio::test]
+async fn calls_fail_fast_by_default() {
+    let (_, channel) = unbound().await;
+
+    let err = TestClient::new(channel)
+        .unary_call(Input {})
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unavailable);
+}
@@ -170,6 +170,18 @@ fn generate_connect(service_ident: &syn::Ident, enabled: bool) -> TokenStream {
                 let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
                 Ok(Self::new(conn))
             }
+
+            /// Create a new client for a given endpoint that connects when it is first used.
+            ///
+            /// Unlike `connect` this does not fail if the endpoint is not reachable yet.
+            pub fn connect_lazy<D>(dst: D) -> Result<Self, tonic::transport::Error>
+            where
+                D: TryInto<tonic::transport::Endpoint>,
+                D::Error: Into<StdError>,
+            {
+                let conn = tonic::transport::Endpoint::new(dst)?.connect_lazy();
+                Ok(Self::new(conn))
+            }
         }
     };
 
@@ -195,6 +207,8 @@ fn generate_methods<T: Service>(
     let mut stream = TokenStream::new();
     let package = if emit_package { service.package() } else { "" };
 
+    check_sender_names(service);
+
     for method in service.methods() {
         let path = format!(
             "/{}{}{}/{}",
@@ -225,6 +239,33 @@ fn generate_methods<T: Service>(
     stream
 }
 
+/// Panics if the `_with_sender` method generated for a client streaming method has the name
+/// of another method of the service, as the client could not have both.
+fn check_sender_names<T: Service>(service: &T) {
+    let names: HashSet<_> = service
+        .methods()
+        .iter()
+        .map(|method| method.name())
+        .collect();
+
+    for method in service.methods() {
+        if !method.client_streaming() {
+            continue;
+        }
+
+        let sender_name = format!("{}_with_sender", method.name());
+        if names.contains(sender_name.as_str()) {
+            panic!(
+                "the client of service `{}` can not have both the `{}` method generated for \
+                 `{}` and a method of the same name, rename one of them",
+                service.name(),
+                sender_name,
+                method.identifier(),
+            );
+        }
+    }
+}
+
 fn generate_unary<T: Method>(
     method: &T,
     proto_path: &str,
@@ -284,6 +325,7 @@ fn generate_client_streaming<T: Method>(
 ) -> TokenStream {
     let codec_name = syn::parse_str::<syn::Path>(method.codec_path()).unwrap();
     let ident = format_ident!("{}", method.name());
+    let sender_ident = format_ident!("{}_with_sender", method.name());
 
     let (request, response) = method.request_response_name(proto_path, compile_well_known_types);
 
@@ -299,6 +341,21 @@ fn generate_client_streaming<T: Method>(
             let path = http::uri::PathAndQuery::from_static(#path);
             self.inner.client_streaming(request.into_streaming_request(), path, codec).await
         }
+
+        /// Starts the call with a request stream fed through the returned sender, which
+        /// buffers up to `buffer` messages. The request ends once the sender is dropped.
+        pub fn #sender_ident(
+            &mut self,
+            buffer: usize,
+        ) -> (
+            tonic::client::RequestSender<#request>,
+            impl std::future::Future<
+                Output = std::result::Result<tonic::Response<#response>, tonic::Status>,
+            > + '_,
+        ) {
+            let (sender, requests) = tonic::client::request_channel(buffer);
+            (sender, self.#ident(requests))
+        }
     }
 }
 
@@ -310,6 +367,7 @@ fn generate_streaming<T: Method>(
 ) -> TokenStream {
     let codec_name = syn::parse_str::<syn::Path>(method.codec_path()).unwrap();
     let ident = format_ident!("{}", method.name());
+    let sender_ident = format_ident!("{}_with_sender", method.name());
 
     let (request, response) = method.request_response_name(proto_path, compile_well_known_types);
 
@@ -325,5 +383,80 @@ fn generate_streaming<T: Method>(
             let path = http::uri::PathAndQuery::from_static(#path);
             self.inner.streaming(request.into_streaming_request(), path, codec).await
         }
+
+        /// Starts the call with a request stream fed through the returned sender, which
+        /// buffers up to `buffer` messages, and resolves to the response stream. The two
+        /// halves can be moved to separate tasks. The request ends once the sender is dropped.
+        ///
+        /// Messages sent before the returned future is polled wait in the buffer, so once it
+        /// is full, keep polling the future while sending more.
+        pub fn #sender_ident(
+            &mut self,
+            buffer: usize,
+        ) -> (
+            tonic::client::RequestSender<#request>,
+            impl std::future::Future<
+                Output = std::result::Result<
+                    tonic::Response<tonic::codec::Streaming<#response>>,
+                    tonic::Status,
+                >,
+            > + '_,
+        ) {
+            let (sender, requests) = tonic::client::request_channel(buffer);
+            (sender, self.#ident(requests))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manual;
+
+    fn method(name: &str, client_streaming: bool) -> manual::Method {
+        let builder = manual::Method::builder()
+            .name(name)
+            .route_name(name)
+            .input_type("crate::Input")
+            .output_type("crate::Output")
+            .codec_path("crate::Codec");
+        if client_streaming {
+            builder.client_streaming().build()
+        } else {
+            builder.build()
+        }
+    }
+
+    fn generate(methods: Vec<manual::Method>) -> TokenStream {
+        let service = methods
+            .into_iter()
+            .fold(
+                manual::Service::builder()
+                    .name("Accumulator")
+                    .package("test"),
+                |service, method| service.method(method),
+            )
+            .build();
+        generate_internal(
+            &service,
+            true,
+            "super",
+            false,
+            false,
+            &Attributes::default(),
+            &HashSet::default(),
+        )
+    }
+
+    #[test]
+    fn generates_sender_methods_for_client_streaming() {
+        let client = generate(vec![method("sum", true), method("count", false)]);
+        assert!(client.to_string().contains("fn sum_with_sender"));
+    }
+
+    #[test]
+    #[should_panic(expected = "can not have both the `sum_with_sender` method generated for `sum`")]
+    fn sender_methods_colliding_with_methods_fail() {
+        generate(vec![method("sum", true), method("sum_with_sender", false)]);
     }
 }
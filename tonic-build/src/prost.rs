@@ -20,8 +20,10 @@ pub fn configure() -> Builder {
         build_server: true,
         build_transport: true,
         file_descriptor_set_path: None,
+        emit_file_descriptor_set: false,
         out_dir: None,
         extern_path: Vec::new(),
+        bytes: Vec::new(),
         field_attributes: Vec::new(),
         message_attributes: Vec::new(),
         enum_attributes: Vec::new(),
@@ -35,6 +37,9 @@ pub fn configure() -> Builder {
         include_file: None,
         emit_rerun_if_changed: std::env::var_os("CARGO").is_some(),
         disable_comments: HashSet::default(),
+        codec_path: PROST_CODEC_PATH.to_string(),
+        serde_derives: Vec::new(),
+        serde_attributes: Vec::new(),
     }
 }
 
@@ -57,6 +62,8 @@ pub fn compile_protos(proto: impl AsRef<Path>) -> io::Result<()> {
 
 const PROST_CODEC_PATH: &str = "tonic::codec::ProstCodec";
 
+const SERDE_DERIVE: &str = "#[derive(serde::Serialize, serde::Deserialize)]";
+
 /// Non-path Rust types allowed for request/response types.
 const NON_PATH_TYPE_ALLOWLIST: &[&str] = &["()"];
 
@@ -140,10 +147,143 @@ impl crate::Method for Method {
     }
 }
 
+/// A service whose methods are generated with the codec and extern paths configured on the
+/// [`Builder`].
+struct TonicBuildService {
+    prost_service: Service,
+    methods: Vec<TonicBuildMethod>,
+}
+
+impl TonicBuildService {
+    fn new(prost_service: Service, builder: &Builder) -> Self {
+        let methods = prost_service
+            .methods
+            .iter()
+            .map(|prost_method| TonicBuildMethod {
+                prost_method: prost_method.clone(),
+                codec_path: builder.codec_path.clone(),
+                extern_input: is_extern_type(&prost_method.input_proto_type, &builder.extern_path),
+                extern_output: is_extern_type(
+                    &prost_method.output_proto_type,
+                    &builder.extern_path,
+                ),
+            })
+            .collect();
+
+        Self {
+            prost_service,
+            methods,
+        }
+    }
+}
+
+impl crate::Service for TonicBuildService {
+    type Method = TonicBuildMethod;
+    type Comment = String;
+
+    fn name(&self) -> &str {
+        crate::Service::name(&self.prost_service)
+    }
+
+    fn package(&self) -> &str {
+        crate::Service::package(&self.prost_service)
+    }
+
+    fn identifier(&self) -> &str {
+        crate::Service::identifier(&self.prost_service)
+    }
+
+    fn comment(&self) -> &[Self::Comment] {
+        crate::Service::comment(&self.prost_service)
+    }
+
+    fn methods(&self) -> &[Self::Method] {
+        &self.methods[..]
+    }
+}
+
+struct TonicBuildMethod {
+    prost_method: Method,
+    codec_path: String,
+    extern_input: bool,
+    extern_output: bool,
+}
+
+impl crate::Method for TonicBuildMethod {
+    type Comment = String;
+
+    fn name(&self) -> &str {
+        crate::Method::name(&self.prost_method)
+    }
+
+    fn identifier(&self) -> &str {
+        crate::Method::identifier(&self.prost_method)
+    }
+
+    fn codec_path(&self) -> &str {
+        &self.codec_path
+    }
+
+    fn client_streaming(&self) -> bool {
+        crate::Method::client_streaming(&self.prost_method)
+    }
+
+    fn server_streaming(&self) -> bool {
+        crate::Method::server_streaming(&self.prost_method)
+    }
+
+    fn comment(&self) -> &[Self::Comment] {
+        crate::Method::comment(&self.prost_method)
+    }
+
+    fn request_response_name(
+        &self,
+        proto_path: &str,
+        compile_well_known_types: bool,
+    ) -> (TokenStream, TokenStream) {
+        let (request, response) = crate::Method::request_response_name(
+            &self.prost_method,
+            proto_path,
+            compile_well_known_types,
+        );
+
+        // Extern types are already resolved to their Rust path by prost, which may be a crate
+        // path like `uuid::Uuid` that must not be made relative to `proto_path`.
+        let extern_type = |rust_type: &str| -> TokenStream {
+            syn::parse_str::<syn::Type>(rust_type)
+                .unwrap()
+                .to_token_stream()
+        };
+
+        let request = if self.extern_input {
+            extern_type(&self.prost_method.input_type)
+        } else {
+            request
+        };
+        let response = if self.extern_output {
+            extern_type(&self.prost_method.output_type)
+        } else {
+            response
+        };
+        (request, response)
+    }
+}
+
 fn is_google_type(ty: &str) -> bool {
     ty.starts_with(".google.protobuf")
 }
 
+/// Returns whether `proto_type` is, or is nested in, one of the declared extern paths.
+fn is_extern_type(proto_type: &str, extern_path: &[(String, String)]) -> bool {
+    extern_path.iter().any(|(proto_path, _)| {
+        proto_type == proto_path
+            || matches!(
+                proto_type.strip_prefix(proto_path.as_str()),
+                Some(rest) if rest.starts_with('.')
+            )
+    })
+}
+
 struct ServiceGenerator {
     builder: Builder,
     clients: TokenStream,
@@ -162,6 +302,8 @@ impl ServiceGenerator {
 
 impl prost_build::ServiceGenerator for ServiceGenerator {
     fn generate(&mut self, service: prost_build::Service, _buf: &mut String) {
+        let service = TonicBuildService::new(service, &self.builder);
+
         if self.builder.build_server {
             let server = CodeGenBuilder::new()
                 .emit_package(self.builder.emit_package)
@@ -215,6 +357,26 @@ impl prost_build::ServiceGenerator for ServiceGenerator {
             self.servers = TokenStream::default();
         }
     }
+
+    fn finalize_package(&mut self, _package: &str, buf: &mut String) {
+        if !self.builder.emit_file_descriptor_set {
+            return;
+        }
+
+        if let Some(path) = self.builder.file_descriptor_set_path.as_ref() {
+            let path = path.display().to_string();
+
+            let file_descriptor_set = quote::quote! {
+                /// The encoded `FileDescriptorSet` of the protos this package was compiled from,
+                /// ready to be registered with a gRPC reflection service.
+                pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!(#path);
+            };
+
+            let ast: syn::File = syn::parse2(file_descriptor_set).expect("not a valid tokenstream");
+            let code = prettyplease::unparse(&ast);
+            buf.push_str(&code);
+        }
+    }
 }
 
 /// Service generator builder.
@@ -224,7 +386,9 @@ pub struct Builder {
     pub(crate) build_server: bool,
     pub(crate) build_transport: bool,
     pub(crate) file_descriptor_set_path: Option<PathBuf>,
+    pub(crate) emit_file_descriptor_set: bool,
     pub(crate) extern_path: Vec<(String, String)>,
+    pub(crate) bytes: Vec<String>,
     pub(crate) field_attributes: Vec<(String, String)>,
     pub(crate) type_attributes: Vec<(String, String)>,
     pub(crate) message_attributes: Vec<(String, String)>,
@@ -238,18 +402,26 @@ pub struct Builder {
     pub(crate) include_file: Option<PathBuf>,
     pub(crate) emit_rerun_if_changed: bool,
     pub(crate) disable_comments: HashSet<String>,
+    pub(crate) codec_path: String,
+    pub(crate) serde_derives: Vec<String>,
+    pub(crate) serde_attributes: Vec<(String, String)>,
 
     out_dir: Option<PathBuf>,
 }
 
 impl Builder {
     /// Enable or disable gRPC client code generation.
+    ///
+    /// Crates that only implement servers can disable this.
     pub fn build_client(mut self, enable: bool) -> Self {
         self.build_client = enable;
         self
     }
 
     /// Enable or disable gRPC server code generation.
+    ///
+    /// Crates that only need client stubs can disable this and depend on `tonic` with just
+    /// its `channel` feature instead of `transport`.
     pub fn build_server(mut self, enable: bool) -> Self {
         self.build_server = enable;
         self
@@ -271,6 +443,18 @@ impl Builder {
         self
     }
 
+    /// Enable or disable emitting a `FILE_DESCRIPTOR_SET` constant holding the encoded
+    /// `prost_types::FileDescriptorSet` into every package that contains services.
+    ///
+    /// The set is written to the path configured with [`Builder::file_descriptor_set_path`],
+    /// or to `<first proto file name>_descriptor.bin` in the output directory if none is set.
+    ///
+    /// This defaults to `false`.
+    pub fn emit_file_descriptor_set(mut self, enable: bool) -> Self {
+        self.emit_file_descriptor_set = enable;
+        self
+    }
+
     /// Set the output directory to generate code to.
     ///
     /// Defaults to the `OUT_DIR` environment variable.
@@ -283,7 +467,8 @@ impl Builder {
     ///
     /// Passed directly to `prost_build::Config.extern_path`.
     /// Note that both the Protobuf path and the rust package paths should both be fully qualified.
-    /// i.e. Protobuf paths should start with "." and rust paths should start with "::"
+    /// i.e. Protobuf paths should start with "." and rust paths should start with "::", "crate::"
+    /// or the name of a crate the generated code's crate depends on.
     pub fn extern_path(mut self, proto_path: impl AsRef<str>, rust_path: impl AsRef<str>) -> Self {
         self.extern_path.push((
             proto_path.as_ref().to_string(),
@@ -292,6 +477,15 @@ impl Builder {
         self
     }
 
+    /// Generate `bytes::Bytes` instead of `Vec<u8>` for matched `bytes` fields.
+    ///
+    /// Passed directly to `prost_build::Config.bytes`.
+    /// Use `"."` to match every `bytes` field in the compiled protos.
+    pub fn bytes<P: AsRef<str>>(mut self, path: P) -> Self {
+        self.bytes.push(path.as_ref().to_string());
+        self
+    }
+
     /// Add additional attribute to matched messages, enums, and one-offs.
     ///
     /// Passed directly to `prost_build::Config.field_attribute`.
@@ -332,6 +526,28 @@ impl Builder {
         self
     }
 
+    /// Derive `serde::Serialize` and `serde::Deserialize` for matched messages, enums, and
+    /// one-offs.
+    ///
+    /// The crate including the generated code must depend on `serde` with its `derive`
+    /// feature enabled.
+    pub fn derive_serde<P: AsRef<str>>(mut self, path: P) -> Self {
+        self.serde_derives.push(path.as_ref().to_string());
+        self
+    }
+
+    /// Add a `#[serde(...)]` attribute to matched messages, enums, and one-offs, for
+    /// example `serde_attribute(".my_package", "rename_all = \"camelCase\"")`.
+    ///
+    /// The attribute is emitted after the derives added with [`Builder::derive_serde`].
+    pub fn serde_attribute<P: AsRef<str>, A: AsRef<str>>(mut self, path: P, attribute: A) -> Self {
+        self.serde_attributes.push((
+            path.as_ref().to_string(),
+            format!("#[serde({})]", attribute.as_ref()),
+        ));
+        self
+    }
+
     /// Add additional attribute to matched server `mod`s. Matches on the package name.
     pub fn server_mod_attribute<P: AsRef<str>, A: AsRef<str>>(
         mut self,
@@ -377,6 +593,19 @@ impl Builder {
         self
     }
 
+    /// Set the path to the [`Codec`] the generated clients and servers use to encode and
+    /// decode their messages.
+    ///
+    /// The codec must implement `Default` and be generic over the request and response
+    /// types, like the default `tonic::codec::ProstCodec`. A path starting with `crate::`
+    /// is resolved from the root of the crate that includes the generated code.
+    ///
+    /// [`Codec`]: https://docs.rs/tonic/latest/tonic/codec/trait.Codec.html
+    pub fn codec_path(mut self, codec_path: impl AsRef<str>) -> Self {
+        self.codec_path = codec_path.as_ref().to_string();
+        self
+    }
+
     /// Configure Prost `protoc_args` build arguments.
     ///
     /// Note: Enabling `--experimental_allow_proto3_optional` requires protobuf >= 3.12.
@@ -449,7 +678,7 @@ impl Builder {
     /// Compile the .proto files and execute code generation using a
     /// custom `prost_build::Config`.
     pub fn compile_with_config(
-        self,
+        mut self,
         mut config: Config,
         protos: &[impl AsRef<Path>],
         includes: &[impl AsRef<Path>],
@@ -460,6 +689,23 @@ impl Builder {
             PathBuf::from(std::env::var("OUT_DIR").unwrap())
         };
 
+        if self.emit_file_descriptor_set && self.file_descriptor_set_path.is_none() {
+            let name = protos
+                .first()
+                .and_then(|proto| proto.as_ref().file_stem())
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "file".to_string());
+            self.file_descriptor_set_path = Some(out_dir.join(format!("{}_descriptor.bin", name)));
+        }
+        if self.emit_file_descriptor_set {
+            // The generated `include_bytes!` resolves relative paths from the generated file.
+            if let Some(path) = self.file_descriptor_set_path.as_mut() {
+                if path.is_relative() {
+                    *path = std::env::current_dir()?.join(&path);
+                }
+            }
+        }
+
         config.out_dir(out_dir);
         if let Some(path) = self.file_descriptor_set_path.as_ref() {
             config.file_descriptor_set_path(path);
@@ -467,12 +713,21 @@ impl Builder {
         for (proto_path, rust_path) in self.extern_path.iter() {
             config.extern_path(proto_path, rust_path);
         }
+        if !self.bytes.is_empty() {
+            config.bytes(&self.bytes);
+        }
         for (prost_path, attr) in self.field_attributes.iter() {
             config.field_attribute(prost_path, attr);
         }
         for (prost_path, attr) in self.type_attributes.iter() {
             config.type_attribute(prost_path, attr);
         }
+        for prost_path in self.serde_derives.iter() {
+            config.type_attribute(prost_path, SERDE_DERIVE);
+        }
+        for (prost_path, attr) in self.serde_attributes.iter() {
+            config.type_attribute(prost_path, attr);
+        }
         for (prost_path, attr) in self.message_attributes.iter() {
             config.message_attribute(prost_path, attr);
         }
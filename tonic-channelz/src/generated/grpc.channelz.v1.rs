@@ -0,0 +1,1501 @@
+/// Channel is a logical grouping of channels, subchannels, and sockets.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Channel {
+    /// The identifier for this channel. This should bet set.
+    #[prost(message, optional, tag = "1")]
+    pub r#ref: ::core::option::Option<ChannelRef>,
+    /// Data specific to this channel.
+    ///
+    /// At most one of 'channel_ref+subchannel_ref' and 'socket' is set.
+    #[prost(message, optional, tag = "2")]
+    pub data: ::core::option::Option<ChannelData>,
+    /// There are no ordering guarantees on the order of channel refs.
+    /// There may not be cycles in the ref graph.
+    /// A channel ref may be present in more than one channel or subchannel.
+    #[prost(message, repeated, tag = "3")]
+    pub channel_ref: ::prost::alloc::vec::Vec<ChannelRef>,
+    /// At most one of 'channel_ref+subchannel_ref' and 'socket' is set.
+    /// There are no ordering guarantees on the order of subchannel refs.
+    /// There may not be cycles in the ref graph.
+    /// A sub channel ref may be present in more than one channel or subchannel.
+    #[prost(message, repeated, tag = "4")]
+    pub subchannel_ref: ::prost::alloc::vec::Vec<SubchannelRef>,
+    /// There are no ordering guarantees on the order of sockets.
+    #[prost(message, repeated, tag = "5")]
+    pub socket_ref: ::prost::alloc::vec::Vec<SocketRef>,
+}
+/// Subchannel is a logical grouping of channels, subchannels, and sockets.
+/// A subchannel is load balanced over by it's ancestor
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Subchannel {
+    /// The identifier for this channel.
+    #[prost(message, optional, tag = "1")]
+    pub r#ref: ::core::option::Option<SubchannelRef>,
+    /// Data specific to this channel.
+    ///
+    /// At most one of 'channel_ref+subchannel_ref' and 'socket' is set.
+    #[prost(message, optional, tag = "2")]
+    pub data: ::core::option::Option<ChannelData>,
+    /// There are no ordering guarantees on the order of channel refs.
+    /// There may not be cycles in the ref graph.
+    /// A channel ref may be present in more than one channel or subchannel.
+    #[prost(message, repeated, tag = "3")]
+    pub channel_ref: ::prost::alloc::vec::Vec<ChannelRef>,
+    /// At most one of 'channel_ref+subchannel_ref' and 'socket' is set.
+    /// There are no ordering guarantees on the order of subchannel refs.
+    /// There may not be cycles in the ref graph.
+    /// A sub channel ref may be present in more than one channel or subchannel.
+    #[prost(message, repeated, tag = "4")]
+    pub subchannel_ref: ::prost::alloc::vec::Vec<SubchannelRef>,
+    /// There are no ordering guarantees on the order of sockets.
+    #[prost(message, repeated, tag = "5")]
+    pub socket_ref: ::prost::alloc::vec::Vec<SocketRef>,
+}
+/// These come from the specified states in this document:
+/// <https://github.com/grpc/grpc/blob/master/doc/connectivity-semantics-and-api.md>
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelConnectivityState {
+    #[prost(enumeration = "channel_connectivity_state::State", tag = "1")]
+    pub state: i32,
+}
+/// Nested message and enum types in `ChannelConnectivityState`.
+pub mod channel_connectivity_state {
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
+    )]
+    #[repr(i32)]
+    pub enum State {
+        Unknown = 0,
+        Idle = 1,
+        Connecting = 2,
+        Ready = 3,
+        TransientFailure = 4,
+        Shutdown = 5,
+    }
+    impl State {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                State::Unknown => "UNKNOWN",
+                State::Idle => "IDLE",
+                State::Connecting => "CONNECTING",
+                State::Ready => "READY",
+                State::TransientFailure => "TRANSIENT_FAILURE",
+                State::Shutdown => "SHUTDOWN",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "UNKNOWN" => Some(Self::Unknown),
+                "IDLE" => Some(Self::Idle),
+                "CONNECTING" => Some(Self::Connecting),
+                "READY" => Some(Self::Ready),
+                "TRANSIENT_FAILURE" => Some(Self::TransientFailure),
+                "SHUTDOWN" => Some(Self::Shutdown),
+                _ => None,
+            }
+        }
+    }
+}
+/// Channel data is data related to a specific Channel or Subchannel.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelData {
+    /// The connectivity state of the channel or subchannel.  Implementations
+    /// should always set this.
+    #[prost(message, optional, tag = "1")]
+    pub state: ::core::option::Option<ChannelConnectivityState>,
+    /// The target this channel originally tried to connect to.  May be absent
+    #[prost(string, tag = "2")]
+    pub target: ::prost::alloc::string::String,
+    /// A trace of recent events on the channel.  May be absent.
+    #[prost(message, optional, tag = "3")]
+    pub trace: ::core::option::Option<ChannelTrace>,
+    /// The number of calls started on the channel
+    #[prost(int64, tag = "4")]
+    pub calls_started: i64,
+    /// The number of calls that have completed with an OK status
+    #[prost(int64, tag = "5")]
+    pub calls_succeeded: i64,
+    /// The number of calls that have completed with a non-OK status
+    #[prost(int64, tag = "6")]
+    pub calls_failed: i64,
+    /// The last time a call was started on the channel.
+    #[prost(message, optional, tag = "7")]
+    pub last_call_started_timestamp: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// A trace event is an interesting thing that happened to a channel or
+/// subchannel, such as creation, address resolution, subchannel creation, etc.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelTraceEvent {
+    /// High level description of the event.
+    #[prost(string, tag = "1")]
+    pub description: ::prost::alloc::string::String,
+    /// the severity of the trace event
+    #[prost(enumeration = "channel_trace_event::Severity", tag = "2")]
+    pub severity: i32,
+    /// When this event occurred.
+    #[prost(message, optional, tag = "3")]
+    pub timestamp: ::core::option::Option<::prost_types::Timestamp>,
+    /// ref of referenced channel or subchannel.
+    /// Optional, only present if this event refers to a child object. For example,
+    /// this field would be filled if this trace event was for a subchannel being
+    /// created.
+    #[prost(oneof = "channel_trace_event::ChildRef", tags = "4, 5")]
+    pub child_ref: ::core::option::Option<channel_trace_event::ChildRef>,
+}
+/// Nested message and enum types in `ChannelTraceEvent`.
+pub mod channel_trace_event {
+    /// The supported severity levels of trace events.
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
+    )]
+    #[repr(i32)]
+    pub enum Severity {
+        CtUnknown = 0,
+        CtInfo = 1,
+        CtWarning = 2,
+        CtError = 3,
+    }
+    impl Severity {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                Severity::CtUnknown => "CT_UNKNOWN",
+                Severity::CtInfo => "CT_INFO",
+                Severity::CtWarning => "CT_WARNING",
+                Severity::CtError => "CT_ERROR",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "CT_UNKNOWN" => Some(Self::CtUnknown),
+                "CT_INFO" => Some(Self::CtInfo),
+                "CT_WARNING" => Some(Self::CtWarning),
+                "CT_ERROR" => Some(Self::CtError),
+                _ => None,
+            }
+        }
+    }
+    /// ref of referenced channel or subchannel.
+    /// Optional, only present if this event refers to a child object. For example,
+    /// this field would be filled if this trace event was for a subchannel being
+    /// created.
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum ChildRef {
+        #[prost(message, tag = "4")]
+        ChannelRef(super::ChannelRef),
+        #[prost(message, tag = "5")]
+        SubchannelRef(super::SubchannelRef),
+    }
+}
+/// ChannelTrace represents the recent events that have occurred on the channel.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelTrace {
+    /// Number of events ever logged in this tracing object. This can differ from
+    /// events.size() because events can be overwritten or garbage collected by
+    /// implementations.
+    #[prost(int64, tag = "1")]
+    pub num_events_logged: i64,
+    /// Time that this channel was created.
+    #[prost(message, optional, tag = "2")]
+    pub creation_timestamp: ::core::option::Option<::prost_types::Timestamp>,
+    /// List of events that have occurred on this channel.
+    #[prost(message, repeated, tag = "3")]
+    pub events: ::prost::alloc::vec::Vec<ChannelTraceEvent>,
+}
+/// ChannelRef is a reference to a Channel.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelRef {
+    /// The globally unique id for this channel.  Must be a positive number.
+    #[prost(int64, tag = "1")]
+    pub channel_id: i64,
+    /// An optional name associated with the channel.
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+}
+/// SubchannelRef is a reference to a Subchannel.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubchannelRef {
+    /// The globally unique id for this subchannel.  Must be a positive number.
+    #[prost(int64, tag = "7")]
+    pub subchannel_id: i64,
+    /// An optional name associated with the subchannel.
+    #[prost(string, tag = "8")]
+    pub name: ::prost::alloc::string::String,
+}
+/// SocketRef is a reference to a Socket.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SocketRef {
+    /// The globally unique id for this socket.  Must be a positive number.
+    #[prost(int64, tag = "3")]
+    pub socket_id: i64,
+    /// An optional name associated with the socket.
+    #[prost(string, tag = "4")]
+    pub name: ::prost::alloc::string::String,
+}
+/// ServerRef is a reference to a Server.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ServerRef {
+    /// A globally unique identifier for this server.  Must be a positive number.
+    #[prost(int64, tag = "5")]
+    pub server_id: i64,
+    /// An optional name associated with the server.
+    #[prost(string, tag = "6")]
+    pub name: ::prost::alloc::string::String,
+}
+/// Server represents a single server.  There may be multiple servers in a single
+/// program.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Server {
+    /// The identifier for a Server.  This should be set.
+    #[prost(message, optional, tag = "1")]
+    pub r#ref: ::core::option::Option<ServerRef>,
+    /// The associated data of the Server.
+    #[prost(message, optional, tag = "2")]
+    pub data: ::core::option::Option<ServerData>,
+    /// The sockets that the server is listening on.  There are no ordering
+    /// guarantees.  This may be absent.
+    #[prost(message, repeated, tag = "3")]
+    pub listen_socket: ::prost::alloc::vec::Vec<SocketRef>,
+}
+/// ServerData is data for a specific Server.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ServerData {
+    /// A trace of recent events on the server.  May be absent.
+    #[prost(message, optional, tag = "1")]
+    pub trace: ::core::option::Option<ChannelTrace>,
+    /// The number of incoming calls started on the server
+    #[prost(int64, tag = "2")]
+    pub calls_started: i64,
+    /// The number of incoming calls that have completed with an OK status
+    #[prost(int64, tag = "3")]
+    pub calls_succeeded: i64,
+    /// The number of incoming calls that have a completed with a non-OK status
+    #[prost(int64, tag = "4")]
+    pub calls_failed: i64,
+    /// The last time a call was started on the server.
+    #[prost(message, optional, tag = "5")]
+    pub last_call_started_timestamp: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// Information about an actual connection.  Pronounced "sock-ay".
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Socket {
+    /// The identifier for the Socket.
+    #[prost(message, optional, tag = "1")]
+    pub r#ref: ::core::option::Option<SocketRef>,
+    /// Data specific to this Socket.
+    #[prost(message, optional, tag = "2")]
+    pub data: ::core::option::Option<SocketData>,
+    /// The locally bound address.
+    #[prost(message, optional, tag = "3")]
+    pub local: ::core::option::Option<Address>,
+    /// The remote bound address.  May be absent.
+    #[prost(message, optional, tag = "4")]
+    pub remote: ::core::option::Option<Address>,
+    /// Security details for this socket.  May be absent if not available, or
+    /// there is no security on the socket.
+    #[prost(message, optional, tag = "5")]
+    pub security: ::core::option::Option<Security>,
+    /// Optional, represents the name of the remote endpoint, if different than
+    /// the original target name.
+    #[prost(string, tag = "6")]
+    pub remote_name: ::prost::alloc::string::String,
+}
+/// SocketData is data associated for a specific Socket.  The fields present
+/// are specific to the implementation, so there may be minor differences in
+/// the semantics.  (e.g. flow control windows)
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SocketData {
+    /// The number of streams that have been started.
+    #[prost(int64, tag = "1")]
+    pub streams_started: i64,
+    /// The number of streams that have ended successfully:
+    /// On client side, received frame with eos bit set;
+    /// On server side, sent frame with eos bit set.
+    #[prost(int64, tag = "2")]
+    pub streams_succeeded: i64,
+    /// The number of streams that have ended unsuccessfully:
+    /// On client side, ended without receiving frame with eos bit set;
+    /// On server side, ended without sending frame with eos bit set.
+    #[prost(int64, tag = "3")]
+    pub streams_failed: i64,
+    /// The number of grpc messages successfully sent on this socket.
+    #[prost(int64, tag = "4")]
+    pub messages_sent: i64,
+    /// The number of grpc messages received on this socket.
+    #[prost(int64, tag = "5")]
+    pub messages_received: i64,
+    /// The number of keep alives sent.  This is typically implemented with HTTP/2
+    /// ping messages.
+    #[prost(int64, tag = "6")]
+    pub keep_alives_sent: i64,
+    /// The last time a stream was created by this endpoint.  Usually unset for
+    /// servers.
+    #[prost(message, optional, tag = "7")]
+    pub last_local_stream_created_timestamp: ::core::option::Option<
+        ::prost_types::Timestamp,
+    >,
+    /// The last time a stream was created by the remote endpoint.  Usually unset
+    /// for clients.
+    #[prost(message, optional, tag = "8")]
+    pub last_remote_stream_created_timestamp: ::core::option::Option<
+        ::prost_types::Timestamp,
+    >,
+    /// The last time a message was sent by this endpoint.
+    #[prost(message, optional, tag = "9")]
+    pub last_message_sent_timestamp: ::core::option::Option<::prost_types::Timestamp>,
+    /// The last time a message was received by this endpoint.
+    #[prost(message, optional, tag = "10")]
+    pub last_message_received_timestamp: ::core::option::Option<
+        ::prost_types::Timestamp,
+    >,
+    /// The amount of window, granted to the local endpoint by the remote endpoint.
+    /// This may be slightly out of date due to network latency.  This does NOT
+    /// include stream level or TCP level flow control info.
+    #[prost(message, optional, tag = "11")]
+    pub local_flow_control_window: ::core::option::Option<i64>,
+    /// The amount of window, granted to the remote endpoint by the local endpoint.
+    /// This may be slightly out of date due to network latency.  This does NOT
+    /// include stream level or TCP level flow control info.
+    #[prost(message, optional, tag = "12")]
+    pub remote_flow_control_window: ::core::option::Option<i64>,
+    /// Socket options set on this socket.  May be absent if 'summary' is set
+    /// on GetSocketRequest.
+    #[prost(message, repeated, tag = "13")]
+    pub option: ::prost::alloc::vec::Vec<SocketOption>,
+}
+/// Address represents the address used to create the socket.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Address {
+    #[prost(oneof = "address::Address", tags = "1, 2, 3")]
+    pub address: ::core::option::Option<address::Address>,
+}
+/// Nested message and enum types in `Address`.
+pub mod address {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct TcpIpAddress {
+        /// Either the IPv4 or IPv6 address in bytes.  Will be either 4 bytes or 16
+        /// bytes in length.
+        #[prost(bytes = "vec", tag = "1")]
+        pub ip_address: ::prost::alloc::vec::Vec<u8>,
+        /// 0-64k, or -1 if not appropriate.
+        #[prost(int32, tag = "2")]
+        pub port: i32,
+    }
+    /// A Unix Domain Socket address.
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct UdsAddress {
+        #[prost(string, tag = "1")]
+        pub filename: ::prost::alloc::string::String,
+    }
+    /// An address type not included above.
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct OtherAddress {
+        /// The human readable version of the value.  This value should be set.
+        #[prost(string, tag = "1")]
+        pub name: ::prost::alloc::string::String,
+        /// The actual address message.
+        #[prost(message, optional, tag = "2")]
+        pub value: ::core::option::Option<::prost_types::Any>,
+    }
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Address {
+        #[prost(message, tag = "1")]
+        TcpipAddress(TcpIpAddress),
+        #[prost(message, tag = "2")]
+        UdsAddress(UdsAddress),
+        #[prost(message, tag = "3")]
+        OtherAddress(OtherAddress),
+    }
+}
+/// Security represents details about how secure the socket is.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Security {
+    #[prost(oneof = "security::Model", tags = "1, 2")]
+    pub model: ::core::option::Option<security::Model>,
+}
+/// Nested message and enum types in `Security`.
+pub mod security {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Tls {
+        /// the certificate used by this endpoint.
+        #[prost(bytes = "vec", tag = "3")]
+        pub local_certificate: ::prost::alloc::vec::Vec<u8>,
+        /// the certificate used by the remote endpoint.
+        #[prost(bytes = "vec", tag = "4")]
+        pub remote_certificate: ::prost::alloc::vec::Vec<u8>,
+        #[prost(oneof = "tls::CipherSuite", tags = "1, 2")]
+        pub cipher_suite: ::core::option::Option<tls::CipherSuite>,
+    }
+    /// Nested message and enum types in `Tls`.
+    pub mod tls {
+        #[allow(clippy::derive_partial_eq_without_eq)]
+        #[derive(Clone, PartialEq, ::prost::Oneof)]
+        pub enum CipherSuite {
+            /// The cipher suite name in the RFC 4346 format:
+            /// <https://tools.ietf.org/html/rfc4346#appendix-C>
+            #[prost(string, tag = "1")]
+            StandardName(::prost::alloc::string::String),
+            /// Some other way to describe the cipher suite if
+            /// the RFC 4346 name is not available.
+            #[prost(string, tag = "2")]
+            OtherName(::prost::alloc::string::String),
+        }
+    }
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct OtherSecurity {
+        /// The human readable version of the value.
+        #[prost(string, tag = "1")]
+        pub name: ::prost::alloc::string::String,
+        /// The actual security details message.
+        #[prost(message, optional, tag = "2")]
+        pub value: ::core::option::Option<::prost_types::Any>,
+    }
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Model {
+        #[prost(message, tag = "1")]
+        Tls(Tls),
+        #[prost(message, tag = "2")]
+        Other(OtherSecurity),
+    }
+}
+/// SocketOption represents socket options for a socket.  Specifically, these
+/// are the options returned by getsockopt().
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SocketOption {
+    /// The full name of the socket option.  Typically this will be the upper case
+    /// name, such as "SO_REUSEPORT".
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// The human readable value of this socket option.  At least one of value or
+    /// additional will be set.
+    #[prost(string, tag = "2")]
+    pub value: ::prost::alloc::string::String,
+    /// Additional data associated with the socket option.  At least one of value
+    /// or additional will be set.
+    #[prost(message, optional, tag = "3")]
+    pub additional: ::core::option::Option<::prost_types::Any>,
+}
+/// For use with SocketOption's additional field.  This is primarily used for
+/// SO_RCVTIMEO and SO_SNDTIMEO
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SocketOptionTimeout {
+    #[prost(message, optional, tag = "1")]
+    pub duration: ::core::option::Option<::prost_types::Duration>,
+}
+/// For use with SocketOption's additional field.  This is primarily used for
+/// SO_LINGER.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SocketOptionLinger {
+    /// active maps to `struct linger.l_onoff`
+    #[prost(bool, tag = "1")]
+    pub active: bool,
+    /// duration maps to `struct linger.l_linger`
+    #[prost(message, optional, tag = "2")]
+    pub duration: ::core::option::Option<::prost_types::Duration>,
+}
+/// For use with SocketOption's additional field.  Tcp info for
+/// SOL_TCP and TCP_INFO.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SocketOptionTcpInfo {
+    #[prost(uint32, tag = "1")]
+    pub tcpi_state: u32,
+    #[prost(uint32, tag = "2")]
+    pub tcpi_ca_state: u32,
+    #[prost(uint32, tag = "3")]
+    pub tcpi_retransmits: u32,
+    #[prost(uint32, tag = "4")]
+    pub tcpi_probes: u32,
+    #[prost(uint32, tag = "5")]
+    pub tcpi_backoff: u32,
+    #[prost(uint32, tag = "6")]
+    pub tcpi_options: u32,
+    #[prost(uint32, tag = "7")]
+    pub tcpi_snd_wscale: u32,
+    #[prost(uint32, tag = "8")]
+    pub tcpi_rcv_wscale: u32,
+    #[prost(uint32, tag = "9")]
+    pub tcpi_rto: u32,
+    #[prost(uint32, tag = "10")]
+    pub tcpi_ato: u32,
+    #[prost(uint32, tag = "11")]
+    pub tcpi_snd_mss: u32,
+    #[prost(uint32, tag = "12")]
+    pub tcpi_rcv_mss: u32,
+    #[prost(uint32, tag = "13")]
+    pub tcpi_unacked: u32,
+    #[prost(uint32, tag = "14")]
+    pub tcpi_sacked: u32,
+    #[prost(uint32, tag = "15")]
+    pub tcpi_lost: u32,
+    #[prost(uint32, tag = "16")]
+    pub tcpi_retrans: u32,
+    #[prost(uint32, tag = "17")]
+    pub tcpi_fackets: u32,
+    #[prost(uint32, tag = "18")]
+    pub tcpi_last_data_sent: u32,
+    #[prost(uint32, tag = "19")]
+    pub tcpi_last_ack_sent: u32,
+    #[prost(uint32, tag = "20")]
+    pub tcpi_last_data_recv: u32,
+    #[prost(uint32, tag = "21")]
+    pub tcpi_last_ack_recv: u32,
+    #[prost(uint32, tag = "22")]
+    pub tcpi_pmtu: u32,
+    #[prost(uint32, tag = "23")]
+    pub tcpi_rcv_ssthresh: u32,
+    #[prost(uint32, tag = "24")]
+    pub tcpi_rtt: u32,
+    #[prost(uint32, tag = "25")]
+    pub tcpi_rttvar: u32,
+    #[prost(uint32, tag = "26")]
+    pub tcpi_snd_ssthresh: u32,
+    #[prost(uint32, tag = "27")]
+    pub tcpi_snd_cwnd: u32,
+    #[prost(uint32, tag = "28")]
+    pub tcpi_advmss: u32,
+    #[prost(uint32, tag = "29")]
+    pub tcpi_reordering: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetTopChannelsRequest {
+    /// start_channel_id indicates that only channels at or above this id should be
+    /// included in the results.
+    /// To request the first page, this should be set to 0. To request
+    /// subsequent pages, the client generates this value by adding 1 to
+    /// the highest seen result ID.
+    #[prost(int64, tag = "1")]
+    pub start_channel_id: i64,
+    /// If non-zero, the server will return a page of results containing
+    /// at most this many items. If zero, the server will choose a
+    /// reasonable page size.  Must never be negative.
+    #[prost(int64, tag = "2")]
+    pub max_results: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetTopChannelsResponse {
+    /// list of channels that the connection detail service knows about.  Sorted in
+    /// ascending channel_id order.
+    /// Must contain at least 1 result, otherwise 'end' must be true.
+    #[prost(message, repeated, tag = "1")]
+    pub channel: ::prost::alloc::vec::Vec<Channel>,
+    /// If set, indicates that the list of channels is the final list.  Requesting
+    /// more channels can only return more if they are created after this RPC
+    /// completes.
+    #[prost(bool, tag = "2")]
+    pub end: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetServersRequest {
+    /// start_server_id indicates that only servers at or above this id should be
+    /// included in the results.
+    /// To request the first page, this must be set to 0. To request
+    /// subsequent pages, the client generates this value by adding 1 to
+    /// the highest seen result ID.
+    #[prost(int64, tag = "1")]
+    pub start_server_id: i64,
+    /// If non-zero, the server will return a page of results containing
+    /// at most this many items. If zero, the server will choose a
+    /// reasonable page size.  Must never be negative.
+    #[prost(int64, tag = "2")]
+    pub max_results: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetServersResponse {
+    /// list of servers that the connection detail service knows about.  Sorted in
+    /// ascending server_id order.
+    /// Must contain at least 1 result, otherwise 'end' must be true.
+    #[prost(message, repeated, tag = "1")]
+    pub server: ::prost::alloc::vec::Vec<Server>,
+    /// If set, indicates that the list of servers is the final list.  Requesting
+    /// more servers will only return more if they are created after this RPC
+    /// completes.
+    #[prost(bool, tag = "2")]
+    pub end: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetServerRequest {
+    /// server_id is the identifier of the specific server to get.
+    #[prost(int64, tag = "1")]
+    pub server_id: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetServerResponse {
+    /// The Server that corresponds to the requested server_id.  This field
+    /// should be set.
+    #[prost(message, optional, tag = "1")]
+    pub server: ::core::option::Option<Server>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetServerSocketsRequest {
+    #[prost(int64, tag = "1")]
+    pub server_id: i64,
+    /// start_socket_id indicates that only sockets at or above this id should be
+    /// included in the results.
+    /// To request the first page, this must be set to 0. To request
+    /// subsequent pages, the client generates this value by adding 1 to
+    /// the highest seen result ID.
+    #[prost(int64, tag = "2")]
+    pub start_socket_id: i64,
+    /// If non-zero, the server will return a page of results containing
+    /// at most this many items. If zero, the server will choose a
+    /// reasonable page size.  Must never be negative.
+    #[prost(int64, tag = "3")]
+    pub max_results: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetServerSocketsResponse {
+    /// list of socket refs that the connection detail service knows about.  Sorted in
+    /// ascending socket_id order.
+    /// Must contain at least 1 result, otherwise 'end' must be true.
+    #[prost(message, repeated, tag = "1")]
+    pub socket_ref: ::prost::alloc::vec::Vec<SocketRef>,
+    /// If set, indicates that the list of sockets is the final list.  Requesting
+    /// more sockets will only return more if they are created after this RPC
+    /// completes.
+    #[prost(bool, tag = "2")]
+    pub end: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetChannelRequest {
+    /// channel_id is the identifier of the specific channel to get.
+    #[prost(int64, tag = "1")]
+    pub channel_id: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetChannelResponse {
+    /// The Channel that corresponds to the requested channel_id.  This field
+    /// should be set.
+    #[prost(message, optional, tag = "1")]
+    pub channel: ::core::option::Option<Channel>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSubchannelRequest {
+    /// subchannel_id is the identifier of the specific subchannel to get.
+    #[prost(int64, tag = "1")]
+    pub subchannel_id: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSubchannelResponse {
+    /// The Subchannel that corresponds to the requested subchannel_id.  This
+    /// field should be set.
+    #[prost(message, optional, tag = "1")]
+    pub subchannel: ::core::option::Option<Subchannel>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSocketRequest {
+    /// socket_id is the identifier of the specific socket to get.
+    #[prost(int64, tag = "1")]
+    pub socket_id: i64,
+    /// If true, the response will contain only high level information
+    /// that is inexpensive to obtain. Fields thay may be omitted are
+    /// documented.
+    #[prost(bool, tag = "2")]
+    pub summary: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSocketResponse {
+    /// The Socket that corresponds to the requested socket_id.  This field
+    /// should be set.
+    #[prost(message, optional, tag = "1")]
+    pub socket: ::core::option::Option<Socket>,
+}
+/// Generated client implementations.
+pub mod channelz_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    /// Channelz is a service exposed by gRPC servers that provides detailed debug
+    /// information.
+    #[derive(Debug, Clone)]
+    pub struct ChannelzClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl<T> ChannelzClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> ChannelzClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + Send + Sync,
+        {
+            ChannelzClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        /// Gets all root channels (i.e. channels the application has directly
+        /// created). This does not include subchannels nor non-top level channels.
+        pub async fn get_top_channels(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetTopChannelsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetTopChannelsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.channelz.v1.Channelz/GetTopChannels",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Gets all servers that exist in the process.
+        pub async fn get_servers(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetServersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetServersResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.channelz.v1.Channelz/GetServers",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Returns a single Server, or else a NOT_FOUND code.
+        pub async fn get_server(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetServerRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetServerResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.channelz.v1.Channelz/GetServer",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Gets all server sockets that exist in the process.
+        pub async fn get_server_sockets(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetServerSocketsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetServerSocketsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.channelz.v1.Channelz/GetServerSockets",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Returns a single Channel, or else a NOT_FOUND code.
+        pub async fn get_channel(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetChannelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetChannelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.channelz.v1.Channelz/GetChannel",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Returns a single Subchannel, or else a NOT_FOUND code.
+        pub async fn get_subchannel(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetSubchannelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSubchannelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.channelz.v1.Channelz/GetSubchannel",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Returns a single Socket or else a NOT_FOUND code.
+        pub async fn get_socket(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetSocketRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSocketResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.channelz.v1.Channelz/GetSocket",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod channelz_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with ChannelzServer.
+    #[async_trait]
+    pub trait Channelz: Send + Sync + 'static {
+        /// Gets all root channels (i.e. channels the application has directly
+        /// created). This does not include subchannels nor non-top level channels.
+        async fn get_top_channels(
+            &self,
+            request: tonic::Request<super::GetTopChannelsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetTopChannelsResponse>,
+            tonic::Status,
+        >;
+        /// Gets all servers that exist in the process.
+        async fn get_servers(
+            &self,
+            request: tonic::Request<super::GetServersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetServersResponse>,
+            tonic::Status,
+        >;
+        /// Returns a single Server, or else a NOT_FOUND code.
+        async fn get_server(
+            &self,
+            request: tonic::Request<super::GetServerRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetServerResponse>,
+            tonic::Status,
+        >;
+        /// Gets all server sockets that exist in the process.
+        async fn get_server_sockets(
+            &self,
+            request: tonic::Request<super::GetServerSocketsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetServerSocketsResponse>,
+            tonic::Status,
+        >;
+        /// Returns a single Channel, or else a NOT_FOUND code.
+        async fn get_channel(
+            &self,
+            request: tonic::Request<super::GetChannelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetChannelResponse>,
+            tonic::Status,
+        >;
+        /// Returns a single Subchannel, or else a NOT_FOUND code.
+        async fn get_subchannel(
+            &self,
+            request: tonic::Request<super::GetSubchannelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSubchannelResponse>,
+            tonic::Status,
+        >;
+        /// Returns a single Socket or else a NOT_FOUND code.
+        async fn get_socket(
+            &self,
+            request: tonic::Request<super::GetSocketRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSocketResponse>,
+            tonic::Status,
+        >;
+    }
+    /// Channelz is a service exposed by gRPC servers that provides detailed debug
+    /// information.
+    #[derive(Debug)]
+    pub struct ChannelzServer<T: Channelz> {
+        inner: _Inner<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    struct _Inner<T>(Arc<T>);
+    impl<T: Channelz> ChannelzServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            let inner = _Inner(inner);
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for ChannelzServer<T>
+    where
+        T: Channelz,
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/grpc.channelz.v1.Channelz/GetTopChannels" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetTopChannelsSvc<T: Channelz>(pub Arc<T>);
+                    impl<
+                        T: Channelz,
+                    > tonic::server::UnaryService<super::GetTopChannelsRequest>
+                    for GetTopChannelsSvc<T> {
+                        type Response = super::GetTopChannelsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetTopChannelsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_top_channels(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetTopChannelsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/grpc.channelz.v1.Channelz/GetServers" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetServersSvc<T: Channelz>(pub Arc<T>);
+                    impl<
+                        T: Channelz,
+                    > tonic::server::UnaryService<super::GetServersRequest>
+                    for GetServersSvc<T> {
+                        type Response = super::GetServersResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetServersRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).get_servers(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetServersSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/grpc.channelz.v1.Channelz/GetServer" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetServerSvc<T: Channelz>(pub Arc<T>);
+                    impl<
+                        T: Channelz,
+                    > tonic::server::UnaryService<super::GetServerRequest>
+                    for GetServerSvc<T> {
+                        type Response = super::GetServerResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetServerRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).get_server(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetServerSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/grpc.channelz.v1.Channelz/GetServerSockets" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetServerSocketsSvc<T: Channelz>(pub Arc<T>);
+                    impl<
+                        T: Channelz,
+                    > tonic::server::UnaryService<super::GetServerSocketsRequest>
+                    for GetServerSocketsSvc<T> {
+                        type Response = super::GetServerSocketsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetServerSocketsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_server_sockets(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetServerSocketsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/grpc.channelz.v1.Channelz/GetChannel" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetChannelSvc<T: Channelz>(pub Arc<T>);
+                    impl<
+                        T: Channelz,
+                    > tonic::server::UnaryService<super::GetChannelRequest>
+                    for GetChannelSvc<T> {
+                        type Response = super::GetChannelResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetChannelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).get_channel(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetChannelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/grpc.channelz.v1.Channelz/GetSubchannel" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetSubchannelSvc<T: Channelz>(pub Arc<T>);
+                    impl<
+                        T: Channelz,
+                    > tonic::server::UnaryService<super::GetSubchannelRequest>
+                    for GetSubchannelSvc<T> {
+                        type Response = super::GetSubchannelResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetSubchannelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_subchannel(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetSubchannelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/grpc.channelz.v1.Channelz/GetSocket" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetSocketSvc<T: Channelz>(pub Arc<T>);
+                    impl<
+                        T: Channelz,
+                    > tonic::server::UnaryService<super::GetSocketRequest>
+                    for GetSocketSvc<T> {
+                        type Response = super::GetSocketResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetSocketRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).get_socket(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetSocketSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        Ok(
+                            http::Response::builder()
+                                .status(200)
+                                .header("grpc-status", "12")
+                                .header("content-type", "application/grpc")
+                                .body(empty_body())
+                                .unwrap(),
+                        )
+                    })
+                }
+            }
+        }
+    }
+    impl<T: Channelz> Clone for ChannelzServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+                max_decoding_message_size: self.max_decoding_message_size,
+                max_encoding_message_size: self.max_encoding_message_size,
+            }
+        }
+    }
+    impl<T: Channelz> Clone for _Inner<T> {
+        fn clone(&self) -> Self {
+            Self(Arc::clone(&self.0))
+        }
+    }
+    impl<T: std::fmt::Debug> std::fmt::Debug for _Inner<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+    impl<T: Channelz> tonic::server::NamedService for ChannelzServer<T> {
+        const NAME: &'static str = "grpc.channelz.v1.Channelz";
+    }
+}
@@ -0,0 +1,311 @@
+//! Middleware that tracks the calls of channels and servers.
+
+use crate::registry::{Call, ChannelEntry, Entry, ServerEntry, SocketEntry};
+use futures_core::Stream;
+use http::HeaderMap;
+use pin_project::pin_project;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tonic::transport::server::{Connected, TcpConnectInfo};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A layer that tracks the calls of a server, created with
+/// [`Registry::server`](crate::Registry::server).
+#[derive(Debug, Clone)]
+pub struct ServerLayer {
+    server: Arc<Entry<ServerEntry>>,
+}
+
+impl ServerLayer {
+    pub(crate) fn new(server: Arc<Entry<ServerEntry>>) -> Self {
+        Self { server }
+    }
+
+    /// Reports the connections `incoming` yields as sockets of this server, for use with
+    /// `serve_with_incoming`.
+    ///
+    /// A socket is reported until its connection is closed, and counts the calls made on
+    /// it as streams.
+    pub fn incoming<I>(&self, incoming: I) -> TrackedIncoming<I> {
+        TrackedIncoming {
+            inner: incoming,
+            server: self.server.clone(),
+        }
+    }
+}
+
+impl<S> Layer<S> for ServerLayer {
+    type Service = Tracked<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Tracked {
+            inner,
+            target: Target::Server(self.server.clone()),
+        }
+    }
+}
+
+/// A layer that tracks the calls of a channel, created with
+/// [`Registry::channel`](crate::Registry::channel).
+#[derive(Debug, Clone)]
+pub struct ChannelLayer {
+    channel: Arc<Entry<ChannelEntry>>,
+}
+
+impl ChannelLayer {
+    pub(crate) fn new(channel: Arc<Entry<ChannelEntry>>) -> Self {
+        Self { channel }
+    }
+}
+
+impl<S> Layer<S> for ChannelLayer {
+    type Service = Tracked<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Tracked {
+            inner,
+            target: Target::Channel(self.channel.clone()),
+        }
+    }
+}
+
+/// A channel or server whose calls are tracked.
+///
+/// A call succeeds when it ends with an `OK` status. It fails when it ends with any
+/// other status, or without one, such as when it is cancelled.
+#[derive(Debug, Clone)]
+pub struct Tracked<S> {
+    inner: S,
+    target: Target,
+}
+
+#[derive(Debug, Clone)]
+enum Target {
+    Channel(Arc<Entry<ChannelEntry>>),
+    Server(Arc<Entry<ServerEntry>>),
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for Tracked<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+{
+    type Response = http::Response<TrackedBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let call = match &self.target {
+            Target::Channel(channel) => Call::start(channel.data.calls.clone(), None),
+            Target::Server(server) => {
+                let socket = req
+                    .extensions()
+                    .get::<TcpConnectInfo>()
+                    .and_then(TcpConnectInfo::remote_addr)
+                    .and_then(|remote| server.socket(remote));
+                Call::start(
+                    server.data.calls.clone(),
+                    socket.map(|socket| socket.data.streams.clone()),
+                )
+            }
+        };
+
+        ResponseFuture {
+            inner: self.inner.call(req),
+            call: Some(call),
+        }
+    }
+}
+
+/// Response future for [`Tracked`].
+#[pin_project]
+#[derive(Debug)]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    call: Option<Call>,
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+{
+    type Output = Result<http::Response<TrackedBody<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let response = futures_core::ready!(this.inner.poll(cx))?;
+
+        let mut call = this.call.take();
+        // A call that fails before sending a message responds with its status in the
+        // headers.
+        if let Some(succeeded) = status_is_ok(response.headers()) {
+            if let Some(call) = call.take() {
+                call.finish(succeeded);
+            }
+        }
+
+        Poll::Ready(Ok(response.map(|inner| TrackedBody { inner, call })))
+    }
+}
+
+/// A response body that finishes its call once it has read the status in the trailers.
+#[pin_project]
+#[derive(Debug)]
+pub struct TrackedBody<B> {
+    #[pin]
+    inner: B,
+    call: Option<Call>,
+}
+
+impl<B: http_body::Body> http_body::Body for TrackedBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = self.project();
+        let data = futures_core::ready!(this.inner.poll_data(cx));
+        if let Some(Err(_)) = &data {
+            if let Some(call) = this.call.take() {
+                call.finish(false);
+            }
+        }
+        Poll::Ready(data)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let this = self.project();
+        let trailers = futures_core::ready!(this.inner.poll_trailers(cx));
+        if let Some(call) = this.call.take() {
+            let succeeded = match &trailers {
+                Ok(Some(trailers)) => status_is_ok(trailers).unwrap_or(false),
+                _ => false,
+            };
+            call.finish(succeeded);
+        }
+        Poll::Ready(trailers)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Returns whether the `grpc-status` in `headers` is `OK`, if there is one.
+fn status_is_ok(headers: &HeaderMap) -> Option<bool> {
+    headers
+        .get("grpc-status")
+        .map(|status| status.as_bytes() == b"0")
+}
+
+/// A stream of connections that are reported as sockets of a server, created with
+/// [`ServerLayer::incoming`].
+#[pin_project]
+#[derive(Debug)]
+pub struct TrackedIncoming<I> {
+    #[pin]
+    inner: I,
+    server: Arc<Entry<ServerEntry>>,
+}
+
+impl<I, IO, IE> Stream for TrackedIncoming<I>
+where
+    I: Stream<Item = Result<IO, IE>>,
+    IO: Connected<ConnectInfo = TcpConnectInfo>,
+{
+    type Item = Result<TrackedIo<IO>, IE>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let io = match futures_core::ready!(this.inner.poll_next(cx)) {
+            Some(Ok(io)) => io,
+            Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+            None => return Poll::Ready(None),
+        };
+
+        let info = io.connect_info();
+        let socket = this
+            .server
+            .add_socket(info.local_addr(), info.remote_addr());
+
+        Poll::Ready(Some(Ok(TrackedIo {
+            inner: io,
+            _socket: socket,
+        })))
+    }
+}
+
+/// A connection that is reported as a socket until it is dropped.
+#[pin_project]
+#[derive(Debug)]
+pub struct TrackedIo<IO> {
+    #[pin]
+    inner: IO,
+    _socket: Arc<Entry<SocketEntry>>,
+}
+
+impl<IO: Connected> Connected for TrackedIo<IO> {
+    type ConnectInfo = IO::ConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.inner.connect_info()
+    }
+}
+
+impl<IO: AsyncRead> AsyncRead for TrackedIo<IO> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+}
+
+impl<IO: AsyncWrite> AsyncWrite for TrackedIo<IO> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
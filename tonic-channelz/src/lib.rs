@@ -0,0 +1,79 @@
+//! A `tonic` based implementation of the gRPC channelz service.
+//!
+//! Channelz reports the channels, servers and sockets of a process, along with the number
+//! of calls each of them has started, succeeded and failed, so that standard gRPC tooling
+//! can inspect the connectivity of a running process. They are tracked with the layers of
+//! a [`Registry`], which also creates the service reporting on them.
+//!
+//! Subchannels, the connections a channel opens to each of its endpoints, are not reported.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! use tonic::transport::{server::TcpIncoming, Channel, Server};
+//! use tonic_channelz::Registry;
+//! use tower_layer::Layer;
+//!
+//! let registry = Registry::new();
+//!
+//! // Track the calls and the connectivity state of a channel, and of the clients created
+//! // from it.
+//! let channel = Channel::from_static("http://[::1]:50051").connect().await?;
+//! let channel = registry
+//!     .channel_with_state("http://[::1]:50051", &channel)
+//!     .layer(channel);
+//!
+//! // Track the calls of a server, and report its connections as sockets.
+//! let server = registry.server();
+//! let incoming = TcpIncoming::new("[::1]:50052".parse()?, true, None)?;
+//! Server::builder()
+//!     .layer(server.clone())
+//!     .add_service(registry.service())
+//!     .serve_with_incoming(server.incoming(incoming))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+#![warn(
+    missing_debug_implementations,
+    missing_docs,
+    rust_2018_idioms,
+    unreachable_pub
+)]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/tokio-rs/website/master/public/img/icons/tonic.svg"
+)]
+#![deny(rustdoc::broken_intra_doc_links)]
+#![doc(html_root_url = "https://docs.rs/tonic-channelz/0.1.0")]
+#![doc(issue_tracker_base_url = "https://github.com/hyperium/tonic/issues/")]
+#![doc(test(no_crate_inject, attr(deny(rust_2018_idioms))))]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+/// Generated protobuf types from the `grpc.channelz.v1` package.
+pub mod pb {
+    #![allow(unreachable_pub)]
+    #![allow(missing_docs)]
+    include!("generated/grpc.channelz.v1.rs");
+
+    /// Byte encoded FILE_DESCRIPTOR_SET.
+    pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!("generated/grpc_channelz_v1.bin");
+
+    #[cfg(test)]
+    mod tests {
+        use super::FILE_DESCRIPTOR_SET;
+        use prost::Message as _;
+
+        #[test]
+        fn file_descriptor_set_is_valid() {
+            prost_types::FileDescriptorSet::decode(FILE_DESCRIPTOR_SET).unwrap();
+        }
+    }
+}
+
+pub mod layer;
+mod registry;
+pub mod server;
+
+pub use registry::Registry;
@@ -0,0 +1,345 @@
+use crate::layer::{ChannelLayer, ServerLayer};
+use crate::pb::channelz_server::ChannelzServer;
+use crate::server::ChannelzService;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::SystemTime;
+use tonic::transport::Channel;
+
+/// The page size used when a request does not ask for one.
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// A registry of the channels, servers and sockets that a channelz service reports on.
+///
+/// Channels and servers are tracked with the layers created by
+/// [`channel`](Registry::channel) and [`server`](Registry::server), and stay registered
+/// until the last clone of their layer, and of every service it created, is dropped.
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    inner: Arc<Inner>,
+}
+
+impl Registry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a `grpc.channelz.v1.Channelz` service that reports on this registry, to be
+    /// added to a Tonic server with `add_service`.
+    pub fn service(&self) -> ChannelzServer<ChannelzService> {
+        ChannelzServer::new(ChannelzService::new(self.clone()))
+    }
+
+    /// Registers a server and returns the layer that tracks its calls.
+    ///
+    /// The layer is meant for `Server::layer`. To also report the connections of the server
+    /// as sockets, pass its incoming stream through [`ServerLayer::incoming`].
+    pub fn server(&self) -> ServerLayer {
+        ServerLayer::new(self.register(ServerEntry::default()))
+    }
+
+    /// Registers a channel to `target` and returns the layer that tracks its calls.
+    ///
+    /// Wrap a `Channel` in this layer before creating a client from it. Its connectivity
+    /// state is reported as unknown, use [`channel_with_state`](Registry::channel_with_state)
+    /// to report it as well.
+    pub fn channel(&self, target: impl Into<String>) -> ChannelLayer {
+        ChannelLayer::new(self.register(ChannelEntry {
+            target: target.into(),
+            calls: Arc::default(),
+            channel: None,
+        }))
+    }
+
+    /// Registers `channel` to `target` and returns the layer that tracks its calls, like
+    /// [`channel`](Registry::channel), also reporting the connectivity state of `channel`.
+    ///
+    /// The registry keeps a clone of `channel` for as long as the channel is registered.
+    pub fn channel_with_state(&self, target: impl Into<String>, channel: &Channel) -> ChannelLayer {
+        ChannelLayer::new(self.register(ChannelEntry {
+            target: target.into(),
+            calls: Arc::default(),
+            channel: Some(channel.clone()),
+        }))
+    }
+
+    pub(crate) fn register<T: Kind>(&self, data: T) -> Arc<Entry<T>> {
+        let entry = Arc::new(Entry {
+            id: self.inner.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            created: SystemTime::now(),
+            registry: self.inner.clone(),
+            data,
+        });
+        T::entries(&self.inner)
+            .lock()
+            .unwrap()
+            .insert(entry.id, Arc::downgrade(&entry));
+        entry
+    }
+
+    pub(crate) fn get<T: Kind>(&self, id: i64) -> Option<Arc<Entry<T>>> {
+        T::entries(&self.inner)
+            .lock()
+            .unwrap()
+            .get(&id)
+            .and_then(Weak::upgrade)
+    }
+
+    /// Returns the entries with an id of at least `start`, and whether there are no more.
+    pub(crate) fn page<T: Kind>(&self, start: i64, max_results: i64) -> (Vec<Arc<Entry<T>>>, bool) {
+        page(&T::entries(&self.inner).lock().unwrap(), start, max_results)
+    }
+}
+
+pub(crate) fn page<T>(
+    entries: &BTreeMap<i64, Weak<T>>,
+    start: i64,
+    max_results: i64,
+) -> (Vec<Arc<T>>, bool) {
+    let max_results = match usize::try_from(max_results) {
+        Ok(max) if max > 0 && max <= DEFAULT_PAGE_SIZE => max,
+        _ => DEFAULT_PAGE_SIZE,
+    };
+
+    let mut entries = entries.range(start..).map(|(_, entry)| entry);
+    let page: Vec<_> = entries
+        .by_ref()
+        .filter_map(Weak::upgrade)
+        .take(max_results)
+        .collect();
+    // Entries are not upgraded here, as dropping the last reference to one while the map
+    // is locked would deadlock.
+    let end = !entries.any(|entry| entry.strong_count() > 0);
+    (page, end)
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Inner {
+    next_id: AtomicI64,
+    channels: Mutex<BTreeMap<i64, Weak<Entry<ChannelEntry>>>>,
+    servers: Mutex<BTreeMap<i64, Weak<Entry<ServerEntry>>>>,
+    sockets: Mutex<BTreeMap<i64, Weak<Entry<SocketEntry>>>>,
+}
+
+/// Something a registry keeps track of.
+pub(crate) trait Kind: Sized {
+    fn entries(inner: &Inner) -> &Mutex<BTreeMap<i64, Weak<Entry<Self>>>>;
+
+    /// Called when the entry with `id` is dropped, after it has been unregistered.
+    fn unregister(&self, _id: i64) {}
+}
+
+/// A registered channel, server or socket, that is unregistered when it is dropped.
+#[derive(Debug)]
+pub(crate) struct Entry<T: Kind> {
+    pub(crate) id: i64,
+    pub(crate) created: SystemTime,
+    registry: Arc<Inner>,
+    pub(crate) data: T,
+}
+
+impl<T: Kind> Drop for Entry<T> {
+    fn drop(&mut self) {
+        T::entries(&self.registry).lock().unwrap().remove(&self.id);
+        self.data.unregister(self.id);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ChannelEntry {
+    pub(crate) target: String,
+    pub(crate) calls: Arc<CallMetrics>,
+    /// The channel to report the connectivity state of, if it is known.
+    pub(crate) channel: Option<Channel>,
+}
+
+impl Kind for ChannelEntry {
+    fn entries(inner: &Inner) -> &Mutex<BTreeMap<i64, Weak<Entry<Self>>>> {
+        &inner.channels
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ServerEntry {
+    pub(crate) calls: Arc<CallMetrics>,
+    pub(crate) sockets: Mutex<ServerSockets>,
+}
+
+/// The open connections of a server.
+#[derive(Debug, Default)]
+pub(crate) struct ServerSockets {
+    pub(crate) by_id: BTreeMap<i64, Weak<Entry<SocketEntry>>>,
+    by_remote: HashMap<SocketAddr, Weak<Entry<SocketEntry>>>,
+}
+
+impl Kind for ServerEntry {
+    fn entries(inner: &Inner) -> &Mutex<BTreeMap<i64, Weak<Entry<Self>>>> {
+        &inner.servers
+    }
+}
+
+impl Entry<ServerEntry> {
+    /// Registers a connection of this server.
+    pub(crate) fn add_socket(
+        self: &Arc<Self>,
+        local: Option<SocketAddr>,
+        remote: Option<SocketAddr>,
+    ) -> Arc<Entry<SocketEntry>> {
+        let registry = Registry {
+            inner: self.registry.clone(),
+        };
+        let socket = registry.register(SocketEntry {
+            local,
+            remote,
+            streams: Arc::default(),
+            server: self.clone(),
+        });
+
+        let mut sockets = self.data.sockets.lock().unwrap();
+        sockets.by_id.insert(socket.id, Arc::downgrade(&socket));
+        if let Some(remote) = remote {
+            sockets.by_remote.insert(remote, Arc::downgrade(&socket));
+        }
+        socket
+    }
+
+    /// Returns the open connection of this server from `remote`.
+    pub(crate) fn socket(&self, remote: SocketAddr) -> Option<Arc<Entry<SocketEntry>>> {
+        let sockets = self.data.sockets.lock().unwrap();
+        sockets.by_remote.get(&remote).and_then(Weak::upgrade)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SocketEntry {
+    pub(crate) local: Option<SocketAddr>,
+    pub(crate) remote: Option<SocketAddr>,
+    pub(crate) streams: Arc<CallMetrics>,
+    server: Arc<Entry<ServerEntry>>,
+}
+
+impl Kind for SocketEntry {
+    fn entries(inner: &Inner) -> &Mutex<BTreeMap<i64, Weak<Entry<Self>>>> {
+        &inner.sockets
+    }
+
+    fn unregister(&self, id: i64) {
+        let mut sockets = self.server.data.sockets.lock().unwrap();
+        sockets.by_id.remove(&id);
+        if let Some(remote) = self.remote {
+            // A new connection from the same address may have replaced this one already.
+            if let Some(socket) = sockets.by_remote.get(&remote) {
+                if socket.strong_count() == 0 {
+                    sockets.by_remote.remove(&remote);
+                }
+            }
+        }
+    }
+}
+
+/// The number of calls started, succeeded and failed on a channel, server or socket.
+#[derive(Debug, Default)]
+pub(crate) struct CallMetrics {
+    pub(crate) started: AtomicI64,
+    pub(crate) succeeded: AtomicI64,
+    pub(crate) failed: AtomicI64,
+    pub(crate) last_started: Mutex<Option<SystemTime>>,
+}
+
+impl CallMetrics {
+    fn start(&self) {
+        self.started.fetch_add(1, Ordering::Relaxed);
+        *self.last_started.lock().unwrap() = Some(SystemTime::now());
+    }
+
+    fn finish(&self, succeeded: bool) {
+        if succeeded {
+            self.succeeded.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A call in progress, that counts as failed if it is dropped before it finishes.
+#[derive(Debug)]
+pub(crate) struct Call {
+    calls: Arc<CallMetrics>,
+    stream: Option<Arc<CallMetrics>>,
+    finished: bool,
+}
+
+impl Call {
+    /// Starts a call, which is also a stream on a socket if `stream` is set.
+    pub(crate) fn start(calls: Arc<CallMetrics>, stream: Option<Arc<CallMetrics>>) -> Self {
+        calls.start();
+        if let Some(stream) = &stream {
+            stream.start();
+        }
+
+        Self {
+            calls,
+            stream,
+            finished: false,
+        }
+    }
+
+    pub(crate) fn finish(mut self, succeeded: bool) {
+        self.record(succeeded);
+    }
+
+    fn record(&mut self, succeeded: bool) {
+        self.finished = true;
+        self.calls.finish(succeeded);
+        if let Some(stream) = &self.stream {
+            stream.finish(succeeded);
+        }
+    }
+}
+
+impl Drop for Call {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.record(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids<T: Kind>(registry: &Registry, start: i64, max_results: i64) -> (Vec<i64>, bool) {
+        let (entries, end) = registry.page::<T>(start, max_results);
+        (entries.iter().map(|entry| entry.id).collect(), end)
+    }
+
+    #[test]
+    fn pages_skip_dropped_entries() {
+        let registry = Registry::new();
+        let channels: Vec<_> = (0..5)
+            .map(|i| registry.channel(format!("http://{}", i)))
+            .collect();
+        drop(channels);
+
+        let first = registry.server();
+        let _second = registry.server();
+        let third = registry.server();
+        drop(third);
+
+        // Ids are shared by all kinds of entries, so the servers are 6, 7 and 8.
+        assert_eq!(ids::<ServerEntry>(&registry, 0, 1), (vec![6], false));
+        assert_eq!(
+            ids::<ServerEntry>(&registry, 7, 1),
+            (vec![7], true),
+            "dropped entries are not listed"
+        );
+
+        drop(first);
+        assert_eq!(ids::<ServerEntry>(&registry, 0, 0), (vec![7], true));
+        assert_eq!(ids::<ChannelEntry>(&registry, 0, 0), (vec![], true));
+    }
+}
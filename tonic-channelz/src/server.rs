@@ -0,0 +1,264 @@
+//! Contains the channelz service.
+
+use crate::pb;
+use crate::pb::channel_connectivity_state::State;
+use crate::pb::channelz_server::Channelz;
+use crate::registry::{page, CallMetrics, ChannelEntry, Entry, ServerEntry, SocketEntry};
+use crate::Registry;
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tonic::transport::channel::ConnectivityState;
+use tonic::{Request, Response, Status};
+
+/// A service providing an implementation of `grpc.channelz.v1.Channelz`, which reports on
+/// the channels, servers and sockets of a [`Registry`].
+///
+/// Created with [`Registry::service`]. The connectivity state of a channel is reported when
+/// it was registered with [`Registry::channel_with_state`].
+///
+/// Subchannels are not reported: a `Channel` does not expose the connections to each of its
+/// endpoints, so channels have no subchannel or socket references and
+/// [`get_subchannel`](Channelz::get_subchannel) always responds with `NOT_FOUND`. The
+/// messages sent on sockets are not tracked either.
+#[derive(Debug)]
+pub struct ChannelzService {
+    registry: Registry,
+}
+
+impl ChannelzService {
+    pub(crate) fn new(registry: Registry) -> Self {
+        Self { registry }
+    }
+}
+
+#[tonic::async_trait]
+impl Channelz for ChannelzService {
+    async fn get_top_channels(
+        &self,
+        request: Request<pb::GetTopChannelsRequest>,
+    ) -> Result<Response<pb::GetTopChannelsResponse>, Status> {
+        let request = request.into_inner();
+        let (channels, end) = self
+            .registry
+            .page::<ChannelEntry>(request.start_channel_id, request.max_results);
+
+        Ok(Response::new(pb::GetTopChannelsResponse {
+            channel: channels
+                .iter()
+                .map(|channel| channel_message(channel))
+                .collect(),
+            end,
+        }))
+    }
+
+    async fn get_servers(
+        &self,
+        request: Request<pb::GetServersRequest>,
+    ) -> Result<Response<pb::GetServersResponse>, Status> {
+        let request = request.into_inner();
+        let (servers, end) = self
+            .registry
+            .page::<ServerEntry>(request.start_server_id, request.max_results);
+
+        Ok(Response::new(pb::GetServersResponse {
+            server: servers
+                .iter()
+                .map(|server| server_message(server))
+                .collect(),
+            end,
+        }))
+    }
+
+    async fn get_server(
+        &self,
+        request: Request<pb::GetServerRequest>,
+    ) -> Result<Response<pb::GetServerResponse>, Status> {
+        let id = request.into_inner().server_id;
+        let server = self
+            .registry
+            .get::<ServerEntry>(id)
+            .ok_or_else(|| Status::not_found(format!("server {} not found", id)))?;
+
+        Ok(Response::new(pb::GetServerResponse {
+            server: Some(server_message(&server)),
+        }))
+    }
+
+    async fn get_server_sockets(
+        &self,
+        request: Request<pb::GetServerSocketsRequest>,
+    ) -> Result<Response<pb::GetServerSocketsResponse>, Status> {
+        let request = request.into_inner();
+        let server = self
+            .registry
+            .get::<ServerEntry>(request.server_id)
+            .ok_or_else(|| Status::not_found(format!("server {} not found", request.server_id)))?;
+
+        let (sockets, end) = {
+            let sockets = server.data.sockets.lock().unwrap();
+            page(&sockets.by_id, request.start_socket_id, request.max_results)
+        };
+
+        Ok(Response::new(pb::GetServerSocketsResponse {
+            socket_ref: sockets.iter().map(|socket| socket_ref(socket)).collect(),
+            end,
+        }))
+    }
+
+    async fn get_channel(
+        &self,
+        request: Request<pb::GetChannelRequest>,
+    ) -> Result<Response<pb::GetChannelResponse>, Status> {
+        let id = request.into_inner().channel_id;
+        let channel = self
+            .registry
+            .get::<ChannelEntry>(id)
+            .ok_or_else(|| Status::not_found(format!("channel {} not found", id)))?;
+
+        Ok(Response::new(pb::GetChannelResponse {
+            channel: Some(channel_message(&channel)),
+        }))
+    }
+
+    async fn get_subchannel(
+        &self,
+        request: Request<pb::GetSubchannelRequest>,
+    ) -> Result<Response<pb::GetSubchannelResponse>, Status> {
+        let id = request.into_inner().subchannel_id;
+        // Subchannels are never registered, see the docs of `ChannelzService`.
+        Err(Status::not_found(format!("subchannel {} not found", id)))
+    }
+
+    async fn get_socket(
+        &self,
+        request: Request<pb::GetSocketRequest>,
+    ) -> Result<Response<pb::GetSocketResponse>, Status> {
+        let id = request.into_inner().socket_id;
+        let socket = self
+            .registry
+            .get::<SocketEntry>(id)
+            .ok_or_else(|| Status::not_found(format!("socket {} not found", id)))?;
+
+        Ok(Response::new(pb::GetSocketResponse {
+            socket: Some(socket_message(&socket)),
+        }))
+    }
+}
+
+fn channel_message(channel: &Entry<ChannelEntry>) -> pb::Channel {
+    let calls = &channel.data.calls;
+    pb::Channel {
+        r#ref: Some(pb::ChannelRef {
+            channel_id: channel.id,
+            name: channel.data.target.clone(),
+        }),
+        data: Some(pb::ChannelData {
+            state: Some(pb::ChannelConnectivityState {
+                state: channel
+                    .data
+                    .channel
+                    .as_ref()
+                    .map_or(State::Unknown, |channel| state(channel.state()))
+                    as i32,
+            }),
+            target: channel.data.target.clone(),
+            trace: Some(trace(channel.created)),
+            calls_started: calls.started.load(Ordering::Relaxed),
+            calls_succeeded: calls.succeeded.load(Ordering::Relaxed),
+            calls_failed: calls.failed.load(Ordering::Relaxed),
+            last_call_started_timestamp: last_started(calls),
+        }),
+        channel_ref: Vec::new(),
+        subchannel_ref: Vec::new(),
+        socket_ref: Vec::new(),
+    }
+}
+
+fn state(state: ConnectivityState) -> State {
+    match state {
+        ConnectivityState::Idle => State::Idle,
+        ConnectivityState::Connecting => State::Connecting,
+        ConnectivityState::Ready => State::Ready,
+        ConnectivityState::TransientFailure => State::TransientFailure,
+        ConnectivityState::Shutdown => State::Shutdown,
+    }
+}
+
+fn server_message(server: &Entry<ServerEntry>) -> pb::Server {
+    let calls = &server.data.calls;
+    pb::Server {
+        r#ref: Some(pb::ServerRef {
+            server_id: server.id,
+            name: String::new(),
+        }),
+        data: Some(pb::ServerData {
+            trace: Some(trace(server.created)),
+            calls_started: calls.started.load(Ordering::Relaxed),
+            calls_succeeded: calls.succeeded.load(Ordering::Relaxed),
+            calls_failed: calls.failed.load(Ordering::Relaxed),
+            last_call_started_timestamp: last_started(calls),
+        }),
+        listen_socket: Vec::new(),
+    }
+}
+
+fn socket_ref(socket: &Entry<SocketEntry>) -> pb::SocketRef {
+    let name = match (socket.data.local, socket.data.remote) {
+        (Some(local), Some(remote)) => format!("{} -> {}", remote, local),
+        _ => String::new(),
+    };
+
+    pb::SocketRef {
+        socket_id: socket.id,
+        name,
+    }
+}
+
+fn socket_message(socket: &Entry<SocketEntry>) -> pb::Socket {
+    let streams = &socket.data.streams;
+    pb::Socket {
+        r#ref: Some(socket_ref(socket)),
+        data: Some(pb::SocketData {
+            streams_started: streams.started.load(Ordering::Relaxed),
+            streams_succeeded: streams.succeeded.load(Ordering::Relaxed),
+            streams_failed: streams.failed.load(Ordering::Relaxed),
+            // Server sockets only carry streams that the remote endpoint created.
+            last_remote_stream_created_timestamp: last_started(streams),
+            ..Default::default()
+        }),
+        local: socket.data.local.map(address),
+        remote: socket.data.remote.map(address),
+        security: None,
+        remote_name: String::new(),
+    }
+}
+
+fn address(addr: SocketAddr) -> pb::Address {
+    let ip_address = match addr {
+        SocketAddr::V4(addr) => addr.ip().octets().to_vec(),
+        SocketAddr::V6(addr) => addr.ip().octets().to_vec(),
+    };
+
+    pb::Address {
+        address: Some(pb::address::Address::TcpipAddress(
+            pb::address::TcpIpAddress {
+                ip_address,
+                port: i32::from(addr.port()),
+            },
+        )),
+    }
+}
+
+fn trace(created: SystemTime) -> pb::ChannelTrace {
+    pb::ChannelTrace {
+        num_events_logged: 0,
+        creation_timestamp: Some(created.into()),
+        events: Vec::new(),
+    }
+}
+
+fn last_started(calls: &Arc<CallMetrics>) -> Option<prost_types::Timestamp> {
+    calls.last_started.lock().unwrap().map(Into::into)
+}
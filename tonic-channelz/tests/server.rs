@@ -0,0 +1,185 @@
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Channel, Server},
+    Code, Request,
+};
+use tonic_channelz::{
+    pb::{
+        address, channel_connectivity_state::State, channelz_client::ChannelzClient,
+        GetChannelRequest, GetServerSocketsRequest, GetServersRequest, GetSocketRequest,
+        GetSubchannelRequest, GetTopChannelsRequest,
+    },
+    Registry,
+};
+use tower_layer::Layer;
+
+#[tokio::test]
+async fn reports_tracked_channels_servers_and_sockets() {
+    let registry = Registry::new();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = registry.server();
+    tokio::spawn(
+        Server::builder()
+            .layer(server.clone())
+            .add_service(registry.service())
+            .serve_with_incoming(server.incoming(TcpListenerStream::new(listener))),
+    );
+
+    let target = format!("http://{}", addr);
+    let channel = Channel::from_shared(target.clone())
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = ChannelzClient::new(registry.channel(target.clone()).layer(channel.clone()));
+
+    let channels = client
+        .get_top_channels(GetTopChannelsRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(channels.end);
+    assert_eq!(channels.channel.len(), 1);
+    let data = channels.channel[0].data.clone().unwrap();
+    assert_eq!(data.target, target);
+    // The call that returned these counts was still in progress.
+    assert_eq!(
+        (data.calls_started, data.calls_succeeded, data.calls_failed),
+        (1, 0, 0)
+    );
+    assert!(data.last_call_started_timestamp.is_some());
+
+    let status = client
+        .get_socket(GetSocketRequest {
+            socket_id: 1000,
+            summary: false,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+
+    let channels = client
+        .get_top_channels(GetTopChannelsRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    let data = channels.channel[0].data.clone().unwrap();
+    assert_eq!(
+        (data.calls_started, data.calls_succeeded, data.calls_failed),
+        (3, 1, 1)
+    );
+
+    let servers = client
+        .get_servers(GetServersRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(servers.end);
+    assert_eq!(servers.server.len(), 1);
+    let server_id = servers.server[0].r#ref.clone().unwrap().server_id;
+    let data = servers.server[0].data.clone().unwrap();
+    assert_eq!(
+        (data.calls_started, data.calls_succeeded, data.calls_failed),
+        (4, 2, 1)
+    );
+
+    let sockets = client
+        .get_server_sockets(GetServerSocketsRequest {
+            server_id,
+            start_socket_id: 0,
+            max_results: 0,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(sockets.end);
+    assert_eq!(sockets.socket_ref.len(), 1);
+
+    let socket = client
+        .get_socket(Request::new(GetSocketRequest {
+            socket_id: sockets.socket_ref[0].socket_id,
+            summary: false,
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .socket
+        .unwrap();
+    let data = socket.data.unwrap();
+    assert_eq!(
+        (
+            data.streams_started,
+            data.streams_succeeded,
+            data.streams_failed
+        ),
+        (6, 4, 1)
+    );
+    match socket.local.unwrap().address {
+        Some(address::Address::TcpipAddress(local)) => {
+            assert_eq!(local.ip_address, vec![127, 0, 0, 1]);
+            assert_eq!(local.port, i32::from(addr.port()));
+        }
+        other => panic!("expected a TCP address, got {:?}", other),
+    }
+
+    // Once its last clone is gone, the tracked channel is no longer reported.
+    drop(client);
+    let channels = ChannelzClient::new(channel)
+        .get_top_channels(GetTopChannelsRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(channels.end);
+    assert!(channels.channel.is_empty());
+}
+
+#[tokio::test]
+async fn reports_the_connectivity_state_of_channels() {
+    let registry = Registry::new();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(
+        Server::builder()
+            .add_service(registry.service())
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+
+    let channel = Channel::from_shared(target.clone())
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let untracked = registry.channel(target.clone());
+    let mut client = ChannelzClient::new(
+        registry
+            .channel_with_state(target.clone(), &channel)
+            .layer(channel.clone()),
+    );
+
+    let state = |channel_id| {
+        let mut client = client.clone();
+        async move {
+            let channel = client
+                .get_channel(GetChannelRequest { channel_id })
+                .await
+                .unwrap()
+                .into_inner()
+                .channel
+                .unwrap();
+            channel.data.unwrap().state.unwrap().state()
+        }
+    };
+    assert_eq!(state(1).await, State::Unknown);
+    assert_eq!(state(2).await, State::Ready);
+    drop(untracked);
+
+    let status = client
+        .get_subchannel(GetSubchannelRequest { subchannel_id: 1 })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+}
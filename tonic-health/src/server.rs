@@ -22,7 +22,7 @@ use tonic::{Request, Response, Status};
 /// builder.
 pub fn health_reporter() -> (HealthReporter, HealthServer<impl Health>) {
     let reporter = HealthReporter::new();
-    let service = HealthService::new(reporter.statuses.clone());
+    let service = HealthService::new(&reporter);
     let server = HealthServer::new(service);
 
     (reporter, server)
@@ -30,12 +30,16 @@ pub fn health_reporter() -> (HealthReporter, HealthServer<impl Health>) {
 
 type StatusPair = (watch::Sender<ServingStatus>, watch::Receiver<ServingStatus>);
 
+/// Notified whenever a service is registered, for watchers of services that are unknown.
+type Registrations = Arc<(watch::Sender<()>, watch::Receiver<()>)>;
+
 /// A handle providing methods to update the health status of gRPC services. A
 /// `HealthReporter` is connected to a `HealthServer` which serves the statuses
 /// over the `grpc.health.v1.Health` service.
 #[derive(Clone, Debug)]
 pub struct HealthReporter {
     statuses: Arc<RwLock<HashMap<String, StatusPair>>>,
+    registrations: Registrations,
 }
 
 impl HealthReporter {
@@ -45,7 +49,10 @@ impl HealthReporter {
 
         let statuses = Arc::new(RwLock::new(HashMap::from([server_status])));
 
-        HealthReporter { statuses }
+        HealthReporter {
+            statuses,
+            registrations: Arc::new(watch::channel(())),
+        }
     }
 
     /// Sets the status of the service implemented by `S` to `Serving`. This notifies any watchers
@@ -92,11 +99,19 @@ impl HealthReporter {
             }
             None => {
                 writer.insert(service_name.to_string(), watch::channel(status));
+                // The receiver is kept alongside the sender, so this cannot fail either.
+                self.registrations
+                    .0
+                    .send(())
+                    .expect("channel should not be closed");
             }
         };
     }
 
     /// Clear the status of the given service.
+    ///
+    /// Checks of the service then fail with `NOT_FOUND`, and watchers are sent
+    /// `SERVICE_UNKNOWN` until the service is registered again.
     pub async fn clear_service_status(&mut self, service_name: &str) {
         let mut writer = self.statuses.write().await;
         let _ = writer.remove(service_name);
@@ -107,11 +122,15 @@ impl HealthReporter {
 #[derive(Debug)]
 pub struct HealthService {
     statuses: Arc<RwLock<HashMap<String, StatusPair>>>,
+    registrations: Registrations,
 }
 
 impl HealthService {
-    fn new(services: Arc<RwLock<HashMap<String, StatusPair>>>) -> Self {
-        HealthService { statuses: services }
+    fn new(reporter: &HealthReporter) -> Self {
+        HealthService {
+            statuses: reporter.statuses.clone(),
+            registrations: reporter.registrations.clone(),
+        }
     }
 
     async fn service_health(&self, service_name: &str) -> Option<ServingStatus> {
@@ -144,20 +163,42 @@ impl Health for HealthService {
         &self,
         request: Request<HealthCheckRequest>,
     ) -> Result<Response<Self::WatchStream>, Status> {
-        let service_name = request.get_ref().service.as_str();
-        let mut status_rx = match self.statuses.read().await.get(service_name) {
-            None => return Err(Status::not_found("service not registered")),
-            Some(pair) => pair.1.clone(),
-        };
+        let service_name = request.into_inner().service;
+        let statuses = self.statuses.clone();
+        // Cloned before the service is first looked up, so that no registration is missed.
+        let mut registered = self.registrations.1.clone();
 
+        // As the specification requires, a service that is unknown is reported as
+        // `SERVICE_UNKNOWN` without ending the call, until it is registered.
         let output = async_stream::try_stream! {
-            // yield the current value
-            let status = crate::pb::health_check_response::ServingStatus::from(*status_rx.borrow()) as i32;
-            yield HealthCheckResponse { status };
-
-            while let Ok(_) = status_rx.changed().await {
-                let status = crate::pb::health_check_response::ServingStatus::from(*status_rx.borrow()) as i32;
-                yield HealthCheckResponse { status };
+            let mut unknown_sent = false;
+
+            loop {
+                let status_rx = statuses.read().await.get(&service_name).map(|pair| pair.1.clone());
+
+                if let Some(mut status_rx) = status_rx {
+                    unknown_sent = false;
+
+                    // yield the current value
+                    let status = crate::pb::health_check_response::ServingStatus::from(*status_rx.borrow()) as i32;
+                    yield HealthCheckResponse { status };
+
+                    // This ends once the status of the service is cleared.
+                    while status_rx.changed().await.is_ok() {
+                        let status = crate::pb::health_check_response::ServingStatus::from(*status_rx.borrow()) as i32;
+                        yield HealthCheckResponse { status };
+                    }
+                }
+
+                if !unknown_sent {
+                    unknown_sent = true;
+                    let status = crate::pb::health_check_response::ServingStatus::ServiceUnknown as i32;
+                    yield HealthCheckResponse { status };
+                }
+
+                if registered.changed().await.is_err() {
+                    break;
+                }
             }
         };
 
@@ -180,6 +221,11 @@ mod tests {
         assert_eq!(wire, expected);
     }
 
+    fn assert_service_unknown(wire: i32) {
+        let expected = crate::pb::health_check_response::ServingStatus::ServiceUnknown as i32;
+        assert_eq!(wire, expected);
+    }
+
     fn assert_grpc_status(wire: Option<Status>, expected: Code) {
         let wire = wire.expect("status is not None").code();
         assert_eq!(wire, expected);
@@ -197,7 +243,7 @@ mod tests {
             );
         }
 
-        let health_service = HealthService::new(health_reporter.statuses.clone());
+        let health_service = HealthService::new(&health_reporter);
         (health_reporter, health_service)
     }
 
@@ -273,8 +319,25 @@ mod tests {
                 service: "Unregistered".to_string(),
             }))
             .await;
-        assert!(resp.is_err());
-        assert_grpc_status(resp.err(), Code::NotFound);
+        assert!(resp.is_ok());
+        let mut unregistered = resp.unwrap().into_inner();
+        let item = unregistered
+            .next()
+            .await
+            .expect("streamed response is Some")
+            .expect("response is ok");
+        assert_service_unknown(item.status);
+
+        // Unregistered service - registered later
+        reporter
+            .set_service_status("Unregistered", ServingStatus::Serving)
+            .await;
+        let item = unregistered
+            .next()
+            .await
+            .expect("streamed response is Some")
+            .expect("response is ok");
+        assert_serving_status(item.status, ServingStatus::Serving);
 
         // Registered service
         let resp = service
@@ -318,7 +381,22 @@ mod tests {
 
         // De-registered service
         reporter.clear_service_status("TestService").await;
-        let item = resp.next().await;
-        assert!(item.is_none());
+        let item = resp
+            .next()
+            .await
+            .expect("streamed response is Some")
+            .expect("response is ok");
+        assert_service_unknown(item.status);
+
+        // Re-registered service
+        reporter
+            .set_service_status("TestService", ServingStatus::NotServing)
+            .await;
+        let item = resp
+            .next()
+            .await
+            .expect("streamed response is Some")
+            .expect("response is ok");
+        assert_serving_status(item.status, ServingStatus::NotServing);
     }
 }
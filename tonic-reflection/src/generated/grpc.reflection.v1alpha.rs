@@ -249,6 +249,29 @@ pub mod server_reflection_client {
             );
             self.inner.streaming(request.into_streaming_request(), path, codec).await
         }
+        /// Starts the call with a request stream fed through the returned sender, which
+        /// buffers up to `buffer` messages, and resolves to the response stream. The two
+        /// halves can be moved to separate tasks. The request ends once the sender is dropped.
+        ///
+        /// Messages sent before the returned future is polled wait in the buffer, so once it
+        /// is full, keep polling the future while sending more.
+        pub fn server_reflection_info_with_sender(
+            &mut self,
+            buffer: usize,
+        ) -> (
+            tonic::client::RequestSender<super::ServerReflectionRequest>,
+            impl std::future::Future<
+                Output = std::result::Result<
+                    tonic::Response<
+                        tonic::codec::Streaming<super::ServerReflectionResponse>,
+                    >,
+                    tonic::Status,
+                >,
+            > + '_,
+        ) {
+            let (sender, requests) = tonic::client::request_channel(buffer);
+            (sender, self.server_reflection_info(requests))
+        }
     }
 }
 /// Generated server implementations.
@@ -15,24 +15,43 @@
 #![doc(test(no_crate_inject, attr(deny(rust_2018_idioms))))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
-/// Generated protobuf types from the `grpc.reflection.v1alpha` package.
+/// Generated protobuf types of gRPC Server Reflection.
+///
+/// The types of the `grpc.reflection.v1alpha` package are also available at the root of
+/// this module.
 pub mod pb {
-    #![allow(unreachable_pub)]
-    #![allow(missing_docs)]
-    #![allow(rustdoc::invalid_html_tags)]
-    include!("generated/grpc.reflection.v1alpha.rs");
+    /// Generated protobuf types from the `grpc.reflection.v1` package.
+    pub mod v1 {
+        #![allow(unreachable_pub)]
+        #![allow(missing_docs)]
+        #![allow(rustdoc::invalid_html_tags)]
+        include!("generated/grpc.reflection.v1.rs");
 
-    /// Byte encoded FILE_DESCRIPTOR_SET.
-    pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!("generated/reflection_v1alpha1.bin");
+        /// Byte encoded FILE_DESCRIPTOR_SET.
+        pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!("generated/reflection_v1.bin");
+    }
+
+    /// Generated protobuf types from the `grpc.reflection.v1alpha` package.
+    pub mod v1alpha {
+        #![allow(unreachable_pub)]
+        #![allow(missing_docs)]
+        #![allow(rustdoc::invalid_html_tags)]
+        include!("generated/grpc.reflection.v1alpha.rs");
+
+        /// Byte encoded FILE_DESCRIPTOR_SET.
+        pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!("generated/reflection_v1alpha1.bin");
+    }
+
+    pub use v1alpha::*;
 
     #[cfg(test)]
     mod tests {
-        use super::FILE_DESCRIPTOR_SET;
         use prost::Message as _;
 
         #[test]
-        fn file_descriptor_set_is_valid() {
-            prost_types::FileDescriptorSet::decode(FILE_DESCRIPTOR_SET).unwrap();
+        fn file_descriptor_sets_are_valid() {
+            prost_types::FileDescriptorSet::decode(super::v1::FILE_DESCRIPTOR_SET).unwrap();
+            prost_types::FileDescriptorSet::decode(super::v1alpha::FILE_DESCRIPTOR_SET).unwrap();
         }
     }
 }
@@ -1,10 +1,5 @@
-use crate::pb::server_reflection_request::MessageRequest;
-use crate::pb::server_reflection_response::MessageResponse;
 pub use crate::pb::server_reflection_server::{ServerReflection, ServerReflectionServer};
-use crate::pb::{
-    ExtensionNumberResponse, FileDescriptorResponse, ListServiceResponse, ServerReflectionRequest,
-    ServerReflectionResponse, ServiceResponse,
-};
+use crate::pb::{v1, v1alpha};
 use prost::{DecodeError, Message};
 use prost_types::{
     DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
@@ -106,10 +101,38 @@ impl<'b> Builder<'b> {
         self
     }
 
-    /// Build a gRPC Reflection Service to be served via Tonic.
-    pub fn build(mut self) -> Result<ServerReflectionServer<impl ServerReflection>, Error> {
+    /// Build a gRPC Reflection Service to be served via Tonic, implementing the
+    /// `grpc.reflection.v1alpha` version of the protocol.
+    ///
+    /// Some clients only support this version and others only [`v1`](Builder::build_v1). To
+    /// support both, build and serve one service of each version.
+    pub fn build(self) -> Result<ServerReflectionServer<impl ServerReflection>, Error> {
+        let state = self.build_state(v1alpha::FILE_DESCRIPTOR_SET)?;
+        Ok(ServerReflectionServer::new(ReflectionService { state }))
+    }
+
+    /// Build a gRPC Reflection Service to be served via Tonic, implementing the
+    /// `grpc.reflection.v1` version of the protocol.
+    pub fn build_v1(
+        self,
+    ) -> Result<
+        v1::server_reflection_server::ServerReflectionServer<
+            impl v1::server_reflection_server::ServerReflection,
+        >,
+        Error,
+    > {
+        let state = self.build_state(v1::FILE_DESCRIPTOR_SET)?;
+        Ok(v1::server_reflection_server::ServerReflectionServer::new(
+            ReflectionServiceV1 { state },
+        ))
+    }
+
+    fn build_state(
+        mut self,
+        reflection_service: &'b [u8],
+    ) -> Result<Arc<ReflectionServiceState>, Error> {
         if self.include_reflection_service {
-            self = self.register_encoded_file_descriptor_set(crate::pb::FILE_DESCRIPTOR_SET);
+            self = self.register_encoded_file_descriptor_set(reflection_service);
         }
 
         for encoded in &self.encoded_file_descriptor_sets {
@@ -140,18 +163,10 @@ impl<'b> Builder<'b> {
             }
         }
 
-        let service_names = self
-            .service_names
-            .iter()
-            .map(|name| ServiceResponse { name: name.clone() })
-            .collect();
-
-        Ok(ServerReflectionServer::new(ReflectionService {
-            state: Arc::new(ReflectionServiceState {
-                service_names,
-                files,
-                symbols: self.symbols,
-            }),
+        Ok(Arc::new(ReflectionServiceState {
+            service_names: self.service_names,
+            files,
+            symbols: self.symbols,
         }))
     }
 
@@ -262,53 +277,65 @@ fn extract_name(
 
 #[derive(Debug)]
 struct ReflectionServiceState {
-    service_names: Vec<ServiceResponse>,
+    service_names: Vec<String>,
     files: HashMap<String, Arc<FileDescriptorProto>>,
     symbols: HashMap<String, Arc<FileDescriptorProto>>,
 }
 
 impl ReflectionServiceState {
-    fn list_services(&self) -> MessageResponse {
-        MessageResponse::ListServicesResponse(ListServiceResponse {
-            service: self.service_names.clone(),
-        })
-    }
-
-    fn symbol_by_name(&self, symbol: &str) -> Result<MessageResponse, Status> {
+    fn symbol_by_name(&self, symbol: &str) -> Result<Vec<u8>, Status> {
         match self.symbols.get(symbol) {
             None => Err(Status::not_found(format!("symbol '{}' not found", symbol))),
-            Some(fd) => {
-                let mut encoded_fd = Vec::new();
-                if fd.clone().encode(&mut encoded_fd).is_err() {
-                    return Err(Status::internal("encoding error"));
-                };
-
-                Ok(MessageResponse::FileDescriptorResponse(
-                    FileDescriptorResponse {
-                        file_descriptor_proto: vec![encoded_fd],
-                    },
-                ))
-            }
+            Some(fd) => encode(fd),
         }
     }
 
-    fn file_by_filename(&self, filename: &str) -> Result<MessageResponse, Status> {
+    fn file_by_filename(&self, filename: &str) -> Result<Vec<u8>, Status> {
         match self.files.get(filename) {
             None => Err(Status::not_found(format!("file '{}' not found", filename))),
-            Some(fd) => {
-                let mut encoded_fd = Vec::new();
-                if fd.clone().encode(&mut encoded_fd).is_err() {
-                    return Err(Status::internal("encoding error"));
+            Some(fd) => encode(fd),
+        }
+    }
+}
+
+fn encode(fd: &FileDescriptorProto) -> Result<Vec<u8>, Status> {
+    let mut encoded_fd = Vec::new();
+    if fd.encode(&mut encoded_fd).is_err() {
+        return Err(Status::internal("encoding error"));
+    }
+    Ok(encoded_fd)
+}
+
+/// Answers each request on `requests` with `respond`, until the stream or a response fails.
+fn spawn_responder<Req, Res>(
+    state: Arc<ReflectionServiceState>,
+    mut requests: Streaming<Req>,
+    respond: fn(&ReflectionServiceState, Req) -> Result<Res, Status>,
+) -> ReceiverStream<Result<Res, Status>>
+where
+    Req: Send + 'static,
+    Res: Send + 'static,
+{
+    let (resp_tx, resp_rx) = mpsc::channel::<Result<Res, Status>>(1);
+
+    tokio::spawn(async move {
+        while let Some(req) = requests.next().await {
+            let req = match req {
+                Ok(req) => req,
+                Err(_) => {
+                    return;
                 }
+            };
 
-                Ok(MessageResponse::FileDescriptorResponse(
-                    FileDescriptorResponse {
-                        file_descriptor_proto: vec![encoded_fd],
-                    },
-                ))
+            let resp = respond(&state, req);
+            let failed = resp.is_err();
+            if resp_tx.send(resp).await.is_err() || failed {
+                return;
             }
         }
-    }
+    });
+
+    ReceiverStream::new(resp_rx)
 }
 
 #[derive(Debug)]
@@ -318,62 +345,131 @@ struct ReflectionService {
 
 #[tonic::async_trait]
 impl ServerReflection for ReflectionService {
-    type ServerReflectionInfoStream = ReceiverStream<Result<ServerReflectionResponse, Status>>;
+    type ServerReflectionInfoStream =
+        ReceiverStream<Result<v1alpha::ServerReflectionResponse, Status>>;
 
     async fn server_reflection_info(
         &self,
-        req: Request<Streaming<ServerReflectionRequest>>,
+        req: Request<Streaming<v1alpha::ServerReflectionRequest>>,
     ) -> Result<Response<Self::ServerReflectionInfoStream>, Status> {
-        let mut req_rx = req.into_inner();
-        let (resp_tx, resp_rx) = mpsc::channel::<Result<ServerReflectionResponse, Status>>(1);
-
-        let state = self.state.clone();
+        Ok(Response::new(spawn_responder(
+            self.state.clone(),
+            req.into_inner(),
+            respond_v1alpha,
+        )))
+    }
+}
 
-        tokio::spawn(async move {
-            while let Some(req) = req_rx.next().await {
-                let req = match req {
-                    Ok(req) => req,
-                    Err(_) => {
-                        return;
-                    }
-                };
+fn respond_v1alpha(
+    state: &ReflectionServiceState,
+    req: v1alpha::ServerReflectionRequest,
+) -> Result<v1alpha::ServerReflectionResponse, Status> {
+    use v1alpha::{
+        server_reflection_request::MessageRequest, server_reflection_response::MessageResponse,
+        ExtensionNumberResponse, FileDescriptorResponse, ListServiceResponse, ServiceResponse,
+    };
+
+    let file_descriptor = |fd| {
+        MessageResponse::FileDescriptorResponse(FileDescriptorResponse {
+            file_descriptor_proto: vec![fd],
+        })
+    };
+
+    let resp_msg = match req.message_request.clone() {
+        None => return Err(Status::invalid_argument("invalid MessageRequest")),
+        Some(msg) => match msg {
+            MessageRequest::FileByFilename(s) => file_descriptor(state.file_by_filename(&s)?),
+            MessageRequest::FileContainingSymbol(s) => file_descriptor(state.symbol_by_name(&s)?),
+            MessageRequest::FileContainingExtension(_) => {
+                return Err(Status::not_found("extensions are not supported"))
+            }
+            MessageRequest::AllExtensionNumbersOfType(_) => {
+                // NOTE: Workaround. Some grpc clients (e.g. grpcurl) expect this method not to fail.
+                // https://github.com/hyperium/tonic/issues/1077
+                MessageResponse::AllExtensionNumbersResponse(ExtensionNumberResponse::default())
+            }
+            MessageRequest::ListServices(_) => {
+                MessageResponse::ListServicesResponse(ListServiceResponse {
+                    service: state
+                        .service_names
+                        .iter()
+                        .map(|name| ServiceResponse { name: name.clone() })
+                        .collect(),
+                })
+            }
+        },
+    };
+
+    Ok(v1alpha::ServerReflectionResponse {
+        valid_host: req.host.clone(),
+        original_request: Some(req),
+        message_response: Some(resp_msg),
+    })
+}
 
-                let resp_msg = match req.message_request.clone() {
-                    None => Err(Status::invalid_argument("invalid MessageRequest")),
-                    Some(msg) => match msg {
-                        MessageRequest::FileByFilename(s) => state.file_by_filename(&s),
-                        MessageRequest::FileContainingSymbol(s) => state.symbol_by_name(&s),
-                        MessageRequest::FileContainingExtension(_) => {
-                            Err(Status::not_found("extensions are not supported"))
-                        }
-                        MessageRequest::AllExtensionNumbersOfType(_) => {
-                            // NOTE: Workaround. Some grpc clients (e.g. grpcurl) expect this method not to fail.
-                            // https://github.com/hyperium/tonic/issues/1077
-                            Ok(MessageResponse::AllExtensionNumbersResponse(
-                                ExtensionNumberResponse::default(),
-                            ))
-                        }
-                        MessageRequest::ListServices(_) => Ok(state.list_services()),
-                    },
-                };
+#[derive(Debug)]
+struct ReflectionServiceV1 {
+    state: Arc<ReflectionServiceState>,
+}
 
-                match resp_msg {
-                    Ok(resp_msg) => {
-                        let resp = ServerReflectionResponse {
-                            valid_host: req.host.clone(),
-                            original_request: Some(req.clone()),
-                            message_response: Some(resp_msg),
-                        };
-                        resp_tx.send(Ok(resp)).await.expect("send");
-                    }
-                    Err(status) => {
-                        resp_tx.send(Err(status)).await.expect("send");
-                        return;
-                    }
-                }
-            }
-        });
+#[tonic::async_trait]
+impl v1::server_reflection_server::ServerReflection for ReflectionServiceV1 {
+    type ServerReflectionInfoStream = ReceiverStream<Result<v1::ServerReflectionResponse, Status>>;
 
-        Ok(Response::new(ReceiverStream::new(resp_rx)))
+    async fn server_reflection_info(
+        &self,
+        req: Request<Streaming<v1::ServerReflectionRequest>>,
+    ) -> Result<Response<Self::ServerReflectionInfoStream>, Status> {
+        Ok(Response::new(spawn_responder(
+            self.state.clone(),
+            req.into_inner(),
+            respond_v1,
+        )))
     }
 }
+
+fn respond_v1(
+    state: &ReflectionServiceState,
+    req: v1::ServerReflectionRequest,
+) -> Result<v1::ServerReflectionResponse, Status> {
+    use v1::{
+        server_reflection_request::MessageRequest, server_reflection_response::MessageResponse,
+        ExtensionNumberResponse, FileDescriptorResponse, ListServiceResponse, ServiceResponse,
+    };
+
+    let file_descriptor = |fd| {
+        MessageResponse::FileDescriptorResponse(FileDescriptorResponse {
+            file_descriptor_proto: vec![fd],
+        })
+    };
+
+    let resp_msg = match req.message_request.clone() {
+        None => return Err(Status::invalid_argument("invalid MessageRequest")),
+        Some(msg) => match msg {
+            MessageRequest::FileByFilename(s) => file_descriptor(state.file_by_filename(&s)?),
+            MessageRequest::FileContainingSymbol(s) => file_descriptor(state.symbol_by_name(&s)?),
+            MessageRequest::FileContainingExtension(_) => {
+                return Err(Status::not_found("extensions are not supported"))
+            }
+            MessageRequest::AllExtensionNumbersOfType(_) => {
+                // See the note in `respond_v1alpha`.
+                MessageResponse::AllExtensionNumbersResponse(ExtensionNumberResponse::default())
+            }
+            MessageRequest::ListServices(_) => {
+                MessageResponse::ListServicesResponse(ListServiceResponse {
+                    service: state
+                        .service_names
+                        .iter()
+                        .map(|name| ServiceResponse { name: name.clone() })
+                        .collect(),
+                })
+            }
+        },
+    };
+
+    Ok(v1::ServerReflectionResponse {
+        valid_host: req.host.clone(),
+        original_request: Some(req),
+        message_response: Some(resp_msg),
+    })
+}
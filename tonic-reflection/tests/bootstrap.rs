@@ -2,21 +2,23 @@ use std::{path::PathBuf, process::Command};
 
 #[test]
 fn bootstrap() {
-    let iface_files = &["proto/reflection.proto"];
-    let dirs = &["proto"];
-
     let out_dir = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"))
         .join("src")
         .join("generated");
 
-    tonic_build::configure()
-        .build_client(true)
-        .build_server(true)
-        .build_transport(false)
-        .file_descriptor_set_path(out_dir.join("reflection_v1alpha1.bin"))
-        .out_dir(&out_dir)
-        .compile(iface_files, dirs)
-        .unwrap();
+    for (proto, descriptor_set) in [
+        ("proto/reflection.proto", "reflection_v1alpha1.bin"),
+        ("proto/reflection_v1.proto", "reflection_v1.bin"),
+    ] {
+        tonic_build::configure()
+            .build_client(true)
+            .build_server(true)
+            .build_transport(false)
+            .file_descriptor_set_path(out_dir.join(descriptor_set))
+            .out_dir(&out_dir)
+            .compile(&[proto], &["proto"])
+            .unwrap();
+    }
 
     let status = Command::new("git")
         .arg("diff")
@@ -147,3 +147,104 @@ async fn make_test_reflection_request(request: ServerReflectionRequest) -> Messa
 
     response
 }
+
+#[tokio::test]
+async fn test_v1_alongside_v1alpha() {
+    use tonic_reflection::pb::v1;
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().expect("SocketAddr parse");
+    let listener = tokio::net::TcpListener::bind(addr).await.expect("bind");
+    let local_addr = format!("http://{}", listener.local_addr().expect("local address"));
+    let jh = tokio::spawn(async move {
+        let v1alpha = Builder::configure().build().unwrap();
+        let v1 = Builder::configure().build_v1().unwrap();
+
+        Server::builder()
+            .add_service(v1alpha)
+            .add_service(v1)
+            .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown_rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    let conn = tonic::transport::Endpoint::new(local_addr)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = v1::server_reflection_client::ServerReflectionClient::new(conn.clone());
+
+    let requests = vec![
+        v1::ServerReflectionRequest {
+            host: "".to_string(),
+            message_request: Some(v1::server_reflection_request::MessageRequest::ListServices(
+                String::new(),
+            )),
+        },
+        v1::ServerReflectionRequest {
+            host: "".to_string(),
+            message_request: Some(
+                v1::server_reflection_request::MessageRequest::FileContainingSymbol(String::from(
+                    "grpc.reflection.v1.ServerReflection",
+                )),
+            ),
+        },
+    ];
+    let mut inbound = client
+        .server_reflection_info(Request::new(stream::iter(requests)))
+        .await
+        .expect("request")
+        .into_inner();
+
+    match inbound.next().await.unwrap().unwrap().message_response {
+        Some(v1::server_reflection_response::MessageResponse::ListServicesResponse(services)) => {
+            assert_eq!(
+                services.service,
+                vec![v1::ServiceResponse {
+                    name: String::from("grpc.reflection.v1.ServerReflection")
+                }]
+            );
+        }
+        other => panic!("Expected a ListServicesResponse variant, got {:?}", other),
+    }
+
+    let mut expected = Vec::new();
+    prost_types::FileDescriptorSet::decode(v1::FILE_DESCRIPTOR_SET)
+        .unwrap()
+        .file[0]
+        .encode(&mut expected)
+        .unwrap();
+    match inbound.next().await.unwrap().unwrap().message_response {
+        Some(v1::server_reflection_response::MessageResponse::FileDescriptorResponse(
+            descriptor,
+        )) => assert_eq!(descriptor.file_descriptor_proto, vec![expected]),
+        other => panic!("Expected a FileDescriptorResponse variant, got {:?}", other),
+    }
+    assert!(inbound.next().await.is_none());
+
+    // The v1alpha service is still served next to it.
+    let mut client = ServerReflectionClient::new(conn);
+    let request = ServerReflectionRequest {
+        host: "".to_string(),
+        message_request: Some(MessageRequest::ListServices(String::new())),
+    };
+    let mut inbound = client
+        .server_reflection_info(Request::new(stream::iter(vec![request])))
+        .await
+        .expect("request")
+        .into_inner();
+    match inbound.next().await.unwrap().unwrap().message_response {
+        Some(MessageResponse::ListServicesResponse(services)) => assert_eq!(
+            services.service,
+            vec![ServiceResponse {
+                name: String::from("grpc.reflection.v1alpha.ServerReflection")
+            }]
+        ),
+        other => panic!("Expected a ListServicesResponse variant, got {:?}", other),
+    }
+
+    shutdown_tx.send(()).expect("send shutdown");
+    jh.await.expect("server shutdown");
+}
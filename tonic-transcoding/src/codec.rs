@@ -0,0 +1,680 @@
+//! Conversion between JSON and encoded protobuf messages, following the proto3 JSON
+//! mapping.
+
+use crate::descriptor::{FieldDescriptor, MessageDescriptor, Pool};
+use base64::Engine as _;
+use bytes::{Buf, BufMut};
+use prost::encoding::{
+    decode_key, decode_varint, encode_key, encode_varint, skip_field, DecodeContext, WireType,
+};
+use prost_types::field_descriptor_proto::Type;
+use serde_json::{Map, Number, Value};
+use std::str::FromStr;
+use tonic::Status;
+
+/// Encodes `object` as a `message`.
+///
+/// Fields may be named by either their proto or their JSON name. Scalar values may also be
+/// given as strings, as they are when they come from the path or the query of a request.
+pub(crate) fn encode(
+    pool: &Pool,
+    message: &MessageDescriptor,
+    object: &Map<String, Value>,
+    buf: &mut Vec<u8>,
+) -> Result<(), Status> {
+    for (name, value) in object {
+        let field = message.field(name).ok_or_else(|| {
+            Status::invalid_argument(format!(
+                "unknown field `{}` in message `{}`",
+                name, message.name
+            ))
+        })?;
+        if value.is_null() {
+            continue;
+        }
+
+        if let Some(entry) = map_entry(pool, field) {
+            let (key_field, value_field) = entry_fields(entry)?;
+            let entries = value
+                .as_object()
+                .ok_or_else(|| invalid_value(field, value))?;
+            for (key, value) in entries {
+                let mut entry = Vec::new();
+                encode_value(pool, key_field, &Value::String(key.clone()), &mut entry)?;
+                if !value.is_null() {
+                    encode_value(pool, value_field, value, &mut entry)?;
+                }
+                encode_length_delimited(field.number, &entry, buf);
+            }
+        } else if field.repeated {
+            let values = value
+                .as_array()
+                .ok_or_else(|| invalid_value(field, value))?;
+            for value in values {
+                encode_value(pool, field, value, buf)?;
+            }
+        } else {
+            encode_value(pool, field, value, buf)?;
+        }
+    }
+    Ok(())
+}
+
+fn encode_value(
+    pool: &Pool,
+    field: &FieldDescriptor,
+    value: &Value,
+    buf: &mut Vec<u8>,
+) -> Result<(), Status> {
+    let invalid = || invalid_value(field, value);
+    let tag = field.number;
+
+    match field.ty {
+        Type::Double => {
+            encode_key(tag, WireType::SixtyFourBit, buf);
+            buf.put_f64_le(float(value).ok_or_else(invalid)?);
+        }
+        Type::Float => {
+            encode_key(tag, WireType::ThirtyTwoBit, buf);
+            buf.put_f32_le(float(value).ok_or_else(invalid)? as f32);
+        }
+        Type::Int32 => {
+            let value: i32 = integer(value).ok_or_else(invalid)?;
+            encode_key(tag, WireType::Varint, buf);
+            encode_varint(value as i64 as u64, buf);
+        }
+        Type::Int64 => {
+            let value: i64 = integer(value).ok_or_else(invalid)?;
+            encode_key(tag, WireType::Varint, buf);
+            encode_varint(value as u64, buf);
+        }
+        Type::Uint32 => {
+            let value: u32 = integer(value).ok_or_else(invalid)?;
+            encode_key(tag, WireType::Varint, buf);
+            encode_varint(u64::from(value), buf);
+        }
+        Type::Uint64 => {
+            let value: u64 = integer(value).ok_or_else(invalid)?;
+            encode_key(tag, WireType::Varint, buf);
+            encode_varint(value, buf);
+        }
+        Type::Sint32 => {
+            let value: i32 = integer(value).ok_or_else(invalid)?;
+            encode_key(tag, WireType::Varint, buf);
+            encode_varint(u64::from(((value << 1) ^ (value >> 31)) as u32), buf);
+        }
+        Type::Sint64 => {
+            let value: i64 = integer(value).ok_or_else(invalid)?;
+            encode_key(tag, WireType::Varint, buf);
+            encode_varint(((value << 1) ^ (value >> 63)) as u64, buf);
+        }
+        Type::Fixed32 => {
+            encode_key(tag, WireType::ThirtyTwoBit, buf);
+            buf.put_u32_le(integer(value).ok_or_else(invalid)?);
+        }
+        Type::Fixed64 => {
+            encode_key(tag, WireType::SixtyFourBit, buf);
+            buf.put_u64_le(integer(value).ok_or_else(invalid)?);
+        }
+        Type::Sfixed32 => {
+            encode_key(tag, WireType::ThirtyTwoBit, buf);
+            buf.put_i32_le(integer(value).ok_or_else(invalid)?);
+        }
+        Type::Sfixed64 => {
+            encode_key(tag, WireType::SixtyFourBit, buf);
+            buf.put_i64_le(integer(value).ok_or_else(invalid)?);
+        }
+        Type::Bool => {
+            let value = match value {
+                Value::Bool(value) => *value,
+                Value::String(value) => bool::from_str(value).map_err(|_| invalid())?,
+                _ => return Err(invalid()),
+            };
+            encode_key(tag, WireType::Varint, buf);
+            encode_varint(u64::from(value), buf);
+        }
+        Type::String => {
+            let value = value.as_str().ok_or_else(invalid)?;
+            encode_length_delimited(tag, value.as_bytes(), buf);
+        }
+        Type::Bytes => {
+            let value = value.as_str().ok_or_else(invalid)?;
+            let value = base64_decode(value).ok_or_else(invalid)?;
+            encode_length_delimited(tag, &value, buf);
+        }
+        Type::Enum => {
+            let number = match value {
+                Value::String(name) => pool
+                    .enum_type(&field.type_name)
+                    .and_then(|enum_type| {
+                        enum_type
+                            .values
+                            .iter()
+                            .find(|(value, _)| value == name)
+                            .map(|(_, number)| *number)
+                    })
+                    .or_else(|| name.parse().ok()),
+                value => integer(value),
+            };
+            encode_key(tag, WireType::Varint, buf);
+            encode_varint(number.ok_or_else(invalid)? as i64 as u64, buf);
+        }
+        Type::Message => {
+            let message = message_type(pool, field)?;
+            let object = value.as_object().ok_or_else(invalid)?;
+            let mut nested = Vec::new();
+            encode(pool, message, object, &mut nested)?;
+            encode_length_delimited(tag, &nested, buf);
+        }
+        Type::Group => return Err(unsupported(field)),
+    }
+    Ok(())
+}
+
+fn encode_length_delimited(tag: u32, value: &[u8], buf: &mut Vec<u8>) {
+    encode_key(tag, WireType::LengthDelimited, buf);
+    encode_varint(value.len() as u64, buf);
+    buf.extend_from_slice(value);
+}
+
+/// Decodes an encoded `message` to JSON, with fields named by their JSON names.
+///
+/// As in the proto3 JSON mapping, 64 bit integers are written as strings, bytes are base64
+/// encoded, enum values are written by name and fields that are not set are left out.
+pub(crate) fn decode(
+    pool: &Pool,
+    message: &MessageDescriptor,
+    mut buf: &[u8],
+) -> Result<Map<String, Value>, Status> {
+    let mut object = Map::new();
+    while buf.has_remaining() {
+        let (tag, wire_type) = decode_key(&mut buf).map_err(invalid_message)?;
+        let field = match message.field_by_number(tag) {
+            Some(field) => field,
+            None => {
+                skip_field(wire_type, tag, &mut buf, DecodeContext::default())
+                    .map_err(invalid_message)?;
+                continue;
+            }
+        };
+
+        if let Some(entry) = map_entry(pool, field) {
+            let (key_field, value_field) = entry_fields(entry)?;
+            check_wire_type(field, wire_type, WireType::LengthDelimited)?;
+            let mut decoded = decode(pool, entry, length_delimited(&mut buf)?)?;
+            let key = match decoded.remove(&key_field.json_name) {
+                Some(Value::String(key)) => key,
+                Some(key) => key.to_string(),
+                None => match default_value(pool, key_field) {
+                    Value::String(key) => key,
+                    key => key.to_string(),
+                },
+            };
+            let value = decoded
+                .remove(&value_field.json_name)
+                .unwrap_or_else(|| default_value(pool, value_field));
+
+            if let Value::Object(entries) = object
+                .entry(field.json_name.clone())
+                .or_insert_with(|| Value::Object(Map::new()))
+            {
+                entries.insert(key, value);
+            }
+        } else if field.repeated {
+            let mut values = Vec::new();
+            if wire_type == WireType::LengthDelimited && is_packable(field.ty) {
+                let mut packed = length_delimited(&mut buf)?;
+                while packed.has_remaining() {
+                    values.push(decode_value(
+                        pool,
+                        field,
+                        packed_wire_type(field.ty),
+                        &mut packed,
+                    )?);
+                }
+            } else {
+                values.push(decode_value(pool, field, wire_type, &mut buf)?);
+            }
+
+            if let Value::Array(array) = object
+                .entry(field.json_name.clone())
+                .or_insert_with(|| Value::Array(Vec::new()))
+            {
+                array.extend(values);
+            }
+        } else {
+            let value = decode_value(pool, field, wire_type, &mut buf)?;
+            object.insert(field.json_name.clone(), value);
+        }
+    }
+    Ok(object)
+}
+
+fn decode_value(
+    pool: &Pool,
+    field: &FieldDescriptor,
+    wire_type: WireType,
+    buf: &mut &[u8],
+) -> Result<Value, Status> {
+    let value = match field.ty {
+        Type::Int32 | Type::Int64 | Type::Uint32 | Type::Uint64 => {
+            check_wire_type(field, wire_type, WireType::Varint)?;
+            let value = decode_varint(buf).map_err(invalid_message)?;
+            match field.ty {
+                Type::Int32 => Value::from(value as i32),
+                Type::Int64 => Value::String((value as i64).to_string()),
+                Type::Uint32 => Value::from(value as u32),
+                _ => Value::String(value.to_string()),
+            }
+        }
+        Type::Sint32 => {
+            check_wire_type(field, wire_type, WireType::Varint)?;
+            let value = decode_varint(buf).map_err(invalid_message)? as u32;
+            Value::from((value >> 1) as i32 ^ -((value & 1) as i32))
+        }
+        Type::Sint64 => {
+            check_wire_type(field, wire_type, WireType::Varint)?;
+            let value = decode_varint(buf).map_err(invalid_message)?;
+            Value::String(((value >> 1) as i64 ^ -((value & 1) as i64)).to_string())
+        }
+        Type::Bool => {
+            check_wire_type(field, wire_type, WireType::Varint)?;
+            Value::Bool(decode_varint(buf).map_err(invalid_message)? != 0)
+        }
+        Type::Enum => {
+            check_wire_type(field, wire_type, WireType::Varint)?;
+            let number = decode_varint(buf).map_err(invalid_message)? as i32;
+            pool.enum_type(&field.type_name)
+                .and_then(|enum_type| {
+                    enum_type
+                        .values
+                        .iter()
+                        .find(|(_, value)| *value == number)
+                        .map(|(name, _)| Value::String(name.clone()))
+                })
+                .unwrap_or_else(|| Value::from(number))
+        }
+        Type::Fixed32 | Type::Sfixed32 | Type::Float => {
+            check_wire_type(field, wire_type, WireType::ThirtyTwoBit)?;
+            if buf.remaining() < 4 {
+                return Err(invalid_message("buffer underflow"));
+            }
+            match field.ty {
+                Type::Fixed32 => Value::from(buf.get_u32_le()),
+                Type::Sfixed32 => Value::from(buf.get_i32_le()),
+                // Formatting the `f32` first keeps its shortest representation, which
+                // widening it to an `f64` would lose.
+                _ => float_value(f64::from_str(&buf.get_f32_le().to_string()).unwrap()),
+            }
+        }
+        Type::Fixed64 | Type::Sfixed64 | Type::Double => {
+            check_wire_type(field, wire_type, WireType::SixtyFourBit)?;
+            if buf.remaining() < 8 {
+                return Err(invalid_message("buffer underflow"));
+            }
+            match field.ty {
+                Type::Fixed64 => Value::String(buf.get_u64_le().to_string()),
+                Type::Sfixed64 => Value::String(buf.get_i64_le().to_string()),
+                _ => float_value(buf.get_f64_le()),
+            }
+        }
+        Type::String => {
+            check_wire_type(field, wire_type, WireType::LengthDelimited)?;
+            let value = std::str::from_utf8(length_delimited(buf)?).map_err(invalid_message)?;
+            Value::String(value.to_string())
+        }
+        Type::Bytes => {
+            check_wire_type(field, wire_type, WireType::LengthDelimited)?;
+            Value::String(base64::engine::general_purpose::STANDARD.encode(length_delimited(buf)?))
+        }
+        Type::Message => {
+            check_wire_type(field, wire_type, WireType::LengthDelimited)?;
+            let message = message_type(pool, field)?;
+            Value::Object(decode(pool, message, length_delimited(buf)?)?)
+        }
+        Type::Group => return Err(unsupported(field)),
+    };
+    Ok(value)
+}
+
+/// Returns the JSON value of `field` when it is not set.
+pub(crate) fn default_value(pool: &Pool, field: &FieldDescriptor) -> Value {
+    if map_entry(pool, field).is_some() {
+        return Value::Object(Map::new());
+    }
+    if field.repeated {
+        return Value::Array(Vec::new());
+    }
+
+    match field.ty {
+        Type::Int64 | Type::Uint64 | Type::Sint64 | Type::Fixed64 | Type::Sfixed64 => {
+            Value::String("0".to_string())
+        }
+        Type::String | Type::Bytes => Value::String(String::new()),
+        Type::Bool => Value::Bool(false),
+        Type::Enum => pool
+            .enum_type(&field.type_name)
+            .and_then(|enum_type| enum_type.values.iter().find(|(_, number)| *number == 0))
+            .map_or_else(|| Value::from(0), |(name, _)| Value::String(name.clone())),
+        Type::Message | Type::Group => Value::Object(Map::new()),
+        _ => Value::from(0),
+    }
+}
+
+fn map_entry<'a>(pool: &'a Pool, field: &FieldDescriptor) -> Option<&'a MessageDescriptor> {
+    if field.ty != Type::Message || !field.repeated {
+        return None;
+    }
+    pool.message(&field.type_name)
+        .filter(|message| message.map_entry)
+}
+
+fn entry_fields(entry: &MessageDescriptor) -> Result<(&FieldDescriptor, &FieldDescriptor), Status> {
+    match (entry.field_by_number(1), entry.field_by_number(2)) {
+        (Some(key), Some(value)) => Ok((key, value)),
+        _ => Err(Status::internal(format!(
+            "invalid map entry `{}`",
+            entry.name
+        ))),
+    }
+}
+
+fn message_type<'a>(
+    pool: &'a Pool,
+    field: &FieldDescriptor,
+) -> Result<&'a MessageDescriptor, Status> {
+    pool.message(&field.type_name).ok_or_else(|| {
+        Status::internal(format!(
+            "message `{}` is missing from the file descriptor set",
+            field.type_name
+        ))
+    })
+}
+
+fn length_delimited<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], Status> {
+    let len = decode_varint(buf).map_err(invalid_message)?;
+    if len > buf.len() as u64 {
+        return Err(invalid_message("buffer underflow"));
+    }
+    let (value, rest) = buf.split_at(len as usize);
+    *buf = rest;
+    Ok(value)
+}
+
+fn check_wire_type(
+    field: &FieldDescriptor,
+    actual: WireType,
+    expected: WireType,
+) -> Result<(), Status> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(invalid_message(format!(
+            "invalid wire type {:?} for field `{}`",
+            actual, field.name
+        )))
+    }
+}
+
+fn is_packable(ty: Type) -> bool {
+    !matches!(ty, Type::String | Type::Bytes | Type::Message | Type::Group)
+}
+
+fn packed_wire_type(ty: Type) -> WireType {
+    match ty {
+        Type::Fixed32 | Type::Sfixed32 | Type::Float => WireType::ThirtyTwoBit,
+        Type::Fixed64 | Type::Sfixed64 | Type::Double => WireType::SixtyFourBit,
+        _ => WireType::Varint,
+    }
+}
+
+fn integer<T>(value: &Value) -> Option<T>
+where
+    T: TryFrom<i64> + TryFrom<u64> + FromStr,
+{
+    match value {
+        Value::Number(number) => {
+            if let Some(value) = number.as_i64() {
+                T::try_from(value).ok()
+            } else if let Some(value) = number.as_u64() {
+                T::try_from(value).ok()
+            } else {
+                // Integers may also be written with an exponent, such as `1e3`.
+                let value = number.as_f64()?;
+                if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+                    T::try_from(value as i64).ok()
+                } else {
+                    None
+                }
+            }
+        }
+        Value::String(value) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+fn float(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(value) => match value.as_str() {
+            "NaN" => Some(f64::NAN),
+            "Infinity" => Some(f64::INFINITY),
+            "-Infinity" => Some(f64::NEG_INFINITY),
+            value => value.parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+fn float_value(value: f64) -> Value {
+    match Number::from_f64(value) {
+        Some(number) => Value::Number(number),
+        None if value.is_nan() => Value::String("NaN".to_string()),
+        None if value > 0.0 => Value::String("Infinity".to_string()),
+        None => Value::String("-Infinity".to_string()),
+    }
+}
+
+/// Decodes standard or URL-safe base64, with or without padding.
+fn base64_decode(value: &str) -> Option<Vec<u8>> {
+    use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+
+    let value = value.trim_end_matches('=');
+    STANDARD_NO_PAD
+        .decode(value)
+        .or_else(|_| URL_SAFE_NO_PAD.decode(value))
+        .ok()
+}
+
+fn invalid_value(field: &FieldDescriptor, value: &Value) -> Status {
+    Status::invalid_argument(format!(
+        "invalid value for field `{}`: {}",
+        field.name, value
+    ))
+}
+
+fn invalid_message(error: impl std::fmt::Display) -> Status {
+    Status::internal(format!("failed to decode response message: {}", error))
+}
+
+fn unsupported(field: &FieldDescriptor) -> Status {
+    Status::internal(format!(
+        "field `{}` is a group, which is not supported",
+        field.name
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_types::{
+        DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+        FileDescriptorProto, FileDescriptorSet, MessageOptions,
+    };
+    use serde_json::json;
+
+    fn field(name: &str, number: i32, ty: Type, type_name: Option<&str>) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            r#type: Some(ty as i32),
+            type_name: type_name.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    fn repeated(mut field: FieldDescriptorProto) -> FieldDescriptorProto {
+        field.set_label(prost_types::field_descriptor_proto::Label::Repeated);
+        field
+    }
+
+    fn pool() -> Pool {
+        let entry = DescriptorProto {
+            name: Some("LabelsEntry".to_string()),
+            field: vec![
+                field("key", 1, Type::String, None),
+                field("value", 2, Type::Int64, None),
+            ],
+            options: Some(MessageOptions {
+                map_entry: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let user = DescriptorProto {
+            name: Some("User".to_string()),
+            field: vec![
+                field("id", 1, Type::Int64, None),
+                field("display_name", 2, Type::String, None),
+                field("age", 3, Type::Sint32, None),
+                field("score", 4, Type::Double, None),
+                field("avatar", 5, Type::Bytes, None),
+                field("role", 6, Type::Enum, Some(".test.Role")),
+                field("admin", 7, Type::Bool, None),
+                repeated(field("tags", 8, Type::String, None)),
+                repeated(field("scores", 9, Type::Int32, None)),
+                repeated(field(
+                    "labels",
+                    10,
+                    Type::Message,
+                    Some(".test.User.LabelsEntry"),
+                )),
+                field("manager", 11, Type::Message, Some(".test.User")),
+            ],
+            nested_type: vec![entry],
+            ..Default::default()
+        };
+        let role = EnumDescriptorProto {
+            name: Some("Role".to_string()),
+            value: ["ROLE_UNSPECIFIED", "ROLE_ADMIN"]
+                .iter()
+                .zip(0..)
+                .map(|(name, number)| EnumValueDescriptorProto {
+                    name: Some(name.to_string()),
+                    number: Some(number),
+                    options: None,
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        Pool::new(&FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                package: Some("test".to_string()),
+                message_type: vec![user],
+                enum_type: vec![role],
+                ..Default::default()
+            }],
+        })
+    }
+
+    fn round_trip(value: Value) -> Value {
+        let pool = pool();
+        let user = pool.message("test.User").unwrap();
+        let mut buf = Vec::new();
+        encode(&pool, user, value.as_object().unwrap(), &mut buf).unwrap();
+        Value::Object(decode(&pool, user, &buf).unwrap())
+    }
+
+    #[test]
+    fn round_trips_the_proto3_json_mapping() {
+        let user = json!({
+            "id": "-12",
+            "displayName": "Ferris",
+            "age": -3,
+            "score": 1.5,
+            "avatar": "AAEC",
+            "role": "ROLE_ADMIN",
+            "admin": true,
+            "tags": ["a", "b"],
+            "scores": [1, -2],
+            "labels": {"x": "1", "y": "-2"},
+            "manager": {"id": "1"},
+        });
+        assert_eq!(round_trip(user.clone()), user);
+    }
+
+    #[test]
+    fn accepts_proto_names_and_lenient_values() {
+        let user = json!({
+            "id": 12,
+            "display_name": "Ferris",
+            "age": "7",
+            "score": "NaN",
+            "avatar": "_-8",
+            "role": 1,
+            "admin": "false",
+            "scores": ["3"],
+            "manager": null,
+        });
+        assert_eq!(
+            round_trip(user),
+            json!({
+                "id": "12",
+                "displayName": "Ferris",
+                "age": 7,
+                "score": "NaN",
+                "avatar": "/+8=",
+                "role": "ROLE_ADMIN",
+                "admin": false,
+                "scores": [3],
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let pool = pool();
+        let user = pool.message("test.User").unwrap();
+        for value in [
+            json!({"unknown": 1}),
+            json!({"id": 1.5}),
+            json!({"age": "seven"}),
+            json!({"scores": [4294967296u64]}),
+            json!({"role": "ROLE_OWNER"}),
+            json!({"tags": "a"}),
+            json!({"manager": 1}),
+        ] {
+            let status =
+                encode(&pool, user, value.as_object().unwrap(), &mut Vec::new()).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "{}", value);
+        }
+    }
+
+    #[test]
+    fn decodes_packed_fields_and_skips_unknown_ones() {
+        let pool = pool();
+        let user = pool.message("test.User").unwrap();
+        let mut buf = Vec::new();
+        // `scores`, packed.
+        encode_length_delimited(9, &[1, 2], &mut buf);
+        // An unknown field.
+        encode_key(100, WireType::Varint, &mut buf);
+        encode_varint(1, &mut buf);
+
+        assert_eq!(
+            Value::Object(decode(&pool, user, &buf).unwrap()),
+            json!({"scores": [1, 2]})
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! The parts of a `FileDescriptorSet` that transcoding needs.
+
+use crate::pb::HttpRule;
+use crate::Error;
+use prost::Message as _;
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::{DescriptorProto, EnumDescriptorProto, FileDescriptorSet};
+use std::collections::HashMap;
+
+/// The messages and enums of a `FileDescriptorSet`, by their full names.
+#[derive(Debug, Default)]
+pub(crate) struct Pool {
+    messages: HashMap<String, MessageDescriptor>,
+    enums: HashMap<String, EnumDescriptor>,
+}
+
+#[derive(Debug)]
+pub(crate) struct MessageDescriptor {
+    pub(crate) name: String,
+    pub(crate) fields: Vec<FieldDescriptor>,
+    pub(crate) map_entry: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct FieldDescriptor {
+    pub(crate) name: String,
+    pub(crate) json_name: String,
+    pub(crate) number: u32,
+    pub(crate) ty: Type,
+    pub(crate) repeated: bool,
+    /// The full name of the message or enum type of the field, without a leading `.`.
+    pub(crate) type_name: String,
+}
+
+#[derive(Debug)]
+pub(crate) struct EnumDescriptor {
+    pub(crate) values: Vec<(String, i32)>,
+}
+
+impl Pool {
+    pub(crate) fn new(set: &FileDescriptorSet) -> Self {
+        let mut pool = Pool::default();
+        for file in &set.file {
+            let package = file.package();
+            for message in &file.message_type {
+                pool.add_message(package, message);
+            }
+            for enum_type in &file.enum_type {
+                pool.add_enum(package, enum_type);
+            }
+        }
+        pool
+    }
+
+    fn add_message(&mut self, scope: &str, message: &DescriptorProto) {
+        let name = qualify(scope, message.name());
+        for nested in &message.nested_type {
+            self.add_message(&name, nested);
+        }
+        for enum_type in &message.enum_type {
+            self.add_enum(&name, enum_type);
+        }
+
+        let fields = message
+            .field
+            .iter()
+            .map(|field| FieldDescriptor {
+                name: field.name().to_string(),
+                json_name: field
+                    .json_name
+                    .clone()
+                    .unwrap_or_else(|| json_name(field.name())),
+                number: field.number() as u32,
+                ty: field.r#type(),
+                repeated: field.label() == Label::Repeated,
+                type_name: field.type_name().trim_start_matches('.').to_string(),
+            })
+            .collect();
+        let map_entry = matches!(&message.options, Some(options) if options.map_entry());
+
+        self.messages.insert(
+            name.clone(),
+            MessageDescriptor {
+                name,
+                fields,
+                map_entry,
+            },
+        );
+    }
+
+    fn add_enum(&mut self, scope: &str, enum_type: &EnumDescriptorProto) {
+        let values = enum_type
+            .value
+            .iter()
+            .map(|value| (value.name().to_string(), value.number()))
+            .collect();
+        self.enums
+            .insert(qualify(scope, enum_type.name()), EnumDescriptor { values });
+    }
+
+    pub(crate) fn message(&self, name: &str) -> Option<&MessageDescriptor> {
+        self.messages.get(name)
+    }
+
+    pub(crate) fn enum_type(&self, name: &str) -> Option<&EnumDescriptor> {
+        self.enums.get(name)
+    }
+}
+
+impl MessageDescriptor {
+    /// Returns the field called `name`, by either its proto or its JSON name.
+    pub(crate) fn field(&self, name: &str) -> Option<&FieldDescriptor> {
+        self.fields
+            .iter()
+            .find(|field| field.name == name || field.json_name == name)
+    }
+
+    pub(crate) fn field_by_number(&self, number: u32) -> Option<&FieldDescriptor> {
+        self.fields.iter().find(|field| field.number == number)
+    }
+}
+
+fn qualify(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", scope, name)
+    }
+}
+
+/// Converts a field name to lowerCamelCase, the way `protoc` does when it fills in the
+/// `json_name` of a field.
+fn json_name(name: &str) -> String {
+    let mut json_name = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            json_name.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            json_name.push(c);
+        }
+    }
+    json_name
+}
+
+/// A unary method with a `google.api.http` rule.
+#[derive(Debug)]
+pub(crate) struct Method {
+    /// The path the method is served on, such as `/helloworld.Greeter/SayHello`.
+    pub(crate) path: String,
+    pub(crate) input_type: String,
+    pub(crate) output_type: String,
+    pub(crate) rule: HttpRule,
+}
+
+/// Returns the unary methods of the services in `encoded`, an encoded `FileDescriptorSet`,
+/// that have a `google.api.http` rule.
+///
+/// `prost_types` drops the extensions of `MethodOptions`, so the rules are decoded with
+/// messages that only keep the fields needed here.
+pub(crate) fn methods(encoded: &[u8]) -> Result<Vec<Method>, Error> {
+    let set = RawFileDescriptorSet::decode(encoded)?;
+
+    let mut methods = Vec::new();
+    for file in set.file {
+        for service in file.service {
+            let service_name = qualify(&file.package, &service.name);
+            for method in service.method {
+                if method.client_streaming || method.server_streaming {
+                    continue;
+                }
+                let rule = match method.options.and_then(|options| options.http) {
+                    Some(rule) => rule,
+                    None => continue,
+                };
+
+                methods.push(Method {
+                    path: format!("/{}/{}", service_name, method.name),
+                    input_type: method.input_type.trim_start_matches('.').to_string(),
+                    output_type: method.output_type.trim_start_matches('.').to_string(),
+                    rule,
+                });
+            }
+        }
+    }
+    Ok(methods)
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawFileDescriptorSet {
+    #[prost(message, repeated, tag = "1")]
+    file: Vec<RawFileDescriptorProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawFileDescriptorProto {
+    #[prost(string, tag = "2")]
+    package: String,
+    #[prost(message, repeated, tag = "6")]
+    service: Vec<RawServiceDescriptorProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawServiceDescriptorProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(message, repeated, tag = "2")]
+    method: Vec<RawMethodDescriptorProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawMethodDescriptorProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(string, tag = "2")]
+    input_type: String,
+    #[prost(string, tag = "3")]
+    output_type: String,
+    #[prost(message, optional, tag = "4")]
+    options: Option<RawMethodOptions>,
+    #[prost(bool, tag = "5")]
+    client_streaming: bool,
+    #[prost(bool, tag = "6")]
+    server_streaming: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawMethodOptions {
+    /// The `google.api.http` extension.
+    #[prost(message, optional, tag = "72295728")]
+    http: Option<HttpRule>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_names_are_lower_camel_case() {
+        assert_eq!(json_name("name"), "name");
+        assert_eq!(json_name("display_name"), "displayName");
+        assert_eq!(json_name("page_token_2"), "pageToken2");
+    }
+}
@@ -0,0 +1,634 @@
+//! Middleware that transcodes REST/JSON requests onto gRPC methods.
+
+use crate::codec;
+use crate::descriptor::{self, MessageDescriptor, Pool};
+use crate::pb::{http_rule::Pattern, HttpRule};
+use crate::template::PathTemplate;
+use bytes::{BufMut, Bytes};
+use http::{header, HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Uri, Version};
+use http_body::Body as _;
+use hyper::Body;
+use pin_project::pin_project;
+use prost::{DecodeError, Message as _};
+use prost_types::field_descriptor_proto::Type;
+use prost_types::FileDescriptorSet;
+use serde_json::{json, Map, Value};
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tonic::body::BoxBody;
+use tonic::{Code, Status};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Represents an error in the construction of a [`TranscodingLayer`].
+#[derive(Debug)]
+pub enum Error {
+    /// An error was encountered decoding a `prost_types::FileDescriptorSet` from a buffer.
+    DecodeError(prost::DecodeError),
+    /// A `google.api.http` rule is invalid, or refers to something that is not in the
+    /// `FileDescriptorSet`.
+    InvalidRule(String),
+}
+
+impl From<DecodeError> for Error {
+    fn from(e: DecodeError) -> Self {
+        Error::DecodeError(e)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::DecodeError(_) => f.write_str("error decoding FileDescriptorSet from buffer"),
+            Error::InvalidRule(s) => write!(f, "invalid google.api.http rule - {}", s),
+        }
+    }
+}
+
+// The default largest body that is read, the same as the default decoding limit of gRPC
+// messages.
+const DEFAULT_MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+/// A layer that transcodes REST/JSON requests onto the unary methods of a server, following
+/// their `google.api.http` rules.
+#[derive(Debug, Clone)]
+pub struct TranscodingLayer {
+    routes: Arc<Routes>,
+    max_body_size: usize,
+}
+
+impl TranscodingLayer {
+    /// Creates a layer for the methods of the services in an encoded
+    /// `prost_types::FileDescriptorSet`.
+    ///
+    /// The set must include the files the services import, as the one written by
+    /// `tonic_build`'s `file_descriptor_set_path` does.
+    pub fn new(encoded_file_descriptor_set: &[u8]) -> Result<Self, Error> {
+        let pool = Pool::new(&FileDescriptorSet::decode(encoded_file_descriptor_set)?);
+
+        let mut routes = Vec::new();
+        for method in descriptor::methods(encoded_file_descriptor_set)? {
+            let bindings = std::iter::once(&method.rule).chain(&method.rule.additional_bindings);
+            for rule in bindings {
+                let route = Route::new(&pool, &method, rule)
+                    .map_err(|e| Error::InvalidRule(format!("{}: {}", method.path, e)))?;
+                routes.push(route);
+            }
+        }
+
+        Ok(Self {
+            routes: Arc::new(Routes { pool, routes }),
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        })
+    }
+
+    /// Limits the size of the bodies that are read, both of the REST requests and of the gRPC
+    /// responses they are transcoded from.
+    ///
+    /// Requests with a larger body are rejected with `413 Payload Too Large`, and calls with a
+    /// larger response fail with `RESOURCE_EXHAUSTED`.
+    ///
+    /// Default: 4MB
+    pub fn max_body_size(mut self, limit: usize) -> Self {
+        self.max_body_size = limit;
+        self
+    }
+}
+
+impl<S> Layer<S> for TranscodingLayer {
+    type Service = Transcoding<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Transcoding {
+            inner,
+            routes: self.routes.clone(),
+            max_body_size: self.max_body_size,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Routes {
+    pool: Pool,
+    routes: Vec<Route>,
+}
+
+/// An HTTP method and path template, bound to a gRPC method.
+#[derive(Debug)]
+struct Route {
+    /// The HTTP method of the route, or `None` if it matches any method.
+    method: Option<Method>,
+    template: PathTemplate,
+    path: Uri,
+    input_type: String,
+    output_type: String,
+    body: String,
+    response_body: String,
+}
+
+impl Route {
+    fn new(pool: &Pool, method: &descriptor::Method, rule: &HttpRule) -> Result<Self, String> {
+        let (http_method, template) = match &rule.pattern {
+            Some(Pattern::Get(template)) => (Some(Method::GET), template),
+            Some(Pattern::Put(template)) => (Some(Method::PUT), template),
+            Some(Pattern::Post(template)) => (Some(Method::POST), template),
+            Some(Pattern::Delete(template)) => (Some(Method::DELETE), template),
+            Some(Pattern::Patch(template)) => (Some(Method::PATCH), template),
+            Some(Pattern::Custom(custom)) if custom.kind == "*" => (None, &custom.path),
+            Some(Pattern::Custom(custom)) => (
+                Some(
+                    Method::from_bytes(custom.kind.as_bytes())
+                        .map_err(|_| format!("invalid HTTP method `{}`", custom.kind))?,
+                ),
+                &custom.path,
+            ),
+            None => return Err("missing a pattern".to_string()),
+        };
+        let template = PathTemplate::parse(template)
+            .map_err(|e| format!("invalid path template `{}`: {}", template, e))?;
+
+        let message = |name: &str| {
+            pool.message(name)
+                .ok_or_else(|| format!("message `{}` is missing from the set", name))
+        };
+        let input = message(&method.input_type)?;
+        let output = message(&method.output_type)?;
+        for variable in &template.variables {
+            field_path(pool, input, &variable.field_path)?;
+        }
+        if !matches!(rule.body.as_str(), "" | "*") {
+            field_path(pool, input, std::slice::from_ref(&rule.body))?;
+        }
+        if !rule.response_body.is_empty() {
+            field_path(pool, output, std::slice::from_ref(&rule.response_body))?;
+        }
+
+        Ok(Route {
+            method: http_method,
+            template,
+            path: Uri::try_from(&method.path).map_err(|e| e.to_string())?,
+            input_type: method.input_type.clone(),
+            output_type: method.output_type.clone(),
+            body: rule.body.clone(),
+            response_body: rule.response_body.clone(),
+        })
+    }
+
+    /// Builds the request message from the body, the variables of the path and the query.
+    fn request_message(
+        &self,
+        pool: &Pool,
+        uri: &Uri,
+        values: Vec<String>,
+        body: &[u8],
+    ) -> Result<Vec<u8>, Status> {
+        let input = pool.message(&self.input_type).unwrap();
+
+        let mut object = Map::new();
+        if !self.body.is_empty() && !body.is_empty() {
+            let body: Value = serde_json::from_slice(body).map_err(|e| {
+                Status::invalid_argument(format!("invalid JSON request body: {}", e))
+            })?;
+            if self.body == "*" {
+                object = match body {
+                    Value::Object(object) => object,
+                    _ => {
+                        return Err(Status::invalid_argument(
+                            "the request body must be a JSON object",
+                        ))
+                    }
+                };
+            } else {
+                object.insert(self.body.clone(), body);
+            }
+        }
+
+        // With a body of `*`, every field not bound by the path is taken from the body.
+        if self.body != "*" {
+            for (name, value) in query(uri) {
+                let path: Vec<_> = name.split('.').map(String::from).collect();
+                set_field(pool, input, &mut object, &path, value, true)?;
+            }
+        }
+        for (variable, value) in self.template.variables.iter().zip(values) {
+            set_field(pool, input, &mut object, &variable.field_path, value, false)?;
+        }
+
+        let mut buf = Vec::new();
+        codec::encode(pool, input, &object, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Converts the message of a successful response to its JSON body.
+    fn response_body(&self, pool: &Pool, message: &[u8]) -> Result<Value, Status> {
+        let output = pool.message(&self.output_type).unwrap();
+        let mut object = codec::decode(pool, output, message)?;
+
+        if self.response_body.is_empty() {
+            return Ok(Value::Object(object));
+        }
+        let field = output.field(&self.response_body).unwrap();
+        Ok(object
+            .remove(&field.json_name)
+            .unwrap_or_else(|| codec::default_value(pool, field)))
+    }
+}
+
+/// Checks that `path` names a field of `message`, through singular message fields.
+fn field_path<'a>(
+    pool: &'a Pool,
+    mut message: &'a MessageDescriptor,
+    path: &[String],
+) -> Result<(), String> {
+    for (i, name) in path.iter().enumerate() {
+        let field = message
+            .field(name)
+            .ok_or_else(|| format!("no field `{}` in message `{}`", name, message.name))?;
+        if i + 1 < path.len() {
+            message = match pool.message(&field.type_name) {
+                Some(nested) if field.ty == Type::Message && !field.repeated => nested,
+                _ => return Err(format!("field `{}` is not a singular message", name)),
+            };
+        }
+    }
+    Ok(())
+}
+
+/// Sets the field at `path` in `object`, a JSON `message`, to `value`, appending it to the
+/// values of repeated fields if `append` is set.
+fn set_field(
+    pool: &Pool,
+    message: &MessageDescriptor,
+    object: &mut Map<String, Value>,
+    path: &[String],
+    value: String,
+    append: bool,
+) -> Result<(), Status> {
+    let (name, rest) = path.split_first().unwrap();
+    let field = message.field(name).ok_or_else(|| {
+        Status::invalid_argument(format!(
+            "unknown field `{}` in message `{}`",
+            name, message.name
+        ))
+    })?;
+
+    // The body may name the field by either of its names.
+    let json_value = object.remove(&field.json_name);
+    let existing = object.remove(&field.name).or(json_value);
+
+    let value = if !rest.is_empty() {
+        let nested = match pool.message(&field.type_name) {
+            Some(nested) if field.ty == Type::Message && !field.repeated => nested,
+            _ => {
+                return Err(Status::invalid_argument(format!(
+                    "field `{}` is not a singular message",
+                    name
+                )))
+            }
+        };
+        let mut nested_object = match existing {
+            Some(Value::Object(object)) => object,
+            _ => Map::new(),
+        };
+        set_field(pool, nested, &mut nested_object, rest, value, append)?;
+        Value::Object(nested_object)
+    } else if field.repeated {
+        let mut values = match existing {
+            Some(Value::Array(values)) if append => values,
+            _ => Vec::new(),
+        };
+        values.push(Value::String(value));
+        Value::Array(values)
+    } else {
+        Value::String(value)
+    };
+
+    object.insert(field.name.clone(), value);
+    Ok(())
+}
+
+/// Returns the decoded parameters of the query of `uri`.
+fn query(uri: &Uri) -> impl Iterator<Item = (String, String)> + '_ {
+    let decode = |s: &str| {
+        percent_encoding::percent_decode_str(&s.replace('+', " "))
+            .decode_utf8_lossy()
+            .into_owned()
+    };
+
+    uri.query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(move |pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), decode(value))
+        })
+}
+
+/// A service that transcodes REST/JSON requests onto the gRPC methods of its inner service,
+/// created with [`TranscodingLayer`].
+///
+/// Requests that do not match a `google.api.http` rule, and all gRPC requests, are passed
+/// through to the inner service unchanged.
+#[derive(Debug, Clone)]
+pub struct Transcoding<S> {
+    inner: S,
+    routes: Arc<Routes>,
+    max_body_size: usize,
+}
+
+impl<S> Service<Request<Body>> for Transcoding<S>
+where
+    S: Service<Request<Body>, Response = Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let is_grpc = matches!(
+            req.headers().get(header::CONTENT_TYPE),
+            Some(content_type) if content_type.as_bytes().starts_with(b"application/grpc")
+        );
+        let route = self
+            .routes
+            .routes
+            .iter()
+            .enumerate()
+            .filter(|(_, route)| !matches!(&route.method, Some(m) if m != req.method()))
+            .find_map(|(i, route)| Some((i, route.template.matches(req.uri().path())?)));
+
+        match route {
+            Some((index, values)) if !is_grpc => {
+                // The service that was driven to readiness is the one that is called.
+                let clone = self.inner.clone();
+                let inner = std::mem::replace(&mut self.inner, clone);
+                let routes = self.routes.clone();
+                let max_body_size = self.max_body_size;
+
+                ResponseFuture {
+                    kind: Kind::Transcoded(Box::pin(async move {
+                        transcode(routes, index, values, req, inner, max_body_size)
+                            .await
+                            .unwrap_or_else(error_response)
+                    })),
+                }
+            }
+            _ => ResponseFuture {
+                kind: Kind::Inner(self.inner.call(req)),
+            },
+        }
+    }
+}
+
+async fn transcode<S>(
+    routes: Arc<Routes>,
+    index: usize,
+    values: Vec<String>,
+    req: Request<Body>,
+    mut inner: S,
+    max_body_size: usize,
+) -> Result<Response<BoxBody>, Status>
+where
+    S: Service<Request<Body>, Response = Response<BoxBody>>,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let route = &routes.routes[index];
+    let (parts, mut body) = req.into_parts();
+    let body = match read_body(&mut body, max_body_size).await {
+        Ok(body) => body,
+        Err(ReadError::TooLarge) => {
+            let status = Status::resource_exhausted(format!(
+                "the request body is larger than the limit of {} bytes",
+                max_body_size
+            ));
+            let mut response = error_response(status);
+            *response.status_mut() = StatusCode::PAYLOAD_TOO_LARGE;
+            return Ok(response);
+        }
+        Err(ReadError::Body(e)) => {
+            return Err(Status::invalid_argument(format!(
+                "failed to read request body: {}",
+                e
+            )))
+        }
+    };
+    let message = route.request_message(&routes.pool, &parts.uri, values, &body)?;
+
+    let mut framed = Vec::with_capacity(message.len() + 5);
+    framed.put_u8(0);
+    framed.put_u32(message.len() as u32);
+    framed.extend_from_slice(&message);
+
+    let mut request = Request::new(Body::from(framed));
+    *request.method_mut() = Method::POST;
+    *request.uri_mut() = route.path.clone();
+    *request.version_mut() = Version::HTTP_2;
+    *request.headers_mut() = request_headers(parts.headers);
+    *request.extensions_mut() = parts.extensions;
+
+    let response = inner
+        .call(request)
+        .await
+        .map_err(|e| Status::from_error(e.into()))?;
+    let (parts, mut body) = response.into_parts();
+
+    let data = match read_body(&mut body, max_body_size).await {
+        Ok(data) => data,
+        Err(ReadError::TooLarge) => {
+            return Err(Status::resource_exhausted(format!(
+                "the response is larger than the limit of {} bytes",
+                max_body_size
+            )))
+        }
+        Err(ReadError::Body(status)) => return Err(status),
+    };
+    let trailers = body.trailers().await?;
+    // A call that fails before sending a message responds with its status in the headers.
+    let status = trailers
+        .as_ref()
+        .and_then(Status::from_header_map)
+        .or_else(|| Status::from_header_map(&parts.headers))
+        .unwrap_or_else(|| Status::internal("the response is missing a grpc-status"));
+    if status.code() != Code::Ok {
+        return Err(status);
+    }
+
+    let message = match data.first() {
+        Some(0) if data.len() >= 5 && message_len(&data) == data.len() - 5 => &data[5..],
+        Some(1) => return Err(Status::internal("compressed responses are not supported")),
+        _ => {
+            return Err(Status::internal(
+                "the response must contain exactly one message",
+            ))
+        }
+    };
+    let body = route.response_body(&routes.pool, message)?;
+
+    let mut response = Response::new(json_body(&body));
+    *response.headers_mut() = response_headers(parts.headers, trailers);
+    Ok(response)
+}
+
+enum ReadError<E> {
+    TooLarge,
+    Body(E),
+}
+
+/// Reads the data of `body`, unless it is larger than `limit`.
+async fn read_body<B>(body: &mut B, limit: usize) -> Result<Vec<u8>, ReadError<B::Error>>
+where
+    B: http_body::Body<Data = Bytes> + Unpin,
+{
+    if body.size_hint().lower() > limit as u64 {
+        return Err(ReadError::TooLarge);
+    }
+
+    let mut data = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(ReadError::Body)?;
+        if data.len() + chunk.len() > limit {
+            return Err(ReadError::TooLarge);
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok(data)
+}
+
+/// Returns the length of the first message of a gRPC body, from its 5 byte prefix.
+fn message_len(data: &[u8]) -> usize {
+    u32::from_be_bytes([data[1], data[2], data[3], data[4]]) as usize
+}
+
+/// Headers that describe an HTTP/1.1 request or its JSON body, rather than the call itself.
+const REQUEST_HEADERS: &[header::HeaderName] = &[
+    header::ACCEPT,
+    header::ACCEPT_ENCODING,
+    header::CONNECTION,
+    header::CONTENT_LENGTH,
+    header::CONTENT_TYPE,
+    header::HOST,
+    header::TE,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+];
+
+fn request_headers(mut headers: HeaderMap) -> HeaderMap {
+    for name in REQUEST_HEADERS {
+        headers.remove(name);
+    }
+    // Compressed responses are not supported.
+    headers.remove("grpc-accept-encoding");
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/grpc"),
+    );
+    headers.insert(header::TE, HeaderValue::from_static("trailers"));
+    headers
+}
+
+/// Merges the custom metadata of a gRPC response, from its headers and trailers, into the
+/// headers of its JSON response.
+fn response_headers(headers: HeaderMap, trailers: Option<HeaderMap>) -> HeaderMap {
+    let mut merged = HeaderMap::new();
+    let all = headers.into_iter().chain(trailers.into_iter().flatten());
+    let mut last = None;
+    for (name, value) in all {
+        // Later values of the same header have no name of their own.
+        let name = match name {
+            Some(name) => last.insert(name).clone(),
+            None => last.clone().unwrap(),
+        };
+        if name == header::CONTENT_TYPE
+            || name == header::CONTENT_LENGTH
+            || name.as_str().starts_with("grpc-")
+        {
+            continue;
+        }
+        merged.append(name, value);
+    }
+    merged.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    merged
+}
+
+/// Converts a status to a JSON response, with the HTTP status code the status is mapped to
+/// by `google.rpc.Code`.
+fn error_response(status: Status) -> Response<BoxBody> {
+    let http_status = match status.code() {
+        Code::Ok => StatusCode::OK,
+        Code::Cancelled => StatusCode::from_u16(499).unwrap(),
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+            StatusCode::BAD_REQUEST
+        }
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+
+    let body = json!({
+        "code": status.code() as i32,
+        "message": status.message(),
+    });
+    let mut response = Response::new(json_body(&body));
+    *response.status_mut() = http_status;
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+fn json_body(value: &Value) -> BoxBody {
+    let body = Bytes::from(serde_json::to_vec(value).unwrap());
+    http_body::Full::new(body)
+        .map_err(|err| match err {})
+        .boxed_unsync()
+}
+
+/// Response future for [`Transcoding`].
+#[allow(missing_debug_implementations)]
+#[pin_project]
+pub struct ResponseFuture<F> {
+    #[pin]
+    kind: Kind<F>,
+}
+
+#[pin_project(project = KindProj)]
+enum Kind<F> {
+    Inner(#[pin] F),
+    Transcoded(Pin<Box<dyn Future<Output = Response<BoxBody>> + Send>>),
+}
+
+impl<F, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<BoxBody>, E>>,
+{
+    type Output = Result<Response<BoxBody>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().kind.project() {
+            KindProj::Inner(future) => future.poll(cx),
+            KindProj::Transcoded(future) => future.as_mut().poll(cx).map(Ok),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! A `tonic` based transcoder of REST/JSON requests onto gRPC methods.
+//!
+//! The methods of a service can be annotated with `google.api.http` rules, which map REST
+//! requests such as `GET /v1/users/42` onto them:
+//!
+//! ```proto
+//! import "google/api/annotations.proto";
+//!
+//! service Users {
+//!   rpc GetUser(GetUserRequest) returns (User) {
+//!     option (google.api.http) = { get: "/v1/users/{id}" };
+//!   }
+//! }
+//! ```
+//!
+//! A [`TranscodingLayer`] serves those requests from the same server as the gRPC service:
+//! it builds the request message from the path, the query and the JSON body of a request,
+//! calls the method, and converts its response, or its status, back to JSON. The rules are
+//! read from the encoded `FileDescriptorSet` of the services, such as the one written by
+//! `tonic_build`'s `file_descriptor_set_path`, which must also include
+//! `google/api/http.proto` and `google/api/annotations.proto`. Copies of them can be found
+//! in the `proto` directory of this crate.
+//!
+//! Messages are converted with the [proto3 JSON mapping], with the exception of the
+//! well-known types, such as `google.protobuf.Timestamp`, which are converted like any other
+//! message. Only unary methods are transcoded.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # mod pb { pub const FILE_DESCRIPTOR_SET: &[u8] = &[]; }
+//! # async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! # let users = tonic_health::server::health_reporter().1;
+//! use tonic::transport::Server;
+//! use tonic_transcoding::TranscodingLayer;
+//!
+//! Server::builder()
+//!     // REST clients use HTTP/1.1.
+//!     .accept_http1(true)
+//!     .layer(TranscodingLayer::new(pb::FILE_DESCRIPTOR_SET)?)
+//!     .add_service(users)
+//!     .serve("[::1]:50051".parse()?)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [proto3 JSON mapping]: https://protobuf.dev/programming-guides/proto3/#json
+
+#![warn(
+    missing_debug_implementations,
+    missing_docs,
+    rust_2018_idioms,
+    unreachable_pub
+)]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/tokio-rs/website/master/public/img/icons/tonic.svg"
+)]
+#![deny(rustdoc::broken_intra_doc_links)]
+#![doc(html_root_url = "https://docs.rs/tonic-transcoding/0.1.0")]
+#![doc(issue_tracker_base_url = "https://github.com/hyperium/tonic/issues/")]
+#![doc(test(no_crate_inject, attr(deny(rust_2018_idioms))))]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+/// Generated protobuf types from the `google.api` package.
+pub mod pb {
+    #![allow(unreachable_pub)]
+    #![allow(missing_docs)]
+    include!("generated/google.api.rs");
+}
+
+mod codec;
+mod descriptor;
+pub mod layer;
+mod template;
+
+pub use layer::{Error, TranscodingLayer};
@@ -0,0 +1,234 @@
+//! The path templates of `google.api.http` rules.
+
+use percent_encoding::percent_decode_str;
+
+/// A parsed path template, such as `/v1/{name=shelves/*/books/*}:publish`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct PathTemplate {
+    segments: Vec<Segment>,
+    verb: Option<String>,
+    pub(crate) variables: Vec<Variable>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    /// `*`, which matches a single segment.
+    Single,
+    /// `**`, which matches the rest of the path.
+    Rest,
+}
+
+/// A variable of a path template, which binds the segments it matches to a field of the
+/// request message.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Variable {
+    /// The field the variable binds to, split on `.`.
+    pub(crate) field_path: Vec<String>,
+    start: usize,
+    end: usize,
+}
+
+impl PathTemplate {
+    pub(crate) fn parse(template: &str) -> Result<Self, String> {
+        let path = template
+            .strip_prefix('/')
+            .ok_or_else(|| "path templates must start with `/`".to_string())?;
+
+        // The verb follows the last segment, which may be a variable containing `/`.
+        let mut verb_start = None;
+        let mut depth = 0;
+        for (i, c) in path.char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                '/' if depth == 0 => verb_start = None,
+                ':' if depth == 0 && verb_start.is_none() => verb_start = Some(i),
+                _ => {}
+            }
+        }
+        let (mut rest, verb) = match verb_start {
+            Some(i) => (&path[..i], Some(path[i + 1..].to_string())),
+            None => (path, None),
+        };
+
+        let mut segments = Vec::new();
+        let mut variables = Vec::new();
+        loop {
+            if let Some(variable) = rest.strip_prefix('{') {
+                let end = variable
+                    .find('}')
+                    .ok_or_else(|| "unclosed variable".to_string())?;
+                let (field_path, template) = match variable[..end].split_once('=') {
+                    Some((field_path, template)) => (field_path, template),
+                    None => (&variable[..end], "*"),
+                };
+                if field_path.split('.').any(str::is_empty) {
+                    return Err(format!("invalid field path `{}`", field_path));
+                }
+
+                let start = segments.len();
+                for segment in template.split('/') {
+                    segments.push(Segment::parse(segment)?);
+                }
+                variables.push(Variable {
+                    field_path: field_path.split('.').map(String::from).collect(),
+                    start,
+                    end: segments.len(),
+                });
+                rest = &variable[end + 1..];
+            } else {
+                let end = rest.find('/').unwrap_or(rest.len());
+                segments.push(Segment::parse(&rest[..end])?);
+                rest = &rest[end..];
+            }
+
+            if rest.is_empty() {
+                break;
+            }
+            rest = rest
+                .strip_prefix('/')
+                .ok_or_else(|| "variables must be followed by `/`".to_string())?;
+        }
+
+        if let Some(i) = segments.iter().position(|s| *s == Segment::Rest) {
+            if i != segments.len() - 1 {
+                return Err("`**` must be the last segment".to_string());
+            }
+        }
+
+        Ok(PathTemplate {
+            segments,
+            verb,
+            variables,
+        })
+    }
+
+    /// Matches `path` against this template, returning the values of its variables in
+    /// order if it matches.
+    pub(crate) fn matches(&self, path: &str) -> Option<Vec<String>> {
+        let path = path.strip_prefix('/')?;
+        let path = match &self.verb {
+            Some(verb) => path.strip_suffix(verb.as_str())?.strip_suffix(':')?,
+            None => path,
+        };
+        let parts: Vec<&str> = path.split('/').collect();
+
+        match self.segments.last() {
+            Some(Segment::Rest) if parts.len() + 1 >= self.segments.len() => {}
+            _ if parts.len() == self.segments.len() => {}
+            _ => return None,
+        }
+        for (segment, part) in self.segments.iter().zip(&parts) {
+            match segment {
+                Segment::Literal(literal) if literal != part => return None,
+                Segment::Single if part.is_empty() => return None,
+                _ => {}
+            }
+        }
+
+        let values = self
+            .variables
+            .iter()
+            .map(|variable| {
+                let end = if variable.end == self.segments.len() {
+                    parts.len()
+                } else {
+                    variable.end
+                };
+                parts[variable.start.min(end)..end]
+                    .iter()
+                    .map(|part| percent_decode_str(part).decode_utf8_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .collect();
+        Some(values)
+    }
+}
+
+impl Segment {
+    fn parse(segment: &str) -> Result<Self, String> {
+        match segment {
+            "*" => Ok(Segment::Single),
+            "**" => Ok(Segment::Rest),
+            "" => Err("empty segment".to_string()),
+            _ if segment.contains(['{', '}', '=', '*']) => {
+                Err(format!("invalid segment `{}`", segment))
+            }
+            _ => Ok(Segment::Literal(segment.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(template: &str, path: &str) -> Option<Vec<String>> {
+        PathTemplate::parse(template).unwrap().matches(path)
+    }
+
+    #[test]
+    fn literals_and_variables() {
+        assert_eq!(
+            matches("/v1/users/{id}", "/v1/users/42"),
+            Some(vec!["42".into()])
+        );
+        assert_eq!(matches("/v1/users/{id}", "/v1/users"), None);
+        assert_eq!(matches("/v1/users/{id}", "/v1/users/"), None);
+        assert_eq!(matches("/v1/users/{id}", "/v1/users/42/posts"), None);
+        assert_eq!(matches("/v1/users/{id}", "/v2/users/42"), None);
+        assert_eq!(
+            matches("/v1/users/{id}", "/v1/users/a%20b"),
+            Some(vec!["a b".into()])
+        );
+    }
+
+    #[test]
+    fn variables_with_templates() {
+        let template = PathTemplate::parse("/v1/{name=shelves/*/books/*}").unwrap();
+        assert_eq!(template.variables[0].field_path, vec!["name"]);
+        assert_eq!(
+            template.matches("/v1/shelves/1/books/2"),
+            Some(vec!["shelves/1/books/2".into()])
+        );
+        assert_eq!(template.matches("/v1/shelves/1/records/2"), None);
+
+        assert_eq!(
+            matches("/v1/{book.name=**}", "/v1/a/b/c"),
+            Some(vec!["a/b/c".into()])
+        );
+        assert_eq!(matches("/v1/{book.name=**}", "/v1"), Some(vec!["".into()]));
+        assert_eq!(
+            matches("/v1/{parent=*}/books/{id}", "/v1/a/books/b"),
+            Some(vec!["a".into(), "b".into()])
+        );
+    }
+
+    #[test]
+    fn verbs() {
+        assert_eq!(
+            matches("/v1/{name=messages/*}:publish", "/v1/messages/1:publish"),
+            Some(vec!["messages/1".into()])
+        );
+        assert_eq!(
+            matches("/v1/{name=messages/*}:publish", "/v1/messages/1"),
+            None
+        );
+        assert_eq!(
+            matches("/v1/messages:batchGet", "/v1/messages:batchGet"),
+            Some(vec![])
+        );
+    }
+
+    #[test]
+    fn invalid_templates() {
+        assert!(PathTemplate::parse("v1/users").is_err());
+        assert!(PathTemplate::parse("/v1//users").is_err());
+        assert!(PathTemplate::parse("/v1/{id").is_err());
+        assert!(PathTemplate::parse("/v1/**/users").is_err());
+        assert!(PathTemplate::parse("/v1/{name=a/{id}}").is_err());
+        assert!(PathTemplate::parse("/v1/{id}x").is_err());
+    }
+}
@@ -0,0 +1,29 @@
+use std::{path::PathBuf, process::Command};
+
+#[test]
+fn bootstrap() {
+    let iface_files = &["proto/google/api/http.proto"];
+    let dirs = &["proto"];
+
+    let out_dir = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join("generated");
+
+    tonic_build::configure()
+        .build_client(false)
+        .build_server(false)
+        .build_transport(false)
+        .out_dir(&out_dir)
+        .compile(iface_files, dirs)
+        .unwrap();
+
+    let status = Command::new("git")
+        .arg("diff")
+        .arg("--exit-code")
+        .arg("--")
+        .arg(&out_dir)
+        .status()
+        .unwrap();
+
+    assert!(status.success(), "You should commit the protobuf files");
+}
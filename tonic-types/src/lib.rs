@@ -4,9 +4,22 @@
 //! [`tonic::Status`], allowing the implementation of the
 //! [gRPC Richer Error Model] with [`tonic`] in a convenient way.
 //!
+//! The [`AnyExt`], [`TimestampExt`] and [`DurationExt`] traits, and
+//! `StructExt` with the `json` feature, add conversions to the
+//! `google.protobuf` well-known types from `prost-types`.
+//!
+//! # Feature Flags
+//!
+//! - `chrono`: Enables conversions between `Timestamp`/`Duration` and the
+//!   [`chrono`] types. Not enabled by default.
+//! - `json`: Enables `StructExt`, converting between `Struct` and
+//!   [`serde_json`] objects. Not enabled by default.
+//!
 //! [`tonic::Status`]: https://docs.rs/tonic/latest/tonic/struct.Status.html
 //! [`tonic`]: https://docs.rs/tonic/latest/tonic/
 //! [gRPC Richer Error Model]: https://www.grpc.io/docs/guides/error/
+//! [`chrono`]: https://docs.rs/chrono
+//! [`serde_json`]: https://docs.rs/serde_json
 
 #![warn(
     missing_debug_implementations,
@@ -46,11 +59,18 @@ pub use pb::Status;
 mod richer_error;
 
 pub use richer_error::{
-    BadRequest, DebugInfo, ErrorDetail, ErrorDetails, ErrorInfo, FieldViolation,
-    PreconditionFailure, PreconditionViolation, QuotaFailure, QuotaViolation, RequestInfo,
-    ResourceInfo, RetryInfo, StatusExt,
+    BadRequest, DebugInfo, ErrorDetail, ErrorDetails, ErrorInfo, FieldViolation, Help, HelpLink,
+    LocalizedMessage, PreconditionFailure, PreconditionViolation, QuotaFailure, QuotaViolation,
+    RequestInfo, ResourceInfo, RetryInfo, StatusExt,
 };
 
+mod well_known;
+
+pub use well_known::{AnyExt, DurationExt, TimestampExt};
+
+#[cfg(feature = "json")]
+pub use well_known::StructExt;
+
 mod sealed {
     #[allow(unreachable_pub)]
     pub trait Sealed {}
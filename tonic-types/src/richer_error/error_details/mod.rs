@@ -1,8 +1,9 @@
 use std::{collections::HashMap, time};
 
 use super::std_messages::{
-    BadRequest, DebugInfo, ErrorInfo, FieldViolation, PreconditionFailure, PreconditionViolation,
-    QuotaFailure, QuotaViolation, RequestInfo, ResourceInfo, RetryInfo,
+    BadRequest, DebugInfo, ErrorInfo, FieldViolation, Help, HelpLink, LocalizedMessage,
+    PreconditionFailure, PreconditionViolation, QuotaFailure, QuotaViolation, RequestInfo,
+    ResourceInfo, RetryInfo,
 };
 
 pub(crate) mod vec;
@@ -37,6 +38,12 @@ pub struct ErrorDetails {
 
     /// This field stores [`ResourceInfo`] data, if any.
     pub(crate) resource_info: Option<ResourceInfo>,
+
+    /// This field stores [`Help`] data, if any.
+    pub(crate) help: Option<Help>,
+
+    /// This field stores [`LocalizedMessage`] data, if any.
+    pub(crate) localized_message: Option<LocalizedMessage>,
 }
 
 impl ErrorDetails {
@@ -311,6 +318,66 @@ impl ErrorDetails {
         }
     }
 
+    /// Generates an [`ErrorDetails`] struct with [`Help`] details and
+    /// remaining fields set to `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::{ErrorDetails, HelpLink};
+    ///
+    /// let err_details = ErrorDetails::with_help(vec![
+    ///     HelpLink::new("description of link a", "resource-a.example.local"),
+    ///     HelpLink::new("description of link b", "resource-b.example.local"),
+    /// ]);
+    /// ```
+    pub fn with_help(links: Vec<HelpLink>) -> Self {
+        ErrorDetails {
+            help: Some(Help::new(links)),
+            ..ErrorDetails::new()
+        }
+    }
+
+    /// Generates an [`ErrorDetails`] struct with [`Help`] details (one
+    /// [`HelpLink`] set) and remaining fields set to `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::ErrorDetails;
+    ///
+    /// let err_details = ErrorDetails::with_help_link(
+    ///     "description of link a",
+    ///     "resource-a.example.local"
+    /// );
+    /// ```
+    pub fn with_help_link(description: impl Into<String>, url: impl Into<String>) -> Self {
+        ErrorDetails {
+            help: Some(Help::with_link(description, url)),
+            ..ErrorDetails::new()
+        }
+    }
+
+    /// Generates an [`ErrorDetails`] struct with [`LocalizedMessage`] details
+    /// and remaining fields set to `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::ErrorDetails;
+    ///
+    /// let err_details = ErrorDetails::with_localized_message(
+    ///     "en-US",
+    ///     "message for the user"
+    /// );
+    /// ```
+    pub fn with_localized_message(locale: impl Into<String>, message: impl Into<String>) -> Self {
+        ErrorDetails {
+            localized_message: Some(LocalizedMessage::new(locale, message)),
+            ..ErrorDetails::new()
+        }
+    }
+
     /// Get [`RetryInfo`] details, if any.
     pub fn retry_info(&self) -> Option<RetryInfo> {
         self.retry_info.clone()
@@ -351,6 +418,16 @@ impl ErrorDetails {
         self.resource_info.clone()
     }
 
+    /// Get [`Help`] details, if any.
+    pub fn help(&self) -> Option<Help> {
+        self.help.clone()
+    }
+
+    /// Get [`LocalizedMessage`] details, if any.
+    pub fn localized_message(&self) -> Option<LocalizedMessage> {
+        self.localized_message.clone()
+    }
+
     /// Set [`RetryInfo`] details. Can be chained with other `.set_` and
     /// `.add_` [`ErrorDetails`] methods.
     ///
@@ -706,4 +783,97 @@ impl ErrorDetails {
         ));
         self
     }
+
+    /// Set [`Help`] details. Can be chained with other `.set_` and `.add_`
+    /// [`ErrorDetails`] methods.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::{ErrorDetails, HelpLink};
+    ///
+    /// let mut err_details = ErrorDetails::new();
+    ///
+    /// err_details.set_help(vec![
+    ///     HelpLink::new("description of link a", "resource-a.example.local"),
+    ///     HelpLink::new("description of link b", "resource-b.example.local"),
+    /// ]);
+    /// ```
+    pub fn set_help(&mut self, links: Vec<HelpLink>) -> &mut Self {
+        self.help = Some(Help::new(links));
+        self
+    }
+
+    /// Adds a [`HelpLink`] to [`Help`] details. Sets [`Help`] details if it is
+    /// not set yet. Can be chained with other `.set_` and `.add_`
+    /// [`ErrorDetails`] methods.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::ErrorDetails;
+    ///
+    /// let mut err_details = ErrorDetails::new();
+    ///
+    /// err_details.add_help_link("description of link", "resource.example.local");
+    /// ```
+    pub fn add_help_link(
+        &mut self,
+        description: impl Into<String>,
+        url: impl Into<String>,
+    ) -> &mut Self {
+        match &mut self.help {
+            Some(help) => {
+                help.add_link(description, url);
+            }
+            None => {
+                self.help = Some(Help::with_link(description, url));
+            }
+        };
+        self
+    }
+
+    /// Returns `true` if [`Help`] is set and its `links` vector is not empty,
+    /// otherwise returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::ErrorDetails;
+    ///
+    /// let mut err_details = ErrorDetails::with_help(vec![]);
+    ///
+    /// assert_eq!(err_details.has_help_links(), false);
+    ///
+    /// err_details.add_help_link("description of link", "resource.example.local");
+    ///
+    /// assert_eq!(err_details.has_help_links(), true);
+    /// ```
+    pub fn has_help_links(&self) -> bool {
+        if let Some(help) = &self.help {
+            return !help.links.is_empty();
+        }
+        false
+    }
+
+    /// Set [`LocalizedMessage`] details. Can be chained with other `.set_` and
+    /// `.add_` [`ErrorDetails`] methods.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::ErrorDetails;
+    ///
+    /// let mut err_details = ErrorDetails::new();
+    ///
+    /// err_details.set_localized_message("en-US", "message for the user");
+    /// ```
+    pub fn set_localized_message(
+        &mut self,
+        locale: impl Into<String>,
+        message: impl Into<String>,
+    ) -> &mut Self {
+        self.localized_message = Some(LocalizedMessage::new(locale, message));
+        self
+    }
 }
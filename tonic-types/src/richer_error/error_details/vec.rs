@@ -1,6 +1,6 @@
 use super::super::std_messages::{
-    BadRequest, DebugInfo, ErrorInfo, PreconditionFailure, QuotaFailure, RequestInfo, ResourceInfo,
-    RetryInfo,
+    BadRequest, DebugInfo, ErrorInfo, Help, LocalizedMessage, PreconditionFailure, QuotaFailure,
+    RequestInfo, ResourceInfo, RetryInfo,
 };
 
 /// Wraps the structs corresponding to the standard error messages, allowing
@@ -31,6 +31,12 @@ pub enum ErrorDetail {
 
     /// Wraps the [`ResourceInfo`] struct.
     ResourceInfo(ResourceInfo),
+
+    /// Wraps the [`Help`] struct.
+    Help(Help),
+
+    /// Wraps the [`LocalizedMessage`] struct.
+    LocalizedMessage(LocalizedMessage),
 }
 
 impl From<RetryInfo> for ErrorDetail {
@@ -80,3 +86,15 @@ impl From<ResourceInfo> for ErrorDetail {
         ErrorDetail::ResourceInfo(err_detail)
     }
 }
+
+impl From<Help> for ErrorDetail {
+    fn from(err_detail: Help) -> Self {
+        ErrorDetail::Help(err_detail)
+    }
+}
+
+impl From<LocalizedMessage> for ErrorDetail {
+    fn from(err_detail: LocalizedMessage) -> Self {
+        ErrorDetail::LocalizedMessage(err_detail)
+    }
+}
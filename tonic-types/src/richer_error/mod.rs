@@ -12,8 +12,9 @@ use super::pb;
 
 pub use error_details::{vec::ErrorDetail, ErrorDetails};
 pub use std_messages::{
-    BadRequest, DebugInfo, ErrorInfo, FieldViolation, PreconditionFailure, PreconditionViolation,
-    QuotaFailure, QuotaViolation, RequestInfo, ResourceInfo, RetryInfo,
+    BadRequest, DebugInfo, ErrorInfo, FieldViolation, Help, HelpLink, LocalizedMessage,
+    PreconditionFailure, PreconditionViolation, QuotaFailure, QuotaViolation, RequestInfo,
+    ResourceInfo, RetryInfo,
 };
 
 trait IntoAny {
@@ -424,6 +425,50 @@ pub trait StatusExt: crate::sealed::Sealed {
     /// }
     /// ```
     fn get_details_resource_info(&self) -> Option<ResourceInfo>;
+
+    /// Get first [`Help`] details found on `tonic::Status`, if any. If some
+    /// `prost::DecodeError` occurs, returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic::{Status, Response};
+    /// use tonic_types::StatusExt;
+    ///
+    /// fn handle_request_result<T>(req_result: Result<Response<T>, Status>) {
+    ///     match req_result {
+    ///         Ok(_) => {},
+    ///         Err(status) => {
+    ///             if let Some(help) = status.get_details_help() {
+    ///                 // Handle help details
+    ///             }
+    ///         }
+    ///     };
+    /// }
+    /// ```
+    fn get_details_help(&self) -> Option<Help>;
+
+    /// Get first [`LocalizedMessage`] details found on `tonic::Status`, if
+    /// any. If some `prost::DecodeError` occurs, returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic::{Status, Response};
+    /// use tonic_types::StatusExt;
+    ///
+    /// fn handle_request_result<T>(req_result: Result<Response<T>, Status>) {
+    ///     match req_result {
+    ///         Ok(_) => {},
+    ///         Err(status) => {
+    ///             if let Some(localized_message) = status.get_details_localized_message() {
+    ///                 // Handle localized_message details
+    ///             }
+    ///         }
+    ///     };
+    /// }
+    /// ```
+    fn get_details_localized_message(&self) -> Option<LocalizedMessage>;
 }
 
 impl crate::sealed::Sealed for tonic::Status {}
@@ -471,6 +516,14 @@ impl StatusExt for tonic::Status {
             conv_details.push(resource_info.into_any());
         }
 
+        if let Some(help) = details.help {
+            conv_details.push(help.into_any());
+        }
+
+        if let Some(localized_message) = details.localized_message {
+            conv_details.push(localized_message.into_any());
+        }
+
         let details = gen_details_bytes(code, &message, conv_details);
 
         tonic::Status::with_details_and_metadata(code, message, details, metadata)
@@ -516,6 +569,12 @@ impl StatusExt for tonic::Status {
                 ErrorDetail::ResourceInfo(res_info) => {
                     conv_details.push(res_info.into_any());
                 }
+                ErrorDetail::Help(help) => {
+                    conv_details.push(help.into_any());
+                }
+                ErrorDetail::LocalizedMessage(loc_message) => {
+                    conv_details.push(loc_message.into_any());
+                }
             }
         }
 
@@ -568,6 +627,12 @@ impl StatusExt for tonic::Status {
                 ResourceInfo::TYPE_URL => {
                     details.resource_info = Some(ResourceInfo::from_any(any)?);
                 }
+                Help::TYPE_URL => {
+                    details.help = Some(Help::from_any(any)?);
+                }
+                LocalizedMessage::TYPE_URL => {
+                    details.localized_message = Some(LocalizedMessage::from_any(any)?);
+                }
                 _ => {}
             }
         }
@@ -610,6 +675,12 @@ impl StatusExt for tonic::Status {
                 ResourceInfo::TYPE_URL => {
                     details.push(ResourceInfo::from_any(any)?.into());
                 }
+                Help::TYPE_URL => {
+                    details.push(Help::from_any(any)?.into());
+                }
+                LocalizedMessage::TYPE_URL => {
+                    details.push(LocalizedMessage::from_any(any)?.into());
+                }
                 _ => {}
             }
         }
@@ -732,6 +803,34 @@ impl StatusExt for tonic::Status {
 
         None
     }
+
+    fn get_details_help(&self) -> Option<Help> {
+        let status = pb::Status::decode(self.details()).ok()?;
+
+        for any in status.details.into_iter() {
+            if any.type_url.as_str() == Help::TYPE_URL {
+                if let Ok(detail) = Help::from_any(any) {
+                    return Some(detail);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn get_details_localized_message(&self) -> Option<LocalizedMessage> {
+        let status = pb::Status::decode(self.details()).ok()?;
+
+        for any in status.details.into_iter() {
+            if any.type_url.as_str() == LocalizedMessage::TYPE_URL {
+                if let Ok(detail) = LocalizedMessage::from_any(any) {
+                    return Some(detail);
+                }
+            }
+        }
+
+        None
+    }
 }
 
 #[cfg(test)]
@@ -740,8 +839,8 @@ mod tests {
     use tonic::{Code, Status};
 
     use super::{
-        BadRequest, DebugInfo, ErrorDetails, ErrorInfo, PreconditionFailure, QuotaFailure,
-        RequestInfo, RetryInfo, StatusExt,
+        BadRequest, DebugInfo, ErrorDetails, ErrorInfo, Help, LocalizedMessage,
+        PreconditionFailure, QuotaFailure, RequestInfo, RetryInfo, StatusExt,
     };
 
     #[test]
@@ -761,7 +860,9 @@ mod tests {
             .set_error_info("SOME_INFO", "example.local", metadata.clone())
             .add_precondition_failure_violation("TOS", "example.local", "description")
             .add_bad_request_violation("field", "description")
-            .set_request_info("request-id", "some-request-data");
+            .set_request_info("request-id", "some-request-data")
+            .add_help_link("link to resource", "resource.example.local")
+            .set_localized_message("en-US", "message for the user");
 
         let fmt_details = format!("{:?}", err_details);
 
@@ -777,6 +878,8 @@ mod tests {
             PreconditionFailure::with_violation("TOS", "example.local", "description").into(),
             BadRequest::with_violation("field", "description").into(),
             RequestInfo::new("request-id", "some-request-data").into(),
+            Help::with_link("link to resource", "resource.example.local").into(),
+            LocalizedMessage::new("en-US", "message for the user").into(),
         ];
 
         let fmt_details_vec = format!("{:?}", err_details_vec);
@@ -823,4 +926,53 @@ mod tests {
             "Extracted details vec differs from original details vec"
         );
     }
+
+    #[test]
+    fn gen_status_with_help_and_localized_message() {
+        let status = Status::with_error_details_vec(
+            Code::Unavailable,
+            "service is down for maintenance",
+            vec![
+                Help::with_link("status page", "https://status.example.local").into(),
+                LocalizedMessage::new("en-US", "we will be back soon").into(),
+            ],
+        );
+
+        let help = match status.get_details_help() {
+            Some(help) => help,
+            None => panic!("Help details missing from status"),
+        };
+
+        assert!(
+            format!("{:?}", help).eq("Help { links: [HelpLink { description: \"status page\", url: \"https://status.example.local\" }] }"),
+            "Extracted Help differs from original Help"
+        );
+
+        let loc_message = match status.get_details_localized_message() {
+            Some(loc_message) => loc_message,
+            None => panic!("LocalizedMessage details missing from status"),
+        };
+
+        assert!(
+            format!("{:?}", loc_message)
+                .eq("LocalizedMessage { locale: \"en-US\", message: \"we will be back soon\" }"),
+            "Extracted LocalizedMessage differs from original LocalizedMessage"
+        );
+
+        let ext_details = match status.check_error_details() {
+            Ok(ext_details) => ext_details,
+            Err(err) => panic!("Error extracting details struct from status: {:?}", err),
+        };
+
+        assert!(
+            format!("{:?}", ext_details.help()).eq(&format!("{:?}", Some(help))),
+            "Help in extracted details struct differs from original Help"
+        );
+
+        assert!(
+            format!("{:?}", ext_details.localized_message())
+                .eq(&format!("{:?}", Some(loc_message))),
+            "LocalizedMessage in extracted details struct differs from original LocalizedMessage"
+        );
+    }
 }
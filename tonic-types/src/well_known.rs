@@ -0,0 +1,367 @@
+use std::time::SystemTime;
+
+use prost::{DecodeError, Message};
+use prost_types::{Any, Duration, Timestamp};
+
+#[cfg(feature = "json")]
+use prost_types::{value::Kind, ListValue, Struct, Value};
+
+const TYPE_URL_PREFIX: &str = "type.googleapis.com/";
+
+/// Returns the part of a type URL after its last `/`, which is the fully
+/// qualified name of the message type.
+fn type_name(type_url: &str) -> &str {
+    type_url
+        .rsplit_once('/')
+        .map_or(type_url, |(_, type_name)| type_name)
+}
+
+/// Used to pack messages into and unpack them from `google.protobuf.Any`.
+///
+/// Message types are identified by their fully qualified protobuf name, like
+/// `my.package.MyMessage`, or by a full type URL.
+///
+/// ```
+/// use tonic_types::{AnyExt, RetryInfo};
+/// use prost_types::{Any, Duration};
+///
+/// let delay = Duration { seconds: 5, nanos: 0 };
+/// let any = Any::pack(&delay, "google.protobuf.Duration");
+///
+/// assert!(any.is("google.protobuf.Duration"));
+/// assert_eq!(any.unpack::<Duration>("google.protobuf.Duration").unwrap(), Some(delay));
+/// assert_eq!(any.unpack::<Duration>(RetryInfo::TYPE_URL).unwrap(), None);
+/// ```
+pub trait AnyExt: crate::sealed::Sealed {
+    /// Encodes `message` into an `Any` holding the given message type.
+    ///
+    /// A type name without a `/` is prefixed with `type.googleapis.com/`.
+    fn pack<M: Message>(message: &M, type_name: &str) -> Any;
+
+    /// Returns `true` if the `Any` holds a message of the given type.
+    fn is(&self, type_name: &str) -> bool;
+
+    /// Decodes the message held by the `Any`. Returns `Ok(None)` if the `Any`
+    /// holds a message of another type.
+    fn unpack<M: Message + Default>(&self, type_name: &str) -> Result<Option<M>, DecodeError>;
+}
+
+impl crate::sealed::Sealed for Any {}
+
+impl AnyExt for Any {
+    fn pack<M: Message>(message: &M, type_name: &str) -> Any {
+        let type_url = if type_name.contains('/') {
+            type_name.to_string()
+        } else {
+            format!("{}{}", TYPE_URL_PREFIX, type_name)
+        };
+
+        Any {
+            type_url,
+            value: message.encode_to_vec(),
+        }
+    }
+
+    fn is(&self, type_name: &str) -> bool {
+        self::type_name(&self.type_url) == self::type_name(type_name)
+    }
+
+    fn unpack<M: Message + Default>(&self, type_name: &str) -> Result<Option<M>, DecodeError> {
+        if !self.is(type_name) {
+            return Ok(None);
+        }
+
+        M::decode(&self.value[..]).map(Some)
+    }
+}
+
+/// Conversions between `google.protobuf.Timestamp` and the standard library
+/// and [`chrono`] time types.
+///
+/// `prost_types::Timestamp` also implements `From<SystemTime>`.
+///
+/// [`chrono`]: https://docs.rs/chrono
+pub trait TimestampExt: crate::sealed::Sealed {
+    /// Returns the current time as a `Timestamp`.
+    fn now() -> Timestamp;
+
+    /// Converts the `Timestamp` to a [`SystemTime`]. Returns `None` if it is
+    /// out of the range `SystemTime` supports on this platform.
+    fn to_system_time(&self) -> Option<SystemTime>;
+
+    /// Converts a `chrono::DateTime` in any time zone to a `Timestamp`.
+    #[cfg(feature = "chrono")]
+    fn from_chrono<Tz: chrono::TimeZone>(date_time: &chrono::DateTime<Tz>) -> Timestamp;
+
+    /// Converts the `Timestamp` to a `chrono::DateTime<Utc>`. Returns `None`
+    /// if it is out of the range `chrono` supports.
+    #[cfg(feature = "chrono")]
+    fn to_chrono(&self) -> Option<chrono::DateTime<chrono::Utc>>;
+}
+
+impl crate::sealed::Sealed for Timestamp {}
+
+impl TimestampExt for Timestamp {
+    fn now() -> Timestamp {
+        SystemTime::now().into()
+    }
+
+    fn to_system_time(&self) -> Option<SystemTime> {
+        SystemTime::try_from(self.clone()).ok()
+    }
+
+    #[cfg(feature = "chrono")]
+    fn from_chrono<Tz: chrono::TimeZone>(date_time: &chrono::DateTime<Tz>) -> Timestamp {
+        let mut timestamp = Timestamp {
+            seconds: date_time.timestamp(),
+            nanos: date_time.timestamp_subsec_nanos() as i32,
+        };
+        // Leap seconds are represented with more than a second worth of nanoseconds.
+        timestamp.normalize();
+        timestamp
+    }
+
+    #[cfg(feature = "chrono")]
+    fn to_chrono(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let mut timestamp = self.clone();
+        timestamp.normalize();
+        chrono::DateTime::from_timestamp(timestamp.seconds, timestamp.nanos as u32)
+    }
+}
+
+/// Conversions between `google.protobuf.Duration` and the standard library
+/// and [`chrono`] duration types.
+///
+/// `prost_types::Duration` also implements `TryFrom<std::time::Duration>`.
+///
+/// [`chrono`]: https://docs.rs/chrono
+pub trait DurationExt: crate::sealed::Sealed {
+    /// Converts the `Duration` to a [`std::time::Duration`]. Returns `None` if
+    /// it is negative.
+    fn to_std(&self) -> Option<std::time::Duration>;
+
+    /// Converts a `chrono::TimeDelta` to a `Duration`.
+    #[cfg(feature = "chrono")]
+    fn from_chrono(delta: chrono::TimeDelta) -> Duration;
+
+    /// Converts the `Duration` to a `chrono::TimeDelta`. Returns `None` if it
+    /// is out of the range `chrono` supports.
+    #[cfg(feature = "chrono")]
+    fn to_chrono(&self) -> Option<chrono::TimeDelta>;
+}
+
+impl crate::sealed::Sealed for Duration {}
+
+impl DurationExt for Duration {
+    fn to_std(&self) -> Option<std::time::Duration> {
+        std::time::Duration::try_from(self.clone()).ok()
+    }
+
+    #[cfg(feature = "chrono")]
+    fn from_chrono(delta: chrono::TimeDelta) -> Duration {
+        // In both, the seconds and nanoseconds share the sign of the duration.
+        Duration {
+            seconds: delta.num_seconds(),
+            nanos: delta.subsec_nanos(),
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    fn to_chrono(&self) -> Option<chrono::TimeDelta> {
+        let mut duration = self.clone();
+        duration.normalize();
+        chrono::TimeDelta::try_seconds(duration.seconds)?
+            .checked_add(&chrono::TimeDelta::nanoseconds(duration.nanos.into()))
+    }
+}
+
+/// Conversions between `google.protobuf.Struct` and [`serde_json`] objects.
+///
+/// Numbers are converted to `f64`, and non-finite numbers to `null`, as
+/// `google.protobuf.Value` can't represent anything else.
+///
+/// [`serde_json`]: https://docs.rs/serde_json
+#[cfg(feature = "json")]
+pub trait StructExt: crate::sealed::Sealed {
+    /// Converts a JSON object to a `Struct`.
+    fn from_json(object: serde_json::Map<String, serde_json::Value>) -> Struct;
+
+    /// Converts the `Struct` to a JSON object.
+    fn into_json(self) -> serde_json::Map<String, serde_json::Value>;
+}
+
+#[cfg(feature = "json")]
+impl crate::sealed::Sealed for Struct {}
+
+#[cfg(feature = "json")]
+impl StructExt for Struct {
+    fn from_json(object: serde_json::Map<String, serde_json::Value>) -> Struct {
+        Struct {
+            fields: object
+                .into_iter()
+                .map(|(key, value)| (key, value_from_json(value)))
+                .collect(),
+        }
+    }
+
+    fn into_json(self) -> serde_json::Map<String, serde_json::Value> {
+        self.fields
+            .into_iter()
+            .map(|(key, value)| (key, value_into_json(value)))
+            .collect()
+    }
+}
+
+#[cfg(feature = "json")]
+fn value_from_json(value: serde_json::Value) -> Value {
+    let kind = match value {
+        serde_json::Value::Null => Kind::NullValue(0),
+        serde_json::Value::Bool(value) => Kind::BoolValue(value),
+        serde_json::Value::Number(value) => match value.as_f64() {
+            Some(value) => Kind::NumberValue(value),
+            None => Kind::NullValue(0),
+        },
+        serde_json::Value::String(value) => Kind::StringValue(value),
+        serde_json::Value::Array(values) => Kind::ListValue(ListValue {
+            values: values.into_iter().map(value_from_json).collect(),
+        }),
+        serde_json::Value::Object(object) => Kind::StructValue(Struct::from_json(object)),
+    };
+
+    Value { kind: Some(kind) }
+}
+
+#[cfg(feature = "json")]
+fn value_into_json(value: Value) -> serde_json::Value {
+    match value.kind {
+        None | Some(Kind::NullValue(_)) => serde_json::Value::Null,
+        Some(Kind::BoolValue(value)) => serde_json::Value::Bool(value),
+        Some(Kind::NumberValue(value)) => serde_json::Number::from_f64(value)
+            .map_or(serde_json::Value::Null, serde_json::Value::Number),
+        Some(Kind::StringValue(value)) => serde_json::Value::String(value),
+        Some(Kind::ListValue(list)) => {
+            serde_json::Value::Array(list.values.into_iter().map(value_into_json).collect())
+        }
+        Some(Kind::StructValue(object)) => serde_json::Value::Object(object.into_json()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::{Any, Duration, Timestamp};
+
+    use super::{AnyExt, DurationExt, TimestampExt};
+    use crate::RetryInfo;
+
+    #[test]
+    fn pack_and_unpack_any() {
+        let duration = Duration {
+            seconds: 3,
+            nanos: 500,
+        };
+
+        let any = Any::pack(&duration, "google.protobuf.Duration");
+        assert_eq!(any.type_url, "type.googleapis.com/google.protobuf.Duration");
+        assert!(any.is("google.protobuf.Duration"));
+        assert!(any.is("example.com/google.protobuf.Duration"));
+        assert!(!any.is("google.protobuf.Timestamp"));
+
+        let unpacked = any.unpack::<Duration>("google.protobuf.Duration");
+        assert_eq!(unpacked.unwrap(), Some(duration));
+
+        let other = any.unpack::<Duration>(RetryInfo::TYPE_URL);
+        assert_eq!(other.unwrap(), None);
+
+        let any = Any::pack(
+            &Timestamp::default(),
+            "example.com/google.protobuf.Timestamp",
+        );
+        assert_eq!(any.type_url, "example.com/google.protobuf.Timestamp");
+    }
+
+    #[test]
+    fn unpack_any_with_invalid_value() {
+        let any = Any {
+            type_url: "type.googleapis.com/google.protobuf.Duration".to_string(),
+            value: vec![0xff],
+        };
+
+        assert!(any.unpack::<Duration>("google.protobuf.Duration").is_err());
+    }
+
+    #[test]
+    fn std_conversions() {
+        let duration = Duration {
+            seconds: 1,
+            nanos: 5,
+        };
+        assert_eq!(duration.to_std(), Some(std::time::Duration::new(1, 5)));
+
+        let negative = Duration {
+            seconds: -1,
+            nanos: 0,
+        };
+        assert_eq!(negative.to_std(), None);
+
+        let timestamp = Timestamp {
+            seconds: 10,
+            nanos: 0,
+        };
+        assert_eq!(
+            timestamp.to_system_time(),
+            Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(10))
+        );
+        assert!(Timestamp::now().seconds > 0);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_conversions() {
+        let date_time = chrono::DateTime::from_timestamp(1_600_000_000, 123).unwrap();
+        let timestamp = Timestamp::from_chrono(&date_time);
+        assert_eq!(
+            timestamp,
+            Timestamp {
+                seconds: 1_600_000_000,
+                nanos: 123
+            }
+        );
+        assert_eq!(timestamp.to_chrono(), Some(date_time));
+
+        let delta = chrono::TimeDelta::milliseconds(-1_500);
+        let duration = Duration::from_chrono(delta);
+        assert_eq!(
+            duration,
+            Duration {
+                seconds: -1,
+                nanos: -500_000_000
+            }
+        );
+        assert_eq!(duration.to_chrono(), Some(delta));
+
+        let too_long = Duration {
+            seconds: i64::MAX,
+            nanos: 0,
+        };
+        assert_eq!(too_long.to_chrono(), None);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn struct_json_conversions() {
+        use super::StructExt;
+        use prost_types::Struct;
+
+        let json = serde_json::json!({
+            "name": "tonic",
+            "stars": 7.0,
+            "tags": ["grpc", null, true],
+            "nested": { "empty": {} },
+        });
+        let object = json.as_object().unwrap().clone();
+
+        let proto = Struct::from_json(object.clone());
+        assert_eq!(proto.fields.len(), 4);
+        assert_eq!(proto.into_json(), object);
+    }
+}
@@ -5,7 +5,7 @@ use std::task::{Context, Poll};
 use base64::Engine as _;
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use futures_core::{ready, Stream};
-use http::{header, HeaderMap, HeaderValue};
+use http::{header, header::HeaderName, HeaderMap, HeaderValue};
 use http_body::{Body, SizeHint};
 use pin_project::pin_project;
 use tonic::Status;
@@ -42,8 +42,12 @@ const GRPC_WEB_TRAILERS_BIT: u8 = 0b10000000;
 
 #[derive(Copy, Clone, PartialEq, Debug)]
 enum Direction {
+    // A grpc-web request received by a server.
     Request,
+    // A response of a server to a grpc-web request.
     Response,
+    // A grpc-web response received by a client.
+    ClientResponse,
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -52,14 +56,19 @@ pub(crate) enum Encoding {
     None,
 }
 
+/// A body translated between gRPC and grpc-web.
 #[pin_project]
-pub(crate) struct GrpcWebCall<B> {
+#[derive(Debug)]
+pub struct GrpcWebCall<B> {
     #[pin]
     inner: B,
     buf: BytesMut,
     direction: Direction,
     encoding: Encoding,
     poll_trailers: bool,
+    // The bytes left of the data frame that is being passed through.
+    frame_remaining: usize,
+    trailers: Option<HeaderMap>,
 }
 
 impl<B> GrpcWebCall<B> {
@@ -71,6 +80,10 @@ impl<B> GrpcWebCall<B> {
         Self::new(inner, Direction::Response, encoding)
     }
 
+    pub(crate) fn client_response(inner: B) -> Self {
+        Self::new(inner, Direction::ClientResponse, Encoding::None)
+    }
+
     fn new(inner: B, direction: Direction, encoding: Encoding) -> Self {
         GrpcWebCall {
             inner,
@@ -81,6 +94,8 @@ impl<B> GrpcWebCall<B> {
             direction,
             encoding,
             poll_trailers: true,
+            frame_remaining: 0,
+            trailers: None,
         }
     }
 
@@ -173,13 +188,68 @@ where
                     *this.poll_trailers = false;
                     Poll::Ready(Some(Ok(frame.into())))
                 }
-                Ok(None) => Poll::Ready(None),
+                Ok(None) => {
+                    *this.poll_trailers = false;
+                    Poll::Ready(None)
+                }
                 Err(e) => Poll::Ready(Some(Err(internal_error(e)))),
             };
         }
 
         Poll::Ready(None)
     }
+
+    // Passes data frames through as they are, until the trailers frame, which is kept for
+    // `poll_trailers`.
+    fn poll_decode_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<B::Data, Status>>> {
+        let mut this = self.as_mut().project();
+
+        loop {
+            if !*this.poll_trailers {
+                return Poll::Ready(None);
+            }
+
+            if *this.frame_remaining > 0 && this.buf.has_remaining() {
+                let len = (*this.frame_remaining).min(this.buf.len());
+                *this.frame_remaining -= len;
+                return Poll::Ready(Some(Ok(this.buf.split_to(len).freeze())));
+            }
+
+            if *this.frame_remaining == 0 && this.buf.len() >= FRAME_HEADER_SIZE {
+                let len = u32::from_be_bytes([this.buf[1], this.buf[2], this.buf[3], this.buf[4]])
+                    as usize;
+
+                if this.buf[0] & GRPC_WEB_TRAILERS_BIT == 0 {
+                    *this.frame_remaining = FRAME_HEADER_SIZE + len;
+                    continue;
+                }
+
+                if this.buf.len() >= FRAME_HEADER_SIZE + len {
+                    this.buf.advance(FRAME_HEADER_SIZE);
+                    let trailers = this.buf.split_to(len);
+                    *this.trailers = Some(decode_trailers_frame(&trailers)?);
+                    *this.poll_trailers = false;
+                    continue;
+                }
+            }
+
+            match ready!(this.inner.as_mut().poll_data(cx)) {
+                Some(Ok(data)) => this.buf.put(data),
+                Some(Err(e)) => return Poll::Ready(Some(Err(internal_error(e)))),
+                None => {
+                    *this.poll_trailers = false;
+                    return if this.buf.has_remaining() || *this.frame_remaining > 0 {
+                        Poll::Ready(Some(Err(internal_error("malformed grpc-web response"))))
+                    } else {
+                        Poll::Ready(None)
+                    };
+                }
+            }
+        }
+    }
 }
 
 impl<B> Body for GrpcWebCall<B>
@@ -197,6 +267,7 @@ where
         match self.direction {
             Direction::Request => self.poll_decode(cx),
             Direction::Response => self.poll_encode(cx),
+            Direction::ClientResponse => self.poll_decode_trailers(cx),
         }
     }
 
@@ -204,15 +275,28 @@ where
         self: Pin<&mut Self>,
         _: &mut Context<'_>,
     ) -> Poll<Result<Option<HeaderMap<HeaderValue>>, Self::Error>> {
-        Poll::Ready(Ok(None))
+        Poll::Ready(Ok(self.project().trailers.take()))
     }
 
     fn is_end_stream(&self) -> bool {
-        self.inner.is_end_stream()
+        match (self.direction, self.encoding) {
+            (Direction::Request, Encoding::None) => self.inner.is_end_stream(),
+            (Direction::Request, Encoding::Base64) => {
+                self.inner.is_end_stream() && self.buf.is_empty()
+            }
+            // The trailers of the inner body are sent as the last frame of this one, so it
+            // only ends once they have been polled.
+            (Direction::Response, _) => !self.poll_trailers,
+            (Direction::ClientResponse, _) => !self.poll_trailers && self.trailers.is_none(),
+        }
     }
 
     fn size_hint(&self) -> SizeHint {
-        self.inner.size_hint()
+        match (self.direction, self.encoding) {
+            (Direction::Request, Encoding::None) => self.inner.size_hint(),
+            // Base64 and the trailers frame change the length of the body.
+            _ => SizeHint::default(),
+        }
     }
 }
 
@@ -267,6 +351,39 @@ fn encode_trailers(trailers: HeaderMap) -> Vec<u8> {
     })
 }
 
+// The inverse of `encode_trailers`, which also accepts `\n` line endings and names that
+// are not lowercase.
+fn decode_trailers_frame(trailers: &[u8]) -> Result<HeaderMap, Status> {
+    let mut map = HeaderMap::new();
+
+    for line in trailers.split(|b| *b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+
+        let colon = line
+            .iter()
+            .position(|b| *b == b':')
+            .ok_or_else(|| internal_error("malformed grpc-web trailers"))?;
+        let (name, value) = line.split_at(colon);
+        let name = HeaderName::from_bytes(&name.to_ascii_lowercase()).map_err(internal_error)?;
+        let value = &value[1..];
+        let start = value
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(value.len());
+        let end = value
+            .iter()
+            .rposition(|b| !b.is_ascii_whitespace())
+            .map_or(start, |end| end + 1);
+        let value = HeaderValue::from_bytes(&value[start..end]).map_err(internal_error)?;
+        map.append(name, value);
+    }
+
+    Ok(map)
+}
+
 fn make_trailers_frame(trailers: HeaderMap) -> Vec<u8> {
     let trailers = encode_trailers(trailers);
     let len = trailers.len();
@@ -284,6 +401,129 @@ fn make_trailers_frame(trailers: HeaderMap) -> Vec<u8> {
 mod tests {
     use super::*;
 
+    /// A body of one chunk and then trailers, that knows its exact length.
+    struct Sized(Option<Bytes>, Option<HeaderMap>);
+
+    impl Body for Sized {
+        type Data = Bytes;
+        type Error = Status;
+
+        fn poll_data(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Option<Result<Bytes, Status>>> {
+            Poll::Ready(self.0.take().map(Ok))
+        }
+
+        fn poll_trailers(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Result<Option<HeaderMap>, Status>> {
+            Poll::Ready(Ok(self.1.take()))
+        }
+
+        fn is_end_stream(&self) -> bool {
+            self.0.is_none()
+        }
+
+        fn size_hint(&self) -> SizeHint {
+            SizeHint::with_exact(self.0.as_ref().map_or(0, |data| data.len() as u64))
+        }
+    }
+
+    fn sized() -> Sized {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", HeaderValue::from_static("0"));
+        Sized(Some(Bytes::from_static(b"hello")), Some(trailers))
+    }
+
+    async fn data<B: Body<Data = Bytes> + Unpin>(body: &mut B) -> Option<Bytes>
+    where
+        B::Error: std::fmt::Debug,
+    {
+        body.data().await.map(Result::unwrap)
+    }
+
+    #[tokio::test]
+    async fn response_ends_after_the_trailers_frame() {
+        let mut body = GrpcWebCall::response(sized(), Encoding::None);
+        assert_eq!(Body::size_hint(&body).exact(), None);
+
+        assert_eq!(data(&mut body).await.unwrap(), "hello");
+        // The inner body ended, but its trailers have not been sent yet.
+        assert!(!body.is_end_stream());
+
+        assert_eq!(
+            data(&mut body).await.unwrap(),
+            &b"\x80\0\0\0\x0fgrpc-status:0\r\n"[..]
+        );
+        assert!(body.is_end_stream());
+        assert_eq!(data(&mut body).await, None);
+    }
+
+    /// A body that yields its chunks one at a time.
+    struct Chunks(Vec<&'static [u8]>);
+
+    impl Body for Chunks {
+        type Data = Bytes;
+        type Error = Status;
+
+        fn poll_data(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Option<Result<Bytes, Status>>> {
+            if self.0.is_empty() {
+                return Poll::Ready(None);
+            }
+            Poll::Ready(Some(Ok(Bytes::from_static(self.0.remove(0)))))
+        }
+
+        fn poll_trailers(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Result<Option<HeaderMap>, Status>> {
+            Poll::Ready(Ok(None))
+        }
+    }
+
+    #[tokio::test]
+    async fn client_response_moves_trailers_frame_to_trailers() {
+        let mut body = GrpcWebCall::client_response(Chunks(vec![
+            b"\0\0\0",
+            b"\0\x02h",
+            b"i\x80\0\0",
+            b"\0\x26grpc-status: 3\r\n",
+            b"Grpc-Message:bad input\n",
+        ]));
+
+        let mut data = BytesMut::new();
+        while let Some(chunk) = body.data().await {
+            data.put(chunk.unwrap());
+        }
+        assert_eq!(&data[..], b"\0\0\0\0\x02hi");
+
+        let trailers = body.trailers().await.unwrap().unwrap();
+        assert_eq!(trailers["grpc-status"], "3");
+        assert_eq!(trailers["grpc-message"], "bad input");
+    }
+
+    #[tokio::test]
+    async fn client_response_rejects_truncated_frames() {
+        let mut body = GrpcWebCall::client_response(Chunks(vec![b"\0\0\0\0\x05hi"]));
+
+        assert_eq!(body.data().await.unwrap().unwrap(), "\0\0\0\0\x05hi");
+        assert!(body.data().await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn text_response_has_no_exact_size() {
+        let mut body = GrpcWebCall::response(sized(), Encoding::Base64);
+        assert_eq!(Body::size_hint(&body).exact(), None);
+
+        assert_eq!(data(&mut body).await.unwrap(), "aGVsbG8=");
+        assert!(!body.is_end_stream());
+    }
+
     #[test]
     fn encoding_constructors() {
         let cases = &[
@@ -0,0 +1,119 @@
+//! grpc-web for clients.
+//!
+//! A client can speak grpc-web to a server by wrapping its HTTP service in a
+//! [`GrpcWebClientLayer`]. As grpc-web does not need HTTP/2, this can be a plain HTTP/1.1
+//! client, for environments that do not support HTTP/2:
+//!
+//! ```ignore
+//! let client = hyper::Client::builder().build_http();
+//! let client = tower::ServiceBuilder::new()
+//!     .layer(GrpcWebClientLayer::new())
+//!     .service(client);
+//!
+//! let mut client = GreeterClient::with_origin(client, "http://127.0.0.1:3000".try_into()?);
+//! ```
+//!
+//! Requests are sent as binary `application/grpc-web+proto`, and the trailers the server
+//! sends at the end of the response body are returned as the trailers of the response.
+
+use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_core::ready;
+use http::{header, HeaderValue, Request, Response, Version};
+use http_body::Body;
+use pin_project::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::call::content_types::GRPC_WEB_PROTO;
+use crate::call::GrpcWebCall;
+
+/// Layer translating the gRPC requests of a client to grpc-web.
+#[derive(Debug, Clone)]
+pub struct GrpcWebClientLayer {
+    _priv: (),
+}
+
+impl GrpcWebClientLayer {
+    /// Create a new grpc-web client layer.
+    pub fn new() -> GrpcWebClientLayer {
+        Self { _priv: () }
+    }
+}
+
+impl Default for GrpcWebClientLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for GrpcWebClientLayer {
+    type Service = GrpcWebClientService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcWebClientService { inner }
+    }
+}
+
+/// Service translating the gRPC requests of a client to grpc-web.
+#[derive(Debug, Clone)]
+pub struct GrpcWebClientService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for GrpcWebClientService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    ResBody: Body<Data = Bytes>,
+    ResBody::Error: Error,
+{
+    type Response = Response<GrpcWebCall<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        // The framing of grpc-web requests is the same as that of gRPC, without the need
+        // for HTTP/2.
+        if req.version() == Version::HTTP_2 {
+            *req.version_mut() = Version::HTTP_11;
+        }
+
+        req.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(GRPC_WEB_PROTO),
+        );
+
+        ResponseFuture {
+            inner: self.inner.call(req),
+        }
+    }
+}
+
+/// Response future for the [`GrpcWebClientService`].
+#[pin_project]
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = Result<Response<GrpcWebCall<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = ready!(self.project().inner.poll(cx))?;
+        Poll::Ready(Ok(res.map(GrpcWebCall::client_response)))
+    }
+}
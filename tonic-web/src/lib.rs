@@ -61,6 +61,11 @@
 //! }
 //! ```
 //!
+//! ## Clients
+//!
+//! The [`client`] module translates the requests of tonic clients to grpc-web, so they can
+//! call grpc-web servers over HTTP/1.1.
+//!
 //! ## Limitations
 //!
 //! * `tonic_web` is designed to work with grpc-web-compliant clients only. It is not expected to
@@ -87,10 +92,13 @@
 #![doc(html_root_url = "https://docs.rs/tonic-web/0.5.0")]
 #![doc(issue_tracker_base_url = "https://github.com/hyperium/tonic/issues/")]
 
+pub use call::GrpcWebCall;
+pub use client::{GrpcWebClientLayer, GrpcWebClientService};
 pub use layer::GrpcWebLayer;
 pub use service::{GrpcWebService, ResponseFuture};
 
 mod call;
+pub mod client;
 mod layer;
 mod service;
 
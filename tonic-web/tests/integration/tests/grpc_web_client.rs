@@ -0,0 +1,89 @@
+use std::net::SocketAddr;
+
+use hyper::client::HttpConnector;
+use hyper::Client;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_stream::StreamExt;
+use tonic::body::BoxBody;
+use tonic::transport::Server;
+use tonic::Code;
+use tower_layer::Layer;
+
+use integration::pb::{test_client::TestClient, test_server::TestServer, Input, Output};
+use integration::Svc;
+use tonic_web::{GrpcWebClientLayer, GrpcWebClientService, GrpcWebLayer};
+
+type WebClient = TestClient<GrpcWebClientService<Client<HttpConnector, BoxBody>>>;
+
+#[tokio::test]
+async fn unary_over_http1() {
+    let mut client = spawn().await;
+
+    let res = client.unary_call(input("one")).await.unwrap();
+    assert_eq!(
+        res.into_inner(),
+        Output {
+            id: 1,
+            desc: "one".to_owned()
+        }
+    );
+}
+
+#[tokio::test]
+async fn error_status_over_http1() {
+    let mut client = spawn().await;
+
+    let status = client.unary_call(input("boom")).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "invalid boom");
+}
+
+#[tokio::test]
+async fn server_stream_over_http1() {
+    let mut client = spawn().await;
+
+    let mut stream = client
+        .server_stream(input("one"))
+        .await
+        .unwrap()
+        .into_inner();
+    let mut descs = Vec::new();
+    while let Some(output) = stream.next().await {
+        descs.push(output.unwrap().desc);
+    }
+    assert_eq!(descs, ["1-one", "2-one"]);
+
+    let trailers = stream.trailers().await.unwrap().unwrap();
+    assert_eq!(trailers.get("grpc-status").unwrap(), "0");
+}
+
+async fn spawn() -> WebClient {
+    let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+    let listener = TcpListener::bind(addr).await.expect("listener");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let listener_stream = TcpListenerStream::new(listener);
+
+    tokio::spawn(async move {
+        Server::builder()
+            .accept_http1(true)
+            .layer(GrpcWebLayer::new())
+            .add_service(TestServer::new(Svc))
+            .serve_with_incoming(listener_stream)
+            .await
+            .unwrap()
+    });
+
+    // A plain HTTP/1.1 client, which could not make gRPC calls by itself.
+    let client = Client::builder().build_http();
+    let client = GrpcWebClientLayer::new().layer(client);
+
+    TestClient::with_origin(client, url.parse().unwrap())
+}
+
+fn input(desc: &str) -> Input {
+    Input {
+        id: 1,
+        desc: desc.to_owned(),
+    }
+}
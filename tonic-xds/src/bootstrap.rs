@@ -0,0 +1,261 @@
+use crate::pb::envoy::config::core::v3::{node::UserAgentVersionType, Locality, Node};
+use crate::Error;
+use serde_json::{Map, Value};
+
+/// The environment variable naming the file the bootstrap config is read from.
+const BOOTSTRAP_FILE_ENV: &str = "GRPC_XDS_BOOTSTRAP";
+
+/// The environment variable holding the bootstrap config itself.
+const BOOTSTRAP_CONFIG_ENV: &str = "GRPC_XDS_BOOTSTRAP_CONFIG";
+
+/// The features of the client that management servers may rely on.
+const CLIENT_FEATURES: &[&str] = &[
+    "envoy.lb.does_not_support_overprovisioning",
+    "xds.config.resource-in-sotw",
+];
+
+#[cfg(feature = "tls")]
+const UNSUPPORTED_CREDS: &str = "only `insecure` and `tls` channel credentials are supported";
+
+#[cfg(not(feature = "tls"))]
+const UNSUPPORTED_CREDS: &str =
+    "only `insecure` channel credentials are supported, `tls` ones require the `tls` feature";
+
+/// How to reach the xDS management server, and what to tell it about this client.
+///
+/// This is the [bootstrap config] of other gRPC implementations, such as the one Istio puts in
+/// the file named by the `GRPC_XDS_BOOTSTRAP` environment variable. The first of its
+/// `xds_servers` is used, with the first of its `channel_creds` that is supported, and its
+/// `node` identifies the client to the management server.
+///
+/// `insecure` channel credentials are always supported. `tls` ones are supported with the
+/// `tls` feature, which verifies the server against the trust roots of the platform: the
+/// `config` of the credentials, such as certificate providers, is ignored.
+///
+/// ```
+/// let bootstrap = tonic_xds::Bootstrap::from_json(
+///     r#"{
+///         "xds_servers": [{
+///             "server_uri": "istiod.istio-system.svc:15010",
+///             "channel_creds": [{ "type": "insecure" }],
+///             "server_features": ["xds_v3"]
+///         }],
+///         "node": { "id": "sidecar~10.0.0.1~my-pod.default~default.svc.cluster.local" }
+///     }"#,
+/// )
+/// .unwrap();
+/// ```
+///
+/// [bootstrap config]: https://github.com/grpc/proposal/blob/master/A27-xds-global-load-balancing.md
+#[derive(Debug, Clone)]
+pub struct Bootstrap {
+    pub(crate) server_uri: String,
+    /// Whether to connect to the server over TLS.
+    pub(crate) tls: bool,
+    pub(crate) node: Node,
+}
+
+impl Bootstrap {
+    /// Reads the bootstrap config from the environment.
+    ///
+    /// It is read from the file named by the `GRPC_XDS_BOOTSTRAP` environment variable if it is
+    /// set, and from the `GRPC_XDS_BOOTSTRAP_CONFIG` environment variable otherwise.
+    pub fn from_env() -> Result<Self, Error> {
+        let json = match std::env::var_os(BOOTSTRAP_FILE_ENV) {
+            Some(path) => std::fs::read_to_string(&path).map_err(|e| {
+                Error::InvalidBootstrap(format!("failed to read {:?}: {}", path, e))
+            })?,
+            None => std::env::var(BOOTSTRAP_CONFIG_ENV).map_err(|_| {
+                Error::InvalidBootstrap(format!(
+                    "neither {} nor {} is set",
+                    BOOTSTRAP_FILE_ENV, BOOTSTRAP_CONFIG_ENV
+                ))
+            })?,
+        };
+
+        Self::from_json(&json)
+    }
+
+    /// Parses a bootstrap config from its JSON form.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let value =
+            serde_json::from_str(json).map_err(|e| Error::InvalidBootstrap(e.to_string()))?;
+
+        parse(&value).map_err(Error::InvalidBootstrap)
+    }
+}
+
+fn parse(value: &Value) -> Result<Bootstrap, String> {
+    let config = value.as_object().ok_or("the config must be an object")?;
+
+    let server = match config.get("xds_servers") {
+        Some(Value::Array(servers)) => servers.first().and_then(Value::as_object),
+        _ => None,
+    }
+    .ok_or("`xds_servers` must be a list of servers")?;
+
+    let server_uri = string(server, "server_uri")?
+        .ok_or("`server_uri` is required")?
+        .to_string();
+
+    let creds = match server.get("channel_creds") {
+        Some(Value::Array(creds)) => creds,
+        _ => return Err("`channel_creds` must be a list".into()),
+    };
+    let tls = creds
+        .iter()
+        .filter_map(Value::as_object)
+        .find_map(|creds| match creds.get("type") {
+            Some(Value::String(kind)) if kind == "insecure" => Some(false),
+            #[cfg(feature = "tls")]
+            Some(Value::String(kind)) if kind == "tls" => Some(true),
+            _ => None,
+        })
+        .ok_or(UNSUPPORTED_CREDS)?;
+
+    let empty = Map::new();
+    let node = match config.get("node") {
+        Some(Value::Object(node)) => node,
+        None => &empty,
+        Some(_) => return Err("`node` must be an object".into()),
+    };
+
+    Ok(Bootstrap {
+        server_uri,
+        tls,
+        node: self::node(node)?,
+    })
+}
+
+fn node(node: &Map<String, Value>) -> Result<Node, String> {
+    let locality = match node.get("locality") {
+        Some(Value::Object(locality)) => Some(Locality {
+            region: string(locality, "region")?.unwrap_or_default().into(),
+            zone: string(locality, "zone")?.unwrap_or_default().into(),
+            sub_zone: string(locality, "sub_zone")?.unwrap_or_default().into(),
+        }),
+        None => None,
+        Some(_) => return Err("`locality` must be an object".into()),
+    };
+
+    let metadata = match node.get("metadata") {
+        Some(Value::Object(metadata)) => Some(prost_types::Struct {
+            fields: metadata
+                .iter()
+                .map(|(key, value)| (key.clone(), to_proto(value)))
+                .collect(),
+        }),
+        None => None,
+        Some(_) => return Err("`metadata` must be an object".into()),
+    };
+
+    Ok(Node {
+        id: string(node, "id")?.unwrap_or_default().into(),
+        cluster: string(node, "cluster")?.unwrap_or_default().into(),
+        metadata,
+        locality,
+        user_agent_name: "tonic".into(),
+        user_agent_version_type: Some(UserAgentVersionType::UserAgentVersion(
+            env!("CARGO_PKG_VERSION").into(),
+        )),
+        client_features: CLIENT_FEATURES
+            .iter()
+            .map(|&feature| feature.into())
+            .collect(),
+    })
+}
+
+fn string<'a>(object: &'a Map<String, Value>, field: &str) -> Result<Option<&'a str>, String> {
+    match object.get(field) {
+        Some(Value::String(value)) => Ok(Some(value)),
+        Some(_) => Err(format!("`{}` must be a string", field)),
+        None => Ok(None),
+    }
+}
+
+fn to_proto(value: &Value) -> prost_types::Value {
+    use prost_types::value::Kind;
+
+    let kind = match value {
+        Value::Null => Kind::NullValue(0),
+        Value::Bool(value) => Kind::BoolValue(*value),
+        Value::Number(value) => Kind::NumberValue(value.as_f64().unwrap_or_default()),
+        Value::String(value) => Kind::StringValue(value.clone()),
+        Value::Array(values) => Kind::ListValue(prost_types::ListValue {
+            values: values.iter().map(to_proto).collect(),
+        }),
+        Value::Object(fields) => Kind::StructValue(prost_types::Struct {
+            fields: fields
+                .iter()
+                .map(|(key, value)| (key.clone(), to_proto(value)))
+                .collect(),
+        }),
+    };
+
+    prost_types::Value { kind: Some(kind) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bootstrap_configs() {
+        let bootstrap = Bootstrap::from_json(
+            r#"{
+                "xds_servers": [{
+                    "server_uri": "unix:///etc/istio/proxy/XDS",
+                    "channel_creds": [{ "type": "google_default" }, { "type": "insecure" }]
+                }],
+                "node": {
+                    "id": "node-1",
+                    "locality": { "zone": "a" },
+                    "metadata": { "ISTIO_VERSION": "1.18.0", "GENERATOR": ["grpc"] }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(bootstrap.server_uri, "unix:///etc/istio/proxy/XDS");
+        assert!(!bootstrap.tls);
+        assert_eq!(bootstrap.node.id, "node-1");
+        assert_eq!(bootstrap.node.locality.unwrap().zone, "a");
+        assert_eq!(bootstrap.node.metadata.unwrap().fields.len(), 2);
+        assert_eq!(bootstrap.node.user_agent_name, "tonic");
+    }
+
+    #[test]
+    fn uses_the_first_supported_channel_creds() {
+        let result = Bootstrap::from_json(
+            r#"{
+                "xds_servers": [{
+                    "server_uri": "localhost:1",
+                    "channel_creds": [{ "type": "tls", "config": {} }, { "type": "insecure" }]
+                }]
+            }"#,
+        );
+
+        #[cfg(feature = "tls")]
+        assert!(result.unwrap().tls);
+        #[cfg(not(feature = "tls"))]
+        assert!(!result.unwrap().tls);
+    }
+
+    #[test]
+    fn rejects_invalid_bootstrap_configs() {
+        for json in [
+            "[]",
+            r#"{ "xds_servers": [] }"#,
+            r#"{ "xds_servers": [{ "channel_creds": [{ "type": "insecure" }] }] }"#,
+            r#"{ "xds_servers": [{ "server_uri": "localhost:1" }] }"#,
+            r#"{
+                "xds_servers": [{
+                    "server_uri": "localhost:1",
+                    "channel_creds": [{ "type": "google_default" }]
+                }]
+            }"#,
+        ] {
+            assert!(Bootstrap::from_json(json).is_err(), "{}", json);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::client::{Client, RouteTable, Routing};
+use crate::{Bootstrap, Error};
+use futures_util::future::poll_fn;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+use tokio::sync::watch;
+use tonic::{
+    body::BoxBody,
+    transport::{channel::ResponseBody, Channel, Endpoint},
+    Status,
+};
+use tower_service::Service;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A channel to an `xds:///` target.
+///
+/// The channel is configured by the xDS management server of its [`Bootstrap`] config: calls
+/// wait until the listener of the target and the route configuration it leads to have been
+/// received, and are then sent to the cluster of the first route that matches their path.
+/// Calls fail with [`Code::Unavailable`](tonic::Code::Unavailable) when the management server
+/// reports that the listener does not exist, or when no route matches them.
+///
+/// Cloning the channel is cheap, and clones share the ADS stream and the connections to the
+/// endpoints of the clusters. The stream is closed once every clone is dropped.
+#[derive(Clone)]
+pub struct XdsChannel {
+    routing: watch::Receiver<Routing>,
+    /// The number of calls sent so far, which picks between weighted clusters.
+    picks: Arc<AtomicUsize>,
+}
+
+impl XdsChannel {
+    /// Creates a channel to `target`, with the bootstrap config read from the environment
+    /// by [`Bootstrap::from_env`].
+    ///
+    /// This must be called from within a tokio runtime.
+    pub fn new(target: &str) -> Result<Self, Error> {
+        Self::with_bootstrap(target, Bootstrap::from_env()?)
+    }
+
+    /// Creates a channel to `target`, configured by the management server of `bootstrap`.
+    ///
+    /// This must be called from within a tokio runtime.
+    pub fn with_bootstrap(target: &str, bootstrap: Bootstrap) -> Result<Self, Error> {
+        let listener = listener_name(target)?;
+        let endpoint = Endpoint::from_shared(format!("http://{}", listener))
+            .map_err(|e| Error::InvalidTarget(e.to_string()))?;
+
+        let (tx, rx) = watch::channel(None);
+        tokio::spawn(Client::new(bootstrap, listener, endpoint).run(tx));
+
+        Ok(Self {
+            routing: rx,
+            picks: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+}
+
+impl fmt::Debug for XdsChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XdsChannel").finish()
+    }
+}
+
+impl Service<http::Request<BoxBody>> for XdsChannel {
+    type Response = http::Response<ResponseBody>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Calls wait for the routes, and for the channel of their cluster to be ready.
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
+        let mut routing = self.routing.clone();
+        let picks = self.picks.clone();
+
+        Box::pin(async move {
+            let table = loop {
+                let current = routing.borrow_and_update().clone();
+                match current {
+                    Some(Ok(table)) => break table,
+                    Some(Err(error)) => return Err(Status::unavailable(error).into()),
+                    None => {}
+                }
+
+                if routing.changed().await.is_err() {
+                    return Err(Status::unavailable("the xDS client stopped").into());
+                }
+            };
+
+            let mut channel = pick(&table, request.uri().path(), &picks).ok_or_else(|| {
+                Status::unavailable(format!("no route for {}", request.uri().path()))
+            })?;
+
+            poll_fn(|cx| channel.poll_ready(cx)).await?;
+            channel.call(request).await.map_err(Into::into)
+        })
+    }
+}
+
+/// Returns the channel of the cluster the first route that matches `path` sends the call to,
+/// spreading the calls of weighted clusters over them according to their weights.
+fn pick(table: &RouteTable, path: &str, picks: &AtomicUsize) -> Option<Channel> {
+    let route = table.routes.iter().find(|route| route.matches(path))?;
+
+    let total = route
+        .clusters
+        .iter()
+        .map(|(_, weight)| u64::from(*weight))
+        .sum::<u64>();
+    if total == 0 {
+        return None;
+    }
+
+    let mut pick = picks.fetch_add(1, Ordering::Relaxed) as u64 % total;
+    for (channel, weight) in &route.clusters {
+        let weight = u64::from(*weight);
+        if pick < weight {
+            return Some(channel.clone());
+        }
+        pick -= weight;
+    }
+
+    None
+}
+
+/// Returns the name of the listener of an `xds:///` target.
+fn listener_name(target: &str) -> Result<String, Error> {
+    let rest = target
+        .strip_prefix("xds:")
+        .ok_or_else(|| Error::InvalidTarget(format!("{} is not an xds: target", target)))?;
+
+    let name = match rest.strip_prefix("//") {
+        Some(rest) => rest.strip_prefix('/').ok_or_else(|| {
+            Error::InvalidTarget(format!(
+                "{} names an authority, which is unsupported",
+                target
+            ))
+        })?,
+        None => rest.trim_start_matches('/'),
+    };
+
+    if name.is_empty() {
+        return Err(Error::InvalidTarget(format!(
+            "{} names no listener",
+            target
+        )));
+    }
+    Ok(name.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_targets() {
+        assert_eq!(
+            listener_name("xds:///my-service.default.svc.cluster.local:50051").unwrap(),
+            "my-service.default.svc.cluster.local:50051"
+        );
+        assert_eq!(listener_name("xds:my-service").unwrap(), "my-service");
+
+        for target in ["http://my-service", "xds://authority/my-service", "xds:///"] {
+            assert!(listener_name(target).is_err(), "{}", target);
+        }
+    }
+}
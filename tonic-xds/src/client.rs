@@ -0,0 +1,467 @@
+use crate::bootstrap::Bootstrap;
+use crate::pb::envoy::config::{
+    cluster::v3::Cluster as ClusterProto, core::v3::Node, endpoint::v3::ClusterLoadAssignment,
+    listener::v3::Listener, route::v3::RouteConfiguration,
+};
+use crate::pb::envoy::service::discovery::v3::{
+    aggregated_discovery_service_client::AggregatedDiscoveryServiceClient, DiscoveryRequest,
+    DiscoveryResponse,
+};
+use crate::pb::google::rpc::Status;
+use crate::resources::{
+    self, Endpoints, Route, RouteSource, CLUSTER, CLUSTER_LOAD_ASSIGNMENT, LISTENER,
+    ROUTE_CONFIGURATION,
+};
+use futures_util::stream::{Map, StreamExt};
+use std::{
+    collections::{BTreeSet, HashMap},
+    convert::Infallible,
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, watch},
+    time::Instant,
+};
+use tokio_stream::wrappers::{UnboundedReceiverStream, WatchStream};
+#[cfg(feature = "tls")]
+use tonic::transport::ClientTlsConfig;
+use tonic::transport::{channel::Resolver, Channel, Endpoint};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// The resource types, in the order they are subscribed to.
+const TYPES: [&str; 4] = [
+    LISTENER,
+    ROUTE_CONFIGURATION,
+    CLUSTER,
+    CLUSTER_LOAD_ASSIGNMENT,
+];
+
+/// What the client has found out about the target so far: nothing, an error that fails calls,
+/// or the routes to send calls along.
+pub(crate) type Routing = Option<Result<Arc<RouteTable>, String>>;
+
+#[derive(Debug)]
+pub(crate) struct RouteTable {
+    pub(crate) routes: Vec<Route<Channel>>,
+}
+
+#[derive(Debug, Default)]
+struct Subscription {
+    names: BTreeSet<String>,
+    version: String,
+    nonce: String,
+}
+
+/// A cluster one of the routes sends calls to.
+struct Cluster {
+    endpoints: Option<Endpoints>,
+    channel: Channel,
+    addresses: watch::Sender<Vec<SocketAddr>>,
+}
+
+/// Keeps the ADS stream to the management server, and the route table built from the
+/// resources it sends.
+pub(crate) struct Client {
+    bootstrap: Bootstrap,
+    listener: String,
+    /// The endpoint the channels of the clusters are created with.
+    endpoint: Endpoint,
+    subscriptions: HashMap<&'static str, Subscription>,
+    /// The name of the route configuration of the listener, when it is fetched with RDS.
+    route_config_name: Option<String>,
+    routes: Option<Vec<Route<String>>>,
+    error: Option<String>,
+    clusters: HashMap<String, Cluster>,
+    /// The addresses of the endpoints fetched with EDS, by the name they are fetched under.
+    assignments: HashMap<String, Vec<SocketAddr>>,
+}
+
+impl Client {
+    pub(crate) fn new(bootstrap: Bootstrap, listener: String, endpoint: Endpoint) -> Self {
+        let mut subscriptions = HashMap::<_, Subscription>::new();
+        subscriptions
+            .entry(LISTENER)
+            .or_default()
+            .names
+            .insert(listener.clone());
+
+        Self {
+            bootstrap,
+            listener,
+            endpoint,
+            subscriptions,
+            route_config_name: None,
+            routes: None,
+            error: None,
+            clusters: HashMap::new(),
+            assignments: HashMap::new(),
+        }
+    }
+
+    /// Keeps an ADS stream open, reconnecting with a backoff whenever it fails, until every
+    /// receiver of `routing` is dropped.
+    pub(crate) async fn run(mut self, routing: watch::Sender<Routing>) {
+        let mut backoff = INITIAL_BACKOFF;
+
+        loop {
+            let started = Instant::now();
+            tokio::select! {
+                result = self.stream(&routing) => match result {
+                    Ok(()) => tracing::debug!("xds; ADS stream closed"),
+                    Err(error) => tracing::debug!("xds; ADS stream failed: {}", error),
+                },
+                _ = routing.closed() => return,
+            }
+
+            if started.elapsed() > MAX_BACKOFF {
+                backoff = INITIAL_BACKOFF;
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => {}
+                _ = routing.closed() => return,
+            }
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    async fn stream(
+        &mut self,
+        routing: &watch::Sender<Routing>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let endpoint =
+            Endpoint::from_shared(server_uri(&self.bootstrap.server_uri, self.bootstrap.tls))?;
+        #[cfg(feature = "tls")]
+        let endpoint = if self.bootstrap.tls {
+            endpoint.tls_config(ClientTlsConfig::new())?
+        } else {
+            endpoint
+        };
+        let channel = endpoint.connect().await?;
+        let mut client = AggregatedDiscoveryServiceClient::new(channel);
+
+        // Nonces only belong to the stream they were sent on.
+        for subscription in self.subscriptions.values_mut() {
+            subscription.nonce.clear();
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut requests = Requests {
+            tx,
+            node: Some(self.bootstrap.node.clone()),
+        };
+        for type_url in TYPES {
+            if !self.names(type_url).is_empty() {
+                requests.send(self.request(type_url, None));
+            }
+        }
+
+        let mut responses = client
+            .stream_aggregated_resources(UnboundedReceiverStream::new(rx))
+            .await?
+            .into_inner();
+        while let Some(response) = responses.message().await? {
+            self.handle(response, &mut requests);
+            routing.send_replace(self.routing());
+        }
+
+        Ok(())
+    }
+
+    fn handle(&mut self, response: DiscoveryResponse, requests: &mut Requests) {
+        let (type_url, result) = match response.type_url.as_str() {
+            LISTENER => (LISTENER, self.on_listeners(&response)),
+            ROUTE_CONFIGURATION => (ROUTE_CONFIGURATION, self.on_route_configs(&response)),
+            CLUSTER => (CLUSTER, self.on_clusters(&response)),
+            CLUSTER_LOAD_ASSIGNMENT => (CLUSTER_LOAD_ASSIGNMENT, self.on_assignments(&response)),
+            type_url => {
+                tracing::debug!("xds; ignoring resources of type {}", type_url);
+                return;
+            }
+        };
+
+        let subscription = self.subscriptions.entry(type_url).or_default();
+        subscription.nonce = response.nonce;
+        let error = match result {
+            Ok(()) => {
+                subscription.version = response.version_info;
+                None
+            }
+            Err(error) => {
+                tracing::warn!(
+                    "xds; rejecting version {} of {}: {}",
+                    response.version_info,
+                    type_url,
+                    error
+                );
+                Some(error)
+            }
+        };
+        requests.send(self.request(type_url, error));
+
+        self.update_subscriptions(requests);
+    }
+
+    fn on_listeners(&mut self, response: &DiscoveryResponse) -> Result<(), String> {
+        let listeners = resources::decode::<Listener>(&response.resources, LISTENER)?;
+        let listener = match listeners.iter().find(|l| l.name == self.listener) {
+            Some(listener) => listener,
+            None => {
+                self.route_config_name = None;
+                self.routes = None;
+                self.error = Some(format!("listener {} does not exist", self.listener));
+                return Ok(());
+            }
+        };
+
+        match resources::route_source(listener)? {
+            RouteSource::Rds(name) => {
+                if self.route_config_name.as_ref() != Some(&name) {
+                    self.route_config_name = Some(name);
+                    self.routes = None;
+                    self.error = None;
+                }
+            }
+            RouteSource::Inline(config) => {
+                self.route_config_name = None;
+                self.apply_routes(&config)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_route_configs(&mut self, response: &DiscoveryResponse) -> Result<(), String> {
+        let configs =
+            resources::decode::<RouteConfiguration>(&response.resources, ROUTE_CONFIGURATION)?;
+        let config = configs
+            .iter()
+            .find(|config| Some(&config.name) == self.route_config_name.as_ref());
+
+        match config {
+            Some(config) => self.apply_routes(config),
+            None => Ok(()),
+        }
+    }
+
+    fn apply_routes(&mut self, config: &RouteConfiguration) -> Result<(), String> {
+        let routes = match resources::routes(config, &self.listener)? {
+            Some(routes) => routes,
+            None => {
+                self.routes = None;
+                self.error = Some(format!("no virtual host matches {}", self.listener));
+                return Ok(());
+            }
+        };
+
+        let names = routes
+            .iter()
+            .flat_map(|route| route.clusters.iter().map(|(name, _)| name))
+            .collect::<BTreeSet<_>>();
+        self.clusters.retain(|name, _| names.contains(name));
+        for name in names {
+            if !self.clusters.contains_key(name) {
+                let (addresses, rx) = watch::channel(Vec::new());
+                let cluster = Cluster {
+                    endpoints: None,
+                    channel: Channel::balance_resolver(self.endpoint.clone(), ClusterResolver(rx)),
+                    addresses,
+                };
+                self.clusters.insert(name.clone(), cluster);
+            }
+        }
+
+        self.routes = Some(routes);
+        self.error = None;
+        Ok(())
+    }
+
+    fn on_clusters(&mut self, response: &DiscoveryResponse) -> Result<(), String> {
+        let clusters = resources::decode::<ClusterProto>(&response.resources, CLUSTER)?;
+        let endpoints = clusters
+            .iter()
+            .filter(|cluster| self.clusters.contains_key(&cluster.name))
+            .map(|cluster| Ok((cluster.name.clone(), resources::endpoints(cluster)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        for (name, endpoints) in endpoints {
+            let cluster = self.clusters.get_mut(&name).expect("subscribed cluster");
+            match &endpoints {
+                Endpoints::Static(addresses) => {
+                    cluster.addresses.send_replace(addresses.clone());
+                }
+                Endpoints::Eds(name) => {
+                    if let Some(addresses) = self.assignments.get(name) {
+                        cluster.addresses.send_replace(addresses.clone());
+                    }
+                }
+            }
+            cluster.endpoints = Some(endpoints);
+        }
+
+        Ok(())
+    }
+
+    fn on_assignments(&mut self, response: &DiscoveryResponse) -> Result<(), String> {
+        let assignments = resources::decode::<ClusterLoadAssignment>(
+            &response.resources,
+            CLUSTER_LOAD_ASSIGNMENT,
+        )?;
+
+        let names = self.names(CLUSTER_LOAD_ASSIGNMENT);
+        for assignment in assignments {
+            if !names.contains(&assignment.cluster_name) {
+                continue;
+            }
+
+            let addresses = resources::addresses(&assignment);
+            for cluster in self.clusters.values() {
+                if cluster.endpoints == Some(Endpoints::Eds(assignment.cluster_name.clone())) {
+                    cluster.addresses.send_replace(addresses.clone());
+                }
+            }
+            self.assignments.insert(assignment.cluster_name, addresses);
+        }
+
+        Ok(())
+    }
+
+    /// Subscribes to the resources the ones received so far refer to.
+    fn update_subscriptions(&mut self, requests: &mut Requests) {
+        let route_configs = self.route_config_name.iter().cloned().collect();
+        let clusters = self.clusters.keys().cloned().collect();
+        let assignments = self
+            .clusters
+            .values()
+            .filter_map(|cluster| match &cluster.endpoints {
+                Some(Endpoints::Eds(name)) => Some(name.clone()),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>();
+        self.assignments
+            .retain(|name, _| assignments.contains(name));
+
+        for (type_url, names) in [
+            (ROUTE_CONFIGURATION, route_configs),
+            (CLUSTER, clusters),
+            (CLUSTER_LOAD_ASSIGNMENT, assignments),
+        ] {
+            // Asking for no clusters would subscribe to all of them, so the last ones stay
+            // subscribed to instead.
+            if type_url == CLUSTER && names.is_empty() {
+                continue;
+            }
+
+            let subscription = self.subscriptions.entry(type_url).or_default();
+            if subscription.names != names {
+                subscription.names = names;
+                requests.send(self.request(type_url, None));
+            }
+        }
+    }
+
+    fn names(&self, type_url: &str) -> BTreeSet<String> {
+        self.subscriptions
+            .get(type_url)
+            .map(|subscription| subscription.names.clone())
+            .unwrap_or_default()
+    }
+
+    fn request(&self, type_url: &'static str, error: Option<String>) -> DiscoveryRequest {
+        let subscription = &self.subscriptions[type_url];
+
+        DiscoveryRequest {
+            version_info: subscription.version.clone(),
+            node: None,
+            resource_names: subscription.names.iter().cloned().collect(),
+            type_url: type_url.into(),
+            response_nonce: subscription.nonce.clone(),
+            error_detail: error.map(|message| Status {
+                code: tonic::Code::InvalidArgument as i32,
+                message,
+                details: Vec::new(),
+            }),
+        }
+    }
+
+    fn routing(&self) -> Routing {
+        if let Some(error) = &self.error {
+            return Some(Err(error.clone()));
+        }
+
+        let routes = self.routes.as_ref()?;
+        let table = RouteTable {
+            routes: routes
+                .iter()
+                .map(|route| route.map(|name| self.clusters[name].channel.clone()))
+                .collect(),
+        };
+        Some(Ok(Arc::new(table)))
+    }
+}
+
+/// The requests of an ADS stream, the first of which identifies the client with its node.
+struct Requests {
+    tx: mpsc::UnboundedSender<DiscoveryRequest>,
+    node: Option<Node>,
+}
+
+impl Requests {
+    fn send(&mut self, mut request: DiscoveryRequest) {
+        request.node = self.node.take();
+        // The stream failed if the receiver was dropped, which its responses report.
+        let _ = self.tx.send(request);
+    }
+}
+
+/// Resolves the addresses of a cluster to the ones its endpoints were last updated with.
+struct ClusterResolver(watch::Receiver<Vec<SocketAddr>>);
+
+impl Resolver for ClusterResolver {
+    type Error = Infallible;
+    type Stream = Map<
+        WatchStream<Vec<SocketAddr>>,
+        fn(Vec<SocketAddr>) -> Result<Vec<SocketAddr>, Infallible>,
+    >;
+
+    fn resolve(&mut self, _target: &str) -> Self::Stream {
+        WatchStream::new(self.0.clone()).map(Ok)
+    }
+}
+
+/// Turns the `server_uri` of the bootstrap config, which is a gRPC target, into the uri of an
+/// [`Endpoint`], connected to over TLS if `tls` is set.
+fn server_uri(target: &str, tls: bool) -> String {
+    if target.starts_with("unix:") || target.contains("://") && !target.starts_with("dns:") {
+        return target.into();
+    }
+
+    let authority = target.trim_start_matches("dns:").trim_start_matches("///");
+    let scheme = if tls { "https" } else { "http" };
+    format!("{}://{}", scheme, authority)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_server_uris() {
+        assert_eq!(
+            server_uri("istiod.istio-system.svc:15010", false),
+            "http://istiod.istio-system.svc:15010"
+        );
+        assert_eq!(
+            server_uri("dns:///localhost:15010", false),
+            "http://localhost:15010"
+        );
+        assert_eq!(
+            server_uri("istiod.istio-system.svc:15012", true),
+            "https://istiod.istio-system.svc:15012"
+        );
+        assert_eq!(server_uri("http://[::1]:15010", true), "http://[::1]:15010");
+    }
+}
@@ -0,0 +1,21 @@
+use std::fmt::{Display, Formatter};
+
+/// An error creating an [`XdsChannel`](crate::XdsChannel).
+#[derive(Debug)]
+pub enum Error {
+    /// The bootstrap config could not be read, or is not valid.
+    InvalidBootstrap(String),
+    /// The target is not a valid `xds:///` uri.
+    InvalidTarget(String),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidBootstrap(s) => write!(f, "invalid xDS bootstrap config - {}", s),
+            Error::InvalidTarget(s) => write!(f, "invalid xDS target - {}", s),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Cluster {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub eds_cluster_config: ::core::option::Option<cluster::EdsClusterConfig>,
+    #[prost(enumeration = "cluster::LbPolicy", tag = "6")]
+    pub lb_policy: i32,
+    /// The endpoints of `STATIC` clusters.
+    #[prost(message, optional, tag = "33")]
+    pub load_assignment: ::core::option::Option<
+        super::super::endpoint::v3::ClusterLoadAssignment,
+    >,
+    #[prost(oneof = "cluster::ClusterDiscoveryType", tags = "2")]
+    pub cluster_discovery_type: ::core::option::Option<cluster::ClusterDiscoveryType>,
+}
+/// Nested message and enum types in `Cluster`.
+pub mod cluster {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct EdsClusterConfig {
+        /// The name of the endpoints of the cluster, if it is not the name of the cluster.
+        #[prost(string, tag = "2")]
+        pub service_name: ::prost::alloc::string::String,
+    }
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
+    )]
+    #[repr(i32)]
+    pub enum DiscoveryType {
+        Static = 0,
+        StrictDns = 1,
+        LogicalDns = 2,
+        Eds = 3,
+        OriginalDst = 4,
+    }
+    impl DiscoveryType {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                DiscoveryType::Static => "STATIC",
+                DiscoveryType::StrictDns => "STRICT_DNS",
+                DiscoveryType::LogicalDns => "LOGICAL_DNS",
+                DiscoveryType::Eds => "EDS",
+                DiscoveryType::OriginalDst => "ORIGINAL_DST",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "STATIC" => Some(Self::Static),
+                "STRICT_DNS" => Some(Self::StrictDns),
+                "LOGICAL_DNS" => Some(Self::LogicalDns),
+                "EDS" => Some(Self::Eds),
+                "ORIGINAL_DST" => Some(Self::OriginalDst),
+                _ => None,
+            }
+        }
+    }
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
+    )]
+    #[repr(i32)]
+    pub enum LbPolicy {
+        RoundRobin = 0,
+        LeastRequest = 1,
+        RingHash = 2,
+        Random = 3,
+        Maglev = 5,
+        ClusterProvided = 6,
+        LoadBalancingPolicyConfig = 7,
+    }
+    impl LbPolicy {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                LbPolicy::RoundRobin => "ROUND_ROBIN",
+                LbPolicy::LeastRequest => "LEAST_REQUEST",
+                LbPolicy::RingHash => "RING_HASH",
+                LbPolicy::Random => "RANDOM",
+                LbPolicy::Maglev => "MAGLEV",
+                LbPolicy::ClusterProvided => "CLUSTER_PROVIDED",
+                LbPolicy::LoadBalancingPolicyConfig => "LOAD_BALANCING_POLICY_CONFIG",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "ROUND_ROBIN" => Some(Self::RoundRobin),
+                "LEAST_REQUEST" => Some(Self::LeastRequest),
+                "RING_HASH" => Some(Self::RingHash),
+                "RANDOM" => Some(Self::Random),
+                "MAGLEV" => Some(Self::Maglev),
+                "CLUSTER_PROVIDED" => Some(Self::ClusterProvided),
+                "LOAD_BALANCING_POLICY_CONFIG" => Some(Self::LoadBalancingPolicyConfig),
+                _ => None,
+            }
+        }
+    }
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum ClusterDiscoveryType {
+        #[prost(enumeration = "DiscoveryType", tag = "2")]
+        Type(i32),
+    }
+}
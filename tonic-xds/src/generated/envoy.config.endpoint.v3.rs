@@ -0,0 +1,49 @@
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClusterLoadAssignment {
+    #[prost(string, tag = "1")]
+    pub cluster_name: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub endpoints: ::prost::alloc::vec::Vec<LocalityLbEndpoints>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LocalityLbEndpoints {
+    #[prost(message, optional, tag = "1")]
+    pub locality: ::core::option::Option<super::super::core::v3::Locality>,
+    #[prost(message, repeated, tag = "2")]
+    pub lb_endpoints: ::prost::alloc::vec::Vec<LbEndpoint>,
+    #[prost(message, optional, tag = "3")]
+    pub load_balancing_weight: ::core::option::Option<u32>,
+    /// Endpoints of lower priorities are only used when none of a higher one is available.
+    /// Zero is the highest priority.
+    #[prost(uint32, tag = "5")]
+    pub priority: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LbEndpoint {
+    #[prost(enumeration = "super::super::core::v3::HealthStatus", tag = "2")]
+    pub health_status: i32,
+    #[prost(message, optional, tag = "4")]
+    pub load_balancing_weight: ::core::option::Option<u32>,
+    #[prost(oneof = "lb_endpoint::HostIdentifier", tags = "1")]
+    pub host_identifier: ::core::option::Option<lb_endpoint::HostIdentifier>,
+}
+/// Nested message and enum types in `LbEndpoint`.
+pub mod lb_endpoint {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum HostIdentifier {
+        #[prost(message, tag = "1")]
+        Endpoint(super::Endpoint),
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Endpoint {
+    #[prost(message, optional, tag = "1")]
+    pub address: ::core::option::Option<super::super::core::v3::Address>,
+    #[prost(string, tag = "3")]
+    pub hostname: ::prost::alloc::string::String,
+}
@@ -0,0 +1,18 @@
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Listener {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub address: ::core::option::Option<super::super::core::v3::Address>,
+    /// The listener of clients that do not accept connections, such as gRPC clients.
+    #[prost(message, optional, tag = "19")]
+    pub api_listener: ::core::option::Option<ApiListener>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ApiListener {
+    /// An `HttpConnectionManager` for gRPC clients.
+    #[prost(message, optional, tag = "1")]
+    pub api_listener: ::core::option::Option<::prost_types::Any>,
+}
@@ -0,0 +1,108 @@
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RouteConfiguration {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub virtual_hosts: ::prost::alloc::vec::Vec<VirtualHost>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VirtualHost {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// The hosts the virtual host serves, which may start or end with a `*` wildcard.
+    #[prost(string, repeated, tag = "2")]
+    pub domains: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// The routes of the virtual host, which are matched in order.
+    #[prost(message, repeated, tag = "3")]
+    pub routes: ::prost::alloc::vec::Vec<Route>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Route {
+    #[prost(string, tag = "14")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "1")]
+    pub r#match: ::core::option::Option<RouteMatch>,
+    #[prost(oneof = "route::Action", tags = "2, 18")]
+    pub action: ::core::option::Option<route::Action>,
+}
+/// Nested message and enum types in `Route`.
+pub mod route {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Action {
+        #[prost(message, tag = "2")]
+        Route(super::RouteAction),
+        #[prost(message, tag = "18")]
+        NonForwardingAction(super::NonForwardingAction),
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RouteMatch {
+    /// Defaults to true.
+    #[prost(message, optional, tag = "4")]
+    pub case_sensitive: ::core::option::Option<bool>,
+    #[prost(message, repeated, tag = "6")]
+    pub headers: ::prost::alloc::vec::Vec<HeaderMatcher>,
+    #[prost(oneof = "route_match::PathSpecifier", tags = "1, 2")]
+    pub path_specifier: ::core::option::Option<route_match::PathSpecifier>,
+}
+/// Nested message and enum types in `RouteMatch`.
+pub mod route_match {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum PathSpecifier {
+        #[prost(string, tag = "1")]
+        Prefix(::prost::alloc::string::String),
+        #[prost(string, tag = "2")]
+        Path(::prost::alloc::string::String),
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HeaderMatcher {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RouteAction {
+    #[prost(oneof = "route_action::ClusterSpecifier", tags = "1, 2, 3")]
+    pub cluster_specifier: ::core::option::Option<route_action::ClusterSpecifier>,
+}
+/// Nested message and enum types in `RouteAction`.
+pub mod route_action {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum ClusterSpecifier {
+        #[prost(string, tag = "1")]
+        Cluster(::prost::alloc::string::String),
+        #[prost(string, tag = "2")]
+        ClusterHeader(::prost::alloc::string::String),
+        #[prost(message, tag = "3")]
+        WeightedClusters(super::WeightedCluster),
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WeightedCluster {
+    #[prost(message, repeated, tag = "1")]
+    pub clusters: ::prost::alloc::vec::Vec<weighted_cluster::ClusterWeight>,
+}
+/// Nested message and enum types in `WeightedCluster`.
+pub mod weighted_cluster {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct ClusterWeight {
+        #[prost(string, tag = "1")]
+        pub name: ::prost::alloc::string::String,
+        #[prost(message, optional, tag = "2")]
+        pub weight: ::core::option::Option<u32>,
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NonForwardingAction {}
@@ -0,0 +1,29 @@
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HttpConnectionManager {
+    #[prost(string, tag = "2")]
+    pub stat_prefix: ::prost::alloc::string::String,
+    #[prost(oneof = "http_connection_manager::RouteSpecifier", tags = "3, 4")]
+    pub route_specifier: ::core::option::Option<http_connection_manager::RouteSpecifier>,
+}
+/// Nested message and enum types in `HttpConnectionManager`.
+pub mod http_connection_manager {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum RouteSpecifier {
+        /// The route configuration is fetched with RDS.
+        #[prost(message, tag = "3")]
+        Rds(super::Rds),
+        /// The route configuration is inlined.
+        #[prost(message, tag = "4")]
+        RouteConfig(
+            super::super::super::super::super::super::config::route::v3::RouteConfiguration,
+        ),
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Rds {
+    #[prost(string, tag = "2")]
+    pub route_config_name: ::prost::alloc::string::String,
+}
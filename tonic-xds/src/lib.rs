@@ -0,0 +1,124 @@
+//! A `tonic` based xDS client.
+//!
+//! xDS is the protocol Envoy, and the proxyless gRPC clients of other languages, get their
+//! configuration from a control plane such as Istio with. An [`XdsChannel`] connects to the
+//! control plane named by a [`Bootstrap`] config, subscribes to the listener of its
+//! `xds:///` target over the aggregated discovery service (ADS), and follows the route
+//! configuration, clusters and endpoints that listener leads to: every call is sent to the
+//! cluster its route picks, and balanced in round robin order over the endpoints of that
+//! cluster.
+//!
+//! Only what these clients need of the xDS API is supported:
+//!
+//! - Listeners with an `api_listener` whose `HttpConnectionManager` has either an inline
+//!   route configuration or an RDS route configuration name.
+//! - The virtual host that matches the target best, and its routes that match on a path
+//!   `prefix` or an exact `path` and send calls to a cluster or to weighted clusters. Routes
+//!   that also match on headers, or that pick their cluster from a header, are skipped.
+//! - `EDS` and `STATIC` clusters. Calls are always balanced in round robin order, whatever
+//!   the `lb_policy` of the cluster.
+//! - Endpoints with a socket address that is an IP address. Only the endpoints of the highest
+//!   priority that has any healthy ones are used.
+//! - `insecure` channel credentials for the management server, and `tls` ones with the `tls`
+//!   feature, see [`Bootstrap`].
+//!
+//! Resources that are invalid are rejected with a NACK and the previous ones are kept.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! use tonic_xds::XdsChannel;
+//!
+//! // Reads the bootstrap config from the file named by `GRPC_XDS_BOOTSTRAP`.
+//! let channel = XdsChannel::new("xds:///my-service.default.svc.cluster.local:50051")?;
+//! # drop(channel);
+//! // let client = GreeterClient::new(channel);
+//! # Ok(())
+//! # }
+//! ```
+
+#![warn(
+    missing_debug_implementations,
+    missing_docs,
+    rust_2018_idioms,
+    unreachable_pub
+)]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/tokio-rs/website/master/public/img/icons/tonic.svg"
+)]
+#![deny(rustdoc::broken_intra_doc_links)]
+#![doc(html_root_url = "https://docs.rs/tonic-xds/0.1.0")]
+#![doc(issue_tracker_base_url = "https://github.com/hyperium/tonic/issues/")]
+#![doc(test(no_crate_inject, attr(deny(rust_2018_idioms))))]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+/// Generated protobuf types of the parts of the xDS API that are supported.
+pub mod pb {
+    #![allow(unreachable_pub)]
+    #![allow(missing_docs)]
+
+    pub mod envoy {
+        pub mod config {
+            pub mod cluster {
+                pub mod v3 {
+                    include!("generated/envoy.config.cluster.v3.rs");
+                }
+            }
+            pub mod core {
+                pub mod v3 {
+                    include!("generated/envoy.config.core.v3.rs");
+                }
+            }
+            pub mod endpoint {
+                pub mod v3 {
+                    include!("generated/envoy.config.endpoint.v3.rs");
+                }
+            }
+            pub mod listener {
+                pub mod v3 {
+                    include!("generated/envoy.config.listener.v3.rs");
+                }
+            }
+            pub mod route {
+                pub mod v3 {
+                    include!("generated/envoy.config.route.v3.rs");
+                }
+            }
+        }
+        pub mod extensions {
+            pub mod filters {
+                pub mod network {
+                    pub mod http_connection_manager {
+                        pub mod v3 {
+                            include!("generated/envoy.extensions.filters.network.http_connection_manager.v3.rs");
+                        }
+                    }
+                }
+            }
+        }
+        pub mod service {
+            pub mod discovery {
+                pub mod v3 {
+                    include!("generated/envoy.service.discovery.v3.rs");
+                }
+            }
+        }
+    }
+
+    pub mod google {
+        pub mod rpc {
+            include!("generated/google.rpc.rs");
+        }
+    }
+}
+
+mod bootstrap;
+mod channel;
+mod client;
+mod error;
+mod resources;
+
+pub use bootstrap::Bootstrap;
+pub use channel::XdsChannel;
+pub use error::Error;
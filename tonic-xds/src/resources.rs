@@ -0,0 +1,419 @@
+use crate::pb::envoy::config::{
+    cluster::v3::{cluster::ClusterDiscoveryType, cluster::DiscoveryType, Cluster},
+    core::v3::{address, socket_address::PortSpecifier, HealthStatus},
+    endpoint::v3::{lb_endpoint::HostIdentifier, ClusterLoadAssignment},
+    listener::v3::Listener,
+    route::v3::{
+        route::Action, route_action::ClusterSpecifier, route_match::PathSpecifier,
+        RouteConfiguration, VirtualHost,
+    },
+};
+use crate::pb::envoy::extensions::filters::network::http_connection_manager::v3::{
+    http_connection_manager::RouteSpecifier, HttpConnectionManager,
+};
+use crate::pb::envoy::service::discovery::v3::Resource;
+use prost::Message;
+use prost_types::Any;
+use std::{collections::BTreeMap, net::SocketAddr};
+
+pub(crate) const LISTENER: &str = "type.googleapis.com/envoy.config.listener.v3.Listener";
+pub(crate) const ROUTE_CONFIGURATION: &str =
+    "type.googleapis.com/envoy.config.route.v3.RouteConfiguration";
+pub(crate) const CLUSTER: &str = "type.googleapis.com/envoy.config.cluster.v3.Cluster";
+pub(crate) const CLUSTER_LOAD_ASSIGNMENT: &str =
+    "type.googleapis.com/envoy.config.endpoint.v3.ClusterLoadAssignment";
+
+const HTTP_CONNECTION_MANAGER: &str = "type.googleapis.com/envoy.extensions.filters.network.http_connection_manager.v3.HttpConnectionManager";
+const RESOURCE: &str = "type.googleapis.com/envoy.service.discovery.v3.Resource";
+
+/// Decodes the resources of a response for resources of type `type_url`, including those
+/// wrapped in a `Resource`.
+pub(crate) fn decode<M>(resources: &[Any], type_url: &str) -> Result<Vec<M>, String>
+where
+    M: Message + Default,
+{
+    resources
+        .iter()
+        .map(|any| {
+            let resource;
+            let any = match any.type_url.as_str() {
+                RESOURCE => {
+                    resource = Resource::decode(&*any.value).map_err(|e| e.to_string())?;
+                    resource.resource.as_ref().ok_or("empty resource")?
+                }
+                _ => any,
+            };
+
+            if any.type_url != type_url {
+                return Err(format!("unexpected resource of type {}", any.type_url));
+            }
+            M::decode(&*any.value).map_err(|e| format!("invalid {}: {}", type_url, e))
+        })
+        .collect()
+}
+
+/// Where the route configuration of a listener comes from.
+#[derive(Debug)]
+pub(crate) enum RouteSource {
+    /// It is fetched with RDS, under this name.
+    Rds(String),
+    Inline(RouteConfiguration),
+}
+
+pub(crate) fn route_source(listener: &Listener) -> Result<RouteSource, String> {
+    let manager = listener
+        .api_listener
+        .as_ref()
+        .and_then(|api_listener| api_listener.api_listener.as_ref())
+        .ok_or_else(|| format!("listener {} has no api_listener", listener.name))?;
+    if manager.type_url != HTTP_CONNECTION_MANAGER {
+        return Err(format!("unsupported api_listener {}", manager.type_url));
+    }
+
+    let manager = HttpConnectionManager::decode(&*manager.value).map_err(|e| e.to_string())?;
+    match manager.route_specifier {
+        Some(RouteSpecifier::Rds(rds)) if !rds.route_config_name.is_empty() => {
+            Ok(RouteSource::Rds(rds.route_config_name))
+        }
+        Some(RouteSpecifier::RouteConfig(config)) => Ok(RouteSource::Inline(config)),
+        _ => Err(format!(
+            "listener {} has no route configuration",
+            listener.name
+        )),
+    }
+}
+
+/// A route of the virtual host of the target, with the clusters it sends calls to and their
+/// weights.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Route<C> {
+    path: PathMatch,
+    case_sensitive: bool,
+    pub(crate) clusters: Vec<(C, u32)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathMatch {
+    Prefix(String),
+    Path(String),
+}
+
+impl<C> Route<C> {
+    pub(crate) fn matches(&self, path: &str) -> bool {
+        let (pattern, exact) = match &self.path {
+            PathMatch::Prefix(prefix) => (prefix, false),
+            PathMatch::Path(path) => (path, true),
+        };
+
+        let path = match path.get(..pattern.len()) {
+            Some(start) if !exact || path.len() == pattern.len() => start,
+            _ => return false,
+        };
+        if self.case_sensitive {
+            path == pattern
+        } else {
+            path.eq_ignore_ascii_case(pattern)
+        }
+    }
+
+    pub(crate) fn map<D>(&self, mut f: impl FnMut(&C) -> D) -> Route<D> {
+        Route {
+            path: self.path.clone(),
+            case_sensitive: self.case_sensitive,
+            clusters: self
+                .clusters
+                .iter()
+                .map(|(cluster, weight)| (f(cluster), *weight))
+                .collect(),
+        }
+    }
+}
+
+/// Returns the routes of the virtual host of `config` that serves `host`, or `None` if no
+/// virtual host does.
+pub(crate) fn routes(
+    config: &RouteConfiguration,
+    host: &str,
+) -> Result<Option<Vec<Route<String>>>, String> {
+    let virtual_host = match virtual_host(&config.virtual_hosts, host) {
+        Some(virtual_host) => virtual_host,
+        None => return Ok(None),
+    };
+
+    let mut routes = Vec::new();
+    for route in &virtual_host.routes {
+        let matcher = match &route.r#match {
+            Some(matcher) => matcher,
+            None => return Err(format!("route {:?} has no match", route.name)),
+        };
+        let path = match &matcher.path_specifier {
+            Some(PathSpecifier::Prefix(prefix)) => PathMatch::Prefix(prefix.clone()),
+            Some(PathSpecifier::Path(path)) => PathMatch::Path(path.clone()),
+            None => {
+                tracing::debug!(
+                    "xds; skipping route {:?} with unsupported match",
+                    route.name
+                );
+                continue;
+            }
+        };
+        if !matcher.headers.is_empty() {
+            tracing::debug!("xds; skipping route {:?} with header matchers", route.name);
+            continue;
+        }
+
+        let clusters = match &route.action {
+            Some(Action::Route(action)) => match &action.cluster_specifier {
+                Some(ClusterSpecifier::Cluster(cluster)) if !cluster.is_empty() => {
+                    vec![(cluster.clone(), 1)]
+                }
+                Some(ClusterSpecifier::WeightedClusters(weighted)) => {
+                    let clusters = weighted
+                        .clusters
+                        .iter()
+                        .filter(|cluster| cluster.weight.unwrap_or_default() > 0)
+                        .map(|cluster| (cluster.name.clone(), cluster.weight.unwrap_or_default()))
+                        .collect::<Vec<_>>();
+                    if clusters.is_empty() || clusters.iter().any(|(name, _)| name.is_empty()) {
+                        return Err(format!(
+                            "route {:?} has invalid weighted clusters",
+                            route.name
+                        ));
+                    }
+                    clusters
+                }
+                Some(ClusterSpecifier::ClusterHeader(_)) => {
+                    tracing::debug!("xds; skipping route {:?} with a cluster header", route.name);
+                    continue;
+                }
+                _ => return Err(format!("route {:?} has no cluster", route.name)),
+            },
+            // Calls that match routes which do not forward them fail.
+            _ => Vec::new(),
+        };
+
+        routes.push(Route {
+            path,
+            case_sensitive: matcher.case_sensitive.unwrap_or(true),
+            clusters,
+        });
+    }
+
+    Ok(Some(routes))
+}
+
+/// Returns the virtual host with the domain that matches `host` best.
+///
+/// An exact domain matches best, followed by the longest domain that starts with a `*`
+/// wildcard, the longest domain that ends with one, and finally `*`.
+fn virtual_host<'a>(virtual_hosts: &'a [VirtualHost], host: &str) -> Option<&'a VirtualHost> {
+    let host = &host.to_ascii_lowercase();
+
+    virtual_hosts
+        .iter()
+        .flat_map(|virtual_host| {
+            virtual_host.domains.iter().filter_map(move |domain| {
+                let domain = domain.to_ascii_lowercase();
+                let rank = if domain == *host {
+                    0
+                } else if domain == "*" {
+                    3
+                } else if domain.starts_with('*') && host.ends_with(&domain[1..]) {
+                    1
+                } else if domain.ends_with('*') && host.starts_with(&domain[..domain.len() - 1]) {
+                    2
+                } else {
+                    return None;
+                };
+                Some(((rank, usize::MAX - domain.len()), virtual_host))
+            })
+        })
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, virtual_host)| virtual_host)
+}
+
+/// Where the endpoints of a cluster come from.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Endpoints {
+    /// They are fetched with EDS, under this name.
+    Eds(String),
+    Static(Vec<SocketAddr>),
+}
+
+pub(crate) fn endpoints(cluster: &Cluster) -> Result<Endpoints, String> {
+    match cluster.cluster_discovery_type {
+        Some(ClusterDiscoveryType::Type(kind)) if kind == DiscoveryType::Eds as i32 => {
+            let service_name = cluster
+                .eds_cluster_config
+                .as_ref()
+                .map(|config| config.service_name.as_str())
+                .unwrap_or_default();
+            Ok(Endpoints::Eds(match service_name {
+                "" => cluster.name.clone(),
+                service_name => service_name.into(),
+            }))
+        }
+        Some(ClusterDiscoveryType::Type(kind)) if kind == DiscoveryType::Static as i32 => {
+            match &cluster.load_assignment {
+                Some(assignment) => Ok(Endpoints::Static(addresses(assignment))),
+                None => Err(format!("static cluster {} has no endpoints", cluster.name)),
+            }
+        }
+        _ => Err(format!("cluster {} has an unsupported type", cluster.name)),
+    }
+}
+
+/// Returns the addresses of the healthy endpoints of the highest priority that has any.
+pub(crate) fn addresses(assignment: &ClusterLoadAssignment) -> Vec<SocketAddr> {
+    let mut priorities = BTreeMap::<_, Vec<_>>::new();
+
+    for locality in &assignment.endpoints {
+        let addresses = priorities.entry(locality.priority).or_default();
+
+        for endpoint in &locality.lb_endpoints {
+            let healthy = endpoint.health_status == HealthStatus::Unknown as i32
+                || endpoint.health_status == HealthStatus::Healthy as i32;
+            let address = match &endpoint.host_identifier {
+                Some(HostIdentifier::Endpoint(endpoint)) => endpoint
+                    .address
+                    .as_ref()
+                    .and_then(|address| address.address.as_ref()),
+                None => None,
+            };
+
+            match address {
+                Some(address::Address::SocketAddress(address)) if healthy => {
+                    let port = match address.port_specifier {
+                        Some(PortSpecifier::PortValue(port)) => u16::try_from(port).ok(),
+                        _ => None,
+                    };
+                    match (address.address.parse(), port) {
+                        (Ok(ip), Some(port)) => addresses.push(SocketAddr::new(ip, port)),
+                        _ => tracing::debug!("xds; skipping endpoint {:?}", address),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    priorities
+        .into_values()
+        .find(|addresses| !addresses.is_empty())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::envoy::config::route::v3::{
+        weighted_cluster::ClusterWeight, Route as RouteProto, RouteAction, RouteMatch,
+        WeightedCluster,
+    };
+
+    fn virtual_host(name: &str, domains: &[&str], routes: Vec<RouteProto>) -> VirtualHost {
+        VirtualHost {
+            name: name.into(),
+            domains: domains.iter().map(|&domain| domain.into()).collect(),
+            routes,
+        }
+    }
+
+    fn route(path: PathSpecifier, cluster: ClusterSpecifier) -> RouteProto {
+        RouteProto {
+            name: String::new(),
+            r#match: Some(RouteMatch {
+                path_specifier: Some(path),
+                case_sensitive: None,
+                headers: Vec::new(),
+            }),
+            action: Some(Action::Route(RouteAction {
+                cluster_specifier: Some(cluster),
+            })),
+        }
+    }
+
+    #[test]
+    fn picks_the_best_virtual_host() {
+        let hosts = vec![
+            virtual_host("any", &["*"], vec![]),
+            virtual_host("suffix", &["*.example.com"], vec![]),
+            virtual_host("longer-suffix", &["*.svc.example.com"], vec![]),
+            virtual_host("prefix", &["foo.*"], vec![]),
+            virtual_host("exact", &["Foo.svc.example.com:50051"], vec![]),
+        ];
+        let best = |host| super::virtual_host(&hosts, host).map(|host| host.name.as_str());
+
+        assert_eq!(best("foo.svc.example.com:50051"), Some("exact"));
+        assert_eq!(best("bar.svc.example.com"), Some("longer-suffix"));
+        assert_eq!(best("bar.example.com"), Some("suffix"));
+        assert_eq!(best("foo.bar"), Some("prefix"));
+        assert_eq!(best("bar"), Some("any"));
+        assert_eq!(super::virtual_host(&hosts[1..2], "bar"), None);
+    }
+
+    #[test]
+    fn converts_and_matches_routes() {
+        let config = RouteConfiguration {
+            name: "routes".into(),
+            virtual_hosts: vec![virtual_host(
+                "host",
+                &["*"],
+                vec![
+                    route(
+                        PathSpecifier::Path("/foo.Foo/Bar".into()),
+                        ClusterSpecifier::WeightedClusters(WeightedCluster {
+                            clusters: vec![
+                                ClusterWeight {
+                                    name: "a".into(),
+                                    weight: Some(3),
+                                },
+                                ClusterWeight {
+                                    name: "b".into(),
+                                    weight: Some(0),
+                                },
+                            ],
+                        }),
+                    ),
+                    route(
+                        PathSpecifier::Prefix("/foo.Foo/".into()),
+                        ClusterSpecifier::ClusterHeader("x-cluster".into()),
+                    ),
+                    route(
+                        PathSpecifier::Prefix("".into()),
+                        ClusterSpecifier::Cluster("c".into()),
+                    ),
+                ],
+            )],
+        };
+
+        let routes = routes(&config, "foo").unwrap().unwrap();
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].clusters, vec![("a".to_string(), 3)]);
+        assert!(routes[0].matches("/foo.Foo/Bar"));
+        assert!(!routes[0].matches("/foo.Foo/Bar2"));
+        assert!(!routes[0].matches("/foo.foo/bar"));
+        assert!(routes[1].matches("/foo.Foo/Baz"));
+
+        let mut insensitive = routes[0].clone();
+        insensitive.case_sensitive = false;
+        assert!(insensitive.matches("/foo.foo/bar"));
+    }
+
+    #[test]
+    fn rejects_routes_without_clusters() {
+        let config = RouteConfiguration {
+            name: "routes".into(),
+            virtual_hosts: vec![virtual_host(
+                "host",
+                &["*"],
+                vec![route(
+                    PathSpecifier::Prefix("".into()),
+                    ClusterSpecifier::Cluster("".into()),
+                )],
+            )],
+        };
+
+        assert!(routes(&config, "foo").is_err());
+    }
+}
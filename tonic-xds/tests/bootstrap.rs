@@ -0,0 +1,34 @@
+use std::{path::PathBuf, process::Command};
+
+#[test]
+fn bootstrap() {
+    let iface_files = &[
+        "proto/envoy/service/discovery/v3/discovery.proto",
+        "proto/envoy/config/listener/v3/listener.proto",
+        "proto/envoy/extensions/filters/network/http_connection_manager/v3/http_connection_manager.proto",
+        "proto/envoy/config/cluster/v3/cluster.proto",
+    ];
+    let dirs = &["proto"];
+
+    let out_dir = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join("generated");
+
+    tonic_build::configure()
+        .build_client(true)
+        .build_server(true)
+        .build_transport(false)
+        .out_dir(&out_dir)
+        .compile(iface_files, dirs)
+        .unwrap();
+
+    let status = Command::new("git")
+        .arg("diff")
+        .arg("--exit-code")
+        .arg("--")
+        .arg(&out_dir)
+        .status()
+        .unwrap();
+
+    assert!(status.success(), "You should commit the protobuf files");
+}
@@ -0,0 +1,424 @@
+use prost::Message;
+use prost_types::Any;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::watch};
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{transport::Server, Code, Request, Response, Status, Streaming};
+use tonic_health::{
+    pb::{health_client::HealthClient, HealthCheckRequest},
+    ServingStatus,
+};
+use tonic_xds::{
+    pb::envoy::{
+        config::{
+            cluster::v3::{cluster, Cluster},
+            core::v3::{address, socket_address::PortSpecifier, Address, SocketAddress},
+            endpoint::v3::{
+                lb_endpoint, ClusterLoadAssignment, Endpoint, LbEndpoint, LocalityLbEndpoints,
+            },
+            listener::v3::{ApiListener, Listener},
+            route::v3::{
+                route, route_action::ClusterSpecifier, route_match::PathSpecifier,
+                weighted_cluster::ClusterWeight, Route, RouteAction, RouteConfiguration,
+                RouteMatch, VirtualHost, WeightedCluster,
+            },
+        },
+        extensions::filters::network::http_connection_manager::v3::{
+            http_connection_manager::RouteSpecifier, HttpConnectionManager, Rds,
+        },
+        service::discovery::v3::{
+            aggregated_discovery_service_server::{
+                AggregatedDiscoveryService, AggregatedDiscoveryServiceServer,
+            },
+            DiscoveryRequest, DiscoveryResponse,
+        },
+    },
+    Bootstrap, XdsChannel,
+};
+
+const LISTENER: &str = "type.googleapis.com/envoy.config.listener.v3.Listener";
+const ROUTE_CONFIGURATION: &str = "type.googleapis.com/envoy.config.route.v3.RouteConfiguration";
+const CLUSTER: &str = "type.googleapis.com/envoy.config.cluster.v3.Cluster";
+const CLUSTER_LOAD_ASSIGNMENT: &str =
+    "type.googleapis.com/envoy.config.endpoint.v3.ClusterLoadAssignment";
+const HTTP_CONNECTION_MANAGER: &str = "type.googleapis.com/envoy.extensions.filters.network.http_connection_manager.v3.HttpConnectionManager";
+
+type Resources = HashMap<&'static str, Vec<Any>>;
+
+/// A management server, which sends all of its resources of a type whenever they change or
+/// the resources a client subscribes to do.
+struct Ads {
+    resources: watch::Receiver<Resources>,
+    requests: Arc<Mutex<Vec<DiscoveryRequest>>>,
+}
+
+#[tonic::async_trait]
+impl AggregatedDiscoveryService for Ads {
+    type StreamAggregatedResourcesStream = ReceiverStream<Result<DiscoveryResponse, Status>>;
+
+    async fn stream_aggregated_resources(
+        &self,
+        request: Request<Streaming<DiscoveryRequest>>,
+    ) -> Result<Response<Self::StreamAggregatedResourcesStream>, Status> {
+        let mut stream = request.into_inner();
+        let mut resources = self.resources.clone();
+        let requests = self.requests.clone();
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+
+        tokio::spawn(async move {
+            let mut subscriptions = HashMap::<String, Vec<String>>::new();
+            let mut nonce = 0;
+            let mut respond = |type_url: &str, resources: &Resources| {
+                nonce += 1;
+                let response = DiscoveryResponse {
+                    version_info: nonce.to_string(),
+                    resources: resources.get(type_url).cloned().unwrap_or_default(),
+                    canary: false,
+                    type_url: type_url.into(),
+                    nonce: nonce.to_string(),
+                };
+                tx.try_send(Ok(response)).is_ok()
+            };
+
+            loop {
+                tokio::select! {
+                    request = stream.message() => {
+                        let request = match request {
+                            Ok(Some(request)) => request,
+                            _ => return,
+                        };
+                        requests.lock().unwrap().push(request.clone());
+
+                        let names = subscriptions.insert(request.type_url.clone(), request.resource_names);
+                        if request.response_nonce.is_empty() || names != subscriptions.get(&request.type_url).cloned() {
+                            let current = resources.borrow().clone();
+                            if !respond(&request.type_url, &current) {
+                                return;
+                            }
+                        }
+                    }
+                    changed = resources.changed() => {
+                        if changed.is_err() {
+                            return;
+                        }
+                        let current = resources.borrow().clone();
+                        for type_url in subscriptions.keys() {
+                            if !respond(type_url, &current) {
+                                return;
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+struct ManagementServer {
+    resources: watch::Sender<Resources>,
+    requests: Arc<Mutex<Vec<DiscoveryRequest>>>,
+    bootstrap: Bootstrap,
+}
+
+async fn management_server(resources: Resources) -> ManagementServer {
+    let (tx, rx) = watch::channel(resources);
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let ads = Ads {
+        resources: rx,
+        requests: requests.clone(),
+    };
+
+    let addr = serve(|server| server.add_service(AggregatedDiscoveryServiceServer::new(ads))).await;
+    let bootstrap = Bootstrap::from_json(&format!(
+        r#"{{
+            "xds_servers": [{{ "server_uri": "{}", "channel_creds": [{{ "type": "insecure" }}] }}],
+            "node": {{ "id": "test" }}
+        }}"#,
+        addr
+    ))
+    .unwrap();
+
+    ManagementServer {
+        resources: tx,
+        requests,
+        bootstrap,
+    }
+}
+
+async fn serve<F>(router: F) -> SocketAddr
+where
+    F: FnOnce(&mut Server) -> tonic::transport::server::Router,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = router(&mut Server::builder());
+    tokio::spawn(router.serve_with_incoming(TcpListenerStream::new(listener)));
+
+    addr
+}
+
+/// Starts a backend whose health service only knows the service `name`.
+async fn backend(name: &str) -> SocketAddr {
+    let (mut reporter, service) = tonic_health::server::health_reporter();
+    reporter
+        .set_service_status(name, ServingStatus::Serving)
+        .await;
+
+    serve(|server| server.add_service(service)).await
+}
+
+/// Returns the number of the calls the backend with the service `name` answered.
+async fn answered(client: &mut HealthClient<XdsChannel>, name: &str, calls: usize) -> usize {
+    let mut answered = 0;
+    for _ in 0..calls {
+        let request = HealthCheckRequest {
+            service: name.into(),
+        };
+        match client.check(request).await {
+            Ok(_) => answered += 1,
+            Err(status) => assert_eq!(status.code(), Code::NotFound, "{:?}", status),
+        }
+    }
+    answered
+}
+
+fn any<M: Message>(type_url: &str, message: &M) -> Any {
+    Any {
+        type_url: type_url.into(),
+        value: message.encode_to_vec(),
+    }
+}
+
+fn listener(name: &str, route_config_name: &str) -> Any {
+    let manager = HttpConnectionManager {
+        stat_prefix: String::new(),
+        route_specifier: Some(RouteSpecifier::Rds(Rds {
+            route_config_name: route_config_name.into(),
+        })),
+    };
+    let listener = Listener {
+        name: name.into(),
+        address: None,
+        api_listener: Some(ApiListener {
+            api_listener: Some(any(HTTP_CONNECTION_MANAGER, &manager)),
+        }),
+    };
+    any(LISTENER, &listener)
+}
+
+fn route_config(name: &str, clusters: ClusterSpecifier) -> Any {
+    let route = Route {
+        name: String::new(),
+        r#match: Some(RouteMatch {
+            path_specifier: Some(PathSpecifier::Prefix("/grpc.health.v1.Health/".into())),
+            case_sensitive: None,
+            headers: Vec::new(),
+        }),
+        action: Some(route::Action::Route(RouteAction {
+            cluster_specifier: Some(clusters),
+        })),
+    };
+    let config = RouteConfiguration {
+        name: name.into(),
+        virtual_hosts: vec![VirtualHost {
+            name: "host".into(),
+            domains: vec!["my-service*".into()],
+            routes: vec![route],
+        }],
+    };
+    any(ROUTE_CONFIGURATION, &config)
+}
+
+fn eds_cluster(name: &str, service_name: &str) -> Any {
+    let cluster = Cluster {
+        name: name.into(),
+        cluster_discovery_type: Some(cluster::ClusterDiscoveryType::Type(
+            cluster::DiscoveryType::Eds as i32,
+        )),
+        eds_cluster_config: Some(cluster::EdsClusterConfig {
+            service_name: service_name.into(),
+        }),
+        lb_policy: 0,
+        load_assignment: None,
+    };
+    any(CLUSTER, &cluster)
+}
+
+fn static_cluster(name: &str, addr: SocketAddr) -> Any {
+    let cluster = Cluster {
+        name: name.into(),
+        cluster_discovery_type: Some(cluster::ClusterDiscoveryType::Type(
+            cluster::DiscoveryType::Static as i32,
+        )),
+        eds_cluster_config: None,
+        lb_policy: 0,
+        load_assignment: Some(assignment(name, addr)),
+    };
+    any(CLUSTER, &cluster)
+}
+
+fn assignment(cluster_name: &str, addr: SocketAddr) -> ClusterLoadAssignment {
+    let endpoint = Endpoint {
+        address: Some(Address {
+            address: Some(address::Address::SocketAddress(SocketAddress {
+                protocol: 0,
+                address: addr.ip().to_string(),
+                port_specifier: Some(PortSpecifier::PortValue(addr.port().into())),
+            })),
+        }),
+        hostname: String::new(),
+    };
+    ClusterLoadAssignment {
+        cluster_name: cluster_name.into(),
+        endpoints: vec![LocalityLbEndpoints {
+            locality: None,
+            lb_endpoints: vec![LbEndpoint {
+                host_identifier: Some(lb_endpoint::HostIdentifier::Endpoint(endpoint)),
+                health_status: 0,
+                load_balancing_weight: None,
+            }],
+            load_balancing_weight: None,
+            priority: 0,
+        }],
+    }
+}
+
+#[tokio::test]
+async fn follows_listeners_to_their_endpoints() {
+    let (a, b) = (backend("a").await, backend("b").await);
+    let resources = |addr| {
+        HashMap::from([
+            (LISTENER, vec![listener("my-service:50051", "routes")]),
+            (
+                ROUTE_CONFIGURATION,
+                vec![route_config(
+                    "routes",
+                    ClusterSpecifier::Cluster("cluster".into()),
+                )],
+            ),
+            (CLUSTER, vec![eds_cluster("cluster", "endpoints")]),
+            (
+                CLUSTER_LOAD_ASSIGNMENT,
+                vec![any(CLUSTER_LOAD_ASSIGNMENT, &assignment("endpoints", addr))],
+            ),
+        ])
+    };
+    let server = management_server(resources(a)).await;
+
+    let channel = XdsChannel::with_bootstrap("xds:///my-service:50051", server.bootstrap).unwrap();
+    let mut client = HealthClient::new(channel);
+    assert_eq!(answered(&mut client, "a", 3).await, 3);
+
+    server.resources.send_replace(resources(b));
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while answered(&mut client, "b", 1).await == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    let requests = server.requests.lock().unwrap();
+    assert!(requests[0].node.is_some());
+    assert!(requests[1..].iter().all(|request| request.node.is_none()));
+    assert!(requests
+        .iter()
+        .all(|request| request.error_detail.is_none()));
+}
+
+#[tokio::test]
+async fn splits_calls_between_weighted_clusters() {
+    let (a, b) = (backend("a").await, backend("b").await);
+    let clusters = WeightedCluster {
+        clusters: vec![
+            ClusterWeight {
+                name: "a".into(),
+                weight: Some(1),
+            },
+            ClusterWeight {
+                name: "b".into(),
+                weight: Some(3),
+            },
+        ],
+    };
+    let server = management_server(HashMap::from([
+        (LISTENER, vec![listener("my-service", "routes")]),
+        (
+            ROUTE_CONFIGURATION,
+            vec![route_config(
+                "routes",
+                ClusterSpecifier::WeightedClusters(clusters),
+            )],
+        ),
+        (
+            CLUSTER,
+            vec![static_cluster("a", a), static_cluster("b", b)],
+        ),
+    ]))
+    .await;
+
+    let channel = XdsChannel::with_bootstrap("xds:///my-service", server.bootstrap).unwrap();
+    let mut client = HealthClient::new(channel);
+    assert_eq!(answered(&mut client, "a", 8).await, 2);
+}
+
+#[tokio::test]
+async fn fails_calls_to_unknown_listeners() {
+    let server = management_server(HashMap::from([(
+        LISTENER,
+        vec![listener("other-service", "routes")],
+    )]))
+    .await;
+
+    let channel = XdsChannel::with_bootstrap("xds:///my-service", server.bootstrap).unwrap();
+    let mut client = HealthClient::new(channel);
+    let status = client
+        .check(HealthCheckRequest::default())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert!(status.message().contains("my-service"), "{}", status);
+}
+
+#[tokio::test]
+async fn rejects_invalid_resources() {
+    let server = management_server(HashMap::from([
+        (LISTENER, vec![listener("my-service", "routes")]),
+        (
+            ROUTE_CONFIGURATION,
+            vec![route_config(
+                "routes",
+                ClusterSpecifier::Cluster(String::new()),
+            )],
+        ),
+    ]))
+    .await;
+    let requests = server.requests.clone();
+
+    let channel = XdsChannel::with_bootstrap("xds:///my-service", server.bootstrap).unwrap();
+    let mut client = HealthClient::new(channel);
+    let call = tokio::time::timeout(
+        Duration::from_millis(500),
+        client.check(HealthCheckRequest::default()),
+    );
+    assert!(call.await.is_err(), "calls wait for valid routes");
+
+    let requests = requests.lock().unwrap();
+    let nack = requests
+        .iter()
+        .find(|request| request.error_detail.is_some())
+        .unwrap();
+    assert_eq!(nack.type_url, ROUTE_CONFIGURATION);
+    assert_eq!(nack.version_info, "");
+    assert_eq!(
+        nack.error_detail.as_ref().unwrap().code,
+        Code::InvalidArgument as i32
+    );
+}
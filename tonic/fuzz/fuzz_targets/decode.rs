@@ -0,0 +1,59 @@
+//! Feeds arbitrary bytes, split into arbitrary chunks, through `Streaming`.
+//!
+//! The first byte of the input picks the chunk size, the rest is the body.
+//! Decoding must never panic or hang, whatever the input.
+
+#![no_main]
+
+use bytes::Bytes;
+use futures_util::FutureExt;
+use http_body::Body;
+use libfuzzer_sys::fuzz_target;
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tonic::{codec::IdentityCodec, Status, Streaming};
+
+struct ChunkedBody(VecDeque<Bytes>);
+
+impl Body for ChunkedBody {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        Poll::Ready(self.0.pop_front().map(Ok))
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(None))
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let (chunk_size, data) = match data.split_first() {
+        Some((chunk_size, data)) => (*chunk_size as usize + 1, data),
+        None => return,
+    };
+
+    let chunks = data
+        .chunks(chunk_size)
+        .map(Bytes::copy_from_slice)
+        .collect();
+    let mut stream = Streaming::new_request(
+        IdentityCodec::default(),
+        ChunkedBody(chunks),
+        None,
+        Some(1024),
+    );
+
+    // The body is always ready, so every call completes immediately.
+    while let Some(Ok(Some(_))) = stream.message().now_or_never() {}
+});
@@ -1,9 +1,12 @@
-use crate::codec::compression::{CompressionEncoding, EnabledCompressionEncodings};
+use crate::codec::compression::{
+    CompressionEncoding, EnabledCompressionEncodings, RequestCompressionOverride,
+};
 use crate::{
     body::BoxBody,
     client::GrpcService,
-    codec::{encode_client, Codec, Decoder, Streaming},
+    codec::{encode_client, Codec, Decoder, EncodeError, Streaming},
     request::SanitizeHeaders,
+    response::MaxMessageSize,
     Code, Request, Response, Status,
 };
 use futures_core::Stream;
@@ -132,6 +135,9 @@ impl<T> Grpc<T> {
 
     /// Limits the maximum size of a decoded message.
     ///
+    /// Messages whose length prefix exceeds the limit are rejected with
+    /// [`Code::ResourceExhausted`] before their body is buffered.
+    ///
     /// # Example
     ///
     /// The most common way of using this is through a client generated by tonic-build:
@@ -293,24 +299,40 @@ impl<T> Grpc<T> {
         M1: Send + Sync + 'static,
         M2: Send + Sync + 'static,
     {
+        let send_compression_encoding = request
+            .extensions()
+            .get::<RequestCompressionOverride>()
+            .map(|compression_override| compression_override.0)
+            .unwrap_or(self.config.send_compression_encodings);
+
+        let encode_error = EncodeError::default();
+
         let request = request
             .map(|s| {
                 encode_client(
                     codec.encoder(),
                     s,
-                    self.config.send_compression_encodings,
+                    send_compression_encoding,
                     self.config.max_encoding_message_size,
+                    encode_error.clone(),
                 )
             })
             .map(BoxBody::new);
 
-        let request = self.config.prepare_request(request, path);
+        let request = self.config.prepare_request(
+            request,
+            path,
+            send_compression_encoding,
+            codec.content_type(),
+        );
 
-        let response = self
-            .inner
-            .call(request)
-            .await
-            .map_err(Status::from_error_generic)?;
+        let response = self.inner.call(request).await.map_err(|err| {
+            // If encoding the request failed the transport only reports a reset stream,
+            // so prefer the original error.
+            encode_error
+                .take()
+                .unwrap_or_else(|| Status::from_error_generic(err))
+        })?;
 
         let decoder = codec.decoder();
 
@@ -349,15 +371,18 @@ impl<T> Grpc<T> {
             true
         };
 
+        // The channel may limit the messages of the method further, from its service config.
+        let max_message_size = match (
+            self.config.max_decoding_message_size,
+            response.extensions().get::<MaxMessageSize>(),
+        ) {
+            (Some(limit), Some(MaxMessageSize(method_limit))) => Some(limit.min(*method_limit)),
+            (limit, method_limit) => limit.or(method_limit.map(|limit| limit.0)),
+        };
+
         let response = response.map(|body| {
             if expect_additional_trailers {
-                Streaming::new_response(
-                    decoder,
-                    body,
-                    status_code,
-                    encoding,
-                    self.config.max_decoding_message_size,
-                )
+                Streaming::new_response(decoder, body, status_code, encoding, max_message_size)
             } else {
                 Streaming::new_empty(decoder, body)
             }
@@ -372,6 +397,8 @@ impl GrpcConfig {
         &self,
         request: Request<BoxBody>,
         path: PathAndQuery,
+        send_compression_encoding: Option<CompressionEncoding>,
+        content_type: &'static str,
     ) -> http::Request<BoxBody> {
         let scheme = self.origin.scheme().cloned();
         let authority = self.origin.authority().cloned();
@@ -398,9 +425,9 @@ impl GrpcConfig {
         // Set the content type
         request
             .headers_mut()
-            .insert(CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+            .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
 
-        if let Some(encoding) = self.send_compression_encodings {
+        if let Some(encoding) = send_compression_encoding {
             request.headers_mut().insert(
                 crate::codec::compression::ENCODING_HEADER,
                 encoding.into_header_value(),
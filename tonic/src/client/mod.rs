@@ -15,9 +15,22 @@
 //! which is cheap as all client instances will share the same channel for
 //! communication. For more details, see
 //! [transport::Channel](../transport/struct.Channel.html#multiplexing-requests).
+//!
+//! ## Sending streaming requests
+//!
+//! Streaming requests take any `Stream` of messages. When the messages are
+//! produced imperatively, [`request_channel`] creates a [`RequestSender`] to
+//! send them with and the [`RequestStream`] to pass as the request.
+//!
+//! Clients generated by `tonic-build` do this in their `<method>_with_sender`
+//! methods, which return the sender along with the call. For bidirectional
+//! streaming calls, the sender and the response's `Streaming` can then be
+//! driven by independent tasks.
 
 mod grpc;
+mod sender;
 mod service;
 
 pub use self::grpc::Grpc;
+pub use self::sender::{request_channel, RequestSender, RequestStream, SendError};
 pub use self::service::GrpcService;
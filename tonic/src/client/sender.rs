@@ -0,0 +1,173 @@
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_channel::mpsc;
+use futures_core::Stream;
+use futures_util::future::poll_fn;
+
+/// Creates a bounded channel to send the messages of a streaming request through.
+///
+/// The [`RequestStream`] is passed as the request of a client or bidirectional
+/// streaming call, the [`RequestSender`] is then used to send it messages from
+/// anywhere. At most `buffer` messages, plus one for each sender, are queued
+/// before [`RequestSender::send`] waits for the call to send them, and the
+/// request stream ends once every sender has been dropped.
+///
+/// ```rust,no_run
+/// # use tonic::client::request_channel;
+/// # async fn run(mut client: tonic::client::Grpc<tonic::transport::Channel>) {
+/// let (mut sender, requests) = request_channel::<u32>(16);
+///
+/// tokio::spawn(async move {
+///     for i in 0..100 {
+///         if sender.send(i).await.is_err() {
+///             // The call has ended, its result holds the reason.
+///             break;
+///         }
+///     }
+/// });
+///
+/// // Pass `requests` to the generated client method, e.g.
+/// // `client.record_route(requests).await`.
+/// # drop((client, requests));
+/// # }
+/// ```
+///
+pub fn request_channel<T>(buffer: usize) -> (RequestSender<T>, RequestStream<T>) {
+    let (tx, rx) = mpsc::channel(buffer);
+    (RequestSender { tx }, RequestStream { rx })
+}
+
+/// Sends the messages of a streaming request, created by [`request_channel`].
+///
+/// Senders can be cloned to send messages from several tasks.
+pub struct RequestSender<T> {
+    tx: mpsc::Sender<T>,
+}
+
+impl<T> RequestSender<T> {
+    /// Sends a message on the request stream, waiting for the call to make room
+    /// for it if the buffer is full.
+    ///
+    /// Returns the message back in a [`SendError`] if the call has ended, either
+    /// because it completed or because it failed. The call's result then holds
+    /// the reason.
+    pub async fn send(&mut self, message: T) -> Result<(), SendError<T>> {
+        if poll_fn(|cx| self.tx.poll_ready(cx)).await.is_err() {
+            return Err(SendError(message));
+        }
+
+        self.tx
+            .try_send(message)
+            .map_err(|error| SendError(error.into_inner()))
+    }
+
+    /// Returns `true` if the call has ended and no more messages can be sent.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+impl<T> Clone for RequestSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for RequestSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestSender")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+/// The messages sent through a [`RequestSender`], to be passed as the request of
+/// a streaming call.
+pub struct RequestStream<T> {
+    rx: mpsc::Receiver<T>,
+}
+
+impl<T> Stream for RequestStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        Pin::new(&mut self.rx).poll_next(cx)
+    }
+}
+
+impl<T> fmt::Debug for RequestStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestStream").finish()
+    }
+}
+
+/// Error returned by [`RequestSender::send`] once the call has ended, holding
+/// the message that couldn't be sent.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the call has ended")
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn stream_ends_once_senders_are_dropped() {
+        let (mut sender, mut requests) = request_channel(2);
+        let mut other = sender.clone();
+
+        sender.send(1).await.unwrap();
+        other.send(2).await.unwrap();
+        drop(sender);
+        drop(other);
+
+        assert_eq!(requests.next().await, Some(1));
+        assert_eq!(requests.next().await, Some(2));
+        assert_eq!(requests.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn send_fails_once_the_call_ended() {
+        let (mut sender, requests) = request_channel(1);
+        assert!(!sender.is_closed());
+
+        drop(requests);
+
+        assert!(sender.is_closed());
+        assert_eq!(sender.send(1).await, Err(SendError(1)));
+    }
+
+    #[tokio::test]
+    async fn send_waits_for_room_in_the_buffer() {
+        let (mut sender, mut requests) = request_channel(0);
+        sender.send(1).await.unwrap();
+
+        let mut send = Box::pin(sender.send(2));
+        assert!((&mut send).now_or_never().is_none());
+
+        assert_eq!(requests.next().await, Some(1));
+        send.await.unwrap();
+        assert_eq!(requests.next().await, Some(2));
+    }
+}
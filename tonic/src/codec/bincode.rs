@@ -0,0 +1,181 @@
+use super::{BufferSettings, Codec, DecodeBuf, Decoder, Encoder};
+use crate::codec::EncodeBuf;
+use crate::{Code, Status};
+use bincode::Options;
+use bytes::{Buf, BufMut};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// A [`Codec`] that implements `application/grpc+bincode` via the bincode library.
+///
+/// bincode isn't self describing and has no schema evolution, so this is best
+/// suited for services where both ends are built from the same Rust types.
+#[derive(Debug, Clone)]
+pub struct BincodeCodec<T, U> {
+    _pd: PhantomData<(T, U)>,
+}
+
+impl<T, U> Default for BincodeCodec<T, U> {
+    fn default() -> Self {
+        Self { _pd: PhantomData }
+    }
+}
+
+impl<T, U> BincodeCodec<T, U>
+where
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+{
+    /// Create a bincode [`Encoder`] with the provided [`BufferSettings`].
+    pub fn raw_encoder(buffer_settings: BufferSettings) -> BincodeEncoder<T> {
+        BincodeEncoder {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+
+    /// Create a bincode [`Decoder`] with the provided [`BufferSettings`].
+    pub fn raw_decoder(buffer_settings: BufferSettings) -> BincodeDecoder<U> {
+        BincodeDecoder {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+}
+
+impl<T, U> Codec for BincodeCodec<T, U>
+where
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+{
+    type Encode = T;
+    type Decode = U;
+
+    type Encoder = BincodeEncoder<T>;
+    type Decoder = BincodeDecoder<U>;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        Self::raw_encoder(BufferSettings::default())
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        Self::raw_decoder(BufferSettings::default())
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/grpc+bincode"
+    }
+}
+
+/// A [`Encoder`] that knows how to encode `T` with bincode.
+#[derive(Debug, Clone)]
+pub struct BincodeEncoder<T> {
+    _pd: PhantomData<T>,
+    buffer_settings: BufferSettings,
+}
+
+impl<T: Serialize> Encoder for BincodeEncoder<T> {
+    type Item = T;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        bincode::serialize_into(buf.writer(), &item).map_err(|error| {
+            Status::new(
+                Code::Internal,
+                format!("Error serializing bincode message: {}", error),
+            )
+        })
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+/// A [`Decoder`] that knows how to decode `U` with bincode.
+#[derive(Debug, Clone)]
+pub struct BincodeDecoder<U> {
+    _pd: PhantomData<U>,
+    buffer_settings: BufferSettings,
+}
+
+impl<U: DeserializeOwned> Decoder for BincodeDecoder<U> {
+    type Item = U;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        // Lengths in the message can not be larger than the message itself, so that a peer
+        // can not make the decoder allocate more than it sent.
+        let item = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(buf.remaining() as u64)
+            .deserialize_from(buf.reader())
+            .map(Option::Some)
+            .map_err(from_decode_error)?;
+
+        Ok(item)
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+fn from_decode_error(error: bincode::Error) -> crate::Status {
+    // Map parse errors to an INTERNAL status code, as is done for protobuf, see
+    // https://github.com/grpc/grpc/blob/master/doc/statuscodes.md
+    Status::new(Code::Internal, error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+    use std::collections::BTreeMap;
+
+    type Message = BTreeMap<String, Vec<u32>>;
+
+    #[test]
+    fn round_trip() {
+        let mut msg = Message::new();
+        msg.insert("numbers".to_string(), vec![1, 2, 3]);
+
+        let mut bytes = BytesMut::new();
+        BincodeCodec::<Message, Message>::default()
+            .encoder()
+            .encode(msg.clone(), &mut EncodeBuf::new(&mut bytes))
+            .unwrap();
+
+        let len = bytes.len();
+        let decoded = BincodeCodec::<Message, Message>::default()
+            .decoder()
+            .decode(&mut DecodeBuf::new(&mut bytes, len))
+            .unwrap();
+        assert_eq!(decoded, Some(msg));
+    }
+
+    #[test]
+    fn decode_truncated_message() {
+        let mut bytes = BytesMut::from(&[1u8, 0, 0][..]);
+        let len = bytes.len();
+
+        let status = BincodeCodec::<Message, Message>::default()
+            .decoder()
+            .decode(&mut DecodeBuf::new(&mut bytes, len))
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Internal);
+    }
+
+    #[test]
+    fn decode_oversized_length_prefix() {
+        let mut bytes = BytesMut::from(&(1u64 << 40).to_le_bytes()[..]);
+        let len = bytes.len();
+
+        let status = BincodeCodec::<String, String>::default()
+            .decoder()
+            .decode(&mut DecodeBuf::new(&mut bytes, len))
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Internal);
+    }
+}
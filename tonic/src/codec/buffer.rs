@@ -1,13 +1,21 @@
 use bytes::buf::UninitSlice;
-use bytes::{Buf, BufMut, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 
 /// A specialized buffer to decode gRPC messages from.
 #[derive(Debug)]
 pub struct DecodeBuf<'a> {
-    buf: &'a mut BytesMut,
+    buf: Source<'a>,
     len: usize,
 }
 
+// The bytes backing a `DecodeBuf`, either the stream's own buffer or a slice
+// of a body chunk that contained the whole message.
+#[derive(Debug)]
+enum Source<'a> {
+    Buffered(&'a mut BytesMut),
+    Chunk(&'a mut Bytes),
+}
+
 /// A specialized buffer to encode gRPC messages into.
 #[derive(Debug)]
 pub struct EncodeBuf<'a> {
@@ -16,7 +24,17 @@ pub struct EncodeBuf<'a> {
 
 impl<'a> DecodeBuf<'a> {
     pub(crate) fn new(buf: &'a mut BytesMut, len: usize) -> Self {
-        DecodeBuf { buf, len }
+        DecodeBuf {
+            buf: Source::Buffered(buf),
+            len,
+        }
+    }
+
+    pub(crate) fn from_chunk(buf: &'a mut Bytes, len: usize) -> Self {
+        DecodeBuf {
+            buf: Source::Chunk(buf),
+            len,
+        }
     }
 }
 
@@ -28,7 +46,10 @@ impl Buf for DecodeBuf<'_> {
 
     #[inline]
     fn chunk(&self) -> &[u8] {
-        let ret = self.buf.chunk();
+        let ret = match &self.buf {
+            Source::Buffered(buf) => buf.chunk(),
+            Source::Chunk(buf) => buf.chunk(),
+        };
 
         if ret.len() > self.len {
             &ret[..self.len]
@@ -40,9 +61,23 @@ impl Buf for DecodeBuf<'_> {
     #[inline]
     fn advance(&mut self, cnt: usize) {
         assert!(cnt <= self.len);
-        self.buf.advance(cnt);
+        match &mut self.buf {
+            Source::Buffered(buf) => buf.advance(cnt),
+            Source::Chunk(buf) => buf.advance(cnt),
+        }
         self.len -= cnt;
     }
+
+    #[inline]
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        assert!(len <= self.len);
+        self.len -= len;
+        // Both sources split off the requested bytes without copying them.
+        match &mut self.buf {
+            Source::Buffered(buf) => buf.copy_to_bytes(len),
+            Source::Chunk(buf) => buf.copy_to_bytes(len),
+        }
+    }
 }
 
 impl<'a> EncodeBuf<'a> {
@@ -106,6 +141,24 @@ mod tests {
         assert!(!buf.has_remaining());
     }
 
+    #[test]
+    fn decode_buf_from_chunk() {
+        let mut payload = Bytes::from(vec![0u8; 50]);
+        let start = payload.as_ptr();
+        let mut buf = DecodeBuf::from_chunk(&mut payload, 20);
+
+        assert_eq!(buf.remaining(), 20);
+        assert_eq!(buf.chunk().len(), 20);
+
+        buf.advance(10);
+        let bytes = buf.copy_to_bytes(10);
+        assert_eq!(bytes.len(), 10);
+        assert_eq!(bytes.as_ptr(), start.wrapping_add(10));
+        assert!(!buf.has_remaining());
+
+        assert_eq!(payload.len(), 30);
+    }
+
     #[test]
     fn encode_buf() {
         let mut bytes = BytesMut::with_capacity(100);
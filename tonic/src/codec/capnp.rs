@@ -0,0 +1,218 @@
+use super::{BufferSettings, Codec, DecodeBuf, Decoder, Encoder};
+use crate::codec::EncodeBuf;
+use crate::{Code, Status};
+use bytes::{Buf, BufMut};
+use capnp::message::{Allocator, ReaderOptions, TypedBuilder, TypedReader};
+use capnp::serialize::OwnedSegments;
+use capnp::traits::Owned;
+use std::{fmt, marker::PhantomData};
+
+/// A Cap'n Proto message with root struct `T`.
+///
+/// Messages are built with a [`TypedBuilder`] and converted with
+/// [`CapnpMessage::from_builder`] before being sent.
+pub struct CapnpMessage<T: Owned> {
+    reader: TypedReader<OwnedSegments, T>,
+}
+
+impl<T: Owned> CapnpMessage<T> {
+    /// Create a message from the contents of `builder`.
+    pub fn from_builder<A: Allocator>(builder: &TypedBuilder<T, A>) -> capnp::Result<Self> {
+        let words = capnp::serialize::write_message_to_words(builder.borrow_inner());
+        let reader = capnp::serialize::read_message(&words[..], ReaderOptions::new())?;
+
+        Ok(reader.into())
+    }
+
+    /// Get the root struct of the message.
+    pub fn get(&self) -> capnp::Result<T::Reader<'_>> {
+        self.reader.get()
+    }
+
+    /// Consumes `self`, returning the underlying [`TypedReader`].
+    pub fn into_inner(self) -> TypedReader<OwnedSegments, T> {
+        self.reader
+    }
+}
+
+impl<T: Owned> From<capnp::message::Reader<OwnedSegments>> for CapnpMessage<T> {
+    fn from(reader: capnp::message::Reader<OwnedSegments>) -> Self {
+        Self {
+            reader: TypedReader::new(reader),
+        }
+    }
+}
+
+impl<T: Owned> fmt::Debug for CapnpMessage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CapnpMessage").finish()
+    }
+}
+
+/// A [`Codec`] that implements `application/grpc+capnp` via the capnp library.
+#[derive(Debug, Clone)]
+pub struct CapnpCodec<T, U> {
+    _pd: PhantomData<(T, U)>,
+}
+
+impl<T, U> Default for CapnpCodec<T, U> {
+    fn default() -> Self {
+        Self { _pd: PhantomData }
+    }
+}
+
+impl<T, U> CapnpCodec<T, U>
+where
+    T: Owned + Send + 'static,
+    U: Owned + Send + 'static,
+{
+    /// Create a capnp [`Encoder`] with the provided [`BufferSettings`].
+    pub fn raw_encoder(buffer_settings: BufferSettings) -> CapnpEncoder<T> {
+        CapnpEncoder {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+
+    /// Create a capnp [`Decoder`] with the provided [`BufferSettings`].
+    pub fn raw_decoder(buffer_settings: BufferSettings) -> CapnpDecoder<U> {
+        CapnpDecoder {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+}
+
+impl<T, U> Codec for CapnpCodec<T, U>
+where
+    T: Owned + Send + 'static,
+    U: Owned + Send + 'static,
+{
+    type Encode = CapnpMessage<T>;
+    type Decode = CapnpMessage<U>;
+
+    type Encoder = CapnpEncoder<T>;
+    type Decoder = CapnpDecoder<U>;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        Self::raw_encoder(BufferSettings::default())
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        Self::raw_decoder(BufferSettings::default())
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/grpc+capnp"
+    }
+}
+
+/// A [`Encoder`] that knows how to encode [`CapnpMessage<T>`].
+#[derive(Debug)]
+pub struct CapnpEncoder<T> {
+    _pd: PhantomData<fn(T)>,
+    buffer_settings: BufferSettings,
+}
+
+impl<T: Owned> Encoder for CapnpEncoder<T> {
+    type Item = CapnpMessage<T>;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        let segments = item.into_inner().into_inner().into_segments();
+
+        capnp::serialize::write_message_segments(buf.writer(), &segments).map_err(|error| {
+            Status::new(
+                Code::Internal,
+                format!("Error serializing capnp message: {}", error),
+            )
+        })
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+/// A [`Decoder`] that knows how to decode [`CapnpMessage<U>`].
+#[derive(Debug)]
+pub struct CapnpDecoder<U> {
+    _pd: PhantomData<fn() -> U>,
+    buffer_settings: BufferSettings,
+}
+
+impl<U: Owned> Decoder for CapnpDecoder<U> {
+    type Item = CapnpMessage<U>;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        // The whole frame is available here, it is copied into word aligned
+        // segments as capnp requires.
+        let item = capnp::serialize::read_message(buf.reader(), ReaderOptions::new())
+            .map(|reader| Some(reader.into()))
+            .map_err(from_decode_error)?;
+
+        Ok(item)
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+fn from_decode_error(error: capnp::Error) -> crate::Status {
+    // Map parse errors to an INTERNAL status code, as is done for protobuf, see
+    // https://github.com/grpc/grpc/blob/master/doc/statuscodes.md
+    Status::new(Code::Internal, error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+
+    type Text = capnp::text::Owned;
+
+    #[test]
+    fn round_trip() {
+        let mut builder = TypedBuilder::<Text>::new_default();
+        builder.set_root("hello").unwrap();
+        let msg = CapnpMessage::from_builder(&builder).unwrap();
+
+        let mut codec = CapnpCodec::<Text, Text>::default();
+
+        let mut bytes = BytesMut::new();
+        codec
+            .encoder()
+            .encode(msg, &mut EncodeBuf::new(&mut bytes))
+            .unwrap();
+
+        let len = bytes.len();
+        let decoded = codec
+            .decoder()
+            .decode(&mut DecodeBuf::new(&mut bytes, len))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(decoded.get().unwrap(), "hello");
+    }
+
+    #[test]
+    fn decode_truncated_message() {
+        let mut bytes = BytesMut::from(&[0u8, 0, 0, 0, 8][..]);
+        let len = bytes.len();
+
+        let status = CapnpCodec::<Text, Text>::default()
+            .decoder()
+            .decode(&mut DecodeBuf::new(&mut bytes, len))
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Internal);
+    }
+
+    #[test]
+    fn messages_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<CapnpMessage<Text>>();
+    }
+}
@@ -1,9 +1,13 @@
 use super::encode::BUFFER_SIZE;
 use crate::{metadata::MetadataValue, Status};
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, BufMut, BytesMut};
 #[cfg(feature = "gzip")]
 use flate2::read::{GzDecoder, GzEncoder};
+#[cfg(feature = "deflate")]
+use flate2::read::{ZlibDecoder, ZlibEncoder};
 use std::fmt;
+#[cfg(feature = "zstd")]
+use zstd::stream::read::{Decoder as ZstdDecoder, Encoder as ZstdEncoder};
 
 pub(crate) const ENCODING_HEADER: &str = "grpc-encoding";
 pub(crate) const ACCEPT_ENCODING_HEADER: &str = "grpc-accept-encoding";
@@ -13,6 +17,10 @@ pub(crate) const ACCEPT_ENCODING_HEADER: &str = "grpc-accept-encoding";
 pub struct EnabledCompressionEncodings {
     #[cfg(feature = "gzip")]
     pub(crate) gzip: bool,
+    #[cfg(feature = "deflate")]
+    pub(crate) deflate: bool,
+    #[cfg(feature = "zstd")]
+    pub(crate) zstd: bool,
 }
 
 impl EnabledCompressionEncodings {
@@ -21,6 +29,10 @@ impl EnabledCompressionEncodings {
         match encoding {
             #[cfg(feature = "gzip")]
             CompressionEncoding::Gzip => self.gzip,
+            #[cfg(feature = "deflate")]
+            CompressionEncoding::Deflate => self.deflate,
+            #[cfg(feature = "zstd")]
+            CompressionEncoding::Zstd => self.zstd,
         }
     }
 
@@ -29,29 +41,45 @@ impl EnabledCompressionEncodings {
         match encoding {
             #[cfg(feature = "gzip")]
             CompressionEncoding::Gzip => self.gzip = true,
+            #[cfg(feature = "deflate")]
+            CompressionEncoding::Deflate => self.deflate = true,
+            #[cfg(feature = "zstd")]
+            CompressionEncoding::Zstd => self.zstd = true,
         }
     }
 
     pub(crate) fn into_accept_encoding_header_value(self) -> Option<http::HeaderValue> {
-        if self.is_gzip_enabled() {
-            Some(http::HeaderValue::from_static("gzip,identity"))
-        } else {
-            None
+        let mut value = BytesMut::new();
+        for &encoding in CompressionEncoding::encodings() {
+            if self.is_enabled(encoding) {
+                value.put_slice(encoding.as_str().as_bytes());
+                value.put_u8(b',');
+            }
         }
-    }
 
-    #[cfg(feature = "gzip")]
-    const fn is_gzip_enabled(&self) -> bool {
-        self.gzip
+        if value.is_empty() {
+            return None;
+        }
+
+        value.put_slice(b"identity");
+        Some(
+            http::HeaderValue::from_maybe_shared(value.freeze())
+                .expect("encoding names are valid header values"),
+        )
     }
 
-    #[cfg(not(feature = "gzip"))]
-    const fn is_gzip_enabled(&self) -> bool {
-        false
+    /// Returns `true` if no compression encoding is enabled.
+    fn is_empty(&self) -> bool {
+        !CompressionEncoding::encodings()
+            .iter()
+            .any(|&encoding| self.is_enabled(encoding))
     }
 }
 
 /// The compression encodings Tonic supports.
+///
+/// Messages are compressed at a fixed level, which is 6 for gzip and deflate and the
+/// default level of zstd, 3.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum CompressionEncoding {
@@ -59,6 +87,14 @@ pub enum CompressionEncoding {
     #[cfg(feature = "gzip")]
     #[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
     Gzip,
+    #[allow(missing_docs)]
+    #[cfg(feature = "deflate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+    Deflate,
+    #[allow(missing_docs)]
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    Zstd,
 }
 
 impl CompressionEncoding {
@@ -67,7 +103,7 @@ impl CompressionEncoding {
         map: &http::HeaderMap,
         enabled_encodings: EnabledCompressionEncodings,
     ) -> Option<Self> {
-        if !enabled_encodings.is_gzip_enabled() {
+        if enabled_encodings.is_empty() {
             return None;
         }
 
@@ -76,7 +112,17 @@ impl CompressionEncoding {
 
         split_by_comma(header_value_str).find_map(|value| match value {
             #[cfg(feature = "gzip")]
-            "gzip" => Some(CompressionEncoding::Gzip),
+            "gzip" if enabled_encodings.is_enabled(CompressionEncoding::Gzip) => {
+                Some(CompressionEncoding::Gzip)
+            }
+            #[cfg(feature = "deflate")]
+            "deflate" if enabled_encodings.is_enabled(CompressionEncoding::Deflate) => {
+                Some(CompressionEncoding::Deflate)
+            }
+            #[cfg(feature = "zstd")]
+            "zstd" if enabled_encodings.is_enabled(CompressionEncoding::Zstd) => {
+                Some(CompressionEncoding::Zstd)
+            }
             _ => None,
         })
     }
@@ -103,6 +149,14 @@ impl CompressionEncoding {
             "gzip" if enabled_encodings.is_enabled(CompressionEncoding::Gzip) => {
                 Ok(Some(CompressionEncoding::Gzip))
             }
+            #[cfg(feature = "deflate")]
+            "deflate" if enabled_encodings.is_enabled(CompressionEncoding::Deflate) => {
+                Ok(Some(CompressionEncoding::Deflate))
+            }
+            #[cfg(feature = "zstd")]
+            "zstd" if enabled_encodings.is_enabled(CompressionEncoding::Zstd) => {
+                Ok(Some(CompressionEncoding::Zstd))
+            }
             "identity" => Ok(None),
             other => {
                 let mut status = Status::unimplemented(format!(
@@ -123,28 +177,37 @@ impl CompressionEncoding {
         }
     }
 
-    pub(crate) fn into_header_value(self) -> http::HeaderValue {
-        match self {
+    #[allow(unused_variables)]
+    pub(crate) fn as_str(&self) -> &'static str {
+        match *self {
             #[cfg(feature = "gzip")]
-            CompressionEncoding::Gzip => http::HeaderValue::from_static("gzip"),
+            CompressionEncoding::Gzip => "gzip",
+            #[cfg(feature = "deflate")]
+            CompressionEncoding::Deflate => "deflate",
+            #[cfg(feature = "zstd")]
+            CompressionEncoding::Zstd => "zstd",
         }
     }
 
+    pub(crate) fn into_header_value(self) -> http::HeaderValue {
+        http::HeaderValue::from_static(self.as_str())
+    }
+
     pub(crate) fn encodings() -> &'static [Self] {
         &[
             #[cfg(feature = "gzip")]
             CompressionEncoding::Gzip,
+            #[cfg(feature = "deflate")]
+            CompressionEncoding::Deflate,
+            #[cfg(feature = "zstd")]
+            CompressionEncoding::Zstd,
         ]
     }
 }
 
 impl fmt::Display for CompressionEncoding {
-    #[allow(unused_variables)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
-            #[cfg(feature = "gzip")]
-            CompressionEncoding::Gzip => write!(f, "gzip"),
-        }
+        f.write_str(self.as_str())
     }
 }
 
@@ -166,15 +229,28 @@ pub(crate) fn compress(
     match encoding {
         #[cfg(feature = "gzip")]
         CompressionEncoding::Gzip => {
-            let mut gzip_encoder = GzEncoder::new(
-                &decompressed_buf[0..len],
-                // FIXME: support customizing the compression level
-                flate2::Compression::new(6),
-            );
+            let mut gzip_encoder =
+                GzEncoder::new(&decompressed_buf[0..len], flate2::Compression::new(6));
             let mut out_writer = bytes::BufMut::writer(out_buf);
 
             std::io::copy(&mut gzip_encoder, &mut out_writer)?;
         }
+        #[cfg(feature = "deflate")]
+        CompressionEncoding::Deflate => {
+            let mut deflate_encoder =
+                ZlibEncoder::new(&decompressed_buf[0..len], flate2::Compression::new(6));
+            let mut out_writer = bytes::BufMut::writer(out_buf);
+
+            std::io::copy(&mut deflate_encoder, &mut out_writer)?;
+        }
+        #[cfg(feature = "zstd")]
+        CompressionEncoding::Zstd => {
+            let mut zstd_encoder =
+                ZstdEncoder::new(&decompressed_buf[0..len], zstd::DEFAULT_COMPRESSION_LEVEL)?;
+            let mut out_writer = bytes::BufMut::writer(out_buf);
+
+            std::io::copy(&mut zstd_encoder, &mut out_writer)?;
+        }
     }
 
     decompressed_buf.advance(len);
@@ -202,6 +278,20 @@ pub(crate) fn decompress(
 
             std::io::copy(&mut gzip_decoder, &mut out_writer)?;
         }
+        #[cfg(feature = "deflate")]
+        CompressionEncoding::Deflate => {
+            let mut deflate_decoder = ZlibDecoder::new(&compressed_buf[0..len]);
+            let mut out_writer = bytes::BufMut::writer(out_buf);
+
+            std::io::copy(&mut deflate_decoder, &mut out_writer)?;
+        }
+        #[cfg(feature = "zstd")]
+        CompressionEncoding::Zstd => {
+            let mut zstd_decoder = ZstdDecoder::new(&compressed_buf[0..len])?;
+            let mut out_writer = bytes::BufMut::writer(out_buf);
+
+            std::io::copy(&mut zstd_decoder, &mut out_writer)?;
+        }
     }
 
     compressed_buf.advance(len);
@@ -209,6 +299,16 @@ pub(crate) fn decompress(
     Ok(())
 }
 
+/// Overrides the client's request compression for a single call.
+///
+/// Stored in the request extensions by [`Request::set_compression`] and
+/// [`Request::disable_compression`]. `None` sends the request uncompressed.
+///
+/// [`Request::set_compression`]: crate::Request::set_compression
+/// [`Request::disable_compression`]: crate::Request::disable_compression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RequestCompressionOverride(pub(crate) Option<CompressionEncoding>);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum SingleMessageCompressionOverride {
     /// Inherit whatever compression is already configured. If the stream is compressed this
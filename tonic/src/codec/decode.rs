@@ -1,24 +1,33 @@
+// The decoder is built on `BoxBody`, which is `Unpin`, so it never needs to
+// pin project or reach for unsafe code.
+#![forbid(unsafe_code)]
+
 use super::compression::{decompress, CompressionEncoding};
-use super::{DecodeBuf, Decoder, DEFAULT_MAX_MESSAGE_SIZE, HEADER_SIZE};
+use super::{pool, DecodeBuf, Decoder, DEFAULT_MAX_MESSAGE_SIZE, HEADER_SIZE};
 use crate::{body::BoxBody, metadata::MetadataMap, Code, Status};
-use bytes::{Buf, BufMut, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use futures_core::Stream;
 use futures_util::{future, ready};
 use http::StatusCode;
 use http_body::Body;
 use std::{
+    error::Error,
     fmt,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 use tracing::{debug, trace};
 
-const BUFFER_SIZE: usize = 8 * 1024;
-
 /// Streaming requests and responses.
 ///
 /// This will wrap some inner [`Body`] and [`Decoder`] and provide an interface
 /// to fetch the message stream and trailing metadata
+///
+/// The body is only polled when the consumer asks for a message that isn't
+/// buffered yet, so a slow consumer applies backpressure to the sender, for
+/// example through HTTP/2 flow control. At most one message, limited by the
+/// maximum decoding message size, and one body chunk are buffered at a time.
 pub struct Streaming<T> {
     decoder: Box<dyn Decoder<Item = T, Error = Status> + Send + 'static>,
     inner: StreamingInner,
@@ -29,6 +38,15 @@ struct StreamingInner {
     state: State,
     direction: Direction,
     buf: BytesMut,
+    // The last body chunk while it is still being decoded in place.
+    chunk: Bytes,
+    // Upper bound of the bytes left in the body, tracked here as mapping the
+    // body's data loses its size hint.
+    body_remaining: Option<u64>,
+    // Position of the message being decoded, for error reporting.
+    message_index: u64,
+    frame_offset: u64,
+    frame_len: usize,
     trailers: Option<MetadataMap>,
     decompress_buf: BytesMut,
     encoding: Option<CompressionEncoding>,
@@ -118,6 +136,8 @@ impl<T> Streaming<T> {
         B::Error: Into<crate::Error>,
         D: Decoder<Item = T, Error = Status> + Send + 'static,
     {
+        let buffer_size = decoder.buffer_settings().buffer_size;
+        let body_remaining = body.size_hint().upper();
         Self {
             decoder: Box::new(decoder),
             inner: StreamingInner {
@@ -127,7 +147,12 @@ impl<T> Streaming<T> {
                     .boxed_unsync(),
                 state: State::ReadHeader,
                 direction,
-                buf: BytesMut::with_capacity(BUFFER_SIZE),
+                buf: pool::take(buffer_size),
+                chunk: Bytes::new(),
+                body_remaining,
+                message_index: 0,
+                frame_offset: 0,
+                frame_len: 0,
                 trailers: None,
                 decompress_buf: BytesMut::new(),
                 encoding,
@@ -137,8 +162,26 @@ impl<T> Streaming<T> {
     }
 }
 
+impl Drop for StreamingInner {
+    fn drop(&mut self) {
+        pool::give(std::mem::take(&mut self.buf));
+    }
+}
+
 impl StreamingInner {
     fn decode_chunk(&mut self) -> Result<Option<DecodeBuf<'_>>, Status> {
+        if !self.chunk.is_empty() {
+            if let Some(len) = self.complete_frame_in_chunk() {
+                self.chunk.advance(HEADER_SIZE);
+                self.frame_len = len;
+                return Ok(Some(DecodeBuf::from_chunk(&mut self.chunk, len)));
+            }
+
+            // The chunk doesn't hold a whole uncompressed message, fall back
+            // to accumulating it in the buffer.
+            self.buf.put(std::mem::take(&mut self.chunk));
+        }
+
         if let State::ReadHeader = self.state {
             if self.buf.remaining() < HEADER_SIZE {
                 return Ok(None);
@@ -177,7 +220,7 @@ impl StreamingInner {
             let limit = self.max_message_size.unwrap_or(DEFAULT_MAX_MESSAGE_SIZE);
             if len > limit {
                 return Err(Status::new(
-                    Code::OutOfRange,
+                    Code::ResourceExhausted,
                     format!(
                         "Error, message length too large: found {} bytes, the limit is: {} bytes",
                         len, limit
@@ -186,6 +229,7 @@ impl StreamingInner {
             }
 
             self.buf.reserve(len);
+            self.frame_len = len;
 
             self.state = State::ReadBody {
                 compression: compression_encoding,
@@ -227,6 +271,30 @@ impl StreamingInner {
         Ok(None)
     }
 
+    // Returns the message length if `chunk` starts with a complete, uncompressed
+    // message within the size limit that can be decoded without copying it.
+    //
+    // Anything else, including malformed headers, is left to the buffered path
+    // so that errors are reported in one place.
+    fn complete_frame_in_chunk(&self) -> Option<usize> {
+        if !matches!(self.state, State::ReadHeader) || !self.buf.is_empty() {
+            return None;
+        }
+
+        let header = self.chunk.get(..HEADER_SIZE)?;
+        if header[0] != 0 {
+            return None;
+        }
+
+        let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        let limit = self.max_message_size.unwrap_or(DEFAULT_MAX_MESSAGE_SIZE);
+        if len > limit || self.chunk.len() - HEADER_SIZE < len {
+            return None;
+        }
+
+        Some(len)
+    }
+
     // Returns Some(()) if data was found or None if the loop in `poll_next` should break
     fn poll_data(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<()>, Status>> {
         let chunk = match ready!(Pin::new(&mut self.body).poll_data(cx)) {
@@ -242,11 +310,21 @@ impl StreamingInner {
         };
 
         Poll::Ready(if let Some(data) = chunk {
-            self.buf.put(data);
+            self.body_remaining = self
+                .body_remaining
+                .map(|remaining| remaining.saturating_sub(data.len() as u64));
+            if self.buf.is_empty() {
+                self.chunk = data;
+            } else {
+                self.buf.put(data);
+            }
             Ok(Some(()))
         } else {
             // FIXME: improve buf usage.
-            if self.buf.has_remaining() {
+            //
+            // Once a header has been read the buffer may be empty while the
+            // message body is still missing, which is just as truncated.
+            if self.buf.has_remaining() || matches!(self.state, State::ReadBody { .. }) {
                 trace!("unexpected EOF decoding stream");
                 Err(Status::new(
                     Code::Internal,
@@ -258,6 +336,65 @@ impl StreamingInner {
         })
     }
 
+    // Adds the position of the current message to an error returned by the
+    // decoder, keeping the original status as its source.
+    fn decode_error(&self, status: Status, consumed: usize) -> Status {
+        let error = DecodeError {
+            message_index: self.message_index,
+            offset: self.frame_offset,
+            frame_len: self.frame_len,
+            consumed,
+            status,
+        };
+
+        let mut status = Status::with_details_and_metadata(
+            error.status.code(),
+            format!("{} ({})", error.status.message(), error),
+            Bytes::copy_from_slice(error.status.details()),
+            error.status.metadata().clone(),
+        );
+        status.set_source(Arc::new(error));
+        status
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if let State::Error = self.state {
+            return (0, Some(0));
+        }
+
+        // Only one of the two holds data at any time.
+        let buffered = if self.buf.is_empty() {
+            &self.chunk[..]
+        } else {
+            &self.buf[..]
+        };
+
+        // A message whose header was read already, and its length.
+        let (pending, pending_len) = match self.state {
+            State::ReadBody { len, .. } => (1, len),
+            _ => (0, 0),
+        };
+
+        let complete = if pending == 1 {
+            buffered.len() >= pending_len
+        } else {
+            buffered.len() >= HEADER_SIZE && {
+                let len = u32::from_be_bytes([buffered[1], buffered[2], buffered[3], buffered[4]]);
+                buffered.len() - HEADER_SIZE >= len as usize
+            }
+        };
+
+        // Every further message takes at least a header, and the stream may end
+        // with one error.
+        let upper = self.body_remaining.map(|remaining| {
+            let unclaimed = (buffered.len() as u64 + remaining).saturating_sub(pending_len as u64);
+            let messages = usize::try_from(unclaimed / HEADER_SIZE as u64).unwrap_or(usize::MAX);
+            messages.saturating_add(pending + 1)
+        });
+
+        (complete as usize, upper)
+    }
+
     fn poll_response(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Status>> {
         if let Direction::Response(status) = self.direction {
             match ready!(Pin::new(&mut self.body).poll_trailers(cx)) {
@@ -361,20 +498,125 @@ impl<T> Streaming<T> {
         map.map(|x| x.map(MetadataMap::from_headers))
     }
 
+    /// Consumes `self`, returning the underlying body and the bytes that were
+    /// already read from it but not decoded yet.
+    ///
+    /// The bytes start at a message boundary and, followed by the rest of the
+    /// body, form the remaining gRPC frames of the stream. Compressed messages
+    /// are left compressed. This allows handing a stream off to a proxy in the
+    /// middle of a call.
+    pub fn into_parts(mut self) -> (BoxBody, Bytes) {
+        let inner = &mut self.inner;
+
+        let mut buffered = BytesMut::new();
+        if let State::ReadBody { compression, len } = inner.state {
+            // The message header was already consumed, put it back.
+            buffered.put_u8(compression.is_some() as u8);
+            buffered.put_u32(len as u32);
+        }
+        buffered.put(std::mem::take(&mut inner.buf));
+        buffered.put(std::mem::take(&mut inner.chunk));
+
+        let body = std::mem::replace(&mut inner.body, crate::body::empty_body());
+
+        (body, buffered.freeze())
+    }
+
     fn decode_chunk(&mut self) -> Result<Option<T>, Status> {
         match self.inner.decode_chunk()? {
-            Some(mut decode_buf) => match self.decoder.decode(&mut decode_buf)? {
-                Some(msg) => {
-                    self.inner.state = State::ReadHeader;
-                    Ok(Some(msg))
+            Some(mut decode_buf) => {
+                let len = decode_buf.remaining();
+                match self.decoder.decode(&mut decode_buf) {
+                    Ok(Some(msg)) => {
+                        let inner = &mut self.inner;
+                        inner.state = State::ReadHeader;
+                        inner.message_index += 1;
+                        inner.frame_offset += (HEADER_SIZE + inner.frame_len) as u64;
+                        Ok(Some(msg))
+                    }
+                    // The frame is complete, so waiting for more data would only decode
+                    // it again.
+                    Ok(None) => {
+                        let consumed = len - decode_buf.remaining();
+                        let status = Status::internal("decoder returned no message for a frame");
+                        Err(self.inner.decode_error(status, consumed))
+                    }
+                    Err(status) => {
+                        let consumed = len - decode_buf.remaining();
+                        Err(self.inner.decode_error(status, consumed))
+                    }
                 }
-                None => Ok(None),
-            },
+            }
             None => Ok(None),
         }
     }
 }
 
+/// The position in the stream of a message that failed to decode.
+///
+/// This is the [source](Error::source) of the [`Status`] a [`Streaming`]
+/// returns when its [`Decoder`] fails, the error returned by the decoder is in
+/// turn the source of this error:
+///
+/// ```rust
+/// # use tonic::{codec::DecodeError, Status};
+/// # fn log(status: &Status) {
+/// use std::error::Error;
+///
+/// if let Some(err) = status.source().and_then(|err| err.downcast_ref::<DecodeError>()) {
+///     println!("message {} at offset {} is malformed", err.message_index(), err.offset());
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DecodeError {
+    message_index: u64,
+    offset: u64,
+    frame_len: usize,
+    consumed: usize,
+    status: Status,
+}
+
+impl DecodeError {
+    /// The zero based index of the message in the stream.
+    pub fn message_index(&self) -> u64 {
+        self.message_index
+    }
+
+    /// The byte offset of the message's frame header in the stream.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The length of the message as stated in its frame header.
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    /// The number of bytes the decoder consumed before it failed.
+    ///
+    /// For compressed messages this counts decompressed bytes.
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "message {} at byte offset {}, frame length {} bytes, {} bytes consumed",
+            self.message_index, self.offset, self.frame_len, self.consumed
+        )
+    }
+}
+
+impl Error for DecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.status)
+    }
+}
+
 impl<T> Stream for Streaming<T> {
     type Item = Result<T, Status>;
 
@@ -402,6 +644,12 @@ impl<T> Stream for Streaming<T> {
             Err(err) => Some(Err(err)),
         })
     }
+
+    /// Bounds the number of remaining items from the buffered frames and the
+    /// size hint of the body, such as its `content-length`.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
 }
 
 impl<T> fmt::Debug for Streaming<T> {
@@ -412,3 +660,279 @@ impl<T> fmt::Debug for Streaming<T> {
 
 #[cfg(test)]
 static_assertions::assert_impl_all!(Streaming<()>: Send);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::IdentityCodec;
+    use futures_util::FutureExt;
+    use quickcheck::{Arbitrary, Gen};
+    use quickcheck_macros::quickcheck;
+    use std::collections::VecDeque;
+
+    // A body that yields `data` in chunks of the given sizes, repeating the
+    // sizes as needed.
+    struct ChunkedBody(VecDeque<Bytes>);
+
+    impl ChunkedBody {
+        fn new(mut data: Bytes, chunk_sizes: &ChunkSizes) -> Self {
+            let mut chunks = VecDeque::new();
+            for size in chunk_sizes.0.iter().cycle() {
+                if data.is_empty() {
+                    break;
+                }
+                chunks.push_back(data.split_to((*size).min(data.len())));
+            }
+            Self(chunks)
+        }
+    }
+
+    impl Body for ChunkedBody {
+        type Data = Bytes;
+        type Error = Status;
+
+        fn poll_data(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+            Poll::Ready(self.0.pop_front().map(Ok))
+        }
+
+        fn poll_trailers(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+            Poll::Ready(Ok(None))
+        }
+
+        fn size_hint(&self) -> http_body::SizeHint {
+            http_body::SizeHint::with_exact(self.0.iter().map(|c| c.len() as u64).sum())
+        }
+    }
+
+    /// Newtype to implement `Arbitrary` for the sizes of the chunks a body is split into.
+    #[derive(Clone, Debug)]
+    struct ChunkSizes(Vec<usize>);
+
+    impl Arbitrary for ChunkSizes {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let sizes: Vec<usize> = Vec::arbitrary(g);
+            // Mostly tiny chunks, so headers and bodies get split up.
+            let sizes = sizes.into_iter().map(|size| size % 16 + 1).collect();
+            Self(sizes).or_default()
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+            Box::new(self.0.shrink().map(|sizes| Self(sizes).or_default()))
+        }
+    }
+
+    impl ChunkSizes {
+        fn or_default(self) -> Self {
+            if self.0.is_empty() || self.0.contains(&0) {
+                Self(vec![1])
+            } else {
+                self
+            }
+        }
+    }
+
+    // Decodes every message in `body`, body chunks are always ready so this
+    // completes without a runtime.
+    fn decode_all(body: ChunkedBody, max_message_size: Option<usize>) -> Result<Vec<Bytes>, Code> {
+        let mut stream =
+            Streaming::new_request(IdentityCodec::default(), body, None, max_message_size);
+
+        let mut messages = Vec::new();
+        loop {
+            let next = stream
+                .message()
+                .now_or_never()
+                .expect("decoding a ready body never waits");
+            match next.map_err(|status| status.code())? {
+                Some(message) => messages.push(message),
+                None => return Ok(messages),
+            }
+        }
+    }
+
+    fn frame(messages: &[Vec<u8>]) -> Bytes {
+        let mut buf = BytesMut::new();
+        for message in messages {
+            buf.put_u8(0);
+            buf.put_u32(message.len() as u32);
+            buf.put(&message[..]);
+        }
+        buf.freeze()
+    }
+
+    #[test]
+    fn eof_after_header_is_an_error() {
+        let data = frame(&[vec![1, 2, 3]]).slice(..HEADER_SIZE);
+
+        let code = decode_all(ChunkedBody::new(data, &ChunkSizes(vec![1])), None).unwrap_err();
+
+        assert_eq!(code, Code::Internal);
+    }
+
+    #[test]
+    fn size_hint() {
+        let data = frame(&[vec![0; 10], vec![0; 10]]);
+        let body = ChunkedBody::new(data, &ChunkSizes(vec![HEADER_SIZE + 10 + 3]));
+        let mut stream = Streaming::new_request(IdentityCodec::default(), body, None, None);
+
+        // 30 bytes hold at most 6 messages, plus an error.
+        assert_eq!(stream.size_hint(), (0, Some(7)));
+
+        stream.message().now_or_never().unwrap().unwrap().unwrap();
+        // 3 bytes of the second message are buffered, 15 bytes remain in total.
+        assert_eq!(stream.size_hint(), (0, Some(4)));
+
+        stream.message().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(stream.size_hint(), (0, Some(1)));
+
+        assert!(stream.message().now_or_never().unwrap().unwrap().is_none());
+    }
+
+    #[test]
+    fn size_hint_counts_buffered_message() {
+        let data = frame(&[vec![0; 10], vec![0; 10]]);
+        let body = ChunkedBody::new(data, &ChunkSizes(vec![30]));
+        let mut stream = Streaming::new_request(IdentityCodec::default(), body, None, None);
+
+        stream.message().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(stream.size_hint(), (1, Some(4)));
+    }
+
+    // Fails on messages starting with 0xff after reading one byte.
+    struct FailingDecoder;
+
+    impl Decoder for FailingDecoder {
+        type Item = Bytes;
+        type Error = Status;
+
+        fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Bytes>, Status> {
+            if buf.get_u8() == 0xff {
+                return Err(Status::new(Code::Internal, "bad message"));
+            }
+            Ok(Some(buf.copy_to_bytes(buf.remaining())))
+        }
+    }
+
+    #[test]
+    fn decode_errors_report_the_message_position() {
+        let data = frame(&[vec![0; 10], vec![0; 4], vec![0xff, 1, 2]]);
+        for sizes in [vec![data.len()], vec![1]] {
+            let body = ChunkedBody::new(data.clone(), &ChunkSizes(sizes));
+            let mut stream = Streaming::new_request(FailingDecoder, body, None, None);
+
+            stream.message().now_or_never().unwrap().unwrap().unwrap();
+            stream.message().now_or_never().unwrap().unwrap().unwrap();
+            let status = stream.message().now_or_never().unwrap().unwrap_err();
+
+            assert_eq!(status.code(), Code::Internal);
+            assert_eq!(
+                status.message(),
+                "bad message (message 2 at byte offset 24, frame length 3 bytes, 1 bytes consumed)"
+            );
+
+            let error = status
+                .source()
+                .and_then(|err| err.downcast_ref::<DecodeError>())
+                .unwrap();
+            assert_eq!(error.message_index(), 2);
+            assert_eq!(error.offset(), 24);
+            assert_eq!(error.frame_len(), 3);
+            assert_eq!(error.consumed(), 1);
+            let original = error.source().unwrap().downcast_ref::<Status>().unwrap();
+            assert_eq!(original.message(), "bad message");
+        }
+    }
+
+    struct EmptyDecoder;
+
+    impl Decoder for EmptyDecoder {
+        type Item = Bytes;
+        type Error = Status;
+
+        fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Bytes>, Status> {
+            if buf.chunk()[0] == 0xff {
+                return Ok(None);
+            }
+            Ok(Some(buf.copy_to_bytes(buf.remaining())))
+        }
+    }
+
+    #[test]
+    fn decoders_without_a_message_fail_the_stream() {
+        let data = frame(&[vec![0; 10], vec![0xff, 1, 2], vec![0; 4]]);
+        for sizes in [vec![data.len()], vec![1]] {
+            let body = ChunkedBody::new(data.clone(), &ChunkSizes(sizes));
+            let mut stream = Streaming::new_request(EmptyDecoder, body, None, None);
+
+            stream.message().now_or_never().unwrap().unwrap().unwrap();
+            let status = stream.message().now_or_never().unwrap().unwrap_err();
+
+            assert_eq!(status.code(), Code::Internal);
+            assert_eq!(
+                status.message(),
+                "decoder returned no message for a frame (message 1 at byte offset 15, frame length 3 bytes, 0 bytes consumed)"
+            );
+        }
+    }
+
+    #[test]
+    fn body_is_not_polled_while_a_message_is_buffered() {
+        let frames = frame(&[vec![0; 10], vec![0; 10], vec![0; 10]]);
+        // The first chunk holds two messages.
+        let body = ChunkedBody::new(frames, &ChunkSizes(vec![30, 15]));
+        let mut stream = Streaming::new_request(IdentityCodec::default(), body, None, None);
+
+        stream.message().now_or_never().unwrap().unwrap().unwrap();
+        // The second chunk is still in the body.
+        assert_eq!(stream.inner.body_remaining, Some(15));
+
+        stream.message().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(stream.inner.body_remaining, Some(15));
+
+        stream.message().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(stream.inner.body_remaining, Some(0));
+    }
+
+    #[quickcheck]
+    fn decodes_messages_split_at_any_boundary(messages: Vec<Vec<u8>>, sizes: ChunkSizes) -> bool {
+        let body = ChunkedBody::new(frame(&messages), &sizes);
+
+        let decoded = decode_all(body, None).unwrap();
+
+        decoded
+            .iter()
+            .map(|m| &m[..])
+            .eq(messages.iter().map(|m| &m[..]))
+    }
+
+    #[quickcheck]
+    fn arbitrary_input_terminates_without_panicking(data: Vec<u8>, sizes: ChunkSizes) -> bool {
+        let body = ChunkedBody::new(Bytes::from(data), &sizes);
+
+        // this just shouldn't panic or hang
+        let _ = decode_all(body, Some(1024));
+
+        true
+    }
+
+    #[quickcheck]
+    fn truncated_input_is_an_error(messages: Vec<Vec<u8>>, cut: usize, sizes: ChunkSizes) -> bool {
+        let data = frame(&messages);
+        if data.is_empty() {
+            return true;
+        }
+        let data = data.slice(..cut % data.len());
+        let at_boundary = data.is_empty()
+            || (0..=messages.len()).any(|n| frame(&messages[..n]).len() == data.len());
+
+        let result = decode_all(ChunkedBody::new(data, &sizes), None);
+
+        result.is_ok() == at_boundary
+    }
+}
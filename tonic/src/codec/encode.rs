@@ -9,6 +9,7 @@ use http_body::Body;
 use pin_project::pin_project;
 use std::{
     pin::Pin,
+    sync::{Arc, Mutex},
     task::{Context, Poll},
 };
 
@@ -42,6 +43,7 @@ pub(crate) fn encode_client<T, U>(
     source: U,
     compression_encoding: Option<CompressionEncoding>,
     max_message_size: Option<usize>,
+    encode_error: EncodeError,
 ) -> EncodeBody<impl Stream<Item = Result<Bytes, Status>>>
 where
     T: Encoder<Error = Status>,
@@ -55,7 +57,7 @@ where
         max_message_size,
     )
     .into_stream();
-    EncodeBody::new_client(stream)
+    EncodeBody::new_client(stream, encode_error)
 }
 
 fn encode<T, U>(
@@ -69,7 +71,8 @@ where
     T: Encoder<Error = Status>,
     U: Stream<Item = Result<T::Item, Status>>,
 {
-    let mut buf = BytesMut::with_capacity(BUFFER_SIZE);
+    let buffer_size = encoder.buffer_settings().buffer_size;
+    let mut buf = BytesMut::with_capacity(buffer_size);
 
     let compression_encoding = if compression_override == SingleMessageCompressionOverride::Disable
     {
@@ -79,7 +82,7 @@ where
     };
 
     let mut uncompression_buf = if compression_encoding.is_some() {
-        BytesMut::with_capacity(BUFFER_SIZE)
+        BytesMut::with_capacity(buffer_size)
     } else {
         BytesMut::new()
     };
@@ -166,9 +169,26 @@ fn finish_encoding(
     Ok(buf.split_to(len + HEADER_SIZE).freeze())
 }
 
+/// Holds the error that made a client [`EncodeBody`] fail.
+///
+/// When a request body errors the transport only reports a reset stream to the caller, so the
+/// client reads the original [`Status`] back from here instead.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EncodeError(Arc<Mutex<Option<Status>>>);
+
+impl EncodeError {
+    fn set(&self, status: Status) {
+        *self.0.lock().unwrap() = Some(status);
+    }
+
+    pub(crate) fn take(&self) -> Option<Status> {
+        self.0.lock().unwrap().take()
+    }
+}
+
 #[derive(Debug)]
 enum Role {
-    Client,
+    Client(EncodeError),
     Server,
 }
 
@@ -191,12 +211,12 @@ impl<S> EncodeBody<S>
 where
     S: Stream<Item = Result<Bytes, Status>>,
 {
-    pub(crate) fn new_client(inner: S) -> Self {
+    pub(crate) fn new_client(inner: S, encode_error: EncodeError) -> Self {
         Self {
             inner,
             state: EncodeState {
                 error: None,
-                role: Role::Client,
+                role: Role::Client(encode_error),
                 is_end_stream: false,
             },
         }
@@ -217,7 +237,7 @@ where
 impl EncodeState {
     fn trailers(&mut self) -> Result<Option<HeaderMap>, Status> {
         match self.role {
-            Role::Client => Ok(None),
+            Role::Client(_) => Ok(None),
             Role::Server => {
                 if self.is_end_stream {
                     return Ok(None);
@@ -254,8 +274,11 @@ where
         let mut self_proj = self.project();
         match ready!(self_proj.inner.try_poll_next_unpin(cx)) {
             Some(Ok(d)) => Some(Ok(d)).into(),
-            Some(Err(status)) => match self_proj.state.role {
-                Role::Client => Some(Err(status)).into(),
+            Some(Err(status)) => match &self_proj.state.role {
+                Role::Client(encode_error) => {
+                    encode_error.set(status.clone());
+                    Some(Err(status)).into()
+                }
                 Role::Server => {
                     self_proj.state.error = Some(status);
                     None.into()
@@ -0,0 +1,294 @@
+use super::{BufferSettings, Codec, DecodeBuf, Decoder, Encoder};
+use crate::codec::EncodeBuf;
+use crate::{Code, Status};
+use bytes::{Buf, BufMut, Bytes};
+use flatbuffers::{Follow, InvalidFlatbuffer, Verifiable};
+use std::{fmt, marker::PhantomData};
+
+/// Names the root table of a FlatBuffers schema.
+///
+/// Tables generated by `flatc` borrow from the buffer they are read from, so
+/// this trait is implemented on a marker type that names the table for any
+/// lifetime:
+///
+/// ```ignore
+/// struct MonsterRoot;
+///
+/// impl<'a> FlatBufferRoot<'a> for MonsterRoot {
+///     type Root = Monster<'a>;
+/// }
+/// ```
+pub trait FlatBufferRoot<'a>: Send + 'static {
+    /// The generated root table type.
+    type Root: Follow<'a, Inner = Self::Root> + Verifiable + 'a;
+}
+
+/// A verified FlatBuffers message with root table `R`.
+///
+/// The message keeps the bytes it was received in, accessing the table through
+/// [`FlatBuffer::root`] does not copy or re-verify them.
+pub struct FlatBuffer<R> {
+    bytes: Bytes,
+    _pd: PhantomData<fn() -> R>,
+}
+
+impl<R> FlatBuffer<R>
+where
+    R: for<'a> FlatBufferRoot<'a>,
+{
+    /// Verify that `bytes` holds a valid buffer with root table `R`.
+    pub fn new(bytes: Bytes) -> Result<Self, InvalidFlatbuffer> {
+        flatbuffers::root::<<R as FlatBufferRoot<'_>>::Root>(&bytes)?;
+
+        Ok(Self {
+            bytes,
+            _pd: PhantomData,
+        })
+    }
+
+    /// Access the root table of the message.
+    pub fn root(&self) -> <R as FlatBufferRoot<'_>>::Root {
+        // SAFETY: the bytes were verified to hold an `R` in `new` and can't be
+        // modified afterwards.
+        unsafe { flatbuffers::root_unchecked::<<R as FlatBufferRoot<'_>>::Root>(&self.bytes) }
+    }
+
+    /// Get a reference to the bytes of the message.
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+
+    /// Consumes `self`, returning the bytes of the message.
+    pub fn into_bytes(self) -> Bytes {
+        self.bytes
+    }
+}
+
+impl<R> Clone for FlatBuffer<R> {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: self.bytes.clone(),
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<R> fmt::Debug for FlatBuffer<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlatBuffer")
+            .field("len", &self.bytes.len())
+            .finish()
+    }
+}
+
+/// A [`Codec`] that implements `application/grpc+flatbuffers` via the flatbuffers library.
+#[derive(Debug, Clone)]
+pub struct FlatBuffersCodec<T, U> {
+    _pd: PhantomData<(T, U)>,
+}
+
+impl<T, U> Default for FlatBuffersCodec<T, U> {
+    fn default() -> Self {
+        Self { _pd: PhantomData }
+    }
+}
+
+impl<T, U> FlatBuffersCodec<T, U>
+where
+    T: for<'a> FlatBufferRoot<'a>,
+    U: for<'a> FlatBufferRoot<'a>,
+{
+    /// Create a flatbuffers [`Encoder`] with the provided [`BufferSettings`].
+    pub fn raw_encoder(buffer_settings: BufferSettings) -> FlatBuffersEncoder<T> {
+        FlatBuffersEncoder {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+
+    /// Create a flatbuffers [`Decoder`] with the provided [`BufferSettings`].
+    pub fn raw_decoder(buffer_settings: BufferSettings) -> FlatBuffersDecoder<U> {
+        FlatBuffersDecoder {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+}
+
+impl<T, U> Codec for FlatBuffersCodec<T, U>
+where
+    T: for<'a> FlatBufferRoot<'a>,
+    U: for<'a> FlatBufferRoot<'a>,
+{
+    type Encode = FlatBuffer<T>;
+    type Decode = FlatBuffer<U>;
+
+    type Encoder = FlatBuffersEncoder<T>;
+    type Decoder = FlatBuffersDecoder<U>;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        Self::raw_encoder(BufferSettings::default())
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        Self::raw_decoder(BufferSettings::default())
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/grpc+flatbuffers"
+    }
+}
+
+/// A [`Encoder`] that knows how to encode [`FlatBuffer<T>`].
+#[derive(Debug)]
+pub struct FlatBuffersEncoder<T> {
+    _pd: PhantomData<fn(T)>,
+    buffer_settings: BufferSettings,
+}
+
+impl<T> Encoder for FlatBuffersEncoder<T>
+where
+    T: for<'a> FlatBufferRoot<'a>,
+{
+    type Item = FlatBuffer<T>;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        buf.put(item.into_bytes());
+
+        Ok(())
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+/// A [`Decoder`] that knows how to decode [`FlatBuffer<U>`].
+#[derive(Debug)]
+pub struct FlatBuffersDecoder<U> {
+    _pd: PhantomData<fn() -> U>,
+    buffer_settings: BufferSettings,
+}
+
+impl<U> Decoder for FlatBuffersDecoder<U>
+where
+    U: for<'a> FlatBufferRoot<'a>,
+{
+    type Item = FlatBuffer<U>;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        // Messages that arrived in a single body chunk are split off without copying.
+        let bytes = buf.copy_to_bytes(buf.remaining());
+
+        let item = FlatBuffer::new(bytes)
+            .map(Option::Some)
+            .map_err(from_decode_error)?;
+
+        Ok(item)
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+fn from_decode_error(error: InvalidFlatbuffer) -> crate::Status {
+    // Map verification errors to an INTERNAL status code, as is done for protobuf, see
+    // https://github.com/grpc/grpc/blob/master/doc/statuscodes.md
+    Status::new(Code::Internal, error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+    use flatbuffers::{FlatBufferBuilder, ForwardsUOffset, Table, VOffsetT, Verifier};
+
+    // What `flatc` generates for `table Greeting { message: string; }`.
+    struct Greeting<'a> {
+        table: Table<'a>,
+    }
+
+    impl Greeting<'_> {
+        const VT_MESSAGE: VOffsetT = 4;
+
+        fn message(&self) -> Option<&str> {
+            unsafe {
+                self.table
+                    .get::<ForwardsUOffset<&str>>(Self::VT_MESSAGE, None)
+            }
+        }
+    }
+
+    impl<'a> Follow<'a> for Greeting<'a> {
+        type Inner = Greeting<'a>;
+
+        unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+            Greeting {
+                table: Table::new(buf, loc),
+            }
+        }
+    }
+
+    impl Verifiable for Greeting<'_> {
+        fn run_verifier(v: &mut Verifier<'_, '_>, pos: usize) -> Result<(), InvalidFlatbuffer> {
+            v.visit_table(pos)?
+                .visit_field::<ForwardsUOffset<&str>>("message", Self::VT_MESSAGE, false)?
+                .finish();
+            Ok(())
+        }
+    }
+
+    struct GreetingRoot;
+
+    impl<'a> FlatBufferRoot<'a> for GreetingRoot {
+        type Root = Greeting<'a>;
+    }
+
+    fn greeting(message: &str) -> FlatBuffer<GreetingRoot> {
+        let mut builder = FlatBufferBuilder::new();
+        let message = builder.create_string(message);
+        let start = builder.start_table();
+        builder.push_slot_always(Greeting::VT_MESSAGE, message);
+        let root = builder.end_table(start);
+        builder.finish_minimal(root);
+
+        FlatBuffer::new(Bytes::copy_from_slice(builder.finished_data())).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut codec = FlatBuffersCodec::<GreetingRoot, GreetingRoot>::default();
+
+        let mut bytes = BytesMut::new();
+        codec
+            .encoder()
+            .encode(greeting("hello"), &mut EncodeBuf::new(&mut bytes))
+            .unwrap();
+
+        let len = bytes.len();
+        let ptr = bytes.as_ptr();
+        let decoded = codec
+            .decoder()
+            .decode(&mut DecodeBuf::new(&mut bytes, len))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(decoded.root().message(), Some("hello"));
+        assert_eq!(decoded.as_bytes().as_ptr(), ptr);
+    }
+
+    #[test]
+    fn decode_invalid_buffer() {
+        let mut bytes = BytesMut::from(&[0xff; 3][..]);
+        let len = bytes.len();
+
+        let status = FlatBuffersCodec::<GreetingRoot, GreetingRoot>::default()
+            .decoder()
+            .decode(&mut DecodeBuf::new(&mut bytes, len))
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Internal);
+    }
+}
@@ -0,0 +1,98 @@
+use super::{BufferSettings, Codec, DecodeBuf, Decoder, Encoder};
+use crate::codec::EncodeBuf;
+use crate::Status;
+use bytes::{Buf, BufMut, Bytes};
+
+/// A [`Codec`] that passes the bytes of each message through as is.
+///
+/// Each length-prefixed gRPC frame is decoded into its raw payload as [`Bytes`]
+/// and encoded back without interpretation, which is useful for proxies and
+/// tools that forward or record messages without knowing their types.
+/// Compression is still handled by tonic, so the payload is always uncompressed.
+#[derive(Debug, Clone, Default)]
+pub struct IdentityCodec {
+    buffer_settings: BufferSettings,
+}
+
+impl IdentityCodec {
+    /// Create an `IdentityCodec` with the provided [`BufferSettings`].
+    pub fn with_buffer_settings(buffer_settings: BufferSettings) -> Self {
+        Self { buffer_settings }
+    }
+}
+
+impl Codec for IdentityCodec {
+    type Encode = Bytes;
+    type Decode = Bytes;
+
+    type Encoder = IdentityCodec;
+    type Decoder = IdentityCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        self.clone()
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        self.clone()
+    }
+}
+
+impl Encoder for IdentityCodec {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        buf.put(item);
+
+        Ok(())
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+impl Decoder for IdentityCodec {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        Ok(Some(buf.copy_to_bytes(buf.remaining())))
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::compression::SingleMessageCompressionOverride;
+    use crate::codec::{encode_server, Streaming};
+
+    #[tokio::test]
+    async fn round_trip_frames() {
+        let msgs = vec![
+            Bytes::from_static(b"first"),
+            Bytes::new(),
+            Bytes::from_static(&[0xff; 100]),
+        ];
+
+        let source = tokio_stream::iter(msgs.clone().into_iter().map(Ok));
+        let body = encode_server(
+            IdentityCodec::default(),
+            source,
+            None,
+            SingleMessageCompressionOverride::default(),
+            None,
+        );
+
+        let mut stream = Streaming::new_request(IdentityCodec::default(), body, None, None);
+
+        for msg in msgs {
+            assert_eq!(stream.message().await.unwrap(), Some(msg));
+        }
+        assert_eq!(stream.message().await.unwrap(), None);
+    }
+}
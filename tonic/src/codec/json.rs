@@ -0,0 +1,160 @@
+use super::{BufferSettings, Codec, DecodeBuf, Decoder, Encoder};
+use crate::codec::EncodeBuf;
+use crate::{Code, Status};
+use bytes::{Buf, BufMut};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// A [`Codec`] that implements `application/grpc+json` via the serde_json library.
+#[derive(Debug, Clone)]
+pub struct JsonCodec<T, U> {
+    _pd: PhantomData<(T, U)>,
+}
+
+impl<T, U> Default for JsonCodec<T, U> {
+    fn default() -> Self {
+        Self { _pd: PhantomData }
+    }
+}
+
+impl<T, U> JsonCodec<T, U>
+where
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+{
+    /// Create a json [`Encoder`] with the provided [`BufferSettings`].
+    pub fn raw_encoder(buffer_settings: BufferSettings) -> JsonEncoder<T> {
+        JsonEncoder {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+
+    /// Create a json [`Decoder`] with the provided [`BufferSettings`].
+    pub fn raw_decoder(buffer_settings: BufferSettings) -> JsonDecoder<U> {
+        JsonDecoder {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+}
+
+impl<T, U> Codec for JsonCodec<T, U>
+where
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+{
+    type Encode = T;
+    type Decode = U;
+
+    type Encoder = JsonEncoder<T>;
+    type Decoder = JsonDecoder<U>;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        Self::raw_encoder(BufferSettings::default())
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        Self::raw_decoder(BufferSettings::default())
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/grpc+json"
+    }
+}
+
+/// A [`Encoder`] that knows how to encode `T` as json.
+#[derive(Debug, Clone)]
+pub struct JsonEncoder<T> {
+    _pd: PhantomData<T>,
+    buffer_settings: BufferSettings,
+}
+
+impl<T: Serialize> Encoder for JsonEncoder<T> {
+    type Item = T;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        serde_json::to_writer(buf.writer(), &item).map_err(|error| {
+            Status::new(
+                Code::Internal,
+                format!("Error serializing json message: {}", error),
+            )
+        })
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+/// A [`Decoder`] that knows how to decode `U` from json.
+#[derive(Debug, Clone)]
+pub struct JsonDecoder<U> {
+    _pd: PhantomData<U>,
+    buffer_settings: BufferSettings,
+}
+
+impl<U: DeserializeOwned> Decoder for JsonDecoder<U> {
+    type Item = U;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        let item = serde_json::from_reader(buf.reader())
+            .map(Option::Some)
+            .map_err(from_decode_error)?;
+
+        Ok(item)
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+fn from_decode_error(error: serde_json::Error) -> crate::Status {
+    // Map parse errors to an INTERNAL status code, as is done for protobuf, see
+    // https://github.com/grpc/grpc/blob/master/doc/statuscodes.md
+    Status::new(Code::Internal, error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+    use std::collections::BTreeMap;
+
+    type Message = BTreeMap<String, Vec<u32>>;
+
+    #[test]
+    fn round_trip() {
+        let mut msg = Message::new();
+        msg.insert("numbers".to_string(), vec![1, 2, 3]);
+
+        let mut bytes = BytesMut::new();
+        JsonCodec::<Message, Message>::default()
+            .encoder()
+            .encode(msg.clone(), &mut EncodeBuf::new(&mut bytes))
+            .unwrap();
+        assert_eq!(&bytes[..], br#"{"numbers":[1,2,3]}"#);
+
+        let len = bytes.len();
+        let decoded = JsonCodec::<Message, Message>::default()
+            .decoder()
+            .decode(&mut DecodeBuf::new(&mut bytes, len))
+            .unwrap();
+        assert_eq!(decoded, Some(msg));
+    }
+
+    #[test]
+    fn decode_invalid_json() {
+        let mut bytes = BytesMut::from(&b"{not json"[..]);
+        let len = bytes.len();
+
+        let status = JsonCodec::<Message, Message>::default()
+            .decoder()
+            .decode(&mut DecodeBuf::new(&mut bytes, len))
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Internal);
+    }
+}
@@ -1,26 +1,119 @@
 //! Generic encoding and decoding.
 //!
-//! This module contains the generic `Codec`, `Encoder` and `Decoder` traits
-//! and a protobuf codec based on prost.
+//! This module contains the generic `Codec`, `Encoder` and `Decoder` traits,
+//! a protobuf codec based on prost, a json codec based on serde_json, a
+//! MessagePack codec based on rmp-serde, a FlatBuffers codec, a Cap'n Proto
+//! codec, a bincode codec and an [`IdentityCodec`] that passes the raw bytes of
+//! messages through.
+//!
+//! # Custom codecs
+//!
+//! The [`Codec`], [`Encoder`] and [`Decoder`] traits are the extension point for
+//! other wire formats. tonic handles the gRPC framing, compression and message
+//! size limits; a codec only turns a single message into bytes and back.
+//!
+//! ```
+//! use bytes::{Buf, BufMut};
+//! use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+//! use tonic::Status;
+//!
+//! /// Sends plain utf-8 strings as messages.
+//! #[derive(Debug, Default)]
+//! pub struct Utf8Codec;
+//!
+//! impl Codec for Utf8Codec {
+//!     type Encode = String;
+//!     type Decode = String;
+//!     type Encoder = Utf8Codec;
+//!     type Decoder = Utf8Codec;
+//!
+//!     fn encoder(&mut self) -> Self::Encoder {
+//!         Utf8Codec
+//!     }
+//!
+//!     fn decoder(&mut self) -> Self::Decoder {
+//!         Utf8Codec
+//!     }
+//! }
+//!
+//! impl Encoder for Utf8Codec {
+//!     type Item = String;
+//!     type Error = Status;
+//!
+//!     fn encode(&mut self, item: String, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+//!         dst.put_slice(item.as_bytes());
+//!         Ok(())
+//!     }
+//! }
+//!
+//! impl Decoder for Utf8Codec {
+//!     type Item = String;
+//!     type Error = Status;
+//!
+//!     fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<String>, Status> {
+//!         let bytes = src.copy_to_bytes(src.remaining());
+//!         String::from_utf8(bytes.to_vec())
+//!             .map(Some)
+//!             .map_err(|e| Status::internal(e.to_string()))
+//!     }
+//! }
+//! ```
+//!
+//! Generated clients and servers pick their codec per method. Services defined
+//! with `tonic_build::manual` name it with `codec_path`, which codegen
+//! instantiates through `Default`. See the [`json-codec`] example for a complete
+//! client and server.
+//!
+//! [`json-codec`]: https://github.com/hyperium/tonic/tree/master/examples/src/json-codec
 
+#[cfg(feature = "bincode-codec")]
+mod bincode;
 mod buffer;
+#[cfg(feature = "capnp")]
+mod capnp;
 pub(crate) mod compression;
 mod decode;
 mod encode;
+#[cfg(feature = "flatbuffers")]
+mod flatbuffers;
+mod identity;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+mod pool;
 #[cfg(feature = "prost")]
 mod prost;
 
 use crate::Status;
 use std::io;
 
-pub(crate) use self::encode::{encode_client, encode_server};
+pub(crate) use self::encode::{encode_client, encode_server, EncodeError};
 
+#[cfg(feature = "bincode-codec")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bincode-codec")))]
+pub use self::bincode::{BincodeCodec, BincodeDecoder, BincodeEncoder};
 pub use self::buffer::{DecodeBuf, EncodeBuf};
+#[cfg(feature = "capnp")]
+#[cfg_attr(docsrs, doc(cfg(feature = "capnp")))]
+pub use self::capnp::{CapnpCodec, CapnpDecoder, CapnpEncoder, CapnpMessage};
 pub use self::compression::{CompressionEncoding, EnabledCompressionEncodings};
-pub use self::decode::Streaming;
+pub use self::decode::{DecodeError, Streaming};
+#[cfg(feature = "flatbuffers")]
+#[cfg_attr(docsrs, doc(cfg(feature = "flatbuffers")))]
+pub use self::flatbuffers::{
+    FlatBuffer, FlatBufferRoot, FlatBuffersCodec, FlatBuffersDecoder, FlatBuffersEncoder,
+};
+pub use self::identity::IdentityCodec;
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub use self::json::{JsonCodec, JsonDecoder, JsonEncoder};
+#[cfg(feature = "msgpack")]
+#[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+pub use self::msgpack::{MsgPackCodec, MsgPackDecoder, MsgPackEncoder};
 #[cfg(feature = "prost")]
 #[cfg_attr(docsrs, doc(cfg(feature = "prost")))]
-pub use self::prost::ProstCodec;
+pub use self::prost::{ProstCodec, ProstDecoder, ProstEncoder};
 
 // 5 bytes
 const HEADER_SIZE: usize =
@@ -32,7 +125,45 @@ const HEADER_SIZE: usize =
 // The default maximum uncompressed size in bytes for a message. Defaults to 4MB.
 const DEFAULT_MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
 
+// The default initial capacity in bytes of the per-RPC encode and decode buffers.
+const DEFAULT_CODEC_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Settings for how tonic allocates and grows buffers.
+///
+/// Tonic eagerly allocates `buffer_size` bytes per RPC for encoding and decoding
+/// messages, and grows the buffers as needed to handle larger messages.
+///
+/// Lowering the buffer size reduces the memory held by idle streams, raising it
+/// avoids reallocations for services that mostly exchange large messages.
+#[derive(Clone, Copy, Debug)]
+pub struct BufferSettings {
+    buffer_size: usize,
+}
+
+impl BufferSettings {
+    /// Create a new `BufferSettings`
+    pub fn new(buffer_size: usize) -> Self {
+        Self { buffer_size }
+    }
+
+    /// The initial capacity in bytes of the encode and decode buffers.
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+}
+
+impl Default for BufferSettings {
+    fn default() -> Self {
+        Self {
+            buffer_size: DEFAULT_CODEC_BUFFER_SIZE,
+        }
+    }
+}
+
 /// Trait that knows how to encode and decode gRPC messages.
+///
+/// A new encoder and decoder is fetched for every call, so they may keep
+/// per-call state. See the [module level docs](self) for an example.
 pub trait Codec {
     /// The encodable message.
     type Encode: Send + 'static;
@@ -48,6 +179,15 @@ pub trait Codec {
     fn encoder(&mut self) -> Self::Encoder;
     /// Fetch the decoder.
     fn decoder(&mut self) -> Self::Decoder;
+
+    /// The `content-type` of the requests and responses of calls using this codec.
+    ///
+    /// Codecs for other formats than protobuf name theirs with a subtype, like
+    /// `application/grpc+json`, so that peers can tell which one a call uses.
+    /// It must be a valid header value.
+    fn content_type(&self) -> &'static str {
+        "application/grpc"
+    }
 }
 
 /// Encodes gRPC message types
@@ -62,6 +202,11 @@ pub trait Encoder {
 
     /// Encodes a message into the provided buffer.
     fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error>;
+
+    /// Controls how tonic creates and expands encode buffers.
+    fn buffer_settings(&self) -> BufferSettings {
+        BufferSettings::default()
+    }
 }
 
 /// Decodes gRPC message types
@@ -76,6 +221,12 @@ pub trait Decoder {
     ///
     /// The buffer will contain exactly the bytes of a full message. There
     /// is no need to get the length from the bytes, gRPC framing is handled
-    /// for you.
+    /// for you. Returning `Ok(None)` fails the stream with an `Internal`
+    /// status, as there is no more data to wait for.
     fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error>;
+
+    /// Controls how tonic creates and expands decode buffers.
+    fn buffer_settings(&self) -> BufferSettings {
+        BufferSettings::default()
+    }
 }
@@ -0,0 +1,163 @@
+use super::{BufferSettings, Codec, DecodeBuf, Decoder, Encoder};
+use crate::codec::EncodeBuf;
+use crate::{Code, Status};
+use bytes::{Buf, BufMut};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// A [`Codec`] that implements `application/grpc+msgpack` via the rmp-serde library.
+///
+/// Structs are encoded as maps keyed by field name, so peers written in other
+/// languages don't need to agree on field order.
+#[derive(Debug, Clone)]
+pub struct MsgPackCodec<T, U> {
+    _pd: PhantomData<(T, U)>,
+}
+
+impl<T, U> Default for MsgPackCodec<T, U> {
+    fn default() -> Self {
+        Self { _pd: PhantomData }
+    }
+}
+
+impl<T, U> MsgPackCodec<T, U>
+where
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+{
+    /// Create a MessagePack [`Encoder`] with the provided [`BufferSettings`].
+    pub fn raw_encoder(buffer_settings: BufferSettings) -> MsgPackEncoder<T> {
+        MsgPackEncoder {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+
+    /// Create a MessagePack [`Decoder`] with the provided [`BufferSettings`].
+    pub fn raw_decoder(buffer_settings: BufferSettings) -> MsgPackDecoder<U> {
+        MsgPackDecoder {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+}
+
+impl<T, U> Codec for MsgPackCodec<T, U>
+where
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+{
+    type Encode = T;
+    type Decode = U;
+
+    type Encoder = MsgPackEncoder<T>;
+    type Decoder = MsgPackDecoder<U>;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        Self::raw_encoder(BufferSettings::default())
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        Self::raw_decoder(BufferSettings::default())
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/grpc+msgpack"
+    }
+}
+
+/// A [`Encoder`] that knows how to encode `T` as MessagePack.
+#[derive(Debug, Clone)]
+pub struct MsgPackEncoder<T> {
+    _pd: PhantomData<T>,
+    buffer_settings: BufferSettings,
+}
+
+impl<T: Serialize> Encoder for MsgPackEncoder<T> {
+    type Item = T;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        rmp_serde::encode::write_named(&mut buf.writer(), &item).map_err(|error| {
+            Status::new(
+                Code::Internal,
+                format!("Error serializing MessagePack message: {}", error),
+            )
+        })
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+/// A [`Decoder`] that knows how to decode `U` from MessagePack.
+#[derive(Debug, Clone)]
+pub struct MsgPackDecoder<U> {
+    _pd: PhantomData<U>,
+    buffer_settings: BufferSettings,
+}
+
+impl<U: DeserializeOwned> Decoder for MsgPackDecoder<U> {
+    type Item = U;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        let item = rmp_serde::from_read(buf.reader())
+            .map(Option::Some)
+            .map_err(from_decode_error)?;
+
+        Ok(item)
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+fn from_decode_error(error: rmp_serde::decode::Error) -> crate::Status {
+    // Map parse errors to an INTERNAL status code, as is done for protobuf, see
+    // https://github.com/grpc/grpc/blob/master/doc/statuscodes.md
+    Status::new(Code::Internal, error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+    use std::collections::BTreeMap;
+
+    type Message = BTreeMap<String, Vec<u32>>;
+
+    #[test]
+    fn round_trip() {
+        let mut msg = Message::new();
+        msg.insert("numbers".to_string(), vec![1, 2, 3]);
+
+        let mut bytes = BytesMut::new();
+        MsgPackCodec::<Message, Message>::default()
+            .encoder()
+            .encode(msg.clone(), &mut EncodeBuf::new(&mut bytes))
+            .unwrap();
+        assert_eq!(&bytes[..], b"\x81\xa7numbers\x93\x01\x02\x03");
+
+        let len = bytes.len();
+        let decoded = MsgPackCodec::<Message, Message>::default()
+            .decoder()
+            .decode(&mut DecodeBuf::new(&mut bytes, len))
+            .unwrap();
+        assert_eq!(decoded, Some(msg));
+    }
+
+    #[test]
+    fn decode_truncated_message() {
+        let mut bytes = BytesMut::from(&b"\x81\xa7num"[..]);
+        let len = bytes.len();
+
+        let status = MsgPackCodec::<Message, Message>::default()
+            .decoder()
+            .decode(&mut DecodeBuf::new(&mut bytes, len))
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Internal);
+    }
+}
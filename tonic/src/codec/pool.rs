@@ -0,0 +1,93 @@
+//! A process wide pool of decode buffers.
+//!
+//! Every [`Streaming`](super::Streaming) needs a buffer to accumulate incoming
+//! frames in. Instead of allocating a fresh buffer per stream, buffers are
+//! taken from this pool and handed back once the stream is dropped, which
+//! avoids allocator churn for services that open and close many streams.
+
+use bytes::BytesMut;
+use std::sync::Mutex;
+
+// The maximum number of idle buffers kept around.
+const MAX_POOLED_BUFFERS: usize = 128;
+
+// Buffers that grew past this capacity while decoding a large message are
+// released instead of being pooled, so a single large message does not pin
+// its memory for the lifetime of the process.
+const MAX_POOLED_BUFFER_CAPACITY: usize = 1024 * 1024;
+
+static POOL: BufferPool = BufferPool::new();
+
+/// Take an empty buffer with at least `capacity` bytes of capacity.
+pub(crate) fn take(capacity: usize) -> BytesMut {
+    POOL.take(capacity)
+}
+
+/// Hand a buffer back to the pool so it can be reused by another stream.
+pub(crate) fn give(buf: BytesMut) {
+    POOL.give(buf)
+}
+
+struct BufferPool {
+    buffers: Mutex<Vec<BytesMut>>,
+}
+
+impl BufferPool {
+    const fn new() -> Self {
+        Self {
+            buffers: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn take(&self, capacity: usize) -> BytesMut {
+        let pooled = self.buffers.lock().ok().and_then(|mut buffers| {
+            let idx = buffers.iter().position(|buf| buf.capacity() >= capacity)?;
+            Some(buffers.swap_remove(idx))
+        });
+
+        pooled.unwrap_or_else(|| BytesMut::with_capacity(capacity))
+    }
+
+    fn give(&self, mut buf: BytesMut) {
+        buf.clear();
+
+        if buf.capacity() == 0 || buf.capacity() > MAX_POOLED_BUFFER_CAPACITY {
+            return;
+        }
+
+        if let Ok(mut buffers) = self.buffers.lock() {
+            if buffers.len() < MAX_POOLED_BUFFERS {
+                buffers.push(buf);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_returned_buffers() {
+        let pool = BufferPool::new();
+        let capacity = 8 * 1024;
+
+        let mut buf = pool.take(capacity);
+        buf.extend_from_slice(b"hello");
+        let ptr = buf.as_ptr();
+        pool.give(buf);
+
+        let buf = pool.take(capacity);
+        assert!(buf.is_empty());
+        assert!(buf.capacity() >= capacity);
+        assert_eq!(buf.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn releases_oversized_buffers() {
+        let pool = BufferPool::new();
+
+        pool.give(BytesMut::with_capacity(MAX_POOLED_BUFFER_CAPACITY + 1));
+        assert!(pool.buffers.lock().unwrap().is_empty());
+    }
+}
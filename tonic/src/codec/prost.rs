@@ -1,4 +1,4 @@
-use super::{Codec, DecodeBuf, Decoder, Encoder};
+use super::{BufferSettings, Codec, DecodeBuf, Decoder, Encoder};
 use crate::codec::EncodeBuf;
 use crate::{Code, Status};
 use prost::Message;
@@ -16,6 +16,32 @@ impl<T, U> Default for ProstCodec<T, U> {
     }
 }
 
+impl<T, U> ProstCodec<T, U>
+where
+    T: Message + Send + 'static,
+    U: Message + Default + Send + 'static,
+{
+    /// Create a prost [`Encoder`] with the provided [`BufferSettings`].
+    ///
+    /// This is a building block for custom codecs that reuse prost encoding.
+    pub fn raw_encoder(buffer_settings: BufferSettings) -> ProstEncoder<T> {
+        ProstEncoder {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+
+    /// Create a prost [`Decoder`] with the provided [`BufferSettings`].
+    ///
+    /// This is a building block for custom codecs that reuse prost decoding.
+    pub fn raw_decoder(buffer_settings: BufferSettings) -> ProstDecoder<U> {
+        ProstDecoder {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+}
+
 impl<T, U> Codec for ProstCodec<T, U>
 where
     T: Message + Send + 'static,
@@ -28,17 +54,20 @@ where
     type Decoder = ProstDecoder<U>;
 
     fn encoder(&mut self) -> Self::Encoder {
-        ProstEncoder(PhantomData)
+        Self::raw_encoder(BufferSettings::default())
     }
 
     fn decoder(&mut self) -> Self::Decoder {
-        ProstDecoder(PhantomData)
+        Self::raw_decoder(BufferSettings::default())
     }
 }
 
 /// A [`Encoder`] that knows how to encode `T`.
 #[derive(Debug, Clone, Default)]
-pub struct ProstEncoder<T>(PhantomData<T>);
+pub struct ProstEncoder<T> {
+    _pd: PhantomData<T>,
+    buffer_settings: BufferSettings,
+}
 
 impl<T: Message> Encoder for ProstEncoder<T> {
     type Item = T;
@@ -50,11 +79,18 @@ impl<T: Message> Encoder for ProstEncoder<T> {
 
         Ok(())
     }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
 }
 
 /// A [`Decoder`] that knows how to decode `U`.
 #[derive(Debug, Clone, Default)]
-pub struct ProstDecoder<U>(PhantomData<U>);
+pub struct ProstDecoder<U> {
+    _pd: PhantomData<U>,
+    buffer_settings: BufferSettings,
+}
 
 impl<U: Message + Default> Decoder for ProstDecoder<U> {
     type Item = U;
@@ -67,6 +103,10 @@ impl<U: Message + Default> Decoder for ProstDecoder<U> {
 
         Ok(item)
     }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
 }
 
 fn from_decode_error(error: prost::DecodeError) -> crate::Status {
@@ -79,10 +119,11 @@ fn from_decode_error(error: prost::DecodeError) -> crate::Status {
 mod tests {
     use crate::codec::compression::SingleMessageCompressionOverride;
     use crate::codec::{
-        encode_server, DecodeBuf, Decoder, EncodeBuf, Encoder, Streaming, HEADER_SIZE,
+        encode_server, BufferSettings, DecodeBuf, Decoder, EncodeBuf, Encoder, ProstCodec,
+        Streaming, HEADER_SIZE,
     };
     use crate::{Code, Status};
-    use bytes::{Buf, BufMut, BytesMut};
+    use bytes::{Buf, BufMut, Bytes, BytesMut};
     use http_body::Body;
 
     const LEN: usize = 10000;
@@ -115,6 +156,103 @@ mod tests {
         assert_eq!(i, 1);
     }
 
+    #[tokio::test]
+    async fn decode_with_custom_buffer_settings() {
+        let decoder = ProstCodec::<(), Vec<u8>>::raw_decoder(BufferSettings::new(16));
+        assert_eq!(decoder.buffer_settings().buffer_size(), 16);
+
+        let msg = vec![1u8; LEN];
+        let encoded = prost::Message::encode_to_vec(&msg);
+
+        let mut buf = BytesMut::new();
+
+        buf.reserve(encoded.len() + HEADER_SIZE);
+        buf.put_u8(0);
+        buf.put_u32(encoded.len() as u32);
+
+        buf.put(&encoded[..]);
+
+        let body = body::MockBody::new(&buf[..], 1000, 0);
+
+        let mut stream = Streaming::new_request(decoder, body, None, None);
+
+        let output_msg = stream.message().await.unwrap().unwrap();
+        assert_eq!(output_msg, msg);
+        assert!(stream.message().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn decode_multiple_messages_split_across_chunks() {
+        let msgs: Vec<Vec<u8>> = (1..=3u8).map(|i| vec![i; 100 * i as usize]).collect();
+
+        let mut buf = BytesMut::new();
+        for msg in &msgs {
+            buf.put_u8(0);
+            buf.put_u32(msg.len() as u32);
+            buf.put(&msg[..]);
+        }
+
+        // The first chunk holds the first message and part of the second one.
+        let body = body::MockBody::new(&buf[..], HEADER_SIZE + 100 + HEADER_SIZE + 50, 0);
+
+        let mut stream = Streaming::new_request(BytesDecoder, body, None, None);
+
+        for msg in &msgs {
+            let output_msg = stream.message().await.unwrap().unwrap();
+            assert_eq!(&output_msg[..], &msg[..]);
+        }
+        assert!(stream.message().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn message_after_end_of_stream() {
+        let msg = vec![0u8; LEN];
+
+        let mut buf = BytesMut::new();
+
+        buf.reserve(msg.len() + HEADER_SIZE);
+        buf.put_u8(0);
+        buf.put_u32(msg.len() as u32);
+
+        buf.put(&msg[..]);
+
+        let body = body::MockBody::new(&buf[..], 10005, 0);
+
+        let mut stream = Streaming::new_request(MockDecoder, body, None, None);
+
+        assert_eq!(stream.message().await.unwrap(), Some(msg));
+        assert_eq!(stream.message().await.unwrap(), None);
+        assert_eq!(stream.message().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn into_parts_mid_stream() {
+        let mut frames = BytesMut::new();
+        for len in [LEN, 100] {
+            frames.put_u8(0);
+            frames.put_u32(len as u32);
+            frames.put(&vec![0u8; len][..]);
+        }
+        let second_frame = frames[HEADER_SIZE + LEN..].to_vec();
+
+        // The first chunk holds the first message and part of the second one.
+        let body = body::MockBody::new(&frames[..], HEADER_SIZE + LEN + HEADER_SIZE + 10, 0);
+
+        let mut stream = Streaming::new_request(MockDecoder, body, None, None);
+
+        assert!(stream.message().await.unwrap().is_some());
+        // Start on the second message, which consumes its header.
+        assert!(futures_util::FutureExt::now_or_never(stream.message()).is_none());
+
+        let (mut body, buffered) = stream.into_parts();
+
+        let mut rest = buffered.to_vec();
+        while let Some(data) = body.data().await {
+            rest.extend_from_slice(&data.unwrap());
+        }
+        assert_eq!(rest, second_frame);
+    }
+
     #[tokio::test]
     async fn decode_max_message_size_exceeded() {
         let decoder = MockDecoder::default();
@@ -136,7 +274,7 @@ mod tests {
         let actual = stream.message().await.unwrap_err();
 
         let expected = Status::new(
-            Code::OutOfRange,
+            Code::ResourceExhausted,
             format!(
                 "Error, message length too large: found {} bytes, the limit is: {} bytes",
                 msg.len(),
@@ -148,6 +286,32 @@ mod tests {
         assert_eq!(actual.message(), expected.message());
     }
 
+    #[tokio::test]
+    async fn decode_max_message_size_exceeded_before_body_is_received() {
+        let decoder = MockDecoder;
+
+        // only send the header, the length prefix alone must be enough to reject the message
+        let mut buf = BytesMut::new();
+        buf.put_u8(0);
+        buf.put_u32(u32::MAX);
+
+        let body = body::MockBody::new(&buf[..], HEADER_SIZE, 0);
+
+        let mut stream = Streaming::new_request(decoder, body, None, Some(MAX_MESSAGE_SIZE));
+
+        let actual = stream.message().await.unwrap_err();
+
+        assert_eq!(actual.code(), Code::ResourceExhausted);
+        assert_eq!(
+            actual.message(),
+            format!(
+                "Error, message length too large: found {} bytes, the limit is: {} bytes",
+                u32::MAX,
+                MAX_MESSAGE_SIZE
+            )
+        );
+    }
+
     #[tokio::test]
     async fn encode() {
         let encoder = MockEncoder::default();
@@ -265,6 +429,18 @@ mod tests {
         }
     }
 
+    #[derive(Debug, Clone)]
+    struct BytesDecoder;
+
+    impl Decoder for BytesDecoder {
+        type Item = Bytes;
+        type Error = Status;
+
+        fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+            Ok(Some(buf.copy_to_bytes(buf.remaining())))
+        }
+    }
+
     mod body {
         use crate::Status;
         use bytes::Bytes;
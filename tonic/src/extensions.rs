@@ -3,10 +3,12 @@ use std::fmt;
 /// A type map of protocol extensions.
 ///
 /// `Extensions` can be used by [`Interceptor`] and [`Request`] to store extra data derived from
-/// the underlying protocol.
+/// the underlying protocol. Extensions of a [`Response`] are passed on to the `http::Response`,
+/// where tower layers wrapping the service can read them.
 ///
 /// [`Interceptor`]: crate::service::Interceptor
 /// [`Request`]: crate::Request
+/// [`Response`]: crate::Response
 #[derive(Default)]
 pub struct Extensions {
     inner: http::Extensions,
@@ -19,7 +19,8 @@
 //! - `transport`: Enables the fully featured, batteries included client and server
 //!     implementation based on [`hyper`], [`tower`] and [`tokio`]. Enabled by default.
 //! - `channel`: Enables just the full featured channel/client portion of the `transport`
-//!     feature.
+//!     feature, without the server and its `axum` dependency. This is all clients generated
+//!     with `tonic-build`'s `build_server(false)` need.
 //! - `codegen`: Enables all the required exports and optional dependencies required
 //! for [`tonic-build`]. Enabled by default.
 //! - `tls`: Enables the `rustls` based TLS options for the `transport` feature. Not
@@ -29,10 +30,27 @@
 //! `tls-roots`.
 //! - `tls-webpki-roots`: Add the standard trust roots from the `webpki-roots` crate to
 //! `rustls`-based gRPC clients. Not enabled by default.
+//! - `tls-native`: Enables the same TLS options backed by the platform TLS library
+//!   through [`native-tls`], such as OpenSSL on Linux. Clients trust the platform roots.
+//!   Servers cannot verify client certificates. `tls` takes precedence when both are
+//!   enabled. Not enabled by default.
 //! - `prost`: Enables the [`prost`] based gRPC [`Codec`] implementation.
+//! - `json`: Enables the [`serde_json`] based gRPC [`Codec`] implementation, useful
+//!   for debugging and clients that can't speak protobuf. Not enabled by default.
+//! - `msgpack`: Enables the [`rmp-serde`] based MessagePack gRPC [`Codec`]
+//!   implementation. Not enabled by default.
+//! - `flatbuffers`: Enables a gRPC [`Codec`] for [`flatbuffers`] generated types
+//!   that verifies and reads messages without copying them. Not enabled by default.
+//! - `capnp`: Enables a gRPC [`Codec`] for [`capnp`] messages. Not enabled by default.
+//! - `bincode-codec`: Enables the [`bincode`] based gRPC [`Codec`] implementation,
+//!   intended for services where both ends are written in Rust. Not enabled by default.
 //! - `gzip`: Enables compressing requests, responses, and streams.
 //! Depends on [flate2]. Not enabled by default.
 //! Replaces the `compression` flag from earlier versions of `tonic` (<= 0.7).
+//! - `deflate`: Enables compressing requests, responses, and streams with deflate.
+//!   Depends on [flate2]. Not enabled by default.
+//! - `zstd`: Enables compressing requests, responses, and streams with zstd.
+//!   Depends on [zstd]. Not enabled by default.
 //!
 //! # Structure
 //!
@@ -51,12 +69,18 @@
 //! It also provides many of the features that the core gRPC libraries provide such as load balancing,
 //! tls, timeouts, and many more. This implementation can also be used as a reference implementation
 //! to build even more feature rich clients and servers. This module also provides the ability to
-//! enable TLS using [`rustls`], via the `tls` feature flag.
+//! enable TLS using [`rustls`], via the `tls` feature flag, or using [`native-tls`], via the
+//! `tls-native` feature flag.
 //!
 //! [gRPC]: https://grpc.io
 //! [`tonic`]: https://github.com/hyperium/tonic
 //! [`tokio`]: https://docs.rs/tokio
 //! [`prost`]: https://docs.rs/prost
+//! [`serde_json`]: https://docs.rs/serde_json
+//! [`rmp-serde`]: https://docs.rs/rmp-serde
+//! [`flatbuffers`]: https://docs.rs/flatbuffers
+//! [`capnp`]: https://docs.rs/capnp
+//! [`bincode`]: https://docs.rs/bincode
 //! [`hyper`]: https://docs.rs/hyper
 //! [`tower`]: https://docs.rs/tower
 //! [`tonic-build`]: https://docs.rs/tonic-build
@@ -65,9 +89,11 @@
 //! [`Channel`]: transport/struct.Channel.html
 //! [`Server`]: transport/struct.Server.html
 //! [`rustls`]: https://docs.rs/rustls
+//! [`native-tls`]: https://docs.rs/native-tls
 //! [`client`]: client/index.html
 //! [`transport`]: transport/index.html
 //! [flate2]: https://crates.io/crates/flate2
+//! [zstd]: https://crates.io/crates/zstd
 
 #![recursion_limit = "256"]
 #![allow(clippy::inconsistent_struct_constructor)]
@@ -93,8 +119,8 @@ pub mod metadata;
 pub mod server;
 pub mod service;
 
-#[cfg(feature = "transport")]
-#[cfg_attr(docsrs, doc(cfg(feature = "transport")))]
+#[cfg(feature = "channel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "channel")))]
 pub mod transport;
 
 mod extensions;
@@ -112,7 +138,7 @@ pub use async_trait::async_trait;
 #[doc(inline)]
 pub use codec::Streaming;
 pub use extensions::Extensions;
-pub use request::{IntoRequest, IntoStreamingRequest, Request};
+pub use request::{GrpcMethod, IntoRequest, IntoStreamingRequest, Request};
 pub use response::Response;
 pub use status::{Code, Status};
 
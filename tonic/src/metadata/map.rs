@@ -998,6 +998,13 @@ impl MetadataMap {
     /// // Trying to insert a key that is binary panics (use insert_bin).
     /// map.insert("x-host-bin", "world".parse().unwrap());
     /// ```
+    ///
+    /// ```compile_fail
+    /// # use tonic::metadata::*;
+    /// let mut map = MetadataMap::new();
+    /// // Binary values can't be inserted, they would not be valid header values.
+    /// map.insert("x-host", BinaryMetadataValue::from_bytes(b"world\xfa"));
+    /// ```
     pub fn insert<K>(&mut self, key: K, val: MetadataValue<Ascii>) -> Option<MetadataValue<Ascii>>
     where
         K: IntoMetadataKey<Ascii>,
@@ -1035,6 +1042,13 @@ impl MetadataMap {
     /// // Trying to insert a key that is not valid panics.
     /// map.insert_bin("x{}host-bin", MetadataValue::from_bytes(b"world")); // This line panics!
     /// ```
+    ///
+    /// ```compile_fail
+    /// # use tonic::metadata::*;
+    /// let mut map = MetadataMap::new();
+    /// // Ascii values can't be inserted, binary values are base64 encoded on the wire.
+    /// map.insert_bin("x-host-bin", AsciiMetadataValue::from_static("world"));
+    /// ```
     pub fn insert_bin<K>(
         &mut self,
         key: K,
@@ -1216,6 +1230,24 @@ impl MetadataMap {
     }
 }
 
+impl<'a> IntoIterator for &'a MetadataMap {
+    type Item = KeyAndValueRef<'a>;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut MetadataMap {
+    type Item = KeyAndMutValueRef<'a>;
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> IterMut<'a> {
+        self.iter_mut()
+    }
+}
+
 // ===== impl Iter =====
 
 impl<'a> Iterator for Iter<'a> {
@@ -2551,6 +2583,37 @@ mod tests {
         assert!(found_x_word_bin);
     }
 
+    #[test]
+    fn test_into_iter_visits_every_value() {
+        let mut map = MetadataMap::new();
+
+        map.insert("x-word", "hello".parse().unwrap());
+        map.append("x-word", "goodbye".parse().unwrap());
+        map.insert_bin("x-word-bin", MetadataValue::from_bytes(b"hello"));
+
+        for key_and_value in &mut map {
+            if let KeyAndMutValueRef::Ascii(_key, value) = key_and_value {
+                value.set_sensitive(true);
+            }
+        }
+
+        let mut ascii = 0;
+        let mut binary = 0;
+        for key_and_value in &map {
+            match key_and_value {
+                KeyAndValueRef::Ascii(_key, value) => {
+                    assert!(value.is_sensitive());
+                    ascii += 1;
+                }
+                KeyAndValueRef::Binary(_key, value) => {
+                    assert!(!value.is_sensitive());
+                    binary += 1;
+                }
+            }
+        }
+        assert_eq!((ascii, binary), (2, 1));
+    }
+
     #[test]
     fn test_iter_mut_categorizes_ascii_entries() {
         let mut map = MetadataMap::new();
@@ -1,13 +1,21 @@
 use crate::metadata::{MetadataMap, MetadataValue};
-#[cfg(all(feature = "transport", feature = "tls"))]
-use crate::transport::server::TlsConnectInfo;
+#[cfg(all(feature = "transport", any(feature = "tls", feature = "tls-native")))]
+use crate::transport::server::{SubjectAltName, TlsConnectInfo};
 #[cfg(feature = "transport")]
-use crate::transport::{server::TcpConnectInfo, Certificate};
+use crate::transport::{server::TcpConnectInfo, Certificate, GrpcDeadline};
 use crate::Extensions;
 use futures_core::Stream;
 #[cfg(feature = "transport")]
 use std::sync::Arc;
-use std::{net::SocketAddr, time::Duration};
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+/// Whether a call waits for its channel to be ready, see [`Request::set_wait_for_ready`].
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "transport"), allow(dead_code))]
+pub(crate) struct WaitForReady(pub(crate) bool);
 
 /// A gRPC request and metadata from an RPC call.
 #[derive(Debug)]
@@ -203,6 +211,40 @@ impl<T> Request<T> {
         }
     }
 
+    /// Get the local address of this connection.
+    ///
+    /// This will return `None` if the `IO` type used
+    /// does not implement `Connected` or when using a unix domain socket.
+    /// This currently only works on the server side.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        #[cfg(feature = "transport")]
+        {
+            #[cfg(any(feature = "tls", feature = "tls-native"))]
+            {
+                self.extensions()
+                    .get::<TcpConnectInfo>()
+                    .and_then(|i| i.local_addr())
+                    .or_else(|| {
+                        self.extensions()
+                            .get::<TlsConnectInfo<TcpConnectInfo>>()
+                            .and_then(|i| i.get_ref().local_addr())
+                    })
+            }
+
+            #[cfg(not(any(feature = "tls", feature = "tls-native")))]
+            {
+                self.extensions()
+                    .get::<TcpConnectInfo>()
+                    .and_then(|i| i.local_addr())
+            }
+        }
+
+        #[cfg(not(feature = "transport"))]
+        {
+            None
+        }
+    }
+
     /// Get the remote address of this connection.
     ///
     /// This will return `None` if the `IO` type used
@@ -211,7 +253,7 @@ impl<T> Request<T> {
     pub fn remote_addr(&self) -> Option<SocketAddr> {
         #[cfg(feature = "transport")]
         {
-            #[cfg(feature = "tls")]
+            #[cfg(any(feature = "tls", feature = "tls-native"))]
             {
                 self.extensions()
                     .get::<TcpConnectInfo>()
@@ -223,7 +265,7 @@ impl<T> Request<T> {
                     })
             }
 
-            #[cfg(not(feature = "tls"))]
+            #[cfg(not(any(feature = "tls", feature = "tls-native")))]
             {
                 self.extensions()
                     .get::<TcpConnectInfo>()
@@ -246,22 +288,41 @@ impl<T> Request<T> {
     #[cfg(feature = "transport")]
     #[cfg_attr(docsrs, doc(cfg(feature = "transport")))]
     pub fn peer_certs(&self) -> Option<Arc<Vec<Certificate>>> {
-        #[cfg(feature = "tls")]
+        #[cfg(any(feature = "tls", feature = "tls-native"))]
         {
             self.extensions()
                 .get::<TlsConnectInfo<TcpConnectInfo>>()
                 .and_then(|i| i.peer_certs())
         }
 
-        #[cfg(not(feature = "tls"))]
+        #[cfg(not(any(feature = "tls", feature = "tls-native")))]
         {
             None
         }
     }
 
+    /// Get the subject alternative names of the connected client's certificate.
+    ///
+    /// With [`ServerTlsConfig::client_ca_root`] set, the certificate has been verified
+    /// against the CA, so these names can be used to authorize the client, for example by
+    /// its SPIFFE id. This returns `None` under the same conditions as
+    /// [`Request::peer_certs`].
+    ///
+    /// [`ServerTlsConfig::client_ca_root`]: crate::transport::ServerTlsConfig::client_ca_root
+    #[cfg(any(feature = "tls", feature = "tls-native"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "tls", feature = "tls-native"))))]
+    pub fn peer_subject_alt_names(&self) -> Option<Vec<SubjectAltName>> {
+        self.extensions()
+            .get::<TlsConnectInfo<TcpConnectInfo>>()
+            .and_then(|i| i.peer_subject_alt_names())
+    }
+
     /// Set the max duration the request is allowed to take.
     ///
     /// Requires the server to support the `grpc-timeout` metadata, which Tonic does.
+    /// A transport `Channel` also enforces it locally and fails the call with
+    /// `DEADLINE_EXCEEDED` when it elapses. A default for all requests can be set with
+    /// `Endpoint::timeout`.
     ///
     /// The duration will be formatted according to [the spec] and use the most precise unit
     /// possible.
@@ -292,6 +353,96 @@ impl<T> Request<T> {
             .insert(crate::metadata::GRPC_TIMEOUT_HEADER, value);
     }
 
+    /// Get the instant by which this request has to complete.
+    ///
+    /// This is the earlier of the `grpc-timeout` sent by the client and the timeout
+    /// configured with `Server::timeout`. Once it has passed the server aborts the
+    /// call with [`Code::DeadlineExceeded`], a handler can use it to avoid starting
+    /// work that can't finish in time.
+    ///
+    /// This will return `None` if neither timeout is set.
+    /// This currently only works on the server side of the `transport` server.
+    ///
+    /// [`Code::DeadlineExceeded`]: crate::Code::DeadlineExceeded
+    pub fn deadline(&self) -> Option<Instant> {
+        #[cfg(feature = "transport")]
+        {
+            self.extensions().get::<GrpcDeadline>().map(|d| d.0)
+        }
+
+        #[cfg(not(feature = "transport"))]
+        {
+            None
+        }
+    }
+
+    /// Set whether the call waits for the channel to connect instead of failing.
+    ///
+    /// By default a call made while a transport `Channel` cannot connect to its server fails
+    /// right away with `UNAVAILABLE`. A call that waits for ready is held instead, and sent
+    /// as soon as a connection is made, until its timeout runs out and it fails with
+    /// `DEADLINE_EXCEEDED`. This is useful to ride out a server restarting.
+    ///
+    /// **Note**: This only has effect on the client side.
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tonic::Request;
+    ///
+    /// let mut request = Request::new(());
+    /// request.set_wait_for_ready(true);
+    /// request.set_timeout(Duration::from_secs(10));
+    /// ```
+    pub fn set_wait_for_ready(&mut self, enabled: bool) {
+        self.extensions_mut().insert(WaitForReady(enabled));
+    }
+
+    /// Compress this request with the provided encoding.
+    ///
+    /// This takes precedence over the encoding configured on the client with
+    /// `send_compressed`, so individual calls can opt into compression.
+    ///
+    /// Requires the server to accept the specified encoding, otherwise it might return an error.
+    ///
+    /// **Note**: This only has effect on the client side.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "gzip", doc = "```rust")]
+    #[cfg_attr(not(feature = "gzip"), doc = "```ignore")]
+    /// use tonic::{codec::CompressionEncoding, Request};
+    ///
+    /// let mut request = Request::new(());
+    ///
+    /// request.set_compression(CompressionEncoding::Gzip);
+    /// ```
+    #[cfg(any(feature = "gzip", feature = "deflate", feature = "zstd"))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "gzip", feature = "deflate", feature = "zstd")))
+    )]
+    pub fn set_compression(&mut self, encoding: crate::codec::CompressionEncoding) {
+        self.extensions_mut()
+            .insert(crate::codec::compression::RequestCompressionOverride(Some(
+                encoding,
+            )));
+    }
+
+    /// Disable compression of this request.
+    ///
+    /// The request will be sent uncompressed, even if compression is enabled on the client.
+    ///
+    /// **Note**: This only has effect on the client side.
+    #[cfg(any(feature = "gzip", feature = "deflate", feature = "zstd"))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "gzip", feature = "deflate", feature = "zstd")))
+    )]
+    pub fn disable_compression(&mut self) {
+        self.extensions_mut()
+            .insert(crate::codec::compression::RequestCompressionOverride(None));
+    }
+
     /// Returns a reference to the associated extensions.
     pub fn extensions(&self) -> &Extensions {
         &self.extensions
@@ -383,13 +534,66 @@ where
     }
 }
 
+/// The gRPC method a request is sent to.
+///
+/// This is parsed from the `:path` of the request, which has the form
+/// `/{package}.{service}/{method}`, and is available through the
+/// [request extensions][ext] in interceptors.
+///
+/// ```
+/// # use tonic::{GrpcMethod, Request, Status};
+/// fn only_say_hello(request: Request<()>) -> Result<Request<()>, Status> {
+///     match request.extensions().get::<GrpcMethod>() {
+///         Some(m) if m.method() == "SayHello" => Ok(request),
+///         _ => Err(Status::permission_denied("only SayHello is allowed")),
+///     }
+/// }
+/// ```
+///
+/// [ext]: crate::Request::extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrpcMethod {
+    path: String,
+    split: usize,
+}
+
+impl GrpcMethod {
+    pub(crate) fn from_path(path: &str) -> Option<Self> {
+        let (service, method) = path.strip_prefix('/')?.split_once('/')?;
+
+        if service.is_empty() || method.is_empty() || method.contains('/') {
+            return None;
+        }
+
+        Some(GrpcMethod {
+            path: path.to_string(),
+            split: service.len() + 1,
+        })
+    }
+
+    /// Get the fully qualified name of the service, e.g. `helloworld.Greeter`.
+    pub fn service(&self) -> &str {
+        &self.path[1..self.split]
+    }
+
+    /// Get the name of the method, e.g. `SayHello`.
+    pub fn method(&self) -> &str {
+        &self.path[self.split + 1..]
+    }
+
+    /// Get the path the method is served at, e.g. `/helloworld.Greeter/SayHello`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
 impl<T> sealed::Sealed for T {}
 
 mod sealed {
     pub trait Sealed {}
 }
 
-fn duration_to_grpc_timeout(duration: Duration) -> String {
+pub(crate) fn duration_to_grpc_timeout(duration: Duration) -> String {
     fn try_format<T: Into<u128>>(
         duration: Duration,
         unit: char,
@@ -436,6 +640,25 @@ mod tests {
     use crate::metadata::MetadataValue;
     use http::Uri;
 
+    #[test]
+    fn grpc_method_from_path() {
+        let method = GrpcMethod::from_path("/helloworld.Greeter/SayHello").unwrap();
+        assert_eq!(method.service(), "helloworld.Greeter");
+        assert_eq!(method.method(), "SayHello");
+        assert_eq!(method.path(), "/helloworld.Greeter/SayHello");
+
+        for path in [
+            "",
+            "/",
+            "helloworld.Greeter/SayHello",
+            "/Greeter/",
+            "//SayHello",
+            "/a/b/c",
+        ] {
+            assert_eq!(GrpcMethod::from_path(path), None, "{:?}", path);
+        }
+    }
+
     #[test]
     fn reserved_headers_are_excluded() {
         let mut r = Request::new(1);
@@ -1,5 +1,11 @@
 use crate::{metadata::MetadataMap, Extensions};
 
+/// The largest message a response may have, set by channels from the service config of the
+/// method it answers.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "transport"), allow(dead_code))]
+pub(crate) struct MaxMessageSize(pub(crate) usize);
+
 /// A gRPC response and metadata from an RPC call.
 #[derive(Debug)]
 pub struct Response<T> {
@@ -118,8 +124,11 @@ impl<T> Response<T> {
     /// **Note**: This only has effect on responses to unary requests and responses to client to
     /// server streams. Response streams (server to client stream and bidirectional streams) will
     /// still be compressed according to the configuration of the server.
-    #[cfg(feature = "gzip")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+    #[cfg(any(feature = "gzip", feature = "deflate", feature = "zstd"))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "gzip", feature = "deflate", feature = "zstd")))
+    )]
     pub fn disable_compression(&mut self) {
         self.extensions_mut()
             .insert(crate::codec::compression::SingleMessageCompressionOverride::Disable);
@@ -122,6 +122,9 @@ where
 
     /// Limits the maximum size of a decoded message.
     ///
+    /// Messages whose length prefix exceeds the limit are rejected with
+    /// [`Code::ResourceExhausted`] before their body is buffered.
+    ///
     /// # Example
     ///
     /// The most common way of using this is through a server generated by tonic-build:
@@ -291,7 +294,11 @@ where
             }
         };
 
-        let response = service.call(request).await;
+        let deadline = request.deadline();
+        let response = service
+            .call(request)
+            .await
+            .map(|r| r.map(|stream| enforce_deadline(stream, deadline)));
 
         self.map_response(
             response,
@@ -355,7 +362,11 @@ where
 
         let request = t!(self.map_request_streaming(req));
 
-        let response = service.call(request).await;
+        let deadline = request.deadline();
+        let response = service
+            .call(request)
+            .await
+            .map(|r| r.map(|stream| enforce_deadline(stream, deadline)));
 
         self.map_response(
             response,
@@ -442,7 +453,7 @@ where
         // Set the content type
         parts.headers.insert(
             http::header::CONTENT_TYPE,
-            http::header::HeaderValue::from_static("application/grpc"),
+            http::header::HeaderValue::from_static(self.codec.content_type()),
         );
 
         if let Some(encoding) = accept_encoding {
@@ -453,6 +464,17 @@ where
             );
         }
 
+        // Advertise which encodings the server accepts for requests
+        if let Some(header_value) = self
+            .accept_compression_encodings
+            .into_accept_encoding_header_value()
+        {
+            parts.headers.insert(
+                crate::codec::compression::ACCEPT_ENCODING_HEADER,
+                header_value,
+            );
+        }
+
         let body = encode_server(
             self.codec.encoder(),
             body.into_stream(),
@@ -508,3 +530,61 @@ fn compression_override_from_response<B, E>(
         })
         .unwrap_or_default()
 }
+
+/// Ends a streaming response with `DEADLINE_EXCEEDED` once `deadline` has passed.
+///
+/// The transport server only bounds the time until the response headers are sent, this
+/// covers the messages that follow. Dropping the stream cancels the handler producing it.
+#[cfg(feature = "transport")]
+fn enforce_deadline<S>(
+    stream: S,
+    deadline: Option<std::time::Instant>,
+) -> impl TryStream<Ok = S::Ok, Error = Status>
+where
+    S: TryStream<Error = Status>,
+{
+    DeadlineStream {
+        inner: Some(Box::pin(stream.into_stream())),
+        sleep: deadline.map(|d| Box::pin(tokio::time::sleep_until(d.into()))),
+    }
+}
+
+#[cfg(not(feature = "transport"))]
+fn enforce_deadline<S>(stream: S, _deadline: Option<std::time::Instant>) -> S {
+    stream
+}
+
+#[cfg(feature = "transport")]
+struct DeadlineStream<S> {
+    inner: Option<std::pin::Pin<Box<S>>>,
+    sleep: Option<std::pin::Pin<Box<tokio::time::Sleep>>>,
+}
+
+#[cfg(feature = "transport")]
+impl<S, T> futures_core::Stream for DeadlineStream<S>
+where
+    S: futures_core::Stream<Item = Result<T, Status>>,
+{
+    type Item = Result<T, Status>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        use std::future::Future;
+        use std::task::Poll;
+
+        if let Some(sleep) = self.sleep.as_mut() {
+            if sleep.as_mut().poll(cx).is_ready() {
+                self.sleep = None;
+                self.inner = None;
+                return Poll::Ready(Some(Err(Status::deadline_exceeded("Timeout expired"))));
+            }
+        }
+
+        match self.inner.as_mut() {
+            Some(inner) => inner.as_mut().poll_next(cx),
+            None => Poll::Ready(None),
+        }
+    }
+}
@@ -5,9 +5,10 @@
 use crate::{
     body::{boxed, BoxBody},
     request::SanitizeHeaders,
-    Status,
+    GrpcMethod, Status,
 };
 use bytes::Bytes;
+use futures_util::ready;
 use pin_project::pin_project;
 use std::{
     fmt,
@@ -22,16 +23,21 @@ use tower_service::Service;
 ///
 /// gRPC interceptors are similar to middleware but have less flexibility. An interceptor allows
 /// you to do two main things, one is to add/remove/check items in the `MetadataMap` of each
-/// request. Two, cancel a request with a `Status`.
+/// request. Two, cancel a request with a `Status`. The method being called is available as a
+/// [`GrpcMethod`] in the request extensions.
 ///
 /// Any function that satisfies the bound `FnMut(Request<()>) -> Result<Request<()>, Status>` can be
 /// used as an `Interceptor`.
 ///
 /// An interceptor can be used on both the server and client side through the `tonic-build` crate's
-/// generated structs.
+/// generated structs. To apply one interceptor to every client sharing a channel, wrap the
+/// channel with the [`interceptor`] layer instead.
 ///
 /// See the [interceptor example][example] for more details.
 ///
+/// Interceptors that need to await, for example to check a token against a remote service,
+/// can be written as an [`AsyncInterceptor`].
+///
 /// If you need more powerful middleware, [tower] is the recommended approach. You can find
 /// examples of how to use tower with tonic [here][tower-example].
 ///
@@ -161,7 +167,7 @@ where
         self.inner.poll_ready(cx)
     }
 
-    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
         // It is bad practice to modify the body (i.e. Message) of the request via an interceptor.
         // To avoid exposing the body of the request to the interceptor function, we first remove it
         // here, allow the interceptor to modify the metadata and extensions, and then recreate the
@@ -170,6 +176,13 @@ where
         let uri = req.uri().clone();
         let method = req.method().clone();
         let version = req.version();
+
+        if req.extensions().get::<GrpcMethod>().is_none() {
+            if let Some(grpc_method) = GrpcMethod::from_path(uri.path()) {
+                req.extensions_mut().insert(grpc_method);
+            }
+        }
+
         let req = crate::Request::from_http(req);
         let (metadata, extensions, msg) = req.into_parts();
 
@@ -252,6 +265,257 @@ where
     }
 }
 
+/// An asynchronous gRPC interceptor.
+///
+/// This works like an [`Interceptor`] but may await before deciding on the request, for
+/// example to validate a token against a cache or an introspection endpoint.
+///
+/// Any function that satisfies the bound `FnMut(Request<()>) -> Fut`, where `Fut` is a future
+/// resolving to `Result<Request<()>, Status>`, can be used as an `AsyncInterceptor`.
+///
+/// ```
+/// use tonic::{service::async_interceptor, Request, Status};
+///
+/// async fn check_token(request: Request<()>) -> Result<Request<()>, Status> {
+///     match request.metadata().get("authorization") {
+///         Some(token) if token == "Bearer secret" => Ok(request),
+///         _ => Err(Status::unauthenticated("invalid token")),
+///     }
+/// }
+///
+/// let layer = async_interceptor(check_token);
+/// ```
+///
+/// The wrapped service is cloned for every request since it is only called once the
+/// interceptor has finished.
+pub trait AsyncInterceptor {
+    /// The future returned by [`AsyncInterceptor::call`].
+    type Future: Future<Output = Result<crate::Request<()>, Status>>;
+
+    /// Intercept a request before it is sent, optionally cancelling it.
+    fn call(&mut self, request: crate::Request<()>) -> Self::Future;
+}
+
+impl<F, U> AsyncInterceptor for F
+where
+    F: FnMut(crate::Request<()>) -> U,
+    U: Future<Output = Result<crate::Request<()>, Status>>,
+{
+    type Future = U;
+
+    fn call(&mut self, request: crate::Request<()>) -> Self::Future {
+        self(request)
+    }
+}
+
+/// Create a new async interceptor layer.
+///
+/// See [`AsyncInterceptor`] for more details.
+pub fn async_interceptor<F>(f: F) -> AsyncInterceptorLayer<F>
+where
+    F: AsyncInterceptor,
+{
+    AsyncInterceptorLayer { f }
+}
+
+/// An async gRPC interceptor that can be used as a [`Layer`],
+/// created by calling [`async_interceptor`].
+///
+/// See [`AsyncInterceptor`] for more details.
+#[derive(Debug, Clone, Copy)]
+pub struct AsyncInterceptorLayer<F> {
+    f: F,
+}
+
+impl<S, F> Layer<S> for AsyncInterceptorLayer<F>
+where
+    F: AsyncInterceptor + Clone,
+{
+    type Service = AsyncInterceptedService<S, F>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        AsyncInterceptedService::new(service, self.f.clone())
+    }
+}
+
+/// A service wrapped in an async interceptor middleware.
+///
+/// See [`AsyncInterceptor`] for more details.
+#[derive(Clone, Copy)]
+pub struct AsyncInterceptedService<S, F> {
+    inner: S,
+    f: F,
+}
+
+impl<S, F> AsyncInterceptedService<S, F> {
+    /// Create a new `AsyncInterceptedService` that wraps `S` and intercepts each request with
+    /// the function `F`.
+    pub fn new(service: S, f: F) -> Self
+    where
+        F: AsyncInterceptor,
+    {
+        Self { inner: service, f }
+    }
+}
+
+impl<S, F> fmt::Debug for AsyncInterceptedService<S, F>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncInterceptedService")
+            .field("inner", &self.inner)
+            .field("f", &format_args!("{}", std::any::type_name::<F>()))
+            .finish()
+    }
+}
+
+impl<S, F, ReqBody, ResBody> Service<http::Request<ReqBody>> for AsyncInterceptedService<S, F>
+where
+    F: AsyncInterceptor,
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>> + Clone,
+    S::Error: Into<crate::Error>,
+    ResBody: Default + http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<crate::Error>,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = S::Error;
+    type Future = AsyncResponseFuture<S, F::Future, ReqBody>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        // See `InterceptedService::call`, the body is kept out of reach of the interceptor in
+        // the same way.
+        let uri = req.uri().clone();
+        let method = req.method().clone();
+        let version = req.version();
+
+        if req.extensions().get::<GrpcMethod>().is_none() {
+            if let Some(grpc_method) = GrpcMethod::from_path(uri.path()) {
+                req.extensions_mut().insert(grpc_method);
+            }
+        }
+
+        let req = crate::Request::from_http(req);
+        let (metadata, extensions, msg) = req.into_parts();
+
+        // The service that was driven to readiness is the one that has to be called.
+        let clone = self.inner.clone();
+        let inner = std::mem::replace(&mut self.inner, clone);
+
+        AsyncResponseFuture {
+            kind: AsyncKind::Intercepting {
+                future: self
+                    .f
+                    .call(crate::Request::from_parts(metadata, extensions, ())),
+                next: Some(Next {
+                    inner,
+                    uri,
+                    method,
+                    version,
+                    msg,
+                }),
+            },
+        }
+    }
+}
+
+// required to use `AsyncInterceptedService` with `Router`
+impl<S, F> crate::server::NamedService for AsyncInterceptedService<S, F>
+where
+    S: crate::server::NamedService,
+{
+    const NAME: &'static str = S::NAME;
+}
+
+/// Response future for [`AsyncInterceptedService`].
+#[pin_project]
+pub struct AsyncResponseFuture<S, I, ReqBody>
+where
+    S: Service<http::Request<ReqBody>>,
+{
+    #[pin]
+    kind: AsyncKind<S, I, ReqBody>,
+}
+
+#[pin_project(project = AsyncKindProj)]
+enum AsyncKind<S, I, ReqBody>
+where
+    S: Service<http::Request<ReqBody>>,
+{
+    Intercepting {
+        #[pin]
+        future: I,
+        next: Option<Next<S, ReqBody>>,
+    },
+    Calling(#[pin] S::Future),
+}
+
+struct Next<S, ReqBody> {
+    inner: S,
+    uri: http::Uri,
+    method: http::Method,
+    version: http::Version,
+    msg: ReqBody,
+}
+
+impl<S, I, ReqBody> fmt::Debug for AsyncResponseFuture<S, I, ReqBody>
+where
+    S: Service<http::Request<ReqBody>>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncResponseFuture").finish()
+    }
+}
+
+impl<S, I, ReqBody, ResBody> Future for AsyncResponseFuture<S, I, ReqBody>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    I: Future<Output = Result<crate::Request<()>, Status>>,
+    ResBody: Default + http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<crate::Error>,
+{
+    type Output = Result<http::Response<BoxBody>, S::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut kind = self.project().kind;
+
+        loop {
+            match kind.as_mut().project() {
+                AsyncKindProj::Intercepting { future, next } => match ready!(future.poll(cx)) {
+                    Ok(req) => {
+                        let Next {
+                            mut inner,
+                            uri,
+                            method,
+                            version,
+                            msg,
+                        } = next.take().expect("polled after completion");
+
+                        let (metadata, extensions, _) = req.into_parts();
+                        let req = crate::Request::from_parts(metadata, extensions, msg);
+                        let req = req.into_http(uri, method, version, SanitizeHeaders::No);
+                        kind.set(AsyncKind::Calling(inner.call(req)));
+                    }
+                    Err(status) => {
+                        let response = status.to_http().map(|_| ResBody::default()).map(boxed);
+                        return Poll::Ready(Ok(response));
+                    }
+                },
+                AsyncKindProj::Calling(future) => {
+                    return future
+                        .poll(cx)
+                        .map(|result| result.map(|res| res.map(boxed)));
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
@@ -340,6 +604,82 @@ mod tests {
         assert_eq!(expected.headers(), response.headers());
     }
 
+    #[tokio::test]
+    async fn provides_the_grpc_method() {
+        let svc = tower::service_fn(|request: http::Request<TestBody>| async move {
+            let method = request
+                .extensions()
+                .get::<GrpcMethod>()
+                .expect("missing in leaf service");
+            assert_eq!(method.service(), "test.Test");
+            assert_eq!(method.method(), "UnaryCall");
+
+            Ok::<_, Status>(http::Response::new(TestBody))
+        });
+
+        let svc = InterceptedService::new(svc, Ok);
+
+        let request = http::Request::builder()
+            .uri("http://example.com/test.Test/UnaryCall")
+            .body(TestBody)
+            .unwrap();
+
+        svc.oneshot(request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn async_interceptor_runs_before_the_service() {
+        let svc = tower::service_fn(|request: http::Request<TestBody>| async move {
+            assert_eq!(
+                request
+                    .headers()
+                    .get("x-checked")
+                    .expect("missing in leaf service"),
+                "yes"
+            );
+            assert!(request.extensions().get::<GrpcMethod>().is_some());
+
+            Ok::<_, Status>(http::Response::new(TestBody))
+        });
+
+        let svc = AsyncInterceptedService::new(svc, |mut request: crate::Request<()>| async move {
+            tokio::task::yield_now().await;
+            request
+                .metadata_mut()
+                .insert("x-checked", "yes".parse().unwrap());
+            Ok(request)
+        });
+
+        let request = http::Request::builder()
+            .uri("http://example.com/test.Test/UnaryCall")
+            .body(TestBody)
+            .unwrap();
+
+        svc.oneshot(request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn handles_async_intercepted_status_as_response() {
+        let message = "Blocked by the interceptor";
+        let expected = Status::permission_denied(message).to_http();
+
+        let svc = tower::service_fn(|_: http::Request<TestBody>| async {
+            Ok::<_, Status>(http::Response::new(TestBody))
+        });
+
+        let svc = AsyncInterceptedService::new(svc, |_: crate::Request<()>| async move {
+            tokio::task::yield_now().await;
+            Err(Status::permission_denied(message))
+        });
+
+        let request = http::Request::builder().body(TestBody).unwrap();
+        let response = svc.oneshot(request).await.unwrap();
+
+        assert_eq!(expected.status(), response.status());
+        assert_eq!(expected.version(), response.version());
+        assert_eq!(expected.headers(), response.headers());
+    }
+
     #[tokio::test]
     async fn doesnt_change_http_method() {
         let svc = tower::service_fn(|request: http::Request<hyper::Body>| async move {
@@ -0,0 +1,388 @@
+//! Middleware for individual services and methods.
+//!
+//! Layers added with `Server::layer` wrap every service on a server. To wrap only some of
+//! them, layer the services before adding them: [`LayerExt::named_layer`] keeps the
+//! [`NamedService`] implementation the router needs, and [`method_layer`] applies a layer to
+//! just the methods of a service that a predicate selects.
+//!
+//! ```
+//! # use tonic::{body::BoxBody, server::NamedService, GrpcMethod};
+//! # use std::{convert::Infallible, future::{ready, Ready}, task::{Context, Poll}};
+//! # #[derive(Clone)]
+//! # struct Svc;
+//! # impl tower_service::Service<http::Request<hyper::Body>> for Svc {
+//! #     type Response = http::Response<BoxBody>;
+//! #     type Error = Infallible;
+//! #     type Future = Ready<Result<Self::Response, Self::Error>>;
+//! #     fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> { Poll::Ready(Ok(())) }
+//! #     fn call(&mut self, _: http::Request<hyper::Body>) -> Self::Future { unimplemented!() }
+//! # }
+//! # impl NamedService for Svc { const NAME: &'static str = "example.Svc"; }
+//! use tonic::{
+//!     service::{interceptor, layered::method_layer, LayerExt},
+//!     transport::Server,
+//!     Request, Status,
+//! };
+//! use tower::limit::ConcurrencyLimitLayer;
+//!
+//! fn check_auth(req: Request<()>) -> Result<Request<()>, Status> {
+//!     match req.metadata().get("authorization") {
+//!         Some(_) => Ok(req),
+//!         None => Err(Status::unauthenticated("no token")),
+//!     }
+//! }
+//!
+//! // Only this service is authenticated...
+//! let svc = interceptor(check_auth).named_layer(Svc);
+//! // ...and it handles at most 10 `Expensive` calls at once.
+//! let svc = method_layer(
+//!     |method: &GrpcMethod| method.method() == "Expensive",
+//!     ConcurrencyLimitLayer::new(10),
+//! )
+//! .named_layer(svc);
+//!
+//! let router = Server::builder().add_service(svc);
+//! ```
+
+use crate::{server::NamedService, GrpcMethod};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A service wrapped in a layer, that is named like the service it wraps.
+///
+/// Created with [`LayerExt::named_layer`].
+pub struct Layered<S, T> {
+    inner: S,
+    _ty: PhantomData<fn() -> T>,
+}
+
+impl<S: Clone, T> Clone for Layered<S, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            _ty: PhantomData,
+        }
+    }
+}
+
+impl<S: fmt::Debug, T> fmt::Debug for Layered<S, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Layered")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<S, T: NamedService> NamedService for Layered<S, T> {
+    const NAME: &'static str = T::NAME;
+}
+
+impl<S, T, Req> Service<Req> for Layered<S, T>
+where
+    S: Service<Req>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        self.inner.call(req)
+    }
+}
+
+/// Extension trait for applying a [`Layer`] to a single service.
+pub trait LayerExt<L>: sealed::Sealed {
+    /// Wraps `service` in this layer, keeping the [`NamedService`] implementation of
+    /// `service` so the result can be added to a router.
+    fn named_layer<S>(&self, service: S) -> Layered<L::Service, S>
+    where
+        L: Layer<S>;
+}
+
+impl<L> LayerExt<L> for L {
+    fn named_layer<S>(&self, service: S) -> Layered<L::Service, S>
+    where
+        L: Layer<S>,
+    {
+        Layered {
+            inner: self.layer(service),
+            _ty: PhantomData,
+        }
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl<T> Sealed for T {}
+}
+
+/// Creates a layer that applies `layer` only to the methods `predicate` returns `true` for.
+///
+/// Requests for other methods, and requests whose path is not a gRPC method, go to the
+/// service directly. Both must respond with the same types.
+pub fn method_layer<P, L>(predicate: P, layer: L) -> MethodLayer<P, L>
+where
+    P: Fn(&GrpcMethod) -> bool + Clone,
+{
+    MethodLayer { predicate, layer }
+}
+
+/// A layer that is applied to some methods of a service, created by calling
+/// [`method_layer`].
+#[derive(Debug, Clone, Copy)]
+pub struct MethodLayer<P, L> {
+    predicate: P,
+    layer: L,
+}
+
+impl<S, P, L> Layer<S> for MethodLayer<P, L>
+where
+    S: Clone,
+    P: Clone,
+    L: Layer<S>,
+{
+    type Service = MethodLayered<S, P, L::Service>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        MethodLayered {
+            layered: self.layer.layer(service.clone()),
+            inner: service,
+            predicate: self.predicate.clone(),
+        }
+    }
+}
+
+/// A service with a layer applied to some of its methods.
+///
+/// See [`method_layer`] for more details.
+#[derive(Clone)]
+pub struct MethodLayered<S, P, L> {
+    inner: S,
+    layered: L,
+    predicate: P,
+}
+
+impl<S, P, L> fmt::Debug for MethodLayered<S, P, L>
+where
+    S: fmt::Debug,
+    L: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MethodLayered")
+            .field("inner", &self.inner)
+            .field("layered", &self.layered)
+            .field("predicate", &format_args!("{}", std::any::type_name::<P>()))
+            .finish()
+    }
+}
+
+impl<S, P, L, ReqBody> Service<http::Request<ReqBody>> for MethodLayered<S, P, L>
+where
+    S: Service<http::Request<ReqBody>> + Clone,
+    L: Service<http::Request<ReqBody>, Response = S::Response, Error = S::Error> + Clone,
+    P: Fn(&GrpcMethod) -> bool,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S, L, http::Request<ReqBody>>;
+
+    // Which service handles a request is only known once it is called, so readiness is
+    // checked in the response future instead. This way a saturated layer, such as a
+    // concurrency limit, does not hold back the other methods.
+    #[inline]
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let layered = match GrpcMethod::from_path(req.uri().path()) {
+            Some(method) => (self.predicate)(&method),
+            None => false,
+        };
+
+        let state = if layered {
+            State::LayeredNotReady {
+                svc: self.layered.clone(),
+                req: Some(req),
+            }
+        } else {
+            State::InnerNotReady {
+                svc: self.inner.clone(),
+                req: Some(req),
+            }
+        };
+
+        ResponseFuture { state }
+    }
+}
+
+impl<S: NamedService, P, L> NamedService for MethodLayered<S, P, L> {
+    const NAME: &'static str = S::NAME;
+}
+
+/// Response future for [`MethodLayered`].
+#[pin_project]
+pub struct ResponseFuture<S, L, Req>
+where
+    S: Service<Req>,
+    L: Service<Req>,
+{
+    #[pin]
+    state: State<S, L, Req>,
+}
+
+#[pin_project(project = StateProj)]
+enum State<S, L, Req>
+where
+    S: Service<Req>,
+    L: Service<Req>,
+{
+    InnerNotReady { svc: S, req: Option<Req> },
+    Inner(#[pin] S::Future),
+    LayeredNotReady { svc: L, req: Option<Req> },
+    Layered(#[pin] L::Future),
+}
+
+impl<S, L, Req> Future for ResponseFuture<S, L, Req>
+where
+    S: Service<Req>,
+    L: Service<Req, Response = S::Response, Error = S::Error>,
+{
+    type Output = Result<S::Response, S::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.project().state;
+
+        loop {
+            let next = match state.as_mut().project() {
+                StateProj::InnerNotReady { svc, req } => {
+                    futures_util::ready!(svc.poll_ready(cx))?;
+                    let req = req.take().expect("polled after ready");
+                    State::Inner(svc.call(req))
+                }
+                StateProj::LayeredNotReady { svc, req } => {
+                    futures_util::ready!(svc.poll_ready(cx))?;
+                    let req = req.take().expect("polled after ready");
+                    State::Layered(svc.call(req))
+                }
+                StateProj::Inner(fut) => return fut.poll(cx),
+                StateProj::Layered(fut) => return fut.poll(cx),
+            };
+            state.set(next);
+        }
+    }
+}
+
+impl<S, L, Req> fmt::Debug for ResponseFuture<S, L, Req>
+where
+    S: Service<Req>,
+    L: Service<Req>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::{Request, Response};
+    use std::convert::Infallible;
+    use tower::{service_fn, ServiceExt};
+
+    #[derive(Clone)]
+    struct Tag(&'static str);
+
+    impl<S> Layer<S> for Tag {
+        type Service = Tagged<S>;
+
+        fn layer(&self, inner: S) -> Self::Service {
+            Tagged(self.0, inner)
+        }
+    }
+
+    #[derive(Clone)]
+    struct Tagged<S>(&'static str, S);
+
+    impl<S> Service<Request<()>> for Tagged<S>
+    where
+        S: Service<Request<()>>,
+    {
+        type Response = S::Response;
+        type Error = S::Error;
+        type Future = S::Future;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.1.poll_ready(cx)
+        }
+
+        fn call(&mut self, mut req: Request<()>) -> Self::Future {
+            req.extensions_mut().insert(self.0);
+            self.1.call(req)
+        }
+    }
+
+    async fn call<S>(svc: S, path: &str) -> Option<&'static str>
+    where
+        S: Service<Request<()>, Response = Response<Option<&'static str>>, Error = Infallible>,
+    {
+        let req = Request::builder().uri(path).body(()).unwrap();
+        svc.oneshot(req).await.unwrap().into_body()
+    }
+
+    fn echo_tag(
+    ) -> impl Service<Request<()>, Response = Response<Option<&'static str>>, Error = Infallible> + Clone
+    {
+        service_fn(|req: Request<()>| async move {
+            let tag = req.extensions().get::<&'static str>().copied();
+            Ok::<_, Infallible>(Response::new(tag))
+        })
+    }
+
+    #[tokio::test]
+    async fn applies_layer_to_selected_methods() {
+        let svc =
+            method_layer(|m: &GrpcMethod| m.method() == "Tagged", Tag("layered")).layer(echo_tag());
+
+        assert_eq!(
+            call(svc.clone(), "/test.Test/Tagged").await,
+            Some("layered")
+        );
+        assert_eq!(call(svc.clone(), "/test.Test/Other").await, None);
+        assert_eq!(call(svc, "/not/a/grpc/method").await, None);
+    }
+
+    #[test]
+    fn named_layer_keeps_the_service_name() {
+        #[derive(Clone)]
+        struct Svc;
+
+        impl NamedService for Svc {
+            const NAME: &'static str = "test.Test";
+        }
+
+        fn name<S: NamedService>(_: &S) -> &'static str {
+            S::NAME
+        }
+
+        let svc = Tag("layered").named_layer(Svc);
+        assert_eq!(name(&svc), "test.Test");
+
+        let svc = method_layer(|_: &GrpcMethod| true, Tag("layered")).named_layer(svc);
+        assert_eq!(name(&svc), "test.Test");
+    }
+}
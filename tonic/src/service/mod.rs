@@ -1,7 +1,12 @@
 //! Utilities for using Tower services with Tonic.
 
 pub mod interceptor;
+pub mod layered;
 
 #[doc(inline)]
 #[allow(deprecated)]
-pub use self::interceptor::{interceptor, interceptor_fn, Interceptor};
+pub use self::interceptor::{
+    async_interceptor, interceptor, interceptor_fn, AsyncInterceptor, Interceptor,
+};
+#[doc(inline)]
+pub use self::layered::LayerExt;
@@ -314,8 +314,10 @@ impl Status {
 
     /// Create a `Status` from various types of `Error`.
     ///
-    /// Inspects the error source chain for recognizable errors, including statuses, HTTP2, and
-    /// hyper, and attempts to maps them to a `Status`, or else returns an Unknown `Status`.
+    /// Inspects the error source chain for recognizable errors, including statuses, HTTP2, hyper,
+    /// io and tower timeout and load shedding errors, and attempts to maps them to a `Status`, or
+    /// else returns an Unknown `Status`.
+    /// The original error is kept as the [source](Error::source) of the `Status`.
     #[cfg_attr(not(feature = "transport"), allow(dead_code))]
     pub fn from_error(err: Box<dyn Error + Send + Sync + 'static>) -> Status {
         Status::try_from_error(err).unwrap_or_else(|err| {
@@ -361,8 +363,18 @@ impl Status {
     // FIXME: bubble this into `transport` and expose generic http2 reasons.
     #[cfg(feature = "transport")]
     fn from_h2_error(err: Box<h2::Error>) -> Status {
+        let mut status = Self::from_h2_error_ref(&err);
+        status.source = Some(Arc::new(*err));
+        status
+    }
+
+    #[cfg(feature = "transport")]
+    fn from_h2_error_ref(err: &h2::Error) -> Status {
         // See https://github.com/grpc/grpc/blob/3977c30/doc/PROTOCOL-HTTP2.md#errors
         let code = match err.reason() {
+            // A stream that was never processed because the connection is
+            // going away can be retried on another connection.
+            Some(h2::Reason::NO_ERROR) if err.is_go_away() => Code::Unavailable,
             Some(h2::Reason::NO_ERROR)
             | Some(h2::Reason::PROTOCOL_ERROR)
             | Some(h2::Reason::INTERNAL_ERROR)
@@ -375,12 +387,13 @@ impl Status {
             Some(h2::Reason::ENHANCE_YOUR_CALM) => Code::ResourceExhausted,
             Some(h2::Reason::INADEQUATE_SECURITY) => Code::PermissionDenied,
 
-            _ => Code::Unknown,
+            _ => err
+                .get_io()
+                .and_then(io_error_code)
+                .unwrap_or(Code::Unknown),
         };
 
-        let mut status = Self::new(code, format!("h2 protocol error: {}", err));
-        status.source = Some(Arc::new(*err));
-        status
+        Self::new(code, format!("h2 protocol error: {}", err))
     }
 
     #[cfg(feature = "transport")]
@@ -493,6 +506,10 @@ impl Status {
     }
 
     /// Get a reference to the custom metadata.
+    ///
+    /// On the client this holds the headers or trailers that carried the
+    /// `grpc-status`, without the `grpc-status`, `grpc-message` and
+    /// `grpc-status-details-bin` entries themselves.
     pub fn metadata(&self) -> &MetadataMap {
         &self.metadata
     }
@@ -585,7 +602,7 @@ impl Status {
     }
 }
 
-fn find_status_in_source_chain(err: &(dyn Error + 'static)) -> Option<Status> {
+pub(crate) fn find_status_in_source_chain(err: &(dyn Error + 'static)) -> Option<Status> {
     let mut source = Some(err);
 
     while let Some(err) = source {
@@ -603,7 +620,17 @@ fn find_status_in_source_chain(err: &(dyn Error + 'static)) -> Option<Status> {
 
         #[cfg(feature = "transport")]
         if let Some(timeout) = err.downcast_ref::<crate::transport::TimeoutExpired>() {
-            return Some(Status::cancelled(timeout.to_string()));
+            return Some(Status::deadline_exceeded(timeout.to_string()));
+        }
+
+        #[cfg(feature = "transport")]
+        if let Some(elapsed) = err.downcast_ref::<tower::timeout::error::Elapsed>() {
+            return Some(Status::deadline_exceeded(elapsed.to_string()));
+        }
+
+        #[cfg(feature = "transport")]
+        if let Some(overloaded) = err.downcast_ref::<tower::load_shed::error::Overloaded>() {
+            return Some(Status::unavailable(overloaded.to_string()));
         }
 
         #[cfg(feature = "transport")]
@@ -614,12 +641,37 @@ fn find_status_in_source_chain(err: &(dyn Error + 'static)) -> Option<Status> {
             return Some(hyper);
         }
 
+        #[cfg(feature = "transport")]
+        if let Some(h2) = err.downcast_ref::<h2::Error>() {
+            return Some(Status::from_h2_error_ref(h2));
+        }
+
+        if let Some(code) = err.downcast_ref::<std::io::Error>().and_then(io_error_code) {
+            return Some(Status::new(code, err.to_string()));
+        }
+
         source = err.source();
     }
 
     None
 }
 
+// Maps the io errors of a broken connection, that are worth retrying, and of
+// timeouts.
+fn io_error_code(err: &std::io::Error) -> Option<Code> {
+    use std::io::ErrorKind;
+
+    match err.kind() {
+        ErrorKind::ConnectionRefused
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::NotConnected
+        | ErrorKind::BrokenPipe => Some(Code::Unavailable),
+        ErrorKind::TimedOut => Some(Code::DeadlineExceeded),
+        _ => None,
+    }
+}
+
 impl fmt::Debug for Status {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // A manual impl to reduce the noise of frequently empty fields.
@@ -736,6 +788,8 @@ pub(crate) fn infer_grpc_status(
         http::StatusCode::UNAUTHORIZED => Code::Unauthenticated,
         http::StatusCode::FORBIDDEN => Code::PermissionDenied,
         http::StatusCode::NOT_FOUND => Code::Unimplemented,
+        // Sent by the server when the request metadata exceeds its limit.
+        http::StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE => Code::ResourceExhausted,
         http::StatusCode::TOO_MANY_REQUESTS
         | http::StatusCode::BAD_GATEWAY
         | http::StatusCode::SERVICE_UNAVAILABLE
@@ -925,6 +979,59 @@ mod tests {
         assert_eq!(source.reason(), Some(h2::Reason::CANCEL));
     }
 
+    #[test]
+    #[cfg(feature = "transport")]
+    fn from_error_h2_nested() {
+        use std::error::Error as _;
+
+        let orig = Nested(Box::new(h2::Error::from(h2::Reason::REFUSED_STREAM)));
+        let found = Status::from_error(Box::new(orig));
+
+        assert_eq!(found.code(), Code::Unavailable);
+
+        let source = found
+            .source()
+            .and_then(|err| err.downcast_ref::<Nested>())
+            .and_then(|err| err.source())
+            .and_then(|err| err.downcast_ref::<h2::Error>())
+            .unwrap();
+        assert_eq!(source.reason(), Some(h2::Reason::REFUSED_STREAM));
+    }
+
+    #[test]
+    fn from_error_io() {
+        use std::error::Error as _;
+        use std::io::{Error, ErrorKind};
+
+        let cases = [
+            (ErrorKind::ConnectionReset, Code::Unavailable),
+            (ErrorKind::BrokenPipe, Code::Unavailable),
+            (ErrorKind::TimedOut, Code::DeadlineExceeded),
+            (ErrorKind::Other, Code::Unknown),
+        ];
+
+        for (kind, code) in cases {
+            let orig = Nested(Box::new(Error::new(kind, "io")));
+            let found = Status::from_error(Box::new(orig));
+
+            assert_eq!(found.code(), code, "{:?}", kind);
+            found
+                .source()
+                .and_then(|err| err.downcast_ref::<Nested>())
+                .unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "transport")]
+    fn from_error_tower() {
+        let found = Status::from_error(Box::new(tower::timeout::error::Elapsed::new()));
+        assert_eq!(found.code(), Code::DeadlineExceeded);
+
+        let found = Status::from_error(Box::new(tower::load_shed::error::Overloaded::new()));
+        assert_eq!(found.code(), Code::Unavailable);
+    }
+
     #[test]
     #[cfg(feature = "transport")]
     fn to_h2_error() {
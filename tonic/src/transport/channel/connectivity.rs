@@ -0,0 +1,20 @@
+/// The connectivity state of a [`Channel`](super::Channel).
+///
+/// These follow the [gRPC connectivity semantics]. A channel that balances across several
+/// endpoints is [`Ready`](ConnectivityState::Ready) as soon as one of them is, otherwise
+/// it reports the state closest to ready among them.
+///
+/// [gRPC connectivity semantics]: https://github.com/grpc/grpc/blob/master/doc/connectivity-semantics-and-api.md
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectivityState {
+    /// Not connected, and not trying to connect until a request is sent.
+    Idle,
+    /// Establishing a connection.
+    Connecting,
+    /// Connected and able to send requests.
+    Ready,
+    /// The last connection attempt failed, it is retried after a backoff.
+    TransientFailure,
+    /// The background task of the channel has stopped, no more requests can be sent.
+    Shutdown,
+}
@@ -0,0 +1,50 @@
+use super::super::BoxFuture;
+use http::Uri;
+use std::{
+    io,
+    task::{Context, Poll},
+};
+use tokio::{io::DuplexStream, sync::mpsc};
+use tower_service::Service;
+
+/// The capacity of each direction of an in-memory connection.
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// A connector for in-memory connections to a server in the same process.
+///
+/// It is created with [`MemoryIncoming::connector`](crate::transport::server::MemoryIncoming::connector)
+/// and used with [`Endpoint::connect_with_connector`](super::Endpoint::connect_with_connector),
+/// which ignores the uri of the endpoint. Connecting fails once the incoming stream has been
+/// dropped.
+#[derive(Debug, Clone)]
+pub struct MemoryConnector {
+    tx: mpsc::UnboundedSender<DuplexStream>,
+}
+
+impl MemoryConnector {
+    #[cfg(feature = "transport")]
+    pub(crate) fn new(tx: mpsc::UnboundedSender<DuplexStream>) -> Self {
+        Self { tx }
+    }
+}
+
+impl Service<Uri> for MemoryConnector {
+    type Response = DuplexStream;
+    type Error = io::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: Uri) -> Self::Future {
+        let (client, server) = tokio::io::duplex(BUFFER_SIZE);
+        let res = self
+            .tx
+            .send(server)
+            .map(|()| client)
+            .map_err(|_| io::Error::new(io::ErrorKind::ConnectionRefused, "server is gone"));
+
+        Box::pin(async move { res })
+    }
+}
@@ -1,44 +1,83 @@
 //! Client implementation and builder.
 
+mod connectivity;
 mod endpoint;
-#[cfg(feature = "tls")]
-#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+mod memory;
+#[cfg(windows)]
+mod named_pipe;
+mod outlier_detection;
+mod queue;
+mod resolver;
+mod retry;
+mod service_config;
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "tls", feature = "tls-native"))))]
 mod tls;
 
+pub use super::service::ResponseBody;
+pub use connectivity::ConnectivityState;
 pub use endpoint::Endpoint;
-#[cfg(feature = "tls")]
+pub use memory::MemoryConnector;
+#[cfg(windows)]
+pub use named_pipe::NamedPipeConnector;
+pub use outlier_detection::OutlierDetection;
+pub use resolver::Resolver;
+pub use retry::{HedgingPolicy, RetryPolicy};
+pub use service_config::ServiceConfig;
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 pub use tls::ClientTlsConfig;
 
-use super::service::{Connection, DynamicServiceStream, SharedExec};
+pub(crate) use self::queue::QueueSlot;
+use self::queue::{Queue, Ticket};
+use self::retry::Retry;
+pub(crate) use self::service_config::LoadBalancing;
+use super::service::{
+    self,
+    grpc_timeout::{self, GrpcTimeout},
+    Connection, Connectivity, DynamicServiceStream, PickFirst, Pool, RoundRobin, SharedConnector,
+    SharedExec,
+};
 use crate::body::BoxBody;
 use crate::transport::Executor;
+use crate::util::{OptionPin, OptionPinProj};
 use bytes::Bytes;
 use http::{
     uri::{InvalidUri, Uri},
     Request, Response,
 };
 use hyper::client::connect::Connection as HyperConnection;
+use pin_project::pin_project;
 use std::{
     fmt,
     future::Future,
     hash::Hash,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::{
     io::{AsyncRead, AsyncWrite},
-    sync::mpsc::{channel, Sender},
+    sync::{
+        mpsc::{channel, Sender},
+        watch, Notify,
+    },
 };
 
 use tower::balance::p2c::Balance;
 use tower::{
     buffer::{self, Buffer},
     discover::{Change, Discover},
-    util::{BoxService, Either},
+    util::{service_fn, BoxService, Either},
     Service,
 };
 
-type Svc = Either<Connection, BoxService<Request<BoxBody>, Response<hyper::Body>, crate::Error>>;
+type Svc = Either<Connection, BoxService<Request<BoxBody>, Response<ResponseBody>, crate::Error>>;
+
+type SendFuture = Either<
+    buffer::future::ResponseFuture<<Svc as Service<Request<BoxBody>>>::Future>,
+    retry::ResponseFuture,
+>;
 
 const DEFAULT_BUFFER_SIZE: usize = 1024;
 
@@ -65,16 +104,50 @@ const DEFAULT_BUFFER_SIZE: usize = 1024;
 /// the channel is backed by a `tower_buffer::Buffer` which runs the connection
 /// in a background task and provides a `mpsc` channel interface. Due to this
 /// cloning the `Channel` type is cheap and encouraged.
+///
+/// # Middleware
+///
+/// `Channel` is a [`Service`] of `http::Request<BoxBody>`, so it can be wrapped in `tower`
+/// middleware before it is passed to a generated client. Errors returned by the middleware
+/// are converted with [`Status::from_error`], a `tower` timeout for example fails the call
+/// with `DEADLINE_EXCEEDED`.
+///
+/// ```no_run
+/// # use tonic::transport::Channel;
+/// # use std::time::Duration;
+/// use tower::ServiceBuilder;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let channel = Channel::from_static("http://[::1]:50051").connect().await?;
+///
+/// let channel = ServiceBuilder::new()
+///     .concurrency_limit(64)
+///     .timeout(Duration::from_secs(10))
+///     .service(channel);
+///
+/// // let client = GreeterClient::new(channel);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Status::from_error`]: crate::Status::from_error
 #[derive(Clone)]
 pub struct Channel {
     svc: Buffer<Svc, Request<BoxBody>>,
+    state: watch::Receiver<ConnectivityState>,
+    retry: Arc<Retry>,
+    queue: Arc<Queue>,
 }
 
 /// A future that resolves to an HTTP response.
 ///
 /// This is returned by the `Service::call` on [`Channel`].
+#[pin_project]
 pub struct ResponseFuture {
-    inner: buffer::future::ResponseFuture<<Svc as Service<Request<BoxBody>>>::Future>,
+    /// The request, which is `None` when the queue was full.
+    #[pin]
+    inner: OptionPin<grpc_timeout::ResponseFuture<SendFuture>>,
+    _ticket: Option<Ticket>,
 }
 
 impl Channel {
@@ -108,10 +181,13 @@ impl Channel {
     /// Balance a list of [`Endpoint`]'s.
     ///
     /// This creates a [`Channel`] that will load balance across all the
-    /// provided endpoints.
+    /// provided endpoints. To add or remove endpoints at runtime use
+    /// [`Channel::balance_channel`] instead.
     pub fn balance_list(list: impl Iterator<Item = Endpoint>) -> Self {
-        let (channel, tx) = Self::balance_channel(DEFAULT_BUFFER_SIZE);
-        list.for_each(|endpoint| {
+        let list = list.collect::<Vec<_>>();
+        // All endpoints are sent before the channel is polled, so they must fit.
+        let (channel, tx) = Self::balance_channel(list.len().max(DEFAULT_BUFFER_SIZE));
+        list.into_iter().for_each(|endpoint| {
             tx.try_send(Change::Insert(endpoint.uri.clone(), endpoint))
                 .unwrap();
         });
@@ -119,6 +195,151 @@ impl Channel {
         channel
     }
 
+    /// Balance a list of [`Endpoint`]'s in round robin order.
+    ///
+    /// This creates a [`Channel`] that sends each request to the next ready endpoint in
+    /// turn. Endpoints that fail to connect are skipped until they reconnect, and when all
+    /// of them are failing requests fail with the connection error instead of waiting. This
+    /// matches the `round_robin` policy of other gRPC implementations. To add or remove
+    /// endpoints at runtime use [`Channel::round_robin_channel`] instead.
+    pub fn round_robin_list(list: impl Iterator<Item = Endpoint>) -> Self {
+        let list = list.collect::<Vec<_>>();
+        // All endpoints are sent before the channel is polled, so they must fit.
+        let (channel, tx) = Self::round_robin_channel(list.len().max(DEFAULT_BUFFER_SIZE));
+        list.into_iter().for_each(|endpoint| {
+            tx.try_send(Change::Insert(endpoint.uri.clone(), endpoint))
+                .unwrap();
+        });
+
+        channel
+    }
+
+    /// Balance [`Endpoint`]'s in round robin order.
+    ///
+    /// This creates a [`Channel`] like [`Channel::round_robin_list`] that will listen to a
+    /// stream of change events and will add or remove provided endpoints.
+    pub fn round_robin_channel<K>(capacity: usize) -> (Self, Sender<Change<K, Endpoint>>)
+    where
+        K: Hash + Eq + Send + Clone + 'static,
+    {
+        let (tx, rx) = channel(capacity);
+        let (connectivity, state) = Connectivity::new();
+        let list = DynamicServiceStream::fail_fast(rx, connectivity);
+        let svc = BoxService::new(RoundRobin::new(list));
+
+        (
+            Self::boxed(
+                svc,
+                state,
+                DEFAULT_BUFFER_SIZE,
+                SharedExec::tokio(),
+                Retry::transparent(),
+                Queue::unbounded(),
+            ),
+            tx,
+        )
+    }
+
+    /// Balance across every address the host of an [`Endpoint`] resolves to.
+    ///
+    /// This creates a [`Channel`] that resolves the host with the system resolver and
+    /// sends requests to the resulting addresses in round robin order, like
+    /// [`Channel::round_robin_list`]. The host is resolved again every `refresh_interval`
+    /// and whenever connecting to one of the addresses fails, so addresses that are added
+    /// or removed, for example by autoscaling, are picked up.
+    ///
+    /// Every address is connected to with the settings of `endpoint`. Requests keep the
+    /// host name as their origin unless [`Endpoint::origin`] is set.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use tonic::transport::{Channel, Endpoint};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let endpoint = Endpoint::from_static("http://my-service.default.svc:50051");
+    /// let channel = Channel::balance_dns(endpoint, Duration::from_secs(30));
+    /// # drop(channel);
+    /// # }
+    /// ```
+    pub fn balance_dns(endpoint: Endpoint, refresh_interval: Duration) -> Self {
+        let resolve_now = Arc::new(Notify::new());
+        let updates = service::dns(&endpoint.uri, refresh_interval, resolve_now.clone());
+
+        Self::resolved(endpoint, updates, Some(resolve_now))
+    }
+
+    /// Balance across the addresses a [`Resolver`] reports for an [`Endpoint`].
+    ///
+    /// This creates a [`Channel`] like [`Channel::balance_dns`] that finds its addresses
+    /// with `resolver` instead of the system resolver. The resolver is given the uri of
+    /// `endpoint` as its target, and every address is connected to with the settings of
+    /// `endpoint`. A service config reported by the resolver replaces the
+    /// [`service_config`](Endpoint::service_config) of `endpoint`.
+    pub fn balance_resolver<R>(mut endpoint: Endpoint, mut resolver: R) -> Self
+    where
+        R: Resolver,
+    {
+        let target = endpoint.uri.to_string();
+        if let Some(config) = resolver.service_config(&target) {
+            endpoint = endpoint.service_config(config);
+        }
+        let updates = resolver.resolve(&target);
+
+        Self::resolved(endpoint, updates, None)
+    }
+
+    /// Connect to the first reachable [`Endpoint`] of a list.
+    ///
+    /// This creates a [`Channel`] that tries the endpoints in order and sends all requests
+    /// to the first one it can connect to. When that connection is lost and cannot be made
+    /// again the endpoints are tried from the start, which makes it suited to primary and
+    /// backup deployments. When every endpoint is failing requests fail with the connection
+    /// error instead of waiting.
+    ///
+    /// Each endpoint is connected to with its own settings, such as its TLS config and
+    /// [`origin`](Endpoint::origin), over a single connection. The settings of the channel
+    /// itself, such as how requests are retried and queued, are the ones of the first
+    /// endpoint. Like [`Endpoint::connect_lazy`] no connection is made until the channel is
+    /// first used.
+    ///
+    /// Returns an error if `list` is empty.
+    pub fn pick_first(list: impl Iterator<Item = Endpoint>) -> Result<Self, super::Error> {
+        let list = list.collect::<Vec<_>>();
+        let first = list
+            .first()
+            .cloned()
+            .ok_or_else(super::Error::new_no_endpoints)?;
+
+        let (connectivity, state) = Connectivity::new();
+        let connections = list
+            .into_iter()
+            .map(|endpoint| {
+                let mut http = endpoint.http_connector();
+                http.set_connect_timeout(endpoint.connect_timeout);
+                let http = service::ProxyConnector::new(http, endpoint.proxy.clone());
+
+                #[cfg(any(feature = "tls", feature = "tls-native"))]
+                let connector = service::connector(http, endpoint.tls.clone());
+
+                #[cfg(not(any(feature = "tls", feature = "tls-native")))]
+                let connector = service::connector(http);
+                let connector = connector.h2c_upgrade(endpoint.h2c_upgrade);
+
+                Connection::fail_fast(connector, endpoint, connectivity.reporter())
+            })
+            .collect();
+        let svc = BoxService::new(PickFirst::new(connections));
+
+        Ok(Self::boxed(
+            svc,
+            state,
+            first.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE),
+            first.executor.clone(),
+            Retry::new(&first),
+            Queue::new(&first),
+        ))
+    }
+
     /// Balance a list of [`Endpoint`]'s.
     ///
     /// This creates a [`Channel`] that will listen to a stream of change events and will add or remove provided endpoints.
@@ -143,8 +364,12 @@ impl Channel {
         E: Executor<Pin<Box<dyn Future<Output = ()> + Send>>> + Send + Sync + 'static,
     {
         let (tx, rx) = channel(capacity);
-        let list = DynamicServiceStream::new(rx);
-        (Self::balance(list, DEFAULT_BUFFER_SIZE, executor), tx)
+        let (connectivity, state) = Connectivity::new();
+        let list = DynamicServiceStream::new(rx, connectivity);
+        (
+            Self::balance(list, state, DEFAULT_BUFFER_SIZE, executor),
+            tx,
+        )
     }
 
     pub(crate) fn new<C>(connector: C, endpoint: Endpoint) -> Self
@@ -156,12 +381,39 @@ impl Channel {
     {
         let buffer_size = endpoint.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE);
         let executor = endpoint.executor.clone();
-
-        let svc = Connection::lazy(connector, endpoint);
-        let (svc, worker) = Buffer::pair(Either::A(svc), buffer_size);
+        let retry = Retry::new(&endpoint);
+        let queue = Queue::new(&endpoint);
+
+        let (connectivity, state) = Connectivity::new();
+        let svc = if endpoint.pooled() {
+            let connector = SharedConnector::new(connector);
+            let connections = (0..endpoint.connections)
+                .map(|_| {
+                    Connection::lazy(connector.clone(), endpoint.clone(), connectivity.reporter())
+                })
+                .collect();
+            Either::B(BoxService::new(Self::pool(
+                connections,
+                connector,
+                endpoint,
+                connectivity,
+            )))
+        } else {
+            Either::A(Connection::lazy(
+                connector,
+                endpoint,
+                connectivity.reporter(),
+            ))
+        };
+        let (svc, worker) = Buffer::pair(svc, buffer_size);
         executor.execute(Box::pin(worker));
 
-        Channel { svc }
+        Channel {
+            svc,
+            state,
+            retry,
+            queue,
+        }
     }
 
     pub(crate) async fn connect<C>(connector: C, endpoint: Endpoint) -> Result<Self, super::Error>
@@ -173,17 +425,72 @@ impl Channel {
     {
         let buffer_size = endpoint.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE);
         let executor = endpoint.executor.clone();
-
-        let svc = Connection::connect(connector, endpoint)
-            .await
-            .map_err(super::Error::from_source)?;
-        let (svc, worker) = Buffer::pair(Either::A(svc), buffer_size);
+        let retry = Retry::new(&endpoint);
+        let queue = Queue::new(&endpoint);
+
+        let (connectivity, state) = Connectivity::new();
+        let svc = if endpoint.pooled() {
+            let connector = SharedConnector::new(connector);
+            let mut connections = Vec::with_capacity(endpoint.connections);
+            for _ in 0..endpoint.connections {
+                let svc = Connection::connect(
+                    connector.clone(),
+                    endpoint.clone(),
+                    connectivity.reporter(),
+                )
+                .await
+                .map_err(super::Error::from_source)?;
+                connections.push(svc);
+            }
+            Either::B(BoxService::new(Self::pool(
+                connections,
+                connector,
+                endpoint,
+                connectivity,
+            )))
+        } else {
+            let svc = Connection::connect(connector, endpoint, connectivity.reporter())
+                .await
+                .map_err(super::Error::from_source)?;
+            Either::A(svc)
+        };
+        let (svc, worker) = Buffer::pair(svc, buffer_size);
         executor.execute(Box::pin(worker));
 
-        Ok(Channel { svc })
+        Ok(Channel {
+            svc,
+            state,
+            retry,
+            queue,
+        })
     }
 
-    pub(crate) fn balance<D, E>(discover: D, buffer_size: usize, executor: E) -> Self
+    fn pool<C>(
+        connections: Vec<Connection>,
+        connector: SharedConnector<C>,
+        endpoint: Endpoint,
+        connectivity: Connectivity,
+    ) -> Pool
+    where
+        C: Service<Uri> + Send + 'static,
+        C::Error: Into<crate::Error> + Send,
+        C::Future: Unpin + Send,
+        C::Response: AsyncRead + AsyncWrite + HyperConnection + Unpin + Send + 'static,
+    {
+        let max_streams = endpoint.max_concurrent_streams;
+        let max_connections = endpoint.max_connections;
+
+        Pool::new(connections, max_streams, max_connections, move || {
+            Connection::lazy(connector.clone(), endpoint.clone(), connectivity.reporter())
+        })
+    }
+
+    pub(crate) fn balance<D, E>(
+        discover: D,
+        state: watch::Receiver<ConnectivityState>,
+        buffer_size: usize,
+        executor: E,
+    ) -> Self
     where
         D: Discover<Service = Connection> + Unpin + Send + 'static,
         D::Error: Into<crate::Error>,
@@ -192,16 +499,129 @@ impl Channel {
     {
         let svc = Balance::new(discover);
 
-        let svc = BoxService::new(svc);
+        Self::boxed(
+            BoxService::new(svc),
+            state,
+            buffer_size,
+            executor,
+            Retry::transparent(),
+            Queue::unbounded(),
+        )
+    }
+
+    fn resolved<S, E>(endpoint: Endpoint, updates: S, resolve_now: Option<Arc<Notify>>) -> Self
+    where
+        S: tokio_stream::Stream<Item = Result<Vec<std::net::SocketAddr>, E>> + Send + 'static,
+        E: Into<crate::Error> + 'static,
+    {
+        let (tx, rx) = channel(DEFAULT_BUFFER_SIZE);
+        let (connectivity, state) = Connectivity::new();
+        let mut list = DynamicServiceStream::fail_fast(rx, connectivity);
+        if let Some(resolve_now) = resolve_now {
+            list = list.on_connect_error(resolve_now);
+        }
+        let load_balancing = endpoint
+            .service_config
+            .as_ref()
+            .and_then(|config| config.load_balancing());
+        let svc = match load_balancing {
+            Some(LoadBalancing::PickFirst) => RoundRobin::pick_first(list),
+            Some(LoadBalancing::RoundRobin) | None => RoundRobin::new(list),
+        };
+        let svc = BoxService::new(svc.outlier_detection(endpoint.outlier_detection.clone()));
+
+        let executor = endpoint.executor.clone();
+        let retry = Retry::new(&endpoint);
+        let queue = Queue::new(&endpoint);
+        executor.execute(Box::pin(service::resolve(endpoint, updates, tx)));
+
+        Self::boxed(svc, state, DEFAULT_BUFFER_SIZE, executor, retry, queue)
+    }
+
+    fn boxed<E>(
+        svc: BoxService<Request<BoxBody>, Response<ResponseBody>, crate::Error>,
+        state: watch::Receiver<ConnectivityState>,
+        buffer_size: usize,
+        executor: E,
+        retry: Arc<Retry>,
+        queue: Arc<Queue>,
+    ) -> Self
+    where
+        E: Executor<futures_core::future::BoxFuture<'static, ()>> + Send + Sync + 'static,
+    {
         let (svc, worker) = Buffer::pair(Either::B(svc), buffer_size);
         executor.execute(Box::pin(worker));
 
-        Channel { svc }
+        Channel {
+            svc,
+            state,
+            retry,
+            queue,
+        }
+    }
+
+    /// Returns the current [`ConnectivityState`] of the channel.
+    ///
+    /// A channel does not connect until it is used, unless it was created with
+    /// [`Endpoint::connect`], so a new channel may stay [`Idle`](ConnectivityState::Idle)
+    /// until the first request is sent.
+    pub fn state(&self) -> ConnectivityState {
+        if self.state.has_changed().is_err() {
+            return ConnectivityState::Shutdown;
+        }
+
+        *self.state.borrow()
+    }
+
+    /// Returns the number of requests that wait to be sent on a connection.
+    ///
+    /// Requests wait while the channel connects, and while every connection is at its
+    /// [`max_concurrent_streams`](Endpoint::max_concurrent_streams) and no other connection
+    /// may be opened. Clones of a channel share their queue. Requests are only counted when
+    /// the channel has a [`max_queued_requests`](Endpoint::max_queued_requests) limit.
+    pub fn queued_requests(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Waits until the connectivity state of the channel is no longer `last_observed` and
+    /// returns the new state.
+    ///
+    /// ```no_run
+    /// # use tonic::transport::{channel::ConnectivityState, Endpoint};
+    /// # async fn wait(endpoint: Endpoint) {
+    /// let channel = endpoint.connect_lazy();
+    ///
+    /// let mut state = channel.state();
+    /// while state != ConnectivityState::Ready {
+    ///     state = channel.wait_for_state_change(state).await;
+    /// }
+    /// # }
+    /// ```
+    pub async fn wait_for_state_change(
+        &self,
+        last_observed: ConnectivityState,
+    ) -> ConnectivityState {
+        let mut state = self.state.clone();
+
+        loop {
+            if state.has_changed().is_err() {
+                return ConnectivityState::Shutdown;
+            }
+
+            let current = *state.borrow_and_update();
+            if current != last_observed {
+                return current;
+            }
+
+            if state.changed().await.is_err() {
+                return ConnectivityState::Shutdown;
+            }
+        }
     }
 }
 
 impl Service<http::Request<BoxBody>> for Channel {
-    type Response = http::Response<super::Body>;
+    type Response = http::Response<ResponseBody>;
     type Error = super::Error;
     type Future = ResponseFuture;
 
@@ -209,20 +629,53 @@ impl Service<http::Request<BoxBody>> for Channel {
         Service::poll_ready(&mut self.svc, cx).map_err(super::Error::from_source)
     }
 
-    fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
-        let inner = Service::call(&mut self.svc, request);
+    fn call(&mut self, mut request: http::Request<BoxBody>) -> Self::Future {
+        let ticket = match self.queue.enqueue(&mut request) {
+            Some(ticket) => ticket,
+            None => {
+                return ResponseFuture {
+                    inner: OptionPin::None,
+                    _ticket: None,
+                }
+            }
+        };
+
+        // The timeout the connection enforces starts once the request left the queue, so it
+        // is also enforced here to count the time the request waits.
+        let timeout = self.queue.timeout();
+        let send = service_fn(|mut request| match self.retry.configure(&mut request) {
+            Some(limits) => Either::B(limits.call(request, |request| self.send(request))),
+            None => self.send(request),
+        });
+        let inner = GrpcTimeout::new_client(send, timeout).call(request);
+
+        ResponseFuture {
+            inner: OptionPin::Some(inner),
+            _ticket: Some(ticket),
+        }
+    }
+}
 
-        ResponseFuture { inner }
+impl Channel {
+    fn send(&mut self, request: http::Request<BoxBody>) -> SendFuture {
+        match self.retry.policy(&request) {
+            Some(policy) => Either::B(self.retry.call(policy, &mut self.svc, request)),
+            None => Either::A(Service::call(&mut self.svc, request)),
+        }
     }
 }
 
 impl Future for ResponseFuture {
-    type Output = Result<Response<hyper::Body>, super::Error>;
-
-    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let val = futures_util::ready!(Pin::new(&mut self.inner).poll(cx))
-            .map_err(super::Error::from_source)?;
-        Ok(val).into()
+    type Output = Result<Response<ResponseBody>, super::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().inner.project() {
+            OptionPinProj::Some(inner) => inner.poll(cx).map_err(super::Error::from_source),
+            OptionPinProj::None => {
+                let status = crate::Status::unavailable("too many requests queued");
+                Poll::Ready(Err(super::Error::from_source(status)))
+            }
+        }
     }
 }
 
@@ -0,0 +1,84 @@
+use super::super::BoxFuture;
+use http::Uri;
+use std::{
+    ffi::OsString,
+    fmt, io,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::net::windows::named_pipe::{ClientOptions, NamedPipeClient};
+use tower_service::Service;
+
+/// `ERROR_PIPE_BUSY`, returned while every instance of the pipe is in use.
+const ERROR_PIPE_BUSY: i32 = 231;
+
+/// How long to wait before retrying to open a busy pipe.
+const BUSY_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// A connector for Windows named pipes.
+///
+/// It opens the pipe it was created with for every connection, ignoring the uri the
+/// [`Endpoint`](super::Endpoint) was created with, and is used with
+/// [`Endpoint::connect_with_connector`](super::Endpoint::connect_with_connector):
+///
+/// ```no_run
+/// # async fn connect() -> Result<(), tonic::transport::Error> {
+/// use tonic::transport::{channel::NamedPipeConnector, Endpoint};
+///
+/// let channel = Endpoint::from_static("http://localhost")
+///     .connect_with_connector(NamedPipeConnector::new(r"\\.\pipe\tonic"))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// A server accepts connections on the pipe with
+/// [`NamedPipeIncoming`](crate::transport::server::NamedPipeIncoming).
+#[cfg_attr(docsrs, doc(cfg(windows)))]
+#[derive(Clone)]
+pub struct NamedPipeConnector {
+    path: Arc<OsString>,
+}
+
+impl NamedPipeConnector {
+    /// Creates a connector for the pipe at `path`, such as `\\.\pipe\tonic`.
+    pub fn new(path: impl Into<OsString>) -> Self {
+        Self {
+            path: Arc::new(path.into()),
+        }
+    }
+}
+
+impl fmt::Debug for NamedPipeConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedPipeConnector")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl Service<Uri> for NamedPipeConnector {
+    type Response = NamedPipeClient;
+    type Error = io::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: Uri) -> Self::Future {
+        let path = self.path.clone();
+
+        Box::pin(async move {
+            loop {
+                match ClientOptions::new().open(path.as_os_str()) {
+                    Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => {}
+                    res => return res,
+                }
+
+                tokio::time::sleep(BUSY_RETRY_DELAY).await;
+            }
+        })
+    }
+}
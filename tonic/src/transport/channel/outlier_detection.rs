@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+/// Passive outlier detection, which takes the endpoints that fail too many calls out of the
+/// rotation of a balanced channel for a while.
+///
+/// An endpoint is ejected once it fails
+/// [`consecutive_failures`](OutlierDetection::consecutive_failures) calls in a row, or when
+/// enough of its calls failed during the last [`interval`](OutlierDetection::interval) to
+/// exceed its [`failure_percentage`](OutlierDetection::failure_percentage). Ejected endpoints
+/// are skipped like endpoints that fail to connect. They are put back after the base ejection
+/// time multiplied by the number of times they were ejected, which goes down again for every
+/// interval they are not ejected, up to the max ejection time. No endpoint is ejected while
+/// that would eject more than the max ejection percentage of them.
+///
+/// A call fails when the channel cannot send it, or when its response starts with a status
+/// other than `OK`, which is how servers answer the calls that fail before any message was
+/// sent. Calls that fail after the server started sending messages count as successes.
+///
+/// See the [gRPC outlier detection design] for details.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use tonic::transport::{channel::OutlierDetection, Endpoint};
+/// let endpoint = Endpoint::from_static("http://my-service.default.svc:50051").outlier_detection(
+///     OutlierDetection::new()
+///         .consecutive_failures(3)
+///         .failure_percentage(50, 20)
+///         .base_ejection_time(Duration::from_secs(10)),
+/// );
+/// ```
+///
+/// [gRPC outlier detection design]: https://github.com/grpc/proposal/blob/master/A50-xds-outlier-detection.md
+#[derive(Debug, Clone)]
+pub struct OutlierDetection {
+    pub(crate) consecutive_failures: u32,
+    /// The percentage of failed calls an endpoint is ejected at, and the number of calls it
+    /// must have made for it to be.
+    pub(crate) failure_percentage: Option<(u32, u64)>,
+    pub(crate) interval: Duration,
+    pub(crate) base_ejection_time: Duration,
+    pub(crate) max_ejection_time: Duration,
+    pub(crate) max_ejection_percent: u32,
+}
+
+impl OutlierDetection {
+    /// Creates an outlier detection that ejects endpoints after 5 consecutive failures.
+    ///
+    /// Endpoints are ejected for 30 seconds at first and for at most 5 minutes, stats are
+    /// gathered over intervals of 10 seconds, and at most 10% of the endpoints are ejected
+    /// at once.
+    pub fn new() -> Self {
+        Self {
+            consecutive_failures: 5,
+            failure_percentage: None,
+            interval: Duration::from_secs(10),
+            base_ejection_time: Duration::from_secs(30),
+            max_ejection_time: Duration::from_secs(300),
+            max_ejection_percent: 10,
+        }
+    }
+
+    /// Sets the number of calls in a row an endpoint must fail to be ejected, where 0 turns
+    /// ejections for consecutive failures off.
+    pub fn consecutive_failures(self, consecutive_failures: u32) -> Self {
+        Self {
+            consecutive_failures,
+            ..self
+        }
+    }
+
+    /// Ejects the endpoints that failed at least `threshold` percent of their calls during
+    /// an interval, if they made at least `request_volume` calls during it.
+    pub fn failure_percentage(self, threshold: u32, request_volume: u64) -> Self {
+        Self {
+            failure_percentage: Some((threshold.min(100), request_volume)),
+            ..self
+        }
+    }
+
+    /// Sets the interval the failure percentage of the endpoints is computed over.
+    pub fn interval(self, interval: Duration) -> Self {
+        Self { interval, ..self }
+    }
+
+    /// Sets how long endpoints are ejected for the first time.
+    pub fn base_ejection_time(self, base_ejection_time: Duration) -> Self {
+        Self {
+            base_ejection_time,
+            ..self
+        }
+    }
+
+    /// Sets the longest time endpoints are ejected for, unless the base ejection time is
+    /// longer.
+    pub fn max_ejection_time(self, max_ejection_time: Duration) -> Self {
+        Self {
+            max_ejection_time,
+            ..self
+        }
+    }
+
+    /// Sets the percentage of the endpoints that may be ejected at once.
+    pub fn max_ejection_percent(self, max_ejection_percent: u32) -> Self {
+        Self {
+            max_ejection_percent: max_ejection_percent.min(100),
+            ..self
+        }
+    }
+
+    /// How long an endpoint is ejected for, when it is ejected for the `ejections`th time.
+    pub(crate) fn ejection_time(&self, ejections: u32) -> Duration {
+        let max = self.max_ejection_time.max(self.base_ejection_time);
+
+        self.base_ejection_time
+            .checked_mul(ejections)
+            .map_or(max, |time| time.min(max))
+    }
+}
+
+impl Default for OutlierDetection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ejection_time_grows_up_to_the_max() {
+        let detection = OutlierDetection::new()
+            .base_ejection_time(Duration::from_secs(30))
+            .max_ejection_time(Duration::from_secs(100));
+
+        assert_eq!(detection.ejection_time(1), Duration::from_secs(30));
+        assert_eq!(detection.ejection_time(3), Duration::from_secs(90));
+        assert_eq!(detection.ejection_time(4), Duration::from_secs(100));
+        assert_eq!(detection.ejection_time(u32::MAX), Duration::from_secs(100));
+
+        let detection = detection.max_ejection_time(Duration::from_secs(1));
+        assert_eq!(detection.ejection_time(2), Duration::from_secs(30));
+    }
+}
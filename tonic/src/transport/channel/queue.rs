@@ -0,0 +1,127 @@
+use super::Endpoint;
+use http::Request;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Weak,
+    },
+    time::Duration,
+};
+
+/// The requests of a channel that wait to be sent on a connection.
+///
+/// Requests are queued in the order they are made, from the call on the channel until a
+/// connection takes them, which includes the time it takes to connect and the time spent
+/// waiting for a stream when the connections are at their
+/// [`max_concurrent_streams`](Endpoint::max_concurrent_streams).
+///
+/// Requests are only counted when the queue has a limit.
+#[derive(Debug, Default)]
+pub(crate) struct Queue {
+    queued: AtomicUsize,
+    max_queued: Option<usize>,
+    timeout: Option<Duration>,
+}
+
+impl Queue {
+    pub(crate) fn new(endpoint: &Endpoint) -> Arc<Self> {
+        Arc::new(Self {
+            queued: AtomicUsize::new(0),
+            max_queued: endpoint.max_queued_requests,
+            timeout: endpoint.timeout,
+        })
+    }
+
+    pub(crate) fn unbounded() -> Arc<Self> {
+        Arc::default()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.queued.load(Ordering::Acquire)
+    }
+
+    /// The timeout of requests, which starts counting when they are queued.
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Queues `request`, unless the queue is full.
+    ///
+    /// The request carries its place in the queue until a connection takes it with
+    /// [`QueueSlot::dispatch`], or until the returned ticket is dropped. A queue without a
+    /// limit does not count requests and hands out empty tickets.
+    pub(crate) fn enqueue<B>(self: &Arc<Self>, request: &mut Request<B>) -> Option<Ticket> {
+        let max_queued = match self.max_queued {
+            Some(max_queued) => max_queued,
+            None => return Some(Ticket(None)),
+        };
+
+        let mut queued = self.queued.load(Ordering::Relaxed);
+        loop {
+            if queued >= max_queued {
+                return None;
+            }
+
+            match self.queued.compare_exchange_weak(
+                queued,
+                queued + 1,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(actual) => queued = actual,
+            }
+        }
+
+        let slot = Arc::new(Slot {
+            queue: self.clone(),
+            queued: AtomicBool::new(true),
+        });
+        let ticket = Ticket(Some(Arc::downgrade(&slot)));
+        request.extensions_mut().insert(QueueSlot(slot));
+
+        Some(ticket)
+    }
+}
+
+struct Slot {
+    queue: Arc<Queue>,
+    queued: AtomicBool,
+}
+
+impl Slot {
+    fn leave(&self) {
+        if self.queued.swap(false, Ordering::AcqRel) {
+            self.queue.queued.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.leave();
+    }
+}
+
+/// The place of a request in the queue of its channel, which it leaves when it is sent or
+/// dropped.
+pub(crate) struct QueueSlot(Arc<Slot>);
+
+impl QueueSlot {
+    /// Takes a request out of the queue to send it.
+    pub(crate) fn dispatch(self) {
+        self.0.leave();
+    }
+}
+
+/// Takes a request out of the queue when its call is dropped, even though the channel only
+/// drops the request itself once it gets to it.
+pub(crate) struct Ticket(Option<Weak<Slot>>);
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        if let Some(slot) = self.0.as_ref().and_then(Weak::upgrade) {
+            slot.leave();
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use super::ServiceConfig;
+use std::{error::Error, net::SocketAddr};
+use tokio_stream::Stream;
+
+/// Resolves a target to the addresses of the servers behind it.
+///
+/// A resolver lets a [`Channel`](super::Channel) find its servers through a service
+/// discovery system such as Consul, Kubernetes endpoints or etcd. Pass it to
+/// [`Channel::balance_resolver`](super::Channel::balance_resolver) to balance requests
+/// across the addresses it reports.
+///
+/// ```
+/// use std::{convert::Infallible, net::SocketAddr};
+/// use tonic::transport::channel::Resolver;
+///
+/// struct Static(Vec<SocketAddr>);
+///
+/// impl Resolver for Static {
+///     type Error = Infallible;
+///     type Stream = tokio_stream::Once<Result<Vec<SocketAddr>, Infallible>>;
+///
+///     fn resolve(&mut self, _target: &str) -> Self::Stream {
+///         tokio_stream::once(Ok(self.0.clone()))
+///     }
+/// }
+/// ```
+pub trait Resolver {
+    /// Errors produced while resolving.
+    type Error: Into<Box<dyn Error + Send + Sync>> + 'static;
+
+    /// The stream of address updates.
+    type Stream: Stream<Item = Result<Vec<SocketAddr>, Self::Error>> + Send + 'static;
+
+    /// Start resolving `target`, the uri of the endpoint the channel was created with.
+    ///
+    /// Every item is the complete list of addresses, which replaces the previous one.
+    /// Errors and empty lists are logged and the previous addresses are kept, as they are
+    /// once the stream ends.
+    fn resolve(&mut self, target: &str) -> Self::Stream;
+
+    /// Returns the service config of `target`, if the resolver knows one.
+    ///
+    /// This is called once when the channel is created, before [`resolve`](Resolver::resolve),
+    /// and the config replaces the one set with
+    /// [`Endpoint::service_config`](super::Endpoint::service_config). By default resolvers have
+    /// no service config.
+    fn service_config(&mut self, target: &str) -> Option<ServiceConfig> {
+        let _ = target;
+        None
+    }
+}
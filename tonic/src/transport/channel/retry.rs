@@ -0,0 +1,1198 @@
+use super::service_config::{MessageLimits, ServiceConfig};
+use super::{Endpoint, ResponseBody, Svc};
+use crate::body::BoxBody;
+use crate::metadata::GRPC_TIMEOUT_HEADER;
+use crate::request::duration_to_grpc_timeout;
+use crate::request::WaitForReady;
+use crate::status::find_status_in_source_chain;
+use crate::transport::service::{
+    backoff::random, grpc_timeout::try_parse_grpc_timeout, ConnectError,
+};
+use crate::transport::TimeoutExpired;
+use crate::{Code, Status};
+use bytes::Bytes;
+use futures_core::future::BoxFuture;
+use futures_util::{future, stream::FuturesUnordered, StreamExt};
+use http::{HeaderMap, HeaderValue, Method, Request, Response, Uri, Version};
+use http_body::Body as _;
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+use tower::{buffer::Buffer, Service, ServiceExt};
+
+const GRPC_STATUS_HEADER: &str = "grpc-status";
+const PUSHBACK_HEADER: &str = "grpc-retry-pushback-ms";
+const PREVIOUS_ATTEMPTS_HEADER: &str = "grpc-previous-rpc-attempts";
+
+/// How many bytes of a request body are kept to be sent again. Requests with a larger body
+/// are not retried or hedged once that much of it has been sent.
+const MAX_REPLAY_SIZE: usize = 256 * 1024;
+
+/// How many bytes of a request body are kept for calls that are only sent again when they
+/// never reached the server, which are most calls, so they hold on to less memory.
+const MAX_TRANSPARENT_REPLAY_SIZE: usize = 16 * 1024;
+
+/// Automatic retries of failed calls, like the `retryPolicy` of a gRPC service config.
+///
+/// A call is retried when it fails with one of the
+/// [`retryable_status_codes`](RetryPolicy::retryable_status_codes) before the server sent any
+/// response headers, so only responses that carry nothing but a status are retried and
+/// streaming responses are never retried halfway through. Retries are made after a random
+/// delay of up to the current backoff, which grows exponentially after every attempt, unless
+/// the server asks for a specific delay with the `grpc-retry-pushback-ms` header. A negative
+/// or invalid pushback stops the retries.
+///
+/// The timeout of the call, from the `grpc-timeout` header or [`Endpoint::timeout`], covers
+/// all of its attempts, and no retry is made that would start after it runs out.
+///
+/// See the [gRPC retry design] for details.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use tonic::{transport::{channel::RetryPolicy, Endpoint}, Code};
+/// let endpoint = Endpoint::from_static("http://[::1]:50051")
+///     .retry_policy(RetryPolicy::new(3))
+///     .method_retry_policy(
+///         "helloworld.Greeter/SayHello",
+///         RetryPolicy::new(5)
+///             .initial_backoff(Duration::from_millis(50))
+///             .retryable_status_codes([Code::Unavailable, Code::ResourceExhausted]),
+///     );
+/// ```
+///
+/// [`Endpoint::timeout`]: super::Endpoint::timeout
+/// [gRPC retry design]: https://github.com/grpc/proposal/blob/master/A6-client-retries.md
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    backoff_multiplier: f64,
+    retryable_status_codes: Vec<Code>,
+}
+
+impl RetryPolicy {
+    /// Creates a policy that makes up to `max_attempts` attempts of a call, counting the
+    /// first one.
+    ///
+    /// Calls are retried when they fail with `UNAVAILABLE`, with a backoff that starts at
+    /// 100 milliseconds and doubles after every attempt, up to 1 second.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            backoff_multiplier: 2.0,
+            retryable_status_codes: vec![Code::Unavailable],
+        }
+    }
+
+    /// Sets the backoff before the first retry.
+    pub fn initial_backoff(self, initial_backoff: Duration) -> Self {
+        Self {
+            initial_backoff,
+            ..self
+        }
+    }
+
+    /// Sets the largest backoff between retries.
+    pub fn max_backoff(self, max_backoff: Duration) -> Self {
+        Self {
+            max_backoff,
+            ..self
+        }
+    }
+
+    /// Sets the factor the backoff grows by after every retry.
+    pub fn backoff_multiplier(self, backoff_multiplier: f64) -> Self {
+        Self {
+            backoff_multiplier,
+            ..self
+        }
+    }
+
+    /// Sets the status codes that calls are retried on.
+    ///
+    /// Errors of the channel itself, such as failing to connect, are retried if the code
+    /// they map to with [`Status::from_error`] is one of these.
+    pub fn retryable_status_codes(self, codes: impl IntoIterator<Item = Code>) -> Self {
+        Self {
+            retryable_status_codes: codes.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// The delay before a retry, after `retries` earlier retries that were not pushed back.
+    fn backoff(&self, retries: u32) -> Duration {
+        let exponent = i32::try_from(retries).unwrap_or(i32::MAX);
+        let max = self.max_backoff.as_secs_f64();
+        let backoff =
+            (self.initial_backoff.as_secs_f64() * self.backoff_multiplier.powi(exponent)).min(max);
+
+        if backoff.is_finite() {
+            Duration::from_secs_f64(backoff.max(0.0) * random())
+        } else {
+            self.max_backoff
+        }
+    }
+}
+
+/// Hedging of calls, like the `hedgingPolicy` of a gRPC service config.
+///
+/// The first attempt of a call is sent right away, and another one is sent after every
+/// [`hedging_delay`](HedgingPolicy::hedging_delay) until the call has a response or
+/// `max_attempts` attempts are in flight. The first attempt to get a response that is not a
+/// failure with one of the [`non_fatal_status_codes`](HedgingPolicy::non_fatal_status_codes)
+/// is the response of the call, and the other attempts are cancelled. When an attempt
+/// fails with a non-fatal code, the next attempt is sent immediately, unless the server asks
+/// for a specific delay with the `grpc-retry-pushback-ms` header. A negative or invalid
+/// pushback stops sending attempts.
+///
+/// As every attempt may be processed by the server, hedging should only be used for
+/// idempotent methods. A hedged attempt is only sent once the whole request body has been
+/// sent by the first one, so hedging is meant for unary and client streaming calls. The
+/// timeout of the call, from the `grpc-timeout` header or [`Endpoint::timeout`], covers all
+/// of its attempts.
+///
+/// See the [gRPC retry design] for details.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use tonic::transport::{channel::HedgingPolicy, Endpoint};
+/// let endpoint = Endpoint::from_static("http://[::1]:50051").method_hedging_policy(
+///     "helloworld.Greeter/SayHello",
+///     HedgingPolicy::new(3, Duration::from_millis(20)),
+/// );
+/// ```
+///
+/// [`Endpoint::timeout`]: super::Endpoint::timeout
+/// [gRPC retry design]: https://github.com/grpc/proposal/blob/master/A6-client-retries.md
+#[derive(Debug, Clone)]
+pub struct HedgingPolicy {
+    max_attempts: u32,
+    hedging_delay: Duration,
+    non_fatal_status_codes: Vec<Code>,
+}
+
+impl HedgingPolicy {
+    /// Creates a policy that sends up to `max_attempts` attempts of a call, counting the
+    /// first one, one every `hedging_delay`.
+    ///
+    /// Attempts that fail with `UNAVAILABLE` are not fatal to the call.
+    pub fn new(max_attempts: u32, hedging_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            hedging_delay,
+            non_fatal_status_codes: vec![Code::Unavailable],
+        }
+    }
+
+    /// Sets the delay between sending two attempts.
+    ///
+    /// A zero delay sends all the attempts at once.
+    pub fn hedging_delay(self, hedging_delay: Duration) -> Self {
+        Self {
+            hedging_delay,
+            ..self
+        }
+    }
+
+    /// Sets the status codes that attempts can fail with while waiting for the other ones.
+    ///
+    /// Errors of the channel itself, such as failing to connect, are not fatal if the code
+    /// they map to with [`Status::from_error`] is one of these.
+    pub fn non_fatal_status_codes(self, codes: impl IntoIterator<Item = Code>) -> Self {
+        Self {
+            non_fatal_status_codes: codes.into_iter().collect(),
+            ..self
+        }
+    }
+}
+
+/// The policy a method retries or hedges its calls with.
+#[derive(Debug, Clone)]
+pub(crate) enum Policy {
+    Retry(Arc<RetryPolicy>),
+    Hedging(Arc<HedgingPolicy>),
+}
+
+impl Policy {
+    /// Returns how many bytes of a request body are kept to send it again.
+    fn max_replay_size(&self) -> usize {
+        let max_attempts = match self {
+            Policy::Retry(policy) => policy.max_attempts,
+            Policy::Hedging(policy) => policy.max_attempts,
+        };
+        if max_attempts > 1 {
+            MAX_REPLAY_SIZE
+        } else {
+            MAX_TRANSPARENT_REPLAY_SIZE
+        }
+    }
+}
+
+impl From<RetryPolicy> for Policy {
+    fn from(policy: RetryPolicy) -> Self {
+        Policy::Retry(Arc::new(policy))
+    }
+}
+
+impl From<HedgingPolicy> for Policy {
+    fn from(policy: HedgingPolicy) -> Self {
+        Policy::Hedging(Arc::new(policy))
+    }
+}
+
+/// Settings of a channel, such as retry policies, by method.
+#[derive(Debug, Clone)]
+pub(crate) struct Methods<T> {
+    default: Option<T>,
+    /// Settings for either a `package.Service` or a `package.Service/Method`.
+    methods: HashMap<String, T>,
+}
+
+/// The retry and hedging policies of a channel, by method.
+pub(crate) type RetryPolicies = Methods<Policy>;
+
+impl<T> Default for Methods<T> {
+    fn default() -> Self {
+        Self {
+            default: None,
+            methods: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Methods<T> {
+    pub(crate) fn set_default(&mut self, value: impl Into<T>) {
+        self.default = Some(value.into());
+    }
+
+    pub(crate) fn insert(&mut self, name: String, value: impl Into<T>) {
+        self.methods
+            .insert(name.trim_matches('/').to_string(), value.into());
+    }
+
+    /// Returns the settings for the method at `path`, such as `/helloworld.Greeter/SayHello`.
+    pub(crate) fn get(&self, path: &str) -> Option<&T> {
+        let method = path.trim_start_matches('/');
+        let service = method.split('/').next().unwrap_or_default();
+
+        self.methods
+            .get(method)
+            .or_else(|| self.methods.get(service))
+            .or(self.default.as_ref())
+    }
+}
+
+pub(crate) type ResponseFuture = BoxFuture<'static, Result<Response<ResponseBody>, crate::Error>>;
+
+/// What a channel needs to retry or hedge the calls made on it.
+#[derive(Debug)]
+pub(crate) struct Retry {
+    policies: RetryPolicies,
+    timeout: Option<Duration>,
+    /// Whether a call that never reached the server is sent again once, even if it was not
+    /// going to be retried.
+    transparent: bool,
+    /// The policy of methods without one, which only sends calls again if they never reached
+    /// the server.
+    single_attempt: Policy,
+    config: Option<Arc<ServiceConfig>>,
+}
+
+impl Retry {
+    /// Returns what is needed to retry the calls of channels created from `endpoint`.
+    pub(crate) fn new(endpoint: &Endpoint) -> Arc<Self> {
+        Arc::new(Self {
+            policies: endpoint.retry_policies.clone(),
+            timeout: endpoint.timeout,
+            transparent: endpoint.transparent_retries,
+            single_attempt: RetryPolicy::new(1).into(),
+            config: endpoint.service_config.clone(),
+        })
+    }
+
+    /// Returns what is needed to make transparent retries of the calls of channels balanced
+    /// over several endpoints.
+    pub(crate) fn transparent() -> Arc<Self> {
+        Arc::new(Self {
+            policies: RetryPolicies::default(),
+            timeout: None,
+            transparent: true,
+            single_attempt: RetryPolicy::new(1).into(),
+            config: None,
+        })
+    }
+
+    /// Applies the method config of `request` from the service config of the channel, and
+    /// returns the message size limits of its call.
+    ///
+    /// The timeout of the method is sent in the `grpc-timeout` header unless the request
+    /// already has a shorter one, and the method only decides whether the call waits for the
+    /// channel to be ready if the request does not.
+    pub(crate) fn configure(&self, request: &mut Request<BoxBody>) -> Option<MessageLimits> {
+        let method = self.config.as_ref()?.method(request.uri().path())?;
+
+        if let Some(timeout) = method.timeout {
+            let header_timeout = try_parse_grpc_timeout(request.headers()).unwrap_or(None);
+            if !matches!(header_timeout, Some(header_timeout) if header_timeout <= timeout) {
+                request
+                    .headers_mut()
+                    .insert(GRPC_TIMEOUT_HEADER, grpc_timeout(timeout));
+            }
+        }
+
+        if let Some(wait_for_ready) = method.wait_for_ready {
+            if request.extensions().get::<WaitForReady>().is_none() {
+                request
+                    .extensions_mut()
+                    .insert(WaitForReady(wait_for_ready));
+            }
+        }
+
+        method.limits()
+    }
+
+    /// Returns the retry or hedging policy of `request`, if it can be sent more than once.
+    ///
+    /// Policies set on the endpoint take precedence over those of the service config.
+    pub(crate) fn policy(&self, request: &Request<BoxBody>) -> Option<Policy> {
+        let path = request.uri().path();
+        let policy = self.policies.get(path).or_else(|| {
+            let method = self.config.as_ref()?.method(path)?;
+            method.policy.as_ref()
+        });
+
+        match policy {
+            Some(policy) => Some(policy.clone()),
+            None if self.transparent || wait_for_ready(request) => {
+                Some(self.single_attempt.clone())
+            }
+            None => None,
+        }
+    }
+
+    /// Sends `request` on `svc`, which must be ready, and retries or hedges it on clones of
+    /// `svc` as `policy` allows.
+    pub(crate) fn call(
+        &self,
+        policy: Policy,
+        svc: &mut Buffer<Svc, Request<BoxBody>>,
+        request: Request<BoxBody>,
+    ) -> ResponseFuture {
+        let header_timeout = try_parse_grpc_timeout(request.headers()).unwrap_or(None);
+        let timeout = match (header_timeout, self.timeout) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+
+        // A timeout too large to be an instant has no deadline.
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        let wait_for_ready = wait_for_ready(&request);
+
+        let (parts, body) = request.into_parts();
+        let body = ReplayBody::new(body, policy.max_replay_size());
+        let attempts = Attempts {
+            method: parts.method.clone(),
+            uri: parts.uri.clone(),
+            version: parts.version,
+            headers: parts.headers.clone(),
+            deadline,
+            transparent: self.transparent,
+            wait_for_ready,
+        };
+        let first = Box::pin(svc.call(Request::from_parts(parts, BoxBody::new(body.clone()))));
+        let svc = svc.clone();
+
+        let future = match policy {
+            Policy::Retry(policy) => retry(policy, svc, attempts, body, first),
+            Policy::Hedging(policy) => hedge(policy, svc, attempts, body, first),
+        };
+        match deadline {
+            // Waiting for the channel to connect is not covered by the timeout of attempts.
+            Some(deadline) if wait_for_ready => Box::pin(async move {
+                let deadline = tokio::time::Instant::from_std(deadline);
+                tokio::time::timeout_at(deadline, future)
+                    .await
+                    .unwrap_or_else(|_| Err(TimeoutExpired::new().into()))
+            }),
+            _ => future,
+        }
+    }
+}
+
+fn wait_for_ready(request: &Request<BoxBody>) -> bool {
+    matches!(
+        request.extensions().get::<WaitForReady>(),
+        Some(WaitForReady(true))
+    )
+}
+
+/// The parts of a request that every attempt of its call is sent with, and when attempts that
+/// never reached the server are sent again.
+struct Attempts {
+    method: Method,
+    uri: Uri,
+    version: Version,
+    headers: HeaderMap,
+    deadline: Option<Instant>,
+    /// Whether the next attempt that never reached the server is sent again.
+    transparent: bool,
+    /// Whether attempts that failed to get a connection are sent again once the channel
+    /// reconnects.
+    wait_for_ready: bool,
+}
+
+impl Attempts {
+    /// Returns how long to wait before sending again an attempt that had `result`, without
+    /// counting it as an attempt, or `None` if it reached the server.
+    fn resend_delay(
+        &mut self,
+        body: &ReplayBody,
+        result: &Result<Response<ResponseBody>, crate::Error>,
+    ) -> Option<Duration> {
+        if !body.can_replay() {
+            return None;
+        }
+        if self.wait_for_ready {
+            if let Some(reconnect_at) = reconnect_at(result) {
+                tracing::trace!("waiting for the channel to be ready");
+                return Some(reconnect_at.saturating_duration_since(tokio::time::Instant::now()));
+            }
+        }
+        if self.transparent && never_processed(result) {
+            // Only one transparent retry is made, so that a server that keeps refusing calls
+            // is not flooded with them.
+            self.transparent = false;
+            tracing::debug!("transparently retrying a call that never reached the server");
+            return Some(Duration::ZERO);
+        }
+        None
+    }
+
+    /// Returns whether an attempt sent after `delay` would start after the deadline.
+    fn too_late(&self, delay: Duration) -> bool {
+        match (self.deadline, Instant::now().checked_add(delay)) {
+            (Some(deadline), Some(start)) => start >= deadline,
+            // A delay too large to be an instant starts after any deadline.
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// Sends an attempt of the call on `svc`, after `previous` other attempts.
+    fn send(
+        &self,
+        svc: &Buffer<Svc, Request<BoxBody>>,
+        body: &ReplayBody,
+        previous: u32,
+    ) -> ResponseFuture {
+        let mut request = Request::new(BoxBody::new(body.replay()));
+        *request.method_mut() = self.method.clone();
+        *request.uri_mut() = self.uri.clone();
+        *request.version_mut() = self.version;
+        *request.headers_mut() = self.headers.clone();
+        request
+            .headers_mut()
+            .insert(PREVIOUS_ATTEMPTS_HEADER, HeaderValue::from(previous));
+        if let Some(deadline) = self.deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            request
+                .headers_mut()
+                .insert(GRPC_TIMEOUT_HEADER, grpc_timeout(remaining));
+        }
+
+        Box::pin(svc.clone().oneshot(request))
+    }
+}
+
+fn retry(
+    policy: Arc<RetryPolicy>,
+    svc: Buffer<Svc, Request<BoxBody>>,
+    mut attempts: Attempts,
+    body: ReplayBody,
+    first: ResponseFuture,
+) -> ResponseFuture {
+    Box::pin(async move {
+        let mut result = first.await;
+        let mut sent = 1;
+        let mut retries = 0;
+
+        loop {
+            if let Some(delay) = attempts.resend_delay(&body, &result) {
+                tokio::time::sleep(delay).await;
+
+                let attempt = attempts.send(&svc, &body, sent - 1);
+                result = attempt.await;
+                continue;
+            }
+
+            let delay = match retry_delay(&policy, &result, sent, &mut retries) {
+                Some(delay) => delay,
+                None => break,
+            };
+            if !body.can_replay() || attempts.too_late(delay) {
+                break;
+            }
+            tokio::time::sleep(delay).await;
+
+            let attempt = attempts.send(&svc, &body, sent);
+            result = attempt.await;
+            sent += 1;
+        }
+
+        body.commit();
+        result
+    })
+}
+
+fn hedge(
+    policy: Arc<HedgingPolicy>,
+    svc: Buffer<Svc, Request<BoxBody>>,
+    mut attempts: Attempts,
+    body: ReplayBody,
+    first: ResponseFuture,
+) -> ResponseFuture {
+    Box::pin(async move {
+        let mut pending = FuturesUnordered::new();
+        pending.push(first);
+        let mut sent = 1;
+        // When the next attempt is sent, if there is one.
+        let mut next = Some(policy.hedging_delay);
+        let mut last = None;
+
+        let result = loop {
+            if sent >= policy.max_attempts || !body.can_replay() {
+                next = None;
+            }
+            if matches!(next, Some(delay) if attempts.too_late(delay)) {
+                next = None;
+            }
+            if pending.is_empty() && next.is_none() {
+                break last.expect("the last attempt failed");
+            }
+
+            // A delay too large to be an instant never passes.
+            let deadline = next.and_then(|delay| tokio::time::Instant::now().checked_add(delay));
+            // Attempts in flight may still be reading the body, so the next one has to wait
+            // until it has been read in full.
+            let wait_for_body = !pending.is_empty();
+            let ready = async {
+                match deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => future::pending().await,
+                }
+                !wait_for_body || future::poll_fn(|cx| body.poll_buffered(cx)).await
+            };
+
+            tokio::select! {
+                Some(result) = pending.next() => {
+                    // Attempts in flight read the body, so it is only sent again when no
+                    // other attempt is left.
+                    if pending.is_empty() {
+                        if let Some(delay) = attempts.resend_delay(&body, &result) {
+                            tokio::time::sleep(delay).await;
+                            pending.push(attempts.send(&svc, &body, sent - 1));
+                            continue;
+                        }
+                    }
+
+                    let headers = match failure(&result) {
+                        Some((code, headers)) if policy.non_fatal_status_codes.contains(&code) => {
+                            tracing::trace!("hedged attempt failed with {:?}", code);
+                            headers
+                        }
+                        _ => break result,
+                    };
+
+                    next = match headers.and_then(|headers| headers.get(PUSHBACK_HEADER)) {
+                        Some(pushback) => next.and(parse_pushback(pushback)),
+                        None => next.map(|_| Duration::ZERO),
+                    };
+                    last = Some(result);
+                }
+                ready = ready => {
+                    if ready {
+                        pending.push(attempts.send(&svc, &body, sent));
+                        sent += 1;
+                        next = Some(policy.hedging_delay);
+                    } else {
+                        next = None;
+                    }
+                }
+            }
+        };
+
+        body.commit();
+        result
+    })
+}
+
+fn grpc_timeout(timeout: Duration) -> HeaderValue {
+    duration_to_grpc_timeout(timeout)
+        .parse()
+        .expect("formatted timeout is a valid header value")
+}
+
+/// Returns the code of a call that had `result`, with the headers of its trailers-only
+/// response, or `None` if it did not fail before it was committed to.
+fn failure(
+    result: &Result<Response<ResponseBody>, crate::Error>,
+) -> Option<(Code, Option<&HeaderMap>)> {
+    match result {
+        Ok(response) => {
+            // Responses that are not trailers-only have been committed to.
+            let status = response.headers().get(GRPC_STATUS_HEADER)?;
+            Some((
+                Code::from_bytes(status.as_bytes()),
+                Some(response.headers()),
+            ))
+        }
+        Err(e) => {
+            let code = find_status_in_source_chain(&**e).map_or(Code::Unknown, |s| s.code());
+            Some((code, None))
+        }
+    }
+}
+
+/// Returns when the channel reconnects, if a call that had `result` failed to get a
+/// connection.
+fn reconnect_at(
+    result: &Result<Response<ResponseBody>, crate::Error>,
+) -> Option<tokio::time::Instant> {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(&**result.as_ref().err()?);
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<ConnectError>() {
+            return Some(err.reconnect_at());
+        }
+        source = err.source();
+    }
+    None
+}
+
+/// Returns whether a call that had `result` failed without ever reaching the server, so it
+/// can be sent again regardless of its retry policy.
+///
+/// That is the case when no connection could be made for it, when the server refused its
+/// stream, and when the server closed the connection gracefully before processing it.
+fn never_processed(result: &Result<Response<ResponseBody>, crate::Error>) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = match result {
+        Ok(_) => return false,
+        Err(e) => Some(&**e),
+    };
+
+    while let Some(err) = source {
+        if err.is::<ConnectError>() {
+            return true;
+        }
+        if let Some(err) = err.downcast_ref::<hyper::Error>() {
+            if err.is_connect() || err.is_canceled() {
+                return true;
+            }
+        }
+        if let Some(err) = err.downcast_ref::<h2::Error>() {
+            return match err.reason() {
+                Some(h2::Reason::REFUSED_STREAM) => err.is_remote(),
+                // Only streams the server did not process fail with a graceful `GOAWAY`.
+                Some(h2::Reason::NO_ERROR) => err.is_go_away() && err.is_remote(),
+                _ => false,
+            };
+        }
+        source = err.source();
+    }
+
+    false
+}
+
+/// Returns the delay of a `grpc-retry-pushback-ms` header, or `None` if it is negative or
+/// invalid.
+fn parse_pushback(pushback: &HeaderValue) -> Option<Duration> {
+    let millis = pushback.to_str().ok()?.parse::<u64>().ok()?;
+    Some(Duration::from_millis(millis))
+}
+
+/// Returns how long to wait before retrying a call that had `result` after `attempts`
+/// attempts, or `None` if it should not be retried.
+fn retry_delay(
+    policy: &RetryPolicy,
+    result: &Result<Response<ResponseBody>, crate::Error>,
+    attempts: u32,
+    retries: &mut u32,
+) -> Option<Duration> {
+    if attempts >= policy.max_attempts {
+        return None;
+    }
+
+    let (code, headers) = failure(result)?;
+    if !policy.retryable_status_codes.contains(&code) {
+        return None;
+    }
+
+    match headers.and_then(|headers| headers.get(PUSHBACK_HEADER)) {
+        Some(pushback) => {
+            let delay = parse_pushback(pushback)?;
+            *retries = 0;
+            Some(delay)
+        }
+        None => {
+            let delay = policy.backoff(*retries);
+            *retries += 1;
+            Some(delay)
+        }
+    }
+}
+
+/// A request body that can be sent again.
+///
+/// Every clone starts over from the beginning of the body, replaying the data read so far
+/// before reading the rest from the original body. Until the whole body has been read, only
+/// the latest clone returns any data, earlier ones belong to attempts that were given up on
+/// and end instead.
+struct ReplayBody {
+    shared: Arc<Mutex<Shared>>,
+    /// The number of chunks this clone has read.
+    read: usize,
+    generation: usize,
+}
+
+struct Shared {
+    body: BoxBody,
+    chunks: Vec<Bytes>,
+    /// How many bytes of the body are kept at most.
+    max_len: usize,
+    /// The number of chunks that were read before the first one of `chunks`, which were
+    /// dropped once the body was committed.
+    offset: usize,
+    len: usize,
+    /// Set once the original body returned all its data.
+    done: bool,
+    trailers: Option<Option<HeaderMap>>,
+    /// Set once the body can no longer be replayed, because it is too large, it failed or
+    /// the call is not retried anymore.
+    committed: bool,
+    generation: usize,
+    /// Woken once the body is buffered or committed.
+    waker: Option<Waker>,
+}
+
+impl Shared {
+    /// Returns whether the whole body has been read and kept.
+    fn is_buffered(&self) -> bool {
+        self.done
+            && self.len <= self.max_len
+            && (self.trailers.is_some() || self.body.is_end_stream())
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Drops the chunks before the `read`th one, once they can no longer be replayed.
+    fn release(&mut self, read: usize) {
+        if self.committed && read > self.offset {
+            self.chunks.drain(..read - self.offset);
+            self.offset = read;
+        }
+    }
+
+    fn poll_data(
+        &mut self,
+        read: &mut usize,
+        generation: usize,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Status>>> {
+        if generation != self.generation && !self.is_buffered() {
+            return Poll::Ready(None);
+        }
+        if let Some(chunk) = self.chunks.get(*read - self.offset) {
+            *read += 1;
+            return Poll::Ready(Some(Ok(chunk.clone())));
+        }
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        match futures_util::ready!(Pin::new(&mut self.body).poll_data(cx)) {
+            Some(Ok(chunk)) => {
+                self.len += chunk.len();
+                if self.len > self.max_len {
+                    self.committed = true;
+                    self.wake();
+                }
+                if !self.committed {
+                    self.chunks.push(chunk.clone());
+                    *read += 1;
+                }
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Some(Err(e)) => {
+                self.committed = true;
+                self.wake();
+                Poll::Ready(Some(Err(e)))
+            }
+            None => {
+                self.done = true;
+                self.wake();
+                Poll::Ready(None)
+            }
+        }
+    }
+}
+
+impl ReplayBody {
+    fn new(body: BoxBody, max_len: usize) -> Self {
+        let shared = Shared {
+            body,
+            chunks: Vec::new(),
+            max_len,
+            offset: 0,
+            len: 0,
+            done: false,
+            trailers: None,
+            committed: false,
+            generation: 0,
+            waker: None,
+        };
+        Self {
+            shared: Arc::new(Mutex::new(shared)),
+            read: 0,
+            generation: 0,
+        }
+    }
+
+    fn can_replay(&self) -> bool {
+        !self.shared.lock().unwrap().committed
+    }
+
+    /// Returns a clone that reads the body from the start, and stops earlier clones from
+    /// reading the original body.
+    fn replay(&self) -> Self {
+        let mut shared = self.shared.lock().unwrap();
+        shared.generation += 1;
+        Self {
+            shared: self.shared.clone(),
+            read: 0,
+            generation: shared.generation,
+        }
+    }
+
+    /// Waits until the whole body has been read by the clones, returning `false` if it
+    /// cannot be replayed instead.
+    fn poll_buffered(&self, cx: &mut Context<'_>) -> Poll<bool> {
+        let mut shared = self.shared.lock().unwrap();
+        if shared.committed {
+            Poll::Ready(false)
+        } else if shared.is_buffered() {
+            Poll::Ready(true)
+        } else {
+            shared.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+
+    fn commit(&self) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.committed = true;
+        shared.wake();
+    }
+}
+
+impl Clone for ReplayBody {
+    fn clone(&self) -> Self {
+        self.replay()
+    }
+}
+
+impl http_body::Body for ReplayBody {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = self.get_mut();
+
+        // Once the attempts that were given up on are gone, the clone of the attempt the call
+        // committed to reads the body on its own, and frees the chunks it already sent.
+        if let Some(shared) = Arc::get_mut(&mut this.shared) {
+            let shared = shared.get_mut().unwrap();
+            shared.release(this.read);
+            return shared.poll_data(&mut this.read, this.generation, cx);
+        }
+
+        let mut shared = this.shared.lock().unwrap();
+        shared.poll_data(&mut this.read, this.generation, cx)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let this = self.get_mut();
+        let mut shared = this.shared.lock().unwrap();
+
+        if this.generation != shared.generation && !shared.is_buffered() {
+            return Poll::Ready(Ok(None));
+        }
+        if let Some(trailers) = &shared.trailers {
+            return Poll::Ready(Ok(trailers.clone()));
+        }
+
+        let trailers = futures_util::ready!(Pin::new(&mut shared.body).poll_trailers(cx))?;
+        shared.trailers = Some(trailers.clone());
+        shared.wake();
+        Poll::Ready(Ok(trailers))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        let shared = self.shared.lock().unwrap();
+        self.read == 0 && shared.chunks.is_empty() && shared.body.is_end_stream()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+
+    fn body(chunks: &'static [&'static [u8]]) -> BoxBody {
+        let stream = futures_util::stream::iter(
+            chunks
+                .iter()
+                .map(|chunk| Ok::<_, std::io::Error>(Bytes::from_static(chunk))),
+        );
+        BoxBody::new(hyper::Body::wrap_stream(stream).map_err(Status::from_error_generic))
+    }
+
+    async fn collect(mut body: ReplayBody) -> Vec<u8> {
+        let mut data = Vec::new();
+        while let Some(chunk) = body.data().await {
+            data.extend_from_slice(&chunk.unwrap());
+        }
+        data
+    }
+
+    #[test]
+    fn delays_too_large_for_an_instant() {
+        let attempts = |deadline| Attempts {
+            method: Method::POST,
+            uri: Uri::default(),
+            version: Version::HTTP_2,
+            headers: HeaderMap::new(),
+            deadline,
+            transparent: false,
+            wait_for_ready: false,
+        };
+
+        assert!(!attempts(None).too_late(Duration::MAX));
+        let deadline = Instant::now() + Duration::from_secs(60);
+        assert!(attempts(Some(deadline)).too_late(Duration::MAX));
+    }
+
+    #[tokio::test]
+    async fn replays_the_body() {
+        let body = ReplayBody::new(body(&[b"a", b"bc", b"d"]), MAX_REPLAY_SIZE);
+
+        let mut first = body.clone();
+        assert_eq!(first.data().await.unwrap().unwrap(), "a");
+
+        let second = body.replay();
+        assert_eq!(collect(second).await, b"abcd");
+        // The first attempt was given up on.
+        assert!(first.data().await.is_none());
+
+        assert!(body.can_replay());
+        assert_eq!(collect(body.replay()).await, b"abcd");
+    }
+
+    #[tokio::test]
+    async fn buffered_bodies_are_read_by_every_clone() {
+        let body = ReplayBody::new(body(&[b"a", b"bc"]), MAX_REPLAY_SIZE);
+        let buffered = || future::poll_fn(|cx| body.poll_buffered(cx)).now_or_never();
+        assert_eq!(buffered(), None);
+
+        let mut first = body.clone();
+        assert_eq!(first.data().await.unwrap().unwrap(), "a");
+        assert_eq!(buffered(), None);
+        assert_eq!(first.data().await.unwrap().unwrap(), "bc");
+        assert!(first.data().await.is_none());
+        assert!(first.trailers().await.unwrap().is_none());
+        assert_eq!(buffered(), Some(true));
+
+        let mut second = body.replay();
+        let mut third = body.replay();
+        assert_eq!(second.data().await.unwrap().unwrap(), "a");
+        assert_eq!(collect(third.replay()).await, b"abc");
+        assert_eq!(second.data().await.unwrap().unwrap(), "bc");
+        assert!(third.data().await.is_some());
+
+        body.commit();
+        assert_eq!(buffered(), Some(false));
+    }
+
+    #[tokio::test]
+    async fn committed_bodies_are_not_kept() {
+        let body = ReplayBody::new(body(&[b"a", b"bc", b"d"]), MAX_REPLAY_SIZE);
+
+        let mut attempt = body.clone();
+        assert_eq!(attempt.data().await.unwrap().unwrap(), "a");
+        body.commit();
+        assert_eq!(attempt.shared.lock().unwrap().chunks.len(), 1);
+
+        // The chunks are freed once the call is done with replaying the body.
+        drop(body);
+        assert_eq!(attempt.data().await.unwrap().unwrap(), "bc");
+        assert!(attempt.shared.lock().unwrap().chunks.is_empty());
+        assert_eq!(attempt.data().await.unwrap().unwrap(), "d");
+        assert!(attempt.shared.lock().unwrap().chunks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn large_bodies_are_not_replayed() {
+        static LARGE: [u8; MAX_REPLAY_SIZE + 1] = [0; MAX_REPLAY_SIZE + 1];
+        static CHUNKS: [&[u8]; 2] = [b"a", &LARGE];
+        let body = ReplayBody::new(body(&CHUNKS), MAX_REPLAY_SIZE);
+
+        assert_eq!(collect(body.clone()).await.len(), MAX_REPLAY_SIZE + 2);
+        assert!(!body.can_replay());
+    }
+
+    #[tokio::test]
+    async fn single_attempts_keep_less_of_the_body() {
+        static LARGE: [u8; MAX_TRANSPARENT_REPLAY_SIZE + 1] = [0; MAX_TRANSPARENT_REPLAY_SIZE + 1];
+        static CHUNKS: [&[u8]; 1] = [&LARGE];
+
+        let single = Policy::from(RetryPolicy::new(1));
+        let single = ReplayBody::new(body(&CHUNKS), single.max_replay_size());
+        collect(single.clone()).await;
+        assert!(!single.can_replay());
+
+        let retried = Policy::from(RetryPolicy::new(2));
+        let retried = ReplayBody::new(body(&CHUNKS), retried.max_replay_size());
+        collect(retried.clone()).await;
+        assert!(retried.can_replay());
+    }
+
+    fn trailers_only(
+        code: Code,
+        pushback: Option<&str>,
+    ) -> Result<Response<ResponseBody>, crate::Error> {
+        let mut response = Response::builder().header(GRPC_STATUS_HEADER, code as i32);
+        if let Some(pushback) = pushback {
+            response = response.header(PUSHBACK_HEADER, pushback);
+        }
+        Ok(response.body(ResponseBody::empty()).unwrap())
+    }
+
+    #[test]
+    fn retries_retryable_failures() {
+        let policy = RetryPolicy::new(3)
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(3));
+        let mut retries = 0;
+
+        let delay = retry_delay(
+            &policy,
+            &trailers_only(Code::Unavailable, None),
+            1,
+            &mut retries,
+        );
+        assert!(delay.unwrap() <= Duration::from_secs(1));
+        let delay = retry_delay(
+            &policy,
+            &trailers_only(Code::Unavailable, None),
+            2,
+            &mut retries,
+        );
+        assert!(delay.unwrap() <= Duration::from_secs(2));
+        assert_eq!(retries, 2);
+
+        assert_eq!(
+            retry_delay(
+                &policy,
+                &trailers_only(Code::Unavailable, None),
+                3,
+                &mut retries
+            ),
+            None
+        );
+        assert_eq!(
+            retry_delay(
+                &policy,
+                &trailers_only(Code::Internal, None),
+                1,
+                &mut retries
+            ),
+            None
+        );
+
+        let unavailable: crate::Error = Box::new(Status::unavailable("connection refused"));
+        assert!(retry_delay(&policy, &Err(unavailable), 1, &mut retries).is_some());
+
+        let committed = Response::new(ResponseBody::empty());
+        assert_eq!(retry_delay(&policy, &Ok(committed), 1, &mut retries), None);
+    }
+
+    #[test]
+    fn honors_pushback() {
+        let policy = RetryPolicy::new(5);
+        let mut retries = 3;
+
+        assert_eq!(
+            retry_delay(
+                &policy,
+                &trailers_only(Code::Unavailable, Some("250")),
+                1,
+                &mut retries
+            ),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(retries, 0);
+
+        assert_eq!(
+            retry_delay(
+                &policy,
+                &trailers_only(Code::Unavailable, Some("-1")),
+                1,
+                &mut retries
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn finds_the_policy_of_a_method() {
+        let mut policies = RetryPolicies::default();
+        assert!(policies.get("/foo.Foo/Bar").is_none());
+
+        policies.set_default(RetryPolicy::new(2));
+        policies.insert("foo.Foo".into(), RetryPolicy::new(3));
+        policies.insert("/foo.Foo/Bar".into(), RetryPolicy::new(4));
+        policies.insert("foo.Foo/Hedged".into(), RetryPolicy::new(5));
+        policies.insert(
+            "foo.Foo/Hedged".into(),
+            HedgingPolicy::new(6, Duration::ZERO),
+        );
+
+        let max_attempts = |path| match policies.get(path).unwrap() {
+            Policy::Retry(policy) => policy.max_attempts,
+            Policy::Hedging(policy) => policy.max_attempts,
+        };
+        assert_eq!(max_attempts("/foo.Foo/Bar"), 4);
+        assert_eq!(max_attempts("/foo.Foo/Baz"), 3);
+        assert_eq!(max_attempts("/foo.Other/Bar"), 2);
+        assert!(matches!(
+            policies.get("/foo.Foo/Hedged"),
+            Some(Policy::Hedging(_))
+        ));
+        assert_eq!(max_attempts("/foo.Foo/Hedged"), 6);
+    }
+}
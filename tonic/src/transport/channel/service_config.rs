@@ -0,0 +1,720 @@
+use super::retry::{Methods, Policy, ResponseFuture};
+use super::{HedgingPolicy, ResponseBody, RetryPolicy};
+use crate::body::BoxBody;
+use crate::response::MaxMessageSize;
+use crate::transport::Error;
+use crate::{Code, Status};
+use bytes::Bytes;
+use http::{HeaderMap, Request, Response};
+use serde_json::{Map, Value};
+use std::{
+    collections::HashSet,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// The size of the header in front of every gRPC message.
+const HEADER_SIZE: usize = 5;
+
+/// The most attempts a retry or hedging policy of a service config may make.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// A gRPC service config, which sets how a channel makes the calls of each method.
+///
+/// A service config is parsed from the [JSON form] other gRPC implementations use, and is
+/// given to a channel with [`Endpoint::service_config`] or by its [`Resolver`]. These parts of
+/// it are supported:
+///
+/// - `methodConfig`, with the `name`s of the services and methods each method config applies
+///   to, and their `timeout`, `waitForReady`, `maxRequestMessageBytes`,
+///   `maxResponseMessageBytes`, and `retryPolicy` or `hedgingPolicy`. Policies may make at most
+///   5 attempts, and larger `maxAttempts` are lowered to 5.
+/// - `loadBalancingConfig`, or the older `loadBalancingPolicy`, with either `round_robin` or
+///   `pick_first`.
+/// - `healthCheckConfig`, whose `serviceName` turns on client side health checks. Round robin
+///   channels, such as those of [`Channel::round_robin_list`] and of resolvers, then watch
+///   the health of that service on every endpoint with the `grpc.health.v1.Health` service,
+///   and skip the endpoints that are not serving it. Like in other gRPC implementations this
+///   does not apply to `pick_first`.
+///
+/// Other fields are ignored.
+///
+/// ```
+/// # use tonic::transport::{channel::ServiceConfig, Endpoint};
+/// let config = ServiceConfig::from_json(
+///     r#"{
+///         "methodConfig": [{
+///             "name": [{ "service": "helloworld.Greeter" }],
+///             "timeout": "1.5s",
+///             "retryPolicy": {
+///                 "maxAttempts": 3,
+///                 "initialBackoff": "0.1s",
+///                 "maxBackoff": "1s",
+///                 "backoffMultiplier": 2,
+///                 "retryableStatusCodes": ["UNAVAILABLE"]
+///             }
+///         }]
+///     }"#,
+/// )
+/// .unwrap();
+///
+/// let endpoint = Endpoint::from_static("http://[::1]:50051").service_config(config);
+/// ```
+///
+/// [JSON form]: https://github.com/grpc/grpc/blob/master/doc/service_config.md
+/// [`Endpoint::service_config`]: super::Endpoint::service_config
+/// [`Resolver`]: super::Resolver
+/// [`Channel::round_robin_list`]: super::Channel::round_robin_list
+#[derive(Debug, Clone, Default)]
+pub struct ServiceConfig {
+    load_balancing: Option<LoadBalancing>,
+    health_check_service: Option<String>,
+    methods: Methods<MethodConfig>,
+}
+
+/// The load balancing policies a service config can choose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LoadBalancing {
+    PickFirst,
+    RoundRobin,
+}
+
+/// How the calls of a method are made, from a `methodConfig` of a service config.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MethodConfig {
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) wait_for_ready: Option<bool>,
+    pub(crate) policy: Option<Policy>,
+    max_request_message_bytes: Option<usize>,
+    max_response_message_bytes: Option<usize>,
+}
+
+impl ServiceConfig {
+    /// Parses a service config from its JSON form.
+    ///
+    /// Fails if `json` is not a valid service config, such as when a method config has both a
+    /// `retryPolicy` and a `hedgingPolicy`, or when none of the load balancing policies of
+    /// its `loadBalancingConfig` is supported.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let value = serde_json::from_str(json).map_err(Error::new_invalid_service_config)?;
+
+        parse(&value).map_err(Error::new_invalid_service_config)
+    }
+
+    pub(crate) fn load_balancing(&self) -> Option<LoadBalancing> {
+        self.load_balancing
+    }
+
+    /// Returns the name of the service whose health the endpoints are checked for, if health
+    /// checks are on.
+    pub(crate) fn health_check_service(&self) -> Option<&str> {
+        self.health_check_service.as_deref()
+    }
+
+    /// Returns the method config for the method at `path`, such as
+    /// `/helloworld.Greeter/SayHello`.
+    pub(crate) fn method(&self, path: &str) -> Option<&MethodConfig> {
+        self.methods.get(path)
+    }
+}
+
+impl MethodConfig {
+    /// Returns the message size limits of the method, if it has any.
+    pub(crate) fn limits(&self) -> Option<MessageLimits> {
+        if self.max_request_message_bytes.is_none() && self.max_response_message_bytes.is_none() {
+            return None;
+        }
+
+        Some(MessageLimits {
+            request: self.max_request_message_bytes,
+            response: self.max_response_message_bytes,
+        })
+    }
+}
+
+fn parse(value: &Value) -> Result<ServiceConfig, String> {
+    let config = object(value, "service config")?;
+    let mut methods = Methods::default();
+    let mut names = HashSet::new();
+
+    for method in array(config.get("methodConfig"), "methodConfig")? {
+        let method = object(method, "methodConfig")?;
+        let method_config = method_config(method)?;
+
+        for name in array(method.get("name"), "name")? {
+            let name = method_name(object(name, "name")?)?;
+            if !names.insert(name.clone()) {
+                return Err(format!("duplicate method config for `{}`", name));
+            }
+
+            match name.as_str() {
+                "" => methods.set_default(method_config.clone()),
+                _ => methods.insert(name, method_config.clone()),
+            }
+        }
+    }
+
+    let health_check_service = match present(config.get("healthCheckConfig")) {
+        Some(health_check) => {
+            let health_check = object(health_check, "healthCheckConfig")?;
+            string(health_check.get("serviceName"), "serviceName")?.map(Into::into)
+        }
+        None => None,
+    };
+
+    Ok(ServiceConfig {
+        load_balancing: load_balancing(config)?,
+        health_check_service,
+        methods,
+    })
+}
+
+/// Returns the name a method config applies to, which is empty for all methods.
+fn method_name(name: &Map<String, Value>) -> Result<String, String> {
+    let service = string(name.get("service"), "service")?.unwrap_or_default();
+    let method = string(name.get("method"), "method")?.unwrap_or_default();
+
+    match (service, method) {
+        ("", "") => Ok(String::new()),
+        ("", _) => Err("a method name without a service".into()),
+        (service, "") => Ok(service.into()),
+        (service, method) => Ok(format!("{}/{}", service, method)),
+    }
+}
+
+fn method_config(method: &Map<String, Value>) -> Result<MethodConfig, String> {
+    let retry = present(method.get("retryPolicy"));
+    let hedging = present(method.get("hedgingPolicy"));
+
+    let policy = match (retry, hedging) {
+        (Some(_), Some(_)) => {
+            return Err("a method config with both a retryPolicy and a hedgingPolicy".into())
+        }
+        (Some(policy), None) => Some(retry_policy(object(policy, "retryPolicy")?)?.into()),
+        (None, Some(policy)) => Some(hedging_policy(object(policy, "hedgingPolicy")?)?.into()),
+        (None, None) => None,
+    };
+
+    Ok(MethodConfig {
+        timeout: duration(method.get("timeout"), "timeout")?,
+        wait_for_ready: boolean(method.get("waitForReady"), "waitForReady")?,
+        policy,
+        max_request_message_bytes: size(
+            method.get("maxRequestMessageBytes"),
+            "maxRequestMessageBytes",
+        )?,
+        max_response_message_bytes: size(
+            method.get("maxResponseMessageBytes"),
+            "maxResponseMessageBytes",
+        )?,
+    })
+}
+
+fn retry_policy(policy: &Map<String, Value>) -> Result<RetryPolicy, String> {
+    let required = |value: Option<Duration>, field: &str| match value {
+        Some(value) if value > Duration::ZERO => Ok(value),
+        _ => Err(format!("`{}` must be a positive duration", field)),
+    };
+
+    let initial_backoff = duration(policy.get("initialBackoff"), "initialBackoff")?;
+    let max_backoff = duration(policy.get("maxBackoff"), "maxBackoff")?;
+    let backoff_multiplier = match present(policy.get("backoffMultiplier")) {
+        Some(Value::Number(number)) => number.as_f64().filter(|multiplier| *multiplier > 0.0),
+        _ => None,
+    };
+    let codes = codes(policy.get("retryableStatusCodes"), "retryableStatusCodes")?;
+    if codes.is_empty() {
+        return Err("`retryableStatusCodes` must not be empty".into());
+    }
+
+    Ok(RetryPolicy::new(max_attempts(policy)?)
+        .initial_backoff(required(initial_backoff, "initialBackoff")?)
+        .max_backoff(required(max_backoff, "maxBackoff")?)
+        .backoff_multiplier(
+            backoff_multiplier.ok_or("`backoffMultiplier` must be a positive number")?,
+        )
+        .retryable_status_codes(codes))
+}
+
+fn hedging_policy(policy: &Map<String, Value>) -> Result<HedgingPolicy, String> {
+    let hedging_delay = duration(policy.get("hedgingDelay"), "hedgingDelay")?.unwrap_or_default();
+    let codes = codes(policy.get("nonFatalStatusCodes"), "nonFatalStatusCodes")?;
+
+    Ok(HedgingPolicy::new(max_attempts(policy)?, hedging_delay).non_fatal_status_codes(codes))
+}
+
+fn max_attempts(policy: &Map<String, Value>) -> Result<u32, String> {
+    match size(policy.get("maxAttempts"), "maxAttempts")? {
+        Some(attempts) if attempts > 1 => Ok(u32::try_from(attempts)
+            .unwrap_or(MAX_ATTEMPTS)
+            .min(MAX_ATTEMPTS)),
+        _ => Err("`maxAttempts` must be larger than 1".into()),
+    }
+}
+
+fn load_balancing(config: &Map<String, Value>) -> Result<Option<LoadBalancing>, String> {
+    let policy = |name: &str| match name {
+        "pick_first" => Some(LoadBalancing::PickFirst),
+        "round_robin" => Some(LoadBalancing::RoundRobin),
+        _ => None,
+    };
+
+    if let Some(configs) = present(config.get("loadBalancingConfig")) {
+        let configs = array(Some(configs), "loadBalancingConfig")?;
+
+        // The first policy of the list that is supported is used.
+        for config in configs {
+            let config = object(config, "loadBalancingConfig")?;
+            if config.len() != 1 {
+                return Err("a loadBalancingConfig entry must name exactly one policy".into());
+            }
+            if let Some(policy) = config.keys().find_map(|name| policy(name)) {
+                return Ok(Some(policy));
+            }
+        }
+
+        if configs.is_empty() {
+            return Ok(None);
+        }
+        return Err("no supported policy in `loadBalancingConfig`".into());
+    }
+
+    match string(config.get("loadBalancingPolicy"), "loadBalancingPolicy")? {
+        Some(name) => match policy(&name.to_ascii_lowercase()) {
+            Some(policy) => Ok(Some(policy)),
+            None => {
+                tracing::debug!("ignoring unsupported load balancing policy {:?}", name);
+                Ok(None)
+            }
+        },
+        None => Ok(None),
+    }
+}
+
+/// Returns `value` unless it is missing or `null`, which the JSON form of protobuf messages
+/// treats the same.
+fn present(value: Option<&Value>) -> Option<&Value> {
+    value.filter(|value| !value.is_null())
+}
+
+fn object<'a>(value: &'a Value, field: &str) -> Result<&'a Map<String, Value>, String> {
+    value
+        .as_object()
+        .ok_or_else(|| format!("`{}` must be an object", field))
+}
+
+fn array<'a>(value: Option<&'a Value>, field: &str) -> Result<&'a [Value], String> {
+    match present(value) {
+        Some(Value::Array(values)) => Ok(values),
+        Some(_) => Err(format!("`{}` must be a list", field)),
+        None => Ok(&[]),
+    }
+}
+
+fn string<'a>(value: Option<&'a Value>, field: &str) -> Result<Option<&'a str>, String> {
+    match present(value) {
+        Some(Value::String(value)) => Ok(Some(value)),
+        Some(_) => Err(format!("`{}` must be a string", field)),
+        None => Ok(None),
+    }
+}
+
+fn boolean(value: Option<&Value>, field: &str) -> Result<Option<bool>, String> {
+    match present(value) {
+        Some(Value::Bool(value)) => Ok(Some(*value)),
+        Some(_) => Err(format!("`{}` must be a boolean", field)),
+        None => Ok(None),
+    }
+}
+
+/// Parses an unsigned integer, which the JSON form of protobuf allows to be a string.
+fn size(value: Option<&Value>, field: &str) -> Result<Option<usize>, String> {
+    let size = match present(value) {
+        Some(Value::Number(number)) => number.as_u64(),
+        Some(Value::String(number)) => number.parse().ok(),
+        Some(_) => None,
+        None => return Ok(None),
+    };
+
+    size.and_then(|size| usize::try_from(size).ok())
+        .map(Some)
+        .ok_or_else(|| format!("`{}` must be an unsigned integer", field))
+}
+
+/// Parses a duration in the JSON form of `google.protobuf.Duration`, such as `1.5s`.
+fn duration(value: Option<&Value>, field: &str) -> Result<Option<Duration>, String> {
+    let invalid = || format!("`{}` must be a duration such as \"1.5s\"", field);
+    let value = match string(value, field)? {
+        Some(value) => value.strip_suffix('s').ok_or_else(invalid)?,
+        None => return Ok(None),
+    };
+
+    let (seconds, nanos) = value.split_once('.').unwrap_or((value, ""));
+    let digits = |value: &str| !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit());
+    if !digits(seconds) || !(nanos.is_empty() || digits(nanos) && nanos.len() <= 9) {
+        return Err(invalid());
+    }
+
+    let seconds = seconds.parse().map_err(|_| invalid())?;
+    let nanos = match nanos {
+        "" => 0,
+        nanos => format!("{:0<9}", nanos).parse().map_err(|_| invalid())?,
+    };
+
+    Ok(Some(Duration::new(seconds, nanos)))
+}
+
+/// Parses a list of status codes, given either by name, such as `UNAVAILABLE`, or by
+/// number.
+fn codes(value: Option<&Value>, field: &str) -> Result<Vec<Code>, String> {
+    array(value, field)?
+        .iter()
+        .map(|code| {
+            let code = match code {
+                Value::String(name) => code_from_name(name),
+                Value::Number(number) => match number.as_i64() {
+                    Some(number @ 0..=16) => Some(Code::from_i32(number as i32)),
+                    _ => None,
+                },
+                _ => None,
+            };
+            code.ok_or_else(|| format!("`{}` must be a list of status codes", field))
+        })
+        .collect()
+}
+
+fn code_from_name(name: &str) -> Option<Code> {
+    let code = match name {
+        "OK" => Code::Ok,
+        "CANCELLED" => Code::Cancelled,
+        "UNKNOWN" => Code::Unknown,
+        "INVALID_ARGUMENT" => Code::InvalidArgument,
+        "DEADLINE_EXCEEDED" => Code::DeadlineExceeded,
+        "NOT_FOUND" => Code::NotFound,
+        "ALREADY_EXISTS" => Code::AlreadyExists,
+        "PERMISSION_DENIED" => Code::PermissionDenied,
+        "RESOURCE_EXHAUSTED" => Code::ResourceExhausted,
+        "FAILED_PRECONDITION" => Code::FailedPrecondition,
+        "ABORTED" => Code::Aborted,
+        "OUT_OF_RANGE" => Code::OutOfRange,
+        "UNIMPLEMENTED" => Code::Unimplemented,
+        "INTERNAL" => Code::Internal,
+        "UNAVAILABLE" => Code::Unavailable,
+        "DATA_LOSS" => Code::DataLoss,
+        "UNAUTHENTICATED" => Code::Unauthenticated,
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+/// The largest messages a call may send and receive, from its method config.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MessageLimits {
+    request: Option<usize>,
+    response: Option<usize>,
+}
+
+impl MessageLimits {
+    /// Sends `request` with `send`, failing it if it sends a message that is too large.
+    ///
+    /// The limit of response messages is handed to the client of the call, which checks it
+    /// while decoding them.
+    pub(crate) fn call<F>(
+        self,
+        request: Request<BoxBody>,
+        send: impl FnOnce(Request<BoxBody>) -> F,
+    ) -> ResponseFuture
+    where
+        F: Future<Output = Result<Response<ResponseBody>, crate::Error>> + Send + 'static,
+    {
+        let error = Arc::new(Mutex::new(None));
+        let request = match self.request {
+            Some(limit) => request.map(|body| {
+                BoxBody::new(LimitBody {
+                    inner: body,
+                    limit,
+                    header: [0; HEADER_SIZE],
+                    header_len: 0,
+                    remaining: 0,
+                    error: error.clone(),
+                })
+            }),
+            None => request,
+        };
+
+        let response = send(request);
+        let limit = self.response;
+
+        Box::pin(async move {
+            let result = response.await;
+            // A request body that fails is only reported as a reset stream, so the original
+            // error is returned instead.
+            if let Some(status) = error.lock().unwrap().take() {
+                return Err(status.into());
+            }
+
+            let mut response = result?;
+            if let Some(limit) = limit {
+                response.extensions_mut().insert(MaxMessageSize(limit));
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// A request body that fails once it sends a message larger than `limit`.
+struct LimitBody {
+    inner: BoxBody,
+    limit: usize,
+    /// The part read so far of the header of the next message.
+    header: [u8; HEADER_SIZE],
+    header_len: usize,
+    /// How many bytes of the current message are left.
+    remaining: usize,
+    error: Arc<Mutex<Option<Status>>>,
+}
+
+impl LimitBody {
+    /// Reads the lengths of the messages in `data`, and fails with the length of the first
+    /// one that is too large.
+    fn check(&mut self, mut data: &[u8]) -> Result<(), usize> {
+        while !data.is_empty() {
+            if self.remaining > 0 {
+                let len = self.remaining.min(data.len());
+                self.remaining -= len;
+                data = &data[len..];
+                continue;
+            }
+
+            let len = (HEADER_SIZE - self.header_len).min(data.len());
+            self.header[self.header_len..self.header_len + len].copy_from_slice(&data[..len]);
+            self.header_len += len;
+            data = &data[len..];
+
+            if self.header_len == HEADER_SIZE {
+                self.header_len = 0;
+                let [_, a, b, c, d] = self.header;
+                let len = u32::from_be_bytes([a, b, c, d]) as usize;
+                if len > self.limit {
+                    return Err(len);
+                }
+                self.remaining = len;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl http_body::Body for LimitBody {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let data = match futures_util::ready!(Pin::new(&mut self.inner).poll_data(cx)) {
+            Some(Ok(data)) => data,
+            other => return Poll::Ready(other),
+        };
+
+        match self.check(&data) {
+            Ok(()) => Poll::Ready(Some(Ok(data))),
+            Err(len) => {
+                let status = Status::new(
+                    Code::OutOfRange,
+                    format!(
+                        "Error, message length too large: found {} bytes, the limit is: {} bytes",
+                        len, self.limit
+                    ),
+                );
+                *self.error.lock().unwrap() = Some(status.clone());
+                Poll::Ready(Some(Err(status)))
+            }
+        }
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.inner).poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Result<ServiceConfig, Error> {
+        ServiceConfig::from_json(json)
+    }
+
+    #[test]
+    fn parses_method_configs() {
+        let config = parse(
+            r#"{
+                "methodConfig": [
+                    {
+                        "name": [{}],
+                        "timeout": "2s"
+                    },
+                    {
+                        "name": [{ "service": "foo.Foo" }, { "service": "foo.Bar", "method": "Baz" }],
+                        "timeout": "0.25s",
+                        "waitForReady": true,
+                        "maxRequestMessageBytes": 1024,
+                        "maxResponseMessageBytes": "2048",
+                        "retryPolicy": {
+                            "maxAttempts": 10,
+                            "initialBackoff": "0.1s",
+                            "maxBackoff": "1s",
+                            "backoffMultiplier": 1.5,
+                            "retryableStatusCodes": ["UNAVAILABLE", 8]
+                        }
+                    },
+                    {
+                        "name": [{ "service": "foo.Foo", "method": "Hedged" }],
+                        "hedgingPolicy": { "maxAttempts": 3, "hedgingDelay": "0.01s" }
+                    }
+                ],
+                "healthCheckConfig": { "serviceName": "foo.Foo" }
+            }"#,
+        )
+        .unwrap();
+
+        let method = config.method("/foo.Foo/Bar").unwrap();
+        assert_eq!(method.timeout, Some(Duration::from_millis(250)));
+        assert_eq!(method.wait_for_ready, Some(true));
+        assert_eq!(method.max_request_message_bytes, Some(1024));
+        assert_eq!(method.max_response_message_bytes, Some(2048));
+        match &method.policy {
+            Some(Policy::Retry(policy)) => {
+                let policy = format!("{:?}", policy);
+                assert!(policy.contains("max_attempts: 5"), "{}", policy);
+                assert!(policy.contains("ResourceExhausted"), "{}", policy);
+            }
+            policy => panic!("unexpected policy {:?}", policy),
+        }
+
+        assert!(config.method("/foo.Bar/Baz").unwrap().policy.is_some());
+        assert!(matches!(
+            config.method("/foo.Foo/Hedged").unwrap().policy,
+            Some(Policy::Hedging(_))
+        ));
+
+        let other = config.method("/foo.Bar/Other").unwrap();
+        assert_eq!(other.timeout, Some(Duration::from_secs(2)));
+        assert!(other.policy.is_none());
+        assert!(other.limits().is_none());
+        assert_eq!(config.load_balancing(), None);
+        assert_eq!(config.health_check_service(), Some("foo.Foo"));
+    }
+
+    #[test]
+    fn parses_load_balancing() {
+        let policy = |json| parse(json).unwrap().load_balancing();
+
+        assert_eq!(
+            policy(r#"{ "loadBalancingConfig": [{ "grpclb": {} }, { "pick_first": {} }] }"#),
+            Some(LoadBalancing::PickFirst)
+        );
+        assert_eq!(
+            policy(r#"{ "loadBalancingPolicy": "ROUND_ROBIN" }"#),
+            Some(LoadBalancing::RoundRobin)
+        );
+        assert_eq!(
+            policy(
+                r#"{
+                    "loadBalancingConfig": [{ "round_robin": {} }],
+                    "loadBalancingPolicy": "pick_first"
+                }"#
+            ),
+            Some(LoadBalancing::RoundRobin)
+        );
+        assert_eq!(policy(r#"{ "loadBalancingPolicy": "grpclb" }"#), None);
+        assert!(parse(r#"{ "loadBalancingConfig": [{ "grpclb": {} }] }"#).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_configs() {
+        for json in [
+            "[]",
+            r#"{ "methodConfig": [{ "name": [{ "method": "Bar" }] }] }"#,
+            r#"{ "methodConfig": [{ "name": [{}] }, { "name": [{}] }] }"#,
+            r#"{ "methodConfig": [{ "name": [{}], "timeout": "1m" }] }"#,
+            r#"{ "methodConfig": [{ "name": [{}], "timeout": "-1s" }] }"#,
+            r#"{ "methodConfig": [{ "name": [{}], "maxRequestMessageBytes": -1 }] }"#,
+            r#"{ "healthCheckConfig": { "serviceName": 1 } }"#,
+            r#"{
+                "methodConfig": [{
+                    "name": [{}],
+                    "retryPolicy": {
+                        "maxAttempts": 2,
+                        "initialBackoff": "1s",
+                        "maxBackoff": "1s",
+                        "backoffMultiplier": 2,
+                        "retryableStatusCodes": []
+                    }
+                }]
+            }"#,
+            r#"{
+                "methodConfig": [{
+                    "name": [{}],
+                    "hedgingPolicy": { "maxAttempts": 1 }
+                }]
+            }"#,
+            r#"{
+                "methodConfig": [{
+                    "name": [{}],
+                    "hedgingPolicy": { "maxAttempts": 2, "nonFatalStatusCodes": ["NOPE"] }
+                }]
+            }"#,
+            r#"{
+                "methodConfig": [{
+                    "name": [{}],
+                    "retryPolicy": {
+                        "maxAttempts": 2,
+                        "initialBackoff": "1s",
+                        "maxBackoff": "1s",
+                        "backoffMultiplier": 2,
+                        "retryableStatusCodes": ["UNAVAILABLE"]
+                    },
+                    "hedgingPolicy": { "maxAttempts": 2 }
+                }]
+            }"#,
+        ] {
+            assert!(parse(json).is_err(), "{}", json);
+        }
+    }
+
+    #[test]
+    fn checks_the_size_of_messages() {
+        let mut body = LimitBody {
+            inner: crate::body::empty_body(),
+            limit: 4,
+            header: [0; HEADER_SIZE],
+            header_len: 0,
+            remaining: 0,
+            error: Default::default(),
+        };
+
+        body.check(&[0, 0, 0, 0, 4, 1, 2]).unwrap();
+        body.check(&[3, 4, 0, 0]).unwrap();
+        body.check(&[0, 0, 2, 1, 2]).unwrap();
+        assert_eq!(body.check(&[0, 0, 0, 0, 5]), Err(5));
+    }
+}
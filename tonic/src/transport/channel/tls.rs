@@ -7,6 +7,26 @@ use http::Uri;
 use std::fmt;
 
 /// Configures TLS settings for endpoints.
+///
+/// The server certificate is verified against the CA certificate set with
+/// [`ca_certificate`](ClientTlsConfig::ca_certificate), plus the platform's roots when the
+/// `tls-roots` feature is enabled or the Mozilla roots when `tls-webpki-roots` is. With one
+/// of those features `https://` endpoints use TLS even without a `ClientTlsConfig`.
+///
+/// ```no_run
+/// # use tonic::transport::{Certificate, Channel, ClientTlsConfig};
+/// # async fn connect(ca: Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+/// let tls = ClientTlsConfig::new()
+///     .ca_certificate(Certificate::from_pem(ca))
+///     .domain_name("example.com");
+///
+/// let channel = Channel::from_static("https://10.0.0.1:50051")
+///     .tls_config(tls)?
+///     .connect()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Clone, Default)]
 pub struct ClientTlsConfig {
     domain: Option<String>,
@@ -25,7 +45,8 @@ impl fmt::Debug for ClientTlsConfig {
 }
 
 impl ClientTlsConfig {
-    /// Creates a new `ClientTlsConfig` using Rustls.
+    /// Creates a new `ClientTlsConfig` using Rustls, or native-tls with the `tls-native`
+    /// feature.
     pub fn new() -> Self {
         ClientTlsConfig {
             domain: None,
@@ -51,6 +72,10 @@ impl ClientTlsConfig {
     }
 
     /// Sets the client identity to present to the server.
+    ///
+    /// This is required by servers that use mutual TLS, which verify the certificate against
+    /// their [`client_ca_root`](crate::transport::ServerTlsConfig::client_ca_root). The
+    /// server sees the presented certificate in [`Request::peer_certs`](crate::Request::peer_certs).
     pub fn identity(self, identity: Identity) -> Self {
         ClientTlsConfig {
             identity: Some(identity),
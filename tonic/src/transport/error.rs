@@ -17,6 +17,8 @@ pub(crate) enum Kind {
     Transport,
     InvalidUri,
     InvalidUserAgent,
+    InvalidServiceConfig,
+    NoEndpoints,
 }
 
 impl Error {
@@ -43,11 +45,21 @@ impl Error {
         Error::new(Kind::InvalidUserAgent)
     }
 
+    pub(crate) fn new_invalid_service_config(source: impl Into<Source>) -> Self {
+        Error::new(Kind::InvalidServiceConfig).with(source)
+    }
+
+    pub(crate) fn new_no_endpoints() -> Self {
+        Error::new(Kind::NoEndpoints)
+    }
+
     fn description(&self) -> &str {
         match &self.inner.kind {
             Kind::Transport => "transport error",
             Kind::InvalidUri => "invalid URI",
             Kind::InvalidUserAgent => "user agent is not a valid header value",
+            Kind::InvalidServiceConfig => "invalid service config",
+            Kind::NoEndpoints => "no endpoints to connect to",
         }
     }
 }
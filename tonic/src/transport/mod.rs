@@ -2,12 +2,12 @@
 //!
 //! This module provides a set of batteries included, fully featured and
 //! fast set of HTTP/2 server and client's. These components each provide a or
-//! `rustls` tls backend when the respective feature flag is enabled, and
+//! `rustls` or `native-tls` tls backend when the respective feature flag is enabled, and
 //! provides builders to configure transport behavior.
 //!
 //! # Features
 //!
-//! - TLS support via [rustls].
+//! - TLS support via [rustls] or [native-tls].
 //! - Load balancing
 //! - Timeouts
 //! - Concurrency Limits
@@ -85,8 +85,10 @@
 //! ```
 //!
 //! [rustls]: https://docs.rs/rustls/0.16.0/rustls/
+//! [native-tls]: https://docs.rs/native-tls
 
 pub mod channel;
+#[cfg(feature = "transport")]
 pub mod server;
 
 mod error;
@@ -99,6 +101,7 @@ mod tls;
 pub use self::channel::{Channel, Endpoint};
 pub use self::error::Error;
 #[doc(inline)]
+#[cfg(feature = "transport")]
 pub use self::server::Server;
 #[doc(inline)]
 pub use self::service::grpc_timeout::TimeoutExpired;
@@ -108,15 +111,17 @@ pub use crate::server::NamedService;
 pub use hyper::{Body, Uri};
 
 pub(crate) use self::service::executor::Executor;
+#[cfg(feature = "transport")]
+pub(crate) use self::service::grpc_timeout::GrpcDeadline;
 
-#[cfg(feature = "tls")]
-#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "tls", feature = "tls-native"))))]
 pub use self::channel::ClientTlsConfig;
-#[cfg(feature = "tls")]
-#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "tls", feature = "tls-native"))))]
 pub use self::server::ServerTlsConfig;
-#[cfg(feature = "tls")]
-#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "tls", feature = "tls-native"))))]
 pub use self::tls::Identity;
 
 type BoxFuture<T, E> =
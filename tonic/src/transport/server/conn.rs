@@ -2,12 +2,12 @@ use hyper::server::conn::AddrStream;
 use std::net::SocketAddr;
 use tokio::net::TcpStream;
 
-#[cfg(feature = "tls")]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+use super::{x509, SubjectAltName, TlsStream};
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 use crate::transport::Certificate;
-#[cfg(feature = "tls")]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 use std::sync::Arc;
-#[cfg(feature = "tls")]
-use tokio_rustls::server::TlsStream;
 
 /// Trait that connected IO resources implement and use to produce info about the connection.
 ///
@@ -68,10 +68,16 @@ pub trait Connected {
 /// [ext]: crate::Request::extensions
 #[derive(Debug, Clone)]
 pub struct TcpConnectInfo {
+    local_addr: Option<SocketAddr>,
     remote_addr: Option<SocketAddr>,
 }
 
 impl TcpConnectInfo {
+    /// Return the local address the IO resource is bound to.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
     /// Return the remote address the IO resource is connected too.
     pub fn remote_addr(&self) -> Option<SocketAddr> {
         self.remote_addr
@@ -83,6 +89,7 @@ impl Connected for AddrStream {
 
     fn connect_info(&self) -> Self::ConnectInfo {
         TcpConnectInfo {
+            local_addr: Some(self.local_addr()),
             remote_addr: Some(self.remote_addr()),
         }
     }
@@ -93,6 +100,7 @@ impl Connected for TcpStream {
 
     fn connect_info(&self) -> Self::ConnectInfo {
         TcpConnectInfo {
+            local_addr: self.local_addr().ok(),
             remote_addr: self.peer_addr().ok(),
         }
     }
@@ -126,6 +134,21 @@ where
     }
 }
 
+#[cfg(all(feature = "tls-native", not(feature = "tls")))]
+impl<T> Connected for TlsStream<T>
+where
+    T: Connected,
+{
+    type ConnectInfo = TlsConnectInfo<T::ConnectInfo>;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        TlsConnectInfo {
+            inner: self.get_ref().connect_info(),
+            certs: self.peer_certs(),
+        }
+    }
+}
+
 /// Connection info for TLS streams.
 ///
 /// This type will be accessible through [request extensions][ext] if you're using a TLS connector.
@@ -133,16 +156,16 @@ where
 /// See [`Connected`] for more details.
 ///
 /// [ext]: crate::Request::extensions
-#[cfg(feature = "tls")]
-#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "tls", feature = "tls-native"))))]
 #[derive(Debug, Clone)]
 pub struct TlsConnectInfo<T> {
     inner: T,
     certs: Option<Arc<Vec<Certificate>>>,
 }
 
-#[cfg(feature = "tls")]
-#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "tls", feature = "tls-native"))))]
 impl<T> TlsConnectInfo<T> {
     /// Get a reference to the underlying connection info.
     pub fn get_ref(&self) -> &T {
@@ -155,7 +178,21 @@ impl<T> TlsConnectInfo<T> {
     }
 
     /// Return the set of connected peer TLS certificates.
+    ///
+    /// The certificates are DER encoded, with the peer's own certificate first. When the
+    /// server was configured with [`ServerTlsConfig::client_ca_root`] they have already been
+    /// verified against it.
+    ///
+    /// [`ServerTlsConfig::client_ca_root`]: super::ServerTlsConfig::client_ca_root
     pub fn peer_certs(&self) -> Option<Arc<Vec<Certificate>>> {
         self.certs.clone()
     }
+
+    /// Return the subject alternative names of the peer's certificate.
+    ///
+    /// This is `None` if the peer did not present a certificate or it could not be parsed.
+    pub fn peer_subject_alt_names(&self) -> Option<Vec<SubjectAltName>> {
+        let cert = self.certs.as_ref()?.first()?;
+        x509::subject_alt_names(cert.get_ref())
+    }
 }
@@ -0,0 +1,216 @@
+use super::Connected;
+use futures_core::Stream;
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    sync::{AcquireError, OwnedSemaphorePermit, Semaphore},
+    time::{Instant, Sleep},
+};
+
+type Acquire = Pin<Box<dyn Future<Output = Result<OwnedSemaphorePermit, AcquireError>> + Send>>;
+
+/// Limits how many connections a stream of incoming connections yields.
+///
+/// While `max` connections are open, or the accept rate has been used up, the inner stream
+/// is not polled, so further connections wait in the listen backlog of the OS.
+#[pin_project]
+pub(crate) struct LimitedIncoming<I> {
+    #[pin]
+    inner: I,
+    connections: Option<Connections>,
+    rate: Option<Rate>,
+}
+
+struct Connections {
+    semaphore: Arc<Semaphore>,
+    acquire: Option<Acquire>,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+struct Rate {
+    num: u64,
+    per: Duration,
+    rem: u64,
+    window: Pin<Box<Sleep>>,
+}
+
+impl<I> LimitedIncoming<I> {
+    pub(crate) fn new(
+        inner: I,
+        max_connections: Option<usize>,
+        accept_rate: Option<(u64, Duration)>,
+    ) -> Self {
+        Self {
+            inner,
+            connections: max_connections.map(|max| Connections {
+                semaphore: Arc::new(Semaphore::new(max)),
+                acquire: None,
+                permit: None,
+            }),
+            rate: accept_rate.map(|(num, per)| Rate {
+                num,
+                per,
+                rem: num,
+                window: Box::pin(tokio::time::sleep(Duration::ZERO)),
+            }),
+        }
+    }
+}
+
+impl<I, IO, IE> Stream for LimitedIncoming<I>
+where
+    I: Stream<Item = Result<IO, IE>>,
+{
+    type Item = Result<LimitedIo<IO>, IE>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        if let Some(rate) = this.rate {
+            if rate.rem == 0 {
+                futures_util::ready!(rate.window.as_mut().poll(cx));
+                rate.rem = rate.num;
+            }
+        }
+
+        if let Some(connections) = this.connections {
+            if connections.permit.is_none() {
+                let semaphore = &connections.semaphore;
+                let acquire = connections
+                    .acquire
+                    .get_or_insert_with(|| Box::pin(semaphore.clone().acquire_owned()));
+                let permit = futures_util::ready!(acquire.as_mut().poll(cx))
+                    .expect("the connection semaphore is never closed");
+                connections.acquire = None;
+                connections.permit = Some(permit);
+            }
+        }
+
+        let io = match futures_util::ready!(this.inner.poll_next(cx)) {
+            Some(Ok(io)) => io,
+            Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+            None => return Poll::Ready(None),
+        };
+
+        if let Some(rate) = this.rate {
+            // A window starts with the first connection accepted in it.
+            if rate.rem == rate.num {
+                let deadline = Instant::now() + rate.per;
+                rate.window.as_mut().reset(deadline);
+            }
+            rate.rem -= 1;
+        }
+
+        let permit = this
+            .connections
+            .as_mut()
+            .and_then(|connections| connections.permit.take());
+
+        Poll::Ready(Some(Ok(LimitedIo {
+            inner: io,
+            _permit: permit,
+        })))
+    }
+}
+
+/// A connection that frees its place under the connection limit when it is dropped.
+#[pin_project]
+#[derive(Debug)]
+pub(crate) struct LimitedIo<IO> {
+    #[pin]
+    inner: IO,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<IO: Connected> Connected for LimitedIo<IO> {
+    type ConnectInfo = IO::ConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.inner.connect_info()
+    }
+}
+
+impl<IO: AsyncRead> AsyncRead for LimitedIo<IO> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+}
+
+impl<IO: AsyncWrite> AsyncWrite for LimitedIo<IO> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{stream, FutureExt, StreamExt};
+    use std::convert::Infallible;
+
+    fn incoming(n: usize) -> impl Stream<Item = Result<usize, Infallible>> {
+        stream::iter((0..n).map(Ok))
+    }
+
+    #[tokio::test]
+    async fn waits_for_connections_to_close() {
+        let mut incoming = Box::pin(LimitedIncoming::new(incoming(3), Some(2), None));
+
+        let first = incoming.next().await.unwrap().unwrap();
+        let _second = incoming.next().await.unwrap().unwrap();
+        assert!(incoming.next().now_or_never().is_none());
+
+        drop(first);
+        assert_eq!(incoming.next().await.unwrap().unwrap().inner, 2);
+    }
+
+    #[tokio::test]
+    async fn limits_the_accept_rate() {
+        let per = Duration::from_millis(200);
+        let mut incoming = Box::pin(LimitedIncoming::new(incoming(3), None, Some((2, per))));
+        let start = Instant::now();
+
+        incoming.next().await.unwrap().unwrap();
+        incoming.next().await.unwrap().unwrap();
+        assert!(start.elapsed() < per);
+
+        incoming.next().await.unwrap().unwrap();
+        assert!(start.elapsed() >= per);
+    }
+}
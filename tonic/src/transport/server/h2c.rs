@@ -0,0 +1,98 @@
+use super::shutdown::ShutdownExec;
+use super::{BoxHttpBody, BoxService};
+use crate::transport::service::h2c::H2cIo;
+use futures_util::future::{self, Either, FutureExt, TryFutureExt};
+use http::{header, HeaderMap, Method, Request, Response, StatusCode, Version};
+use http_body::Body as _;
+use hyper::{rt::Executor, server::conn::Http, Body};
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+/// Upgrades HTTP/1.1 connections to HTTP/2 when asked to with `Upgrade: h2c`.
+///
+/// Only `OPTIONS` requests without a body are upgraded, as the response to the upgrade
+/// request is always an empty `204 No Content`. Upgrade requests with other methods are
+/// served over HTTP/1.1, which RFC 7540 allows.
+pub(super) struct H2cUpgrade {
+    inner: BoxService,
+    /// The service of the connection once it is upgraded.
+    upgraded: Option<BoxService>,
+    http: Http<ShutdownExec>,
+    exec: ShutdownExec,
+}
+
+impl H2cUpgrade {
+    pub(super) fn new(
+        inner: BoxService,
+        upgraded: BoxService,
+        http: Http<ShutdownExec>,
+        exec: ShutdownExec,
+    ) -> Self {
+        Self {
+            inner,
+            upgraded: Some(upgraded),
+            http,
+            exec,
+        }
+    }
+}
+
+impl Service<Request<Body>> for H2cUpgrade {
+    type Response = Response<BoxHttpBody>;
+    type Error = crate::Error;
+    type Future = Either<
+        <BoxService as Service<Request<Body>>>::Future,
+        future::Ready<Result<Self::Response, Self::Error>>,
+    >;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        if !is_upgrade(&req) {
+            return Either::Left(self.inner.call(req));
+        }
+        let svc = match self.upgraded.take() {
+            Some(svc) => svc,
+            None => return Either::Left(self.inner.call(req)),
+        };
+
+        let http = self.http.clone();
+        // `hyper` only hands out the connection once the switching response is sent on it.
+        let conn = hyper::upgrade::on(&mut req)
+            .and_then(move |upgraded| http.serve_connection(H2cIo::server(upgraded), svc));
+        self.exec.execute(conn.map(|res| {
+            if let Err(e) = res {
+                tracing::debug!("h2c connection error: {}", e);
+            }
+        }));
+
+        let mut res = Response::new(BoxHttpBody::default());
+        *res.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+        res.headers_mut()
+            .insert(header::CONNECTION, "Upgrade".parse().unwrap());
+        res.headers_mut()
+            .insert(header::UPGRADE, "h2c".parse().unwrap());
+        Either::Right(future::ok(res))
+    }
+}
+
+fn is_upgrade(req: &Request<Body>) -> bool {
+    req.version() == Version::HTTP_11
+        && req.method() == Method::OPTIONS
+        && req.body().is_end_stream()
+        && has_token(req.headers(), header::UPGRADE, "h2c")
+        && has_token(req.headers(), header::CONNECTION, "upgrade")
+        && has_token(req.headers(), header::CONNECTION, "http2-settings")
+        // The upgrade must be refused unless there is exactly one `HTTP2-Settings` header.
+        && req.headers().get_all("http2-settings").iter().count() == 1
+}
+
+fn has_token(headers: &HeaderMap, name: header::HeaderName, token: &str) -> bool {
+    headers.get_all(name).iter().any(|value| {
+        matches!(value.to_str(), Ok(value) if value
+            .split(',')
+            .any(|t| t.trim().eq_ignore_ascii_case(token)))
+    })
+}
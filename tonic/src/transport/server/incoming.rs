@@ -14,10 +14,10 @@ use std::{
 };
 use tokio::{
     io::{AsyncRead, AsyncWrite},
-    net::TcpListener,
+    net::{TcpListener, TcpSocket},
 };
 
-#[cfg(not(feature = "tls"))]
+#[cfg(not(any(feature = "tls", feature = "tls-native")))]
 pub(crate) fn tcp_incoming<IO, IE, L>(
     incoming: impl Stream<Item = Result<IO, IE>>,
     _server: Server<L>,
@@ -29,7 +29,7 @@ where
     incoming.err_into().map_ok(ServerIo::new_io)
 }
 
-#[cfg(feature = "tls")]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 pub(crate) fn tcp_incoming<IO, IE, L>(
     incoming: impl Stream<Item = Result<IO, IE>>,
     server: Server<L>,
@@ -41,7 +41,7 @@ where
     async_stream::try_stream! {
         futures_util::pin_mut!(incoming);
 
-        #[cfg(feature = "tls")]
+        #[cfg(any(feature = "tls", feature = "tls-native"))]
         let mut tasks = futures_util::stream::futures_unordered::FuturesUnordered::new();
 
         loop {
@@ -77,7 +77,7 @@ where
     }
 }
 
-#[cfg(feature = "tls")]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 async fn select<IO, IE>(
     incoming: &mut (impl Stream<Item = Result<IO, IE>> + Unpin),
     tasks: &mut futures_util::stream::futures_unordered::FuturesUnordered<
@@ -116,7 +116,7 @@ where
     }
 }
 
-#[cfg(feature = "tls")]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 enum SelectOutput<A> {
     Incoming(A),
     Io(ServerIo<A>),
@@ -165,6 +165,7 @@ impl TcpIncoming {
     ///    .serve_with_incoming(tinc);
     /// # Ok(())
     /// # }
+    /// ```
     pub fn new(
         addr: SocketAddr,
         nodelay: bool,
@@ -176,7 +177,55 @@ impl TcpIncoming {
         Ok(TcpIncoming { inner })
     }
 
+    /// Binds a listener like [`TcpIncoming::new`], setting the buffer sizes that accepted
+    /// sockets inherit from it.
+    pub(crate) fn bind(
+        addr: SocketAddr,
+        nodelay: bool,
+        keepalive: Option<Duration>,
+        send_buffer_size: Option<usize>,
+        recv_buffer_size: Option<usize>,
+    ) -> Result<Self, crate::Error> {
+        if send_buffer_size.is_none() && recv_buffer_size.is_none() {
+            return Self::new(addr, nodelay, keepalive);
+        }
+
+        let socket = if addr.is_ipv4() {
+            TcpSocket::new_v4()?
+        } else {
+            TcpSocket::new_v6()?
+        };
+        // Matches `std::net::TcpListener::bind`, which `TcpIncoming::new` goes through.
+        #[cfg(unix)]
+        socket.set_reuseaddr(true)?;
+        if let Some(size) = send_buffer_size {
+            socket.set_send_buffer_size(u32::try_from(size).unwrap_or(u32::MAX))?;
+        }
+        if let Some(size) = recv_buffer_size {
+            socket.set_recv_buffer_size(u32::try_from(size).unwrap_or(u32::MAX))?;
+        }
+        socket.bind(addr)?;
+
+        Self::from_listener(socket.listen(1024)?, nodelay, keepalive)
+    }
+
     /// Creates a new `TcpIncoming` from an existing `tokio::net::TcpListener`.
+    ///
+    /// This serves a socket bound by someone else, such as systemd socket activation or
+    /// `listenfd`, which hand it over as a `std::net::TcpListener`:
+    ///
+    /// ```no_run
+    /// # use tonic::transport::server::TcpIncoming;
+    /// # fn listener_from_systemd() -> std::net::TcpListener { unimplemented!() }
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// let std_listener = listener_from_systemd();
+    /// std_listener.set_nonblocking(true)?;
+    ///
+    /// let listener = tokio::net::TcpListener::from_std(std_listener)?;
+    /// let incoming = TcpIncoming::from_listener(listener, true, None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn from_listener(
         listener: TcpListener,
         nodelay: bool,
@@ -187,6 +236,11 @@ impl TcpIncoming {
         inner.set_keepalive(keepalive);
         Ok(TcpIncoming { inner })
     }
+
+    /// Returns the local address that this incoming is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.inner.local_addr()
+    }
 }
 
 impl Stream for TcpIncoming {
@@ -209,4 +263,40 @@ mod tests {
         }
         let _t3 = TcpIncoming::new(addr, true, None).unwrap();
     }
+
+    #[tokio::test]
+    async fn accepts_on_a_pre_bound_listener() {
+        use tokio_stream::StreamExt;
+
+        let std_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        std_listener.set_nonblocking(true).unwrap();
+        let addr = std_listener.local_addr().unwrap();
+
+        let listener = tokio::net::TcpListener::from_std(std_listener).unwrap();
+        let mut incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        assert_eq!(incoming.local_addr(), addr);
+
+        let client = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let stream = incoming.next().await.unwrap().unwrap();
+        assert_eq!(stream.remote_addr(), client.local_addr().unwrap());
+    }
+
+    #[tokio::test]
+    #[cfg(target_os = "linux")]
+    async fn bind_sets_buffer_sizes_of_accepted_sockets() {
+        use tokio_stream::StreamExt;
+
+        let addr = "127.0.0.1:0".parse().unwrap();
+        let mut incoming = TcpIncoming::bind(addr, true, None, Some(40_000), Some(40_000)).unwrap();
+
+        let _client = tokio::net::TcpStream::connect(incoming.local_addr())
+            .await
+            .unwrap();
+        let stream = incoming.next().await.unwrap().unwrap().into_inner();
+        let socket = tokio::net::TcpSocket::from_std_stream(stream.into_std().unwrap());
+
+        // Linux doubles the requested size to account for bookkeeping overhead.
+        assert_eq!(socket.send_buffer_size().unwrap(), 80_000);
+        assert_eq!(socket.recv_buffer_size().unwrap(), 80_000);
+    }
 }
@@ -0,0 +1,241 @@
+use crate::Status;
+use futures_util::ready;
+use http::{Request, Response};
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+use tokio::sync::{AcquireError, OwnedSemaphorePermit, Semaphore};
+use tower::Service;
+
+type Acquire = Pin<Box<dyn Future<Output = Result<OwnedSemaphorePermit, AcquireError>> + Send>>;
+
+/// The limit on requests in flight that is shared by all connections of a server.
+#[derive(Debug)]
+pub(crate) struct Limit {
+    semaphore: Arc<Semaphore>,
+    queued: AtomicUsize,
+    max_queued: usize,
+}
+
+impl Limit {
+    pub(crate) fn new(max_in_flight: usize, max_queued: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_in_flight)),
+            queued: AtomicUsize::new(0),
+            max_queued,
+        }
+    }
+
+    fn rejection(&self) -> Status {
+        if self.max_queued == 0 {
+            Status::resource_exhausted("too many requests in flight")
+        } else {
+            Status::unavailable("too many requests queued")
+        }
+    }
+}
+
+/// A place in the queue of requests waiting for the limit.
+pub(crate) struct QueueSlot(Arc<Limit>);
+
+impl QueueSlot {
+    fn take(limit: &Arc<Limit>) -> Option<Self> {
+        let mut queued = limit.queued.load(Ordering::Relaxed);
+        loop {
+            if queued >= limit.max_queued {
+                return None;
+            }
+
+            match limit.queued.compare_exchange_weak(
+                queued,
+                queued + 1,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Some(QueueSlot(limit.clone())),
+                Err(actual) => queued = actual,
+            }
+        }
+    }
+}
+
+impl Drop for QueueSlot {
+    fn drop(&mut self) {
+        self.0.queued.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Middleware that limits the number of requests in flight across all connections of a
+/// server.
+///
+/// Requests over the limit wait in a queue until one finishes. Once the queue is full they
+/// are rejected: with `RESOURCE_EXHAUSTED` if there is no queue, and with `UNAVAILABLE`
+/// otherwise. A request counts against the limit until its response body has been
+/// dropped, so streaming responses are included. Without a limit every request is let
+/// through.
+#[derive(Debug, Clone)]
+pub(crate) struct GlobalConcurrencyLimit<S> {
+    inner: S,
+    limit: Option<Arc<Limit>>,
+}
+
+impl<S> GlobalConcurrencyLimit<S> {
+    pub(crate) fn new(inner: S, limit: Option<Arc<Limit>>) -> Self {
+        Self { inner, limit }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for GlobalConcurrencyLimit<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone,
+    S::Error: Into<crate::Error>,
+{
+    type Response = Response<PermitBody<ResBody>>;
+    type Error = crate::Error;
+    type Future = ResponseFuture<S, Request<ReqBody>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let limit = match &self.limit {
+            Some(limit) => limit,
+            None => {
+                return ResponseFuture::Called {
+                    inner: self.inner.call(req),
+                    permit: None,
+                }
+            }
+        };
+
+        if let Ok(permit) = limit.semaphore.clone().try_acquire_owned() {
+            return ResponseFuture::Called {
+                inner: self.inner.call(req),
+                permit: Some(permit),
+            };
+        }
+
+        match QueueSlot::take(limit) {
+            Some(slot) => {
+                // The service is ready, so it is the one that has to handle the request.
+                let clone = self.inner.clone();
+                let svc = mem::replace(&mut self.inner, clone);
+
+                ResponseFuture::Queued {
+                    acquire: Box::pin(limit.semaphore.clone().acquire_owned()),
+                    svc: Some(svc),
+                    req: Some(req),
+                    _slot: slot,
+                }
+            }
+            None => ResponseFuture::Rejected(Some(limit.rejection())),
+        }
+    }
+}
+
+#[pin_project(project = ResponseFutureProj)]
+pub(crate) enum ResponseFuture<S, Req>
+where
+    S: Service<Req>,
+{
+    Called {
+        #[pin]
+        inner: S::Future,
+        permit: Option<OwnedSemaphorePermit>,
+    },
+    Queued {
+        acquire: Acquire,
+        svc: Option<S>,
+        req: Option<Req>,
+        _slot: QueueSlot,
+    },
+    Rejected(Option<Status>),
+}
+
+impl<S, Req, ResBody> Future for ResponseFuture<S, Req>
+where
+    S: Service<Req, Response = Response<ResBody>>,
+    S::Error: Into<crate::Error>,
+{
+    type Output = Result<Response<PermitBody<ResBody>>, crate::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let next = match self.as_mut().project() {
+                ResponseFutureProj::Called { inner, permit } => {
+                    let response = ready!(inner.poll(cx)).map_err(Into::into)?;
+                    let permit = permit.take();
+                    return Poll::Ready(Ok(response.map(|inner| PermitBody {
+                        inner,
+                        _permit: permit,
+                    })));
+                }
+                ResponseFutureProj::Queued {
+                    acquire, svc, req, ..
+                } => {
+                    let permit = ready!(acquire.as_mut().poll(cx))
+                        .expect("the semaphore of a limit is never closed");
+                    let svc = svc.take().expect("polled after completion");
+                    let req = req.take().expect("polled after completion");
+
+                    ResponseFuture::Called {
+                        inner: { svc }.call(req),
+                        permit: Some(permit),
+                    }
+                }
+                ResponseFutureProj::Rejected(status) => {
+                    let status = status.take().expect("polled after completion");
+                    return Poll::Ready(Err(status.into()));
+                }
+            };
+            self.set(next);
+        }
+    }
+}
+
+/// A response body that holds on to the permit of its request.
+#[pin_project]
+pub(crate) struct PermitBody<B> {
+    #[pin]
+    inner: B,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<B> http_body::Body for PermitBody<B>
+where
+    B: http_body::Body,
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        self.project().inner.poll_data(cx)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        self.project().inner.poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
@@ -0,0 +1,65 @@
+use crate::transport::channel::MemoryConnector;
+use futures_core::Stream;
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::{io::DuplexStream, sync::mpsc};
+
+/// A stream of in-memory connections, for serving clients in the same process without
+/// binding a port.
+///
+/// This is mostly useful in tests:
+///
+/// ```
+/// # use tonic::transport::{server::MemoryIncoming, Endpoint, Server};
+/// # async fn serve(router: tonic::transport::server::Router) -> Result<(), Box<dyn std::error::Error>> {
+/// let incoming = MemoryIncoming::new();
+/// let connector = incoming.connector();
+///
+/// tokio::spawn(router.serve_with_incoming(incoming));
+///
+/// let channel = Endpoint::from_static("http://localhost")
+///     .connect_with_connector(connector)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Like a listener, the stream does not end on its own. A [shutdown signal] stops serving
+/// it, after which connecting fails.
+///
+/// [shutdown signal]: super::Router::serve_with_incoming_shutdown
+#[derive(Debug)]
+pub struct MemoryIncoming {
+    tx: mpsc::UnboundedSender<DuplexStream>,
+    rx: mpsc::UnboundedReceiver<DuplexStream>,
+}
+
+impl MemoryIncoming {
+    /// Creates a stream without connections.
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self { tx, rx }
+    }
+
+    /// Returns a connector whose connections are yielded by this stream.
+    pub fn connector(&self) -> MemoryConnector {
+        MemoryConnector::new(self.tx.clone())
+    }
+}
+
+impl Default for MemoryIncoming {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stream for MemoryIncoming {
+    type Item = io::Result<DuplexStream>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx).map(|io| io.map(Ok))
+    }
+}
@@ -1,38 +1,59 @@
 //! Server implementation and builder.
 
 mod conn;
+mod connection_limit;
+mod h2c;
 mod incoming;
+mod limit;
+mod memory;
+#[cfg(windows)]
+mod named_pipe;
 mod recover_error;
-#[cfg(feature = "tls")]
-#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+mod shutdown;
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "tls", feature = "tls-native"))))]
 mod tls;
 #[cfg(unix)]
 mod unix;
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+mod x509;
 
 pub use super::service::Routes;
 pub use crate::server::NamedService;
 pub use conn::{Connected, TcpConnectInfo};
-#[cfg(feature = "tls")]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 pub use tls::ServerTlsConfig;
 
-#[cfg(feature = "tls")]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 pub use conn::TlsConnectInfo;
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "tls", feature = "tls-native"))))]
+pub use x509::SubjectAltName;
 
-#[cfg(feature = "tls")]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 use super::service::TlsAcceptor;
 
 #[cfg(unix)]
 pub use unix::UdsConnectInfo;
 
+#[cfg(windows)]
+pub use named_pipe::NamedPipeIncoming;
+
 pub use incoming::TcpIncoming;
+pub use memory::MemoryIncoming;
 
+#[cfg(all(feature = "tls-native", not(feature = "tls")))]
+pub(crate) use super::service::TlsStream;
 #[cfg(feature = "tls")]
 pub(crate) use tokio_rustls::server::TlsStream;
 
-#[cfg(feature = "tls")]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 use crate::transport::Error;
 
+use self::connection_limit::LimitedIncoming;
+use self::limit::{GlobalConcurrencyLimit, Limit};
 use self::recover_error::RecoverError;
+use self::shutdown::ShutdownExec;
 use super::service::{GrpcTimeout, ServerIo};
 use crate::body::BoxBody;
 use bytes::Bytes;
@@ -40,7 +61,10 @@ use futures_core::Stream;
 use futures_util::{future, ready};
 use http::{Request, Response};
 use http_body::Body as _;
-use hyper::{server::accept, Body};
+use hyper::{
+    server::{accept, conn::Http},
+    Body,
+};
 use pin_project::pin_project;
 use std::{
     convert::Infallible,
@@ -80,19 +104,28 @@ const DEFAULT_HTTP2_KEEPALIVE_TIMEOUT_SECS: u64 = 20;
 pub struct Server<L = Identity> {
     trace_interceptor: Option<TraceInterceptor>,
     concurrency_limit: Option<usize>,
+    global_concurrency_limit: Option<usize>,
+    max_queued_requests: Option<usize>,
+    max_connections: Option<usize>,
+    accept_rate_limit: Option<(u64, Duration)>,
     timeout: Option<Duration>,
-    #[cfg(feature = "tls")]
+    #[cfg(any(feature = "tls", feature = "tls-native"))]
     tls: Option<TlsAcceptor>,
     init_stream_window_size: Option<u32>,
     init_connection_window_size: Option<u32>,
     max_concurrent_streams: Option<u32>,
     tcp_keepalive: Option<Duration>,
     tcp_nodelay: bool,
+    tcp_send_buffer_size: Option<usize>,
+    tcp_recv_buffer_size: Option<usize>,
     http2_keepalive_interval: Option<Duration>,
     http2_keepalive_timeout: Option<Duration>,
     http2_adaptive_window: Option<bool>,
     max_frame_size: Option<u32>,
+    http2_max_header_list_size: Option<u32>,
     accept_http1: bool,
+    accept_h2c_upgrade: bool,
+    shutdown_grace_period: Option<Duration>,
     service_builder: ServiceBuilder<L>,
 }
 
@@ -101,19 +134,28 @@ impl Default for Server<Identity> {
         Self {
             trace_interceptor: None,
             concurrency_limit: None,
+            global_concurrency_limit: None,
+            max_queued_requests: None,
+            max_connections: None,
+            accept_rate_limit: None,
             timeout: None,
-            #[cfg(feature = "tls")]
+            #[cfg(any(feature = "tls", feature = "tls-native"))]
             tls: None,
             init_stream_window_size: None,
             init_connection_window_size: None,
             max_concurrent_streams: None,
             tcp_keepalive: None,
-            tcp_nodelay: false,
+            tcp_nodelay: true,
+            tcp_send_buffer_size: None,
+            tcp_recv_buffer_size: None,
             http2_keepalive_interval: None,
             http2_keepalive_timeout: None,
             http2_adaptive_window: None,
             max_frame_size: None,
+            http2_max_header_list_size: None,
             accept_http1: false,
+            accept_h2c_upgrade: false,
+            shutdown_grace_period: None,
             service_builder: Default::default(),
         }
     }
@@ -143,8 +185,11 @@ impl Server {
 
 impl<L> Server<L> {
     /// Configure TLS for this server.
-    #[cfg(feature = "tls")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+    ///
+    /// Connections accepted by the server are then required to use TLS. This fails if the
+    /// identity or CA certificate of the config cannot be loaded.
+    #[cfg(any(feature = "tls", feature = "tls-native"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "tls", feature = "tls-native"))))]
     pub fn tls_config(self, tls_config: ServerTlsConfig) -> Result<Self, Error> {
         Ok(Server {
             tls: Some(tls_config.tls_acceptor().map_err(Error::from_source)?),
@@ -170,8 +215,120 @@ impl<L> Server<L> {
         }
     }
 
+    /// Set the limit on the number of requests the server handles at once, across all
+    /// connections.
+    ///
+    /// Requests over the limit fail right away with `RESOURCE_EXHAUSTED`, so that clients
+    /// can back off or try another server, unless they may wait for a request in flight to
+    /// complete with [`Server::max_queued_requests`]. A request counts against the limit
+    /// until its response, including a streamed one, is complete.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use tower_service::Service;
+    /// # let builder = Server::builder();
+    /// builder.concurrency_limit(1000);
+    /// ```
+    #[must_use]
+    pub fn concurrency_limit(self, limit: usize) -> Self {
+        Server {
+            global_concurrency_limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Set how many requests may wait for the [`concurrency_limit`] before the server starts
+    /// shedding load.
+    ///
+    /// Requests over the concurrency limit wait for a request in flight to complete, as long
+    /// as fewer than `limit` requests are waiting already. Requests beyond that fail right
+    /// away with `UNAVAILABLE`, so that latency stays bounded under overload and clients
+    /// can retry on another server. This has no effect without a concurrency limit.
+    ///
+    /// [`concurrency_limit`]: Server::concurrency_limit
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use tower_service::Service;
+    /// # let builder = Server::builder();
+    /// builder.concurrency_limit(1000).max_queued_requests(100);
+    /// ```
+    #[must_use]
+    pub fn max_queued_requests(self, limit: usize) -> Self {
+        Server {
+            max_queued_requests: Some(limit),
+            ..self
+        }
+    }
+
+    /// Set the maximum number of connections the server keeps open at once.
+    ///
+    /// While `limit` connections are open no more are accepted, so new ones wait in the
+    /// listen backlog of the OS until an open connection is closed. This bounds the file
+    /// descriptors and memory the server uses, for example during a reconnect storm.
+    /// Connections that are still doing a TLS handshake count towards the limit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use tower_service::Service;
+    /// # let builder = Server::builder();
+    /// builder.max_connections(10_000);
+    /// ```
+    #[must_use]
+    pub fn max_connections(self, limit: usize) -> Self {
+        Server {
+            max_connections: Some(limit),
+            ..self
+        }
+    }
+
+    /// Limit the server to accepting `num` connections per `per` period.
+    ///
+    /// Connections over the rate wait in the listen backlog of the OS until the next
+    /// period, which spreads out the handshakes of many clients reconnecting at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use tower_service::Service;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.accept_rate_limit(100, Duration::from_secs(1));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` is zero.
+    #[must_use]
+    pub fn accept_rate_limit(self, num: u64, per: Duration) -> Self {
+        assert!(
+            num > 0,
+            "the accept rate must allow at least one connection"
+        );
+
+        Server {
+            accept_rate_limit: Some((num, per)),
+            ..self
+        }
+    }
+
     /// Set a timeout on for all request handlers.
     ///
+    /// This applies whether or not the client sent a `grpc-timeout`, if it sent a shorter
+    /// one that is used instead. Calls that exceed it fail with `DEADLINE_EXCEEDED`, for
+    /// streaming responses this also ends the stream. The handler, or the stream it
+    /// returned, is dropped at that point, so it stops running at its next `.await`.
+    /// Handlers can read the deadline with [`Request::deadline`].
+    ///
+    /// [`Request::deadline`]: crate::Request::deadline
+    ///
     /// # Example
     ///
     /// ```
@@ -189,9 +346,39 @@ impl<L> Server<L> {
         }
     }
 
+    /// Sets how long a graceful shutdown waits for in-flight requests.
+    ///
+    /// When the signal passed to [`Router::serve_with_shutdown`] resolves, the server stops
+    /// accepting connections and sends a `GOAWAY` frame on the open ones, so that clients
+    /// start no new requests on them. It then waits for the requests in flight to finish.
+    /// Once this period has passed, the remaining connections are closed and the server
+    /// returns.
+    ///
+    /// Default is to wait for as long as it takes (`None`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.shutdown_grace_period(Duration::from_secs(10));
+    /// ```
+    #[must_use]
+    pub fn shutdown_grace_period(self, period: impl Into<Option<Duration>>) -> Self {
+        Server {
+            shutdown_grace_period: period.into(),
+            ..self
+        }
+    }
+
     /// Sets the [`SETTINGS_INITIAL_WINDOW_SIZE`][spec] option for HTTP2
     /// stream-level flow control.
     ///
+    /// This bounds how many bytes of a single stream the peer may send before waiting for
+    /// this side to acknowledge them, so a stream can move at most one window per round
+    /// trip. Raise it when sending large messages over high-latency links.
+    ///
     /// Default is 65,535
     ///
     /// [spec]: https://http2.github.io/http2-spec/#SETTINGS_INITIAL_WINDOW_SIZE
@@ -205,6 +392,9 @@ impl<L> Server<L> {
 
     /// Sets the max connection-level flow control for HTTP2
     ///
+    /// This bounds the bytes in flight across all streams of a connection, so it should be
+    /// at least as large as the [`initial_stream_window_size`](Server::initial_stream_window_size).
+    ///
     /// Default is 65,535
     #[must_use]
     pub fn initial_connection_window_size(self, sz: impl Into<Option<u32>>) -> Self {
@@ -235,6 +425,10 @@ impl<L> Server<L> {
     /// The timeout for receiving an acknowledgement of the keepalive ping
     /// can be set with [`Server::http2_keepalive_timeout`].
     ///
+    /// Pings are sent whether or not the connection has open streams, so clients that went
+    /// away without closing their connection, for example behind a NAT or an L4 load
+    /// balancer, are detected and their connections closed.
+    ///
     /// Default is no HTTP2 keepalive (`None`)
     ///
     #[must_use]
@@ -261,8 +455,9 @@ impl<L> Server<L> {
     }
 
     /// Sets whether to use an adaptive flow control. Defaults to false.
-    /// Enabling this will override the limits set in http2_initial_stream_window_size and
-    /// http2_initial_connection_window_size.
+    /// Enabling this will override the limits set in
+    /// [`initial_stream_window_size`](Server::initial_stream_window_size) and
+    /// [`initial_connection_window_size`](Server::initial_connection_window_size).
     #[must_use]
     pub fn http2_adaptive_window(self, enabled: Option<bool>) -> Self {
         Server {
@@ -288,6 +483,9 @@ impl<L> Server<L> {
     }
 
     /// Set the value of `TCP_NODELAY` option for accepted connections. Enabled by default.
+    ///
+    /// gRPC messages are usually small and latency sensitive, so Nagle's algorithm is
+    /// disabled unless `false` is passed here.
     #[must_use]
     pub fn tcp_nodelay(self, enabled: bool) -> Self {
         Server {
@@ -296,6 +494,32 @@ impl<L> Server<L> {
         }
     }
 
+    /// Sets the size of the `SO_SNDBUF` buffer of accepted connections.
+    ///
+    /// This only applies to [`serve`](Router::serve) and
+    /// [`serve_with_shutdown`](Router::serve_with_shutdown), which bind the listener
+    /// themselves. Default is the operating system default (`None`).
+    #[must_use]
+    pub fn tcp_send_buffer_size(self, size: impl Into<Option<usize>>) -> Self {
+        Server {
+            tcp_send_buffer_size: size.into(),
+            ..self
+        }
+    }
+
+    /// Sets the size of the `SO_RCVBUF` buffer of accepted connections.
+    ///
+    /// This only applies to [`serve`](Router::serve) and
+    /// [`serve_with_shutdown`](Router::serve_with_shutdown), which bind the listener
+    /// themselves. Default is the operating system default (`None`).
+    #[must_use]
+    pub fn tcp_recv_buffer_size(self, size: impl Into<Option<usize>>) -> Self {
+        Server {
+            tcp_recv_buffer_size: size.into(),
+            ..self
+        }
+    }
+
     /// Sets the maximum frame size to use for HTTP2.
     ///
     /// Passing `None` will do nothing.
@@ -309,6 +533,23 @@ impl<L> Server<L> {
         }
     }
 
+    /// Sets the max size of received header frames, which carry the metadata
+    /// of a request.
+    ///
+    /// Requests with larger metadata are failed with a `431 Request Header
+    /// Fields Too Large` response, which clients see as a
+    /// [`Code::ResourceExhausted`](crate::Code::ResourceExhausted) status,
+    /// while the connection stays open for other requests.
+    ///
+    /// If not set, will default from underlying transport.
+    #[must_use]
+    pub fn http2_max_header_list_size(self, max: impl Into<Option<u32>>) -> Self {
+        Server {
+            http2_max_header_list_size: max.into(),
+            ..self
+        }
+    }
+
     /// Allow this server to accept http1 requests.
     ///
     /// Accepting http1 requests is only useful when developing `grpc-web`
@@ -325,6 +566,25 @@ impl<L> Server<L> {
         }
     }
 
+    /// Allow clients to upgrade HTTP/1.1 connections to HTTP/2 with `Upgrade: h2c`.
+    ///
+    /// This lets gRPC reach the server through intermediaries that only pass plaintext
+    /// HTTP/1.1 on, see [`Endpoint::h2c_upgrade`](crate::transport::Endpoint::h2c_upgrade).
+    /// Clients can still start HTTP/2 right away, and requests that don't ask for an upgrade
+    /// are served over HTTP/1.1 as if [`accept_http1`](Server::accept_http1) was set.
+    ///
+    /// Upgraded connections are not waited for by graceful shutdown, but they are closed
+    /// when the [`shutdown_grace_period`](Server::shutdown_grace_period) runs out.
+    ///
+    /// Default is `false`.
+    #[must_use]
+    pub fn accept_h2c_upgrade(self, accept_h2c_upgrade: bool) -> Self {
+        Server {
+            accept_h2c_upgrade,
+            ..self
+        }
+    }
+
     /// Intercept inbound headers and add a [`tracing::Span`] to each response future.
     #[must_use]
     pub fn trace_fn<F>(self, f: F) -> Self
@@ -341,6 +601,11 @@ impl<L> Server<L> {
     ///
     /// This will clone the `Server` builder and create a router that will
     /// route around different services.
+    ///
+    /// Requests are routed to a service by the first segment of their path,
+    /// `/{package.Service}/{Method}`, where the service name is
+    /// [`NamedService::NAME`]. Requests for services that were not added are
+    /// answered with [`Code::Unimplemented`](crate::Code::Unimplemented).
     pub fn add_service<S>(&mut self, svc: S) -> Router<L>
     where
         S: Service<Request<Body>, Response = Response<BoxBody>, Error = Infallible>
@@ -359,9 +624,9 @@ impl<L> Server<L> {
     /// This will clone the `Server` builder and create a router that will
     /// route around different services.
     ///
-    /// # Note
-    /// Even when the argument given is `None` this will capture *all* requests to this service name.
-    /// As a result, one cannot use this to toggle between two identically named implementations.
+    /// This is useful for services that are only enabled by configuration. When
+    /// `svc` is `None` nothing is added, and requests to the service are answered
+    /// with [`Code::Unimplemented`](crate::Code::Unimplemented).
     pub fn add_optional_service<S>(&mut self, svc: Option<S>) -> Router<L>
     where
         S: Service<Request<Body>, Response = Response<BoxBody>, Error = Infallible>
@@ -395,6 +660,12 @@ impl<L> Server<L> {
     /// Note that timeouts should be set using [`Server::timeout`]. `TimeoutLayer` is only used
     /// here as an example.
     ///
+    /// Errors returned by the layers are sent to the client as a status, see
+    /// [`Status::from_error`]. Tower's timeout and load shedding errors become
+    /// `DEADLINE_EXCEEDED` and `UNAVAILABLE`.
+    ///
+    /// [`Status::from_error`]: crate::Status::from_error
+    ///
     /// You can build more complex layers using [`ServiceBuilder`]. Those layers can include
     /// [interceptors]:
     ///
@@ -432,6 +703,9 @@ impl<L> Server<L> {
     /// Server::builder().layer(layer);
     /// ```
     ///
+    /// To wrap only some of the services, or some of their methods, see
+    /// [`service::layered`](crate::service::layered).
+    ///
     /// [Tower]: https://github.com/tower-rs/tower
     /// [`Layer`]: tower::layer::Layer
     /// [eco]: https://github.com/tower-rs
@@ -442,19 +716,28 @@ impl<L> Server<L> {
             service_builder: self.service_builder.layer(new_layer),
             trace_interceptor: self.trace_interceptor,
             concurrency_limit: self.concurrency_limit,
+            global_concurrency_limit: self.global_concurrency_limit,
+            max_queued_requests: self.max_queued_requests,
+            max_connections: self.max_connections,
+            accept_rate_limit: self.accept_rate_limit,
             timeout: self.timeout,
-            #[cfg(feature = "tls")]
+            #[cfg(any(feature = "tls", feature = "tls-native"))]
             tls: self.tls,
             init_stream_window_size: self.init_stream_window_size,
             init_connection_window_size: self.init_connection_window_size,
             max_concurrent_streams: self.max_concurrent_streams,
             tcp_keepalive: self.tcp_keepalive,
             tcp_nodelay: self.tcp_nodelay,
+            tcp_send_buffer_size: self.tcp_send_buffer_size,
+            tcp_recv_buffer_size: self.tcp_recv_buffer_size,
             http2_keepalive_interval: self.http2_keepalive_interval,
             http2_keepalive_timeout: self.http2_keepalive_timeout,
             http2_adaptive_window: self.http2_adaptive_window,
             max_frame_size: self.max_frame_size,
+            http2_max_header_list_size: self.http2_max_header_list_size,
             accept_http1: self.accept_http1,
+            accept_h2c_upgrade: self.accept_h2c_upgrade,
+            shutdown_grace_period: self.shutdown_grace_period,
         }
     }
 
@@ -479,33 +762,62 @@ impl<L> Server<L> {
     {
         let trace_interceptor = self.trace_interceptor.clone();
         let concurrency_limit = self.concurrency_limit;
+        let max_queued_requests = self.max_queued_requests.unwrap_or(0);
+        let global_concurrency_limit = self
+            .global_concurrency_limit
+            .map(|limit| Arc::new(Limit::new(limit, max_queued_requests)));
         let init_connection_window_size = self.init_connection_window_size;
         let init_stream_window_size = self.init_stream_window_size;
         let max_concurrent_streams = self.max_concurrent_streams;
         let timeout = self.timeout;
         let max_frame_size = self.max_frame_size;
-        let http2_only = !self.accept_http1;
+        let http2_max_header_list_size = self.http2_max_header_list_size;
+        let http2_only = !(self.accept_http1 || self.accept_h2c_upgrade);
+        let accept_h2c_upgrade = self.accept_h2c_upgrade;
 
         let http2_keepalive_interval = self.http2_keepalive_interval;
         let http2_keepalive_timeout = self
             .http2_keepalive_timeout
             .unwrap_or_else(|| Duration::new(DEFAULT_HTTP2_KEEPALIVE_TIMEOUT_SECS, 0));
         let http2_adaptive_window = self.http2_adaptive_window;
+        let shutdown_grace_period = self.shutdown_grace_period;
 
         let svc = self.service_builder.service(svc);
 
+        let incoming = LimitedIncoming::new(incoming, self.max_connections, self.accept_rate_limit);
         let tcp = incoming::tcp_incoming(incoming, self);
         let incoming = accept::from_stream::<_, _, crate::Error>(tcp);
 
+        let (exec, close) = ShutdownExec::new();
+
+        let h2c = accept_h2c_upgrade.then(|| {
+            let mut http = Http::new().with_executor(exec.clone());
+            http.http2_only(true)
+                .http2_initial_connection_window_size(init_connection_window_size)
+                .http2_initial_stream_window_size(init_stream_window_size)
+                .http2_max_concurrent_streams(max_concurrent_streams)
+                .http2_keep_alive_interval(http2_keepalive_interval)
+                .http2_keep_alive_timeout(http2_keepalive_timeout)
+                .http2_adaptive_window(http2_adaptive_window.unwrap_or_default())
+                .http2_max_frame_size(max_frame_size);
+            if let Some(max) = http2_max_header_list_size {
+                http.http2_max_header_list_size(max);
+            }
+            (http, exec.clone())
+        });
+
         let svc = MakeSvc {
             inner: svc,
             concurrency_limit,
+            global_concurrency_limit,
             timeout,
             trace_interceptor,
+            h2c,
             _io: PhantomData,
         };
 
-        let server = hyper::Server::builder(incoming)
+        let mut server = hyper::Server::builder(incoming)
+            .executor(exec)
             .http2_only(http2_only)
             .http2_initial_connection_window_size(init_connection_window_size)
             .http2_initial_stream_window_size(init_stream_window_size)
@@ -515,12 +827,34 @@ impl<L> Server<L> {
             .http2_adaptive_window(http2_adaptive_window.unwrap_or_default())
             .http2_max_frame_size(max_frame_size);
 
+        if let Some(max) = http2_max_header_list_size {
+            server = server.http2_max_header_list_size(max);
+        }
+
         if let Some(signal) = signal {
-            server
-                .serve(svc)
-                .with_graceful_shutdown(signal)
-                .await
-                .map_err(super::Error::from_source)?
+            let (signaled_tx, signaled) = tokio::sync::oneshot::channel();
+            let signal = async move {
+                signal.await;
+                let _ = signaled_tx.send(());
+            };
+
+            let graceful = server.serve(svc).with_graceful_shutdown(signal);
+            futures_util::pin_mut!(graceful);
+
+            let grace_period = async move {
+                match (signaled.await, shutdown_grace_period) {
+                    (Ok(()), Some(period)) => tokio::time::sleep(period).await,
+                    _ => future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                res = &mut graceful => res.map_err(super::Error::from_source)?,
+                _ = grace_period => {
+                    tracing::debug!("shutdown grace period elapsed, closing connections");
+                    let _ = close.send(true);
+                }
+            }
         } else {
             server.serve(svc).await.map_err(super::Error::from_source)?;
         }
@@ -537,6 +871,10 @@ impl<L> Router<L> {
 
 impl<L> Router<L> {
     /// Add a new service to this router.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a service with the same [`NamedService::NAME`] was already added.
     pub fn add_service<S>(mut self, svc: S) -> Self
     where
         S: Service<Request<Body>, Response = Response<BoxBody>, Error = Infallible>
@@ -552,9 +890,13 @@ impl<L> Router<L> {
 
     /// Add a new optional service to this router.
     ///
-    /// # Note
-    /// Even when the argument given is `None` this will capture *all* requests to this service name.
-    /// As a result, one cannot use this to toggle between two identically named implementations.
+    /// When `svc` is `None` nothing is added, and requests to the service are
+    /// answered with [`Code::Unimplemented`](crate::Code::Unimplemented).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `svc` is `Some` and a service with the same [`NamedService::NAME`]
+    /// was already added.
     #[allow(clippy::type_complexity)]
     pub fn add_optional_service<S>(mut self, svc: Option<S>) -> Self
     where
@@ -585,8 +927,14 @@ impl<L> Router<L> {
         ResBody: http_body::Body<Data = Bytes> + Send + 'static,
         ResBody::Error: Into<crate::Error>,
     {
-        let incoming = TcpIncoming::new(addr, self.server.tcp_nodelay, self.server.tcp_keepalive)
-            .map_err(super::Error::from_source)?;
+        let incoming = TcpIncoming::bind(
+            addr,
+            self.server.tcp_nodelay,
+            self.server.tcp_keepalive,
+            self.server.tcp_send_buffer_size,
+            self.server.tcp_recv_buffer_size,
+        )
+        .map_err(super::Error::from_source)?;
         self.server
             .serve_with_shutdown::<_, _, future::Ready<()>, _, _, ResBody>(
                 self.routes.prepare(),
@@ -600,6 +948,9 @@ impl<L> Router<L> {
     /// on [tokio]'s default executor. And shutdown when the provided signal
     /// is received.
     ///
+    /// On shutdown, requests in flight are allowed to finish, for at most the
+    /// [`shutdown_grace_period`](Server::shutdown_grace_period) if one is set.
+    ///
     /// [`Server`]: struct.Server.html
     /// [tokio]: https://docs.rs/tokio
     pub async fn serve_with_shutdown<F: Future<Output = ()>, ResBody>(
@@ -615,8 +966,14 @@ impl<L> Router<L> {
         ResBody: http_body::Body<Data = Bytes> + Send + 'static,
         ResBody::Error: Into<crate::Error>,
     {
-        let incoming = TcpIncoming::new(addr, self.server.tcp_nodelay, self.server.tcp_keepalive)
-            .map_err(super::Error::from_source)?;
+        let incoming = TcpIncoming::bind(
+            addr,
+            self.server.tcp_nodelay,
+            self.server.tcp_keepalive,
+            self.server.tcp_send_buffer_size,
+            self.server.tcp_recv_buffer_size,
+        )
+        .map_err(super::Error::from_source)?;
         self.server
             .serve_with_shutdown(self.routes.prepare(), incoming, Some(signal))
             .await
@@ -625,6 +982,11 @@ impl<L> Router<L> {
     /// Consume this [`Server`] creating a future that will execute the server on
     /// the provided incoming stream of `AsyncRead + AsyncWrite`.
     ///
+    /// The streams must implement [`Connected`], as tokio's TCP and Unix streams do. A
+    /// listener that was bound elsewhere, such as by systemd socket activation, can be
+    /// served with [`TcpIncoming::from_listener`]. The TCP options of the server are not
+    /// applied to the streams.
+    ///
     /// [`Server`]: struct.Server.html
     pub async fn serve_with_incoming<I, IO, IE, ResBody>(
         self,
@@ -776,13 +1138,16 @@ impl<S> fmt::Debug for Svc<S> {
 
 struct MakeSvc<S, IO> {
     concurrency_limit: Option<usize>,
+    global_concurrency_limit: Option<Arc<Limit>>,
     timeout: Option<Duration>,
     inner: S,
     trace_interceptor: Option<TraceInterceptor>,
+    /// How to serve connections upgraded with `h2c`, if they are accepted.
+    h2c: Option<(Http<ShutdownExec>, ShutdownExec)>,
     _io: PhantomData<fn() -> IO>,
 }
 
-impl<S, ResBody, IO> Service<&ServerIo<IO>> for MakeSvc<S, IO>
+impl<S, ResBody, IO> MakeSvc<S, IO>
 where
     IO: Connected,
     S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
@@ -791,29 +1156,22 @@ where
     ResBody: http_body::Body<Data = Bytes> + Send + 'static,
     ResBody::Error: Into<crate::Error>,
 {
-    type Response = BoxService;
-    type Error = crate::Error;
-    type Future = future::Ready<Result<Self::Response, Self::Error>>;
-
-    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Ok(()).into()
-    }
-
-    fn call(&mut self, io: &ServerIo<IO>) -> Self::Future {
+    fn connection_service(&self, io: &ServerIo<IO>) -> BoxService {
         let conn_info = io.connect_info();
-
         let svc = self.inner.clone();
         let concurrency_limit = self.concurrency_limit;
+        let global_concurrency_limit = self.global_concurrency_limit.clone();
         let timeout = self.timeout;
         let trace_interceptor = self.trace_interceptor.clone();
 
         let svc = ServiceBuilder::new()
             .layer_fn(RecoverError::new)
+            .layer_fn(|s| GlobalConcurrencyLimit::new(s, global_concurrency_limit.clone()))
             .option_layer(concurrency_limit.map(ConcurrencyLimitLayer::new))
             .layer_fn(|s| GrpcTimeout::new(s, timeout))
             .service(svc);
 
-        let svc = ServiceBuilder::new()
+        ServiceBuilder::new()
             .layer(BoxService::layer())
             .map_request(move |mut request: Request<Body>| {
                 match &conn_info {
@@ -821,13 +1179,13 @@ where
                         request.extensions_mut().insert(inner.clone());
                     }
                     tower::util::Either::B(inner) => {
-                        #[cfg(feature = "tls")]
+                        #[cfg(any(feature = "tls", feature = "tls-native"))]
                         {
                             request.extensions_mut().insert(inner.clone());
                             request.extensions_mut().insert(inner.get_ref().clone());
                         }
 
-                        #[cfg(not(feature = "tls"))]
+                        #[cfg(not(any(feature = "tls", feature = "tls-native")))]
                         {
                             // just a type check to make sure we didn't forget to
                             // insert this into the extensions
@@ -841,7 +1199,39 @@ where
             .service(Svc {
                 inner: svc,
                 trace_interceptor,
-            });
+            })
+    }
+}
+
+impl<S, ResBody, IO> Service<&ServerIo<IO>> for MakeSvc<S, IO>
+where
+    IO: Connected,
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::Error> + Send,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<crate::Error>,
+{
+    type Response = BoxService;
+    type Error = crate::Error;
+    type Future = future::Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Ok(()).into()
+    }
+
+    fn call(&mut self, io: &ServerIo<IO>) -> Self::Future {
+        let svc = self.connection_service(io);
+
+        let svc = match &self.h2c {
+            Some((http, exec)) => BoxService::new(h2c::H2cUpgrade::new(
+                svc,
+                self.connection_service(io),
+                http.clone(),
+                exec.clone(),
+            )),
+            None => svc,
+        };
 
         future::ready(Ok(svc))
     }
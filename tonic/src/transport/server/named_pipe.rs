@@ -0,0 +1,93 @@
+use super::Connected;
+use futures_core::Stream;
+use std::{
+    ffi::OsString,
+    fmt,
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::net::windows::named_pipe::{NamedPipeServer, ServerOptions};
+
+type Connecting = Pin<Box<dyn Future<Output = io::Result<NamedPipeServer>> + Send>>;
+
+/// A stream of connections to a Windows named pipe, for use with
+/// [`Router::serve_with_incoming`](super::Router::serve_with_incoming).
+///
+/// A new instance of the pipe is created for every connection, so any number of clients
+/// can be connected at once:
+///
+/// ```rust,ignore
+/// let incoming = NamedPipeIncoming::new(r"\\.\pipe\tonic")?;
+///
+/// Server::builder()
+///     .add_service(svc)
+///     .serve_with_incoming(incoming)
+///     .await?;
+/// ```
+///
+/// Clients connect with [`NamedPipeConnector`](crate::transport::channel::NamedPipeConnector).
+#[cfg_attr(docsrs, doc(cfg(windows)))]
+pub struct NamedPipeIncoming {
+    path: OsString,
+    connecting: Connecting,
+}
+
+impl NamedPipeIncoming {
+    /// Creates the pipe at `path`, such as `\\.\pipe\tonic`.
+    ///
+    /// This fails if the pipe already exists, so that another process cannot be serving
+    /// on it as well.
+    pub fn new(path: impl Into<OsString>) -> io::Result<Self> {
+        let path = path.into();
+        let server = ServerOptions::new()
+            .first_pipe_instance(true)
+            .create(&path)?;
+
+        Ok(Self {
+            path,
+            connecting: connect(Ok(server)),
+        })
+    }
+}
+
+fn connect(server: io::Result<NamedPipeServer>) -> Connecting {
+    Box::pin(async move {
+        let server = server?;
+        server.connect().await?;
+        Ok(server)
+    })
+}
+
+impl Stream for NamedPipeIncoming {
+    type Item = io::Result<NamedPipeServer>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let connected = match self.connecting.as_mut().poll(cx) {
+            Poll::Ready(connected) => connected,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        // The next client connects to a new instance of the pipe, which is created before
+        // this one is handed out so that clients never find the pipe missing.
+        let next = ServerOptions::new().create(&self.path);
+        self.connecting = connect(next);
+
+        Poll::Ready(Some(connected))
+    }
+}
+
+impl fmt::Debug for NamedPipeIncoming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedPipeIncoming")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl Connected for NamedPipeServer {
+    type ConnectInfo = ();
+
+    fn connect_info(&self) -> Self::ConnectInfo {}
+}
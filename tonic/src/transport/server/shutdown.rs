@@ -0,0 +1,40 @@
+use std::future::Future;
+use tokio::sync::watch;
+
+/// Spawns the connections of a server so that they can be closed when its shutdown grace
+/// period runs out.
+#[derive(Clone)]
+pub(crate) struct ShutdownExec {
+    closed: watch::Receiver<bool>,
+}
+
+impl ShutdownExec {
+    /// Returns the executor and the sender that closes its connections.
+    pub(crate) fn new() -> (Self, watch::Sender<bool>) {
+        let (tx, closed) = watch::channel(false);
+        (Self { closed }, tx)
+    }
+}
+
+impl<F> hyper::rt::Executor<F> for ShutdownExec
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    fn execute(&self, fut: F) {
+        let mut closed = self.closed.clone();
+
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = fut => {}
+                _ = async {
+                    while !*closed.borrow() {
+                        if closed.changed().await.is_err() {
+                            // The server finished without closing its connections.
+                            std::future::pending::<()>().await;
+                        }
+                    }
+                } => {}
+            }
+        });
+    }
+}
@@ -5,6 +5,10 @@ use crate::transport::{
 use std::fmt;
 
 /// Configures TLS settings for servers.
+///
+/// The server presents its [`identity`](ServerTlsConfig::identity), which is required, and
+/// negotiates HTTP/2 with ALPN. Setting a [`client_ca_root`](ServerTlsConfig::client_ca_root)
+/// turns on mutual TLS.
 #[derive(Clone, Default)]
 pub struct ServerTlsConfig {
     identity: Option<Identity>,
@@ -37,6 +41,9 @@ impl ServerTlsConfig {
     }
 
     /// Sets a certificate against which to validate client TLS certificates.
+    ///
+    /// The `tls-native` backend cannot validate client certificates, so configuring the
+    /// server fails when this is set.
     pub fn client_ca_root(self, cert: Certificate) -> Self {
         ServerTlsConfig {
             client_ca_root: Some(cert),
@@ -58,8 +65,13 @@ impl ServerTlsConfig {
     }
 
     pub(crate) fn tls_acceptor(&self) -> Result<TlsAcceptor, crate::Error> {
+        let identity = self
+            .identity
+            .clone()
+            .ok_or("ServerTlsConfig requires an identity")?;
+
         TlsAcceptor::new(
-            self.identity.clone().unwrap(),
+            identity,
             self.client_ca_root.clone(),
             self.client_auth_optional,
         )
@@ -0,0 +1,97 @@
+//! Reads the subject alternative names of a certificate whose signature has already been
+//! verified by the TLS backend.
+
+use std::net::IpAddr;
+use x509_parser::{certificate::X509Certificate, extensions::GeneralName, prelude::FromDer};
+
+/// A subject alternative name of a certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SubjectAltName {
+    /// A DNS name, such as `service.internal`.
+    Dns(String),
+    /// An email address.
+    Email(String),
+    /// A URI, such as a SPIFFE id.
+    Uri(String),
+    /// An IP address.
+    Ip(IpAddr),
+}
+
+/// Returns the subject alternative names of a DER encoded certificate, or `None` if it is
+/// malformed.
+pub(crate) fn subject_alt_names(cert: &[u8]) -> Option<Vec<SubjectAltName>> {
+    let (_, cert) = X509Certificate::from_der(cert).ok()?;
+    let names = match cert.subject_alternative_name().ok()? {
+        Some(extension) => &extension.value.general_names,
+        None => return Some(Vec::new()),
+    };
+
+    let mut sans = Vec::with_capacity(names.len());
+    for name in names {
+        let san = match *name {
+            GeneralName::RFC822Name(email) => SubjectAltName::Email(email.to_owned()),
+            GeneralName::DNSName(dns) => SubjectAltName::Dns(dns.to_owned()),
+            GeneralName::URI(uri) => SubjectAltName::Uri(uri.to_owned()),
+            GeneralName::IPAddress(ip) => SubjectAltName::Ip(match ip.len() {
+                4 => IpAddr::from(<[u8; 4]>::try_from(ip).ok()?),
+                16 => IpAddr::from(<[u8; 16]>::try_from(ip).ok()?),
+                _ => return None,
+            }),
+            // Other kinds of names are skipped.
+            _ => continue,
+        };
+        sans.push(san);
+    }
+
+    Some(sans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{subject_alt_names, SubjectAltName};
+    use std::io::Cursor;
+
+    fn der(pem: &str) -> Vec<u8> {
+        rustls_pemfile::certs(&mut Cursor::new(pem))
+            .unwrap()
+            .remove(0)
+    }
+
+    #[test]
+    fn reads_dns_and_ip_names() {
+        let cert = der(include_str!("../../../../examples/data/tls/server.pem"));
+
+        assert_eq!(
+            subject_alt_names(&cert).unwrap(),
+            vec![
+                SubjectAltName::Dns("example.com".into()),
+                SubjectAltName::Dns("*.example.com".into()),
+                SubjectAltName::Dns("example.test".into()),
+                SubjectAltName::Dns("localhost".into()),
+                SubjectAltName::Ip("127.0.0.1".parse().unwrap()),
+                SubjectAltName::Ip("::1".parse().unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_uri_names() {
+        let cert = der(include_str!(
+            "../../../../tests/integration_tests/data/tls/client.pem"
+        ));
+
+        assert_eq!(
+            subject_alt_names(&cert).unwrap(),
+            vec![SubjectAltName::Uri("spiffe://example.org/client".into())]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_certificates() {
+        let cert = der(include_str!("../../../../examples/data/tls/server.pem"));
+
+        assert_eq!(subject_alt_names(&cert[..cert.len() / 2]), None);
+        assert_eq!(subject_alt_names(&[]), None);
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+/// Delays between reconnect attempts, following the gRPC connection backoff spec.
+///
+/// See <https://github.com/grpc/grpc/blob/master/doc/connection-backoff.md>.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Backoff {
+    pub(crate) initial: Duration,
+    pub(crate) multiplier: f64,
+    pub(crate) jitter: f64,
+    pub(crate) max: Duration,
+}
+
+impl Backoff {
+    /// The delay before the next attempt after `failures` failed attempts in a row.
+    pub(crate) fn delay(&self, failures: u32) -> Duration {
+        let exponent = i32::try_from(failures.saturating_sub(1)).unwrap_or(i32::MAX);
+        let max = self.max.as_secs_f64();
+        let delay = (self.initial.as_secs_f64() * self.multiplier.powi(exponent)).min(max);
+        let delay = delay * (1.0 + self.jitter * (random() * 2.0 - 1.0));
+
+        if delay.is_finite() {
+            Duration::from_secs_f64(delay.max(0.0))
+        } else {
+            self.max
+        }
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            multiplier: 1.6,
+            jitter: 0.2,
+            max: Duration::from_secs(120),
+        }
+    }
+}
+
+/// A number in `0.0..1.0`, random enough to spread out reconnects.
+pub(crate) fn random() -> f64 {
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_up_to_the_max() {
+        let backoff = Backoff {
+            jitter: 0.0,
+            ..Backoff::default()
+        };
+
+        assert_eq!(backoff.delay(1), Duration::from_secs(1));
+        assert_eq!(backoff.delay(2), Duration::from_millis(1600));
+        assert_eq!(backoff.delay(3), Duration::from_millis(2560));
+        assert_eq!(backoff.delay(100), Duration::from_secs(120));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(120));
+    }
+
+    #[test]
+    fn jitter_stays_in_range() {
+        let backoff = Backoff::default();
+
+        for _ in 0..100 {
+            let delay = backoff.delay(1);
+            assert!(delay >= Duration::from_millis(800), "{:?}", delay);
+            assert!(delay <= Duration::from_millis(1200), "{:?}", delay);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use super::header_list_size;
+use super::pool::InFlight;
+use bytes::Bytes;
+use http::HeaderMap;
+use http_body::{Body, SizeHint};
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The body of the responses of a [`Channel`](crate::transport::Channel).
+///
+/// It is the body of the HTTP/2 stream of the call, so its errors, such as the stream being
+/// reset, are the ones of that stream.
+pub struct ResponseBody {
+    inner: hyper::Body,
+    /// Released once the body is done, when the stream counts towards the streams in flight
+    /// on its connection.
+    in_flight: Option<InFlight>,
+    /// The max size of the trailers, which are replaced with a `RESOURCE_EXHAUSTED` status
+    /// when they are larger.
+    max_header_list_size: Option<u32>,
+}
+
+impl ResponseBody {
+    pub(crate) fn new(inner: hyper::Body) -> Self {
+        Self {
+            inner,
+            in_flight: None,
+            max_header_list_size: None,
+        }
+    }
+
+    /// Creates an empty body.
+    pub fn empty() -> Self {
+        Self::new(hyper::Body::empty())
+    }
+
+    pub(crate) fn set_in_flight(&mut self, in_flight: InFlight) {
+        if !self.inner.is_end_stream() {
+            self.in_flight = Some(in_flight);
+        }
+    }
+
+    pub(crate) fn set_max_header_list_size(&mut self, max: u32) {
+        self.max_header_list_size = Some(max);
+    }
+
+    /// Consumes the body, returning the HTTP/2 body it reads.
+    ///
+    /// The stream no longer counts towards the streams in flight on its connection.
+    pub fn into_inner(self) -> hyper::Body {
+        self.inner
+    }
+}
+
+impl Body for ResponseBody {
+    type Data = Bytes;
+    type Error = hyper::Error;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let data = futures_util::ready!(Pin::new(&mut self.inner).poll_data(cx));
+        if !matches!(data, Some(Ok(_))) {
+            // The stream is done when it fails, and when it ends without trailers.
+            if data.is_some() || self.inner.is_end_stream() {
+                self.in_flight = None;
+            }
+        }
+        Poll::Ready(data)
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let trailers = futures_util::ready!(Pin::new(&mut self.inner).poll_trailers(cx));
+        self.in_flight = None;
+
+        if let (Ok(Some(trailers)), Some(max)) = (&trailers, self.max_header_list_size) {
+            if let Err(status) = header_list_size::check(trailers, max) {
+                return Poll::Ready(Ok(Some(status.to_header_map().unwrap_or_default())));
+            }
+        }
+
+        Poll::Ready(trailers)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Default for ResponseBody {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl From<hyper::Body> for ResponseBody {
+    fn from(inner: hyper::Body) -> Self {
+        Self::new(inner)
+    }
+}
+
+impl fmt::Debug for ResponseBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseBody")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
@@ -1,6 +1,16 @@
 use super::super::BoxFuture;
-use super::{grpc_timeout::GrpcTimeout, reconnect::Reconnect, AddOrigin, UserAgent};
-use crate::{body::BoxBody, transport::Endpoint};
+use super::{
+    grpc_timeout::{GrpcDeadline, GrpcTimeout},
+    header_list_size::MaxHeaderListSize,
+    health::HealthCheck,
+    reconnect::Reconnect,
+    AddOrigin, Reporter, ResponseBody, UserAgent,
+};
+use crate::transport::channel::{LoadBalancing, QueueSlot};
+use crate::{
+    body::BoxBody,
+    transport::{Endpoint, TimeoutExpired},
+};
 use http::Uri;
 use hyper::client::conn::Builder;
 use hyper::client::connect::Connection as HyperConnection;
@@ -8,11 +18,12 @@ use hyper::client::service::Connect as HyperConnect;
 use std::{
     fmt,
     task::{Context, Poll},
+    time::Instant,
 };
 use tokio::io::{AsyncRead, AsyncWrite};
 use tower::load::Load;
 use tower::{
-    layer::Layer,
+    layer::{layer_fn, Layer},
     limit::{concurrency::ConcurrencyLimitLayer, rate::RateLimitLayer},
     util::BoxService,
     ServiceBuilder, ServiceExt,
@@ -20,14 +31,20 @@ use tower::{
 use tower_service::Service;
 
 pub(crate) type Request = http::Request<BoxBody>;
-pub(crate) type Response = http::Response<hyper::Body>;
+pub(crate) type Response = http::Response<ResponseBody>;
 
 pub(crate) struct Connection {
     inner: BoxService<Request, Response, crate::Error>,
 }
 
 impl Connection {
-    fn new<C>(connector: C, endpoint: Endpoint, is_lazy: bool) -> Self
+    fn new<C>(
+        connector: C,
+        endpoint: Endpoint,
+        reporter: Reporter,
+        is_lazy: bool,
+        fail_fast: bool,
+    ) -> Self
     where
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::Error> + Send,
@@ -55,45 +72,85 @@ impl Connection {
         }
 
         let stack = ServiceBuilder::new()
+            .option_layer(
+                endpoint
+                    .http2_max_header_list_size
+                    .map(|max| layer_fn(move |s| MaxHeaderListSize::new(s, max))),
+            )
+            .map_response(|response: http::Response<hyper::Body>| response.map(ResponseBody::new))
             .layer_fn(|s| {
                 let origin = endpoint.origin.as_ref().unwrap_or(&endpoint.uri).clone();
 
                 AddOrigin::new(s, origin)
             })
             .layer_fn(|s| UserAgent::new(s, endpoint.user_agent.clone()))
-            .layer_fn(|s| GrpcTimeout::new(s, endpoint.timeout))
+            .layer_fn(|s| GrpcTimeout::new_client(s, endpoint.timeout))
             .option_layer(endpoint.concurrency_limit.map(ConcurrencyLimitLayer::new))
             .option_layer(endpoint.rate_limit.map(|(l, d)| RateLimitLayer::new(l, d)))
             .into_inner();
 
+        // Balancers skip endpoints that fail their health checks, which like in other gRPC
+        // implementations are not made with `pick_first`.
+        let health_check = endpoint
+            .service_config
+            .as_ref()
+            .filter(|config| fail_fast && config.load_balancing() != Some(LoadBalancing::PickFirst))
+            .and_then(|config| config.health_check_service())
+            .map(String::from);
+
         let connector = HyperConnect::new(connector, settings);
-        let conn = Reconnect::new(connector, endpoint.uri.clone(), is_lazy);
+        let mut conn = Reconnect::new(connector, endpoint.uri.clone(), is_lazy)
+            .backoff(endpoint.backoff)
+            .report_to(reporter);
+        if fail_fast {
+            conn = conn.fail_fast();
+        }
 
         let inner = stack.layer(conn);
+        let inner = match health_check {
+            Some(service) => BoxService::new(HealthCheck::new(inner, service)),
+            None => BoxService::new(inner),
+        };
 
-        Self {
-            inner: BoxService::new(inner),
-        }
+        Self { inner }
     }
 
-    pub(crate) async fn connect<C>(connector: C, endpoint: Endpoint) -> Result<Self, crate::Error>
+    pub(crate) async fn connect<C>(
+        connector: C,
+        endpoint: Endpoint,
+        reporter: Reporter,
+    ) -> Result<Self, crate::Error>
     where
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::Error> + Send,
         C::Future: Unpin + Send,
         C::Response: AsyncRead + AsyncWrite + HyperConnection + Unpin + Send + 'static,
     {
-        Self::new(connector, endpoint, false).ready_oneshot().await
+        Self::new(connector, endpoint, reporter, false, false)
+            .ready_oneshot()
+            .await
     }
 
-    pub(crate) fn lazy<C>(connector: C, endpoint: Endpoint) -> Self
+    pub(crate) fn lazy<C>(connector: C, endpoint: Endpoint, reporter: Reporter) -> Self
     where
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::Error> + Send,
         C::Future: Unpin + Send,
         C::Response: AsyncRead + AsyncWrite + HyperConnection + Unpin + Send + 'static,
     {
-        Self::new(connector, endpoint, true)
+        Self::new(connector, endpoint, reporter, true, false)
+    }
+
+    /// A lazy connection whose connection errors are returned from `poll_ready`, so that it
+    /// can be skipped while it is failing. Polling it again after an error reconnects.
+    pub(crate) fn fail_fast<C>(connector: C, endpoint: Endpoint, reporter: Reporter) -> Self
+    where
+        C: Service<Uri> + Send + 'static,
+        C::Error: Into<crate::Error> + Send,
+        C::Future: Unpin + Send,
+        C::Response: AsyncRead + AsyncWrite + HyperConnection + Unpin + Send + 'static,
+    {
+        Self::new(connector, endpoint, reporter, true, true)
     }
 }
 
@@ -106,7 +163,17 @@ impl Service<Request> for Connection {
         Service::poll_ready(&mut self.inner, cx).map_err(Into::into)
     }
 
-    fn call(&mut self, req: Request) -> Self::Future {
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        if let Some(slot) = req.extensions_mut().remove::<QueueSlot>() {
+            slot.dispatch();
+        }
+
+        // The call of a request whose deadline passed while it was queued has already failed.
+        if matches!(req.extensions().get::<GrpcDeadline>(), Some(deadline) if deadline.0 <= Instant::now())
+        {
+            return Box::pin(async { Err(TimeoutExpired::new().into()) });
+        }
+
         self.inner.call(req)
     }
 }
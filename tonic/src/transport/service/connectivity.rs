@@ -0,0 +1,135 @@
+use crate::transport::channel::ConnectivityState;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::watch;
+
+/// Tracks the connectivity state of the connections behind a channel.
+///
+/// The state of the channel is sent to the receiver returned by `new` whenever it changes.
+/// Once every clone and every reporter has been dropped the channel is considered shut down.
+#[derive(Clone)]
+pub(crate) struct Connectivity {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    tx: watch::Sender<ConnectivityState>,
+    connections: Mutex<Connections>,
+}
+
+#[derive(Default)]
+struct Connections {
+    next_id: usize,
+    states: HashMap<usize, ConnectivityState>,
+}
+
+/// Reports the state of a single connection, which is forgotten once this is dropped.
+pub(crate) struct Reporter {
+    shared: Arc<Shared>,
+    id: usize,
+}
+
+impl Connectivity {
+    pub(crate) fn new() -> (Self, watch::Receiver<ConnectivityState>) {
+        let (tx, rx) = watch::channel(ConnectivityState::Idle);
+        let shared = Arc::new(Shared {
+            tx,
+            connections: Mutex::default(),
+        });
+
+        (Self { shared }, rx)
+    }
+
+    pub(crate) fn reporter(&self) -> Reporter {
+        let mut connections = self.shared.connections.lock().unwrap();
+        let id = connections.next_id;
+        connections.next_id += 1;
+        connections.states.insert(id, ConnectivityState::Idle);
+        self.shared.update(&connections);
+
+        Reporter {
+            shared: self.shared.clone(),
+            id,
+        }
+    }
+}
+
+impl Reporter {
+    pub(crate) fn set(&self, state: ConnectivityState) {
+        let mut connections = self.shared.connections.lock().unwrap();
+        if connections.states.insert(self.id, state) != Some(state) {
+            self.shared.update(&connections);
+        }
+    }
+}
+
+impl Drop for Reporter {
+    fn drop(&mut self) {
+        if let Ok(mut connections) = self.shared.connections.lock() {
+            connections.states.remove(&self.id);
+            self.shared.update(&connections);
+        }
+    }
+}
+
+impl Shared {
+    fn update(&self, connections: &Connections) {
+        let state = aggregate(connections.states.values().copied());
+
+        self.tx.send_if_modified(|current| {
+            let modified = *current != state;
+            *current = state;
+            modified
+        });
+    }
+}
+
+/// The state of a channel is the state closest to ready among its connections.
+fn aggregate(states: impl Iterator<Item = ConnectivityState>) -> ConnectivityState {
+    use ConnectivityState::*;
+
+    states
+        .min_by_key(|state| match state {
+            Ready => 0,
+            Connecting => 1,
+            Idle => 2,
+            TransientFailure => 3,
+            Shutdown => 4,
+        })
+        .unwrap_or(Idle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ConnectivityState::*;
+
+    #[test]
+    fn aggregates_the_state_closest_to_ready() {
+        let (connectivity, rx) = Connectivity::new();
+        assert_eq!(*rx.borrow(), Idle);
+
+        let a = connectivity.reporter();
+        let b = connectivity.reporter();
+
+        a.set(TransientFailure);
+        assert_eq!(*rx.borrow(), Idle);
+
+        b.set(Connecting);
+        assert_eq!(*rx.borrow(), Connecting);
+
+        b.set(Ready);
+        assert_eq!(*rx.borrow(), Ready);
+
+        drop(b);
+        assert_eq!(*rx.borrow(), TransientFailure);
+
+        drop(a);
+        assert_eq!(*rx.borrow(), Idle);
+
+        drop(connectivity);
+        assert!(rx.has_changed().is_err());
+    }
+}
@@ -1,44 +1,64 @@
 use super::super::BoxFuture;
+use super::h2c;
 use super::io::BoxedIo;
-#[cfg(feature = "tls")]
-use super::tls::TlsConnector;
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+use super::TlsConnector;
 use http::Uri;
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 use std::fmt;
 use std::task::{Context, Poll};
 use tower::make::MakeConnection;
 use tower_service::Service;
 
-#[cfg(not(feature = "tls"))]
+#[cfg(not(any(feature = "tls", feature = "tls-native")))]
 pub(crate) fn connector<C>(inner: C) -> Connector<C> {
     Connector::new(inner)
 }
 
-#[cfg(feature = "tls")]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 pub(crate) fn connector<C>(inner: C, tls: Option<TlsConnector>) -> Connector<C> {
     Connector::new(inner, tls)
 }
 
+#[derive(Clone)]
 pub(crate) struct Connector<C> {
     inner: C,
-    #[cfg(feature = "tls")]
+    #[cfg(any(feature = "tls", feature = "tls-native"))]
     tls: Option<TlsConnector>,
-    #[cfg(not(feature = "tls"))]
+    #[cfg(not(any(feature = "tls", feature = "tls-native")))]
     #[allow(dead_code)]
     tls: Option<()>,
+    h2c_upgrade: bool,
 }
 
 impl<C> Connector<C> {
-    #[cfg(not(feature = "tls"))]
+    #[cfg(not(any(feature = "tls", feature = "tls-native")))]
     pub(crate) fn new(inner: C) -> Self {
-        Self { inner, tls: None }
+        Self {
+            inner,
+            tls: None,
+            h2c_upgrade: false,
+        }
     }
 
-    #[cfg(feature = "tls")]
+    #[cfg(any(feature = "tls", feature = "tls-native"))]
     fn new(inner: C, tls: Option<TlsConnector>) -> Self {
-        Self { inner, tls }
+        Self {
+            inner,
+            tls,
+            h2c_upgrade: false,
+        }
     }
 
-    #[cfg(feature = "tls-roots-common")]
+    /// Upgrade plaintext connections to HTTP/2 with an HTTP/1.1 `Upgrade: h2c` request.
+    pub(crate) fn h2c_upgrade(self, h2c_upgrade: bool) -> Self {
+        Self {
+            h2c_upgrade,
+            ..self
+        }
+    }
+
+    #[cfg(any(feature = "tls-roots-common", feature = "tls-native"))]
     fn tls_or_default(&self, scheme: Option<&str>, host: Option<&str>) -> Option<TlsConnector> {
         if self.tls.is_some() {
             return self.tls.clone();
@@ -71,20 +91,28 @@ where
     }
 
     fn call(&mut self, uri: Uri) -> Self::Future {
-        #[cfg(all(feature = "tls", not(feature = "tls-roots-common")))]
+        #[cfg(all(
+            feature = "tls",
+            not(any(feature = "tls-roots-common", feature = "tls-native"))
+        ))]
         let tls = self.tls.clone();
 
-        #[cfg(feature = "tls-roots-common")]
+        #[cfg(any(feature = "tls-roots-common", feature = "tls-native"))]
         let tls = self.tls_or_default(uri.scheme_str(), uri.host());
 
-        #[cfg(feature = "tls")]
+        #[cfg(any(feature = "tls", feature = "tls-native"))]
         let is_https = uri.scheme_str() == Some("https");
+        let h2c_upgrade = self.h2c_upgrade.then(|| {
+            uri.authority()
+                .map_or("localhost", |a| a.as_str())
+                .to_string()
+        });
         let connect = self.inner.make_connection(uri);
 
         Box::pin(async move {
             let io = connect.await?;
 
-            #[cfg(feature = "tls")]
+            #[cfg(any(feature = "tls", feature = "tls-native"))]
             {
                 if let Some(tls) = tls {
                     let conn = tls.connect(io).await?;
@@ -94,20 +122,31 @@ where
                 }
             }
 
+            if let Some(authority) = h2c_upgrade {
+                return Ok(BoxedIo::new(h2c::upgrade(io, &authority).await?));
+            }
+
             Ok(BoxedIo::new(io))
         })
     }
 }
 
 /// Error returned when trying to connect to an HTTPS endpoint without TLS enabled.
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 #[derive(Debug)]
 pub(crate) struct HttpsUriWithoutTlsSupport(());
 
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 impl fmt::Display for HttpsUriWithoutTlsSupport {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Connecting to HTTPS without TLS enabled")
+        write!(
+            f,
+            "Connecting to HTTPS without TLS enabled, configure it with `Endpoint::tls_config` \
+             or enable the `tls-roots` or `tls-webpki-roots` feature"
+        )
     }
 }
 
 // std::error::Error only requires a type to impl Debug and Display
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 impl std::error::Error for HttpsUriWithoutTlsSupport {}
@@ -1,13 +1,16 @@
-use super::super::service;
-use super::connection::Connection;
+use super::super::{service, BoxFuture};
+use super::{connection::Connection, Connectivity};
 use crate::transport::Endpoint;
 
+use http::Uri;
 use std::{
     hash::Hash,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
-use tokio::sync::mpsc::Receiver;
+use tokio::sync::{mpsc::Receiver, Notify};
+use tower_service::Service;
 
 use tokio_stream::Stream;
 use tower::discover::Change;
@@ -16,11 +19,41 @@ type DiscoverResult<K, S, E> = Result<Change<K, S>, E>;
 
 pub(crate) struct DynamicServiceStream<K: Hash + Eq + Clone> {
     changes: Receiver<Change<K, Endpoint>>,
+    fail_fast: bool,
+    on_connect_error: Option<Arc<Notify>>,
+    connectivity: Connectivity,
 }
 
 impl<K: Hash + Eq + Clone> DynamicServiceStream<K> {
-    pub(crate) fn new(changes: Receiver<Change<K, Endpoint>>) -> Self {
-        Self { changes }
+    pub(crate) fn new(changes: Receiver<Change<K, Endpoint>>, connectivity: Connectivity) -> Self {
+        Self {
+            changes,
+            fail_fast: false,
+            on_connect_error: None,
+            connectivity,
+        }
+    }
+
+    /// Yield connections that report connection errors from `poll_ready`, see
+    /// `Connection::fail_fast`.
+    pub(crate) fn fail_fast(
+        changes: Receiver<Change<K, Endpoint>>,
+        connectivity: Connectivity,
+    ) -> Self {
+        Self {
+            changes,
+            fail_fast: true,
+            on_connect_error: None,
+            connectivity,
+        }
+    }
+
+    /// Notify `notify` whenever one of the yielded connections fails to connect.
+    pub(crate) fn on_connect_error(self, notify: Arc<Notify>) -> Self {
+        Self {
+            on_connect_error: Some(notify),
+            ..self
+        }
     }
 }
 
@@ -28,22 +61,33 @@ impl<K: Hash + Eq + Clone> Stream for DynamicServiceStream<K> {
     type Item = DiscoverResult<K, Connection, crate::Error>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let fail_fast = self.fail_fast;
+        let on_connect_error = self.on_connect_error.clone();
+        let connectivity = self.connectivity.clone();
         let c = &mut self.changes;
         match Pin::new(&mut *c).poll_recv(cx) {
             Poll::Pending | Poll::Ready(None) => Poll::Pending,
             Poll::Ready(Some(change)) => match change {
                 Change::Insert(k, endpoint) => {
-                    let mut http = hyper::client::connect::HttpConnector::new();
-                    http.set_nodelay(endpoint.tcp_nodelay);
-                    http.set_keepalive(endpoint.tcp_keepalive);
+                    let mut http = endpoint.http_connector();
                     http.set_connect_timeout(endpoint.connect_timeout);
-                    http.enforce_http(false);
-                    #[cfg(feature = "tls")]
+                    let http = service::ProxyConnector::new(http, endpoint.proxy.clone());
+                    #[cfg(any(feature = "tls", feature = "tls-native"))]
                     let connector = service::connector(http, endpoint.tls.clone());
 
-                    #[cfg(not(feature = "tls"))]
+                    #[cfg(not(any(feature = "tls", feature = "tls-native")))]
                     let connector = service::connector(http);
-                    let connection = Connection::lazy(connector, endpoint);
+                    let connector = connector.h2c_upgrade(endpoint.h2c_upgrade);
+                    let connector = NotifyOnError {
+                        inner: connector,
+                        notify: on_connect_error,
+                    };
+                    let reporter = connectivity.reporter();
+                    let connection = if fail_fast {
+                        Connection::fail_fast(connector, endpoint, reporter)
+                    } else {
+                        Connection::lazy(connector, endpoint, reporter)
+                    };
                     let change = Ok(Change::Insert(k, connection));
                     Poll::Ready(Some(change))
                 }
@@ -54,3 +98,36 @@ impl<K: Hash + Eq + Clone> Stream for DynamicServiceStream<K> {
 }
 
 impl<K: Hash + Eq + Clone> Unpin for DynamicServiceStream<K> {}
+
+struct NotifyOnError<C> {
+    inner: C,
+    notify: Option<Arc<Notify>>,
+}
+
+impl<C> Service<Uri> for NotifyOnError<C>
+where
+    C: Service<Uri>,
+    C::Error: Into<crate::Error>,
+    C::Future: Send + 'static,
+{
+    type Response = C::Response;
+    type Error = crate::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let connect = self.inner.call(uri);
+        let notify = self.notify.clone();
+
+        Box::pin(async move {
+            let result = connect.await.map_err(Into::into);
+            if let (Err(_), Some(notify)) = (&result, notify) {
+                notify.notify_one();
+            }
+            result
+        })
+    }
+}
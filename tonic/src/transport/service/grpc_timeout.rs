@@ -1,4 +1,5 @@
 use crate::metadata::GRPC_TIMEOUT_HEADER;
+use crate::request::duration_to_grpc_timeout;
 use crate::util::{OptionPin, OptionPinProj};
 use http::{HeaderMap, HeaderValue, Request};
 use pin_project::pin_project;
@@ -7,7 +8,7 @@ use std::{
     future::Future,
     pin::Pin,
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio::time::Sleep;
 use tower_service::Service;
@@ -16,13 +17,26 @@ use tower_service::Service;
 pub(crate) struct GrpcTimeout<S> {
     inner: S,
     server_timeout: Option<Duration>,
+    set_header: bool,
 }
 
 impl<S> GrpcTimeout<S> {
+    #[cfg(feature = "transport")]
     pub(crate) fn new(inner: S, server_timeout: Option<Duration>) -> Self {
         Self {
             inner,
             server_timeout,
+            set_header: false,
+        }
+    }
+
+    /// Like `new`, but also sends the timeout that is enforced in the `grpc-timeout` header so
+    /// the server is informed of it.
+    pub(crate) fn new_client(inner: S, timeout: Option<Duration>) -> Self {
+        Self {
+            inner,
+            server_timeout: timeout,
+            set_header: true,
         }
     }
 }
@@ -40,7 +54,7 @@ where
         self.inner.poll_ready(cx).map_err(Into::into)
     }
 
-    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
         let client_timeout = try_parse_grpc_timeout(req.headers()).unwrap_or_else(|e| {
             tracing::trace!("Error parsing `grpc-timeout` header {:?}", e);
             None
@@ -57,6 +71,20 @@ where
             }
         };
 
+        if self.set_header {
+            if let Some(dur) = timeout_duration.filter(|dur| client_timeout != Some(*dur)) {
+                let value = duration_to_grpc_timeout(dur.min(MAX_HEADER_TIMEOUT))
+                    .parse()
+                    .expect("formatted timeout is a valid header value");
+                req.headers_mut().insert(GRPC_TIMEOUT_HEADER, value);
+            }
+        }
+
+        // A timeout too large to be an instant has no deadline.
+        if let Some(deadline) = timeout_duration.and_then(|dur| Instant::now().checked_add(dur)) {
+            req.extensions_mut().insert(GrpcDeadline(deadline));
+        }
+
         ResponseFuture {
             inner: self.inner.call(req),
             sleep: timeout_duration
@@ -101,11 +129,14 @@ where
 const SECONDS_IN_HOUR: u64 = 60 * 60;
 const SECONDS_IN_MINUTE: u64 = 60;
 
+// The largest timeout the `grpc-timeout` header can carry, 8 digits of hours.
+const MAX_HEADER_TIMEOUT: Duration = Duration::from_secs(99_999_999 * SECONDS_IN_HOUR);
+
 /// Tries to parse the `grpc-timeout` header if it is present. If we fail to parse, returns
 /// the value we attempted to parse.
 ///
 /// Follows the [gRPC over HTTP2 spec](https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-HTTP2.md).
-fn try_parse_grpc_timeout(
+pub(crate) fn try_parse_grpc_timeout(
     headers: &HeaderMap<HeaderValue>,
 ) -> Result<Option<Duration>, &HeaderValue> {
     match headers.get(GRPC_TIMEOUT_HEADER) {
@@ -151,6 +182,13 @@ fn try_parse_grpc_timeout(
     }
 }
 
+/// The instant by which a request has to complete, stored in the request extensions.
+///
+/// Read by [`Request::deadline`](crate::Request::deadline).
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "transport"), allow(dead_code))]
+pub(crate) struct GrpcDeadline(pub(crate) Instant);
+
 /// Error returned if a request didn't complete within the configured timeout.
 ///
 /// Timeouts can be configured either with [`Endpoint::timeout`], [`Server::timeout`], or by
@@ -162,6 +200,12 @@ fn try_parse_grpc_timeout(
 #[derive(Debug)]
 pub struct TimeoutExpired(());
 
+impl TimeoutExpired {
+    pub(crate) fn new() -> Self {
+        TimeoutExpired(())
+    }
+}
+
 impl fmt::Display for TimeoutExpired {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Timeout expired")
@@ -251,6 +295,32 @@ mod tests {
         setup_map_try_parse(Some("oneH")).unwrap().unwrap();
     }
 
+    #[tokio::test]
+    async fn timeouts_too_large_for_a_deadline() {
+        let svc = tower::service_fn(|req: Request<()>| async move {
+            Ok::<_, crate::Error>(req.extensions().get::<GrpcDeadline>().is_some())
+        });
+
+        let has_deadline = GrpcTimeout::new(svc, Some(Duration::MAX))
+            .call(Request::new(()))
+            .await
+            .unwrap();
+        assert!(!has_deadline);
+    }
+
+    #[tokio::test]
+    async fn sends_timeouts_too_large_for_the_header_capped() {
+        let svc = tower::service_fn(|req: Request<()>| async move {
+            Ok::<_, crate::Error>(try_parse_grpc_timeout(req.headers()).unwrap())
+        });
+
+        let timeout = GrpcTimeout::new_client(svc, Some(Duration::MAX))
+            .call(Request::new(()))
+            .await
+            .unwrap();
+        assert_eq!(timeout, Some(MAX_HEADER_TIMEOUT));
+    }
+
     #[quickcheck]
     fn fuzz(header_value: HeaderValueGen) -> bool {
         let header_value = header_value.0;
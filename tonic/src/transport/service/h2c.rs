@@ -0,0 +1,503 @@
+//! HTTP/2 over cleartext TCP, reached by upgrading an HTTP/1.1 connection (`h2c`).
+//!
+//! After a successful upgrade the response to the upgrade request is sent on stream 1, which
+//! `h2` knows nothing about: its servers never send that response and its clients start their
+//! own streams at 1. [`H2cIo`] rewrites the frames of the connection to make up for it.
+
+use bytes::{Buf, BytesMut};
+use futures_util::ready;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+
+/// The frame a server sends on stream 1 in response to the upgrade request: a `HEADERS` frame
+/// with `END_STREAM` and `END_HEADERS` set and `:status: 204` encoded with the static table of
+/// HPACK, so that the header compression state of the connection is left untouched.
+const UPGRADE_RESPONSE: [u8; 10] = [0, 0, 1, 0x1, 0x5, 0, 0, 0, 1, 0x89];
+
+const PREFACE_LEN: usize = 24;
+const HEADER_LEN: usize = 9;
+const GOAWAY: u8 = 0x7;
+
+/// The largest HTTP/1.1 response head accepted in reply to the upgrade request.
+const MAX_RESPONSE_HEAD: usize = 8 * 1024;
+
+/// Sends an `h2c` upgrade request over `io` and waits for the server to accept it.
+///
+/// The upgrade request is an `OPTIONS *` request without a body, so that the response the
+/// server sends for it on stream 1 can simply be discarded. The server must not use the
+/// HPACK dynamic table for that response.
+pub(crate) async fn upgrade<IO>(mut io: IO, authority: &str) -> io::Result<H2cIo<IO>>
+where
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    let request = format!(
+        "OPTIONS * HTTP/1.1\r\n\
+         Host: {}\r\n\
+         Connection: Upgrade, HTTP2-Settings\r\n\
+         Upgrade: h2c\r\n\
+         HTTP2-Settings: \r\n\
+         \r\n",
+        authority
+    );
+    io.write_all(request.as_bytes()).await?;
+    io.flush().await?;
+
+    let mut head = Vec::new();
+    let end = loop {
+        if head.len() >= MAX_RESPONSE_HEAD {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "h2c upgrade response head is too large",
+            ));
+        }
+
+        let mut buf = [0; 1024];
+        let n = io.read(&mut buf).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        head.extend_from_slice(&buf[..n]);
+
+        if let Some(end) = head.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+
+    let status_line = head.split(|&b| b == b'\r').next().unwrap_or_default();
+    let status_line = String::from_utf8_lossy(status_line);
+    if status_line.split(' ').nth(1) != Some("101") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("server did not accept the h2c upgrade: {}", status_line),
+        ));
+    }
+
+    let mut io = H2cIo::client(io);
+    // The server preface may have been read along with the response head.
+    io.read
+        .as_mut()
+        .expect("client reads are rewritten")
+        .process(&head[end..], &mut io.read_buf);
+    Ok(io)
+}
+
+/// An IO that rewrites the HTTP/2 frames of an upgraded `h2c` connection.
+///
+/// On servers, the response to the upgrade request is sent right after the preface. On
+/// clients, the streams started by `h2` are moved up by one, to start at 3, and the frames
+/// the server sends on stream 1 are dropped.
+pub(crate) struct H2cIo<IO> {
+    io: IO,
+    read: Option<Rewriter>,
+    read_buf: BytesMut,
+    write: Option<Rewriter>,
+    write_buf: BytesMut,
+}
+
+impl<IO> H2cIo<IO> {
+    #[cfg(feature = "transport")]
+    pub(crate) fn server(io: IO) -> Self {
+        Self::new(io, None, Some(Rewriter::new(Direction::ServerWrite, 0)))
+    }
+
+    fn client(io: IO) -> Self {
+        Self::new(
+            io,
+            Some(Rewriter::new(Direction::ClientRead, 0)),
+            Some(Rewriter::new(Direction::ClientWrite, PREFACE_LEN)),
+        )
+    }
+
+    fn new(io: IO, read: Option<Rewriter>, write: Option<Rewriter>) -> Self {
+        Self {
+            io,
+            read,
+            read_buf: BytesMut::new(),
+            write,
+            write_buf: BytesMut::new(),
+        }
+    }
+}
+
+impl<IO: AsyncWrite + Unpin> H2cIo<IO> {
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while !self.write_buf.is_empty() {
+            let n = ready!(Pin::new(&mut self.io).poll_write(cx, &self.write_buf))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.write_buf.advance(n);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<IO: AsyncRead + Unpin> AsyncRead for H2cIo<IO> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if !this.read_buf.is_empty() {
+                let n = this.read_buf.len().min(buf.remaining());
+                buf.put_slice(&this.read_buf[..n]);
+                this.read_buf.advance(n);
+                return Poll::Ready(Ok(()));
+            }
+
+            let rewriter = match &mut this.read {
+                Some(rewriter) => rewriter,
+                None => return Pin::new(&mut this.io).poll_read(cx, buf),
+            };
+
+            let mut raw = [0; 8 * 1024];
+            let mut raw = ReadBuf::new(&mut raw);
+            ready!(Pin::new(&mut this.io).poll_read(cx, &mut raw))?;
+            if raw.filled().is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+            rewriter.process(raw.filled(), &mut this.read_buf);
+        }
+    }
+}
+
+impl<IO: AsyncWrite + Unpin> AsyncWrite for H2cIo<IO> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+
+        match &mut this.write {
+            Some(rewriter) => {
+                rewriter.process(buf, &mut this.write_buf);
+                if rewriter.is_done() {
+                    this.write = None;
+                }
+                Poll::Ready(Ok(buf.len()))
+            }
+            None => Pin::new(&mut this.io).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.io).poll_shutdown(cx)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Direction {
+    /// Frames written by a server, which get the response to the upgrade request inserted
+    /// after the first one.
+    ServerWrite,
+    /// Frames written by a client, whose streams are moved up by one.
+    ClientWrite,
+    /// Frames read by a client, which are dropped if they are on stream 1 and whose streams
+    /// are moved down by one otherwise.
+    ClientRead,
+}
+
+/// Rewrites the frames of one direction of a connection, as they go through in chunks of any
+/// size.
+struct Rewriter {
+    direction: Direction,
+    /// The bytes of the connection preface that are still to go through as they are.
+    preface: usize,
+    /// The header of the current frame, followed by the last stream id for `GOAWAY` frames.
+    head: [u8; HEADER_LEN + 4],
+    head_len: usize,
+    head_needed: usize,
+    /// The bytes of the payload of the current frame that are still to go through.
+    payload: usize,
+    dropping: bool,
+    frames: usize,
+}
+
+impl Rewriter {
+    fn new(direction: Direction, preface: usize) -> Self {
+        Self {
+            direction,
+            preface,
+            head: [0; HEADER_LEN + 4],
+            head_len: 0,
+            head_needed: HEADER_LEN,
+            payload: 0,
+            dropping: false,
+            frames: 0,
+        }
+    }
+
+    /// Whether the remaining frames go through unchanged.
+    fn is_done(&self) -> bool {
+        self.direction == Direction::ServerWrite && self.frames > 0
+    }
+
+    fn process(&mut self, mut input: &[u8], out: &mut BytesMut) {
+        while !input.is_empty() {
+            if self.is_done() {
+                out.extend_from_slice(input);
+                return;
+            }
+
+            if self.preface > 0 {
+                let n = self.preface.min(input.len());
+                out.extend_from_slice(&input[..n]);
+                self.preface -= n;
+                input = &input[n..];
+                continue;
+            }
+
+            if self.payload > 0 {
+                let n = self.payload.min(input.len());
+                if !self.dropping {
+                    out.extend_from_slice(&input[..n]);
+                }
+                self.payload -= n;
+                input = &input[n..];
+                if self.payload == 0 {
+                    self.end_frame(out);
+                }
+                continue;
+            }
+
+            let n = (self.head_needed - self.head_len).min(input.len());
+            self.head[self.head_len..self.head_len + n].copy_from_slice(&input[..n]);
+            self.head_len += n;
+            input = &input[n..];
+
+            if self.head_len == HEADER_LEN
+                && self.head[3] == GOAWAY
+                && self.payload_len() >= 4
+                && self.head_needed == HEADER_LEN
+            {
+                self.head_needed += 4;
+            } else if self.head_len == self.head_needed {
+                self.start_frame(out);
+            }
+        }
+    }
+
+    fn payload_len(&self) -> usize {
+        usize::from(self.head[0]) << 16 | usize::from(self.head[1]) << 8 | usize::from(self.head[2])
+    }
+
+    fn start_frame(&mut self, out: &mut BytesMut) {
+        let stream_id = read_stream_id(&self.head[5..9]);
+        self.dropping = false;
+
+        match self.direction {
+            Direction::ServerWrite => {}
+            Direction::ClientWrite => {
+                if stream_id % 2 == 1 {
+                    write_stream_id(&mut self.head[5..9], stream_id + 2);
+                }
+            }
+            Direction::ClientRead => {
+                if stream_id == 1 {
+                    self.dropping = true;
+                } else if stream_id % 2 == 1 {
+                    write_stream_id(&mut self.head[5..9], stream_id - 2);
+                }
+
+                if self.head_needed > HEADER_LEN {
+                    let last_stream_id = read_stream_id(&self.head[9..13]);
+                    if last_stream_id % 2 == 1 {
+                        write_stream_id(&mut self.head[9..13], last_stream_id.saturating_sub(2));
+                    }
+                }
+            }
+        }
+
+        if !self.dropping {
+            out.extend_from_slice(&self.head[..self.head_needed]);
+        }
+        self.payload = self.payload_len() - (self.head_needed - HEADER_LEN);
+        self.head_len = 0;
+        self.head_needed = HEADER_LEN;
+
+        if self.payload == 0 {
+            self.end_frame(out);
+        }
+    }
+
+    fn end_frame(&mut self, out: &mut BytesMut) {
+        self.frames += 1;
+        if self.direction == Direction::ServerWrite && self.frames == 1 {
+            out.extend_from_slice(&UPGRADE_RESPONSE);
+        }
+    }
+}
+
+fn read_stream_id(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) & 0x7fff_ffff
+}
+
+fn write_stream_id(bytes: &mut [u8], stream_id: u32) {
+    bytes.copy_from_slice(&(stream_id & 0x7fff_ffff).to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(ty: u8, stream_id: u32, payload: &[u8]) -> Vec<u8> {
+        let mut frame = (payload.len() as u32).to_be_bytes()[1..].to_vec();
+        frame.extend_from_slice(&[ty, 0]);
+        frame.extend_from_slice(&stream_id.to_be_bytes());
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn goaway(last_stream_id: u32) -> Vec<u8> {
+        let mut payload = last_stream_id.to_be_bytes().to_vec();
+        payload.extend_from_slice(&[0; 4]);
+        frame(GOAWAY, 0, &payload)
+    }
+
+    /// Feeds `input` to a rewriter one byte at a time.
+    fn rewrite(rewriter: &mut Rewriter, input: &[u8]) -> Vec<u8> {
+        let mut out = BytesMut::new();
+        for byte in input.chunks(1) {
+            rewriter.process(byte, &mut out);
+        }
+        out.to_vec()
+    }
+
+    #[test]
+    fn server_sends_upgrade_response_after_settings() {
+        let settings = frame(0x4, 0, &[0, 3, 0, 0, 0, 100]);
+        let ping = frame(0x6, 0, &[0; 8]);
+
+        let mut rewriter = Rewriter::new(Direction::ServerWrite, 0);
+        let out = rewrite(&mut rewriter, &[settings.clone(), ping.clone()].concat());
+        assert_eq!(out, [settings, UPGRADE_RESPONSE.to_vec(), ping].concat());
+        assert!(rewriter.is_done());
+    }
+
+    #[test]
+    fn client_moves_streams_up() {
+        let preface = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n".to_vec();
+        let settings = frame(0x4, 0, &[]);
+
+        let mut rewriter = Rewriter::new(Direction::ClientWrite, PREFACE_LEN);
+        let out = rewrite(
+            &mut rewriter,
+            &[
+                preface.clone(),
+                settings.clone(),
+                frame(0x1, 1, b"headers"),
+                frame(0x0, 3, b""),
+                goaway(2),
+            ]
+            .concat(),
+        );
+        assert_eq!(
+            out,
+            [
+                preface,
+                settings,
+                frame(0x1, 3, b"headers"),
+                frame(0x0, 5, b""),
+                goaway(2),
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn client_drops_stream_one_and_moves_streams_down() {
+        let settings = frame(0x4, 0, &[0, 3, 0, 0, 0, 100]);
+
+        let mut rewriter = Rewriter::new(Direction::ClientRead, 0);
+        let out = rewrite(
+            &mut rewriter,
+            &[
+                settings.clone(),
+                UPGRADE_RESPONSE.to_vec(),
+                frame(0x0, 1, b"dropped"),
+                frame(0x1, 3, b"headers"),
+                frame(0x3, 5, &[0; 4]),
+                goaway(5),
+                goaway(1),
+            ]
+            .concat(),
+        );
+        assert_eq!(
+            out,
+            [
+                settings,
+                frame(0x1, 1, b"headers"),
+                frame(0x3, 3, &[0; 4]),
+                goaway(3),
+                goaway(0),
+            ]
+            .concat()
+        );
+    }
+
+    #[tokio::test]
+    async fn upgrade_handshake() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        let settings = frame(0x4, 0, &[]);
+
+        let server = tokio::spawn({
+            let settings = settings.clone();
+            async move {
+                let mut request = vec![0; 1024];
+                let n = server.read(&mut request).await.unwrap();
+                request.truncate(n);
+
+                let mut response = b"HTTP/1.1 101 Switching Protocols\r\n\
+                    Connection: Upgrade\r\n\
+                    Upgrade: h2c\r\n\r\n"
+                    .to_vec();
+                response.extend_from_slice(&settings);
+                response.extend_from_slice(&UPGRADE_RESPONSE);
+                server.write_all(&response).await.unwrap();
+                String::from_utf8(request).unwrap()
+            }
+        });
+
+        let mut io = upgrade(client, "example.com:50051").await.unwrap();
+        let mut read = vec![0; settings.len()];
+        io.read_exact(&mut read).await.unwrap();
+        assert_eq!(read, settings);
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("OPTIONS * HTTP/1.1\r\nHost: example.com:50051\r\n"));
+        assert!(request.contains("\r\nUpgrade: h2c\r\n"));
+    }
+
+    #[tokio::test]
+    async fn upgrade_refused() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        tokio::spawn(async move {
+            let mut request = vec![0; 1024];
+            let _ = server.read(&mut request).await.unwrap();
+            server
+                .write_all(b"HTTP/1.1 400 Bad Request\r\ncontent-length: 0\r\n\r\n")
+                .await
+                .unwrap();
+        });
+
+        let err = upgrade(client, "example.com").await.err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "server did not accept the h2c upgrade: HTTP/1.1 400 Bad Request"
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use super::super::BoxFuture;
+use super::ResponseBody;
+use crate::Status;
+use http::{HeaderMap, Request, Response};
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+/// Fails the responses whose headers are larger than the max header list size of an endpoint,
+/// with a `RESOURCE_EXHAUSTED` status.
+///
+/// `hyper`'s client does not advertise the limit to servers, so it is checked once the
+/// headers are received. Trailers over the limit are replaced by the same status in the
+/// [`ResponseBody`].
+#[derive(Debug)]
+pub(crate) struct MaxHeaderListSize<T> {
+    inner: T,
+    max: u32,
+}
+
+impl<T> MaxHeaderListSize<T> {
+    pub(crate) fn new(inner: T, max: u32) -> Self {
+        Self { inner, max }
+    }
+}
+
+impl<T, ReqBody> Service<Request<ReqBody>> for MaxHeaderListSize<T>
+where
+    T: Service<Request<ReqBody>, Response = Response<ResponseBody>>,
+    T::Error: Into<crate::Error>,
+    T::Future: Send + 'static,
+{
+    type Response = T::Response;
+    type Error = crate::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let max = self.max;
+        let fut = self.inner.call(req);
+
+        Box::pin(async move {
+            let mut response = fut.await.map_err(Into::into)?;
+            check(response.headers(), max)?;
+            response.body_mut().set_max_header_list_size(max);
+            Ok(response)
+        })
+    }
+}
+
+/// Returns a `RESOURCE_EXHAUSTED` status if `headers` are larger than `max`.
+///
+/// Their size is the one HTTP/2 limits: the length of every name and value, plus 32 bytes
+/// for each of them.
+pub(crate) fn check(headers: &HeaderMap, max: u32) -> Result<(), Status> {
+    let size = headers
+        .iter()
+        .map(|(name, value)| name.as_str().len() + value.len() + 32)
+        .sum::<usize>();
+
+    if size > max as usize {
+        return Err(Status::resource_exhausted(format!(
+            "response metadata of {} bytes is larger than the limit of {} bytes",
+            size, max
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    #[test]
+    fn counts_names_values_and_overhead() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-key", HeaderValue::from_static("value"));
+
+        assert!(check(&headers, 42).is_ok());
+        let status = check(&headers, 41).unwrap_err();
+        assert_eq!(status.code(), crate::Code::ResourceExhausted);
+    }
+}
@@ -0,0 +1,278 @@
+use super::super::BoxFuture;
+use super::connection::{Request, Response};
+use crate::codec::{IdentityCodec, Streaming};
+use crate::{Code, Status};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http::header::{CONTENT_TYPE, TE};
+use http::HeaderValue;
+use http_body::Body;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::Sleep;
+use tokio_stream::Stream;
+use tower_service::Service;
+
+const WATCH: &str = "/grpc.health.v1.Health/Watch";
+
+/// The `SERVING` value of `grpc.health.v1.HealthCheckResponse.ServingStatus`.
+const SERVING: u64 = 1;
+
+/// How long to wait before watching the health of an endpoint again after the watch failed.
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Makes an endpoint ready only while its server reports that a service is serving.
+///
+/// Once the connection is up a `grpc.health.v1.Health/Watch` call is made on it, and until
+/// its first response arrives the endpoint is pending like a connection that is still
+/// connecting. After that `poll_ready` fails while the service is not `SERVING`, which makes
+/// a balancer skip the endpoint. A failed watch is made again after a backoff, and servers
+/// that do not implement the health service are assumed to be healthy.
+pub(crate) struct HealthCheck<S> {
+    inner: S,
+    service: String,
+    state: State,
+}
+
+enum State {
+    Idle,
+    Calling(BoxFuture<Response, crate::Error>),
+    /// Watching the health of the service, which is not known until the first response.
+    Watching(Streaming<Bytes>, Option<bool>),
+    Failed(Pin<Box<Sleep>>, Status),
+    Unimplemented,
+}
+
+impl<S> HealthCheck<S> {
+    pub(crate) fn new(inner: S, service: String) -> Self {
+        Self {
+            inner,
+            service,
+            state: State::Idle,
+        }
+    }
+
+    fn request(&self) -> Request {
+        let mut message = BytesMut::new();
+        if !self.service.is_empty() {
+            // The only field of `HealthCheckRequest` is the service name, with tag 1.
+            message.put_u8(0x0a);
+            put_varint(&mut message, self.service.len() as u64);
+            message.put_slice(self.service.as_bytes());
+        }
+
+        let mut frame = BytesMut::with_capacity(5 + message.len());
+        frame.put_u8(0);
+        frame.put_u32(message.len() as u32);
+        frame.put(message);
+
+        let body = http_body::Full::new(frame.freeze())
+            .map_err(|err| match err {})
+            .boxed_unsync();
+        let mut request = http::Request::post(WATCH)
+            .body(body)
+            .expect("health check request is valid");
+        let headers = request.headers_mut();
+        headers.insert(TE, HeaderValue::from_static("trailers"));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+
+        request
+    }
+
+    fn failed(&self, status: Status) -> State {
+        if status.code() == Code::Unimplemented {
+            tracing::error!(
+                "health_check; the server does not implement grpc.health.v1.Health, \
+                 assuming it is healthy"
+            );
+            return State::Unimplemented;
+        }
+
+        tracing::debug!(
+            "health_check; watching {:?} failed: {}",
+            self.service,
+            status
+        );
+        State::Failed(Box::pin(tokio::time::sleep(RETRY_BACKOFF)), status)
+    }
+
+    /// Watches the responses of a watch, unless it failed right away.
+    fn watch(&self, response: Response) -> State {
+        if let Some(status) = Status::from_header_map(response.headers()) {
+            if status.code() != Code::Ok {
+                return self.failed(status);
+            }
+        }
+
+        let status_code = response.status();
+        let stream = Streaming::new_response(
+            IdentityCodec::default(),
+            response.into_body(),
+            status_code,
+            None,
+            None,
+        );
+        State::Watching(stream, None)
+    }
+}
+
+impl<S> Service<Request> for HealthCheck<S>
+where
+    S: Service<Request, Response = Response>,
+    S::Error: Into<crate::Error>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = crate::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.inner.poll_ready(cx) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(error)) => {
+                // The connection is lost, and with it the watch.
+                self.state = State::Idle;
+                return Poll::Ready(Err(error.into()));
+            }
+            Poll::Pending => return Poll::Pending,
+        }
+
+        loop {
+            let state = match &mut self.state {
+                State::Idle => {
+                    let call = self.inner.call(self.request());
+                    self.state =
+                        State::Calling(Box::pin(async move { call.await.map_err(Into::into) }));
+                    // The call used up the readiness of the connection.
+                    return self.poll_ready(cx);
+                }
+                State::Calling(call) => match Pin::new(call).poll(cx) {
+                    Poll::Ready(Ok(response)) => self.watch(response),
+                    Poll::Ready(Err(error)) => self.failed(Status::from_error(error)),
+                    Poll::Pending => return Poll::Pending,
+                },
+                State::Watching(stream, serving) => match Pin::new(stream).poll_next(cx) {
+                    Poll::Ready(Some(Ok(message))) => {
+                        *serving = Some(serving_status(message) == Some(SERVING));
+                        continue;
+                    }
+                    Poll::Ready(Some(Err(status))) => self.failed(status),
+                    Poll::Ready(None) => self.failed(Status::unavailable("health watch ended")),
+                    Poll::Pending => {
+                        return match serving {
+                            Some(true) => Poll::Ready(Ok(())),
+                            Some(false) => Poll::Ready(Err(Status::unavailable(format!(
+                                "the endpoint is not serving {:?}",
+                                self.service
+                            ))
+                            .into())),
+                            None => Poll::Pending,
+                        };
+                    }
+                },
+                State::Failed(delay, status) => match delay.as_mut().poll(cx) {
+                    Poll::Ready(()) => State::Idle,
+                    Poll::Pending => return Poll::Ready(Err(status.clone().into())),
+                },
+                State::Unimplemented => return Poll::Ready(Ok(())),
+            };
+
+            self.state = state;
+        }
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let fut = self.inner.call(request);
+        Box::pin(async move { fut.await.map_err(Into::into) })
+    }
+}
+
+/// Returns the `status` field, with tag 1, of an encoded `HealthCheckResponse`.
+fn serving_status(mut message: Bytes) -> Option<u64> {
+    let mut status = None;
+
+    while message.has_remaining() {
+        let key = varint(&mut message)?;
+        match key & 0x7 {
+            0 => {
+                let value = varint(&mut message)?;
+                if key >> 3 == 1 {
+                    status = Some(value);
+                }
+            }
+            1 => skip(&mut message, 8)?,
+            2 => {
+                let len = varint(&mut message)?;
+                skip(&mut message, usize::try_from(len).ok()?)?;
+            }
+            5 => skip(&mut message, 4)?,
+            _ => return None,
+        }
+    }
+
+    // A missing status is the default, `UNKNOWN`.
+    Some(status.unwrap_or(0))
+}
+
+fn varint(buf: &mut Bytes) -> Option<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        if !buf.has_remaining() {
+            return None;
+        }
+        let byte = buf.get_u8();
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn put_varint(buf: &mut BytesMut, mut value: u64) {
+    while value >= 0x80 {
+        buf.put_u8(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.put_u8(value as u8);
+}
+
+fn skip(buf: &mut Bytes, len: usize) -> Option<()> {
+    if buf.remaining() < len {
+        return None;
+    }
+    buf.advance(len);
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_serving_statuses() {
+        assert_eq!(
+            serving_status(Bytes::from_static(&[0x08, 0x01])),
+            Some(SERVING)
+        );
+        assert_eq!(serving_status(Bytes::from_static(&[0x08, 0x02])), Some(2));
+        assert_eq!(serving_status(Bytes::new()), Some(0));
+        // Unknown fields are skipped.
+        assert_eq!(
+            serving_status(Bytes::from_static(&[0x12, 0x02, 0xff, 0xff, 0x08, 0x01])),
+            Some(SERVING)
+        );
+        assert_eq!(serving_status(Bytes::from_static(&[0x08])), None);
+    }
+
+    #[test]
+    fn encodes_watch_requests() {
+        let check = HealthCheck::new((), "foo.Foo".into());
+        let request = check.request();
+        assert_eq!(request.uri().path(), WATCH);
+        assert_eq!(request.headers()[CONTENT_TYPE], "application/grpc");
+    }
+}
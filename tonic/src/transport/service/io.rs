@@ -1,11 +1,12 @@
+#[cfg(feature = "transport")]
 use crate::transport::server::Connected;
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+use crate::transport::server::TlsStream;
 use hyper::client::connect::{Connected as HyperConnected, Connection};
 use std::io;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
-#[cfg(feature = "tls")]
-use tokio_rustls::server::TlsStream;
 
 pub(in crate::transport) trait Io:
     AsyncRead + AsyncWrite + Send + 'static
@@ -28,6 +29,7 @@ impl Connection for BoxedIo {
     }
 }
 
+#[cfg(feature = "transport")]
 impl Connected for BoxedIo {
     type ConnectInfo = NoneConnectInfo;
 
@@ -36,6 +38,7 @@ impl Connected for BoxedIo {
     }
 }
 
+#[cfg(feature = "transport")]
 #[derive(Copy, Clone)]
 pub(crate) struct NoneConnectInfo;
 
@@ -67,32 +70,38 @@ impl AsyncWrite for BoxedIo {
     }
 }
 
+#[cfg(feature = "transport")]
 pub(crate) enum ServerIo<IO> {
     Io(IO),
-    #[cfg(feature = "tls")]
+    #[cfg(any(feature = "tls", feature = "tls-native"))]
     TlsIo(Box<TlsStream<IO>>),
 }
 
+#[cfg(feature = "transport")]
 use tower::util::Either;
 
-#[cfg(feature = "tls")]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 type ServerIoConnectInfo<IO> =
     Either<<IO as Connected>::ConnectInfo, <TlsStream<IO> as Connected>::ConnectInfo>;
 
-#[cfg(not(feature = "tls"))]
+#[cfg(all(
+    feature = "transport",
+    not(any(feature = "tls", feature = "tls-native"))
+))]
 type ServerIoConnectInfo<IO> = Either<<IO as Connected>::ConnectInfo, ()>;
 
+#[cfg(feature = "transport")]
 impl<IO> ServerIo<IO> {
     pub(in crate::transport) fn new_io(io: IO) -> Self {
         Self::Io(io)
     }
 
-    #[cfg(feature = "tls")]
+    #[cfg(any(feature = "tls", feature = "tls-native"))]
     pub(in crate::transport) fn new_tls_io(io: TlsStream<IO>) -> Self {
         Self::TlsIo(Box::new(io))
     }
 
-    #[cfg(feature = "tls")]
+    #[cfg(any(feature = "tls", feature = "tls-native"))]
     pub(in crate::transport) fn connect_info(&self) -> ServerIoConnectInfo<IO>
     where
         IO: Connected,
@@ -104,7 +113,7 @@ impl<IO> ServerIo<IO> {
         }
     }
 
-    #[cfg(not(feature = "tls"))]
+    #[cfg(not(any(feature = "tls", feature = "tls-native")))]
     pub(in crate::transport) fn connect_info(&self) -> ServerIoConnectInfo<IO>
     where
         IO: Connected,
@@ -115,6 +124,7 @@ impl<IO> ServerIo<IO> {
     }
 }
 
+#[cfg(feature = "transport")]
 impl<IO> AsyncRead for ServerIo<IO>
 where
     IO: AsyncWrite + AsyncRead + Unpin,
@@ -126,12 +136,13 @@ where
     ) -> Poll<io::Result<()>> {
         match &mut *self {
             Self::Io(io) => Pin::new(io).poll_read(cx, buf),
-            #[cfg(feature = "tls")]
+            #[cfg(any(feature = "tls", feature = "tls-native"))]
             Self::TlsIo(io) => Pin::new(io).poll_read(cx, buf),
         }
     }
 }
 
+#[cfg(feature = "transport")]
 impl<IO> AsyncWrite for ServerIo<IO>
 where
     IO: AsyncWrite + AsyncRead + Unpin,
@@ -143,7 +154,7 @@ where
     ) -> Poll<io::Result<usize>> {
         match &mut *self {
             Self::Io(io) => Pin::new(io).poll_write(cx, buf),
-            #[cfg(feature = "tls")]
+            #[cfg(any(feature = "tls", feature = "tls-native"))]
             Self::TlsIo(io) => Pin::new(io).poll_write(cx, buf),
         }
     }
@@ -151,7 +162,7 @@ where
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
         match &mut *self {
             Self::Io(io) => Pin::new(io).poll_flush(cx),
-            #[cfg(feature = "tls")]
+            #[cfg(any(feature = "tls", feature = "tls-native"))]
             Self::TlsIo(io) => Pin::new(io).poll_flush(cx),
         }
     }
@@ -159,7 +170,7 @@ where
     fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
         match &mut *self {
             Self::Io(io) => Pin::new(io).poll_shutdown(cx),
-            #[cfg(feature = "tls")]
+            #[cfg(any(feature = "tls", feature = "tls-native"))]
             Self::TlsIo(io) => Pin::new(io).poll_shutdown(cx),
         }
     }
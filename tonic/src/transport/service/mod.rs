@@ -1,25 +1,57 @@
 mod add_origin;
+pub(crate) mod backoff;
+mod body;
 mod connection;
+mod connectivity;
 mod connector;
 mod discover;
 pub(crate) mod executor;
 pub(crate) mod grpc_timeout;
+pub(crate) mod h2c;
+mod header_list_size;
+mod health;
 mod io;
+mod pick_first;
+mod pool;
+mod proxy;
 mod reconnect;
+mod resolve;
+mod round_robin;
+#[cfg(feature = "transport")]
 mod router;
 #[cfg(feature = "tls")]
 mod tls;
+#[cfg(all(feature = "tls-native", not(feature = "tls")))]
+mod tls_native;
+#[cfg(unix)]
+mod unix;
 mod user_agent;
 
 pub(crate) use self::add_origin::AddOrigin;
+pub(crate) use self::backoff::Backoff;
+pub use self::body::ResponseBody;
 pub(crate) use self::connection::Connection;
+pub(crate) use self::connectivity::{Connectivity, Reporter};
 pub(crate) use self::connector::connector;
 pub(crate) use self::discover::DynamicServiceStream;
 pub(crate) use self::executor::SharedExec;
+#[cfg(feature = "transport")]
 pub(crate) use self::grpc_timeout::GrpcTimeout;
+#[cfg(feature = "transport")]
 pub(crate) use self::io::ServerIo;
+pub(crate) use self::pick_first::PickFirst;
+pub(crate) use self::pool::{Pool, SharedConnector};
+pub(crate) use self::proxy::{proxy_from_env, ProxyConnector};
+pub(crate) use self::reconnect::ConnectError;
+pub(crate) use self::resolve::{dns, resolve};
+pub(crate) use self::round_robin::RoundRobin;
 #[cfg(feature = "tls")]
 pub(crate) use self::tls::{TlsAcceptor, TlsConnector};
+#[cfg(all(feature = "tls-native", not(feature = "tls")))]
+pub(crate) use self::tls_native::{TlsAcceptor, TlsConnector, TlsStream};
+#[cfg(unix)]
+pub(crate) use self::unix::{parse_target as parse_uds_target, UdsConnector};
 pub(crate) use self::user_agent::UserAgent;
 
+#[cfg(feature = "transport")]
 pub use self::router::Routes;
@@ -0,0 +1,106 @@
+use super::super::BoxFuture;
+use super::connection::{Connection, Request, Response};
+use std::{
+    fmt,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// Sends every request to the first endpoint of a list that is ready.
+///
+/// The endpoints are tried in order, and requests stick to the one that connected for as long
+/// as it stays ready. Once it can no longer reconnect the endpoints are tried again from the
+/// start, so the first endpoint is preferred again. When every endpoint is failing the next
+/// request fails with the last connection error instead of waiting.
+///
+/// The connections are expected to be `Connection::fail_fast`, so that a failing endpoint can
+/// be told apart from one that is still connecting.
+pub(crate) struct PickFirst {
+    connections: Vec<Connection>,
+    current: Option<usize>,
+    ready: Ready,
+}
+
+enum Ready {
+    None,
+    Index(usize),
+    Failed(crate::Error),
+}
+
+impl PickFirst {
+    pub(crate) fn new(connections: Vec<Connection>) -> Self {
+        assert!(
+            !connections.is_empty(),
+            "pick first requires at least one endpoint"
+        );
+
+        Self {
+            connections,
+            current: None,
+            ready: Ready::None,
+        }
+    }
+}
+
+impl Service<Request> for PickFirst {
+    type Response = Response;
+    type Error = crate::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.ready = Ready::None;
+
+        if let Some(index) = self.current {
+            match self.connections[index].poll_ready(cx) {
+                Poll::Ready(Ok(())) => {
+                    self.ready = Ready::Index(index);
+                    return Poll::Ready(Ok(()));
+                }
+                Poll::Ready(Err(error)) => {
+                    tracing::debug!("pick_first; lost endpoint {}: {}", index, error);
+                    self.current = None;
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let mut last_error = None;
+        for (index, connection) in self.connections.iter_mut().enumerate() {
+            match connection.poll_ready(cx) {
+                Poll::Ready(Ok(())) => {
+                    tracing::trace!("pick_first; connected to endpoint {}", index);
+                    self.current = Some(index);
+                    self.ready = Ready::Index(index);
+                    return Poll::Ready(Ok(()));
+                }
+                Poll::Ready(Err(error)) => {
+                    tracing::debug!("pick_first; endpoint {} is failing: {}", index, error);
+                    last_error = Some(error);
+                }
+                // The later endpoints are only tried once this one fails.
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let error = last_error.expect("pick first has at least one endpoint");
+        self.ready = Ready::Failed(error);
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        match std::mem::replace(&mut self.ready, Ready::None) {
+            Ready::Index(index) => self.connections[index].call(request),
+            Ready::Failed(error) => Box::pin(async move { Err(error) }),
+            Ready::None => panic!("service not ready; poll_ready must be called first"),
+        }
+    }
+}
+
+impl fmt::Debug for PickFirst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PickFirst")
+            .field("endpoints", &self.connections.len())
+            .field("current", &self.current)
+            .finish()
+    }
+}
@@ -0,0 +1,181 @@
+use super::super::BoxFuture;
+use super::{
+    connection::{Request, Response},
+    Connection,
+};
+use http::Uri;
+use std::{
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+use tower_service::Service;
+
+/// Spreads requests over as many connections as needed to keep at most `max_streams`
+/// streams in flight on each of them.
+///
+/// A stream is counted from the call until its response body has been read to the end or
+/// dropped. Each request goes to the ready connection with the fewest streams in flight, and
+/// when every connection is at its cap a new one is opened, up to `max_connections`.
+/// Connections are kept once opened, so they are reused the next time the load goes up.
+pub(crate) struct Pool {
+    connect: Box<dyn FnMut() -> Connection + Send>,
+    // The streams in flight on a connection are the clones of its `Arc`.
+    connections: Vec<(Connection, Arc<()>)>,
+    max_streams: usize,
+    max_connections: usize,
+    /// Woken when a stream ends, while requests wait for one.
+    waker: Arc<Mutex<Option<Waker>>>,
+    ready: Option<usize>,
+}
+
+impl Pool {
+    pub(crate) fn new<F>(
+        connections: Vec<Connection>,
+        max_streams: Option<u32>,
+        max_connections: Option<usize>,
+        connect: F,
+    ) -> Self
+    where
+        F: FnMut() -> Connection + Send + 'static,
+    {
+        Self {
+            connect: Box::new(connect),
+            connections: connections
+                .into_iter()
+                .map(|connection| (connection, Arc::new(())))
+                .collect(),
+            max_streams: max_streams.map_or(usize::MAX, |max| (max as usize).max(1)),
+            max_connections: max_connections.unwrap_or(usize::MAX),
+            waker: Arc::default(),
+            ready: None,
+        }
+    }
+
+    fn has_capacity(&self, streams: &Arc<()>) -> bool {
+        Arc::strong_count(streams) <= self.max_streams
+    }
+}
+
+impl Service<Request> for Pool {
+    type Response = Response;
+    type Error = crate::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.ready.is_some() {
+            return Poll::Ready(Ok(()));
+        }
+
+        if self.connections.len() < self.max_connections {
+            if !self
+                .connections
+                .iter()
+                .any(|(_, streams)| self.has_capacity(streams))
+            {
+                self.connections.push(((self.connect)(), Arc::new(())));
+            }
+        } else {
+            // Once there are as many connections as allowed, requests wait for a stream to
+            // finish, and the waker is set before looking for one so that none is missed.
+            *self.waker.lock().unwrap() = Some(cx.waker().clone());
+        }
+
+        // Every connection is polled, which also drives the ones that are still connecting.
+        let mut ready = None;
+        let mut fewest = usize::MAX;
+        for index in 0..self.connections.len() {
+            let streams = Arc::strong_count(&self.connections[index].1);
+            if !self.has_capacity(&self.connections[index].1) {
+                continue;
+            }
+
+            if self.connections[index].0.poll_ready(cx)?.is_ready() && streams < fewest {
+                ready = Some(index);
+                fewest = streams;
+            }
+        }
+
+        self.ready = ready;
+        if ready.is_some() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let index = self
+            .ready
+            .take()
+            .expect("Pool::call invoked without ready connection");
+        let (connection, streams) = &mut self.connections[index];
+        let in_flight = InFlight {
+            streams: Some(streams.clone()),
+            waker: self.waker.clone(),
+        };
+        let fut = connection.call(req);
+
+        Box::pin(async move {
+            // The stream stays in flight until its response body is done or dropped.
+            let mut res = fut.await?;
+            res.body_mut().set_in_flight(in_flight);
+            Ok(res)
+        })
+    }
+}
+
+/// A stream in flight on a connection of a [`Pool`].
+pub(crate) struct InFlight {
+    streams: Option<Arc<()>>,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.streams = None;
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Shares one connector between the connections of a [`Pool`].
+///
+/// All connections of a pool are driven by the same task, so a connector is never used
+/// by two of them at once.
+pub(crate) struct SharedConnector<C> {
+    inner: Arc<Mutex<C>>,
+}
+
+impl<C> SharedConnector<C> {
+    pub(crate) fn new(connector: C) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(connector)),
+        }
+    }
+}
+
+impl<C> Clone for SharedConnector<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C> Service<Uri> for SharedConnector<C>
+where
+    C: Service<Uri>,
+{
+    type Response = C::Response;
+    type Error = C::Error;
+    type Future = C::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.lock().unwrap().poll_ready(cx)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        self.inner.lock().unwrap().call(uri)
+    }
+}
@@ -1,11 +1,15 @@
+use super::{Backoff, Reporter};
+use crate::transport::channel::ConnectivityState;
 use crate::Error;
 use pin_project::pin_project;
 use std::fmt;
 use std::{
     future::Future,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
+use tokio::time::{Instant, Sleep};
 use tower::make::MakeService;
 use tower_service::Service;
 use tracing::trace;
@@ -21,6 +25,12 @@ where
     error: Option<crate::Error>,
     has_been_connected: bool,
     is_lazy: bool,
+    fail_fast: bool,
+    backoff: Backoff,
+    failures: u32,
+    delay: Option<Pin<Box<Sleep>>>,
+    last_error: Option<Arc<Error>>,
+    reporter: Option<Reporter>,
 }
 
 #[derive(Debug)]
@@ -43,8 +53,40 @@ where
             error: None,
             has_been_connected: false,
             is_lazy,
+            fail_fast: false,
+            backoff: Backoff::default(),
+            failures: 0,
+            delay: None,
+            last_error: None,
+            reporter: None,
         }
     }
+
+    /// Wait according to `backoff` before reconnecting after a failed attempt.
+    pub(crate) fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Report the connectivity state of this connection to `reporter`.
+    pub(crate) fn report_to(mut self, reporter: Reporter) -> Self {
+        self.reporter = Some(reporter);
+        self
+    }
+
+    fn set_state(&self, state: ConnectivityState) {
+        if let Some(reporter) = &self.reporter {
+            reporter.set(state);
+        }
+    }
+
+    /// Return connection errors from `poll_ready` instead of failing the next call with them.
+    ///
+    /// This lets a balancer tell a failing endpoint apart from a ready one.
+    pub(crate) fn fail_fast(mut self) -> Self {
+        self.fail_fast = true;
+        self
+    }
 }
 
 impl<M, Target, S, Request> Service<Request> for Reconnect<M, Target>
@@ -71,6 +113,28 @@ where
             match self.state {
                 State::Idle => {
                     trace!("poll_ready; idle");
+
+                    if let Some(delay) = self.delay.as_mut() {
+                        if delay.as_mut().poll(cx).is_pending() {
+                            trace!("poll_ready; backing off");
+
+                            let error = ConnectError {
+                                error: self.last_error.clone().expect("backing off after an error"),
+                                reconnect_at: delay.deadline(),
+                            }
+                            .into();
+
+                            if self.fail_fast {
+                                return Poll::Ready(Err(error));
+                            } else {
+                                self.error = Some(error);
+                                return Poll::Ready(Ok(()));
+                            }
+                        }
+
+                        self.delay = None;
+                    }
+
                     match self.mk_service.poll_ready(cx) {
                         Poll::Ready(r) => r?,
                         Poll::Pending => {
@@ -81,12 +145,16 @@ where
 
                     let fut = self.mk_service.make_service(self.target.clone());
                     self.state = State::Connecting(fut);
+                    self.set_state(ConnectivityState::Connecting);
                     continue;
                 }
                 State::Connecting(ref mut f) => {
                     trace!("poll_ready; connecting");
                     match Pin::new(f).poll(cx) {
                         Poll::Ready(Ok(service)) => {
+                            self.failures = 0;
+                            self.last_error = None;
+                            self.set_state(ConnectivityState::Ready);
                             state = State::Connected(service);
                         }
                         Poll::Pending => {
@@ -97,11 +165,30 @@ where
                             trace!("poll_ready; error");
 
                             state = State::Idle;
+                            self.set_state(ConnectivityState::TransientFailure);
 
-                            if !(self.has_been_connected || self.is_lazy) {
+                            if !(self.fail_fast || self.has_been_connected || self.is_lazy) {
+                                self.state = state;
                                 return Poll::Ready(Err(e.into()));
+                            }
+
+                            self.failures = self.failures.saturating_add(1);
+                            let delay = self.backoff.delay(self.failures);
+                            tracing::debug!("reconnect::poll_ready: retrying in {:?}", delay);
+                            self.delay = Some(Box::pin(tokio::time::sleep(delay)));
+
+                            let error = Arc::new(e.into());
+                            self.last_error = Some(error.clone());
+                            let error = ConnectError {
+                                error,
+                                reconnect_at: Instant::now() + delay,
+                            }
+                            .into();
+
+                            if self.fail_fast {
+                                self.state = state;
+                                return Poll::Ready(Err(error));
                             } else {
-                                let error = e.into();
                                 tracing::debug!("reconnect::poll_ready: {:?}", error);
                                 self.error = Some(error);
                                 break;
@@ -125,6 +212,7 @@ where
                         }
                         Poll::Ready(Err(_)) => {
                             trace!("poll_ready; error");
+                            self.set_state(ConnectivityState::Idle);
                             state = State::Idle;
                         }
                     }
@@ -172,6 +260,32 @@ where
     }
 }
 
+/// The error of the last connection attempt, shared by the calls that fail until the next one.
+#[derive(Debug)]
+pub(crate) struct ConnectError {
+    error: Arc<Error>,
+    reconnect_at: Instant,
+}
+
+impl ConnectError {
+    /// Returns when the next connection attempt is made, for calls sent from then on.
+    pub(crate) fn reconnect_at(&self) -> Instant {
+        self.reconnect_at
+    }
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for ConnectError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&**self.error)
+    }
+}
+
 /// Future that resolves to the response or failure to connect.
 #[pin_project]
 #[derive(Debug)]
@@ -0,0 +1,152 @@
+use crate::transport::Endpoint;
+use http::Uri;
+use std::{collections::HashSet, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::sync::{mpsc::Sender, Notify};
+use tokio_stream::{Stream, StreamExt};
+use tower::discover::Change;
+
+/// Keeps a balanced channel in sync with the address lists reported by a resolver.
+///
+/// Runs until the resolver stream ends or the channel is dropped.
+pub(crate) async fn resolve<S, E>(
+    endpoint: Endpoint,
+    updates: S,
+    changes: Sender<Change<SocketAddr, Endpoint>>,
+) where
+    S: Stream<Item = Result<Vec<SocketAddr>, E>>,
+    E: Into<crate::Error>,
+{
+    let template = template(endpoint);
+    let mut current = HashSet::new();
+
+    tokio::pin!(updates);
+
+    loop {
+        let update = tokio::select! {
+            update = updates.next() => update.map(|update| update.map_err(Into::into)),
+            _ = changes.closed() => return,
+        };
+
+        let addrs = match update {
+            Some(Ok(addrs)) => addrs.into_iter().collect::<HashSet<_>>(),
+            Some(Err(error)) => {
+                tracing::debug!("resolve; failed to resolve: {}", error);
+                continue;
+            }
+            None => return,
+        };
+
+        if addrs.is_empty() {
+            tracing::debug!("resolve; resolved to no addresses");
+            continue;
+        }
+
+        for addr in current.difference(&addrs) {
+            if changes.send(Change::Remove(*addr)).await.is_err() {
+                return;
+            }
+        }
+
+        for addr in addrs.difference(&current) {
+            let endpoint = endpoint_for(&template, *addr);
+            if changes.send(Change::Insert(*addr, endpoint)).await.is_err() {
+                return;
+            }
+        }
+
+        current = addrs;
+    }
+}
+
+/// Resolves the host of an endpoint with the system resolver.
+///
+/// The host is resolved again every `interval`, or as soon as `resolve_now` is notified,
+/// which happens when a connection to one of the addresses fails.
+pub(crate) fn dns(
+    uri: &Uri,
+    interval: Duration,
+    resolve_now: Arc<Notify>,
+) -> impl Stream<Item = Result<Vec<SocketAddr>, std::io::Error>> + Send + 'static {
+    // `lookup_host` does not accept the brackets around ipv6 literals.
+    let host = uri
+        .host()
+        .unwrap_or_default()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+        Some("https") => 443,
+        _ => 80,
+    });
+
+    futures_util::stream::unfold(true, move |first| {
+        let host = host.clone();
+        let resolve_now = resolve_now.clone();
+
+        async move {
+            if !first {
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = resolve_now.notified() => {}
+                }
+            }
+
+            let addrs = tokio::net::lookup_host((host.as_str(), port))
+                .await
+                .map(Iterator::collect);
+
+            Some((addrs, false))
+        }
+    })
+}
+
+/// The endpoint every resolved address is connected with.
+fn template(mut endpoint: Endpoint) -> Endpoint {
+    // Requests still carry the host name rather than the address.
+    if endpoint.origin.is_none() {
+        endpoint.origin = Some(endpoint.uri.clone());
+    }
+
+    // The default TLS config verifies the server against the host of the uri, which is
+    // replaced by an address.
+    #[cfg(any(feature = "tls-roots-common", feature = "tls-native"))]
+    if endpoint.tls.is_none() && endpoint.uri.scheme_str() == Some("https") {
+        if let Some(host) = endpoint.uri.host() {
+            endpoint.tls = super::TlsConnector::new(None, None, host.to_string()).ok();
+        }
+    }
+
+    endpoint
+}
+
+fn endpoint_for(template: &Endpoint, addr: SocketAddr) -> Endpoint {
+    let mut parts = template.uri.clone().into_parts();
+    parts.authority = Some(
+        addr.to_string()
+            .parse()
+            .expect("socket address is a valid authority"),
+    );
+
+    let mut endpoint = template.clone();
+    endpoint.uri = Uri::from_parts(parts).expect("uri with a new authority is valid");
+    endpoint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints_keep_the_host_as_origin() {
+        let endpoint = Endpoint::from_static("http://example.com:50051/prefix");
+
+        let template = template(endpoint);
+        let endpoint = endpoint_for(&template, "[::1]:50051".parse().unwrap());
+
+        assert_eq!(endpoint.uri, "http://[::1]:50051/prefix");
+        assert_eq!(
+            endpoint.origin,
+            Some(Uri::from_static("http://example.com:50051/prefix"))
+        );
+    }
+}
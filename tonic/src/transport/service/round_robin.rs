@@ -0,0 +1,308 @@
+use super::super::BoxFuture;
+use crate::transport::channel::OutlierDetection;
+use crate::{Code, Status};
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::Instant;
+use tower::discover::{Change, Discover};
+use tower_service::Service;
+
+/// Sends each request to the next ready endpoint in turn.
+///
+/// Endpoints whose `poll_ready` fails are in transient failure and are skipped until they
+/// reconnect. They are kept and polled again on the next round, which makes them reconnect.
+/// When every endpoint is failing the next request fails with the last connection error
+/// instead of waiting, like a connection that is not `wait_for_ready` in other gRPC
+/// implementations.
+///
+/// Created with [`RoundRobin::pick_first`] it instead keeps sending requests to the same
+/// endpoint for as long as it is ready, like the `pick_first` policy.
+///
+/// With an [`OutlierDetection`] the outcome of every call is counted, and endpoints that fail
+/// too many calls are ejected: they are skipped like failing endpoints until their ejection
+/// time is over.
+pub(crate) struct RoundRobin<D>
+where
+    D: Discover,
+{
+    discover: D,
+    services: Vec<Entry<D::Key, D::Service>>,
+    next: usize,
+    ready: Ready,
+    sticky: bool,
+    outlier_detection: Option<OutlierDetection>,
+    /// When the failure percentages of the current interval are checked.
+    next_sweep: Instant,
+}
+
+struct Entry<K, S> {
+    key: K,
+    svc: S,
+    stats: Arc<Stats>,
+    ejected_until: Option<Instant>,
+    /// The number of times the endpoint was ejected, less the intervals it was not.
+    ejections: u32,
+}
+
+/// The outcomes of the calls of an endpoint, since the start of the current interval.
+#[derive(Default)]
+struct Stats {
+    successes: AtomicU64,
+    failures: AtomicU64,
+    consecutive_failures: AtomicU32,
+}
+
+enum Ready {
+    None,
+    Index(usize),
+    Failed(crate::Error),
+}
+
+impl<D> RoundRobin<D>
+where
+    D: Discover + Unpin,
+    D::Error: Into<crate::Error>,
+{
+    pub(crate) fn new(discover: D) -> Self {
+        Self {
+            discover,
+            services: Vec::new(),
+            next: 0,
+            ready: Ready::None,
+            sticky: false,
+            outlier_detection: None,
+            next_sweep: Instant::now(),
+        }
+    }
+
+    pub(crate) fn pick_first(discover: D) -> Self {
+        Self {
+            sticky: true,
+            ..Self::new(discover)
+        }
+    }
+
+    /// Ejects the endpoints that fail too many calls, as `outlier_detection` says.
+    pub(crate) fn outlier_detection(self, outlier_detection: Option<OutlierDetection>) -> Self {
+        let next_sweep = match &outlier_detection {
+            Some(outlier_detection) => after(Instant::now(), outlier_detection.interval),
+            None => self.next_sweep,
+        };
+
+        Self {
+            outlier_detection,
+            next_sweep,
+            ..self
+        }
+    }
+
+    fn update_from_discover(&mut self, cx: &mut Context<'_>) -> Result<(), crate::Error> {
+        loop {
+            match Pin::new(&mut self.discover).poll_discover(cx) {
+                Poll::Pending | Poll::Ready(None) => return Ok(()),
+                Poll::Ready(Some(change)) => match change.map_err(Into::into)? {
+                    Change::Insert(key, svc) => {
+                        self.remove(&key);
+                        self.services.push(Entry {
+                            key,
+                            svc,
+                            stats: Arc::default(),
+                            ejected_until: None,
+                            ejections: 0,
+                        });
+                    }
+                    Change::Remove(key) => self.remove(&key),
+                },
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &D::Key) {
+        if let Some(index) = self.services.iter().position(|entry| entry.key == *key) {
+            self.services.remove(index);
+        }
+    }
+
+    /// Puts back the endpoints whose ejection time is over, and ejects the outliers.
+    fn detect_outliers(&mut self) {
+        let config = match &self.outlier_detection {
+            Some(config) => config,
+            None => return,
+        };
+        let now = Instant::now();
+
+        for entry in &mut self.services {
+            if matches!(entry.ejected_until, Some(until) if until <= now) {
+                tracing::debug!("round_robin; putting back an ejected endpoint");
+                entry.ejected_until = None;
+            }
+        }
+
+        let sweep = now >= self.next_sweep;
+        if sweep {
+            self.next_sweep = after(now, config.interval);
+        }
+
+        let total = self.services.len() as u64;
+        let mut ejected = self
+            .services
+            .iter()
+            .filter(|entry| entry.ejected_until.is_some())
+            .count() as u64;
+
+        for entry in &mut self.services {
+            let stats = &entry.stats;
+            let consecutive_failures = stats.consecutive_failures.load(Ordering::Relaxed);
+            let mut outlier = config.consecutive_failures > 0
+                && consecutive_failures >= config.consecutive_failures;
+
+            if sweep {
+                let failures = stats.failures.swap(0, Ordering::Relaxed);
+                let calls = failures + stats.successes.swap(0, Ordering::Relaxed);
+                if let Some((threshold, request_volume)) = config.failure_percentage {
+                    outlier |= calls > 0
+                        && calls >= request_volume
+                        && failures * 100 >= u64::from(threshold) * calls;
+                }
+                if !outlier && entry.ejected_until.is_none() {
+                    entry.ejections = entry.ejections.saturating_sub(1);
+                }
+            }
+
+            let allowed = ejected * 100 < u64::from(config.max_ejection_percent) * total;
+            if outlier && entry.ejected_until.is_none() && allowed {
+                entry.ejections = entry.ejections.saturating_add(1);
+                let time = config.ejection_time(entry.ejections);
+                tracing::debug!("round_robin; ejecting an outlier for {:?}", time);
+
+                entry.ejected_until = Some(after(now, time));
+                stats.consecutive_failures.store(0, Ordering::Relaxed);
+                ejected += 1;
+            }
+        }
+    }
+}
+
+impl Stats {
+    fn record(&self, success: bool) {
+        if success {
+            self.successes.fetch_add(1, Ordering::Relaxed);
+            self.consecutive_failures.store(0, Ordering::Relaxed);
+        } else {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+            self.consecutive_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Returns `now` plus `duration`, or an instant thirty years away when that overflows, which
+/// is never reached.
+fn after(now: Instant, duration: Duration) -> Instant {
+    now.checked_add(duration)
+        .unwrap_or_else(|| now + Duration::from_secs(60 * 60 * 24 * 365 * 30))
+}
+
+impl<D, Req, B> Service<Req> for RoundRobin<D>
+where
+    D: Discover + Unpin,
+    D::Error: Into<crate::Error>,
+    D::Service: Service<Req, Response = http::Response<B>>,
+    <D::Service as Service<Req>>::Error: Into<crate::Error>,
+    <D::Service as Service<Req>>::Future: Send + 'static,
+{
+    type Response = http::Response<B>;
+    type Error = crate::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Endpoints may have been added or removed since the last call, so readiness is
+        // always checked again.
+        self.ready = Ready::None;
+        self.update_from_discover(cx)?;
+        self.detect_outliers();
+
+        let len = self.services.len();
+        let mut pending = false;
+        let mut last_error = None;
+
+        for offset in 0..len {
+            let index = (self.next + offset) % len;
+            let entry = &mut self.services[index];
+            if entry.ejected_until.is_some() {
+                if last_error.is_none() {
+                    last_error = Some(Status::unavailable("endpoint ejected as an outlier").into());
+                }
+                continue;
+            }
+
+            match entry.svc.poll_ready(cx) {
+                Poll::Ready(Ok(())) => {
+                    self.ready = Ready::Index(index);
+                    return Poll::Ready(Ok(()));
+                }
+                Poll::Ready(Err(error)) => {
+                    let error = error.into();
+                    tracing::debug!("round_robin; endpoint is failing: {}", error);
+                    last_error = Some(error);
+                }
+                Poll::Pending => pending = true,
+            }
+        }
+
+        match last_error {
+            Some(error) if !pending => {
+                self.ready = Ready::Failed(error);
+                Poll::Ready(Ok(()))
+            }
+            _ => Poll::Pending,
+        }
+    }
+
+    fn call(&mut self, request: Req) -> Self::Future {
+        match std::mem::replace(&mut self.ready, Ready::None) {
+            Ready::Index(index) => {
+                self.next = if self.sticky { index } else { index + 1 };
+                let entry = &mut self.services[index];
+                let stats = entry.stats.clone();
+                let fut = entry.svc.call(request);
+
+                Box::pin(async move {
+                    let result = fut.await.map_err(Into::into);
+                    let success = match &result {
+                        Ok(response) => !matches!(
+                            Status::from_header_map(response.headers()),
+                            Some(status) if status.code() != Code::Ok
+                        ),
+                        Err(_) => false,
+                    };
+                    stats.record(success);
+                    result
+                })
+            }
+            Ready::Failed(error) => Box::pin(async move { Err(error) }),
+            Ready::None => panic!("service not ready; poll_ready must be called first"),
+        }
+    }
+}
+
+impl<D> fmt::Debug for RoundRobin<D>
+where
+    D: Discover,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoundRobin")
+            .field("services", &self.services.len())
+            .field("next", &self.next)
+            .field("sticky", &self.sticky)
+            .field("outlier_detection", &self.outlier_detection)
+            .finish()
+    }
+}
@@ -16,7 +16,7 @@ use tower::ServiceExt;
 use tower_service::Service;
 
 /// A [`Service`] router.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct Routes {
     router: axum::Router,
 }
@@ -32,8 +32,7 @@ impl Routes {
         S::Future: Send + 'static,
         S::Error: Into<crate::Error> + Send,
     {
-        let router = axum::Router::new().fallback(unimplemented);
-        Self { router }.add_service(svc)
+        Self::default().add_service(svc)
     }
 
     pub(crate) fn add_service<S>(mut self, svc: S) -> Self
@@ -62,6 +61,14 @@ impl Routes {
     }
 }
 
+impl Default for Routes {
+    fn default() -> Self {
+        Self {
+            router: axum::Router::new().fallback(unimplemented),
+        }
+    }
+}
+
 async fn unimplemented() -> impl axum::response::IntoResponse {
     let status = http::StatusCode::OK;
     let headers = [("grpc-status", "12"), ("content-type", "application/grpc")];
@@ -80,6 +80,14 @@ impl TlsConnector {
         })
     }
 
+    /// Returns a connector with the same config that verifies the server against `domain`.
+    pub(crate) fn with_domain(&self, domain: String) -> Result<Self, crate::Error> {
+        Ok(Self {
+            config: self.config.clone(),
+            domain: Arc::new(domain.as_str().try_into()?),
+        })
+    }
+
     pub(crate) async fn connect<I>(&self, io: I) -> Result<BoxedIo, crate::Error>
     where
         I: AsyncRead + AsyncWrite + Send + Unpin + 'static,
@@ -0,0 +1,258 @@
+use super::io::BoxedIo;
+use crate::transport::{server::Connected, Certificate, Identity};
+use std::{
+    fmt,
+    io::{self, Cursor},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// h2 alpn in plain format for native-tls.
+const ALPN_H2: &str = "h2";
+
+#[derive(Debug)]
+enum TlsError {
+    H2NotNegotiated,
+    CertificateParseError,
+    ClientAuthNotSupported,
+}
+
+#[derive(Clone)]
+pub(crate) struct TlsConnector {
+    inner: tokio_native_tls::TlsConnector,
+    domain: Arc<String>,
+}
+
+impl TlsConnector {
+    pub(crate) fn new(
+        ca_cert: Option<Certificate>,
+        identity: Option<Identity>,
+        domain: String,
+    ) -> Result<Self, crate::Error> {
+        let mut builder = native_tls::TlsConnector::builder();
+
+        if let Some(cert) = ca_cert {
+            for cert in native_keys::load_certs(&cert)? {
+                builder.add_root_certificate(cert);
+            }
+        }
+
+        if let Some(identity) = identity {
+            builder.identity(native_keys::load_identity(identity)?);
+        }
+
+        builder.request_alpns(&[ALPN_H2]);
+        Ok(Self {
+            inner: builder.build()?.into(),
+            domain: Arc::new(domain),
+        })
+    }
+
+    /// Returns a connector with the same config that verifies the server against `domain`.
+    pub(crate) fn with_domain(&self, domain: String) -> Result<Self, crate::Error> {
+        Ok(Self {
+            inner: self.inner.clone(),
+            domain: Arc::new(domain),
+        })
+    }
+
+    pub(crate) async fn connect<I>(&self, io: I) -> Result<BoxedIo, crate::Error>
+    where
+        I: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        let io = self.inner.connect(&self.domain, io).await?;
+
+        match io.get_ref().negotiated_alpn()? {
+            Some(b) if b == ALPN_H2.as_bytes() => Ok(BoxedIo::new(io)),
+            _ => Err(TlsError::H2NotNegotiated.into()),
+        }
+    }
+}
+
+impl fmt::Debug for TlsConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConnector").finish()
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct TlsAcceptor {
+    inner: tokio_native_tls::TlsAcceptor,
+}
+
+impl TlsAcceptor {
+    pub(crate) fn new(
+        identity: Identity,
+        client_ca_root: Option<Certificate>,
+        _client_auth_optional: bool,
+    ) -> Result<Self, crate::Error> {
+        // native-tls has no way to verify client certificates on the server.
+        if client_ca_root.is_some() {
+            return Err(TlsError::ClientAuthNotSupported.into());
+        }
+
+        let mut builder = native_tls::TlsAcceptor::builder(native_keys::load_identity(identity)?);
+        builder.accept_alpn(&[ALPN_H2]);
+
+        Ok(Self {
+            inner: builder.build()?.into(),
+        })
+    }
+
+    pub(crate) async fn accept<IO>(&self, io: IO) -> Result<TlsStream<IO>, crate::Error>
+    where
+        IO: AsyncRead + AsyncWrite + Connected + Unpin + Send + 'static,
+    {
+        let inner = self.inner.accept(io).await?;
+
+        // Only the leaf certificate of the peer is available from native-tls.
+        let certs = match inner.get_ref().peer_certificate()? {
+            Some(cert) => Some(Arc::new(vec![Certificate::from_pem(cert.to_der()?)])),
+            None => None,
+        };
+
+        Ok(TlsStream { inner, certs })
+    }
+}
+
+/// A TLS stream accepted by the server, along with the certificate of the peer.
+pub(crate) struct TlsStream<IO> {
+    inner: tokio_native_tls::TlsStream<IO>,
+    certs: Option<Arc<Vec<Certificate>>>,
+}
+
+impl<IO> TlsStream<IO> {
+    pub(crate) fn get_ref(&self) -> &IO {
+        self.inner.get_ref().get_ref().get_ref()
+    }
+
+    pub(crate) fn peer_certs(&self) -> Option<Arc<Vec<Certificate>>> {
+        self.certs.clone()
+    }
+}
+
+impl<IO> AsyncRead for TlsStream<IO>
+where
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<IO> AsyncWrite for TlsStream<IO>
+where
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+impl fmt::Debug for TlsAcceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsAcceptor").finish()
+    }
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsError::H2NotNegotiated => write!(f, "HTTP/2 was not negotiated."),
+            TlsError::CertificateParseError => write!(f, "Error parsing TLS certificate."),
+            TlsError::ClientAuthNotSupported => write!(
+                f,
+                "Client certificate verification is not supported by the native-tls backend."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TlsError {}
+
+mod native_keys {
+    use super::{Cursor, TlsError};
+    use crate::transport::{Certificate, Identity};
+
+    pub(super) fn load_certs(
+        cert: &Certificate,
+    ) -> Result<Vec<native_tls::Certificate>, crate::Error> {
+        let certs = rustls_pemfile::certs(&mut Cursor::new(&cert.pem[..]))?;
+        if certs.is_empty() {
+            return Err(Box::new(TlsError::CertificateParseError));
+        }
+
+        certs
+            .iter()
+            .map(|der| native_tls::Certificate::from_der(der).map_err(Into::into))
+            .collect()
+    }
+
+    /// The key has to be PKCS#8 encoded.
+    pub(super) fn load_identity(identity: Identity) -> Result<native_tls::Identity, crate::Error> {
+        native_tls::Identity::from_pkcs8(&identity.cert.pem, &identity.key).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TlsAcceptor, TlsConnector};
+    use crate::transport::{server::Connected, Certificate, Identity};
+
+    const CA: &str = include_str!("../../../../examples/data/tls/ca.pem");
+    const SERVER_CERT: &str = include_str!("../../../../examples/data/tls/server.pem");
+    const SERVER_KEY: &str = include_str!("../../../../examples/data/tls/server.key");
+
+    #[tokio::test]
+    async fn negotiates_h2() {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+
+        let connector = TlsConnector::new(
+            Some(Certificate::from_pem(CA)),
+            None,
+            "localhost".to_string(),
+        )
+        .unwrap();
+        let acceptor =
+            TlsAcceptor::new(Identity::from_pem(SERVER_CERT, SERVER_KEY), None, false).unwrap();
+
+        let (client, server) = tokio::join!(connector.connect(client), acceptor.accept(server));
+        client.unwrap();
+
+        let info = server.unwrap().connect_info();
+        assert!(info.peer_certs().is_none());
+    }
+
+    #[test]
+    fn rejects_client_ca_root() {
+        let identity = Identity::from_pem(SERVER_CERT, SERVER_KEY);
+        let err = TlsAcceptor::new(identity, Some(Certificate::from_pem(CA)), false).unwrap_err();
+
+        assert!(err.to_string().contains("not supported"));
+    }
+
+    #[test]
+    fn rejects_invalid_ca_certificate() {
+        let ca = Certificate::from_pem("not a certificate");
+
+        TlsConnector::new(Some(ca), None, "localhost".to_string()).unwrap_err();
+    }
+}
@@ -0,0 +1,79 @@
+use super::super::BoxFuture;
+use http::Uri;
+use std::{
+    io,
+    path::Path,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::net::UnixStream;
+use tower_service::Service;
+
+/// Connects to a Unix domain socket, ignoring the uri it is called with.
+#[derive(Debug, Clone)]
+pub(crate) struct UdsConnector {
+    path: Arc<Path>,
+}
+
+impl UdsConnector {
+    pub(crate) fn new(path: Arc<Path>) -> Self {
+        Self { path }
+    }
+}
+
+impl Service<Uri> for UdsConnector {
+    type Response = UnixStream;
+    type Error = io::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: Uri) -> Self::Future {
+        let path = self.path.clone();
+        Box::pin(async move { UnixStream::connect(path).await })
+    }
+}
+
+/// Parses the path of a `unix:path` or `unix:///absolute/path` target.
+pub(crate) fn parse_target(target: &str) -> Option<&Path> {
+    let path = target.strip_prefix("unix:")?;
+    let path = match path.strip_prefix("//") {
+        Some(path) if path.starts_with('/') => path,
+        Some(_) => return None,
+        None => path,
+    };
+
+    if path.is_empty() {
+        None
+    } else {
+        Some(Path::new(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_target;
+    use std::path::Path;
+
+    #[test]
+    fn parses_targets() {
+        assert_eq!(
+            parse_target("unix:///tmp/tonic.sock"),
+            Some(Path::new("/tmp/tonic.sock"))
+        );
+        assert_eq!(
+            parse_target("unix:/tmp/tonic.sock"),
+            Some(Path::new("/tmp/tonic.sock"))
+        );
+        assert_eq!(
+            parse_target("unix:tonic.sock"),
+            Some(Path::new("tonic.sock"))
+        );
+
+        assert_eq!(parse_target("unix://localhost/tmp/tonic.sock"), None);
+        assert_eq!(parse_target("unix:"), None);
+        assert_eq!(parse_target("http://localhost"), None);
+    }
+}
@@ -5,8 +5,8 @@ pub struct Certificate {
 }
 
 /// Represents a private key and X509 certificate.
-#[cfg(feature = "tls")]
-#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "tls", feature = "tls-native"))))]
 #[derive(Debug, Clone)]
 pub struct Identity {
     pub(crate) cert: Certificate,
@@ -44,11 +44,12 @@ impl AsRef<[u8]> for Certificate {
     }
 }
 
-#[cfg(feature = "tls")]
+#[cfg(any(feature = "tls", feature = "tls-native"))]
 impl Identity {
     /// Parse a PEM encoded certificate and private key.
     ///
-    /// The provided cert must contain at least one PEM encoded certificate.
+    /// The provided cert must contain at least one PEM encoded certificate. The `tls-native`
+    /// backend only accepts PKCS#8 keys (`BEGIN PRIVATE KEY`).
     pub fn from_pem(cert: impl AsRef<[u8]>, key: impl AsRef<[u8]>) -> Self {
         let cert = Certificate::from_pem(cert);
         let key = key.as_ref().into();